@@ -0,0 +1,78 @@
+//! 帧差异内核基准测试（1080p）
+//!
+//! 运行: `cargo bench --bench frame_kernels`，逐像素 `get_pixel` 的参考实现用于对比加速比
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use image::{DynamicImage, GrayImage, Rgb, RgbImage};
+use video_parse::simd;
+use video_parse::{FrameFeatures, SceneDetector};
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+
+/// 带渐变和噪声的 1080p 合成帧
+fn frame(seed: u32) -> DynamicImage {
+    let mut state = seed;
+    DynamicImage::ImageRgb8(RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let noise = (state >> 27) as u8;
+        Rgb([(x / 8) as u8 ^ noise, (y / 5) as u8, ((x + y) / 12) as u8 | noise])
+    }))
+}
+
+/// 原来按像素索引计算梯度差异的实现
+fn gradient_difference_reference(img1: &GrayImage, img2: &GrayImage) -> u64 {
+    let mut sum = 0u64;
+    for y in 1..(img1.height() - 1) {
+        for x in 1..(img1.width() - 1) {
+            let gx1 = img1.get_pixel(x + 1, y)[0] as i32 - img1.get_pixel(x - 1, y)[0] as i32;
+            let gy1 = img1.get_pixel(x, y + 1)[0] as i32 - img1.get_pixel(x, y - 1)[0] as i32;
+            let gx2 = img2.get_pixel(x + 1, y)[0] as i32 - img2.get_pixel(x - 1, y)[0] as i32;
+            let gy2 = img2.get_pixel(x, y + 1)[0] as i32 - img2.get_pixel(x, y - 1)[0] as i32;
+            let grad1 = ((gx1 * gx1 + gy1 * gy1) as f64).sqrt() as u32;
+            let grad2 = ((gx2 * gx2 + gy2 * gy2) as f64).sqrt() as u32;
+            sum += grad1.abs_diff(grad2) as u64;
+        }
+    }
+    sum
+}
+
+fn kernels(c: &mut Criterion) {
+    let (a, b) = (frame(1), frame(2));
+    let (gray_a, gray_b) = (a.to_luma8(), b.to_luma8());
+    let (width, height) = (WIDTH as usize, HEIGHT as usize);
+
+    let mut group = c.benchmark_group("1080p");
+    group.sample_size(20);
+    group.bench_function("sum_abs_diff", |bench| {
+        bench.iter(|| simd::sum_abs_diff(black_box(gray_a.as_raw()), black_box(gray_b.as_raw())))
+    });
+    group.bench_function("sum_abs_diff_scalar", |bench| {
+        bench.iter(|| simd::sum_abs_diff_scalar(black_box(gray_a.as_raw()), black_box(gray_b.as_raw())))
+    });
+    group.bench_function("histogram", |bench| bench.iter(|| simd::histogram(black_box(gray_a.as_raw()))));
+    group.bench_function("sobel", |bench| {
+        bench.iter(|| simd::sobel_magnitudes(black_box(gray_a.as_raw()), width, height))
+    });
+    group.bench_function("gradient_difference", |bench| {
+        bench.iter(|| {
+            let g1 = simd::gradient_magnitudes(black_box(gray_a.as_raw()), width, height);
+            let g2 = simd::gradient_magnitudes(black_box(gray_b.as_raw()), width, height);
+            simd::sum_abs_diff_u16(&g1, &g2)
+        })
+    });
+    group.bench_function("gradient_difference_reference", |bench| {
+        bench.iter(|| gradient_difference_reference(black_box(&gray_a), black_box(&gray_b)))
+    });
+
+    let detector = SceneDetector::new(0.35, 0.8);
+    group.bench_function("features", |bench| bench.iter(|| FrameFeatures::from_image(black_box(&a))));
+    let (features_a, features_b) = (FrameFeatures::from_image(&a), FrameFeatures::from_image(&b));
+    group.bench_function("features_difference", |bench| {
+        bench.iter(|| detector.calculate_features_difference(black_box(&features_a), black_box(&features_b)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, kernels);
+criterion_main!(benches);
@@ -0,0 +1,201 @@
+//! 关键帧分类标签：逐个关键帧调用 [`KeyframeAnnotator`]，把返回的标签合并到场景的 `tags`
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::metadata::SceneMetadata;
+use crate::pipeline::KeyframeAnnotator;
+use crate::processor::run_blocking;
+
+/// 通过 HTTP 调用外部分类服务的标注器（示例实现）
+///
+/// 每个关键帧发送一个 JSON 请求 `{"timestamp": 1.5, "mime_type": "image/jpeg", "image": "<base64>"}`，
+/// 服务返回 `{"tags": ["outdoor", "beach"]}`
+#[derive(Debug, Clone)]
+pub struct HttpAnnotator {
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Serialize)]
+struct AnnotateRequest {
+    timestamp: f64,
+    mime_type: &'static str,
+    image: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AnnotateResponse {
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl HttpAnnotator {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            headers: Vec::new(),
+            timeout: Duration::from_secs(30),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// 请求附带的头（例如 Authorization）
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// 单个请求的超时时间，默认 30 秒
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl KeyframeAnnotator for HttpAnnotator {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn annotate(&self, image: &DynamicImage, timestamp: f64) -> Result<Vec<String>> {
+        let mut jpeg = Vec::new();
+        image.to_rgb8().write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .context("编码关键帧失败")?;
+        let request = AnnotateRequest {
+            timestamp,
+            mime_type: "image/jpeg",
+            image: base64::engine::general_purpose::STANDARD.encode(jpeg),
+        };
+        let mut builder = self.client.post(&self.endpoint).json(&request).timeout(self.timeout);
+        for (key, value) in &self.headers {
+            builder = builder.header(key, value);
+        }
+        let response = builder.send().await.context("分类服务请求失败")?;
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("分类服务返回错误状态: {} - {}", status, error_text);
+        }
+        let response: AnnotateResponse = response.json().await.context("解析分类服务响应失败")?;
+        Ok(response.tags)
+    }
+}
+
+/// 依次用每个标注器处理每个场景的关键帧，去重后写入 `SceneMetadata.tags`，返回得到标签的场景数
+///
+/// 时间戳使用场景开始时间。与其他关键帧的重复关键帧（`duplicate_of`）复用该关键帧的标签。
+/// 单个关键帧读取或标注失败只记录警告
+pub async fn annotate_scenes(
+    annotators: &[Arc<dyn KeyframeAnnotator>],
+    output_dir: &Path,
+    scenes: &mut [SceneMetadata],
+) -> Result<usize> {
+    if annotators.is_empty() {
+        return Ok(0);
+    }
+    for index in 0..scenes.len() {
+        if let Some(original) = scenes[index].duplicate_of {
+            if let Some(tags) = scenes.iter().find(|s| s.scene_id == original).map(|s| s.tags.clone()) {
+                scenes[index].tags = tags;
+                continue;
+            }
+        }
+        let keyframe_path = output_dir.join(&scenes[index].keyframe_file);
+        let image = match run_blocking("读取关键帧", move || {
+            image::open(&keyframe_path).with_context(|| format!("读取关键帧失败: {}", keyframe_path.display()))
+        }).await {
+            Ok(image) => image,
+            Err(e) => {
+                tracing::warn!(scene_id = scenes[index].scene_id, error = %e, "⚠️  [关键帧分类] 读取关键帧失败");
+                continue;
+            }
+        };
+        let timestamp = scenes[index].start_time;
+        for annotator in annotators {
+            match annotator.annotate(&image, timestamp).await {
+                Ok(tags) => merge_tags(&mut scenes[index].tags, tags),
+                Err(e) => {
+                    tracing::warn!(
+                        scene_id = scenes[index].scene_id,
+                        annotator = annotator.name(),
+                        error = %e,
+                        "⚠️  [关键帧分类] 标注失败"
+                    );
+                }
+            }
+        }
+    }
+    Ok(scenes.iter().filter(|s| !s.tags.is_empty()).count())
+}
+
+/// 追加新标签，忽略空白和已有的标签
+fn merge_tags(tags: &mut Vec<String>, new_tags: Vec<String>) {
+    for tag in new_tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::NoopAnnotator;
+
+    /// 按亮度给出标签，并记录收到的时间戳
+    struct BrightnessAnnotator;
+
+    #[async_trait]
+    impl KeyframeAnnotator for BrightnessAnnotator {
+        fn name(&self) -> &str {
+            "brightness"
+        }
+
+        async fn annotate(&self, image: &DynamicImage, timestamp: f64) -> Result<Vec<String>> {
+            let pixel = image.to_rgb8().get_pixel(0, 0).0;
+            let tag = if pixel[0] > 128 { "bright" } else { "dark" };
+            Ok(vec![tag.to_string(), format!("t={}", timestamp), " ".to_string()])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_annotate_scenes() {
+        let dir = std::env::temp_dir().join(format!("video-parse-annotator-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        image::RgbImage::from_pixel(8, 8, image::Rgb([240, 240, 240])).save(dir.join("a.png")).unwrap();
+        image::RgbImage::from_pixel(8, 8, image::Rgb([10, 10, 10])).save(dir.join("b.png")).unwrap();
+
+        let scene = |scene_id, start_time, keyframe_file: &str| SceneMetadata {
+            keyframe_file: keyframe_file.to_string(),
+            ..SceneMetadata::fixture(scene_id, start_time, start_time + 1.0)
+        };
+        let mut scenes = vec![
+            scene(0, 0.0, "a.png"),
+            scene(1, 2.5, "b.png"),
+            SceneMetadata { duplicate_of: Some(0), ..scene(2, 4.0, "a.png") },
+            scene(3, 6.0, "missing.png"),
+        ];
+        let annotators: Vec<Arc<dyn KeyframeAnnotator>> =
+            vec![Arc::new(BrightnessAnnotator), Arc::new(NoopAnnotator), Arc::new(BrightnessAnnotator)];
+        let tagged = annotate_scenes(&annotators, &dir, &mut scenes).await.unwrap();
+        assert_eq!(tagged, 3);
+        assert_eq!(scenes[0].tags, ["bright", "t=0"]);
+        assert_eq!(scenes[1].tags, ["dark", "t=2.5"]);
+        // 重复关键帧复用原关键帧的标签，读取失败的关键帧没有标签
+        assert_eq!(scenes[2].tags, scenes[0].tags);
+        assert!(scenes[3].tags.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
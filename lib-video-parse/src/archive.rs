@@ -0,0 +1,169 @@
+//! 把输出目录打包为单个 .zip 或 .tar.gz 文件，下游只需要下载一个对象
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use crate::error::{Result, VideoParseError};
+
+/// 打包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz", alias = "tgz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// 全部打包格式
+    pub const ALL: [ArchiveFormat; 2] = [ArchiveFormat::Zip, ArchiveFormat::TarGz];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+
+    /// 打包文件名：`output.zip`、`output.tar.gz`
+    pub fn file_name(&self) -> String {
+        format!("output.{}", self.as_str())
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "zip" => Ok(ArchiveFormat::Zip),
+            "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+            other => Err(format!("不支持的打包格式: {}（可选: zip, tar.gz）", other)),
+        }
+    }
+}
+
+/// 输出目录下需要打包的文件（相对路径，按路径排序）
+///
+/// 跳过以 `.` 开头的文件和目录（检查点等）、分片上传记录和顶层的打包文件（任意格式，换格式重新打包时不包含旧的打包文件）
+pub fn archive_entries(output_dir: &Path) -> Result<Vec<PathBuf>> {
+    let archive_names = ArchiveFormat::ALL.map(|format| format.file_name());
+    let mut entries = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        let dir = output_dir.join(&relative);
+        let read_dir = std::fs::read_dir(&dir)
+            .map_err(|e| VideoParseError::io(format!("读取输出目录失败: {}", dir.display()), e))?;
+        for entry in read_dir.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || name.ends_with(".upload.json") || (relative.as_os_str().is_empty() && archive_names.contains(&name)) {
+                continue;
+            }
+            let path = relative.join(&name);
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => dirs.push(path),
+                Ok(kind) if kind.is_file() => entries.push(path),
+                _ => {}
+            }
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// 把 `output_dir` 下的输出文件打包为 `output_dir/{format.file_name()}`，返回打包文件路径
+///
+/// 先写入临时文件，完成后改名，打包中途失败不会留下不完整的打包文件
+pub fn write_archive(output_dir: &Path, format: ArchiveFormat) -> Result<PathBuf> {
+    let entries = archive_entries(output_dir)?;
+    let archive_path = output_dir.join(format.file_name());
+    let temp_path = output_dir.join(format!(".{}.part", format.file_name()));
+    let file = File::create(&temp_path)
+        .map_err(|e| VideoParseError::io(format!("创建打包文件失败: {}", temp_path.display()), e))?;
+    let result = match format {
+        ArchiveFormat::Zip => write_zip(BufWriter::new(file), output_dir, &entries),
+        ArchiveFormat::TarGz => write_tar_gz(BufWriter::new(file), output_dir, &entries),
+    };
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(VideoParseError::io(format!("打包输出目录失败: {}", output_dir.display()), e));
+    }
+    std::fs::rename(&temp_path, &archive_path)
+        .map_err(|e| VideoParseError::io(format!("保存打包文件失败: {}", archive_path.display()), e))?;
+    tracing::info!(
+        path = %archive_path.display(),
+        files = entries.len(),
+        bytes = crate::resource::disk_usage(&archive_path),
+        "📦 [视频处理] 输出目录已打包"
+    );
+    Ok(archive_path)
+}
+
+/// 打包文件中的路径统一使用 `/` 分隔
+fn entry_name(relative: &Path) -> String {
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn write_zip(writer: impl Write + std::io::Seek, output_dir: &Path, entries: &[PathBuf]) -> std::io::Result<()> {
+    let mut zip = zip::ZipWriter::new(writer);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for relative in entries {
+        zip.start_file(entry_name(relative), options).map_err(std::io::Error::other)?;
+        std::io::copy(&mut File::open(output_dir.join(relative))?, &mut zip)?;
+    }
+    zip.finish().map_err(std::io::Error::other)?.flush()
+}
+
+fn write_tar_gz(writer: impl Write, output_dir: &Path, entries: &[PathBuf]) -> std::io::Result<()> {
+    let mut tar = tar::Builder::new(GzEncoder::new(writer, flate2::Compression::default()));
+    for relative in entries {
+        tar.append_path_with_name(output_dir.join(relative), entry_name(relative))?;
+    }
+    tar.into_inner()?.finish()?.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_archive() {
+        let dir = std::env::temp_dir().join(format!("video-parse-archive-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("clips")).unwrap();
+        std::fs::write(dir.join("metadata.json"), "{}").unwrap();
+        std::fs::write(dir.join("keyframe_0001.jpg"), [0xff, 0xd8, 0xff]).unwrap();
+        std::fs::write(dir.join("clips").join("scene_1.mp4"), "clip").unwrap();
+        std::fs::write(dir.join(".checkpoint.json"), "{}").unwrap();
+        std::fs::write(dir.join("audio.aac.upload.json"), "{}").unwrap();
+
+        let expected = vec!["clips/scene_1.mp4", "keyframe_0001.jpg", "metadata.json"];
+        let zip_path = write_archive(&dir, ArchiveFormat::Zip).unwrap();
+        assert_eq!(zip_path, dir.join("output.zip"));
+        let mut zip = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let mut names: Vec<String> = zip.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(names, expected);
+        let mut clip = String::new();
+        zip.by_name("clips/scene_1.mp4").unwrap().read_to_string(&mut clip).unwrap();
+        assert_eq!(clip, "clip");
+
+        // 换格式再次打包时不包含已有的 zip，也不留下临时文件
+        let tar_path = write_archive(&dir, ArchiveFormat::TarGz).unwrap();
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(File::open(&tar_path).unwrap()));
+        let names: Vec<String> = tar.entries().unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, expected);
+        assert!(!names.iter().any(|name| name == "output.zip"));
+        assert!(!dir.join(".output.tar.gz.part").exists());
+
+        assert_eq!("tgz".parse::<ArchiveFormat>(), Ok(ArchiveFormat::TarGz));
+        assert!("rar".parse::<ArchiveFormat>().is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
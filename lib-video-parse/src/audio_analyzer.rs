@@ -0,0 +1,427 @@
+use anyhow::{bail, Context, Result};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use serde::{Deserialize, Serialize};
+use crate::audio_extractor::ffmpeg_binary;
+
+/// 波形文件名
+pub const WAVEFORM_FILE: &str = "waveform.json";
+
+/// 分析时统一重采样到的采样率（K 加权滤波器系数按 48kHz 给出）
+const ANALYSIS_SAMPLE_RATE: u32 = 48_000;
+/// 静音的电平下限（dBFS），避免输出 -inf
+const SILENCE_DBFS: f64 = -120.0;
+/// EBU R128 绝对门限（LUFS）
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// EBU R128 相对门限（LU）
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// 音频响度统计，写入 `VideoMetadata.loudness`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessStats {
+    /// EBU R128 综合响度（LUFS，整段低于绝对门限时为 None）
+    pub integrated_lufs: Option<f64>,
+    /// 采样峰值（dBFS）
+    pub peak_dbfs: f64,
+    /// 整体 RMS 电平（dBFS）
+    pub rms_dbfs: f64,
+    /// 波形文件名
+    pub waveform: String,
+}
+
+/// 波形文件内容：每秒一个点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waveform {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// 每个点覆盖的秒数
+    pub interval: f64,
+    /// 每秒的 RMS 电平（线性幅度，0.0-1.0）
+    pub rms: Vec<f32>,
+    /// 每秒的采样峰值（线性幅度，0.0-1.0）
+    pub peak: Vec<f32>,
+}
+
+/// 包络分析的采样率（只需要能量，不需要完整频带）
+const ENVELOPE_SAMPLE_RATE: u32 = 16_000;
+
+/// 解码音频文件，计算每秒 RMS/峰值和 EBU R128 综合响度，写出 `waveform.json`
+///
+/// 音频由 ffmpeg 命令行解码为 48kHz 32 位浮点 WAV 并通过管道读取，不落盘
+pub fn analyze_audio(audio_path: &Path, output_dir: &Path) -> Result<LoudnessStats> {
+    let meter = decode_pcm(
+        audio_path,
+        ANALYSIS_SAMPLE_RATE,
+        false,
+        |channels| LevelMeter::new(ANALYSIS_SAMPLE_RATE, channels),
+        LevelMeter::push,
+    )?;
+
+    let (waveform, stats) = meter.finish();
+    let file = std::fs::File::create(output_dir.join(WAVEFORM_FILE)).context("创建波形文件失败")?;
+    serde_json::to_writer(std::io::BufWriter::new(file), &waveform).context("保存波形文件失败")?;
+    Ok(stats)
+}
+
+/// 计算音频的能量包络：每 `hop` 秒一个点，取值为该时间段的 RMS 电平（dBFS）
+///
+/// 输入可以是视频文件，只解码其中的音频流（混为单声道）
+pub fn energy_envelope(input: &Path, hop: f64) -> Result<Vec<f64>> {
+    let hop_frames = ((hop * ENVELOPE_SAMPLE_RATE as f64).round() as usize).max(1);
+    let (mut envelope, sum, frames) = decode_pcm(
+        input,
+        ENVELOPE_SAMPLE_RATE,
+        true,
+        |_| (Vec::new(), 0.0f64, 0usize),
+        |(envelope, sum, frames), frame| {
+            *sum += (frame[0] as f64).powi(2);
+            *frames += 1;
+            if *frames == hop_frames {
+                envelope.push(to_dbfs((*sum / *frames as f64).sqrt()));
+                (*sum, *frames) = (0.0, 0);
+            }
+        },
+    )?;
+    if frames > 0 {
+        envelope.push(to_dbfs((sum / frames as f64).sqrt()));
+    }
+    Ok(envelope)
+}
+
+/// 用 ffmpeg 命令行把音频解码为 32 位浮点 WAV 并通过管道逐帧读取，不落盘
+///
+/// `init` 在读到 WAV 头后按声道数创建累计状态，`push` 每次收到一帧（每个声道一个采样）
+fn decode_pcm<M>(
+    input: &Path,
+    sample_rate: u32,
+    mono: bool,
+    init: impl FnOnce(u16) -> M,
+    mut push: impl FnMut(&mut M, &[f32]),
+) -> Result<M> {
+    let ffmpeg = ffmpeg_binary();
+    let mut command = Command::new(&ffmpeg);
+    command
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-i")
+        .arg(input)
+        .arg("-vn")
+        .arg("-acodec")
+        .arg("pcm_f32le")
+        .arg("-ar")
+        .arg(sample_rate.to_string());
+    if mono {
+        command.arg("-ac").arg("1");
+    }
+    let mut child = command
+        .arg("-f")
+        .arg("wav")
+        .arg("-")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("执行 ffmpeg 命令失败: {}（请安装 FFmpeg 或设置 FFMPEG_PATH）", ffmpeg.display()))?;
+    let mut stdout = child.stdout.take().context("读取 ffmpeg 输出失败")?;
+
+    let read = (|| -> Result<M> {
+        let channels = read_wav_header(&mut stdout)?;
+        let mut state = init(channels);
+        let frame_bytes = channels as usize * 4;
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut pending = Vec::with_capacity(buffer.len() + frame_bytes);
+        let mut frame = vec![0f32; channels as usize];
+        loop {
+            let n = stdout.read(&mut buffer).context("读取解码后的音频失败")?;
+            if n == 0 {
+                break;
+            }
+            pending.extend_from_slice(&buffer[..n]);
+            let complete = pending.len() / frame_bytes * frame_bytes;
+            for bytes in pending[..complete].chunks_exact(frame_bytes) {
+                for (sample, le) in frame.iter_mut().zip(bytes.chunks_exact(4)) {
+                    *sample = f32::from_le_bytes([le[0], le[1], le[2], le[3]]);
+                }
+                push(&mut state, &frame);
+            }
+            pending.drain(..complete);
+        }
+        Ok(state)
+    })();
+    let status = child.wait().context("等待 ffmpeg 退出失败")?;
+    let state = read?;
+    if !status.success() {
+        bail!("ffmpeg 解码音频失败: {}", status);
+    }
+    Ok(state)
+}
+
+/// 读取 WAV 头，返回声道数，读取位置停在 data 块的开头
+///
+/// 管道输出的 WAV 没有准确的长度字段，data 块一直读到 EOF
+fn read_wav_header(reader: &mut impl Read) -> Result<u16> {
+    let mut riff = [0u8; 12];
+    reader.read_exact(&mut riff).context("读取 WAV 头失败")?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        bail!("ffmpeg 输出的不是 WAV 格式");
+    }
+    let mut channels = None;
+    loop {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).context("读取 WAV 块失败")?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        match &header[0..4] {
+            b"fmt " => {
+                let mut fmt = vec![0u8; size + size % 2];
+                reader.read_exact(&mut fmt).context("读取 WAV 格式块失败")?;
+                if size < 16 || u16::from_le_bytes([fmt[14], fmt[15]]) != 32 {
+                    bail!("WAV 格式不是 32 位浮点");
+                }
+                channels = Some(u16::from_le_bytes([fmt[2], fmt[3]]));
+            }
+            b"data" => break,
+            _ => {
+                std::io::copy(&mut reader.by_ref().take((size + size % 2) as u64), &mut std::io::sink())
+                    .context("跳过 WAV 块失败")?;
+            }
+        }
+    }
+    match channels {
+        Some(channels) if channels > 0 => Ok(channels),
+        _ => bail!("WAV 缺少格式块"),
+    }
+}
+
+/// 二阶 IIR 滤波器（直接 I 型）
+#[derive(Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self { b, a, x: [0.0; 2], y: [0.0; 2] }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// ITU-R BS.1770 K 加权滤波器（48kHz）：高架滤波 + 高通滤波
+fn k_weighting() -> [Biquad; 2] {
+    [
+        Biquad::new(
+            [1.535_124_859_586_97, -2.691_696_189_406_38, 1.198_392_810_852_85],
+            [-1.690_659_293_182_41, 0.732_480_774_215_85],
+        ),
+        Biquad::new([1.0, -2.0, 1.0], [-1.990_047_454_833_98, 0.990_072_250_366_21]),
+    ]
+}
+
+/// 逐帧累计电平和响度
+struct LevelMeter {
+    sample_rate: u32,
+    channels: u16,
+    /// 每个声道的 K 加权滤波器
+    filters: Vec<[Biquad; 2]>,
+    /// 声道权重（5.1 的环绕声道为 1.41，LFE 不计入）
+    weights: Vec<f64>,
+    /// 当前一秒内的平方和、峰值和帧数
+    second_sum: f64,
+    second_peak: f32,
+    second_frames: u32,
+    rms: Vec<f32>,
+    peak: Vec<f32>,
+    /// 当前 100ms 子块内各声道 K 加权后的平方和（已乘权重）
+    block_sum: f64,
+    block_frames: u32,
+    /// 每个 100ms 子块的均方能量，400ms 门限块由相邻 4 个子块组成
+    sub_blocks: Vec<f64>,
+    total_sum: f64,
+    total_peak: f32,
+    total_frames: u64,
+}
+
+impl LevelMeter {
+    fn new(sample_rate: u32, channels: u16) -> Self {
+        let weights = (0..channels as usize)
+            .map(|i| match (channels, i) {
+                (6, 3) => 0.0,
+                (6, 4) | (6, 5) => 1.41,
+                _ => 1.0,
+            })
+            .collect();
+        Self {
+            sample_rate,
+            channels,
+            filters: (0..channels).map(|_| k_weighting()).collect(),
+            weights,
+            second_sum: 0.0,
+            second_peak: 0.0,
+            second_frames: 0,
+            rms: Vec::new(),
+            peak: Vec::new(),
+            block_sum: 0.0,
+            block_frames: 0,
+            sub_blocks: Vec::new(),
+            total_sum: 0.0,
+            total_peak: 0.0,
+            total_frames: 0,
+        }
+    }
+
+    /// 加入一帧（每个声道一个采样）
+    fn push(&mut self, frame: &[f32]) {
+        let mut square = 0.0;
+        let mut peak = 0.0f32;
+        let mut weighted = 0.0;
+        for ((&sample, filters), weight) in frame.iter().zip(&mut self.filters).zip(&self.weights) {
+            square += (sample as f64).powi(2);
+            peak = peak.max(sample.abs());
+            let filtered = filters.iter_mut().fold(sample as f64, |value, filter| filter.process(value));
+            weighted += weight * filtered * filtered;
+        }
+        square /= frame.len().max(1) as f64;
+
+        self.second_sum += square;
+        self.second_peak = self.second_peak.max(peak);
+        self.second_frames += 1;
+        if self.second_frames == self.sample_rate {
+            self.flush_second();
+        }
+
+        self.block_sum += weighted;
+        self.block_frames += 1;
+        if self.block_frames == self.sample_rate / 10 {
+            self.sub_blocks.push(self.block_sum / self.block_frames as f64);
+            self.block_sum = 0.0;
+            self.block_frames = 0;
+        }
+
+        self.total_sum += square;
+        self.total_peak = self.total_peak.max(peak);
+        self.total_frames += 1;
+    }
+
+    fn flush_second(&mut self) {
+        self.rms.push(round4((self.second_sum / self.second_frames as f64).sqrt()));
+        self.peak.push(round4(self.second_peak as f64));
+        self.second_sum = 0.0;
+        self.second_peak = 0.0;
+        self.second_frames = 0;
+    }
+
+    /// 结束计算，返回波形和响度统计
+    fn finish(mut self) -> (Waveform, LoudnessStats) {
+        // 最后不足一秒的部分也输出一个点
+        if self.second_frames > 0 {
+            self.flush_second();
+        }
+        let rms = if self.total_frames > 0 { (self.total_sum / self.total_frames as f64).sqrt() } else { 0.0 };
+        let stats = LoudnessStats {
+            integrated_lufs: integrated_loudness(&self.sub_blocks).map(round2),
+            peak_dbfs: round2(to_dbfs(self.total_peak as f64)),
+            rms_dbfs: round2(to_dbfs(rms)),
+            waveform: WAVEFORM_FILE.to_string(),
+        };
+        let waveform = Waveform {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            interval: 1.0,
+            rms: self.rms,
+            peak: self.peak,
+        };
+        (waveform, stats)
+    }
+}
+
+/// EBU R128 综合响度：400ms 门限块（75% 重叠），先按绝对门限 -70 LUFS、
+/// 再按相对门限（绝对门限内的平均响度 -10 LU）筛选后取平均
+fn integrated_loudness(sub_blocks: &[f64]) -> Option<f64> {
+    let loudness = |energy: f64| -0.691 + 10.0 * energy.log10();
+    let blocks: Vec<f64> = sub_blocks.windows(4).map(|w| w.iter().sum::<f64>() / 4.0).collect();
+    let gated = |gate: f64| -> Option<f64> {
+        let passed: Vec<f64> = blocks.iter().copied().filter(|&z| z > 0.0 && loudness(z) > gate).collect();
+        (!passed.is_empty()).then(|| passed.iter().sum::<f64>() / passed.len() as f64)
+    };
+    let relative_gate = loudness(gated(ABSOLUTE_GATE_LUFS)?) + RELATIVE_GATE_LU;
+    gated(relative_gate.max(ABSOLUTE_GATE_LUFS)).map(loudness)
+}
+
+fn to_dbfs(amplitude: f64) -> f64 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()).max(SILENCE_DBFS)
+    } else {
+        SILENCE_DBFS
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn round4(value: f64) -> f32 {
+    ((value * 10_000.0).round() / 10_000.0) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(meter: &mut LevelMeter, seconds: f64, amplitude: f32) {
+        let frames = (seconds * meter.sample_rate as f64) as usize;
+        let channels = meter.channels as usize;
+        for i in 0..frames {
+            let t = i as f64 / meter.sample_rate as f64;
+            let sample = amplitude * (2.0 * std::f64::consts::PI * 1000.0 * t).sin() as f32;
+            meter.push(&vec![sample; channels]);
+        }
+    }
+
+    #[test]
+    fn test_level_meter() {
+        // EBU Tech 3341：1kHz 双声道 -23 dBFS 正弦波的综合响度为 -23 LUFS
+        let amplitude = 10f32.powf(-23.0 / 20.0);
+        let mut meter = LevelMeter::new(ANALYSIS_SAMPLE_RATE, 2);
+        sine(&mut meter, 2.0, 0.0);
+        sine(&mut meter, 10.5, amplitude);
+        let (waveform, stats) = meter.finish();
+
+        let integrated = stats.integrated_lufs.unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "integrated = {}", integrated);
+        assert!((stats.peak_dbfs + 23.0).abs() < 0.01);
+        assert_eq!(waveform.rms.len(), 13);
+        assert_eq!((waveform.rms[0], waveform.peak[0]), (0.0, 0.0));
+        assert!((waveform.rms[5] as f64 - amplitude as f64 / 2f64.sqrt()).abs() < 1e-3);
+        assert!((waveform.peak[5] - amplitude).abs() < 1e-3);
+
+        // 整段静音时没有综合响度
+        let mut silent = LevelMeter::new(ANALYSIS_SAMPLE_RATE, 1);
+        sine(&mut silent, 1.0, 0.0);
+        let (_, stats) = silent.finish();
+        assert_eq!((stats.integrated_lufs, stats.peak_dbfs), (None, SILENCE_DBFS));
+    }
+
+    #[test]
+    fn test_read_wav_header() {
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF\xff\xff\xff\xffWAVE");
+        wav.extend_from_slice(b"LIST\x02\x00\x00\x00ab");
+        wav.extend_from_slice(b"fmt \x10\x00\x00\x00");
+        wav.extend_from_slice(&[3, 0, 2, 0, 0x80, 0xbb, 0, 0, 0, 0, 0, 0, 8, 0, 32, 0]);
+        wav.extend_from_slice(b"data\xff\xff\xff\xff");
+        wav.extend_from_slice(&0.5f32.to_le_bytes());
+        let mut reader = wav.as_slice();
+        assert_eq!(read_wav_header(&mut reader).unwrap(), 2);
+        assert_eq!(reader, 0.5f32.to_le_bytes());
+        assert!(read_wav_header(&mut &b"RIFF\0\0\0\0AVI "[..]).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+/// 音频能量包络的时间分辨率（秒）
+pub const ENVELOPE_HOP: f64 = 0.01;
+/// 相邻包络点的电平变化超过该值（dB）时视为音频突变
+const DISCONTINUITY_DB: f64 = 12.0;
+
+/// 按音频校准场景边界的配置
+///
+/// 镜头切换常与对白停顿或声音突变对齐，启用后把检测到的切点吸附到附近的静音点或音频突变处
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioBoundaryConfig {
+    /// 是否校准
+    pub enabled: bool,
+    /// 在检测到的切点前后多少秒内查找音频边界
+    pub window: f64,
+    /// 低于该电平（dBFS）视为静音
+    pub silence_dbfs: f64,
+}
+
+impl Default for AudioBoundaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 0.5,
+            silence_dbfs: -40.0,
+        }
+    }
+}
+
+/// 把切点吸附到附近的音频边界，返回校准后的切点和移动的切点数
+///
+/// `envelope` 为每 `hop` 秒一个点的 RMS 电平（dBFS）。窗口内有静音时取离切点最近的静音点，
+/// 否则取电平变化最大且超过 12 dB 的位置，都没有时保持不变；视频开头的切点不移动，
+/// 校准后的切点保持递增且不越过相邻切点
+pub fn refine_boundaries(scene_changes: &[f64], envelope: &[f64], hop: f64, config: &AudioBoundaryConfig) -> (Vec<f64>, usize) {
+    let mut refined: Vec<f64> = Vec::with_capacity(scene_changes.len());
+    let mut moved = 0;
+    for (i, &cut) in scene_changes.iter().enumerate() {
+        if cut <= 0.0 || envelope.is_empty() || hop <= 0.0 {
+            refined.push(cut);
+            continue;
+        }
+        let lower = (cut - config.window).max(refined.last().map_or(0.0, |prev| prev + hop));
+        let upper = scene_changes.get(i + 1).map_or(cut + config.window, |next| (cut + config.window).min(next - hop));
+        let first = (lower / hop).ceil().max(0.0) as usize;
+        let last = ((upper / hop).floor() as usize).min(envelope.len() - 1);
+        if first > last {
+            refined.push(cut);
+            continue;
+        }
+
+        let silence = (first..=last)
+            .filter(|&idx| envelope[idx] <= config.silence_dbfs)
+            .map(|idx| (idx as f64 + 0.5) * hop)
+            .min_by(|a, b| (a - cut).abs().total_cmp(&(b - cut).abs()));
+        let discontinuity = || {
+            (first.max(1)..=last)
+                .map(|idx| (idx, (envelope[idx] - envelope[idx - 1]).abs()))
+                .filter(|&(_, jump)| jump >= DISCONTINUITY_DB)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(idx, _)| idx as f64 * hop)
+        };
+        let snapped = silence
+            .or_else(discontinuity)
+            .map(|time| (time * 1000.0).round() / 1000.0)
+            .filter(|time| *time >= lower && *time <= upper)
+            .unwrap_or(cut);
+        if snapped != cut {
+            moved += 1;
+        }
+        refined.push(snapped);
+    }
+    (refined, moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refine_boundaries() {
+        // 10 秒包络：3.0-3.2 秒静音，6.5 秒处从 -30 dBFS 跳到 -10 dBFS
+        let envelope: Vec<f64> = (0..1000)
+            .map(|idx| match idx {
+                300..=319 => -60.0,
+                650.. => -10.0,
+                _ => -30.0,
+            })
+            .collect();
+        let config = AudioBoundaryConfig { enabled: true, ..AudioBoundaryConfig::default() };
+
+        let (refined, moved) = refine_boundaries(&[0.0, 3.4, 6.3, 9.0], &envelope, ENVELOPE_HOP, &config);
+        assert_eq!(moved, 2);
+        // 吸附到最近的静音点，第二个切点吸附到电平突变处，没有音频边界的切点不变
+        assert_eq!(refined, vec![0.0, 3.195, 6.5, 9.0]);
+
+        // 不越过相邻切点
+        let (refined, _) = refine_boundaries(&[0.0, 3.0, 3.4], &envelope, ENVELOPE_HOP, &config);
+        assert!(refined.windows(2).all(|w| w[0] < w[1]));
+        assert!(refined[2] > 3.0);
+        assert_eq!(refine_boundaries(&[0.0, 3.4], &[], ENVELOPE_HOP, &config), (vec![0.0, 3.4], 0));
+    }
+}
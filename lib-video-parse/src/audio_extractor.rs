@@ -1,15 +1,156 @@
 use ffmpeg_next as ffmpeg;
-use anyhow::{Context, Result};
-use std::path::Path;
+use crate::error::{Result, VideoParseError};
+use crate::output_naming::FileNaming;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 音频输出编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AudioCodec {
+    /// AAC（默认）：原始音频为 AAC 且未设置码率、采样率和声道时直接复制音频流
+    #[default]
+    Aac,
+    /// MP3（需要 ffmpeg 带 libmp3lame）
+    Mp3,
+    /// Opus（需要 ffmpeg 带 libopus）
+    Opus,
+    /// 16 位 PCM WAV
+    Wav,
+    /// FLAC 无损压缩
+    Flac,
+    /// 不提取音频
+    None,
+}
+
+impl AudioCodec {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "aac",
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Opus => "opus",
+            AudioCodec::Wav => "wav",
+            AudioCodec::Flac => "flac",
+            AudioCodec::None => "none",
+        }
+    }
+
+    /// 输出文件扩展名
+    pub fn extension(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// ffmpeg 编码器名称
+    fn encoder(&self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "aac",
+            AudioCodec::Mp3 => "libmp3lame",
+            AudioCodec::Opus => "libopus",
+            AudioCodec::Wav => "pcm_s16le",
+            AudioCodec::Flac => "flac",
+            AudioCodec::None => "",
+        }
+    }
+
+    /// 是否为有损编码（码率设置只对有损编码生效）
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, AudioCodec::Aac | AudioCodec::Mp3 | AudioCodec::Opus)
+    }
+
+    /// 未设置码率时的默认码率（kbps）
+    fn default_bitrate_kbps(&self) -> u32 {
+        match self {
+            AudioCodec::Opus => 128,
+            _ => 192,
+        }
+    }
+}
+
+impl FromStr for AudioCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "aac" => Ok(AudioCodec::Aac),
+            "mp3" => Ok(AudioCodec::Mp3),
+            "opus" => Ok(AudioCodec::Opus),
+            "wav" => Ok(AudioCodec::Wav),
+            "flac" => Ok(AudioCodec::Flac),
+            "none" => Ok(AudioCodec::None),
+            other => Err(format!("未知的音频编码: {}（可选值: aac, mp3, opus, wav, flac, none）", other)),
+        }
+    }
+}
+
+/// 音频输出配置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioConfig {
+    pub codec: AudioCodec,
+    /// 码率（kbps），None 时 AAC / MP3 为 192、Opus 为 128；WAV / FLAC 忽略
+    pub bitrate_kbps: Option<u32>,
+    /// 采样率（Hz），None 表示保持原始采样率
+    pub sample_rate: Option<u32>,
+    /// 声道数，None 表示保持原始声道
+    pub channels: Option<u32>,
+}
+
+impl AudioConfig {
+    /// 按文件名模板生成的输出文件名（默认 `audio.aac` 等），不提取音频时为 None
+    pub fn file_name(&self, naming: &FileNaming) -> Option<String> {
+        (self.codec != AudioCodec::None).then(|| naming.audio_file(self.codec.extension()))
+    }
+
+    /// 是否先尝试直接复制音频流
+    fn try_copy(&self) -> bool {
+        self.codec == AudioCodec::Aac && self.bitrate_kbps.is_none() && self.sample_rate.is_none() && self.channels.is_none()
+    }
+
+    /// 重新编码时的 ffmpeg 参数
+    fn encode_args(&self) -> Vec<String> {
+        let mut args = vec!["-acodec".to_string(), self.codec.encoder().to_string()];
+        if self.codec.is_lossy() {
+            let bitrate = self.bitrate_kbps.unwrap_or_else(|| self.codec.default_bitrate_kbps());
+            args.extend(["-b:a".to_string(), format!("{}k", bitrate)]);
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            args.extend(["-ar".to_string(), sample_rate.to_string()]);
+        }
+        if let Some(channels) = self.channels {
+            args.extend(["-ac".to_string(), channels.to_string()]);
+        }
+        args
+    }
+}
 
 /// 音频提取器，从视频中提取音频
 pub struct AudioExtractor {
-    input_path: String,
+    input_path: PathBuf,
+}
+
+/// ffmpeg 命令行工具的路径
+///
+/// 依次查找 FFMPEG_PATH 环境变量、程序所在目录和 PATH 中的 `ffmpeg`（Windows 上为 `ffmpeg.exe`），
+/// 都找不到时返回 `ffmpeg`，由系统在执行时查找
+pub fn ffmpeg_binary() -> PathBuf {
+    if let Some(path) = env::var_os("FFMPEG_PATH").filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    let name = format!("ffmpeg{}", env::consts::EXE_SUFFIX);
+    // Windows 上常把 ffmpeg.exe 和程序放在同一目录
+    let beside_exe = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&name)));
+    let in_path = env::var_os("PATH")
+        .and_then(|paths| env::split_paths(&paths).map(|dir| dir.join(&name)).find(|p| p.is_file()));
+    beside_exe
+        .filter(|p| p.is_file())
+        .or(in_path)
+        .unwrap_or_else(|| PathBuf::from(name))
 }
 
 impl AudioExtractor {
     pub fn new(input_path: impl AsRef<Path>) -> Result<Self> {
-        ffmpeg::init().context("初始化 FFmpeg 失败")?;
+        ffmpeg::init().map_err(|e| VideoParseError::decode("初始化 FFmpeg 失败", e))?;
         
         // 设置 FFmpeg 日志级别为 ERROR，抑制警告和信息消息
         unsafe {
@@ -17,53 +158,73 @@ impl AudioExtractor {
         }
         
         Ok(Self {
-            input_path: input_path.as_ref().to_string_lossy().to_string(),
+            input_path: input_path.as_ref().to_path_buf(),
         })
     }
 
-    /// 提取音频到文件
+    /// 提取音频到文件（AAC，能复制时直接复制音频流）
     pub fn extract_to_file(&self, output_path: impl AsRef<Path>) -> Result<()> {
+        self.extract_with_config(output_path, &AudioConfig::default())
+    }
+
+    /// 按配置的编码、码率、采样率和声道提取音频到文件
+    ///
+    /// 编码为 [`AudioCodec::None`] 时不做任何操作
+    pub fn extract_with_config(&self, output_path: impl AsRef<Path>, config: &AudioConfig) -> Result<()> {
+        if config.codec == AudioCodec::None {
+            return Ok(());
+        }
+        let output_path = output_path.as_ref();
+
+        // 先尝试直接复制音频流，失败（例如原始音频不是 AAC）时重新编码
+        if config.try_copy() && self.run_ffmpeg(output_path, &["-acodec".to_string(), "copy".to_string()])? {
+            return Ok(());
+        }
+        if !self.run_ffmpeg(output_path, &config.encode_args())? {
+            return Err(VideoParseError::Ffmpeg(format!("音频提取失败（{} 编码）", config.codec.as_str())));
+        }
+        Ok(())
+    }
+
+    /// 执行 ffmpeg 命令行提取音频，返回是否成功
+    fn run_ffmpeg(&self, output_path: &Path, codec_args: &[String]) -> Result<bool> {
         use std::process::Command;
-        
-        let output_path_str = output_path.as_ref().to_string_lossy().to_string();
-        
-        // 使用 ffmpeg 命令行工具提取音频
+
+        let ffmpeg = ffmpeg_binary();
         // 使用 -loglevel error 抑制警告和信息消息
-        let status = Command::new("ffmpeg")
+        let status = Command::new(&ffmpeg)
             .arg("-loglevel")
             .arg("error") // 只显示错误信息
             .arg("-i")
             .arg(&self.input_path)
             .arg("-vn") // 不包含视频
-            .arg("-acodec")
-            .arg("copy") // 尝试直接复制音频流
+            .args(codec_args)
             .arg("-y") // 覆盖输出文件
-            .arg(&output_path_str)
+            .arg(output_path)
             .status()
-            .context("执行 ffmpeg 命令失败")?;
-        
-        if !status.success() {
-            // 如果复制失败，尝试重新编码为 AAC
-            let status = Command::new("ffmpeg")
-                .arg("-loglevel")
-                .arg("error") // 只显示错误信息
-                .arg("-i")
-                .arg(&self.input_path)
-                .arg("-vn")
-                .arg("-acodec")
-                .arg("aac")
-                .arg("-b:a")
-                .arg("192k")
-                .arg("-y")
-                .arg(&output_path_str)
-                .status()
-                .context("执行 ffmpeg 重新编码失败")?;
-            
-            if !status.success() {
-                anyhow::bail!("音频提取失败");
-            }
-        }
-        
-        Ok(())
+            .map_err(|e| VideoParseError::io(format!("执行 ffmpeg 命令失败: {}（请安装 FFmpeg 或设置 FFMPEG_PATH）", ffmpeg.display()), e))?;
+        Ok(status.success())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_config() {
+        assert_eq!(AudioConfig::default().file_name(&FileNaming::default()).as_deref(), Some("audio.aac"));
+        assert!(AudioConfig::default().try_copy());
+        assert_eq!(AudioConfig::default().encode_args(), ["-acodec", "aac", "-b:a", "192k"]);
+
+        let opus = AudioConfig { codec: AudioCodec::Opus, sample_rate: Some(48000), channels: Some(1), ..AudioConfig::default() };
+        assert_eq!(opus.file_name(&FileNaming::default()).as_deref(), Some("audio.opus"));
+        assert_eq!(opus.encode_args(), ["-acodec", "libopus", "-b:a", "128k", "-ar", "48000", "-ac", "1"]);
+
+        let wav = AudioConfig { codec: AudioCodec::Wav, bitrate_kbps: Some(320), ..AudioConfig::default() };
+        assert_eq!(wav.encode_args(), ["-acodec", "pcm_s16le"]);
+        assert_eq!("None".parse::<AudioCodec>(), Ok(AudioCodec::None));
+        assert_eq!(AudioConfig { codec: AudioCodec::None, ..AudioConfig::default() }.file_name(&FileNaming::default()), None);
+        assert!("ogg".parse::<AudioCodec>().is_err());
+    }
+}
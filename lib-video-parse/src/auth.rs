@@ -0,0 +1,170 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Arc;
+
+/// API Key 请求头（也接受 `Authorization: Bearer <key>`）
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// 回调签名请求头，取值为 `sha256=<hex>`（也接受不带前缀的 hex）
+pub const SIGNATURE_HEADER: &str = "x-video-parse-signature";
+
+/// 可以用签名代替 API Key 的回调路径（OSS 事件和函数计算调用）
+const SIGNED_PATHS: &[&str] = &["/process", "/invoke"];
+/// 校验签名时读取的最大请求体（字节）
+const MAX_SIGNED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// HTTP 接口认证配置
+///
+/// 未配置 API Key 和签名密钥时不启用认证
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthConfig {
+    /// 允许的 API Key，任意一个匹配即通过
+    pub api_keys: Vec<String>,
+    /// OSS 事件回调的 HMAC-SHA256 签名密钥
+    pub signature_secret: Option<String>,
+    /// 不需要认证的路径
+    pub public_paths: Vec<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: Vec::new(),
+            signature_secret: None,
+            // 函数计算的初始化回调不带认证信息
+            public_paths: ["/", "/health", "/initialize", "/metrics"].map(String::from).to_vec(),
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.signature_secret.is_some()
+    }
+
+    /// 请求头中的 API Key 是否有效
+    fn api_key_valid(&self, headers: &HeaderMap) -> bool {
+        let key = headers
+            .get(API_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| {
+                headers
+                    .get(header::AUTHORIZATION)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.strip_prefix("Bearer "))
+            });
+        match key {
+            Some(key) => self.api_keys.iter().any(|k| constant_time_eq(k.as_bytes(), key.trim().as_bytes())),
+            None => false,
+        }
+    }
+}
+
+/// 解析逗号分隔的列表（API Key、公开路径）
+pub fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// 计算请求体的签名：`sha256=<hex>`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", digest.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+/// 校验请求体签名（常数时间比较）
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(expected) = decode_hex(signature.trim().trim_start_matches("sha256=")) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn unauthorized(message: &str) -> Response {
+    (StatusCode::UNAUTHORIZED, message.to_string()).into_response()
+}
+
+/// 认证中间件：公开路径直接放行；其余请求需要有效的 API Key，
+/// 回调路径也可以用请求体的 HMAC 签名代替
+pub async fn require_auth(State(config): State<Arc<AuthConfig>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if !config.enabled() || config.public_paths.iter().any(|p| p == path) {
+        return next.run(request).await;
+    }
+    if config.api_key_valid(request.headers()) {
+        return next.run(request).await;
+    }
+
+    let Some(secret) = config.signature_secret.as_deref().filter(|_| SIGNED_PATHS.contains(&path)) else {
+        tracing::warn!(path = %path, "🔒 [认证] 缺少或无效的 API Key");
+        return unauthorized("缺少或无效的 API Key");
+    };
+    let Some(signature) = request.headers().get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok()).map(String::from) else {
+        tracing::warn!(path = %path, "🔒 [认证] 缺少 API Key 或回调签名");
+        return unauthorized("缺少 API Key 或回调签名");
+    };
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => return (StatusCode::PAYLOAD_TOO_LARGE, format!("读取请求体失败: {}", e)).into_response(),
+    };
+    if !verify_signature(secret, &body, &signature) {
+        tracing::warn!(path = %parts.uri.path(), "🔒 [认证] 回调签名校验失败");
+        return unauthorized("回调签名校验失败");
+    }
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth() {
+        let signature = sign("secret", b"{\"events\":[]}");
+        assert!(signature.starts_with("sha256=") && signature.len() == 7 + 64);
+        assert!(verify_signature("secret", b"{\"events\":[]}", &signature));
+        assert!(verify_signature("secret", b"{\"events\":[]}", signature.trim_start_matches("sha256=")));
+        assert!(!verify_signature("secret", b"{\"events\":[1]}", &signature));
+        assert!(!verify_signature("other", b"{\"events\":[]}", &signature));
+        assert!(!verify_signature("secret", b"", "sha256=zz"));
+
+        let config = AuthConfig { api_keys: parse_list("k1, k2,,"), ..Default::default() };
+        assert_eq!(config.api_keys, vec!["k1", "k2"]);
+        assert!(config.enabled() && !AuthConfig::default().enabled());
+        let mut headers = HeaderMap::new();
+        assert!(!config.api_key_valid(&headers));
+        headers.insert(header::AUTHORIZATION, "Bearer k2".parse().unwrap());
+        assert!(config.api_key_valid(&headers));
+        headers.insert(API_KEY_HEADER, "k3".parse().unwrap());
+        assert!(!config.api_key_valid(&headers));
+    }
+}
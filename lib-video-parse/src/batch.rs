@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use crate::preflight::is_video_file;
+use crate::processor::{process_video_with_options, ProcessConfig, ProcessOptions};
+
+/// 批量处理汇总报告文件名（写入输出目录）
+pub const BATCH_REPORT_FILE: &str = "batch_report.json";
+
+/// 批量处理中一个视频的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItem {
+    /// 输入视频路径
+    pub input: PathBuf,
+    /// 该视频的输出目录
+    pub output: PathBuf,
+    /// success | failure
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_duration: Option<f64>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 批量处理汇总报告
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub input_dir: PathBuf,
+    pub output_dir: PathBuf,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    /// 按输入路径排序
+    pub items: Vec<BatchItem>,
+}
+
+/// 批量处理参数
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// 同时处理的视频数
+    pub parallelism: usize,
+    /// 单个视频的处理超时
+    pub timeout: Option<Duration>,
+    /// 取消后不再开始新的视频，正在处理的视频也会被取消
+    pub cancel: CancellationToken,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            parallelism: 1,
+            timeout: None,
+            cancel: CancellationToken::new(),
+        }
+    }
+}
+
+/// 递归查找目录下的视频文件（按扩展名判断），按路径排序
+pub fn discover_videos(input_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut videos = Vec::new();
+    let mut pending = vec![input_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir).context(format!("读取目录失败: {}", dir.display()))?;
+        for entry in entries {
+            let entry = entry.context(format!("读取目录失败: {}", dir.display()))?;
+            let path = entry.path();
+            let file_type = entry.file_type().context(format!("读取文件类型失败: {}", path.display()))?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if is_video_file(&entry.file_name().to_string_lossy()) {
+                videos.push(path);
+            }
+        }
+    }
+    videos.sort();
+    Ok(videos)
+}
+
+/// 为每个视频分配输出目录：保留相对输入目录的层级并去掉扩展名（`a/b.mp4` → `<output>/a/b`），
+/// 同名不同扩展名的视频使用 `b_mov` 这样的目录区分
+pub fn output_dirs(input_dir: &Path, output_dir: &Path, videos: &[PathBuf]) -> Vec<PathBuf> {
+    let mut used = HashSet::new();
+    videos
+        .iter()
+        .map(|video| {
+            let relative = video.strip_prefix(input_dir).unwrap_or(video);
+            let mut dir = output_dir.join(relative.with_extension(""));
+            if !used.insert(dir.clone()) {
+                let file_name = relative.file_name().unwrap_or_default().to_string_lossy().replace('.', "_");
+                dir = output_dir.join(relative.with_file_name(file_name));
+                used.insert(dir.clone());
+            }
+            dir
+        })
+        .collect()
+}
+
+/// 处理目录下的所有视频，每个视频输出到单独的目录，并在输出目录写出汇总报告
+///
+/// 单个视频失败只记录到报告中，不影响其他视频
+pub async fn run_batch(input_dir: &Path, output_dir: &Path, config: ProcessConfig, options: BatchOptions) -> Result<BatchReport> {
+    let start = Instant::now();
+    let videos = discover_videos(input_dir)?;
+    let outputs = output_dirs(input_dir, output_dir, &videos);
+    tracing::info!(
+        videos = videos.len(),
+        parallelism = options.parallelism.max(1),
+        input_dir = %input_dir.display(),
+        "📂 [批量处理] 找到视频文件"
+    );
+
+    let semaphore = Arc::new(Semaphore::new(options.parallelism.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (input, output) in videos.into_iter().zip(outputs) {
+        let semaphore = semaphore.clone();
+        let config = config.clone();
+        let options = options.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            process_one(input, output, config, options).await
+        });
+    }
+
+    let mut items = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        items.push(result.context("批量处理任务异常终止")?);
+    }
+    items.sort_by(|a, b| a.input.cmp(&b.input));
+
+    let succeeded = items.iter().filter(|item| item.status == "success").count();
+    let report = BatchReport {
+        input_dir: input_dir.to_path_buf(),
+        output_dir: output_dir.to_path_buf(),
+        total: items.len(),
+        succeeded,
+        failed: items.len() - succeeded,
+        duration_ms: start.elapsed().as_millis() as u64,
+        items,
+    };
+    std::fs::create_dir_all(output_dir).context(format!("创建输出目录失败: {}", output_dir.display()))?;
+    let report_path = output_dir.join(BATCH_REPORT_FILE);
+    std::fs::write(&report_path, serde_json::to_vec_pretty(&report)?)
+        .context(format!("写入批量处理报告失败: {}", report_path.display()))?;
+    tracing::info!(
+        total = report.total,
+        succeeded = report.succeeded,
+        failed = report.failed,
+        duration_ms = report.duration_ms,
+        report = %report_path.display(),
+        "🎉 [批量处理] 全部处理完成"
+    );
+    Ok(report)
+}
+
+async fn process_one(input: PathBuf, output: PathBuf, config: ProcessConfig, options: BatchOptions) -> BatchItem {
+    let start = Instant::now();
+    let result = if options.cancel.is_cancelled() {
+        Err(crate::error::VideoParseError::Cancelled)
+    } else {
+        tracing::info!(input = %input.display(), "🎬 [批量处理] 开始处理");
+        let mut process_options = ProcessOptions::default().with_cancel(options.cancel.child_token());
+        if let Some(timeout) = options.timeout {
+            process_options = process_options.with_timeout(timeout);
+        }
+        process_video_with_options(&input, &output, config, process_options).await
+    };
+    let duration_ms = start.elapsed().as_millis() as u64;
+    match result {
+        Ok(result) => {
+            tracing::info!(input = %input.display(), scene_count = result.metadata.scene_count, duration_ms, "✅ [批量处理] 处理完成");
+            BatchItem {
+                input,
+                output,
+                status: "success",
+                job_id: Some(result.job_id),
+                scene_count: Some(result.metadata.scene_count),
+                video_duration: Some(result.metadata.total_duration),
+                duration_ms,
+                error: None,
+            }
+        }
+        Err(e) => {
+            tracing::warn!(input = %input.display(), error = %e, "❌ [批量处理] 处理失败");
+            BatchItem {
+                input,
+                output,
+                status: "failure",
+                job_id: None,
+                scene_count: None,
+                video_duration: None,
+                duration_ms,
+                error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_videos() {
+        let dir = std::env::temp_dir().join(format!("video-parse-batch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("season1")).unwrap();
+        for file in ["b.mp4", "notes.txt", "season1/a.MOV", "season1/a.mp4"] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+
+        let videos = discover_videos(&dir).unwrap();
+        assert_eq!(videos, vec![dir.join("b.mp4"), dir.join("season1/a.MOV"), dir.join("season1/a.mp4")]);
+        let out = Path::new("/out");
+        assert_eq!(
+            output_dirs(&dir, out, &videos),
+            vec![out.join("b"), out.join("season1/a"), out.join("season1/a_mp4")]
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{debug, warn};
+use crate::{OssClient, ProcessResult, config::ExtendedConfig};
+
+/// 目标 bucket 中缓存记录的存放前缀
+const BUCKET_CACHE_PREFIX: &str = ".video-parse-cache";
+
+/// 缓存存储位置
+#[derive(Debug, Clone)]
+pub enum CacheBackend {
+    /// 本地目录（例如挂载的 NAS）
+    Local(PathBuf),
+    /// 目标 OSS bucket
+    Bucket {
+        bucket: String,
+        region: String,
+    },
+}
+
+/// 缓存记录：(源文件位置, 源文件 ETag, 配置指纹) → 已有处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// 源文件 ETag
+    pub etag: String,
+    /// 处理配置指纹
+    pub config_fingerprint: String,
+    /// 源文件位置（bucket/key）
+    pub source: String,
+    /// 元数据文件位置（本地路径或 oss://bucket/key）
+    pub metadata_location: String,
+    /// 缓存写入时间
+    pub created_at: String,
+    /// 处理结果
+    pub result: ProcessResult,
+}
+
+/// 处理结果缓存
+///
+/// 函数计算重试或重复投递的事件会对同一输入重复处理，
+/// 缓存命中时直接返回已有结果，跳过下载和处理
+pub struct ResultCache {
+    backend: CacheBackend,
+}
+
+impl ResultCache {
+    pub fn new(backend: CacheBackend) -> Self {
+        Self { backend }
+    }
+
+    /// 根据扩展配置创建缓存，未启用缓存时返回 None
+    ///
+    /// 优先使用本地缓存目录；否则在启用 bucket 缓存且配置了目标 bucket 时使用目标 bucket
+    pub fn from_config(config: &ExtendedConfig) -> Option<Self> {
+        if let Some(ref dir) = config.cache_dir {
+            return Some(Self::new(CacheBackend::Local(dir.clone())));
+        }
+
+        if config.cache_in_bucket {
+            if let (Some(bucket), Some(region)) = (
+                config.destination_bucket.clone(),
+                config.destination_region.clone(),
+            ) {
+                return Some(Self::new(CacheBackend::Bucket { bucket, region }));
+            }
+        }
+
+        None
+    }
+
+    pub fn backend(&self) -> &CacheBackend {
+        &self.backend
+    }
+
+    /// 生成缓存键（ETag 去掉两侧引号后与配置指纹、源文件位置哈希拼接）
+    ///
+    /// ETag 只在同一对象内有意义，不同对象内容相同（或 ETag 碰巧相同）时不能共用结果
+    pub fn cache_key(source: &str, etag: &str, config_fingerprint: &str) -> String {
+        format!(
+            "{}_{}_{:016x}",
+            etag.trim_matches('"').to_lowercase(),
+            config_fingerprint,
+            fnv1a64(source.as_bytes())
+        )
+    }
+
+    /// 查询缓存（`source` 为源文件位置 bucket/key），未命中时返回 None
+    pub async fn lookup(
+        &self,
+        oss_client: &OssClient,
+        source: &str,
+        etag: &str,
+        config_fingerprint: &str,
+    ) -> Result<Option<CacheEntry>> {
+        let key = Self::cache_key(source, etag, config_fingerprint);
+
+        let content = match &self.backend {
+            CacheBackend::Local(dir) => {
+                let path = dir.join(format!("{}.json", key));
+                if !path.exists() {
+                    return Ok(None);
+                }
+                std::fs::read_to_string(&path)
+                    .context(format!("读取缓存文件失败: {}", path.display()))?
+            }
+            CacheBackend::Bucket { bucket, region } => {
+                let object_key = format!("{}/{}.json", BUCKET_CACHE_PREFIX, key);
+                let endpoint = format!("oss-{}-internal.aliyuncs.com", region);
+                if oss_client.head_object(bucket, &object_key, Some(&endpoint)).await?.is_none() {
+                    return Ok(None);
+                }
+
+                let local_path = Self::scratch_path(&key);
+                oss_client
+                    .download_file(bucket, &object_key, Some(&endpoint), &local_path)
+                    .await?;
+                let content = std::fs::read_to_string(&local_path)
+                    .context(format!("读取缓存文件失败: {}", local_path.display()))?;
+                let _ = std::fs::remove_file(&local_path);
+                content
+            }
+        };
+
+        match serde_json::from_str::<CacheEntry>(&content) {
+            Ok(entry) if entry.source != source => {
+                // 键的哈希碰撞或记录被改写，按未命中处理
+                warn!("缓存记录的源文件不一致，忽略: key={}, source={}, expected={}", key, entry.source, source);
+                Ok(None)
+            }
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => {
+                // 缓存记录损坏时按未命中处理，随后会被新结果覆盖
+                warn!("缓存记录解析失败，忽略: key={}, error={}", key, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// 写入缓存
+    pub async fn store(&self, oss_client: &OssClient, entry: &CacheEntry) -> Result<()> {
+        let key = Self::cache_key(&entry.source, &entry.etag, &entry.config_fingerprint);
+        let content = serde_json::to_string_pretty(entry)
+            .context("序列化缓存记录失败")?;
+
+        match &self.backend {
+            CacheBackend::Local(dir) => {
+                std::fs::create_dir_all(dir)
+                    .context(format!("创建缓存目录失败: {}", dir.display()))?;
+                let path = dir.join(format!("{}.json", key));
+                std::fs::write(&path, content)
+                    .context(format!("写入缓存文件失败: {}", path.display()))?;
+                debug!("已写入本地缓存: {}", path.display());
+            }
+            CacheBackend::Bucket { bucket, region } => {
+                let object_key = format!("{}/{}.json", BUCKET_CACHE_PREFIX, key);
+                let endpoint = format!("oss-{}-internal.aliyuncs.com", region);
+                let local_path = Self::scratch_path(&key);
+                std::fs::write(&local_path, content)
+                    .context(format!("写入缓存文件失败: {}", local_path.display()))?;
+                let upload = oss_client
+                    .upload_file(bucket, &object_key, &local_path, Some(&endpoint))
+                    .await;
+                let _ = std::fs::remove_file(&local_path);
+                upload?;
+                debug!("已写入 bucket 缓存: oss://{}/{}", bucket, object_key);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// bucket 缓存读写时使用的本地临时文件
+    fn scratch_path(key: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("video-parse").join("cache");
+        let _ = std::fs::create_dir_all(&dir);
+        dir.join(format!("{}.json", key))
+    }
+}
+
+/// FNV-1a 64 位哈希（结果在不同版本和平台间稳定，可用于持久化的键）
+pub fn fnv1a64(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: &str, etag: &str) -> CacheEntry {
+        CacheEntry {
+            etag: etag.to_string(),
+            config_fingerprint: "fp1".to_string(),
+            source: source.to_string(),
+            metadata_location: "oss://dest/videos/demo/metadata.json".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            result: serde_json::from_value(serde_json::json!({
+                "video_file": "demo.mp4",
+                "output_dir": "/tmp/demo",
+                "scene_count": 3,
+                "keyframes": ["keyframe_0000.jpg"],
+                "audio_file": "audio.aac",
+                "metadata_file": "metadata.json",
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let key = ResultCache::cache_key("src/videos/demo.mp4", "\"5EB63BBB\"", "fp1");
+        assert_eq!(key, ResultCache::cache_key("src/videos/demo.mp4", "5eb63bbb", "fp1"));
+        assert!(key.starts_with("5eb63bbb_fp1_"));
+        // 源文件位置或配置不同时键不同
+        assert_ne!(key, ResultCache::cache_key("src/videos/other.mp4", "5eb63bbb", "fp1"));
+        assert_ne!(key, ResultCache::cache_key("src/videos/demo.mp4", "5eb63bbb", "fp2"));
+    }
+
+    #[tokio::test]
+    async fn test_local_lookup_store() {
+        let dir = std::env::temp_dir().join(format!("video-parse-cache-{}", uuid::Uuid::new_v4()));
+        let cache = ResultCache::new(CacheBackend::Local(dir.clone()));
+        let client = OssClient::for_test();
+        let source = "src/videos/demo.mp4";
+
+        assert!(cache.lookup(&client, source, "5eb63bbb", "fp1").await.unwrap().is_none());
+        cache.store(&client, &entry(source, "\"5EB63BBB\"")).await.unwrap();
+
+        let hit = cache.lookup(&client, source, "5eb63bbb", "fp1").await.unwrap().unwrap();
+        assert_eq!(hit.source, source);
+        assert_eq!(hit.result.scene_count, 3);
+        // 相同 ETag 的其他对象、不同配置都不命中
+        assert!(cache.lookup(&client, "src/videos/other.mp4", "5eb63bbb", "fp1").await.unwrap().is_none());
+        assert!(cache.lookup(&client, source, "5eb63bbb", "fp2").await.unwrap().is_none());
+
+        // 记录的源文件与查询不一致时按未命中处理
+        let key = ResultCache::cache_key(source, "5eb63bbb", "fp1");
+        let forged = serde_json::to_string(&entry("src/videos/other.mp4", "5eb63bbb")).unwrap();
+        std::fs::write(dir.join(format!("{}.json", key)), forged).unwrap();
+        assert!(cache.lookup(&client, source, "5eb63bbb", "fp1").await.unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
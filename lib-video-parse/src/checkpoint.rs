@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use crate::metadata::SceneMetadata;
+use crate::scene_detector::TransitionType;
+use crate::video_processor::SampleAspectRatio;
+
+/// 检查点文件名，写在输出目录中（以 `.` 开头，不作为处理结果上传）
+pub const CHECKPOINT_FILE: &str = ".checkpoint.json";
+
+/// 场景检测结果：恢复时跳过帧提取和场景检测，关键帧按时间点重新提取
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectionCheckpoint {
+    pub fps: f64,
+    pub width: u32,
+    pub height: u32,
+    pub sample_aspect_ratio: SampleAspectRatio,
+    pub duration: Option<f64>,
+    /// 采样帧时间戳
+    pub times: Vec<f64>,
+    /// 相邻帧差异
+    pub differences: Vec<f64>,
+    /// 场景起始时间（已按音频校准）
+    pub scene_changes: Vec<f64>,
+    pub transitions: Vec<Option<TransitionType>>,
+}
+
+/// 处理检查点
+///
+/// 函数计算超时后重试同一请求时，已完成的阶段（场景检测、关键帧、音频提取和已上传的文件）不再重复执行。
+/// 输入文件或影响处理结果的配置变化时检查点失效
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// 输入文件大小与配置指纹，不一致时丢弃检查点
+    pub key: String,
+    pub detection: Option<DetectionCheckpoint>,
+    /// 关键帧阶段完成后的场景列表
+    pub scenes: Option<Vec<SceneMetadata>>,
+    /// 已提取的音频文件名
+    pub audio_file: Option<String>,
+    /// 已上传的文件：对象键 → 文件大小
+    pub uploaded: BTreeMap<String, u64>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    /// 检查点键：输入文件大小 + 配置指纹
+    pub fn key(input_size: u64, config_fingerprint: &str) -> String {
+        format!("{}_{}", input_size, config_fingerprint)
+    }
+
+    /// 读取输出目录中的检查点；不存在、损坏或 `key` 不一致时返回空的检查点
+    pub fn load(output_dir: &Path, key: &str) -> Self {
+        match Self::open(output_dir) {
+            Some(checkpoint) if checkpoint.key == key => {
+                info!(
+                    detection = checkpoint.detection.is_some(),
+                    keyframes = checkpoint.scenes.is_some(),
+                    audio = checkpoint.audio_file.is_some(),
+                    uploaded = checkpoint.uploaded.len(),
+                    "♻️  [检查点] 找到上次处理的检查点"
+                );
+                checkpoint
+            }
+            stale => {
+                let path = output_dir.join(CHECKPOINT_FILE);
+                if stale.is_some() {
+                    info!("♻️  [检查点] 输入或处理配置已变化，丢弃上次的检查点");
+                    std::fs::remove_file(&path).ok();
+                }
+                Self { key: key.to_string(), path, ..Default::default() }
+            }
+        }
+    }
+
+    /// 读取输出目录中已有的检查点（不校验键）
+    pub fn open(output_dir: &Path) -> Option<Self> {
+        let path = output_dir.join(CHECKPOINT_FILE);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Self>(&content) {
+            Ok(checkpoint) => Some(Self { path, ..checkpoint }),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "⚠️  [检查点] 检查点文件损坏，忽略");
+                None
+            }
+        }
+    }
+
+    /// 写入检查点：先写临时文件再重命名，写入时超时中断也不会留下不完整的检查点
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_vec(self).context("序列化检查点失败")?;
+        let temp = self.path.with_extension("json.tmp");
+        std::fs::write(&temp, content).context(format!("写入检查点失败: {}", temp.display()))?;
+        std::fs::rename(&temp, &self.path).context(format!("写入检查点失败: {}", self.path.display()))
+    }
+
+    /// 写入检查点，失败只记录警告（检查点只用于加速重试，不影响本次处理）
+    pub fn persist(&self) {
+        if let Err(e) = self.save() {
+            warn!(error = %e, "⚠️  [检查点] 写入检查点失败");
+        }
+    }
+
+    /// 可以直接复用的场景列表：关键帧文件都还在输出目录中时返回
+    pub fn resumable_scenes(&self, output_dir: &Path) -> Option<Vec<SceneMetadata>> {
+        let scenes = self.scenes.as_ref()?;
+        scenes.iter()
+            .flat_map(|scene| std::iter::once(&scene.keyframe_file).chain(&scene.extra_keyframes))
+            .all(|file| output_dir.join(file).exists())
+            .then(|| scenes.clone())
+    }
+
+    /// 文件已按相同大小上传到 `object_key` 时返回 true
+    pub fn is_uploaded(&self, object_key: &str, path: &Path) -> bool {
+        let size = std::fs::metadata(path).map(|m| m.len()).ok();
+        size.is_some() && self.uploaded.get(object_key).copied() == size
+    }
+
+    /// 记录已上传的文件并写入检查点
+    pub fn mark_uploaded(&mut self, object_key: &str, path: &Path) {
+        if let Ok(metadata) = std::fs::metadata(path) {
+            self.uploaded.insert(object_key.to_string(), metadata.len());
+            self.persist();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("video-parse-checkpoint-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = Checkpoint::key(1024, "abc");
+
+        let mut checkpoint = Checkpoint::load(&dir, &key);
+        assert!(checkpoint.detection.is_none() && checkpoint.uploaded.is_empty());
+        checkpoint.detection = Some(DetectionCheckpoint {
+            fps: 25.0,
+            width: 640,
+            height: 360,
+            sample_aspect_ratio: SampleAspectRatio::SQUARE,
+            duration: Some(2.0),
+            times: vec![0.0, 1.0],
+            differences: vec![0.0, 0.4],
+            scene_changes: vec![0.0, 1.0],
+            transitions: vec![None, Some(TransitionType::Cut)],
+        });
+        checkpoint.scenes = Some(Vec::new());
+        checkpoint.persist();
+        let audio = dir.join("audio.aac");
+        std::fs::write(&audio, b"aac").unwrap();
+        checkpoint.mark_uploaded("prefix/audio.aac", &audio);
+
+        let resumed = Checkpoint::load(&dir, &key);
+        assert_eq!(resumed.detection, checkpoint.detection);
+        assert_eq!(resumed.resumable_scenes(&dir).map(|scenes| scenes.len()), Some(0));
+        assert!(resumed.is_uploaded("prefix/audio.aac", &audio));
+        // 文件变化后重新上传
+        std::fs::write(&audio, b"aac-v2").unwrap();
+        assert!(!resumed.is_uploaded("prefix/audio.aac", &audio));
+        // 输入或配置变化时丢弃检查点
+        assert!(Checkpoint::load(&dir, &Checkpoint::key(2048, "abc")).detection.is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use crate::audio_extractor::ffmpeg_binary;
+use crate::metadata::SceneMetadata;
+
+/// 场景片段的切割方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipMode {
+    /// 先尝试直接复制音视频流（速度快，切点对齐到关键帧），失败时重新编码（默认）
+    #[default]
+    Copy,
+    /// 始终重新编码（H.264 + AAC），切点精确到场景边界
+    Reencode,
+}
+
+impl ClipMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipMode::Copy => "copy",
+            ClipMode::Reencode => "reencode",
+        }
+    }
+}
+
+impl FromStr for ClipMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "copy" => Ok(ClipMode::Copy),
+            "reencode" | "re-encode" => Ok(ClipMode::Reencode),
+            other => Err(format!("未知的片段切割方式: {}（可选值: copy, reencode）", other)),
+        }
+    }
+}
+
+/// 场景片段导出配置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipConfig {
+    /// 是否把每个场景导出为单独的 MP4 片段
+    pub enabled: bool,
+    pub mode: ClipMode,
+}
+
+/// 第 `index` 个场景片段的文件名：`clip_0000.mp4`
+pub fn clip_file_name(index: usize) -> String {
+    format!("clip_{:04}.mp4", index)
+}
+
+/// 把每个场景导出为 MP4 片段，文件名写入场景的 `clip_file`
+///
+/// 单个场景导出失败只记录警告，对应场景没有 `clip_file`；返回导出成功的片段数
+pub fn export_clips(config: &ClipConfig, input: &Path, output_dir: &Path, scenes: &mut [SceneMetadata]) -> Result<usize> {
+    let mut exported = 0;
+    for (index, scene) in scenes.iter_mut().enumerate() {
+        let file_name = clip_file_name(index);
+        let clip_path = output_dir.join(&file_name);
+        let copied = config.mode == ClipMode::Copy
+            && cut(input, &clip_path, scene.start_time, scene.duration, false)
+                .inspect_err(|e| tracing::debug!(scene_id = scene.scene_id, error = %e, "🔁 [片段导出] 直接复制失败，重新编码"))
+                .is_ok();
+        let result = if copied {
+            Ok(())
+        } else {
+            cut(input, &clip_path, scene.start_time, scene.duration, true)
+        };
+        match result {
+            Ok(()) => {
+                tracing::debug!(scene_id = scene.scene_id, clip = %file_name, copied, "💾 [片段导出] 已导出场景片段");
+                scene.clip_file = Some(file_name);
+                exported += 1;
+            }
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [片段导出] 导出场景片段失败");
+                std::fs::remove_file(&clip_path).ok();
+            }
+        }
+    }
+    Ok(exported)
+}
+
+/// 用 ffmpeg 命令行切出 `[start, start + duration)` 的片段
+fn cut(input: &Path, output: &Path, start: f64, duration: f64, reencode: bool) -> Result<()> {
+    let ffmpeg = ffmpeg_binary();
+    let mut command = Command::new(&ffmpeg);
+    command
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-ss")
+        .arg(format!("{:.3}", start.max(0.0)))
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg(format!("{:.3}", duration.max(0.001)))
+        .arg("-map")
+        .arg("0:v:0")
+        .arg("-map")
+        .arg("0:a:0?") // 没有音频流时忽略
+        .arg("-sn")
+        .arg("-dn");
+    if reencode {
+        command
+            .arg("-c:v")
+            .arg("libx264")
+            .arg("-preset")
+            .arg("veryfast")
+            .arg("-crf")
+            .arg("20")
+            .arg("-pix_fmt")
+            .arg("yuv420p")
+            .arg("-c:a")
+            .arg("aac")
+            .arg("-b:a")
+            .arg("128k");
+    } else {
+        command
+            .arg("-c")
+            .arg("copy")
+            .arg("-avoid_negative_ts")
+            .arg("make_zero");
+    }
+    let status = command
+        .arg("-movflags")
+        .arg("+faststart")
+        .arg("-y")
+        .arg(output)
+        .status()
+        .with_context(|| format!("执行 ffmpeg 命令失败: {}（请安装 FFmpeg 或设置 FFMPEG_PATH）", ffmpeg.display()))?;
+    if !status.success() {
+        anyhow::bail!("ffmpeg 切割片段失败: {}", status);
+    }
+    // 直接复制时 ffmpeg 可能成功退出但没有写入任何帧
+    if std::fs::metadata(output).map(|m| m.len()).unwrap_or(0) == 0 {
+        anyhow::bail!("片段文件为空");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_mode() {
+        assert_eq!(clip_file_name(12), "clip_0012.mp4");
+        assert_eq!("Re-Encode".parse::<ClipMode>(), Ok(ClipMode::Reencode));
+        assert_eq!(ClipMode::default().as_str(), "copy");
+        assert!("fast".parse::<ClipMode>().is_err());
+    }
+}
@@ -1,7 +1,462 @@
 use anyhow::Result;
+use serde::Deserialize;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::env;
+use std::ffi::OsString;
 use crate::processor::ProcessConfig;
+use crate::preset::Preset;
+use crate::decoder::DecoderBackend;
+use crate::scene_detector::{self, AdaptiveThreshold, DetectionAlgorithm, ThresholdMode};
+use crate::video_processor::HwAccel;
+use crate::telemetry::{self, LogFormat, TelemetryConfig};
+use crate::output_naming::{FileNaming, OutputNaming};
+use crate::archive::ArchiveFormat;
+use crate::watchdog::{self, StageTimeouts};
+use crate::preflight::InputLimits;
+use crate::ocr::OcrConfig;
+use crate::faces::FaceConfig;
+use crate::safety::{self, SafetyConfig};
+use crate::embeddings::EmbeddingConfig;
+use crate::enrichment::EnrichmentConfig;
+use crate::sprite::{self, SpriteConfig};
+use crate::clips::{ClipConfig, ClipMode};
+use crate::preview::{PreviewConfig, PreviewFormat};
+use crate::audio_boundary::AudioBoundaryConfig;
+use crate::audio_extractor::{AudioCodec, AudioConfig};
+use crate::vad::VadConfig;
+use crate::keyframe_image::{KeyframeFormat, KeyframeImageConfig};
+use crate::metadata::ExportFormat;
+use crate::vector_store::VectorStoreConfig;
+use crate::redis_worker::RedisWorkerConfig;
+use crate::jobs::JobsConfig;
+use crate::auth::{self, AuthConfig};
+use crate::dead_letter::RetryConfig;
+use crate::oss_client::{MultipartConfig, DEFAULT_UPLOAD_CONCURRENCY};
+use crate::database::DatabaseConfig;
+use crate::kafka::KafkaConfig;
+use crate::notify::NotifyConfig;
+use crate::webhook::{self, WebhookConfig};
+
+/// 单个配置来源（命令行/请求参数、环境变量或配置文件）中设置的处理参数，未设置的项为 None
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// 速度预设
+    pub preset: Option<Preset>,
+    /// 场景变化检测阈值
+    pub threshold: Option<f64>,
+    /// 最小场景持续时间（秒）
+    pub min_scene_duration: Option<f64>,
+    /// 帧采样率（每秒采样多少帧）
+    pub sample_rate: Option<f64>,
+    /// 场景分析宽度（像素，0 表示原始分辨率）
+    pub analysis_width: Option<u32>,
+    /// 帧差异算法
+    pub detection_algorithm: Option<DetectionAlgorithm>,
+    /// 场景切换阈值模式
+    pub threshold_mode: Option<ThresholdMode>,
+    /// 自适应阈值的标准差倍数 k
+    pub adaptive_k: Option<f64>,
+    /// 自适应阈值统计的最近帧数
+    pub adaptive_window: Option<usize>,
+    /// 帧解码后端
+    pub decoder: Option<DecoderBackend>,
+    /// 硬件解码
+    pub hwaccel: Option<HwAccel>,
+    /// Webhook URL
+    pub webhook_url: Option<String>,
+    /// 失败回调 URL
+    pub webhook_failure_url: Option<String>,
+    /// 进度回调 URL
+    pub webhook_progress_url: Option<String>,
+    /// 进度回调的最短间隔（秒）
+    pub webhook_progress_interval: Option<u64>,
+    /// webhook 最多尝试次数
+    pub webhook_max_attempts: Option<u32>,
+    /// webhook 第一次重试前的等待时间（秒）
+    pub webhook_retry_backoff: Option<u64>,
+    /// 未送达 webhook 的保存目录
+    pub webhook_spool_dir: Option<PathBuf>,
+    /// 未送达 webhook 的 OSS 上传位置（oss://bucket/prefix）
+    pub webhook_spool_oss: Option<String>,
+    /// webhook 请求体的 HMAC-SHA256 签名密钥
+    pub webhook_secret: Option<String>,
+    /// webhook 回调数据模板文件
+    pub webhook_template: Option<PathBuf>,
+    /// webhook 只发送的字段（逗号分隔）
+    pub webhook_fields: Option<String>,
+    /// 下载超时（秒，0 表示不限制）
+    pub download_timeout: Option<u64>,
+    /// 视频帧提取超时（秒，0 表示不限制）
+    pub extract_timeout: Option<u64>,
+    /// 场景检测超时（秒，0 表示不限制）
+    pub detect_timeout: Option<u64>,
+    /// 上传超时（秒，0 表示不限制）
+    pub upload_timeout: Option<u64>,
+    /// 输入文件的最大字节数（0 表示不限制）
+    pub max_input_size_bytes: Option<u64>,
+    /// 视频的最长时长（秒，0 表示不限制）
+    pub max_duration_seconds: Option<f64>,
+    /// 是否识别关键帧中的文字
+    pub ocr: Option<bool>,
+    /// OCR 语言包
+    pub ocr_languages: Option<String>,
+    /// 是否检测关键帧中的人脸
+    pub faces: Option<bool>,
+    /// 人脸检测模型路径
+    pub face_model: Option<PathBuf>,
+    /// 人脸检测最低置信度
+    pub face_min_confidence: Option<f32>,
+    /// 是否对关键帧做内容安全分类
+    pub safety: Option<bool>,
+    /// 内容安全分类模型路径
+    pub safety_model: Option<PathBuf>,
+    /// 模型输出的类别名称（逗号分隔）
+    pub safety_labels: Option<String>,
+    /// 需要拦截的类别（逗号分隔）
+    pub safety_flagged_labels: Option<String>,
+    /// 内容安全拦截阈值
+    pub safety_threshold: Option<f32>,
+    /// 视频被标记时是否跳过上传
+    pub safety_block_upload: Option<bool>,
+    /// 是否计算关键帧图像向量
+    pub embeddings: Option<bool>,
+    /// 图像向量模型路径
+    pub embedding_model: Option<PathBuf>,
+    /// 图像向量模型输入边长
+    pub embedding_input_size: Option<u32>,
+    /// 关键帧标注服务地址
+    pub enrichment_endpoint: Option<String>,
+    /// 关键帧标注请求头（key1=value1,key2=value2）
+    pub enrichment_headers: Option<String>,
+    /// 每个标注请求包含的关键帧数
+    pub enrichment_batch_size: Option<usize>,
+    /// 标注请求超时（秒）
+    pub enrichment_timeout: Option<u64>,
+    /// 是否生成关键帧缩略图拼图
+    pub sprite: Option<bool>,
+    /// 拼图每行的缩略图数
+    pub sprite_columns: Option<u32>,
+    /// 拼图缩略图尺寸（宽x高）
+    pub sprite_tile_size: Option<String>,
+    /// 是否在缩略图上绘制时间
+    pub sprite_timestamps: Option<bool>,
+    /// 是否导出场景片段
+    pub clips: Option<bool>,
+    /// 场景片段切割方式
+    pub clip_mode: Option<ClipMode>,
+    /// 是否生成场景预览动图
+    pub preview: Option<bool>,
+    /// 预览动图格式
+    pub preview_format: Option<PreviewFormat>,
+    /// 预览时长（秒）
+    pub preview_duration: Option<f64>,
+    /// 预览帧率
+    pub preview_fps: Option<u32>,
+    /// 预览宽度（像素）
+    pub preview_width: Option<u32>,
+    /// 是否分析音频波形和响度
+    pub waveform: Option<bool>,
+    /// 关键帧去重的相似度（0 表示不去重）
+    pub dedupe_keyframes: Option<f64>,
+    /// 额外导出的场景列表格式
+    pub export_formats: Option<Vec<ExportFormat>>,
+    /// 场景颜色统计的主色调数量（0 表示不统计）
+    pub color_palette: Option<usize>,
+    /// 是否按音频校准场景边界
+    pub audio_boundary: Option<bool>,
+    /// 音频边界的查找窗口（秒）
+    pub audio_boundary_window: Option<f64>,
+    /// 视为静音的电平（dBFS）
+    pub audio_boundary_silence_db: Option<f64>,
+    /// 是否识别渐变转场
+    pub detect_transitions: Option<bool>,
+    /// 目标场景数范围
+    pub target_scene_range: Option<RangeInclusive<usize>>,
+    /// 是否估计场景景别
+    pub shot_type: Option<bool>,
+    /// 是否生成视频指纹
+    pub fingerprint: Option<bool>,
+    /// 是否生成结果查看页 index.html
+    pub viewer: Option<bool>,
+    /// 音频输出编码
+    pub audio_codec: Option<AudioCodec>,
+    /// 音频码率（kbps，0 表示使用编码的默认码率）
+    pub audio_bitrate: Option<u32>,
+    /// 音频采样率（Hz，0 表示保持原始采样率）
+    pub audio_sample_rate: Option<u32>,
+    /// 音频声道数（0 表示保持原始声道）
+    pub audio_channels: Option<u32>,
+    /// 是否检测语音段和静音段
+    pub vad: Option<bool>,
+    /// 视为有声音的最低电平（dBFS）
+    pub vad_threshold_db: Option<f64>,
+    /// 并入语音段的最长停顿（秒）
+    pub vad_min_silence: Option<f64>,
+    /// 是否写入检查点，超时重试时从上次完成的阶段继续
+    pub checkpoint: Option<bool>,
+    /// 是否按源文件哈希和配置指纹分目录存放输出
+    pub content_addressed: Option<bool>,
+    /// 输出目录打包格式
+    pub archive: Option<ArchiveFormat>,
+    /// 关键帧图片格式
+    pub keyframe_format: Option<KeyframeFormat>,
+    /// 关键帧有损编码质量（1-100）
+    pub keyframe_quality: Option<u8>,
+    /// 关键帧最大宽度（像素）
+    pub keyframe_max_width: Option<u32>,
+    /// 关键帧最大高度（像素）
+    pub keyframe_max_height: Option<u32>,
+    /// 关键帧文件名模板
+    pub naming_keyframe: Option<String>,
+    /// 音频文件名模板
+    pub naming_audio: Option<String>,
+    /// 元数据文件名模板
+    pub naming_metadata: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// 逐项合并，`self` 中已设置的项优先
+    pub fn or(self, other: ConfigOverrides) -> ConfigOverrides {
+        ConfigOverrides {
+            preset: self.preset.or(other.preset),
+            threshold: self.threshold.or(other.threshold),
+            min_scene_duration: self.min_scene_duration.or(other.min_scene_duration),
+            sample_rate: self.sample_rate.or(other.sample_rate),
+            analysis_width: self.analysis_width.or(other.analysis_width),
+            detection_algorithm: self.detection_algorithm.or(other.detection_algorithm),
+            threshold_mode: self.threshold_mode.or(other.threshold_mode),
+            adaptive_k: self.adaptive_k.or(other.adaptive_k),
+            adaptive_window: self.adaptive_window.or(other.adaptive_window),
+            decoder: self.decoder.or(other.decoder),
+            hwaccel: self.hwaccel.or(other.hwaccel),
+            webhook_url: self.webhook_url.or(other.webhook_url),
+            webhook_failure_url: self.webhook_failure_url.or(other.webhook_failure_url),
+            webhook_progress_url: self.webhook_progress_url.or(other.webhook_progress_url),
+            webhook_progress_interval: self.webhook_progress_interval.or(other.webhook_progress_interval),
+            webhook_max_attempts: self.webhook_max_attempts.or(other.webhook_max_attempts),
+            webhook_retry_backoff: self.webhook_retry_backoff.or(other.webhook_retry_backoff),
+            webhook_spool_dir: self.webhook_spool_dir.or(other.webhook_spool_dir),
+            webhook_spool_oss: self.webhook_spool_oss.or(other.webhook_spool_oss),
+            webhook_secret: self.webhook_secret.or(other.webhook_secret),
+            webhook_template: self.webhook_template.or(other.webhook_template),
+            webhook_fields: self.webhook_fields.or(other.webhook_fields),
+            download_timeout: self.download_timeout.or(other.download_timeout),
+            extract_timeout: self.extract_timeout.or(other.extract_timeout),
+            detect_timeout: self.detect_timeout.or(other.detect_timeout),
+            upload_timeout: self.upload_timeout.or(other.upload_timeout),
+            max_input_size_bytes: self.max_input_size_bytes.or(other.max_input_size_bytes),
+            max_duration_seconds: self.max_duration_seconds.or(other.max_duration_seconds),
+            ocr: self.ocr.or(other.ocr),
+            ocr_languages: self.ocr_languages.or(other.ocr_languages),
+            faces: self.faces.or(other.faces),
+            face_model: self.face_model.or(other.face_model),
+            face_min_confidence: self.face_min_confidence.or(other.face_min_confidence),
+            safety: self.safety.or(other.safety),
+            safety_model: self.safety_model.or(other.safety_model),
+            safety_labels: self.safety_labels.or(other.safety_labels),
+            safety_flagged_labels: self.safety_flagged_labels.or(other.safety_flagged_labels),
+            safety_threshold: self.safety_threshold.or(other.safety_threshold),
+            safety_block_upload: self.safety_block_upload.or(other.safety_block_upload),
+            embeddings: self.embeddings.or(other.embeddings),
+            embedding_model: self.embedding_model.or(other.embedding_model),
+            embedding_input_size: self.embedding_input_size.or(other.embedding_input_size),
+            enrichment_endpoint: self.enrichment_endpoint.or(other.enrichment_endpoint),
+            enrichment_headers: self.enrichment_headers.or(other.enrichment_headers),
+            enrichment_batch_size: self.enrichment_batch_size.or(other.enrichment_batch_size),
+            enrichment_timeout: self.enrichment_timeout.or(other.enrichment_timeout),
+            sprite: self.sprite.or(other.sprite),
+            sprite_columns: self.sprite_columns.or(other.sprite_columns),
+            sprite_tile_size: self.sprite_tile_size.or(other.sprite_tile_size),
+            sprite_timestamps: self.sprite_timestamps.or(other.sprite_timestamps),
+            clips: self.clips.or(other.clips),
+            clip_mode: self.clip_mode.or(other.clip_mode),
+            preview: self.preview.or(other.preview),
+            preview_format: self.preview_format.or(other.preview_format),
+            preview_duration: self.preview_duration.or(other.preview_duration),
+            preview_fps: self.preview_fps.or(other.preview_fps),
+            preview_width: self.preview_width.or(other.preview_width),
+            waveform: self.waveform.or(other.waveform),
+            dedupe_keyframes: self.dedupe_keyframes.or(other.dedupe_keyframes),
+            export_formats: self.export_formats.or(other.export_formats),
+            color_palette: self.color_palette.or(other.color_palette),
+            audio_boundary: self.audio_boundary.or(other.audio_boundary),
+            audio_boundary_window: self.audio_boundary_window.or(other.audio_boundary_window),
+            audio_boundary_silence_db: self.audio_boundary_silence_db.or(other.audio_boundary_silence_db),
+            detect_transitions: self.detect_transitions.or(other.detect_transitions),
+            target_scene_range: self.target_scene_range.or(other.target_scene_range),
+            shot_type: self.shot_type.or(other.shot_type),
+            fingerprint: self.fingerprint.or(other.fingerprint),
+            viewer: self.viewer.or(other.viewer),
+            audio_codec: self.audio_codec.or(other.audio_codec),
+            audio_bitrate: self.audio_bitrate.or(other.audio_bitrate),
+            audio_sample_rate: self.audio_sample_rate.or(other.audio_sample_rate),
+            audio_channels: self.audio_channels.or(other.audio_channels),
+            vad: self.vad.or(other.vad),
+            vad_threshold_db: self.vad_threshold_db.or(other.vad_threshold_db),
+            vad_min_silence: self.vad_min_silence.or(other.vad_min_silence),
+            checkpoint: self.checkpoint.or(other.checkpoint),
+            content_addressed: self.content_addressed.or(other.content_addressed),
+            archive: self.archive.or(other.archive),
+            keyframe_format: self.keyframe_format.or(other.keyframe_format),
+            keyframe_quality: self.keyframe_quality.or(other.keyframe_quality),
+            keyframe_max_width: self.keyframe_max_width.or(other.keyframe_max_width),
+            keyframe_max_height: self.keyframe_max_height.or(other.keyframe_max_height),
+            naming_keyframe: self.naming_keyframe.or(other.naming_keyframe),
+            naming_audio: self.naming_audio.or(other.naming_audio),
+            naming_metadata: self.naming_metadata.or(other.naming_metadata),
+        }
+    }
+
+    /// 以预设（未设置时为默认配置）为基础，应用已设置的各项参数
+    pub fn into_config(self) -> ProcessConfig {
+        let base = self.preset
+            .map(|p| p.config())
+            .unwrap_or_default();
+
+        ProcessConfig {
+            threshold: self.threshold.unwrap_or(base.threshold),
+            min_scene_duration: self.min_scene_duration.unwrap_or(base.min_scene_duration),
+            sample_rate: self.sample_rate.unwrap_or(base.sample_rate),
+            analysis_width: match self.analysis_width {
+                Some(width) => Some(width).filter(|width| *width > 0),
+                None => base.analysis_width,
+            },
+            detection_algorithm: self.detection_algorithm.unwrap_or(base.detection_algorithm),
+            threshold_mode: self.threshold_mode.unwrap_or(base.threshold_mode),
+            adaptive_threshold: AdaptiveThreshold {
+                k: self.adaptive_k.unwrap_or(base.adaptive_threshold.k),
+                window: self.adaptive_window.unwrap_or(base.adaptive_threshold.window),
+            },
+            decoder: self.decoder.unwrap_or(base.decoder),
+            hwaccel: self.hwaccel.unwrap_or(base.hwaccel),
+            webhook_url: self.webhook_url.or(base.webhook_url),
+            webhook_failure_url: self.webhook_failure_url.or(base.webhook_failure_url),
+            webhook_progress_url: self.webhook_progress_url.or(base.webhook_progress_url),
+            webhook: WebhookConfig {
+                max_attempts: self.webhook_max_attempts.unwrap_or(base.webhook.max_attempts),
+                initial_backoff: self.webhook_retry_backoff
+                    .map_or(base.webhook.initial_backoff, std::time::Duration::from_secs),
+                spool_dir: self.webhook_spool_dir.or(base.webhook.spool_dir.clone()),
+                spool_oss: self.webhook_spool_oss.or(base.webhook.spool_oss.clone()),
+                secret: self.webhook_secret.or(base.webhook.secret.clone()),
+                template: self.webhook_template.or(base.webhook.template.clone()),
+                fields: self.webhook_fields
+                    .map_or(base.webhook.fields.clone(), |v| webhook::parse_fields(&v)),
+                progress_interval: self.webhook_progress_interval
+                    .map_or(base.webhook.progress_interval, std::time::Duration::from_secs),
+            },
+            timeouts: StageTimeouts {
+                download: self.download_timeout.map_or(base.timeouts.download, watchdog::timeout_from_secs),
+                extract: self.extract_timeout.map_or(base.timeouts.extract, watchdog::timeout_from_secs),
+                detect: self.detect_timeout.map_or(base.timeouts.detect, watchdog::timeout_from_secs),
+                upload: self.upload_timeout.map_or(base.timeouts.upload, watchdog::timeout_from_secs),
+            },
+            limits: InputLimits {
+                max_input_size_bytes: self.max_input_size_bytes
+                    .map_or(base.limits.max_input_size_bytes, |bytes| (bytes > 0).then_some(bytes)),
+                max_duration_seconds: self.max_duration_seconds
+                    .map_or(base.limits.max_duration_seconds, |secs| (secs > 0.0).then_some(secs)),
+            },
+            ocr: OcrConfig {
+                enabled: self.ocr.unwrap_or(base.ocr.enabled),
+                languages: self.ocr_languages.unwrap_or(base.ocr.languages.clone()),
+            },
+            faces: FaceConfig {
+                enabled: self.faces.unwrap_or(base.faces.enabled),
+                model_path: self.face_model.or(base.faces.model_path.clone()),
+                min_confidence: self.face_min_confidence.unwrap_or(base.faces.min_confidence),
+            },
+            safety: SafetyConfig {
+                enabled: self.safety.unwrap_or(base.safety.enabled),
+                model_path: self.safety_model.or(base.safety.model_path.clone()),
+                labels: self.safety_labels
+                    .map_or(base.safety.labels.clone(), |v| safety::parse_labels(&v)),
+                flagged_labels: self.safety_flagged_labels
+                    .map_or(base.safety.flagged_labels.clone(), |v| safety::parse_labels(&v)),
+                threshold: self.safety_threshold.unwrap_or(base.safety.threshold),
+                block_upload: self.safety_block_upload.unwrap_or(base.safety.block_upload),
+            },
+            embeddings: EmbeddingConfig {
+                enabled: self.embeddings.unwrap_or(base.embeddings.enabled),
+                model_path: self.embedding_model.or(base.embeddings.model_path.clone()),
+                input_size: self.embedding_input_size.unwrap_or(base.embeddings.input_size),
+            },
+            enrichment: EnrichmentConfig {
+                endpoint: self.enrichment_endpoint.or(base.enrichment.endpoint.clone()),
+                headers: self.enrichment_headers
+                    .map_or(base.enrichment.headers.clone(), |v| telemetry::parse_headers(&v)),
+                batch_size: self.enrichment_batch_size.unwrap_or(base.enrichment.batch_size),
+                timeout: self.enrichment_timeout
+                    .map_or(base.enrichment.timeout, std::time::Duration::from_secs),
+            },
+            sprite: {
+                let (tile_width, tile_height) = self.sprite_tile_size
+                    .as_deref()
+                    .and_then(sprite::parse_tile_size)
+                    .unwrap_or((base.sprite.tile_width, base.sprite.tile_height));
+                SpriteConfig {
+                    enabled: self.sprite.unwrap_or(base.sprite.enabled),
+                    columns: self.sprite_columns.unwrap_or(base.sprite.columns),
+                    tile_width,
+                    tile_height,
+                    timestamps: self.sprite_timestamps.unwrap_or(base.sprite.timestamps),
+                }
+            },
+            clips: ClipConfig {
+                enabled: self.clips.unwrap_or(base.clips.enabled),
+                mode: self.clip_mode.unwrap_or(base.clips.mode),
+            },
+            preview: PreviewConfig {
+                enabled: self.preview.unwrap_or(base.preview.enabled),
+                format: self.preview_format.unwrap_or(base.preview.format),
+                duration: self.preview_duration.unwrap_or(base.preview.duration),
+                fps: self.preview_fps.unwrap_or(base.preview.fps),
+                width: self.preview_width.unwrap_or(base.preview.width),
+            },
+            waveform: self.waveform.unwrap_or(base.waveform),
+            dedupe_keyframes: self.dedupe_keyframes.or(base.dedupe_keyframes).filter(|similarity| *similarity > 0.0),
+            export_formats: self.export_formats.unwrap_or(base.export_formats),
+            color_palette: self.color_palette.or(base.color_palette).filter(|palette| *palette > 0),
+            audio_boundary: AudioBoundaryConfig {
+                enabled: self.audio_boundary.unwrap_or(base.audio_boundary.enabled),
+                window: self.audio_boundary_window.unwrap_or(base.audio_boundary.window),
+                silence_dbfs: self.audio_boundary_silence_db.unwrap_or(base.audio_boundary.silence_dbfs),
+            },
+            detect_transitions: self.detect_transitions.unwrap_or(base.detect_transitions),
+            target_scene_range: self.target_scene_range.or(base.target_scene_range),
+            shot_type: self.shot_type.unwrap_or(base.shot_type),
+            fingerprint: self.fingerprint.unwrap_or(base.fingerprint),
+            viewer: self.viewer.unwrap_or(base.viewer),
+            audio: AudioConfig {
+                codec: self.audio_codec.unwrap_or(base.audio.codec),
+                bitrate_kbps: self.audio_bitrate.or(base.audio.bitrate_kbps).filter(|bitrate| *bitrate > 0),
+                sample_rate: self.audio_sample_rate.or(base.audio.sample_rate).filter(|rate| *rate > 0),
+                channels: self.audio_channels.or(base.audio.channels).filter(|channels| *channels > 0),
+            },
+            vad: VadConfig {
+                enabled: self.vad.unwrap_or(base.vad.enabled),
+                threshold_dbfs: self.vad_threshold_db.unwrap_or(base.vad.threshold_dbfs),
+                min_silence: self.vad_min_silence.unwrap_or(base.vad.min_silence),
+            },
+            checkpoint: self.checkpoint.unwrap_or(base.checkpoint),
+            content_addressed: self.content_addressed.unwrap_or(base.content_addressed),
+            archive: self.archive.or(base.archive),
+            keyframe_image: KeyframeImageConfig {
+                format: self.keyframe_format.unwrap_or(base.keyframe_image.format),
+                quality: self.keyframe_quality.unwrap_or(base.keyframe_image.quality),
+                max_width: self.keyframe_max_width.or(base.keyframe_image.max_width),
+                max_height: self.keyframe_max_height.or(base.keyframe_image.max_height),
+            },
+            naming: FileNaming {
+                keyframe: self.naming_keyframe.unwrap_or(base.naming.keyframe.clone()),
+                audio: self.naming_audio.unwrap_or(base.naming.audio.clone()),
+                metadata: self.naming_metadata.unwrap_or(base.naming.metadata.clone()),
+            },
+            ..base
+        }
+    }
+}
 
 /// 扩展配置（包含输出路径、OSS配置等）
 #[derive(Debug, Clone)]
@@ -18,8 +473,88 @@ pub struct ExtendedConfig {
     pub destination_region: Option<String>,
     /// 目标 OSS 路径前缀
     pub destination_prefix: Option<String>,
+    /// 目标路径前缀下按视频分目录的命名方式
+    pub output_naming: OutputNaming,
     /// 日志级别（trace, debug, info, warn, error）
     pub log_level: String,
+    /// 日志格式（text, json）
+    pub log_format: LogFormat,
+    /// 结果缓存目录（可选，设置后按 ETag + 配置指纹缓存处理结果）
+    pub cache_dir: Option<PathBuf>,
+    /// 是否在目标 bucket 中缓存处理结果（未设置 cache_dir 时生效）
+    pub cache_in_bucket: bool,
+    /// 是否分段下载 OSS 源文件并边下载边处理
+    pub segmented_download: bool,
+    /// 分段下载的分段大小（MB）
+    pub download_segment_size_mb: u64,
+    /// 下载请求临时失败（超时、连接中断、5xx）时的最多重试次数
+    pub download_max_retries: u32,
+    /// 直接处理 HTTP / HTTPS URL 时允许下载的最大文件大小（MB）
+    pub url_max_size_mb: u64,
+    /// 处理结果中输出文件预签名下载地址的有效期（秒），0 表示不生成
+    pub presign_expires_secs: u64,
+    /// 上传完成后删除视频输出目录下本次未生成的旧文件（output_naming 为 flat 时不生效）
+    pub clean_stale_outputs: bool,
+    /// 目标位置已有相同 ETag 的处理结果时仍然重新处理（默认跳过，用于 OSS 重复投递事件）
+    pub force_reprocess: bool,
+    /// 同时上传的处理结果文件数
+    pub upload_concurrency: usize,
+    /// 大文件分片上传配置
+    pub multipart: MultipartConfig,
+    /// OpenTelemetry 导出配置
+    pub telemetry: TelemetryConfig,
+    /// 场景向量导出到向量数据库
+    pub vector_store: VectorStoreConfig,
+    /// Redis 队列 worker 配置（serve --source redis）
+    pub redis: RedisWorkerConfig,
+    /// PostgreSQL 持久化配置
+    pub database: DatabaseConfig,
+    /// Kafka 结果发布配置
+    pub kafka: KafkaConfig,
+    /// 钉钉 / Slack / 飞书群机器人通知
+    pub notify: NotifyConfig,
+    /// 进程内任务队列（`/invoke` 异步处理）
+    pub jobs: JobsConfig,
+    /// HTTP 接口认证
+    pub auth: AuthConfig,
+    /// OSS 事件处理失败的重试和死信
+    pub retry: RetryConfig,
+}
+
+impl Default for ExtendedConfig {
+    fn default() -> Self {
+        Self {
+            process: ProcessConfig::default(),
+            debug_mode: false,
+            output_path: None,
+            destination_bucket: None,
+            destination_region: None,
+            destination_prefix: None,
+            output_naming: OutputNaming::default(),
+            log_level: "info".to_string(),
+            log_format: LogFormat::Text,
+            cache_dir: None,
+            cache_in_bucket: false,
+            segmented_download: true,
+            download_segment_size_mb: 16,
+            download_max_retries: 3,
+            url_max_size_mb: 2048,
+            presign_expires_secs: 0,
+            clean_stale_outputs: false,
+            force_reprocess: false,
+            upload_concurrency: DEFAULT_UPLOAD_CONCURRENCY,
+            multipart: MultipartConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            vector_store: VectorStoreConfig::default(),
+            redis: RedisWorkerConfig::default(),
+            database: DatabaseConfig::default(),
+            kafka: KafkaConfig::default(),
+            notify: NotifyConfig::default(),
+            jobs: JobsConfig::default(),
+            auth: AuthConfig::default(),
+            retry: RetryConfig::default(),
+        }
+    }
 }
 
 /// 配置加载器
@@ -33,6 +568,26 @@ impl ConfigLoader {
         min_scene_duration: Option<f64>,
         sample_rate: Option<f64>,
         webhook_url: Option<String>,
+    ) -> Result<ProcessConfig> {
+        Self::load_config_with_overrides(
+            config_file,
+            ConfigOverrides {
+                threshold,
+                min_scene_duration,
+                sample_rate,
+                webhook_url,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// 从多个源加载配置，`overrides` 为命令行/请求参数
+    ///
+    /// 优先级：命令行参数 > 环境变量 > 配置文件 > 预设 > 默认值。
+    /// 预设本身也按同样的优先级选取，预设决定的各项参数仍可被单独覆盖
+    pub fn load_config_with_overrides(
+        config_file: Option<&Path>,
+        overrides: ConfigOverrides,
     ) -> Result<ProcessConfig> {
         // 1. 先加载配置文件（如果存在）
         let file_config = if let Some(config_path) = config_file {
@@ -43,58 +598,296 @@ impl ConfigLoader {
         };
 
         // 2. 加载环境变量
-        let (env_threshold, env_min_scene_duration, env_sample_rate, env_webhook_url) = Self::load_from_env();
-
-        // 3. 合并配置（优先级：命令行 > 环境变量 > 配置文件 > 默认值）
-        let config = ProcessConfig {
-            threshold: threshold
-                .or(env_threshold)
-                .or(file_config.as_ref().map(|c| c.threshold))
-                .unwrap_or(0.35),
-            min_scene_duration: min_scene_duration
-                .or(env_min_scene_duration)
-                .or(file_config.as_ref().map(|c| c.min_scene_duration))
-                .unwrap_or(0.8),
-            sample_rate: sample_rate
-                .or(env_sample_rate)
-                .or(file_config.as_ref().map(|c| c.sample_rate))
-                .unwrap_or(0.5),
-            webhook_url: webhook_url
-                .or(env_webhook_url)
-                .or(file_config.as_ref().and_then(|c| c.webhook_url.clone())),
-        };
+        let env_config = Self::load_from_env();
+
+        // 3. 合并配置（优先级：命令行 > 环境变量 > 配置文件 > 预设 > 默认值）
+        let merged = overrides
+            .or(env_config)
+            .or(file_config.unwrap_or_default());
 
-        Ok(config)
+        Ok(merged.into_config())
     }
 
-    /// 从环境变量加载配置（返回Option值，表示是否从环境变量中读取到）
-    fn load_from_env() -> (Option<f64>, Option<f64>, Option<f64>, Option<String>) {
-        (
-            env::var("VIDEO_PARSE_THRESHOLD")
+    /// 从环境变量加载配置（未设置的项为 None）
+    fn load_from_env() -> ConfigOverrides {
+        ConfigOverrides {
+            preset: env::var("VIDEO_PARSE_PRESET")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            threshold: env::var("VIDEO_PARSE_THRESHOLD")
                 .ok()
                 .and_then(|v| v.parse().ok()),
-            env::var("VIDEO_PARSE_MIN_SCENE_DURATION")
+            min_scene_duration: env::var("VIDEO_PARSE_MIN_SCENE_DURATION")
                 .ok()
                 .and_then(|v| v.parse().ok()),
-            env::var("VIDEO_PARSE_SAMPLE_RATE")
+            sample_rate: env::var("VIDEO_PARSE_SAMPLE_RATE")
                 .ok()
                 .and_then(|v| v.parse().ok()),
-            env::var("VIDEO_PARSE_WEBHOOK_URL")
+            analysis_width: env::var("VIDEO_PARSE_ANALYSIS_WIDTH")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            detection_algorithm: env::var("VIDEO_PARSE_DETECTION_ALGORITHM")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            threshold_mode: env::var("VIDEO_PARSE_THRESHOLD_MODE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            adaptive_k: env::var("VIDEO_PARSE_ADAPTIVE_K")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            adaptive_window: env::var("VIDEO_PARSE_ADAPTIVE_WINDOW")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            decoder: env::var("VIDEO_PARSE_DECODER")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            hwaccel: env::var("VIDEO_PARSE_HWACCEL")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            webhook_url: env::var("VIDEO_PARSE_WEBHOOK_URL")
                 .ok(),
-        )
+            webhook_failure_url: env::var("VIDEO_PARSE_WEBHOOK_FAILURE_URL")
+                .ok(),
+            webhook_progress_url: env::var("VIDEO_PARSE_WEBHOOK_PROGRESS_URL")
+                .ok(),
+            webhook_progress_interval: env::var("VIDEO_PARSE_WEBHOOK_PROGRESS_INTERVAL")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            webhook_max_attempts: env::var("VIDEO_PARSE_WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            webhook_retry_backoff: env::var("VIDEO_PARSE_WEBHOOK_RETRY_BACKOFF")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            webhook_spool_dir: env::var("VIDEO_PARSE_WEBHOOK_SPOOL_DIR")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            webhook_spool_oss: env::var("VIDEO_PARSE_WEBHOOK_SPOOL_OSS")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            webhook_secret: env::var("VIDEO_PARSE_WEBHOOK_SECRET")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            webhook_template: env::var("VIDEO_PARSE_WEBHOOK_TEMPLATE")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            webhook_fields: env::var("VIDEO_PARSE_WEBHOOK_FIELDS")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            download_timeout: env::var("VIDEO_PARSE_DOWNLOAD_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            extract_timeout: env::var("VIDEO_PARSE_EXTRACT_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            detect_timeout: env::var("VIDEO_PARSE_DETECT_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            upload_timeout: env::var("VIDEO_PARSE_UPLOAD_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            max_input_size_bytes: env::var("VIDEO_PARSE_MAX_INPUT_SIZE_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            max_duration_seconds: env::var("VIDEO_PARSE_MAX_DURATION_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            ocr: env::var("VIDEO_PARSE_OCR")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            ocr_languages: env::var("VIDEO_PARSE_OCR_LANGUAGES")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            faces: env::var("VIDEO_PARSE_FACES")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            face_model: env::var("VIDEO_PARSE_FACE_MODEL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            face_min_confidence: env::var("VIDEO_PARSE_FACE_MIN_CONFIDENCE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            safety: env::var("VIDEO_PARSE_SAFETY")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            safety_model: env::var("VIDEO_PARSE_SAFETY_MODEL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            safety_labels: env::var("VIDEO_PARSE_SAFETY_LABELS")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            safety_flagged_labels: env::var("VIDEO_PARSE_SAFETY_FLAGGED_LABELS")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            safety_threshold: env::var("VIDEO_PARSE_SAFETY_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            safety_block_upload: env::var("VIDEO_PARSE_SAFETY_BLOCK_UPLOAD")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            embeddings: env::var("VIDEO_PARSE_EMBEDDINGS")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            embedding_model: env::var("VIDEO_PARSE_EMBEDDING_MODEL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            embedding_input_size: env::var("VIDEO_PARSE_EMBEDDING_INPUT_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            enrichment_endpoint: env::var("VIDEO_PARSE_ENRICHMENT_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            enrichment_headers: env::var("VIDEO_PARSE_ENRICHMENT_HEADERS")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            enrichment_batch_size: env::var("VIDEO_PARSE_ENRICHMENT_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            enrichment_timeout: env::var("VIDEO_PARSE_ENRICHMENT_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            sprite: env::var("VIDEO_PARSE_SPRITE")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            sprite_columns: env::var("VIDEO_PARSE_SPRITE_COLUMNS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            sprite_tile_size: env::var("VIDEO_PARSE_SPRITE_TILE_SIZE")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            sprite_timestamps: env::var("VIDEO_PARSE_SPRITE_TIMESTAMPS")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            clips: env::var("VIDEO_PARSE_CLIPS")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            clip_mode: env::var("VIDEO_PARSE_CLIP_MODE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            preview: env::var("VIDEO_PARSE_PREVIEW")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            preview_format: env::var("VIDEO_PARSE_PREVIEW_FORMAT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            preview_duration: env::var("VIDEO_PARSE_PREVIEW_DURATION")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            preview_fps: env::var("VIDEO_PARSE_PREVIEW_FPS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            preview_width: env::var("VIDEO_PARSE_PREVIEW_WIDTH")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            waveform: env::var("VIDEO_PARSE_WAVEFORM")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            dedupe_keyframes: env::var("VIDEO_PARSE_DEDUPE_KEYFRAMES")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            export_formats: env::var("VIDEO_PARSE_EXPORT_FORMATS")
+                .ok()
+                .and_then(|v| ExportFormat::parse_list(&v).ok()),
+            color_palette: env::var("VIDEO_PARSE_COLOR_PALETTE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            audio_boundary: env::var("VIDEO_PARSE_AUDIO_BOUNDARY")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            audio_boundary_window: env::var("VIDEO_PARSE_AUDIO_BOUNDARY_WINDOW")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            audio_boundary_silence_db: env::var("VIDEO_PARSE_AUDIO_BOUNDARY_SILENCE_DB")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            detect_transitions: env::var("VIDEO_PARSE_DETECT_TRANSITIONS")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            target_scene_range: env::var("VIDEO_PARSE_TARGET_SCENE_RANGE")
+                .ok()
+                .and_then(|v| scene_detector::parse_scene_range(&v)),
+            shot_type: env::var("VIDEO_PARSE_SHOT_TYPE")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            fingerprint: env::var("VIDEO_PARSE_FINGERPRINT")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            viewer: env::var("VIDEO_PARSE_VIEWER")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            audio_codec: env::var("VIDEO_PARSE_AUDIO_CODEC")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            audio_bitrate: env::var("VIDEO_PARSE_AUDIO_BITRATE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            audio_sample_rate: env::var("VIDEO_PARSE_AUDIO_SAMPLE_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            audio_channels: env::var("VIDEO_PARSE_AUDIO_CHANNELS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            vad: env::var("VIDEO_PARSE_VAD")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            vad_threshold_db: env::var("VIDEO_PARSE_VAD_THRESHOLD_DB")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            vad_min_silence: env::var("VIDEO_PARSE_VAD_MIN_SILENCE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            checkpoint: env::var("VIDEO_PARSE_CHECKPOINT")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            content_addressed: env::var("VIDEO_PARSE_CONTENT_ADDRESSED")
+                .ok()
+                .map(|v| v.to_lowercase() == "true"),
+            archive: env::var("VIDEO_PARSE_ARCHIVE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            keyframe_format: env::var("VIDEO_PARSE_KEYFRAME_FORMAT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            keyframe_quality: env::var("VIDEO_PARSE_KEYFRAME_QUALITY")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            keyframe_max_width: env::var("VIDEO_PARSE_KEYFRAME_MAX_WIDTH")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            keyframe_max_height: env::var("VIDEO_PARSE_KEYFRAME_MAX_HEIGHT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            naming_keyframe: env::var("VIDEO_PARSE_NAMING_KEYFRAME")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            naming_audio: env::var("VIDEO_PARSE_NAMING_AUDIO")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            naming_metadata: env::var("VIDEO_PARSE_NAMING_METADATA")
+                .ok()
+                .filter(|v| !v.is_empty()),
+        }
     }
 
-    /// 从INI配置文件加载配置
-    fn load_from_file(config_path: &Path) -> Result<ProcessConfig> {
+    /// 从配置文件（INI / TOML / YAML）加载配置（未设置的项为 None）
+    fn load_from_file(config_path: &Path) -> Result<ConfigOverrides> {
         if !config_path.exists() {
             return Err(anyhow::anyhow!("配置文件不存在: {}", config_path.display()));
         }
 
-        let mut config_parser = configparser::ini::Ini::new();
-        config_parser.load(config_path)
-            .map_err(|e| anyhow::anyhow!("读取配置文件失败: {}: {}", config_path.display(), e))?;
+        let config_parser = read_config_file(config_path)?;
 
         // 尝试从 [video_parse] 节读取，如果没有则使用 [DEFAULT] 节
+        let preset = config_parser.get("video_parse", "preset")
+            .or_else(|| config_parser.get("DEFAULT", "preset"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
         let threshold = config_parser.get("video_parse", "threshold")
             .or_else(|| config_parser.get("DEFAULT", "threshold"))
             .and_then(|v| v.parse().ok());
@@ -107,47 +900,449 @@ impl ConfigLoader {
             .or_else(|| config_parser.get("DEFAULT", "sample_rate"))
             .and_then(|v| v.parse().ok());
 
+        let analysis_width = config_parser.get("video_parse", "analysis_width")
+            .or_else(|| config_parser.get("DEFAULT", "analysis_width"))
+            .and_then(|v| v.parse().ok());
+
+        let detection_algorithm = config_parser.get("video_parse", "detection_algorithm")
+            .or_else(|| config_parser.get("DEFAULT", "detection_algorithm"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let threshold_mode = config_parser.get("video_parse", "threshold_mode")
+            .or_else(|| config_parser.get("DEFAULT", "threshold_mode"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let adaptive_k = config_parser.get("video_parse", "adaptive_k")
+            .or_else(|| config_parser.get("DEFAULT", "adaptive_k"))
+            .and_then(|v| v.parse().ok());
+
+        let adaptive_window = config_parser.get("video_parse", "adaptive_window")
+            .or_else(|| config_parser.get("DEFAULT", "adaptive_window"))
+            .and_then(|v| v.parse().ok());
+
+        let decoder = config_parser.get("video_parse", "decoder")
+            .or_else(|| config_parser.get("DEFAULT", "decoder"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let hwaccel = config_parser.get("video_parse", "hwaccel")
+            .or_else(|| config_parser.get("DEFAULT", "hwaccel"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
         let webhook_url = config_parser.get("video_parse", "webhook_url")
             .or_else(|| config_parser.get("DEFAULT", "webhook_url"))
             .filter(|v| !v.is_empty());
 
-        Ok(ProcessConfig {
-            threshold: threshold.unwrap_or(0.35),
-            min_scene_duration: min_scene_duration.unwrap_or(0.8),
-            sample_rate: sample_rate.unwrap_or(0.5),
+        let webhook_failure_url = config_parser.get("video_parse", "webhook_failure_url")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_failure_url"))
+            .filter(|v| !v.is_empty());
+
+        let webhook_progress_url = config_parser.get("video_parse", "webhook_progress_url")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_progress_url"))
+            .filter(|v| !v.is_empty());
+
+        let webhook_progress_interval = config_parser.get("video_parse", "webhook_progress_interval")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_progress_interval"))
+            .and_then(|v| v.parse().ok());
+
+        let webhook_max_attempts = config_parser.get("video_parse", "webhook_max_attempts")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_max_attempts"))
+            .and_then(|v| v.parse().ok());
+
+        let webhook_retry_backoff = config_parser.get("video_parse", "webhook_retry_backoff")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_retry_backoff"))
+            .and_then(|v| v.parse().ok());
+
+        let webhook_spool_dir = config_parser.get("video_parse", "webhook_spool_dir")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_spool_dir"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let webhook_spool_oss = config_parser.get("video_parse", "webhook_spool_oss")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_spool_oss"))
+            .filter(|v| !v.is_empty());
+
+        let webhook_secret = config_parser.get("video_parse", "webhook_secret")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_secret"))
+            .filter(|v| !v.is_empty());
+
+        let webhook_template = config_parser.get("video_parse", "webhook_template")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_template"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let webhook_fields = config_parser.get("video_parse", "webhook_fields")
+            .or_else(|| config_parser.get("DEFAULT", "webhook_fields"))
+            .filter(|v| !v.is_empty());
+
+        let ocr = config_parser.get("video_parse", "ocr")
+            .or_else(|| config_parser.get("DEFAULT", "ocr"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let ocr_languages = config_parser.get("video_parse", "ocr_languages")
+            .or_else(|| config_parser.get("DEFAULT", "ocr_languages"))
+            .filter(|v| !v.is_empty());
+
+        let faces = config_parser.get("video_parse", "faces")
+            .or_else(|| config_parser.get("DEFAULT", "faces"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let face_model = config_parser.get("video_parse", "face_model")
+            .or_else(|| config_parser.get("DEFAULT", "face_model"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let face_min_confidence = config_parser.get("video_parse", "face_min_confidence")
+            .or_else(|| config_parser.get("DEFAULT", "face_min_confidence"))
+            .and_then(|v| v.parse().ok());
+
+        let safety = config_parser.get("video_parse", "safety")
+            .or_else(|| config_parser.get("DEFAULT", "safety"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let safety_model = config_parser.get("video_parse", "safety_model")
+            .or_else(|| config_parser.get("DEFAULT", "safety_model"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let safety_labels = config_parser.get("video_parse", "safety_labels")
+            .or_else(|| config_parser.get("DEFAULT", "safety_labels"))
+            .filter(|v| !v.is_empty());
+
+        let safety_flagged_labels = config_parser.get("video_parse", "safety_flagged_labels")
+            .or_else(|| config_parser.get("DEFAULT", "safety_flagged_labels"))
+            .filter(|v| !v.is_empty());
+
+        let safety_threshold = config_parser.get("video_parse", "safety_threshold")
+            .or_else(|| config_parser.get("DEFAULT", "safety_threshold"))
+            .and_then(|v| v.parse().ok());
+
+        let safety_block_upload = config_parser.get("video_parse", "safety_block_upload")
+            .or_else(|| config_parser.get("DEFAULT", "safety_block_upload"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let embeddings = config_parser.get("video_parse", "embeddings")
+            .or_else(|| config_parser.get("DEFAULT", "embeddings"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let embedding_model = config_parser.get("video_parse", "embedding_model")
+            .or_else(|| config_parser.get("DEFAULT", "embedding_model"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let embedding_input_size = config_parser.get("video_parse", "embedding_input_size")
+            .or_else(|| config_parser.get("DEFAULT", "embedding_input_size"))
+            .and_then(|v| v.parse().ok());
+
+        // 关键帧标注从 [enrichment] 节读取
+        let enrichment_endpoint = config_parser.get("enrichment", "endpoint")
+            .or_else(|| config_parser.get("DEFAULT", "enrichment_endpoint"))
+            .filter(|v| !v.is_empty());
+
+        let enrichment_headers = config_parser.get("enrichment", "headers")
+            .or_else(|| config_parser.get("DEFAULT", "enrichment_headers"))
+            .filter(|v| !v.is_empty());
+
+        let enrichment_batch_size = config_parser.get("enrichment", "batch_size")
+            .or_else(|| config_parser.get("DEFAULT", "enrichment_batch_size"))
+            .and_then(|v| v.parse().ok());
+
+        let enrichment_timeout = config_parser.get("enrichment", "timeout")
+            .or_else(|| config_parser.get("DEFAULT", "enrichment_timeout"))
+            .and_then(|v| v.parse().ok());
+
+        // 缩略图拼图从 [sprite] 节读取
+        let sprite = config_parser.get("sprite", "enabled")
+            .or_else(|| config_parser.get("DEFAULT", "sprite"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let sprite_columns = config_parser.get("sprite", "columns")
+            .or_else(|| config_parser.get("DEFAULT", "sprite_columns"))
+            .and_then(|v| v.parse().ok());
+
+        let sprite_tile_size = config_parser.get("sprite", "tile_size")
+            .or_else(|| config_parser.get("DEFAULT", "sprite_tile_size"))
+            .filter(|v| !v.is_empty());
+
+        let sprite_timestamps = config_parser.get("sprite", "timestamps")
+            .or_else(|| config_parser.get("DEFAULT", "sprite_timestamps"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let clips = config_parser.get("video_parse", "clips")
+            .or_else(|| config_parser.get("DEFAULT", "clips"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let clip_mode = config_parser.get("video_parse", "clip_mode")
+            .or_else(|| config_parser.get("DEFAULT", "clip_mode"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let preview = config_parser.get("video_parse", "preview")
+            .or_else(|| config_parser.get("DEFAULT", "preview"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let preview_format = config_parser.get("video_parse", "preview_format")
+            .or_else(|| config_parser.get("DEFAULT", "preview_format"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let preview_duration = config_parser.get("video_parse", "preview_duration")
+            .or_else(|| config_parser.get("DEFAULT", "preview_duration"))
+            .and_then(|v| v.parse().ok());
+
+        let preview_fps = config_parser.get("video_parse", "preview_fps")
+            .or_else(|| config_parser.get("DEFAULT", "preview_fps"))
+            .and_then(|v| v.parse().ok());
+
+        let preview_width = config_parser.get("video_parse", "preview_width")
+            .or_else(|| config_parser.get("DEFAULT", "preview_width"))
+            .and_then(|v| v.parse().ok());
+
+        let waveform = config_parser.get("video_parse", "waveform")
+            .or_else(|| config_parser.get("DEFAULT", "waveform"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let dedupe_keyframes = config_parser.get("video_parse", "dedupe_keyframes")
+            .or_else(|| config_parser.get("DEFAULT", "dedupe_keyframes"))
+            .and_then(|v| v.parse().ok());
+
+        let export_formats = config_parser.get("video_parse", "export_formats")
+            .or_else(|| config_parser.get("DEFAULT", "export_formats"))
+            .and_then(|v| ExportFormat::parse_list(&v).ok());
+
+        let color_palette = config_parser.get("video_parse", "color_palette")
+            .or_else(|| config_parser.get("DEFAULT", "color_palette"))
+            .and_then(|v| v.parse().ok());
+
+        let audio_boundary = config_parser.get("video_parse", "audio_boundary")
+            .or_else(|| config_parser.get("DEFAULT", "audio_boundary"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let audio_boundary_window = config_parser.get("video_parse", "audio_boundary_window")
+            .or_else(|| config_parser.get("DEFAULT", "audio_boundary_window"))
+            .and_then(|v| v.parse().ok());
+
+        let audio_boundary_silence_db = config_parser.get("video_parse", "audio_boundary_silence_db")
+            .or_else(|| config_parser.get("DEFAULT", "audio_boundary_silence_db"))
+            .and_then(|v| v.parse().ok());
+
+        let detect_transitions = config_parser.get("video_parse", "detect_transitions")
+            .or_else(|| config_parser.get("DEFAULT", "detect_transitions"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let target_scene_range = config_parser.get("video_parse", "target_scene_range")
+            .or_else(|| config_parser.get("DEFAULT", "target_scene_range"))
+            .and_then(|v| scene_detector::parse_scene_range(&v));
+
+        let shot_type = config_parser.get("video_parse", "shot_type")
+            .or_else(|| config_parser.get("DEFAULT", "shot_type"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let fingerprint = config_parser.get("video_parse", "fingerprint")
+            .or_else(|| config_parser.get("DEFAULT", "fingerprint"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let viewer = config_parser.get("video_parse", "viewer")
+            .or_else(|| config_parser.get("DEFAULT", "viewer"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let audio_codec = config_parser.get("video_parse", "audio_codec")
+            .or_else(|| config_parser.get("DEFAULT", "audio_codec"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let audio_bitrate = config_parser.get("video_parse", "audio_bitrate")
+            .or_else(|| config_parser.get("DEFAULT", "audio_bitrate"))
+            .and_then(|v| v.parse().ok());
+
+        let audio_sample_rate = config_parser.get("video_parse", "audio_sample_rate")
+            .or_else(|| config_parser.get("DEFAULT", "audio_sample_rate"))
+            .and_then(|v| v.parse().ok());
+
+        let audio_channels = config_parser.get("video_parse", "audio_channels")
+            .or_else(|| config_parser.get("DEFAULT", "audio_channels"))
+            .and_then(|v| v.parse().ok());
+
+        let vad = config_parser.get("video_parse", "vad")
+            .or_else(|| config_parser.get("DEFAULT", "vad"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let vad_threshold_db = config_parser.get("video_parse", "vad_threshold_db")
+            .or_else(|| config_parser.get("DEFAULT", "vad_threshold_db"))
+            .and_then(|v| v.parse().ok());
+
+        let vad_min_silence = config_parser.get("video_parse", "vad_min_silence")
+            .or_else(|| config_parser.get("DEFAULT", "vad_min_silence"))
+            .and_then(|v| v.parse().ok());
+
+        let checkpoint = config_parser.get("video_parse", "checkpoint")
+            .or_else(|| config_parser.get("DEFAULT", "checkpoint"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let content_addressed = config_parser.get("video_parse", "content_addressed")
+            .or_else(|| config_parser.get("DEFAULT", "content_addressed"))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true");
+
+        let archive = config_parser.get("video_parse", "archive")
+            .or_else(|| config_parser.get("DEFAULT", "archive"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let keyframe_format = config_parser.get("video_parse", "keyframe_format")
+            .or_else(|| config_parser.get("DEFAULT", "keyframe_format"))
+            .filter(|v| !v.is_empty())
+            .and_then(|v| v.parse().ok());
+
+        let keyframe_quality = config_parser.get("video_parse", "keyframe_quality")
+            .or_else(|| config_parser.get("DEFAULT", "keyframe_quality"))
+            .and_then(|v| v.parse().ok());
+
+        let keyframe_max_width = config_parser.get("video_parse", "keyframe_max_width")
+            .or_else(|| config_parser.get("DEFAULT", "keyframe_max_width"))
+            .and_then(|v| v.parse().ok());
+
+        let keyframe_max_height = config_parser.get("video_parse", "keyframe_max_height")
+            .or_else(|| config_parser.get("DEFAULT", "keyframe_max_height"))
+            .and_then(|v| v.parse().ok());
+
+        let naming = |key: &str| {
+            config_parser.get("video_parse", key)
+                .or_else(|| config_parser.get("DEFAULT", key))
+                .filter(|v| !v.is_empty())
+        };
+
+        // 阶段超时从 [timeouts] 节读取
+        let timeout = |key: &str| {
+            config_parser.get("timeouts", key)
+                .or_else(|| config_parser.get("DEFAULT", &format!("{}_timeout", key)))
+                .and_then(|v| v.parse().ok())
+        };
+
+        // 输入上限从 [limits] 节读取
+        let max_input_size_bytes = config_parser.get("limits", "max_input_size_bytes")
+            .or_else(|| config_parser.get("DEFAULT", "max_input_size_bytes"))
+            .and_then(|v| v.parse().ok());
+        let max_duration_seconds = config_parser.get("limits", "max_duration_seconds")
+            .or_else(|| config_parser.get("DEFAULT", "max_duration_seconds"))
+            .and_then(|v| v.parse().ok());
+
+        Ok(ConfigOverrides {
+            preset,
+            threshold,
+            min_scene_duration,
+            sample_rate,
+            analysis_width,
+            detection_algorithm,
+            threshold_mode,
+            adaptive_k,
+            adaptive_window,
+            decoder,
+            hwaccel,
             webhook_url,
+            webhook_failure_url,
+            webhook_progress_url,
+            webhook_progress_interval,
+            webhook_max_attempts,
+            webhook_retry_backoff,
+            webhook_spool_dir,
+            webhook_spool_oss,
+            webhook_secret,
+            webhook_template,
+            webhook_fields,
+            download_timeout: timeout("download"),
+            extract_timeout: timeout("extract"),
+            detect_timeout: timeout("detect"),
+            upload_timeout: timeout("upload"),
+            max_input_size_bytes,
+            max_duration_seconds,
+            ocr,
+            ocr_languages,
+            faces,
+            face_model,
+            face_min_confidence,
+            safety,
+            safety_model,
+            safety_labels,
+            safety_flagged_labels,
+            safety_threshold,
+            safety_block_upload,
+            embeddings,
+            embedding_model,
+            embedding_input_size,
+            enrichment_endpoint,
+            enrichment_headers,
+            enrichment_batch_size,
+            enrichment_timeout,
+            sprite,
+            sprite_columns,
+            sprite_tile_size,
+            sprite_timestamps,
+            clips,
+            clip_mode,
+            preview,
+            preview_format,
+            preview_duration,
+            preview_fps,
+            preview_width,
+            waveform,
+            dedupe_keyframes,
+            export_formats,
+            color_palette,
+            audio_boundary,
+            audio_boundary_window,
+            audio_boundary_silence_db,
+            detect_transitions,
+            target_scene_range,
+            shot_type,
+            fingerprint,
+            viewer,
+            audio_codec,
+            audio_bitrate,
+            audio_sample_rate,
+            audio_channels,
+            vad,
+            vad_threshold_db,
+            vad_min_silence,
+            checkpoint,
+            content_addressed,
+            archive,
+            keyframe_format,
+            keyframe_quality,
+            keyframe_max_width,
+            keyframe_max_height,
+            naming_keyframe: naming("naming_keyframe"),
+            naming_audio: naming("naming_audio"),
+            naming_metadata: naming("naming_metadata"),
         })
     }
 
     /// 从默认位置加载配置文件
-    fn load_from_default_locations() -> Result<ProcessConfig> {
-        // 1. 当前目录的 video-parse.ini
-        let current_dir_config = PathBuf::from("video-parse.ini");
-        if current_dir_config.exists() {
-            return Self::load_from_file(&current_dir_config);
-        }
-
-        // 2. 当前目录的 .video-parse.ini
-        let hidden_config = PathBuf::from(".video-parse.ini");
-        if hidden_config.exists() {
-            return Self::load_from_file(&hidden_config);
-        }
-
-        // 3. 用户主目录的 .video-parse.ini
-        if let Some(home) = env::var_os("HOME") {
-            let home_config = PathBuf::from(home).join(".video-parse.ini");
-            if home_config.exists() {
-                return Self::load_from_file(&home_config);
-            }
-        }
-
-        // 4. /etc/video-parse.ini (Linux/macOS)
-        let etc_config = PathBuf::from("/etc/video-parse.ini");
-        if etc_config.exists() {
-            return Self::load_from_file(&etc_config);
+    fn load_from_default_locations() -> Result<ConfigOverrides> {
+        match find_default_config() {
+            Some(path) => Self::load_from_file(&path),
+            None => Err(anyhow::anyhow!("未找到配置文件")),
         }
-
-        Err(anyhow::anyhow!("未找到配置文件"))
     }
 
     /// 加载扩展配置（包含输出路径、OSS配置等）
@@ -163,36 +1358,321 @@ impl ConfigLoader {
             Self::load_extended_from_default_locations().ok()
         };
 
-        // 3. 加载环境变量
-        let debug_mode = env::var("DEBUG")
+        // 3. 加载环境变量
+        let debug_mode = env::var("DEBUG")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or_else(|_| {
+                file_config.as_ref()
+                    .map(|c| c.debug_mode)
+                    .unwrap_or(false)
+            });
+
+        let output_path = env::var("OUTPUT_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| file_config.as_ref().and_then(|c| c.output_path.clone()));
+
+        let destination_bucket = env::var("DESTINATION_BUCKET")
+            .ok()
+            .or_else(|| file_config.as_ref().and_then(|c| c.destination_bucket.clone()));
+
+        let destination_region = env::var("DESTINATION_REGION")
+            .ok()
+            .or_else(|| file_config.as_ref().and_then(|c| c.destination_region.clone()));
+
+        let destination_prefix = env::var("DESTINATION_PREFIX")
+            .ok()
+            .or_else(|| file_config.as_ref().and_then(|c| c.destination_prefix.clone()));
+
+        let output_naming = env::var("OUTPUT_NAMING")
+            .ok()
+            .and_then(|v| v.parse::<OutputNaming>().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.output_naming))
+            .unwrap_or_default();
+
+        let log_level = env::var("LOG_LEVEL")
+            .ok()
+            .or_else(|| file_config.as_ref().map(|c| c.log_level.clone()))
+            .unwrap_or_else(|| "info".to_string());
+
+        let log_format = env::var("LOG_FORMAT")
+            .ok()
+            .and_then(|v| v.parse::<LogFormat>().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.log_format))
+            .unwrap_or_default();
+
+        let cache_dir = env::var("CACHE_DIR")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| file_config.as_ref().and_then(|c| c.cache_dir.clone()));
+
+        let cache_in_bucket = env::var("CACHE_IN_BUCKET")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or_else(|_| {
                 file_config.as_ref()
-                    .map(|c| c.debug_mode)
+                    .map(|c| c.cache_in_bucket)
                     .unwrap_or(false)
             });
 
-        let output_path = env::var("OUTPUT_PATH")
+        let segmented_download = env::var("SEGMENTED_DOWNLOAD")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or_else(|_| {
+                file_config.as_ref()
+                    .map(|c| c.segmented_download)
+                    .unwrap_or(true)
+            });
+
+        let download_segment_size_mb = env::var("DOWNLOAD_SEGMENT_SIZE_MB")
             .ok()
-            .map(PathBuf::from)
-            .or_else(|| file_config.as_ref().and_then(|c| c.output_path.clone()));
+            .and_then(|v| v.parse().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.download_segment_size_mb))
+            .unwrap_or(16);
 
-        let destination_bucket = env::var("DESTINATION_BUCKET")
+        let download_max_retries = env::var("DOWNLOAD_MAX_RETRIES")
             .ok()
-            .or_else(|| file_config.as_ref().and_then(|c| c.destination_bucket.clone()));
+            .and_then(|v| v.parse().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.download_max_retries))
+            .unwrap_or(3);
 
-        let destination_region = env::var("DESTINATION_REGION")
+        let url_max_size_mb = env::var("URL_MAX_SIZE_MB")
             .ok()
-            .or_else(|| file_config.as_ref().and_then(|c| c.destination_region.clone()));
+            .and_then(|v| v.parse().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.url_max_size_mb))
+            .unwrap_or(2048);
 
-        let destination_prefix = env::var("DESTINATION_PREFIX")
+        let presign_expires_secs = env::var("PRESIGN_EXPIRES_SECS")
             .ok()
-            .or_else(|| file_config.as_ref().and_then(|c| c.destination_prefix.clone()));
+            .and_then(|v| v.parse().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.presign_expires_secs))
+            .unwrap_or(0);
 
-        let log_level = env::var("LOG_LEVEL")
+        let clean_stale_outputs = env::var("CLEAN_STALE_OUTPUTS")
             .ok()
-            .or_else(|| file_config.as_ref().map(|c| c.log_level.clone()))
-            .unwrap_or_else(|| "info".to_string());
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true")
+            .or_else(|| file_config.as_ref().map(|c| c.clean_stale_outputs))
+            .unwrap_or(false);
+
+        let force_reprocess = env::var("FORCE_REPROCESS")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true")
+            .or_else(|| file_config.as_ref().map(|c| c.force_reprocess))
+            .unwrap_or(false);
+
+        let upload_concurrency = env::var("UPLOAD_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .or_else(|| file_config.as_ref().map(|c| c.upload_concurrency))
+            .unwrap_or(DEFAULT_UPLOAD_CONCURRENCY);
+
+        let file_multipart = file_config.as_ref()
+            .map(|c| c.multipart.clone())
+            .unwrap_or_default();
+        let multipart = MultipartConfig {
+            threshold_mb: env::var("MULTIPART_THRESHOLD_MB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_multipart.threshold_mb),
+            part_size_mb: env::var("MULTIPART_PART_SIZE_MB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_multipart.part_size_mb),
+            concurrency: env::var("MULTIPART_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_multipart.concurrency),
+            max_retries: env::var("MULTIPART_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_multipart.max_retries),
+        };
+
+        let file_telemetry = file_config.as_ref()
+            .map(|c| c.telemetry.clone())
+            .unwrap_or_default();
+        let telemetry = TelemetryConfig {
+            otlp_endpoint: env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_telemetry.otlp_endpoint),
+            otlp_protocol: env::var("OTEL_EXPORTER_OTLP_PROTOCOL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_telemetry.otlp_protocol),
+            otlp_headers: env::var("OTEL_EXPORTER_OTLP_HEADERS")
+                .ok()
+                .map(|v| telemetry::parse_headers(&v))
+                .unwrap_or(file_telemetry.otlp_headers),
+            service_name: env::var("OTEL_SERVICE_NAME")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or(file_telemetry.service_name),
+            export_metrics: env::var("OTEL_METRICS_EXPORTER")
+                .map(|v| v.to_lowercase() != "none")
+                .unwrap_or(file_telemetry.export_metrics),
+        };
+
+        let file_vector_store = file_config.as_ref()
+            .map(|c| c.vector_store.clone())
+            .unwrap_or_default();
+        let vector_store = VectorStoreConfig {
+            kind: env::var("VECTOR_STORE_KIND")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_vector_store.kind),
+            endpoint: env::var("VECTOR_STORE_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_vector_store.endpoint),
+            collection: env::var("VECTOR_STORE_COLLECTION")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or(file_vector_store.collection),
+            api_key: env::var("VECTOR_STORE_API_KEY")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_vector_store.api_key),
+        };
+
+        let file_redis = file_config.as_ref()
+            .map(|c| c.redis.clone())
+            .unwrap_or_default();
+        let redis = RedisWorkerConfig {
+            url: env::var("REDIS_URL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or(file_redis.url),
+            queue: env::var("REDIS_QUEUE")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or(file_redis.queue),
+            result_key: env::var("REDIS_RESULT_KEY")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or(file_redis.result_key),
+        };
+
+        let file_database = file_config.as_ref()
+            .map(|c| c.database.clone())
+            .unwrap_or_default();
+        let database = DatabaseConfig {
+            url: env::var("DATABASE_URL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_database.url),
+            max_connections: env::var("DATABASE_MAX_CONNECTIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_database.max_connections),
+        };
+
+        let file_kafka = file_config.as_ref()
+            .map(|c| c.kafka.clone())
+            .unwrap_or_default();
+        let kafka = KafkaConfig {
+            brokers: env::var("KAFKA_BROKERS")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_kafka.brokers),
+            topic: env::var("KAFKA_TOPIC")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or(file_kafka.topic),
+            properties: env::var("KAFKA_PROPERTIES")
+                .ok()
+                .map(|v| telemetry::parse_headers(&v))
+                .unwrap_or(file_kafka.properties),
+        };
+
+        let file_notify = file_config.as_ref()
+            .map(|c| c.notify.clone())
+            .unwrap_or_default();
+        let notify = NotifyConfig {
+            dingtalk_webhook: env::var("NOTIFY_DINGTALK_WEBHOOK")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_notify.dingtalk_webhook),
+            slack_webhook: env::var("NOTIFY_SLACK_WEBHOOK")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_notify.slack_webhook),
+            feishu_webhook: env::var("NOTIFY_FEISHU_WEBHOOK")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_notify.feishu_webhook),
+        };
+
+        let file_jobs = file_config.as_ref()
+            .map(|c| c.jobs.clone())
+            .unwrap_or_default();
+        let jobs = JobsConfig {
+            max_concurrent: env::var("JOBS_MAX_CONCURRENT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_jobs.max_concurrent),
+            max_retained: env::var("JOBS_MAX_RETAINED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_jobs.max_retained),
+            max_queued: env::var("JOBS_MAX_QUEUED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_jobs.max_queued),
+            retry_after_secs: env::var("JOBS_RETRY_AFTER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_jobs.retry_after_secs),
+        };
+
+        let file_auth = file_config.as_ref()
+            .map(|c| c.auth.clone())
+            .unwrap_or_default();
+        let auth = AuthConfig {
+            api_keys: env::var("AUTH_API_KEYS")
+                .ok()
+                .map(|v| auth::parse_list(&v))
+                .filter(|keys| !keys.is_empty())
+                .unwrap_or(file_auth.api_keys),
+            signature_secret: env::var("AUTH_SIGNATURE_SECRET")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_auth.signature_secret),
+            public_paths: env::var("AUTH_PUBLIC_PATHS")
+                .ok()
+                .map(|v| auth::parse_list(&v))
+                .unwrap_or(file_auth.public_paths),
+        };
+
+        let file_retry = file_config.as_ref()
+            .map(|c| c.retry.clone())
+            .unwrap_or_default();
+        let retry = RetryConfig {
+            max_attempts: env::var("EVENT_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(file_retry.max_attempts),
+            initial_backoff: env::var("EVENT_RETRY_BACKOFF_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(file_retry.initial_backoff),
+            dead_letter_dir: match env::var("DEAD_LETTER_DIR") {
+                // 设置为空表示不写本地文件
+                Ok(v) if v.is_empty() => None,
+                Ok(v) => Some(PathBuf::from(v)),
+                Err(_) => file_retry.dead_letter_dir,
+            },
+            dead_letter_bucket: env::var("DEAD_LETTER_BUCKET")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_retry.dead_letter_bucket),
+            dead_letter_prefix: env::var("DEAD_LETTER_PREFIX")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or(file_retry.dead_letter_prefix),
+        };
 
         Ok(ExtendedConfig {
             process: process_config,
@@ -201,22 +1681,42 @@ impl ConfigLoader {
             destination_bucket,
             destination_region,
             destination_prefix,
+            output_naming,
             log_level,
+            log_format,
+            cache_dir,
+            cache_in_bucket,
+            segmented_download,
+            download_segment_size_mb,
+            download_max_retries,
+            url_max_size_mb,
+            presign_expires_secs,
+            clean_stale_outputs,
+            force_reprocess,
+            upload_concurrency,
+            multipart,
+            telemetry,
+            vector_store,
+            redis,
+            database,
+            kafka,
+            notify,
+            jobs,
+            auth,
+            retry,
         })
     }
 
-    /// 从INI配置文件加载扩展配置
+    /// 从配置文件（INI / TOML / YAML）加载扩展配置
     fn load_extended_from_file(config_path: &Path) -> Result<ExtendedConfig> {
         if !config_path.exists() {
             return Err(anyhow::anyhow!("配置文件不存在: {}", config_path.display()));
         }
 
-        let mut config_parser = configparser::ini::Ini::new();
-        config_parser.load(config_path)
-            .map_err(|e| anyhow::anyhow!("读取配置文件失败: {}: {}", config_path.display(), e))?;
+        let config_parser = read_config_file(config_path)?;
 
         // 加载视频处理配置
-        let process_config = Self::load_from_file(config_path)?;
+        let process_config = Self::load_from_file(config_path)?.into_config();
 
         // 加载扩展配置
         let debug_mode = config_parser.get("video_parse", "debug_mode")
@@ -241,10 +1741,202 @@ impl ConfigLoader {
             .or_else(|| config_parser.get("DEFAULT", "destination_prefix"))
             .filter(|v| !v.is_empty());
 
+        let output_naming = config_parser.get("oss", "output_naming")
+            .or_else(|| config_parser.get("DEFAULT", "output_naming"))
+            .and_then(|v| v.parse::<OutputNaming>().ok())
+            .unwrap_or_default();
+
         let log_level = config_parser.get("logging", "level")
             .or_else(|| config_parser.get("DEFAULT", "log_level"))
             .unwrap_or_else(|| "info".to_string());
 
+        let log_format = config_parser.get("logging", "format")
+            .or_else(|| config_parser.get("DEFAULT", "log_format"))
+            .and_then(|v| v.parse::<LogFormat>().ok())
+            .unwrap_or_default();
+
+        let cache_dir = config_parser.get("cache", "dir")
+            .or_else(|| config_parser.get("DEFAULT", "cache_dir"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let cache_in_bucket = config_parser.get("cache", "use_destination_bucket")
+            .or_else(|| config_parser.get("DEFAULT", "cache_in_bucket"))
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let segmented_download = config_parser.get("oss", "segmented_download")
+            .or_else(|| config_parser.get("DEFAULT", "segmented_download"))
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true);
+
+        let download_segment_size_mb = config_parser.get("oss", "segment_size_mb")
+            .or_else(|| config_parser.get("DEFAULT", "download_segment_size_mb"))
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(16);
+
+        let download_max_retries = config_parser.get("oss", "download_max_retries")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3);
+
+        let url_max_size_mb = config_parser.get("oss", "url_max_size_mb")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2048);
+
+        let presign_expires_secs = config_parser.get("oss", "presign_expires_secs")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let clean_stale_outputs = config_parser.get("oss", "clean_stale_outputs")
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let force_reprocess = config_parser.get("oss", "force_reprocess")
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let upload_concurrency = config_parser.get("oss", "upload_concurrency")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_UPLOAD_CONCURRENCY);
+
+        let multipart_defaults = MultipartConfig::default();
+        let multipart = MultipartConfig {
+            threshold_mb: config_parser.get("oss", "multipart_threshold_mb")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(multipart_defaults.threshold_mb),
+            part_size_mb: config_parser.get("oss", "multipart_part_size_mb")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(multipart_defaults.part_size_mb),
+            concurrency: config_parser.get("oss", "multipart_concurrency")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(multipart_defaults.concurrency),
+            max_retries: config_parser.get("oss", "multipart_max_retries")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(multipart_defaults.max_retries),
+        };
+
+        let telemetry_defaults = TelemetryConfig::default();
+        let telemetry = TelemetryConfig {
+            otlp_endpoint: config_parser.get("telemetry", "otlp_endpoint")
+                .filter(|v| !v.is_empty()),
+            otlp_protocol: config_parser.get("telemetry", "otlp_protocol")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(telemetry_defaults.otlp_protocol),
+            otlp_headers: config_parser.get("telemetry", "otlp_headers")
+                .map(|v| telemetry::parse_headers(&v))
+                .unwrap_or_default(),
+            service_name: config_parser.get("telemetry", "service_name")
+                .filter(|v| !v.is_empty())
+                .unwrap_or(telemetry_defaults.service_name),
+            export_metrics: config_parser.get("telemetry", "export_metrics")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(telemetry_defaults.export_metrics),
+        };
+
+        let vector_store_defaults = VectorStoreConfig::default();
+        let vector_store = VectorStoreConfig {
+            kind: config_parser.get("vector_store", "kind")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(vector_store_defaults.kind),
+            endpoint: config_parser.get("vector_store", "endpoint")
+                .filter(|v| !v.is_empty()),
+            collection: config_parser.get("vector_store", "collection")
+                .filter(|v| !v.is_empty())
+                .unwrap_or(vector_store_defaults.collection),
+            api_key: config_parser.get("vector_store", "api_key")
+                .filter(|v| !v.is_empty()),
+        };
+
+        let redis_defaults = RedisWorkerConfig::default();
+        let redis = RedisWorkerConfig {
+            url: config_parser.get("redis", "url")
+                .filter(|v| !v.is_empty())
+                .unwrap_or(redis_defaults.url),
+            queue: config_parser.get("redis", "queue")
+                .filter(|v| !v.is_empty())
+                .unwrap_or(redis_defaults.queue),
+            result_key: config_parser.get("redis", "result_key")
+                .filter(|v| !v.is_empty())
+                .unwrap_or(redis_defaults.result_key),
+        };
+
+        let database = DatabaseConfig {
+            url: config_parser.get("database", "url")
+                .filter(|v| !v.is_empty()),
+            max_connections: config_parser.get("database", "max_connections")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DatabaseConfig::default().max_connections),
+        };
+
+        let kafka = KafkaConfig {
+            brokers: config_parser.get("kafka", "brokers")
+                .filter(|v| !v.is_empty()),
+            topic: config_parser.get("kafka", "topic")
+                .filter(|v| !v.is_empty())
+                .unwrap_or(KafkaConfig::default().topic),
+            properties: config_parser.get("kafka", "properties")
+                .map(|v| telemetry::parse_headers(&v))
+                .unwrap_or_default(),
+        };
+
+        let notify = NotifyConfig {
+            dingtalk_webhook: config_parser.get("notify", "dingtalk_webhook")
+                .filter(|v| !v.is_empty()),
+            slack_webhook: config_parser.get("notify", "slack_webhook")
+                .filter(|v| !v.is_empty()),
+            feishu_webhook: config_parser.get("notify", "feishu_webhook")
+                .filter(|v| !v.is_empty()),
+        };
+
+        let jobs_defaults = JobsConfig::default();
+        let jobs = JobsConfig {
+            max_concurrent: config_parser.get("jobs", "max_concurrent")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(jobs_defaults.max_concurrent),
+            max_retained: config_parser.get("jobs", "max_retained")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(jobs_defaults.max_retained),
+            max_queued: config_parser.get("jobs", "max_queued")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(jobs_defaults.max_queued),
+            retry_after_secs: config_parser.get("jobs", "retry_after")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(jobs_defaults.retry_after_secs),
+        };
+
+        let auth = AuthConfig {
+            api_keys: config_parser.get("auth", "api_keys")
+                .map(|v| auth::parse_list(&v))
+                .unwrap_or_default(),
+            signature_secret: config_parser.get("auth", "signature_secret")
+                .filter(|v| !v.is_empty()),
+            public_paths: config_parser.get("auth", "public_paths")
+                .map(|v| auth::parse_list(&v))
+                .unwrap_or(AuthConfig::default().public_paths),
+        };
+
+        let retry_defaults = RetryConfig::default();
+        let retry = RetryConfig {
+            max_attempts: config_parser.get("retry", "max_attempts")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(retry_defaults.max_attempts),
+            initial_backoff: config_parser.get("retry", "backoff_secs")
+                .and_then(|v| v.parse().ok())
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(retry_defaults.initial_backoff),
+            dead_letter_dir: match config_parser.get("retry", "dead_letter_dir") {
+                Some(v) if v.is_empty() => None,
+                Some(v) => Some(PathBuf::from(v)),
+                None => retry_defaults.dead_letter_dir,
+            },
+            dead_letter_bucket: config_parser.get("retry", "dead_letter_bucket")
+                .filter(|v| !v.is_empty()),
+            dead_letter_prefix: config_parser.get("retry", "dead_letter_prefix")
+                .filter(|v| !v.is_empty()),
+        };
+
         Ok(ExtendedConfig {
             process: process_config,
             debug_mode,
@@ -252,54 +1944,183 @@ impl ConfigLoader {
             destination_bucket,
             destination_region,
             destination_prefix,
+            output_naming,
             log_level,
+            log_format,
+            cache_dir,
+            cache_in_bucket,
+            segmented_download,
+            download_segment_size_mb,
+            download_max_retries,
+            url_max_size_mb,
+            presign_expires_secs,
+            clean_stale_outputs,
+            force_reprocess,
+            upload_concurrency,
+            multipart,
+            telemetry,
+            vector_store,
+            redis,
+            database,
+            kafka,
+            notify,
+            jobs,
+            auth,
+            retry,
         })
     }
 
     /// 从默认位置加载扩展配置文件
     fn load_extended_from_default_locations() -> Result<ExtendedConfig> {
-        // 1. 当前目录的 video-parse.ini
-        let current_dir_config = PathBuf::from("video-parse.ini");
-        if current_dir_config.exists() {
-            return Self::load_extended_from_file(&current_dir_config);
+        match find_default_config() {
+            Some(path) => Self::load_extended_from_file(&path),
+            None => Err(anyhow::anyhow!("未找到配置文件")),
         }
-
-        // 2. 当前目录的 .video-parse.ini
-        let hidden_config = PathBuf::from(".video-parse.ini");
-        if hidden_config.exists() {
-            return Self::load_extended_from_file(&hidden_config);
-        }
-
-        // 3. 用户主目录的 .video-parse.ini
-        if let Some(home) = env::var_os("HOME") {
-            let home_config = PathBuf::from(home).join(".video-parse.ini");
-            if home_config.exists() {
-                return Self::load_extended_from_file(&home_config);
-            }
-        }
-
-        // 4. /etc/video-parse.ini (Linux/macOS)
-        let etc_config = PathBuf::from("/etc/video-parse.ini");
-        if etc_config.exists() {
-            return Self::load_extended_from_file(&etc_config);
-        }
-
-        Err(anyhow::anyhow!("未找到配置文件"))
     }
 
     /// 创建默认配置文件
     pub fn create_default_config(config_path: &Path) -> Result<()> {
         let mut config_parser = configparser::ini::Ini::new();
+        config_parser.set("video_parse", "preset", Some("".to_string()));
         config_parser.set("video_parse", "threshold", Some("0.35".to_string()));
         config_parser.set("video_parse", "min_scene_duration", Some("0.8".to_string()));
         config_parser.set("video_parse", "sample_rate", Some("0.5".to_string()));
+        config_parser.set("video_parse", "analysis_width", Some("".to_string()));
+        config_parser.set("video_parse", "detection_algorithm", Some("metrics".to_string()));
+        config_parser.set("video_parse", "threshold_mode", Some("fixed".to_string()));
+        config_parser.set("video_parse", "adaptive_k", Some("3.0".to_string()));
+        config_parser.set("video_parse", "adaptive_window", Some("30".to_string()));
+        config_parser.set("video_parse", "decoder", Some("ffmpeg".to_string()));
+        config_parser.set("video_parse", "hwaccel", Some("none".to_string()));
         config_parser.set("video_parse", "webhook_url", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_failure_url", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_progress_url", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_progress_interval", Some("10".to_string()));
+        config_parser.set("video_parse", "webhook_max_attempts", Some("4".to_string()));
+        config_parser.set("video_parse", "webhook_retry_backoff", Some("1".to_string()));
+        config_parser.set("video_parse", "webhook_spool_dir", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_spool_oss", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_secret", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_template", Some("".to_string()));
+        config_parser.set("video_parse", "webhook_fields", Some("".to_string()));
         config_parser.set("video_parse", "debug_mode", Some("false".to_string()));
         config_parser.set("video_parse", "output_path", Some("".to_string()));
+        config_parser.set("video_parse", "keyframe_format", Some("jpeg".to_string()));
+        config_parser.set("video_parse", "keyframe_quality", Some("75".to_string()));
+        config_parser.set("video_parse", "keyframe_max_width", Some("".to_string()));
+        config_parser.set("video_parse", "keyframe_max_height", Some("".to_string()));
+        config_parser.set("video_parse", "naming_keyframe", Some("keyframe_{index:04}.{ext}".to_string()));
+        config_parser.set("video_parse", "naming_audio", Some("audio.{ext}".to_string()));
+        config_parser.set("video_parse", "naming_metadata", Some("metadata.json".to_string()));
+        config_parser.set("video_parse", "clips", Some("false".to_string()));
+        config_parser.set("video_parse", "clip_mode", Some("copy".to_string()));
+        config_parser.set("video_parse", "preview", Some("false".to_string()));
+        config_parser.set("video_parse", "preview_format", Some("gif".to_string()));
+        config_parser.set("video_parse", "preview_duration", Some("2".to_string()));
+        config_parser.set("video_parse", "preview_fps", Some("8".to_string()));
+        config_parser.set("video_parse", "preview_width", Some("320".to_string()));
+        config_parser.set("video_parse", "waveform", Some("false".to_string()));
+        config_parser.set("video_parse", "dedupe_keyframes", Some("".to_string()));
+        config_parser.set("video_parse", "export_formats", Some("".to_string()));
+        config_parser.set("video_parse", "color_palette", Some("".to_string()));
+        config_parser.set("video_parse", "audio_boundary", Some("false".to_string()));
+        config_parser.set("video_parse", "audio_boundary_window", Some("0.5".to_string()));
+        config_parser.set("video_parse", "audio_boundary_silence_db", Some("-40".to_string()));
+        config_parser.set("video_parse", "detect_transitions", Some("false".to_string()));
+        config_parser.set("video_parse", "target_scene_range", Some("".to_string()));
+        config_parser.set("video_parse", "shot_type", Some("false".to_string()));
+        config_parser.set("video_parse", "fingerprint", Some("false".to_string()));
+        config_parser.set("video_parse", "viewer", Some("false".to_string()));
+        config_parser.set("video_parse", "audio_codec", Some("aac".to_string()));
+        config_parser.set("video_parse", "audio_bitrate", Some("".to_string()));
+        config_parser.set("video_parse", "audio_sample_rate", Some("".to_string()));
+        config_parser.set("video_parse", "audio_channels", Some("".to_string()));
+        config_parser.set("video_parse", "vad", Some("false".to_string()));
+        config_parser.set("video_parse", "vad_threshold_db", Some("-35".to_string()));
+        config_parser.set("video_parse", "vad_min_silence", Some("0.3".to_string()));
+        config_parser.set("video_parse", "checkpoint", Some("false".to_string()));
+        config_parser.set("video_parse", "content_addressed", Some("false".to_string()));
+        config_parser.set("video_parse", "archive", Some("".to_string()));
+        config_parser.set("video_parse", "ocr", Some("false".to_string()));
+        config_parser.set("video_parse", "ocr_languages", Some("chi_sim+eng".to_string()));
+        config_parser.set("video_parse", "faces", Some("false".to_string()));
+        config_parser.set("video_parse", "face_model", Some("".to_string()));
+        config_parser.set("video_parse", "face_min_confidence", Some("0.7".to_string()));
+        config_parser.set("video_parse", "safety", Some("false".to_string()));
+        config_parser.set("video_parse", "safety_model", Some("".to_string()));
+        config_parser.set("video_parse", "safety_labels", Some("drawings,hentai,neutral,porn,sexy".to_string()));
+        config_parser.set("video_parse", "safety_flagged_labels", Some("hentai,porn,sexy".to_string()));
+        config_parser.set("video_parse", "safety_threshold", Some("0.8".to_string()));
+        config_parser.set("video_parse", "safety_block_upload", Some("false".to_string()));
+        config_parser.set("video_parse", "embeddings", Some("false".to_string()));
+        config_parser.set("video_parse", "embedding_model", Some("".to_string()));
+        config_parser.set("video_parse", "embedding_input_size", Some("224".to_string()));
         config_parser.set("oss", "destination_bucket", Some("".to_string()));
         config_parser.set("oss", "destination_region", Some("".to_string()));
         config_parser.set("oss", "destination_prefix", Some("processed".to_string()));
+        config_parser.set("oss", "output_naming", Some("stem_hash".to_string()));
+        config_parser.set("oss", "segmented_download", Some("true".to_string()));
+        config_parser.set("oss", "segment_size_mb", Some("16".to_string()));
+        config_parser.set("oss", "download_max_retries", Some("3".to_string()));
+        config_parser.set("oss", "url_max_size_mb", Some("2048".to_string()));
+        config_parser.set("oss", "presign_expires_secs", Some("0".to_string()));
+        config_parser.set("oss", "clean_stale_outputs", Some("false".to_string()));
+        config_parser.set("oss", "force_reprocess", Some("false".to_string()));
+        config_parser.set("oss", "upload_concurrency", Some(DEFAULT_UPLOAD_CONCURRENCY.to_string()));
+        config_parser.set("oss", "multipart_threshold_mb", Some("32".to_string()));
+        config_parser.set("oss", "multipart_part_size_mb", Some("8".to_string()));
+        config_parser.set("oss", "multipart_concurrency", Some("4".to_string()));
+        config_parser.set("oss", "multipart_max_retries", Some("3".to_string()));
         config_parser.set("logging", "level", Some("info".to_string()));
+        config_parser.set("logging", "format", Some("text".to_string()));
+        config_parser.set("enrichment", "endpoint", Some("".to_string()));
+        config_parser.set("enrichment", "headers", Some("".to_string()));
+        config_parser.set("enrichment", "batch_size", Some("4".to_string()));
+        config_parser.set("enrichment", "timeout", Some("60".to_string()));
+        config_parser.set("sprite", "enabled", Some("false".to_string()));
+        config_parser.set("sprite", "columns", Some("5".to_string()));
+        config_parser.set("sprite", "tile_size", Some("160x90".to_string()));
+        config_parser.set("sprite", "timestamps", Some("true".to_string()));
+        config_parser.set("timeouts", "download", Some("0".to_string()));
+        config_parser.set("timeouts", "extract", Some("0".to_string()));
+        config_parser.set("timeouts", "detect", Some("0".to_string()));
+        config_parser.set("timeouts", "upload", Some("0".to_string()));
+        config_parser.set("limits", "max_input_size_bytes", Some("0".to_string()));
+        config_parser.set("limits", "max_duration_seconds", Some("0".to_string()));
+        config_parser.set("cache", "dir", Some("".to_string()));
+        config_parser.set("cache", "use_destination_bucket", Some("false".to_string()));
+        config_parser.set("telemetry", "otlp_endpoint", Some("".to_string()));
+        config_parser.set("telemetry", "otlp_protocol", Some("grpc".to_string()));
+        config_parser.set("telemetry", "otlp_headers", Some("".to_string()));
+        config_parser.set("telemetry", "service_name", Some("video-parse".to_string()));
+        config_parser.set("telemetry", "export_metrics", Some("true".to_string()));
+        config_parser.set("vector_store", "kind", Some("qdrant".to_string()));
+        config_parser.set("vector_store", "endpoint", Some("".to_string()));
+        config_parser.set("vector_store", "collection", Some("video_scenes".to_string()));
+        config_parser.set("vector_store", "api_key", Some("".to_string()));
+        config_parser.set("redis", "url", Some("redis://127.0.0.1:6379".to_string()));
+        config_parser.set("redis", "queue", Some("video-parse:jobs".to_string()));
+        config_parser.set("redis", "result_key", Some("video-parse:results".to_string()));
+        config_parser.set("database", "url", Some("".to_string()));
+        config_parser.set("database", "max_connections", Some("4".to_string()));
+        config_parser.set("kafka", "brokers", Some("".to_string()));
+        config_parser.set("kafka", "topic", Some("video-parse-results".to_string()));
+        config_parser.set("kafka", "properties", Some("".to_string()));
+        config_parser.set("notify", "dingtalk_webhook", Some("".to_string()));
+        config_parser.set("notify", "slack_webhook", Some("".to_string()));
+        config_parser.set("notify", "feishu_webhook", Some("".to_string()));
+        config_parser.set("jobs", "max_concurrent", Some("2".to_string()));
+        config_parser.set("jobs", "max_retained", Some("200".to_string()));
+        config_parser.set("jobs", "max_queued", Some("8".to_string()));
+        config_parser.set("jobs", "retry_after", Some("30".to_string()));
+        config_parser.set("auth", "api_keys", Some("".to_string()));
+        config_parser.set("auth", "signature_secret", Some("".to_string()));
+        config_parser.set("auth", "public_paths", Some(AuthConfig::default().public_paths.join(",")));
+        config_parser.set("retry", "max_attempts", Some("1".to_string()));
+        config_parser.set("retry", "backoff_secs", Some("5".to_string()));
+        config_parser.set("retry", "dead_letter_dir", Some(crate::dead_letter::default_dead_letter_dir().display().to_string()));
+        config_parser.set("retry", "dead_letter_bucket", Some("".to_string()));
+        config_parser.set("retry", "dead_letter_prefix", Some("".to_string()));
 
         config_parser.write(config_path)
             .map_err(|e| anyhow::anyhow!("写入配置文件失败: {}: {}", config_path.display(), e))?;
@@ -307,3 +2128,491 @@ impl ConfigLoader {
         Ok(())
     }
 }
+
+/// 生成 TOML / YAML 配置文件中的一个节：每个键有确定的类型，拼错的键和类型不符的值直接报错
+///
+/// 解析后按 INI 的写法转换为字符串写入 INI 解析器，三种格式的读取逻辑一致
+macro_rules! config_section {
+    ($(#[$meta:meta])* $name:ident { $($key:ident: $ty:ty),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct $name {
+            $($key: Option<$ty>,)*
+        }
+
+        impl $name {
+            fn write_fields(self, ini: &mut configparser::ini::Ini, section: &str) {
+                $(if let Some(value) = self.$key {
+                    IniEntry::write(value, ini, section, stringify!($key));
+                })*
+            }
+        }
+
+        impl IniEntry for $name {
+            fn write(self, ini: &mut configparser::ini::Ini, _section: &str, key: &str) {
+                self.write_fields(ini, key);
+            }
+        }
+    };
+}
+
+/// `[video_parse]` 节的键，顶层（`[DEFAULT]`）也可以使用
+macro_rules! video_parse_section {
+    ($(#[$meta:meta])* $name:ident { $($extra:tt)* }) => {
+        config_section! {
+            $(#[$meta])*
+            $name {
+                preset: String,
+                threshold: f64,
+                min_scene_duration: f64,
+                sample_rate: f64,
+                analysis_width: u32,
+                detection_algorithm: String,
+                threshold_mode: String,
+                adaptive_k: f64,
+                adaptive_window: usize,
+                decoder: String,
+                hwaccel: String,
+                debug_mode: bool,
+                output_path: String,
+                webhook_url: String,
+                webhook_failure_url: String,
+                webhook_progress_url: String,
+                webhook_progress_interval: u64,
+                webhook_max_attempts: u32,
+                webhook_retry_backoff: u64,
+                webhook_spool_dir: String,
+                webhook_spool_oss: String,
+                webhook_secret: String,
+                webhook_template: String,
+                webhook_fields: ConfigList,
+                ocr: bool,
+                ocr_languages: ConfigList,
+                faces: bool,
+                face_model: String,
+                face_min_confidence: f32,
+                safety: bool,
+                safety_model: String,
+                safety_labels: ConfigList,
+                safety_flagged_labels: ConfigList,
+                safety_threshold: f32,
+                safety_block_upload: bool,
+                embeddings: bool,
+                embedding_model: String,
+                embedding_input_size: u32,
+                clips: bool,
+                clip_mode: String,
+                preview: bool,
+                preview_format: String,
+                preview_duration: f64,
+                preview_fps: u32,
+                preview_width: u32,
+                waveform: bool,
+                dedupe_keyframes: f64,
+                export_formats: ConfigList,
+                color_palette: usize,
+                audio_boundary: bool,
+                audio_boundary_window: f64,
+                audio_boundary_silence_db: f64,
+                detect_transitions: bool,
+                target_scene_range: String,
+                shot_type: bool,
+                fingerprint: bool,
+                viewer: bool,
+                audio_codec: String,
+                audio_bitrate: u32,
+                audio_sample_rate: u32,
+                audio_channels: u32,
+                vad: bool,
+                vad_threshold_db: f64,
+                vad_min_silence: f64,
+                checkpoint: bool,
+                content_addressed: bool,
+                archive: String,
+                keyframe_format: String,
+                keyframe_quality: u8,
+                keyframe_max_width: u32,
+                keyframe_max_height: u32,
+                naming_keyframe: String,
+                naming_audio: String,
+                naming_metadata: String,
+                $($extra)*
+            }
+        }
+    };
+}
+
+video_parse_section! {
+    /// TOML / YAML 配置文件：与 INI 相同的节和键，顶层的键等同于 `[DEFAULT]` 节
+    ConfigFile {
+        destination_bucket: String,
+        destination_region: String,
+        destination_prefix: String,
+        output_naming: String,
+        segmented_download: bool,
+        download_segment_size_mb: u64,
+        cache_dir: String,
+        cache_in_bucket: bool,
+        log_level: String,
+        log_format: String,
+        max_input_size_bytes: u64,
+        max_duration_seconds: f64,
+        download_timeout: u64,
+        extract_timeout: u64,
+        detect_timeout: u64,
+        upload_timeout: u64,
+        enrichment_endpoint: String,
+        enrichment_headers: ConfigList,
+        enrichment_batch_size: usize,
+        enrichment_timeout: u64,
+        sprite: SpriteEntry,
+        sprite_columns: u32,
+        sprite_tile_size: String,
+        sprite_timestamps: bool,
+        video_parse: VideoParseSection,
+        oss: OssSection,
+        limits: LimitsSection,
+        timeouts: TimeoutsSection,
+        logging: LoggingSection,
+        cache: CacheSection,
+        enrichment: EnrichmentSection,
+        telemetry: TelemetrySection,
+        vector_store: VectorStoreSection,
+        redis: RedisSection,
+        database: DatabaseSection,
+        kafka: KafkaSection,
+        notify: NotifySection,
+        jobs: JobsSection,
+        auth: AuthSection,
+        retry: RetrySection,
+    }
+}
+
+video_parse_section! {
+    VideoParseSection {}
+}
+
+config_section! {
+    OssSection {
+        destination_bucket: String,
+        destination_region: String,
+        destination_prefix: String,
+        output_naming: String,
+        segmented_download: bool,
+        segment_size_mb: u64,
+        download_max_retries: u32,
+        url_max_size_mb: u64,
+        presign_expires_secs: u64,
+        clean_stale_outputs: bool,
+        force_reprocess: bool,
+        upload_concurrency: usize,
+        multipart_threshold_mb: u64,
+        multipart_part_size_mb: u64,
+        multipart_concurrency: usize,
+        multipart_max_retries: u32,
+    }
+}
+
+config_section! {
+    LimitsSection { max_input_size_bytes: u64, max_duration_seconds: f64 }
+}
+
+config_section! {
+    TimeoutsSection { download: u64, extract: u64, detect: u64, upload: u64 }
+}
+
+config_section! {
+    LoggingSection { level: String, format: String }
+}
+
+config_section! {
+    CacheSection { dir: String, use_destination_bucket: bool }
+}
+
+config_section! {
+    EnrichmentSection { endpoint: String, headers: ConfigList, batch_size: usize, timeout: u64 }
+}
+
+config_section! {
+    SpriteSection { enabled: bool, columns: u32, tile_size: String, timestamps: bool }
+}
+
+config_section! {
+    TelemetrySection {
+        otlp_endpoint: String,
+        otlp_protocol: String,
+        otlp_headers: ConfigList,
+        service_name: String,
+        export_metrics: bool,
+    }
+}
+
+config_section! {
+    VectorStoreSection { kind: String, endpoint: String, collection: String, api_key: String }
+}
+
+config_section! {
+    RedisSection { url: String, queue: String, result_key: String }
+}
+
+config_section! {
+    DatabaseSection { url: String, max_connections: u32 }
+}
+
+config_section! {
+    KafkaSection { brokers: ConfigList, topic: String, properties: ConfigList }
+}
+
+config_section! {
+    NotifySection { dingtalk_webhook: String, slack_webhook: String, feishu_webhook: String }
+}
+
+config_section! {
+    JobsSection { max_concurrent: usize, max_retained: usize, max_queued: usize, retry_after: u64 }
+}
+
+config_section! {
+    AuthSection { api_keys: ConfigList, signature_secret: String, public_paths: ConfigList }
+}
+
+config_section! {
+    RetrySection {
+        max_attempts: u32,
+        backoff_secs: u64,
+        dead_letter_dir: String,
+        dead_letter_bucket: String,
+        dead_letter_prefix: String,
+    }
+}
+
+/// 顶层的 `sprite`：布尔值等同于 `[DEFAULT]` 中的 `sprite`，表等同于 `[sprite]` 节
+#[derive(Debug)]
+enum SpriteEntry {
+    Enabled(bool),
+    Section(SpriteSection),
+}
+
+impl<'de> Deserialize<'de> for SpriteEntry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = SpriteEntry;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a boolean or a [sprite] table")
+            }
+
+            fn visit_bool<E: serde::de::Error>(self, v: bool) -> std::result::Result<SpriteEntry, E> {
+                Ok(SpriteEntry::Enabled(v))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> std::result::Result<SpriteEntry, A::Error> {
+                SpriteSection::deserialize(serde::de::value::MapAccessDeserializer::new(map)).map(SpriteEntry::Section)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl IniEntry for SpriteEntry {
+    fn write(self, ini: &mut configparser::ini::Ini, section: &str, key: &str) {
+        match self {
+            Self::Enabled(enabled) => enabled.write(ini, section, key),
+            Self::Section(sprite) => sprite.write_fields(ini, key),
+        }
+    }
+}
+
+/// 写入 INI 解析器的配置项：值按字符串写入所在的节，节写入以键命名的节
+trait IniEntry {
+    fn write(self, ini: &mut configparser::ini::Ini, section: &str, key: &str);
+}
+
+macro_rules! ini_value {
+    ($($ty:ty),*) => {
+        $(impl IniEntry for $ty {
+            fn write(self, ini: &mut configparser::ini::Ini, section: &str, key: &str) {
+                ini.set(section, key, Some(self.to_string()));
+            }
+        })*
+    };
+}
+
+ini_value!(bool, u8, u32, u64, usize, f32, f64, String);
+
+/// 列表：可以写成数组或逗号分隔的字符串，数组按逗号拼接，与 INI 中的列表写法一致
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ConfigList {
+    Joined(String),
+    Items(Vec<String>),
+}
+
+impl IniEntry for ConfigList {
+    fn write(self, ini: &mut configparser::ini::Ini, section: &str, key: &str) {
+        let value = match self {
+            Self::Joined(value) => value,
+            Self::Items(items) => items.join(","),
+        };
+        ini.set(section, key, Some(value));
+    }
+}
+
+impl ConfigFile {
+    /// 转换为 INI 解析器，后续按节和键读取配置项
+    fn into_ini(self) -> configparser::ini::Ini {
+        let mut ini = configparser::ini::Ini::new();
+        self.write_fields(&mut ini, "DEFAULT");
+        ini
+    }
+}
+
+/// 读取配置文件，按扩展名识别格式：`.toml`、`.yaml` / `.yml`，其他按 INI 解析
+fn read_config_file(config_path: &Path) -> Result<configparser::ini::Ini> {
+    let read_error = |e: &dyn std::fmt::Display| anyhow::anyhow!("读取配置文件失败: {}: {}", config_path.display(), e);
+    let extension = config_path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    match extension.as_deref() {
+        Some(ext @ ("toml" | "yaml" | "yml")) => {
+            let content = std::fs::read_to_string(config_path).map_err(|e| read_error(&e))?;
+            let file: ConfigFile = if ext == "toml" {
+                toml::from_str(&content).map_err(|e| read_error(&e))?
+            } else {
+                // 空的 YAML 文件解析为 null
+                serde_yaml::from_str::<Option<ConfigFile>>(&content).map_err(|e| read_error(&e))?.unwrap_or_default()
+            };
+            Ok(file.into_ini())
+        }
+        _ => {
+            let mut config_parser = configparser::ini::Ini::new();
+            config_parser.load(config_path).map_err(|e| read_error(&e))?;
+            Ok(config_parser)
+        }
+    }
+}
+
+/// 默认配置文件的扩展名，同一位置按此顺序查找
+const CONFIG_EXTENSIONS: &[&str] = &["ini", "toml", "yaml", "yml"];
+
+/// 默认配置文件的搜索路径，按优先级排列
+///
+/// 当前目录的 `video-parse.*`、`.video-parse.*`，用户主目录的 `.video-parse.*`
+/// （Windows 上未设置 HOME 时使用 USERPROFILE），之后 Windows 为 `%APPDATA%` 和 `%PROGRAMDATA%`
+/// 下的 `video-parse\video-parse.*`，Linux/macOS 为 `/etc/video-parse.*`；
+/// 每个位置依次查找 `.ini`、`.toml`、`.yaml`、`.yml`
+fn config_search_paths(var: impl Fn(&str) -> Option<OsString>, windows: bool) -> Vec<PathBuf> {
+    let dir = |key: &str| var(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let mut bases = vec![PathBuf::from("video-parse"), PathBuf::from(".video-parse")];
+    let home = dir("HOME").or_else(|| if windows { dir("USERPROFILE") } else { None });
+    if let Some(home) = home {
+        bases.push(home.join(".video-parse"));
+    }
+    if windows {
+        for key in ["APPDATA", "PROGRAMDATA"] {
+            if let Some(dir) = dir(key) {
+                bases.push(dir.join("video-parse").join("video-parse"));
+            }
+        }
+    } else {
+        bases.push(PathBuf::from("/etc/video-parse"));
+    }
+    bases
+        .iter()
+        .flat_map(|base| CONFIG_EXTENSIONS.iter().map(move |ext| base.with_extension(ext)))
+        .collect()
+}
+
+/// 第一个存在的默认配置文件
+fn find_default_config() -> Option<PathBuf> {
+    config_search_paths(|key| env::var_os(key), cfg!(windows))
+        .into_iter()
+        .find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_search_paths() {
+        let unix = config_search_paths(|key| (key == "HOME").then(|| OsString::from("/home/dev")), false);
+        assert_eq!(unix[..4], ["video-parse.ini", "video-parse.toml", "video-parse.yaml", "video-parse.yml"].map(PathBuf::from));
+        assert_eq!(unix[4], PathBuf::from(".video-parse.ini"));
+        assert_eq!(unix[8], PathBuf::from("/home/dev/.video-parse.ini"));
+        assert_eq!(unix[9], PathBuf::from("/home/dev/.video-parse.toml"));
+        assert_eq!(unix.last(), Some(&PathBuf::from("/etc/video-parse.yml")));
+
+        let windows = config_search_paths(
+            |key| match key {
+                "USERPROFILE" => Some(OsString::from("/users/dev")),
+                "APPDATA" => Some(OsString::from("/users/dev/appdata")),
+                "PROGRAMDATA" => Some(OsString::from("/programdata")),
+                _ => None,
+            },
+            true,
+        );
+        assert_eq!(windows[8], PathBuf::from("/users/dev/.video-parse.ini"));
+        assert_eq!(windows[12], PathBuf::from("/users/dev/appdata/video-parse/video-parse.ini"));
+        assert_eq!(windows[16], PathBuf::from("/programdata/video-parse/video-parse.ini"));
+        assert!(!windows.contains(&PathBuf::from("/etc/video-parse.ini")));
+    }
+
+    #[test]
+    fn test_toml_and_yaml_config_files() {
+        let dir = std::env::temp_dir().join(format!("video-parse-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml = dir.join("video-parse.toml");
+        std::fs::write(&toml, "debug_mode = true\n\n[video_parse]\nthreshold = 0.4\nsample_rate = 2\nexport_formats = [\"edl\", \"srt\"]\n\n[jobs]\nmax_queued = 3\n").unwrap();
+        let yaml = dir.join("video-parse.yml");
+        std::fs::write(&yaml, "debug_mode: true\nvideo_parse:\n  threshold: 0.4\n  sample_rate: 2\n  export_formats: [edl, srt]\njobs:\n  max_queued: 3\n").unwrap();
+
+        for path in [&toml, &yaml] {
+            let overrides = ConfigLoader::load_from_file(path).unwrap();
+            assert_eq!(overrides.threshold, Some(0.4), "{}", path.display());
+            assert_eq!(overrides.sample_rate, Some(2.0));
+            assert_eq!(overrides.export_formats, Some(vec![ExportFormat::Edl, ExportFormat::Srt]));
+            let extended = ConfigLoader::load_extended_from_file(path).unwrap();
+            assert!(extended.debug_mode);
+            assert_eq!(extended.jobs.max_queued, 3);
+        }
+        std::fs::write(&yaml, "").unwrap();
+        assert_eq!(ConfigLoader::load_from_file(&yaml).unwrap().threshold, None);
+        std::fs::write(&toml, "threshold = ").unwrap();
+        assert!(ConfigLoader::load_from_file(&toml).is_err());
+        // 拼错的键、类型不符的值和多余的嵌套表都报错
+        for content in [
+            "[video_parse]\nthresold = 0.4\n",
+            "[video_parse]\nthreshold = \"high\"\n",
+            "[jobs]\nmax_queued = 2.5\n",
+            "[video_parse.webhook]\nurl = \"https://example.com\"\n",
+            "[unknown]\nkey = 1\n",
+        ] {
+            std::fs::write(&toml, content).unwrap();
+            assert!(ConfigLoader::load_from_file(&toml).is_err(), "{}", content);
+        }
+        std::fs::write(&yaml, "video_parse:\n  threshold: [0.4]\n").unwrap();
+        assert!(ConfigLoader::load_from_file(&yaml).is_err());
+        // 顶层的 sprite 可以是开关，也可以是 [sprite] 节
+        std::fs::write(&toml, "sprite = true\nsprite_columns = 4\n").unwrap();
+        let overrides = ConfigLoader::load_from_file(&toml).unwrap();
+        assert_eq!((overrides.sprite, overrides.sprite_columns), (Some(true), Some(4)));
+        std::fs::write(&toml, "[sprite]\nenabled = true\ncolumns = 6\n").unwrap();
+        let overrides = ConfigLoader::load_from_file(&toml).unwrap();
+        assert_eq!((overrides.sprite, overrides.sprite_columns), (Some(true), Some(6)));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_analysis_width_override() {
+        let fast = |analysis_width| ConfigOverrides { preset: Some(Preset::Fast), analysis_width, ..Default::default() }.into_config();
+        assert_eq!(fast(None).analysis_width, Some(320));
+        assert_eq!(fast(Some(480)).analysis_width, Some(480));
+        // 0 表示使用原始分辨率，覆盖预设
+        assert_eq!(fast(Some(0)).analysis_width, None);
+        assert_eq!(ConfigOverrides::default().into_config().analysis_width, None);
+    }
+}
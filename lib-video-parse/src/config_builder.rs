@@ -0,0 +1,473 @@
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use crate::error::{Result, VideoParseError};
+use crate::processor::ProcessConfig;
+use crate::preset::Preset;
+use crate::scene_detector::{AdaptiveThreshold, DetectionAlgorithm, DetectionMetrics, ThresholdMode};
+use crate::video_processor::{HwAccel, SeekMode};
+use crate::decoder::DecoderBackend;
+use crate::watchdog::StageTimeouts;
+use crate::preflight::InputLimits;
+use crate::ocr::OcrConfig;
+use crate::sprite::SpriteConfig;
+use crate::clips::ClipConfig;
+use crate::preview::PreviewConfig;
+use crate::audio_boundary::AudioBoundaryConfig;
+use crate::audio_extractor::{AudioCodec, AudioConfig};
+use crate::vad::VadConfig;
+use crate::keyframe_image::KeyframeImageConfig;
+use crate::output_naming::FileNaming;
+use crate::archive::ArchiveFormat;
+use crate::metadata::ExportFormat;
+use crate::faces::FaceConfig;
+use crate::safety::SafetyConfig;
+use crate::embeddings::EmbeddingConfig;
+use crate::enrichment::EnrichmentConfig;
+use crate::webhook::{self, WebhookConfig};
+
+/// [`ProcessConfig`] 构建器，`build()` 时检查参数范围和互斥的选项
+///
+/// 新增配置项不会影响已有的构建代码，库的调用方应优先使用构建器而不是结构体字面量
+#[derive(Debug, Clone, Default)]
+pub struct ProcessConfigBuilder {
+    config: ProcessConfig,
+}
+
+impl ProcessConfig {
+    /// 从默认配置开始构建
+    pub fn builder() -> ProcessConfigBuilder {
+        ProcessConfigBuilder::default()
+    }
+
+    /// 检查参数范围和互斥的选项
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(VideoParseError::Config(message));
+        if !(0.0..=1.0).contains(&self.threshold) {
+            return invalid(format!("threshold 必须在 0 到 1 之间: {}", self.threshold));
+        }
+        if !(self.min_scene_duration >= 0.0 && self.min_scene_duration.is_finite()) {
+            return invalid(format!("min_scene_duration 不能为负数: {}", self.min_scene_duration));
+        }
+        if !(self.sample_rate > 0.0 && self.sample_rate.is_finite()) {
+            return invalid(format!("sample_rate 必须大于 0: {}", self.sample_rate));
+        }
+        if self.analysis_width == Some(0) {
+            return invalid("analysis_width 必须大于 0".to_string());
+        }
+        if !(self.adaptive_threshold.k > 0.0 && self.adaptive_threshold.k.is_finite()) {
+            return invalid(format!("adaptive_k 必须大于 0: {}", self.adaptive_threshold.k));
+        }
+        if self.adaptive_threshold.window == 0 {
+            return invalid("adaptive_window 必须大于 0".to_string());
+        }
+        if self.metrics.names().is_empty() {
+            return invalid("至少需要启用一个场景检测度量项".to_string());
+        }
+        if self.webhook.max_attempts == 0 {
+            return invalid("webhook_max_attempts 必须大于 0".to_string());
+        }
+        if self.webhook.template.is_some() && !self.webhook.fields.is_empty() {
+            return invalid("webhook_template 和 webhook_fields 不能同时设置".to_string());
+        }
+        if let Some(location) = self.webhook.spool_oss.as_deref().filter(|l| webhook::parse_oss_location(l).is_none()) {
+            return invalid(format!("webhook_spool_oss 必须是 oss://bucket/prefix 形式: {}", location));
+        }
+        if self.faces.enabled && self.faces.model_path.is_none() {
+            return invalid("启用人脸检测时必须设置 face_model".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.faces.min_confidence) {
+            return invalid(format!("face_min_confidence 必须在 0 到 1 之间: {}", self.faces.min_confidence));
+        }
+        if self.safety.enabled && self.safety.model_path.is_none() {
+            return invalid("启用内容安全检测时必须设置 safety_model".to_string());
+        }
+        if self.safety.block_upload && !self.safety.enabled {
+            return invalid("safety_block_upload 需要同时启用内容安全检测".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.safety.threshold) {
+            return invalid(format!("safety_threshold 必须在 0 到 1 之间: {}", self.safety.threshold));
+        }
+        if self.embeddings.enabled && self.embeddings.model_path.is_none() {
+            return invalid("启用图像向量时必须设置 embedding_model".to_string());
+        }
+        if self.embeddings.input_size == 0 {
+            return invalid("embedding_input_size 必须大于 0".to_string());
+        }
+        if self.enrichment.batch_size == 0 {
+            return invalid("enrichment_batch_size 必须大于 0".to_string());
+        }
+        if let Some(similarity) = self.dedupe_keyframes.filter(|s| !(0.0..=1.0).contains(s)) {
+            return invalid(format!("dedupe_keyframes 必须在 0 到 1 之间: {}", similarity));
+        }
+        if self.limits.max_input_size_bytes == Some(0) || self.limits.max_duration_seconds.is_some_and(|s| s <= 0.0) {
+            return invalid("max_input_size_bytes / max_duration_seconds 必须大于 0".to_string());
+        }
+        if let Some(range) = self.target_scene_range.as_ref().filter(|r| r.is_empty() || *r.end() == 0) {
+            return invalid(format!("target_scene_range 无效: {}-{}", range.start(), range.end()));
+        }
+        if self.color_palette == Some(0) {
+            return invalid("color_palette 必须大于 0".to_string());
+        }
+        if self.preview.enabled {
+            if self.preview.duration <= 0.0 {
+                return invalid(format!("preview_duration 必须大于 0: {}", self.preview.duration));
+            }
+            if self.preview.fps == 0 || self.preview.width == 0 {
+                return invalid("preview_fps 和 preview_width 必须大于 0".to_string());
+            }
+        }
+        if self.audio_boundary.enabled && self.audio_boundary.window <= 0.0 {
+            return invalid(format!("audio_boundary_window 必须大于 0: {}", self.audio_boundary.window));
+        }
+        if !(1..=100).contains(&self.keyframe_image.quality) {
+            return invalid(format!("keyframe_quality 必须在 1 到 100 之间: {}", self.keyframe_image.quality));
+        }
+        if self.keyframe_image.max_width == Some(0) || self.keyframe_image.max_height == Some(0) {
+            return invalid("keyframe_max_width / keyframe_max_height 必须大于 0".to_string());
+        }
+        if let Err(e) = self.naming.validate() {
+            return invalid(e);
+        }
+        if self.audio.bitrate_kbps == Some(0) {
+            return invalid("audio_bitrate 必须大于 0".to_string());
+        }
+        if let Some(rate) = self.audio.sample_rate.filter(|rate| !(8000..=192000).contains(rate)) {
+            return invalid(format!("audio_sample_rate 必须在 8000 到 192000 之间: {}", rate));
+        }
+        // libopus 只支持这几种采样率
+        if let Some(rate) = self.audio.sample_rate.filter(|rate| self.audio.codec == AudioCodec::Opus && ![8000, 12000, 16000, 24000, 48000].contains(rate)) {
+            return invalid(format!("opus 编码的 audio_sample_rate 只能是 8000 / 12000 / 16000 / 24000 / 48000: {}", rate));
+        }
+        if let Some(channels) = self.audio.channels.filter(|channels| !(1..=8).contains(channels)) {
+            return invalid(format!("audio_channels 必须在 1 到 8 之间: {}", channels));
+        }
+        if self.vad.enabled && (self.vad.min_silence < 0.0 || !self.vad.min_silence.is_finite()) {
+            return invalid(format!("vad_min_silence 不能小于 0: {}", self.vad.min_silence));
+        }
+        Ok(())
+    }
+}
+
+/// 从已有的配置继续修改
+impl From<ProcessConfig> for ProcessConfigBuilder {
+    fn from(config: ProcessConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ProcessConfigBuilder {
+    /// 从预设开始构建，之后设置的参数覆盖预设
+    pub fn from_preset(preset: Preset) -> Self {
+        Self { config: preset.config() }
+    }
+
+    /// 场景变化检测阈值（0 到 1）
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.config.threshold = threshold;
+        self
+    }
+
+    /// 最小场景持续时间（秒）
+    pub fn min_scene_duration(mut self, seconds: f64) -> Self {
+        self.config.min_scene_duration = seconds;
+        self
+    }
+
+    /// 帧采样率（每秒采样多少帧）
+    pub fn sample_rate(mut self, sample_rate: f64) -> Self {
+        self.config.sample_rate = sample_rate;
+        self
+    }
+
+    pub fn webhook_url(mut self, url: impl Into<String>) -> Self {
+        self.config.webhook_url = Some(url.into());
+        self
+    }
+
+    pub fn webhook_failure_url(mut self, url: impl Into<String>) -> Self {
+        self.config.webhook_failure_url = Some(url.into());
+        self
+    }
+
+    /// 进度回调地址
+    pub fn webhook_progress_url(mut self, url: impl Into<String>) -> Self {
+        self.config.webhook_progress_url = Some(url.into());
+        self
+    }
+
+    /// webhook 投递重试和回调数据格式
+    pub fn webhook(mut self, webhook: WebhookConfig) -> Self {
+        self.config.webhook = webhook;
+        self
+    }
+
+    /// webhook 请求体的 HMAC-SHA256 签名密钥
+    pub fn webhook_secret(mut self, secret: impl Into<String>) -> Self {
+        self.config.webhook.secret = Some(secret.into());
+        self
+    }
+
+    /// 分析分辨率（帧宽度）
+    pub fn analysis_width(mut self, width: u32) -> Self {
+        self.config.analysis_width = Some(width);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: DetectionMetrics) -> Self {
+        self.config.metrics = metrics;
+        self
+    }
+
+    /// 帧差异算法
+    pub fn detection_algorithm(mut self, algorithm: DetectionAlgorithm) -> Self {
+        self.config.detection_algorithm = algorithm;
+        self
+    }
+
+    /// 场景切换阈值模式
+    pub fn threshold_mode(mut self, threshold_mode: ThresholdMode) -> Self {
+        self.config.threshold_mode = threshold_mode;
+        self
+    }
+
+    /// 自适应阈值参数
+    pub fn adaptive_threshold(mut self, adaptive_threshold: AdaptiveThreshold) -> Self {
+        self.config.adaptive_threshold = adaptive_threshold;
+        self
+    }
+
+    pub fn seek_mode(mut self, seek_mode: SeekMode) -> Self {
+        self.config.seek_mode = seek_mode;
+        self
+    }
+
+    /// 帧解码后端
+    pub fn decoder(mut self, decoder: DecoderBackend) -> Self {
+        self.config.decoder = decoder;
+        self
+    }
+
+    /// 硬件解码，设备不可用时回退到软件解码
+    pub fn hwaccel(mut self, hwaccel: HwAccel) -> Self {
+        self.config.hwaccel = hwaccel;
+        self
+    }
+
+    pub fn timeouts(mut self, timeouts: StageTimeouts) -> Self {
+        self.config.timeouts = timeouts;
+        self
+    }
+
+    /// 视频帧提取超时
+    pub fn extract_timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeouts.extract = Some(timeout);
+        self
+    }
+
+    /// 场景检测超时
+    pub fn detect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeouts.detect = Some(timeout);
+        self
+    }
+
+    /// 输入文件大小和时长上限，超出时拒绝处理
+    pub fn limits(mut self, limits: InputLimits) -> Self {
+        self.config.limits = limits;
+        self
+    }
+
+    pub fn ocr(mut self, ocr: OcrConfig) -> Self {
+        self.config.ocr = ocr;
+        self
+    }
+
+    pub fn faces(mut self, faces: FaceConfig) -> Self {
+        self.config.faces = faces;
+        self
+    }
+
+    pub fn safety(mut self, safety: SafetyConfig) -> Self {
+        self.config.safety = safety;
+        self
+    }
+
+    pub fn embeddings(mut self, embeddings: EmbeddingConfig) -> Self {
+        self.config.embeddings = embeddings;
+        self
+    }
+
+    pub fn enrichment(mut self, enrichment: EnrichmentConfig) -> Self {
+        self.config.enrichment = enrichment;
+        self
+    }
+
+    /// 关键帧图片格式、质量和尺寸
+    pub fn keyframe_image(mut self, keyframe_image: KeyframeImageConfig) -> Self {
+        self.config.keyframe_image = keyframe_image;
+        self
+    }
+
+    /// 关键帧、音频和元数据的文件名模板，例如 `{stem}_scene{scene_id:04}_{start_ms}.{ext}`
+    pub fn naming(mut self, naming: FileNaming) -> Self {
+        self.config.naming = naming;
+        self
+    }
+
+    /// 关键帧缩略图拼图
+    pub fn sprite(mut self, sprite: SpriteConfig) -> Self {
+        self.config.sprite = sprite;
+        self
+    }
+
+    /// 场景片段导出
+    pub fn clips(mut self, clips: ClipConfig) -> Self {
+        self.config.clips = clips;
+        self
+    }
+
+    /// 场景预览动图
+    pub fn preview(mut self, preview: PreviewConfig) -> Self {
+        self.config.preview = preview;
+        self
+    }
+
+    /// 关键帧去重的相似度（None 表示不去重）
+    pub fn dedupe_keyframes(mut self, similarity: Option<f64>) -> Self {
+        self.config.dedupe_keyframes = similarity;
+        self
+    }
+
+    /// 额外导出的场景列表格式
+    pub fn export_formats(mut self, formats: Vec<ExportFormat>) -> Self {
+        self.config.export_formats = formats;
+        self
+    }
+
+    /// 场景颜色统计的主色调数量（None 表示不统计）
+    pub fn color_palette(mut self, palette: Option<usize>) -> Self {
+        self.config.color_palette = palette;
+        self
+    }
+
+    /// 按音频校准场景边界
+    pub fn audio_boundary(mut self, audio_boundary: AudioBoundaryConfig) -> Self {
+        self.config.audio_boundary = audio_boundary;
+        self
+    }
+
+    /// 识别渐隐、渐显和叠化等渐变转场
+    pub fn detect_transitions(mut self, enabled: bool) -> Self {
+        self.config.detect_transitions = enabled;
+        self
+    }
+
+    /// 目标场景数范围，检测到的场景数不在范围内时自动调整阈值
+    pub fn target_scene_range(mut self, range: Option<RangeInclusive<usize>>) -> Self {
+        self.config.target_scene_range = range;
+        self
+    }
+
+    /// 估计每个场景的景别（特写、中景、全景）
+    pub fn shot_type(mut self, enabled: bool) -> Self {
+        self.config.shot_type = enabled;
+        self
+    }
+
+    /// 生成视频指纹 fingerprint.json，用于识别重复上传的内容
+    pub fn fingerprint(mut self, enabled: bool) -> Self {
+        self.config.fingerprint = enabled;
+        self
+    }
+
+    /// 在输出目录生成结果查看页 index.html
+    pub fn viewer(mut self, enabled: bool) -> Self {
+        self.config.viewer = enabled;
+        self
+    }
+
+    /// 音频输出的编码、码率、采样率和声道
+    pub fn audio(mut self, audio: AudioConfig) -> Self {
+        self.config.audio = audio;
+        self
+    }
+
+    /// 语音活动检测
+    pub fn vad(mut self, vad: VadConfig) -> Self {
+        self.config.vad = vad;
+        self
+    }
+
+    /// 写入检查点，超时后重新处理同一输入时从上次完成的阶段继续
+    pub fn checkpoint(mut self, enabled: bool) -> Self {
+        self.config.checkpoint = enabled;
+        self
+    }
+
+    /// 输出放在 `{源文件哈希}/{配置指纹}/` 子目录下，同一视频用不同参数处理时不会互相覆盖
+    pub fn content_addressed(mut self, enabled: bool) -> Self {
+        self.config.content_addressed = enabled;
+        self
+    }
+
+    /// 处理完成后把输出目录打包为单个 .zip / .tar.gz 文件
+    pub fn archive(mut self, format: ArchiveFormat) -> Self {
+        self.config.archive = Some(format);
+        self
+    }
+
+    /// 音频波形和响度分析
+    pub fn waveform(mut self, enabled: bool) -> Self {
+        self.config.waveform = enabled;
+        self
+    }
+
+    /// 检查配置并返回 [`ProcessConfig`]，参数无效时返回 [`VideoParseError::Config`]
+    pub fn build(self) -> Result<ProcessConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_builder_validation() {
+        let config = ProcessConfig::builder()
+            .threshold(0.3)
+            .sample_rate(2.0)
+            .analysis_width(320)
+            .build()
+            .unwrap();
+        assert_eq!(config.threshold, 0.3);
+        assert_eq!(config.sample_rate, 2.0);
+        assert_eq!(config.analysis_width, Some(320));
+
+        let fast = ProcessConfigBuilder::from_preset(Preset::Fast).build().unwrap();
+        assert_eq!(fast.sample_rate, Preset::Fast.config().sample_rate);
+
+        assert!(matches!(
+            ProcessConfig::builder().threshold(1.5).build(),
+            Err(VideoParseError::Config(_))
+        ));
+        assert!(ProcessConfig::builder().sample_rate(0.0).build().is_err());
+        assert!(ProcessConfig::builder().min_scene_duration(f64::NAN).build().is_err());
+
+        let webhook = WebhookConfig {
+            template: Some(PathBuf::from("webhook.j2")),
+            fields: vec!["job_id".to_string()],
+            ..Default::default()
+        };
+        assert!(ProcessConfig::builder().webhook(webhook).build().is_err());
+
+        let safety = SafetyConfig { block_upload: true, ..Default::default() };
+        assert!(ProcessConfig::builder().safety(safety).build().is_err());
+
+        let opus = AudioConfig { codec: AudioCodec::Opus, sample_rate: Some(44100), ..Default::default() };
+        assert!(ProcessConfig::builder().audio(opus).build().is_err());
+        let mono = AudioConfig { codec: AudioCodec::Mp3, sample_rate: Some(44100), channels: Some(1), ..Default::default() };
+        assert!(ProcessConfig::builder().audio(mono).build().is_ok());
+    }
+}
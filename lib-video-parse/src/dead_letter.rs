@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::oss_client::OssClient;
+use crate::oss_event::OssEvent;
+
+/// 两次处理之间的最长等待时间
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// OSS 事件处理失败后的重试和死信配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    /// 最多处理次数（包括第一次），1 表示不重试
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍
+    pub initial_backoff: Duration,
+    /// 重试用尽后写入死信的本地目录（None 表示不写本地文件）
+    pub dead_letter_dir: Option<PathBuf>,
+    /// 死信上传的 bucket（None 表示使用事件所在的 bucket）
+    pub dead_letter_bucket: Option<String>,
+    /// 死信上传的 OSS 前缀（None 表示不上传）
+    pub dead_letter_prefix: Option<String>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::from_secs(5),
+            dead_letter_dir: Some(default_dead_letter_dir()),
+            dead_letter_bucket: None,
+            dead_letter_prefix: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// 第 `attempt` 次处理失败后的等待时间（指数退避，上限 5 分钟）
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+}
+
+/// 默认的死信目录
+pub fn default_dead_letter_dir() -> PathBuf {
+    std::env::temp_dir().join("video-parse").join("dead-letter")
+}
+
+/// 重试用尽仍然失败的 OSS 事件，`event` 可以原样重新提交到 `/process`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub request_id: String,
+    /// `oss://bucket/key`
+    pub source: String,
+    /// 最后一次处理返回的 HTTP 状态码
+    pub status: u16,
+    /// 最后一次处理的错误信息
+    pub error: String,
+    /// 已处理次数
+    pub attempts: u32,
+    /// 放弃处理的时间（RFC 3339）
+    pub failed_at: String,
+    /// 原始事件
+    pub event: OssEvent,
+}
+
+impl DeadLetter {
+    /// 死信文件名：`{request_id}.json`
+    pub fn file_name(&self) -> String {
+        format!("{}.json", self.request_id.replace(['/', '\\'], "_"))
+    }
+}
+
+/// 写出死信：写入本地目录，配置了 OSS 前缀时同时上传，返回写入的位置
+///
+/// 一处写入失败不影响另一处，都失败时返回错误
+pub async fn store(config: &RetryConfig, letter: &DeadLetter, bucket: &str, region: &str) -> Result<Vec<String>> {
+    let body = serde_json::to_vec_pretty(letter).context("序列化死信失败")?;
+    let mut locations = Vec::new();
+    let mut errors = Vec::new();
+
+    let local_path = match config.dead_letter_dir {
+        Some(ref dir) => write_local(dir, &letter.file_name(), &body)?,
+        // 只上传 OSS 时先写入临时文件
+        None if config.dead_letter_prefix.is_some() => {
+            write_local(&std::env::temp_dir(), &format!("dead-letter-{}", letter.file_name()), &body)?
+        }
+        None => return Ok(locations),
+    };
+    if config.dead_letter_dir.is_some() {
+        locations.push(local_path.display().to_string());
+    }
+
+    if let Some(ref prefix) = config.dead_letter_prefix {
+        let bucket = config.dead_letter_bucket.as_deref().unwrap_or(bucket);
+        let key = format!("{}/{}", prefix.trim_end_matches('/'), letter.file_name());
+        let endpoint = format!("oss-{}-internal.aliyuncs.com", region);
+        let uploaded = match OssClient::new() {
+            Ok(client) => client.upload_file(bucket, &key, &local_path, Some(&endpoint)).await,
+            Err(e) => Err(e),
+        };
+        match uploaded {
+            Ok(()) => locations.push(format!("oss://{}/{}", bucket, key)),
+            Err(e) => errors.push(format!("上传死信失败 oss://{}/{}: {}", bucket, key, e)),
+        }
+        if config.dead_letter_dir.is_none() {
+            std::fs::remove_file(&local_path).ok();
+        }
+    }
+
+    if locations.is_empty() && !errors.is_empty() {
+        anyhow::bail!(errors.join("; "));
+    }
+    for error in errors {
+        tracing::warn!(error = %error, "⚠️  [死信] 写入失败");
+    }
+    Ok(locations)
+}
+
+fn write_local(dir: &Path, file_name: &str, body: &[u8]) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).context(format!("创建死信目录失败: {}", dir.display()))?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, body).context(format!("写入死信文件失败: {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_store_dead_letter() {
+        let config = RetryConfig { initial_backoff: Duration::from_secs(10), ..Default::default() };
+        assert_eq!(config.backoff(1), Duration::from_secs(10));
+        assert_eq!(config.backoff(3), Duration::from_secs(40));
+        assert_eq!(config.backoff(10), MAX_BACKOFF);
+
+        let dir = std::env::temp_dir().join(format!("video-parse-dead-letter-{}", uuid::Uuid::new_v4()));
+        let config = RetryConfig { dead_letter_dir: Some(dir.clone()), ..config };
+        let letter = DeadLetter {
+            request_id: "req/1".to_string(),
+            source: "oss://bucket/videos/a.mp4".to_string(),
+            status: 500,
+            error: "处理视频失败".to_string(),
+            attempts: 3,
+            failed_at: chrono::Utc::now().to_rfc3339(),
+            event: OssEvent { events: Vec::new() },
+        };
+        let locations = store(&config, &letter, "bucket", "cn-hangzhou").await.unwrap();
+        assert_eq!(locations, vec![dir.join("req_1.json").display().to_string()]);
+        let stored: DeadLetter = serde_json::from_slice(&std::fs::read(dir.join("req_1.json")).unwrap()).unwrap();
+        assert_eq!((stored.attempts, stored.status), (3, 500));
+
+        let disabled = RetryConfig { dead_letter_dir: None, ..Default::default() };
+        assert!(store(&disabled, &letter, "bucket", "cn-hangzhou").await.unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
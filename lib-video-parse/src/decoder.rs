@@ -0,0 +1,226 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::pipeline::{FfmpegFrameSource, FrameSource};
+
+#[cfg(feature = "gstreamer")]
+pub use gst_source::GstreamerFrameSource;
+
+/// 帧解码后端
+///
+/// 只影响帧提取；音频提取和输入预检仍然使用 FFmpeg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecoderBackend {
+    /// ffmpeg-next（默认）
+    #[default]
+    Ffmpeg,
+    /// GStreamer，需要以 `--features gstreamer` 编译
+    Gstreamer,
+}
+
+impl DecoderBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecoderBackend::Ffmpeg => "ffmpeg",
+            DecoderBackend::Gstreamer => "gstreamer",
+        }
+    }
+}
+
+impl FromStr for DecoderBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ffmpeg" => Ok(DecoderBackend::Ffmpeg),
+            "gstreamer" | "gst" => Ok(DecoderBackend::Gstreamer),
+            other => Err(format!("未知的解码后端: {}（可选值: ffmpeg, gstreamer）", other)),
+        }
+    }
+}
+
+/// 按解码后端创建帧来源
+#[cfg(feature = "gstreamer")]
+pub fn frame_source(backend: DecoderBackend) -> Arc<dyn FrameSource> {
+    match backend {
+        DecoderBackend::Ffmpeg => Arc::new(FfmpegFrameSource),
+        DecoderBackend::Gstreamer => Arc::new(GstreamerFrameSource),
+    }
+}
+
+/// 未启用 `gstreamer` feature 时始终使用 FFmpeg
+#[cfg(not(feature = "gstreamer"))]
+pub fn frame_source(backend: DecoderBackend) -> Arc<dyn FrameSource> {
+    if backend == DecoderBackend::Gstreamer {
+        tracing::warn!("⚠️  已选择 GStreamer 解码，但未启用 gstreamer feature，使用 FFmpeg");
+    }
+    Arc::new(FfmpegFrameSource)
+}
+
+#[cfg(feature = "gstreamer")]
+mod gst_source {
+    use anyhow::{anyhow, Context, Result};
+    use gstreamer as gst;
+    use gstreamer::prelude::*;
+    use gstreamer_app as gst_app;
+    use gstreamer_video as gst_video;
+    use image::{DynamicImage, RgbImage};
+    use tracing::{info, Span};
+    use crate::error::VideoParseError;
+    use crate::metadata::MediaKind;
+    use crate::pipeline::{DecodedVideo, FrameSource, VideoInput};
+    use crate::processor::ProcessConfig;
+    use crate::video_processor::{SampleAspectRatio, SeekMode};
+
+    /// 等待流水线状态切换（预卷、seek）完成的最长时间
+    const STATE_TIMEOUT_SECS: u64 = 30;
+
+    /// 使用 GStreamer 解码的帧来源
+    ///
+    /// 流水线为 `uridecodebin ! videoconvert ! videoscale ! appsink`，输出方形像素的 RGB 帧，
+    /// 在 PAUSED 状态下逐个 seek 到采样时间点并取预卷帧。只支持包含视频流的输入，
+    /// 帧已缩放到显示分辨率，因此 `width`/`height` 为显示分辨率
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct GstreamerFrameSource;
+
+    impl FrameSource for GstreamerFrameSource {
+        fn extract(&self, input: &VideoInput, config: &ProcessConfig) -> Result<DecodedVideo> {
+            // GStreamer 直接读取文件，分段下载时等待下载完成
+            if let Some(ref file) = input.progressive {
+                file.wait_complete()?;
+            }
+            gst::init().context("初始化 GStreamer 失败")?;
+
+            let path = std::fs::canonicalize(&input.path)
+                .with_context(|| format!("视频文件不存在: {}", input.path.display()))?;
+            let uri = gst::glib::filename_to_uri(&path, None)
+                .with_context(|| format!("无效的文件路径: {}", path.display()))?;
+            let description = format!(
+                "uridecodebin uri=\"{}\" ! videoconvert ! videoscale \
+                 ! video/x-raw,format=RGB,pixel-aspect-ratio=1/1 ! appsink name=sink sync=false",
+                uri
+            );
+            let pipeline = gst::parse::launch(&description)
+                .context("创建 GStreamer 流水线失败")?
+                .downcast::<gst::Pipeline>()
+                .map_err(|_| anyhow!("GStreamer 流水线类型错误"))?;
+            let sink = pipeline
+                .by_name("sink")
+                .and_then(|e| e.downcast::<gst_app::AppSink>().ok())
+                .context("GStreamer 流水线缺少 appsink")?;
+
+            let result = decode(&pipeline, &sink, config);
+            let _ = pipeline.set_state(gst::State::Null);
+            result
+        }
+    }
+
+    fn decode(pipeline: &gst::Pipeline, sink: &gst_app::AppSink, config: &ProcessConfig) -> Result<DecodedVideo> {
+        let timeout = gst::ClockTime::from_seconds(STATE_TIMEOUT_SECS);
+        pipeline.set_state(gst::State::Paused)
+            .map_err(|_| pipeline_error(pipeline, "打开视频失败"))?;
+        // 预卷完成后才能查询时长和 seek
+        pipeline.state(timeout).0
+            .map_err(|_| pipeline_error(pipeline, "打开视频失败"))?;
+
+        let duration = pipeline
+            .query_duration::<gst::ClockTime>()
+            .map(|d| d.nseconds() as f64 / 1e9)
+            .unwrap_or(0.0);
+        let flags = match config.seek_mode {
+            SeekMode::Keyframe => gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+            SeekMode::Nearest => gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_NEAREST,
+            SeekMode::Exact => gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+        };
+
+        let frame_interval = 1.0 / config.sample_rate;
+        let mut frames = Vec::new();
+        let mut info = None;
+        let mut target = 0.0;
+        loop {
+            pipeline
+                .seek_simple(flags, gst::ClockTime::from_nseconds((target * 1e9) as u64))
+                .map_err(|_| pipeline_error(pipeline, &format!("seek 到 {:.2}s 失败", target)))?;
+            pipeline.state(timeout).0
+                .map_err(|_| pipeline_error(pipeline, &format!("seek 到 {:.2}s 失败", target)))?;
+
+            let sample = sink.pull_preroll()
+                .map_err(|_| pipeline_error(pipeline, "读取视频帧失败"))?;
+            let caps = sample.caps().context("视频帧缺少格式信息")?;
+            let video_info = gst_video::VideoInfo::from_caps(caps).context("解析视频帧格式失败")?;
+            let buffer = sample.buffer().context("视频帧缺少数据")?;
+            let timestamp = buffer.pts().map_or(target, |pts| pts.nseconds() as f64 / 1e9);
+            let map = buffer.map_readable().context("读取视频帧数据失败")?;
+            let image = to_rgb_image(&video_info, map.as_slice())?;
+
+            // 关键帧 seek 可能落到同一帧，跳过重复的时间点
+            if frames.last().is_none_or(|&(last, _)| timestamp > last) {
+                frames.push((timestamp, DynamicImage::ImageRgb8(image)));
+            }
+            info.get_or_insert(video_info);
+
+            target += frame_interval;
+            if target >= duration {
+                break;
+            }
+        }
+
+        let info = info.context("未解码到视频帧")?;
+        let fps = info.fps();
+        let fps = if fps.denom() > 0 { fps.numer() as f64 / fps.denom() as f64 } else { 0.0 };
+        let span = Span::current();
+        span.record("resolution", format!("{}x{}", info.width(), info.height()));
+        span.record("fps", fps);
+        info!(frames = frames.len(), "✅ [视频处理] GStreamer 帧提取完成");
+
+        Ok(DecodedVideo {
+            fps,
+            width: info.width(),
+            height: info.height(),
+            sample_aspect_ratio: SampleAspectRatio::SQUARE,
+            media_kind: MediaKind::Video,
+            duration: (duration > 0.0).then_some(duration),
+            frames,
+        })
+    }
+
+    /// 按行拷贝 RGB 数据（GStreamer 的每行末尾可能有对齐填充）
+    fn to_rgb_image(info: &gst_video::VideoInfo, data: &[u8]) -> Result<RgbImage> {
+        let (width, height) = (info.width(), info.height());
+        let stride = info.stride()[0] as usize;
+        let row_bytes = width as usize * 3;
+        let mut image = RgbImage::new(width, height);
+        for (y, row) in image.chunks_exact_mut(row_bytes).enumerate() {
+            let start = y * stride;
+            let src = data.get(start..start + row_bytes).context("视频帧数据不完整")?;
+            row.copy_from_slice(src);
+        }
+        Ok(image)
+    }
+
+    /// 从总线取出流水线报告的错误，没有视频流等输入问题归为 `InvalidInput`
+    fn pipeline_error(pipeline: &gst::Pipeline, message: &str) -> anyhow::Error {
+        let detail = pipeline.bus().and_then(|bus| {
+            bus.pop_filtered(&[gst::MessageType::Error]).and_then(|msg| match msg.view() {
+                gst::MessageView::Error(err) => Some(err.error().to_string()),
+                _ => None,
+            })
+        });
+        match detail {
+            Some(detail) => VideoParseError::InvalidInput(format!("{}: {}", message, detail)).into(),
+            None => anyhow!("{}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_backend() {
+        assert_eq!("FFmpeg".parse::<DecoderBackend>(), Ok(DecoderBackend::Ffmpeg));
+        assert_eq!("gst".parse::<DecoderBackend>(), Ok(DecoderBackend::Gstreamer));
+        assert!("vaapi".parse::<DecoderBackend>().is_err());
+        assert_eq!(DecoderBackend::default().as_str(), "ffmpeg");
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::metadata::SceneMetadata;
+
+/// 向量文件名（位于输出目录）
+pub const EMBEDDINGS_FILE: &str = "embeddings.jsonl";
+
+/// 关键帧向量配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingConfig {
+    /// 是否计算关键帧的图像向量
+    pub enabled: bool,
+    /// CLIP 图像编码器 ONNX 模型路径（输入 1x3xNxN，输出 [1, D]）
+    pub model_path: Option<PathBuf>,
+    /// 模型输入边长
+    pub input_size: u32,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_path: None,
+            input_size: 224,
+        }
+    }
+}
+
+/// 向量文件索引，写入 `VideoMetadata.embeddings`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingsIndex {
+    /// 向量文件名（JSONL，每行一个场景）
+    pub file: String,
+    /// 模型名称（模型文件名）
+    pub model: String,
+    /// 向量维度
+    pub dimensions: usize,
+    /// 向量数量
+    pub count: usize,
+}
+
+/// 向量文件中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneEmbedding {
+    pub scene_id: usize,
+    pub keyframe_file: String,
+    /// L2 归一化后的向量，可直接用点积计算余弦相似度
+    pub embedding: Vec<f32>,
+}
+
+/// L2 归一化（零向量保持不变）
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// 把向量写入输出目录的 JSONL 文件
+pub fn write_embeddings(output_dir: &Path, model: &str, embeddings: &[SceneEmbedding]) -> Result<EmbeddingsIndex> {
+    let path = output_dir.join(EMBEDDINGS_FILE);
+    let file = std::fs::File::create(&path)
+        .context(format!("创建向量文件失败: {}", path.display()))?;
+    let mut writer = std::io::BufWriter::new(file);
+    for embedding in embeddings {
+        serde_json::to_writer(&mut writer, embedding)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush().context(format!("写入向量文件失败: {}", path.display()))?;
+    Ok(EmbeddingsIndex {
+        file: EMBEDDINGS_FILE.to_string(),
+        model: model.to_string(),
+        dimensions: embeddings.first().map_or(0, |e| e.embedding.len()),
+        count: embeddings.len(),
+    })
+}
+
+/// 计算每个场景关键帧的 CLIP 图像向量，写入 `embeddings.jsonl`
+///
+/// 需要以 `--features embeddings` 编译，并能加载 onnxruntime 动态库（通过 ORT_DYLIB_PATH 指定路径）。
+/// 单帧计算失败只记录警告。没有任何向量时不写文件，返回 None
+#[cfg(feature = "embeddings")]
+pub fn embed_scenes(config: &EmbeddingConfig, output_dir: &Path, scenes: &[SceneMetadata]) -> Result<Option<EmbeddingsIndex>> {
+    let model_path = config.model_path.as_ref().context("未配置图像向量模型路径（embedding_model）")?;
+    let mut encoder = clip::ImageEncoder::load(model_path, config.input_size)?;
+    let mut embeddings = Vec::with_capacity(scenes.len());
+    for scene in scenes {
+        let keyframe_path = output_dir.join(&scene.keyframe_file);
+        let result = image::open(&keyframe_path)
+            .context(format!("读取关键帧失败: {}", keyframe_path.display()))
+            .and_then(|image| encoder.encode(&image));
+        match result {
+            Ok(embedding) => embeddings.push(SceneEmbedding {
+                scene_id: scene.scene_id,
+                keyframe_file: scene.keyframe_file.clone(),
+                embedding,
+            }),
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [图像向量] 关键帧向量计算失败");
+            }
+        }
+    }
+    if embeddings.is_empty() {
+        return Ok(None);
+    }
+    let model = model_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("clip");
+    write_embeddings(output_dir, model, &embeddings).map(Some)
+}
+
+/// 未启用 `embeddings` feature 时跳过向量计算
+#[cfg(not(feature = "embeddings"))]
+pub fn embed_scenes(_config: &EmbeddingConfig, _output_dir: &Path, _scenes: &[SceneMetadata]) -> Result<Option<EmbeddingsIndex>> {
+    tracing::warn!("⚠️  已启用图像向量，但未启用 embeddings feature，跳过");
+    Ok(None)
+}
+
+#[cfg(feature = "embeddings")]
+mod clip {
+    use super::l2_normalize;
+    use anyhow::{Context, Result};
+    use image::{imageops::FilterType, DynamicImage};
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use std::path::Path;
+
+    /// CLIP 预处理的均值和标准差
+    const MEAN: [f32; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+    const STD: [f32; 3] = [0.268_629_54, 0.261_302_6, 0.275_777_1];
+
+    /// CLIP 图像编码器
+    ///
+    /// 预处理与 CLIP 一致：短边缩放到输入边长后居中裁剪，按通道减均值除标准差，NCHW 布局
+    pub struct ImageEncoder {
+        session: Session,
+        input_size: u32,
+    }
+
+    impl ImageEncoder {
+        pub fn load(model_path: &Path, input_size: u32) -> Result<Self> {
+            let session = Session::builder()
+                .and_then(|b| b.with_intra_threads(1))
+                .and_then(|b| b.commit_from_file(model_path))
+                .context(format!("加载图像向量模型失败: {}", model_path.display()))?;
+            Ok(Self { session, input_size })
+        }
+
+        pub fn encode(&mut self, image: &DynamicImage) -> Result<Vec<f32>> {
+            let size = self.input_size;
+            let (width, height) = (image.width().max(1), image.height().max(1));
+            let scale = size as f32 / width.min(height) as f32;
+            let resized_width = ((width as f32 * scale).round() as u32).max(size);
+            let resized_height = ((height as f32 * scale).round() as u32).max(size);
+            let cropped = image
+                .resize_exact(resized_width, resized_height, FilterType::CatmullRom)
+                .crop_imm((resized_width - size) / 2, (resized_height - size) / 2, size, size)
+                .to_rgb8();
+
+            let plane = (size * size) as usize;
+            let mut input = vec![0f32; plane * 3];
+            for (i, pixel) in cropped.pixels().enumerate() {
+                for c in 0..3 {
+                    input[c * plane + i] = (pixel[c] as f32 / 255.0 - MEAN[c]) / STD[c];
+                }
+            }
+            let tensor = Tensor::from_array(([1usize, 3, size as usize, size as usize], input))?;
+            let outputs = self.session.run(ort::inputs![tensor])?;
+            let (_, values) = outputs[0].try_extract_tensor::<f32>()?;
+            let mut embedding = values.to_vec();
+            l2_normalize(&mut embedding);
+            Ok(embedding)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_embeddings() {
+        let dir = std::env::temp_dir().join(format!("video-parse-embeddings-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut embedding = vec![3.0, 4.0];
+        l2_normalize(&mut embedding);
+        assert_eq!(embedding, vec![0.6, 0.8]);
+
+        let scene = SceneEmbedding { scene_id: 0, keyframe_file: "keyframe_0000.jpg".to_string(), embedding };
+        let index = write_embeddings(&dir, "clip-vit-b32", std::slice::from_ref(&scene)).unwrap();
+        assert_eq!(index.dimensions, 2);
+        assert_eq!(index.count, 1);
+
+        let content = std::fs::read_to_string(dir.join(EMBEDDINGS_FILE)).unwrap();
+        let line: SceneEmbedding = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        assert_eq!(line, scene);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
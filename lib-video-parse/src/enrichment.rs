@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::time::Duration;
+use crate::metadata::SceneMetadata;
+use crate::keyframe_image::KeyframeFormat;
+
+/// 外部模型标注配置
+///
+/// 设置 `endpoint` 后，把关键帧按批 POST 到该地址，并把返回的描述和标签合并到场景元数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnrichmentConfig {
+    /// 标注服务地址（未设置时不标注）
+    pub endpoint: Option<String>,
+    /// 请求附带的头（例如 Authorization）
+    pub headers: Vec<(String, String)>,
+    /// 每个请求包含的关键帧数
+    pub batch_size: usize,
+    /// 单个请求的超时时间
+    pub timeout: Duration,
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            headers: Vec::new(),
+            batch_size: 4,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl EnrichmentConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.endpoint.is_some()
+    }
+}
+
+/// 发送给标注服务的请求
+#[derive(Debug, Serialize)]
+pub struct EnrichmentRequest<'a> {
+    pub job_id: &'a str,
+    pub scenes: Vec<EnrichmentScene<'a>>,
+}
+
+/// 请求中的单个场景
+#[derive(Debug, Serialize)]
+pub struct EnrichmentScene<'a> {
+    pub scene_id: usize,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub keyframe_file: &'a str,
+    /// 关键帧图片 MIME 类型
+    pub mime_type: &'static str,
+    /// Base64 编码的关键帧图片
+    pub image: String,
+    /// OCR 识别出的文字（启用 OCR 时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<&'a str>,
+}
+
+/// 标注服务的响应
+#[derive(Debug, Default, Deserialize)]
+pub struct EnrichmentResponse {
+    #[serde(default)]
+    pub scenes: Vec<SceneEnrichment>,
+}
+
+/// 单个场景的标注结果
+///
+/// `caption` 和 `labels` 以外的字段原样保存到 `SceneMetadata.attributes`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SceneEnrichment {
+    pub scene_id: usize,
+    #[serde(default)]
+    pub caption: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(flatten)]
+    pub attributes: Map<String, Value>,
+}
+
+/// 把标注结果合并到场景元数据，返回合并的场景数（未知的 scene_id 被忽略）
+pub fn merge(scenes: &mut [SceneMetadata], results: Vec<SceneEnrichment>) -> usize {
+    let mut merged = 0;
+    for result in results {
+        let Some(scene) = scenes.iter_mut().find(|s| s.scene_id == result.scene_id) else {
+            tracing::debug!(scene_id = result.scene_id, "标注结果中的场景不存在，忽略");
+            continue;
+        };
+        if result.caption.is_some() {
+            scene.caption = result.caption;
+        }
+        for label in result.labels {
+            if !scene.labels.contains(&label) {
+                scene.labels.push(label);
+            }
+        }
+        scene.attributes.extend(result.attributes);
+        merged += 1;
+    }
+    merged
+}
+
+/// 调用外部标注服务（VLM/LLM 等）为关键帧生成描述和标签
+///
+/// 请求和响应格式与具体模型无关，见 docs/configuration.md。单个批次失败只记录警告。返回标注的场景数
+pub async fn enrich_scenes(
+    config: &EnrichmentConfig,
+    job_id: &str,
+    output_dir: &Path,
+    scenes: &mut [SceneMetadata],
+) -> Result<usize> {
+    let Some(ref endpoint) = config.endpoint else {
+        return Ok(0);
+    };
+    let client = reqwest::Client::new();
+    let mut enriched = 0;
+    let ids: Vec<usize> = scenes.iter().map(|s| s.scene_id).collect();
+    for batch in ids.chunks(config.batch_size.max(1)) {
+        match request_batch(&client, config, endpoint, job_id, output_dir, scenes, batch).await {
+            Ok(results) => enriched += merge(scenes, results),
+            Err(e) => {
+                tracing::warn!(scenes = ?batch, error = %e, "⚠️  [关键帧标注] 标注请求失败");
+            }
+        }
+    }
+    Ok(enriched)
+}
+
+async fn request_batch(
+    client: &reqwest::Client,
+    config: &EnrichmentConfig,
+    endpoint: &str,
+    job_id: &str,
+    output_dir: &Path,
+    scenes: &[SceneMetadata],
+    batch: &[usize],
+) -> Result<Vec<SceneEnrichment>> {
+    let mut request = EnrichmentRequest { job_id, scenes: Vec::with_capacity(batch.len()) };
+    for scene in scenes.iter().filter(|s| batch.contains(&s.scene_id)) {
+        let keyframe_path = output_dir.join(&scene.keyframe_file);
+        let image = tokio::fs::read(&keyframe_path)
+            .await
+            .context(format!("读取关键帧失败: {}", keyframe_path.display()))?;
+        request.scenes.push(EnrichmentScene {
+            scene_id: scene.scene_id,
+            start_time: scene.start_time,
+            end_time: scene.end_time,
+            keyframe_file: &scene.keyframe_file,
+            mime_type: KeyframeFormat::from_file_name(&scene.keyframe_file).mime_type(),
+            image: base64::engine::general_purpose::STANDARD.encode(image),
+            text: scene.ocr_text.as_deref(),
+        });
+    }
+
+    let mut builder = client.post(endpoint).json(&request).timeout(config.timeout);
+    for (key, value) in &config.headers {
+        builder = builder.header(key, value);
+    }
+    let response = builder.send().await.context("标注服务请求失败")?;
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        anyhow::bail!("标注服务返回错误状态: {} - {}", status, error_text);
+    }
+    let response: EnrichmentResponse = response.json().await.context("解析标注服务响应失败")?;
+    Ok(response.scenes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_enrichment() {
+        let mut scenes = vec![SceneMetadata {
+            labels: vec!["outdoor".to_string()],
+            ..SceneMetadata::fixture(0, 0.0, 4.0)
+        }];
+        let response: EnrichmentResponse = serde_json::from_str(
+            r#"{"scenes":[
+                {"scene_id":0,"caption":"两个人在海边散步","labels":["outdoor","beach"],"mood":"calm"},
+                {"scene_id":9,"caption":"unknown"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(merge(&mut scenes, response.scenes), 1);
+        assert_eq!(scenes[0].caption.as_deref(), Some("两个人在海边散步"));
+        assert_eq!(scenes[0].labels, vec!["outdoor", "beach"]);
+        assert_eq!(scenes[0].attributes.get("mood"), Some(&Value::from("calm")));
+    }
+}
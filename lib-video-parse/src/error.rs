@@ -0,0 +1,190 @@
+use ffmpeg_next as ffmpeg;
+use crate::failure::StageFailure;
+use crate::preflight::Rejection;
+use crate::watchdog::StageTimeoutError;
+
+/// 库公开接口（`process_video`、`VideoProcessor`、`OssClient`、`SceneDetector`）的结果类型
+pub type Result<T, E = VideoParseError> = std::result::Result<T, E>;
+
+/// 存储错误的底层原因（OSS SDK 或 HTTP 请求错误）
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 库公开接口的错误类型
+///
+/// 显示为简短的错误信息，底层原因可通过 `source()` 获取。
+/// 下游可以按变体区分失败原因，例如下载失败时重试、没有视频流时直接放弃
+#[derive(Debug, thiserror::Error)]
+pub enum VideoParseError {
+    /// 输入无效：文件不存在、格式不支持等
+    #[error("{0}")]
+    InvalidInput(String),
+    /// 输入文件没有视频流
+    #[error("未找到视频流")]
+    NoVideoStream,
+    /// 配置错误：缺少凭证、无效的 endpoint 等
+    #[error("{0}")]
+    Config(String),
+    /// FFmpeg 打开或解码视频失败
+    #[error("{message}")]
+    Decode {
+        message: String,
+        #[source]
+        source: ffmpeg::Error,
+    },
+    /// ffmpeg 命令行工具（音频提取等）执行失败
+    #[error("{0}")]
+    Ffmpeg(String),
+    /// 本地文件读写失败
+    #[error("{message}")]
+    Io {
+        message: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// 从 OSS 下载输入文件失败
+    #[error("{message}")]
+    Download {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    /// 上传处理结果到 OSS 失败
+    #[error("{message}")]
+    Upload {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    /// 其他 OSS 请求失败（查询对象信息等）
+    #[error("{message}")]
+    Storage {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    /// 处理阶段超时
+    #[error(transparent)]
+    Timeout(#[from] StageTimeoutError),
+    /// 处理被取消
+    #[error("处理已取消")]
+    Cancelled,
+    /// 输入超出大小或时长上限，拒绝处理
+    #[error(transparent)]
+    Rejected(#[from] Rejection),
+    /// 其他错误（外部服务、模型推理等）
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl VideoParseError {
+    pub(crate) fn decode(message: impl Into<String>, source: ffmpeg::Error) -> Self {
+        Self::Decode { message: message.into(), source }
+    }
+
+    pub(crate) fn io(message: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io { message: message.into(), source }
+    }
+
+    pub(crate) fn storage(message: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        Self::Storage { message: message.into(), source: Some(source.into()) }
+    }
+
+    /// 没有底层错误的存储错误（例如返回了错误状态码）
+    pub(crate) fn storage_status(message: impl Into<String>) -> Self {
+        Self::Storage { message: message.into(), source: None }
+    }
+
+    /// 下载过程中的 OSS 请求错误归为下载失败，其他错误不变
+    pub(crate) fn into_download(self) -> Self {
+        match self {
+            Self::Storage { message, source } => Self::Download { message, source },
+            other => other,
+        }
+    }
+
+    /// 上传过程中的 OSS 请求错误归为上传失败，其他错误不变
+    pub(crate) fn into_upload(self) -> Self {
+        match self {
+            Self::Storage { message, source } => Self::Upload { message, source },
+            other => other,
+        }
+    }
+
+    /// 错误分类名（`download_failed`、`no_video_stream` 等），便于下游记录日志和指标
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "invalid_input",
+            Self::NoVideoStream => "no_video_stream",
+            Self::Config(_) => "config",
+            Self::Decode { .. } => "decode_failed",
+            Self::Ffmpeg(_) => "ffmpeg_failed",
+            Self::Io { .. } => "io",
+            Self::Download { .. } => "download_failed",
+            Self::Upload { .. } => "upload_failed",
+            Self::Storage { .. } => "storage",
+            Self::Timeout(_) => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::Rejected(_) => "rejected",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// 阶段超时时返回超时信息
+    pub fn as_timeout(&self) -> Option<&StageTimeoutError> {
+        match self {
+            Self::Timeout(timeout) => Some(timeout),
+            _ => None,
+        }
+    }
+}
+
+/// 内部流水线使用 `anyhow`，在公开接口处转换为具体的错误类型
+///
+/// 去掉 [`StageFailure`] 包装后，原本就是 `VideoParseError` 或超时的错误还原为对应的分类，其余归为 `Other`
+impl From<anyhow::Error> for VideoParseError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<StageFailure>() {
+            Ok(failure) => failure.error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<VideoParseError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        match error.downcast::<StageTimeoutError>() {
+            Ok(timeout) => Self::Timeout(timeout),
+            Err(error) => Self::Other(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::failure::Progress;
+    use crate::watchdog::Stage;
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_anyhow() {
+        let error = Progress::new("extract_frames")
+            .fail(VideoParseError::NoVideoStream.into());
+        let error = VideoParseError::from(error);
+        assert!(matches!(error, VideoParseError::NoVideoStream));
+        assert_eq!(error.to_string(), "未找到视频流");
+        assert_eq!(error.kind(), "no_video_stream");
+
+        let error = VideoParseError::storage_status("下载文件失败: status=503").into_download();
+        assert!(matches!(error, VideoParseError::Download { .. }));
+        assert_eq!(error.kind(), "download_failed");
+        assert!(matches!(VideoParseError::Cancelled.into_upload(), VideoParseError::Cancelled));
+
+        let timeout = StageTimeoutError { stage: Stage::Extract, timeout: Duration::from_secs(10) };
+        let error = VideoParseError::from(anyhow::Error::from(timeout));
+        assert_eq!(error.as_timeout().unwrap().stage, Stage::Extract);
+
+        let error = VideoParseError::from(anyhow::anyhow!("模型推理失败"));
+        assert!(matches!(error, VideoParseError::Other(_)));
+        assert_eq!(error.to_string(), "模型推理失败");
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::metadata::SceneMetadata;
+
+/// 人脸检测配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceConfig {
+    /// 是否检测关键帧中的人脸
+    pub enabled: bool,
+    /// UltraFace ONNX 模型路径（version-RFB-320.onnx 或 version-slim-320.onnx）
+    pub model_path: Option<PathBuf>,
+    /// 最低置信度
+    pub min_confidence: f32,
+}
+
+impl Default for FaceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_path: None,
+            min_confidence: 0.7,
+        }
+    }
+}
+
+/// 人脸边界框（坐标为相对关键帧宽高的比例，0.0-1.0）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FaceBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// 置信度
+    pub confidence: f32,
+}
+
+impl FaceBox {
+    fn area(&self) -> f32 {
+        self.width.max(0.0) * self.height.max(0.0)
+    }
+
+    /// 与另一个边界框的交并比
+    pub fn iou(&self, other: &FaceBox) -> f32 {
+        let x1 = self.x.max(other.x);
+        let y1 = self.y.max(other.y);
+        let x2 = (self.x + self.width).min(other.x + other.width);
+        let y2 = (self.y + self.height).min(other.y + other.height);
+        let intersection = (x2 - x1).max(0.0) * (y2 - y1).max(0.0);
+        let union = self.area() + other.area() - intersection;
+        if union <= 0.0 { 0.0 } else { intersection / union }
+    }
+}
+
+/// 整个视频的人脸汇总
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaceSummary {
+    /// 关键帧中有人脸的场景编号
+    pub scenes_with_faces: Vec<usize>,
+    /// 单个关键帧中的最多人脸数
+    pub max_face_count: usize,
+}
+
+/// 汇总各场景的人脸检测结果（没有场景被检测时返回 None）
+pub fn summarize(scenes: &[SceneMetadata]) -> Option<FaceSummary> {
+    let mut summary = FaceSummary { scenes_with_faces: Vec::new(), max_face_count: 0 };
+    let mut detected = false;
+    for scene in scenes {
+        let Some(face_count) = scene.face_count else {
+            continue;
+        };
+        detected = true;
+        if face_count > 0 {
+            summary.scenes_with_faces.push(scene.scene_id);
+        }
+        summary.max_face_count = summary.max_face_count.max(face_count);
+    }
+    detected.then_some(summary)
+}
+
+/// 非极大值抑制：按置信度从高到低保留，去掉与已保留框重叠超过 `iou_threshold` 的框
+pub fn non_max_suppression(mut boxes: Vec<FaceBox>, iou_threshold: f32) -> Vec<FaceBox> {
+    boxes.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut kept: Vec<FaceBox> = Vec::new();
+    for candidate in boxes {
+        if kept.iter().all(|k| k.iou(&candidate) <= iou_threshold) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// 检测每个场景关键帧中的人脸，写入 `SceneMetadata.face_count` 和 `faces`
+///
+/// 使用 UltraFace ONNX 模型，需要以 `--features faces` 编译，并能加载 onnxruntime 动态库
+/// （通过 ORT_DYLIB_PATH 指定路径）。去重合并的场景复用同一关键帧的结果，单帧检测失败只记录警告。返回检测到人脸的场景数
+#[cfg(feature = "faces")]
+pub fn detect_scenes(config: &FaceConfig, output_dir: &Path, scenes: &mut [SceneMetadata]) -> Result<usize> {
+    use anyhow::Context;
+
+    let model_path = config.model_path.as_ref().context("未配置人脸检测模型路径（face_model）")?;
+    let mut detector = ultraface::UltraFace::load(model_path)?;
+    let mut computed: std::collections::HashMap<String, Vec<FaceBox>> = std::collections::HashMap::new();
+    let mut with_faces = 0;
+    for scene in scenes.iter_mut() {
+        if let Some(faces) = computed.get(&scene.keyframe_file) {
+            with_faces += usize::from(!faces.is_empty());
+            scene.face_count = Some(faces.len());
+            scene.faces = faces.clone();
+            continue;
+        }
+        let keyframe_path = output_dir.join(&scene.keyframe_file);
+        let image = match image::open(&keyframe_path) {
+            Ok(image) => image,
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [人脸检测] 读取关键帧失败");
+                continue;
+            }
+        };
+        match detector.detect(&image, config.min_confidence) {
+            Ok(faces) => {
+                if !faces.is_empty() {
+                    with_faces += 1;
+                }
+                computed.insert(scene.keyframe_file.clone(), faces.clone());
+                scene.face_count = Some(faces.len());
+                scene.faces = faces;
+            }
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [人脸检测] 关键帧人脸检测失败");
+            }
+        }
+    }
+    Ok(with_faces)
+}
+
+/// 未启用 `faces` feature 时跳过人脸检测
+#[cfg(not(feature = "faces"))]
+pub fn detect_scenes(_config: &FaceConfig, _output_dir: &Path, _scenes: &mut [SceneMetadata]) -> Result<usize> {
+    tracing::warn!("⚠️  已启用人脸检测，但未启用 faces feature，跳过");
+    Ok(0)
+}
+
+#[cfg(feature = "faces")]
+mod ultraface {
+    use super::{non_max_suppression, FaceBox};
+    use anyhow::{Context, Result};
+    use image::{imageops::FilterType, DynamicImage};
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use std::path::Path;
+
+    /// 模型输入尺寸
+    const INPUT_WIDTH: u32 = 320;
+    const INPUT_HEIGHT: u32 = 240;
+    /// NMS 交并比阈值
+    const IOU_THRESHOLD: f32 = 0.3;
+
+    /// UltraFace 人脸检测模型
+    ///
+    /// 输入 1x3x240x320（RGB，(x - 127) / 128），输出 scores [1, N, 2] 和 boxes [1, N, 4]（归一化的 x1, y1, x2, y2）
+    pub struct UltraFace {
+        session: Session,
+    }
+
+    impl UltraFace {
+        pub fn load(model_path: &Path) -> Result<Self> {
+            let session = Session::builder()
+                .and_then(|b| b.with_intra_threads(1))
+                .and_then(|b| b.commit_from_file(model_path))
+                .context(format!("加载人脸检测模型失败: {}", model_path.display()))?;
+            Ok(Self { session })
+        }
+
+        pub fn detect(&mut self, image: &DynamicImage, min_confidence: f32) -> Result<Vec<FaceBox>> {
+            let resized = image
+                .resize_exact(INPUT_WIDTH, INPUT_HEIGHT, FilterType::Triangle)
+                .to_rgb8();
+            let plane = (INPUT_WIDTH * INPUT_HEIGHT) as usize;
+            let mut input = vec![0f32; plane * 3];
+            for (i, pixel) in resized.pixels().enumerate() {
+                for c in 0..3 {
+                    input[c * plane + i] = (pixel[c] as f32 - 127.0) / 128.0;
+                }
+            }
+            let tensor = Tensor::from_array(([1usize, 3, INPUT_HEIGHT as usize, INPUT_WIDTH as usize], input))?;
+
+            let outputs = self.session.run(ort::inputs![tensor])?;
+            let (_, scores) = outputs[0].try_extract_tensor::<f32>()?;
+            let (_, boxes) = outputs[1].try_extract_tensor::<f32>()?;
+
+            let candidates = scores
+                .chunks_exact(2)
+                .zip(boxes.chunks_exact(4))
+                .filter(|(score, _)| score[1] >= min_confidence)
+                .map(|(score, b)| {
+                    let (x1, y1) = (b[0].clamp(0.0, 1.0), b[1].clamp(0.0, 1.0));
+                    let (x2, y2) = (b[2].clamp(0.0, 1.0), b[3].clamp(0.0, 1.0));
+                    FaceBox {
+                        x: x1,
+                        y: y1,
+                        width: x2 - x1,
+                        height: y2 - y1,
+                        confidence: score[1],
+                    }
+                })
+                .collect();
+            Ok(non_max_suppression(candidates, IOU_THRESHOLD))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(x: f32, y: f32, confidence: f32) -> FaceBox {
+        FaceBox { x, y, width: 0.2, height: 0.2, confidence }
+    }
+
+    #[test]
+    fn test_non_max_suppression() {
+        let boxes = vec![face(0.1, 0.1, 0.8), face(0.11, 0.1, 0.95), face(0.6, 0.5, 0.9)];
+        let kept = non_max_suppression(boxes, 0.3);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].confidence, 0.95);
+        assert_eq!(kept[1].confidence, 0.9);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let scene = |scene_id: usize, face_count: Option<usize>| SceneMetadata { face_count, ..SceneMetadata::fixture(scene_id, 0.0, 1.0) };
+        assert_eq!(summarize(&[scene(0, None)]), None);
+        let summary = summarize(&[scene(0, Some(0)), scene(1, Some(3)), scene(2, None), scene(3, Some(1))]).unwrap();
+        assert_eq!(summary, FaceSummary { scenes_with_faces: vec![1, 3], max_face_count: 3 });
+    }
+}
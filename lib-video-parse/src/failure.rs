@@ -0,0 +1,213 @@
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use crate::watchdog;
+use crate::error::VideoParseError;
+use crate::progress::{ProgressReporter, StageProgress};
+
+/// 处理失败时已完成的部分结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PartialResult {
+    /// 已完成的阶段
+    pub completed_stages: Vec<&'static str>,
+    /// 输出目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    /// 检测到的场景数（场景检测完成后）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_count: Option<usize>,
+    /// 已保存的关键帧文件（关键帧提取完成后）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keyframes: Vec<String>,
+}
+
+/// 处理进度：记录当前阶段和已完成的部分结果，失败时用于构建失败报告
+pub struct Progress {
+    stage: &'static str,
+    pub partial: PartialResult,
+    reporter: Option<Arc<dyn ProgressReporter>>,
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("stage", &self.stage)
+            .field("partial", &self.partial)
+            .field("reporter", &self.reporter.is_some())
+            .finish()
+    }
+}
+
+impl Progress {
+    pub fn new(stage: &'static str) -> Self {
+        Self { stage, partial: PartialResult::default(), reporter: None }
+    }
+
+    /// 阶段变化和阶段内进度同时通知 `reporter`
+    pub fn with_reporter(mut self, reporter: Option<Arc<dyn ProgressReporter>>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// 进度接收方，供阻塞线程池中的阶段报告阶段内进度
+    pub fn reporter(&self) -> Option<Arc<dyn ProgressReporter>> {
+        self.reporter.clone()
+    }
+
+    /// 当前阶段
+    pub fn stage(&self) -> &'static str {
+        self.stage
+    }
+
+    /// 开始下一个阶段，当前阶段记为已完成
+    pub fn begin(&mut self, stage: &'static str) {
+        let completed = std::mem::replace(&mut self.stage, stage);
+        self.partial.completed_stages.push(completed);
+        if let Some(ref reporter) = self.reporter {
+            reporter.stage_started(stage);
+        }
+    }
+
+    /// 报告阶段内进度
+    pub fn report(&self, progress: StageProgress) {
+        if let Some(ref reporter) = self.reporter {
+            reporter.update(&progress);
+        }
+    }
+
+    /// 把错误包装为 [`StageFailure`]，带上当前阶段和部分结果
+    pub fn fail(self, error: anyhow::Error) -> anyhow::Error {
+        StageFailure { stage: self.stage, partial: self.partial, error }.into()
+    }
+}
+
+/// 视频处理失败：失败的阶段和失败前已完成的部分结果
+///
+/// 显示为原始错误信息，可以从 `anyhow::Error` 中 downcast 出来
+#[derive(Debug)]
+pub struct StageFailure {
+    pub stage: &'static str,
+    pub partial: PartialResult,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for StageFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for StageFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// 失败回调中的错误信息
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureError {
+    /// 错误码：`<stage>_timeout`、`<stage>_cancelled` 或 `<stage>_failed`
+    pub code: String,
+    /// 失败的阶段
+    pub stage: &'static str,
+    /// 错误信息
+    pub message: String,
+}
+
+/// 重试建议
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetryInfo {
+    /// 重试是否可能成功（超时、网络和外部服务错误）
+    pub retryable: bool,
+    /// 建议的重试间隔（秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+/// 处理失败报告，作为失败回调的内容
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureReport {
+    pub error: FailureError,
+    pub partial: PartialResult,
+    pub retry: RetryInfo,
+}
+
+/// 依赖网络或外部服务、重试可能成功的阶段
+const RETRYABLE_STAGES: &[&str] = &["download", "upload", "enrichment"];
+
+impl FailureReport {
+    /// 从错误构建失败报告
+    ///
+    /// 错误中带有 [`StageFailure`] 时使用其中的阶段和部分结果，否则使用调用方所在的阶段 `stage`
+    pub fn new(stage: &'static str, error: &anyhow::Error) -> Self {
+        let (stage, partial) = match error.downcast_ref::<StageFailure>() {
+            Some(failure) => (failure.stage, failure.partial.clone()),
+            None => (stage, PartialResult::default()),
+        };
+        let timeout = watchdog::as_timeout(error).is_some();
+        let cancelled = is_cancelled(error);
+        let retryable = !cancelled && (timeout || RETRYABLE_STAGES.contains(&stage));
+        let kind = if timeout {
+            "timeout"
+        } else if cancelled {
+            "cancelled"
+        } else {
+            "failed"
+        };
+        Self {
+            error: FailureError {
+                code: format!("{}_{}", stage, kind),
+                stage,
+                message: error.to_string(),
+            },
+            partial,
+            retry: RetryInfo {
+                retryable,
+                retry_after_secs: retryable.then_some(if timeout { 300 } else { 60 }),
+            },
+        }
+    }
+}
+
+/// 判断错误是否为处理被取消（包括处理阶段失败中包装的取消）
+fn is_cancelled(error: &anyhow::Error) -> bool {
+    let error = match error.downcast_ref::<StageFailure>() {
+        Some(failure) => &failure.error,
+        None => error,
+    };
+    matches!(error.downcast_ref::<VideoParseError>(), Some(VideoParseError::Cancelled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watchdog::{Stage, StageTimeoutError};
+    use std::time::Duration;
+
+    #[test]
+    fn test_failure_report() {
+        let mut progress = Progress::new("extract_frames");
+        progress.begin("detect_scenes");
+        progress.partial.scene_count = Some(3);
+        progress.begin("keyframes");
+        let error = progress.fail(anyhow::anyhow!("磁盘已满"));
+        assert_eq!(error.to_string(), "磁盘已满");
+
+        let report = FailureReport::new("process", &error);
+        assert_eq!(report.error.code, "keyframes_failed");
+        assert_eq!(report.partial.completed_stages, vec!["extract_frames", "detect_scenes"]);
+        assert_eq!(report.partial.scene_count, Some(3));
+        assert!(!report.retry.retryable);
+
+        let timeout = StageTimeoutError { stage: Stage::Download, timeout: Duration::from_secs(10) };
+        let error = Progress::new("extract_frames").fail(timeout.into());
+        assert!(watchdog::as_timeout(&error).is_some());
+        let report = FailureReport::new("process", &error);
+        assert_eq!(report.error.code, "extract_frames_timeout");
+        assert_eq!(report.retry.retry_after_secs, Some(300));
+
+        let report = FailureReport::new("download", &anyhow::anyhow!("连接被重置"));
+        assert_eq!(report.error.code, "download_failed");
+        assert!(report.retry.retryable);
+    }
+}
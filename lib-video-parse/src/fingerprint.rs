@@ -0,0 +1,191 @@
+//! 视频指纹：按场景顺序记录关键帧的感知哈希和亮度直方图，写入 fingerprint.json，
+//! 用于识别不同 bucket 中重复上传的同一内容（转码、改分辨率后仍能匹配）
+
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::metadata::VideoMetadata;
+use crate::scene_detector::{DetectionAlgorithm, FrameFeatures};
+use crate::simd;
+
+/// 指纹文件名
+pub const FINGERPRINT_FILE: &str = "fingerprint.json";
+/// 指纹格式版本，不同版本的指纹不能比较
+pub const FINGERPRINT_VERSION: u32 = 1;
+/// 亮度直方图的分桶数
+const HISTOGRAM_BINS: usize = 16;
+/// 两个场景的相似度达到该值时视为同一镜头
+const SCENE_MATCH_THRESHOLD: f64 = 0.85;
+
+/// 视频指纹
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoFingerprint {
+    pub version: u32,
+    /// 视频总时长（秒）
+    pub duration: f64,
+    /// 按时间顺序的场景指纹
+    pub scenes: Vec<SceneFingerprint>,
+}
+
+/// 单个场景的指纹
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneFingerprint {
+    /// 场景持续时间（秒），比较时作为权重
+    pub duration: f64,
+    /// 关键帧的 64 位感知哈希（16 位十六进制）
+    pub hash: String,
+    /// 关键帧的亮度直方图（16 个分桶，按占比量化到 0-255）
+    pub histogram: Vec<u8>,
+}
+
+impl SceneFingerprint {
+    /// 由关键帧计算场景指纹
+    pub fn from_image(image: &DynamicImage, duration: f64) -> Self {
+        let hash = FrameFeatures::hashed(image, DetectionAlgorithm::PHash).hash().unwrap_or_default();
+        let gray = image.thumbnail(64, 64).to_luma8();
+        let counts = simd::histogram(gray.as_raw());
+        let total = gray.as_raw().len().max(1) as f64;
+        let histogram = counts
+            .chunks(256 / HISTOGRAM_BINS)
+            .map(|bin| (bin.iter().sum::<u32>() as f64 / total * 255.0).round() as u8)
+            .collect();
+        Self { duration, hash: format!("{:016x}", hash), histogram }
+    }
+
+    /// 与另一个场景的相似度（0 到 1）：感知哈希相似度和直方图交集各占一半
+    pub fn similarity(&self, other: &SceneFingerprint) -> f64 {
+        let (Ok(a), Ok(b)) = (u64::from_str_radix(&self.hash, 16), u64::from_str_radix(&other.hash, 16)) else {
+            return 0.0;
+        };
+        let hash_similarity = 1.0 - (a ^ b).count_ones() as f64 / 64.0;
+        let intersection: u32 = self.histogram.iter().zip(&other.histogram).map(|(&x, &y)| x.min(y) as u32).sum();
+        let total = self.histogram.iter().map(|&x| x as u32).sum::<u32>()
+            .max(other.histogram.iter().map(|&x| x as u32).sum::<u32>());
+        let histogram_similarity = if total == 0 { 0.0 } else { intersection as f64 / total as f64 };
+        (hash_similarity + histogram_similarity) / 2.0
+    }
+}
+
+/// 由输出目录中的关键帧计算视频指纹，读取失败的关键帧跳过
+pub fn compute(metadata: &VideoMetadata, output_dir: &Path) -> VideoFingerprint {
+    let scenes = metadata
+        .scenes
+        .iter()
+        .filter_map(|scene| match image::open(output_dir.join(&scene.keyframe_file)) {
+            Ok(image) => Some(SceneFingerprint::from_image(&image, scene.duration)),
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [视频指纹] 读取关键帧失败，跳过");
+                None
+            }
+        })
+        .collect();
+    VideoFingerprint { version: FINGERPRINT_VERSION, duration: metadata.total_duration, scenes }
+}
+
+/// 计算视频指纹并写入 `output_dir/fingerprint.json`
+pub fn write(metadata: &VideoMetadata, output_dir: &Path) -> Result<VideoFingerprint> {
+    let fingerprint = compute(metadata, output_dir);
+    let path = output_dir.join(FINGERPRINT_FILE);
+    let json = serde_json::to_string(&fingerprint).context("序列化视频指纹失败")?;
+    std::fs::write(&path, json).with_context(|| format!("写入视频指纹失败: {}", path.display()))?;
+    Ok(fingerprint)
+}
+
+/// 读取 fingerprint.json
+pub fn load(path: &Path) -> Result<VideoFingerprint> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("读取视频指纹失败: {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("解析视频指纹失败: {}", path.display()))
+}
+
+/// 比较两个视频指纹，返回相似度（0 到 1）
+///
+/// 按场景顺序找出相似度不低于阈值的场景的最长公共子序列（按场景时长加权），
+/// 结果为匹配场景分别占两个视频总时长比例的平均值。片头片尾被裁剪、个别场景边界不同时相似度按比例降低；
+/// 版本不同或任一指纹没有场景时返回 0
+pub fn compare_fingerprints(a: &VideoFingerprint, b: &VideoFingerprint) -> f64 {
+    if a.version != b.version || a.scenes.is_empty() || b.scenes.is_empty() {
+        return 0.0;
+    }
+    let total = |scenes: &[SceneFingerprint]| scenes.iter().map(|s| s.duration.max(0.0)).sum::<f64>();
+    let (total_a, total_b) = (total(&a.scenes), total(&b.scenes));
+    if total_a <= 0.0 || total_b <= 0.0 {
+        return 0.0;
+    }
+
+    // best[i][j]：a 的前 i 个场景与 b 的前 j 个场景匹配的最大权重
+    let (n, m) = (a.scenes.len(), b.scenes.len());
+    let mut best = vec![vec![0.0f64; m + 1]; n + 1];
+    for i in 1..=n {
+        for j in 1..=m {
+            let (scene_a, scene_b) = (&a.scenes[i - 1], &b.scenes[j - 1]);
+            let mut value = best[i - 1][j].max(best[i][j - 1]);
+            if scene_a.similarity(scene_b) >= SCENE_MATCH_THRESHOLD {
+                let weight = (scene_a.duration.max(0.0) / total_a + scene_b.duration.max(0.0) / total_b) / 2.0;
+                value = value.max(best[i - 1][j - 1] + weight);
+            }
+            best[i][j] = value;
+        }
+    }
+    best[n][m].clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    /// 4x3 个色块组成的画面，色块亮度由 seed 决定
+    fn frame(seed: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(96, 54, |x, y| {
+            let cell = (y / 18) * 4 + x / 24;
+            let v = (cell.wrapping_mul(seed).wrapping_mul(2654435761) >> 24) as u8;
+            Rgb([v, v, v])
+        }))
+    }
+
+    fn fingerprint(scenes: &[(u32, f64)]) -> VideoFingerprint {
+        VideoFingerprint {
+            version: FINGERPRINT_VERSION,
+            duration: scenes.iter().map(|(_, d)| d).sum(),
+            scenes: scenes.iter().map(|&(seed, duration)| SceneFingerprint::from_image(&frame(seed), duration)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_fingerprints() {
+        let original = fingerprint(&[(3, 4.0), (11, 6.0), (29, 10.0)]);
+        assert!((compare_fingerprints(&original, &original) - 1.0).abs() < 1e-9);
+
+        // 重新编码（缩小分辨率）后仍然匹配
+        let resized = VideoFingerprint {
+            scenes: [3, 11, 29].iter().zip([4.0, 6.0, 10.0])
+                .map(|(&seed, duration)| SceneFingerprint::from_image(&frame(seed).thumbnail(48, 27), duration))
+                .collect(),
+            ..original.clone()
+        };
+        assert!(compare_fingerprints(&original, &resized) > 0.99);
+
+        // 裁掉片头后按剩余时长比例匹配
+        let trimmed = fingerprint(&[(11, 6.0), (29, 10.0)]);
+        let similarity = compare_fingerprints(&original, &trimmed);
+        assert!((similarity - (16.0 / 20.0 + 1.0) / 2.0).abs() < 1e-9, "{}", similarity);
+
+        // 内容不同（亮度反转）的视频不匹配
+        let other = VideoFingerprint {
+            scenes: [3, 11, 29].iter().zip([4.0, 6.0, 10.0])
+                .map(|(&seed, duration)| {
+                    let mut image = frame(seed);
+                    image.invert();
+                    SceneFingerprint::from_image(&image, duration)
+                })
+                .collect(),
+            ..original.clone()
+        };
+        assert_eq!(compare_fingerprints(&original, &other), 0.0);
+        assert_eq!(compare_fingerprints(&original, &VideoFingerprint { version: 0, ..original.clone() }), 0.0);
+
+        let json = serde_json::to_string(&original).unwrap();
+        assert_eq!(serde_json::from_str::<VideoFingerprint>(&json).unwrap(), original);
+    }
+}
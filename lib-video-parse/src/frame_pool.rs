@@ -0,0 +1,158 @@
+use ffmpeg_next as ffmpeg;
+use image::{DynamicImage, RgbImage};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 默认最多缓存的 RGB 图像缓冲区数量（1080p 约 6MB/个，控制常驻内存）
+const DEFAULT_MAX_IMAGE_BUFFERS: usize = 8;
+/// 默认最多缓存的 FFmpeg 帧数量
+const DEFAULT_MAX_VIDEO_FRAMES: usize = 4;
+
+/// 帧缓冲池
+///
+/// 复用缩放输出的 `ffmpeg::frame::Video` 和 `RgbImage` 的像素缓冲区，
+/// 避免提取循环中每帧分配/释放数 MB 的内存。
+/// 池是有上限的，超出上限归还的缓冲区直接释放
+pub struct FramePool {
+    image_buffers: Mutex<Vec<Vec<u8>>>,
+    video_frames: Mutex<Vec<ffmpeg::frame::Video>>,
+    max_image_buffers: usize,
+    max_video_frames: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl FramePool {
+    pub fn new(max_image_buffers: usize, max_video_frames: usize) -> Self {
+        Self {
+            image_buffers: Mutex::new(Vec::new()),
+            video_frames: Mutex::new(Vec::new()),
+            max_image_buffers,
+            max_video_frames,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// 进程内共享的缓冲池（服务模式下多个任务之间复用）
+    pub fn shared() -> Arc<FramePool> {
+        static SHARED: OnceLock<Arc<FramePool>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(FramePool::new(DEFAULT_MAX_IMAGE_BUFFERS, DEFAULT_MAX_VIDEO_FRAMES)))
+            .clone()
+    }
+
+    /// 取一个指定尺寸的 RGB 图像缓冲区（长度为 width * height * 3，内容未清零）
+    pub fn take_image_buffer(&self, width: u32, height: u32) -> Vec<u8> {
+        let len = width as usize * height as usize * 3;
+        let reused = {
+            let mut buffers = self.image_buffers.lock().unwrap();
+            // 优先选择容量足够的缓冲区，避免 resize 时重新分配
+            buffers
+                .iter()
+                .position(|buf| buf.capacity() >= len)
+                .map(|idx| buffers.swap_remove(idx))
+        };
+        match reused {
+            Some(mut buf) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                buf.resize(len, 0);
+                buf
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                vec![0; len]
+            }
+        }
+    }
+
+    /// 取一张 RGB 图像，像素缓冲区来自池
+    pub fn take_image(&self, width: u32, height: u32) -> RgbImage {
+        let buf = self.take_image_buffer(width, height);
+        RgbImage::from_raw(width, height, buf).expect("缓冲区长度与图像尺寸一致")
+    }
+
+    /// 归还图像的像素缓冲区（非 RGB8 图像直接释放）
+    pub fn recycle_image(&self, image: DynamicImage) {
+        if let DynamicImage::ImageRgb8(img) = image {
+            self.recycle_image_buffer(img.into_raw());
+        }
+    }
+
+    /// 批量归还图像
+    pub fn recycle_images(&self, images: impl IntoIterator<Item = DynamicImage>) {
+        for image in images {
+            self.recycle_image(image);
+        }
+    }
+
+    /// 归还像素缓冲区
+    pub fn recycle_image_buffer(&self, mut buf: Vec<u8>) {
+        let mut buffers = self.image_buffers.lock().unwrap();
+        if buffers.len() < self.max_image_buffers {
+            buf.clear();
+            buffers.push(buf);
+        }
+    }
+
+    /// 取一个指定格式和尺寸的视频帧（池中没有匹配的帧时新分配）
+    pub fn take_video_frame(&self, format: ffmpeg::format::Pixel, width: u32, height: u32) -> ffmpeg::frame::Video {
+        let reused = {
+            let mut frames = self.video_frames.lock().unwrap();
+            frames
+                .iter()
+                .position(|f| f.format() == format && f.width() == width && f.height() == height)
+                .map(|idx| frames.swap_remove(idx))
+        };
+        match reused {
+            Some(frame) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                frame
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                ffmpeg::frame::Video::new(format, width, height)
+            }
+        }
+    }
+
+    /// 归还视频帧
+    pub fn recycle_video_frame(&self, frame: ffmpeg::frame::Video) {
+        let mut frames = self.video_frames.lock().unwrap();
+        if frames.len() < self.max_video_frames {
+            frames.push(frame);
+        }
+    }
+
+    /// (命中次数, 未命中次数)
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_buffer_reused() {
+        let pool = FramePool::new(2, 1);
+        let img = pool.take_image(64, 32);
+        let ptr = img.as_raw().as_ptr();
+        pool.recycle_image(DynamicImage::ImageRgb8(img));
+
+        // 更小的图像复用同一块内存
+        let img = pool.take_image(32, 32);
+        assert_eq!(img.as_raw().as_ptr(), ptr);
+        assert_eq!(img.as_raw().len(), 32 * 32 * 3);
+        assert_eq!(pool.stats(), (1, 1));
+    }
+
+    #[test]
+    fn test_pool_is_bounded() {
+        let pool = FramePool::new(1, 1);
+        pool.recycle_image_buffer(vec![0; 16]);
+        pool.recycle_image_buffer(vec![0; 16]);
+        assert_eq!(pool.image_buffers.lock().unwrap().len(), 1);
+    }
+}
@@ -1,13 +1,27 @@
 use axum::{
-    extract::{Json, Query},
+    extract::{Json, Path, Query, Request, ws::{Message, WebSocket, WebSocketUpgrade}},
     body::Bytes,
-    http::{StatusCode, HeaderMap},
-    response::Json as ResponseJson,
+    http::{header, StatusCode, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Json as ResponseJson, Response},
 };
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
-use crate::{OssEvent, ProcessResponse, ProcessResult, OssClient, ProcessConfig, process_video, config::ConfigLoader, ExtendedConfig};
-use tracing::{info, error, warn, debug};
+use crate::watchdog::{self, with_timeout, Stage};
+use crate::{EventResult, EventStatus, OssEvent, OssEventItem, ProcessResponse, dead_letter::{self, DeadLetter}, ProcessResult, OssClient, ProcessConfig, Preset, processor, Pipeline, ProcessOptions, config::{ConfigLoader, ConfigOverrides, ExtendedConfig}, CacheEntry, ResultCache, resource::{self, format_mb}, telemetry, metrics::{self, Metrics}, vector_store, database, FailureReport, kafka, notify, JobLogSnapshot, JobLogStore, JobList, JobManager, JobState, QueueFull, preflight, output_naming::{self, OutputNaming}, progress::{self, JobProgress, ProgressEvent, ProgressReporter, ProgressStore, ProgressUnit, StageProgress, WebhookProgress}};
+use crate::url_input;
+use crate::oss_client::{UploadReport, UploadTask};
+use crate::archive::ArchiveFormat;
+use crate::preflight::Rejection;
+use crate::{VideoProcessor, KeyframeFormat, KeyframeImageConfig, VideoParseError};
+use tracing::{info, info_span, error, warn, debug, instrument, Instrument, Span};
+use tracing::field::Empty;
+
+/// 分段下载的并发请求数
+const DOWNLOAD_CONCURRENCY: usize = 4;
+/// 探测 OSS 输入时预签名 URL 的有效期（秒）
+const PROBE_URL_EXPIRES_SECS: u64 = 600;
 
 /// 处理 OSS Event 的 Handler（接受任何HTTP方法）
 /// 用于函数计算环境，兼容不同的调用方式
@@ -52,8 +66,9 @@ pub async fn handle_oss_event_any(
             (StatusCode::BAD_REQUEST, format!("解析 JSON 失败: {}", e))
         })?;
     
-    // 调用原有的处理逻辑
-    handle_oss_event_internal(event, Some(request_id.to_string())).await
+    // 调用原有的处理逻辑（没有请求 ID 时生成一个，避免不同请求的任务日志混在一起）
+    let request_id = (request_id != "unknown").then(|| request_id.to_string());
+    handle_oss_event_internal(event, request_id).await
 }
 
 /// 处理 OSS Event 的 Handler（原始版本，仅接受POST JSON）
@@ -64,9 +79,149 @@ pub async fn handle_oss_event(
 }
 
 /// 内部处理 OSS Event 的逻辑（提取公共部分）
+///
+/// 一次请求包含多个事件时按顺序逐个处理，每个事件使用 `{request_id}-{下标}` 作为任务 ID，
+/// 在响应的 `events` 中返回各事件的结果；全部事件都失败时返回最后一个失败的状态码
 async fn handle_oss_event_internal(
     event: OssEvent,
     request_id: Option<String>,
+) -> Result<ResponseJson<ProcessResponse>, (StatusCode, String)> {
+    // 尝试使用函数计算的 request_id（优先使用传入的参数，其次环境变量，最后生成）
+    // 重试时使用同一个 request_id，复用临时目录和任务日志
+    let request_id = request_id
+        .or_else(|| std::env::var("FC_REQUEST_ID").ok())
+        .unwrap_or_else(|| {
+            format!("{}_{}", 
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+                uuid::Uuid::new_v4().to_string()
+            )
+        });
+    if event.events.len() <= 1 {
+        return process_with_retry(event, request_id).await;
+    }
+
+    let total = event.events.len();
+    info!(request_id = %request_id, events = total, "📦 [OSS Event] 请求包含多个事件，逐个处理");
+    let mut results = Vec::with_capacity(total);
+    let mut last_failure = None;
+    for (index, item) in event.events.iter().enumerate() {
+        let source = format!("oss://{}/{}", item.oss.bucket.name, item.oss.object.key);
+        let single = OssEvent { events: vec![item.clone()] };
+        let result = match process_with_retry(single, format!("{}-{}", request_id, index)).await {
+            Ok(ResponseJson(response)) => EventResult {
+                index,
+                source,
+                status: match (response.success, &response.rejection) {
+                    (true, _) => EventStatus::Success,
+                    (false, Some(_)) => EventStatus::Rejected,
+                    (false, None) => EventStatus::Skipped,
+                },
+                status_code: None,
+                message: response.message,
+                result: response.result,
+            },
+            Err((status, message)) => {
+                last_failure = Some(status);
+                EventResult {
+                    index,
+                    source,
+                    status: EventStatus::Failure,
+                    status_code: Some(status.as_u16()),
+                    message,
+                    result: None,
+                }
+            }
+        };
+        info!(request_id = %request_id, index, total, status = %result.status, source = %result.source, "📦 [OSS Event] 事件处理结束");
+        results.push(result);
+    }
+
+    let response = ProcessResponse::from_events(results);
+    match last_failure {
+        Some(status) if response.events.iter().all(|e| e.status == EventStatus::Failure) => {
+            error!(request_id = %request_id, events = total, "❌ [OSS Event] 全部事件处理失败");
+            Err((status, response.message))
+        }
+        Some(_) => {
+            warn!(request_id = %request_id, events = total, "⚠️  [OSS Event] {}", response.message);
+            Ok(ResponseJson(response))
+        }
+        None => {
+            info!(request_id = %request_id, events = total, "✅ [OSS Event] {}", response.message);
+            Ok(ResponseJson(response))
+        }
+    }
+}
+
+/// 处理单个事件的请求：处理返回服务端错误（5xx）时按 `[retry]` 配置重试，重试用尽后把事件和错误写入死信
+async fn process_with_retry(
+    event: OssEvent,
+    request_id: String,
+) -> Result<ResponseJson<ProcessResponse>, (StatusCode, String)> {
+    let _active_job = Metrics::shared().active_job();
+    // 返回前推送处理结束事件，未标记成功的返回都记为失败
+    let mut finish = ProgressStore::shared().finish_guard(request_id.as_str());
+    let retry = ConfigLoader::load_extended_config(None).unwrap_or_default().retry;
+    let max_attempts = retry.max_attempts.max(1);
+
+    let mut attempt = 1;
+    let (status, message) = loop {
+        match process_oss_event(event.clone(), request_id.clone(), attempt, attempt >= max_attempts).await {
+            Ok(response) => {
+                finish.succeed();
+                return Ok(response);
+            }
+            // 请求本身有问题（4xx）时重试也不会成功
+            Err((status, message)) if !status.is_server_error() => return Err((status, message)),
+            Err((status, message)) if attempt >= max_attempts => break (status, message),
+            Err((status, message)) => {
+                let backoff = retry.backoff(attempt);
+                warn!(
+                    request_id = %request_id,
+                    attempt,
+                    max_attempts,
+                    status = status.as_u16(),
+                    backoff_secs = backoff.as_secs(),
+                    error = %message,
+                    "🔁 [视频处理] 处理失败，等待后重试"
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+        }
+    };
+
+    if let Some(event_item) = event.events.first() {
+        let letter = DeadLetter {
+            request_id: request_id.clone(),
+            source: format!("oss://{}/{}", event_item.oss.bucket.name, event_item.oss.object.key),
+            status: status.as_u16(),
+            error: message.clone(),
+            attempts: attempt,
+            failed_at: chrono::Utc::now().to_rfc3339(),
+            event: event.clone(),
+        };
+        match dead_letter::store(&retry, &letter, &event_item.oss.bucket.name, &event_item.region).await {
+            Ok(locations) if locations.is_empty() => {}
+            Ok(locations) => warn!(request_id = %request_id, attempts = attempt, locations = ?locations, "📮 [视频处理] 重试用尽，事件已写入死信"),
+            Err(e) => error!(request_id = %request_id, error = %e, "❌ [视频处理] 写入死信失败，事件丢失"),
+        }
+    }
+    Err((status, message))
+}
+
+/// 处理一次 OSS Event
+///
+/// 不是最后一次尝试（`final_attempt` 为 false）时失败只记录指标，不发送失败回调和群通知
+#[instrument(name = "oss_event", skip_all, fields(request_id = %request_id, attempt = attempt, bucket = Empty, key = Empty))]
+async fn process_oss_event(
+    event: OssEvent,
+    request_id: String,
+    attempt: u32,
+    final_attempt: bool,
 ) -> Result<ResponseJson<ProcessResponse>, (StatusCode, String)> {
     // 记录接收到的请求详情
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -94,15 +249,7 @@ async fn handle_oss_event_internal(
 
     // 加载扩展配置
     let extended_config = ConfigLoader::load_extended_config(None)
-        .unwrap_or_else(|_| ExtendedConfig {
-            process: ProcessConfig::default(),
-            debug_mode: false,
-            output_path: None,
-            destination_bucket: None,
-            destination_region: None,
-            destination_prefix: None,
-            log_level: "info".to_string(),
-        });
+        .unwrap_or_default();
 
     // DEBUG 模式：如果设置了 DEBUG=true 或配置文件中启用了，直接返回成功，用于测试部署和事件触发
     if extended_config.debug_mode {
@@ -128,6 +275,8 @@ async fn handle_oss_event_internal(
             success: true,
             message: format!("DEBUG 模式：事件接收成功，事件信息: {}", event_info),
             result: None,
+            events: Vec::new(),
+            rejection: None,
         }));
     }
 
@@ -140,35 +289,51 @@ async fn handle_oss_event_internal(
         ));
     }
 
-    // 处理第一个事件（通常只有一个）
+    // 每次只处理一个事件（多事件请求由 handle_oss_event_internal 拆分）
     let event_item = &event.events[0];
     
-    // 只处理 ObjectCreated 事件（包括 Put, Post, Copy, CompleteMultipartUpload, PutSymlink）
-    if !event_item.event_name.starts_with("ObjectCreated") {
-        warn!("[OSS Event] 不支持的事件类型: {}，跳过处理", event_item.event_name);
+    // 只处理视频文件的 ObjectCreated 事件（包括 Put, Post, Copy, CompleteMultipartUpload, PutSymlink）
+    if let Some(reason) = event_item.skip_reason() {
+        warn!("[OSS Event] {}，跳过处理", reason);
         return Ok(ResponseJson(ProcessResponse {
             success: false,
-            message: format!("不支持的事件类型: {}", event_item.event_name),
+            message: format!("{}，已跳过处理", reason),
             result: None,
+            events: Vec::new(),
+            rejection: None,
         }));
     }
 
     let bucket = &event_item.oss.bucket.name;
-    let object_key = event_item.oss.object.key.clone();
+    let mut object_key = event_item.oss.object.key.clone();
+    let mut object_size = event_item.oss.object.size as u64;
+    let mut etag = event_item.oss.object.e_tag.clone();
     let region = &event_item.region;
+    Span::current().record("bucket", bucket.as_str());
+    Span::current().record("key", object_key.as_str());
     
-    // 处理符号链接事件（参考 Python 示例）
+    // 符号链接事件：解析出目标对象，之后按普通对象处理目标
     if event_item.event_name == "ObjectCreated:PutSymlink" {
-        // TODO: 实现符号链接解析
-        // 在函数计算环境中，符号链接需要通过 OSS API 解析
-        // 当前先记录日志，后续可以实现 get_symlink 功能
-        warn!("[OSS Event] 检测到符号链接事件，需要解析符号链接: {}，当前暂不支持", object_key);
-        // 注意：符号链接解析需要 OSS SDK 支持，当前暂不处理
-        return Ok(ResponseJson(ProcessResponse {
-            success: false,
-            message: format!("暂不支持符号链接事件: {}", event_item.event_name),
-            result: None,
-        }));
+        info!("🔗 [OSS Event] 检测到符号链接事件，解析目标对象: {}", object_key);
+        let (target, size, target_etag) = resolve_symlink(bucket, &object_key, region).await
+            .map_err(|e| {
+                error!("❌ [OSS Event] 解析符号链接失败: {}", e);
+                (StatusCode::BAD_GATEWAY, format!("解析符号链接失败: {}", e))
+            })?;
+        if !preflight::is_video_file(&target) {
+            warn!("[OSS Event] 符号链接目标 {} 不是视频文件，跳过处理", target);
+            return Ok(ResponseJson(ProcessResponse {
+                success: false,
+                message: format!("符号链接目标 {} 不是视频文件，已跳过处理", target),
+                result: None,
+                events: Vec::new(),
+                rejection: None,
+            }));
+        }
+        info!("✅ [OSS Event] 符号链接 {} -> {}", object_key, target);
+        object_key = target;
+        object_size = size;
+        etag = target_etag;
     }
     
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -177,36 +342,26 @@ async fn handle_oss_event_internal(
     info!("  • Object Key: {}", object_key);
     info!("  • Region: {}", region);
     info!("  • 文件大小: {} bytes ({:.2} MB)", 
-        event_item.oss.object.size,
-        event_item.oss.object.size as f64 / 1024.0 / 1024.0);
+        object_size,
+        object_size as f64 / 1024.0 / 1024.0);
     
     let process_start_time = std::time::Instant::now();
 
     // 加载扩展配置
     let extended_config = ConfigLoader::load_extended_config(None)
-        .unwrap_or_else(|_| ExtendedConfig {
-            process: ProcessConfig::default(),
-            debug_mode: false,
-            output_path: None,
-            destination_bucket: None,
-            destination_region: None,
-            destination_prefix: None,
-            log_level: "info".to_string(),
-        });
+        .unwrap_or_default();
+
+    // 下载前按事件中的文件大小检查输入上限，超出时直接拒绝
+    if let Err(rejection) = extended_config.process.limits.check_size(object_size) {
+        return Ok(rejected_response(rejection));
+    }
 
     // 创建临时目录或使用配置的输出路径
-    // 尝试使用函数计算的 request_id（优先使用传入的参数，其次环境变量，最后生成）
-    let request_id = request_id
-        .or_else(|| std::env::var("FC_REQUEST_ID").ok())
-        .unwrap_or_else(|| {
-            format!("{}_{}", 
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                uuid::Uuid::new_v4().to_string()
-            )
-        });
+    // 各阶段进度按 request_id 保存，可通过 GET /jobs/{id}/progress 查询
+    let source = format!("oss://{}/{}", bucket, object_key);
+    let store_reporter = ProgressStore::shared().reporter(request_id.as_str());
+    let webhook_reporter = WebhookProgress::from_config(&extended_config.process, Some(request_id.clone()), &source);
+    let reporter = progress::combine(Some(store_reporter.clone()), webhook_reporter).unwrap_or(store_reporter);
     info!("📁 [视频处理] 创建临时目录 RequestId: {}", request_id);
     
     // 使用配置的输出路径，如果没有则使用临时目录
@@ -222,54 +377,206 @@ async fn handle_oss_event_internal(
         })?;
     info!("✅ [视频处理] 临时目录创建成功: {}", temp_dir.display());
 
+    // 下载前确认临时目录能放下视频文件，避免下载到一半磁盘写满
+    if let Err(e) = preflight::ensure_free_space(&temp_dir, object_size) {
+        error!(error = %e, "❌ [视频处理] 输入预检失败");
+        let e = anyhow::Error::from(e);
+        notify_failure(&extended_config.process, bucket, &object_key, "preflight", &e, final_attempt).await;
+        return Err((StatusCode::INSUFFICIENT_STORAGE, e.to_string()));
+    }
+
     // 下载视频文件
     info!("🔧 [视频处理] 初始化 OSS 客户端...");
     let oss_client = OssClient::new()
+        .map(|client| {
+            client
+                .with_multipart(extended_config.multipart.clone())
+                .with_download_retries(extended_config.download_max_retries)
+        })
         .map_err(|e| {
             error!("❌ [视频处理] 创建 OSS 客户端失败: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, format!("创建 OSS 客户端失败: {}", e))
         })?;
     info!("✅ [视频处理] OSS 客户端初始化成功");
     
-    let video_path_buf = PathBuf::from(&object_key);
-    let video_filename = video_path_buf
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("video.mp4");
+    // 查询结果缓存（同一源文件、相同 ETag + 相同处理配置时直接返回已有结果）
+    let result_cache = ResultCache::from_config(&extended_config);
+    let config_fingerprint = extended_config.process.fingerprint();
+    let cache_source = format!("{}/{}", bucket, object_key);
+    if let Some(ref cache) = result_cache {
+        match cache.lookup(&oss_client, &cache_source, &etag, &config_fingerprint).await {
+            Ok(Some(entry)) => {
+                info!("♻️  [视频处理] 命中结果缓存，跳过处理");
+                telemetry::record_job("cached");
+                info!("  • ETag: {}", etag);
+                info!("  • 配置指纹: {}", config_fingerprint);
+                info!("  • 元数据位置: {}", entry.metadata_location);
+                info!("  • 缓存时间: {}", entry.created_at);
+                return Ok(ResponseJson(ProcessResponse {
+                    success: true,
+                    message: format!(
+                        "命中结果缓存，检测到 {} 个场景，元数据位置: {}",
+                        entry.result.scene_count, entry.metadata_location
+                    ),
+                    result: Some(entry.result),
+                    events: Vec::new(),
+                    rejection: None,
+                }));
+            }
+            Ok(None) => {
+                debug!("结果缓存未命中: etag={}, fingerprint={}", etag, config_fingerprint);
+            }
+            Err(e) => {
+                // 缓存不可用时不影响正常处理
+                warn!("⚠️  [视频处理] 查询结果缓存失败，继续处理: {}", e);
+            }
+        }
+    }
     
-    let video_path = temp_dir.join(video_filename);
+    // 幂等检查：OSS 重复投递事件时，目标位置已有该 ETag 的处理结果则跳过
+    let content_addressed = extended_config.process.content_addressed && !etag.is_empty();
+    if let (Some(dest_bucket), Some(dest_region)) = (&extended_config.destination_bucket, &extended_config.destination_region) {
+        if extended_config.force_reprocess {
+            debug!("已开启 force_reprocess，不检查已有输出");
+        } else if extended_config.output_naming != OutputNaming::StemHash && !content_addressed {
+            debug!(naming = extended_config.output_naming.as_str(), "输出目录不包含 ETag，无法判断是否已处理，跳过幂等检查");
+        } else {
+            // 打包输出目录时只上传打包文件，以打包文件是否存在判断
+            let metadata_file = match extended_config.process.archive {
+                Some(format) => format.file_name(),
+                None => extended_config.process.naming
+                    .for_input(std::path::Path::new(&output_naming::local_file_name(&object_key)))
+                    .metadata_file(),
+            };
+            let mut prefix = dest_prefix(&extended_config, &object_key, &etag);
+            if content_addressed {
+                // 按内容寻址时同一 ETag 用不同参数处理的输出在不同目录，只有参数相同时才跳过
+                prefix = format!("{}/{}", prefix, output_naming::content_path(&output_naming::etag_hash(&etag), &config_fingerprint));
+            }
+            let metadata_key = format!("{}/{}", prefix, metadata_file);
+            let dest_endpoint = format!("oss-{}-internal.aliyuncs.com", dest_region);
+            let existing = if extended_config.process.archive.is_some() {
+                // 打包文件单独上传，存在即表示结果完整
+                oss_client.head_object(dest_bucket, &metadata_key, Some(&dest_endpoint)).await.map(|head| head.is_some())
+            } else {
+                existing_metadata_matches(&oss_client, dest_bucket, &metadata_key, &dest_endpoint, &etag, &temp_dir).await
+            };
+            match existing {
+                Ok(true) => {
+                    let metadata_location = format!("oss://{}/{}", dest_bucket, metadata_key);
+                    info!(etag = %etag, metadata = %metadata_location, "⏭️  [视频处理] 该 ETag 已处理过，跳过处理");
+                    telemetry::record_job("skipped");
+                    return Ok(ResponseJson(ProcessResponse {
+                        success: true,
+                        message: format!("该视频已处理过，跳过处理，元数据位置: {}", metadata_location),
+                        result: None,
+                        events: Vec::new(),
+                        rejection: None,
+                    }));
+                }
+                Ok(false) => debug!("目标位置没有该 ETag 的完整输出: {}", metadata_key),
+                // 检查失败时不影响正常处理
+                Err(e) => warn!("⚠️  [视频处理] 检查已有输出失败，继续处理: {}", e),
+            }
+        }
+    }
+
+    let video_filename = output_naming::local_file_name(&object_key);
+    let video_path = temp_dir.join(&video_filename);
     
     // 构建 internal endpoint（内网访问更快且免费）
     // 格式: oss-{region}-internal.aliyuncs.com
     let endpoint = format!("oss-{}-internal.aliyuncs.com", region);
     
-    // 尝试从 OSS 下载（使用 internal endpoint）
-    info!("⬇️  [视频处理] 开始下载视频文件");
-    info!("  • 源地址: oss://{}/{}", bucket, object_key);
-    info!("  • 目标路径: {}", video_path.display());
-    info!("  • Endpoint: {}", endpoint);
-    let download_start = std::time::Instant::now();
-    let downloaded_path = oss_client
-        .download_file(bucket, &object_key, Some(&endpoint), &video_path)
-        .await
-        .map_err(|e| {
-            error!("❌ [视频处理] 下载文件失败: bucket={}, key={}, error={}", bucket, object_key, e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("下载文件失败: {}", e))
-        })?;
-    let download_duration = download_start.elapsed();
-    let file_size_mb = event_item.oss.object.size as f64 / 1024.0 / 1024.0;
-    let download_speed = file_size_mb / download_duration.as_secs_f64();
-    info!("✅ [视频处理] 文件下载成功");
-    info!("  • 文件路径: {}", downloaded_path.display());
-    info!("  • 下载耗时: {:.2}秒", download_duration.as_secs_f64());
-    info!("  • 下载速度: {:.2} MB/s", download_speed);
+    // 大文件分段下载：帧提取在已下载的部分上立即开始，与下载重叠执行
+    let segment_size = extended_config.download_segment_size_mb.max(1) * 1024 * 1024;
+    let segmented = extended_config.segmented_download
+        && object_size >= segment_size * 2;
     
-    // 验证下载的文件
-    if let Ok(metadata) = std::fs::metadata(&downloaded_path) {
-        info!("  • 实际文件大小: {} bytes ({:.2} MB)", 
-            metadata.len(),
-            metadata.len() as f64 / 1024.0 / 1024.0);
-    }
+    let download_span = info_span!(
+        "download",
+        bucket = %bucket,
+        key = %object_key,
+        endpoint = %endpoint,
+        size_bytes = object_size,
+        segmented,
+        duration_ms = Empty,
+    );
+    let timeouts = extended_config.process.timeouts;
+    let download_start = std::time::Instant::now();
+    let mut download_duration = std::time::Duration::ZERO;
+    let mut download_bytes = 0;
+    let mut progressive_download = None;
+    // 分段下载在后台进行，函数返回时停止报告下载进度
+    let mut _download_ticker = None;
+    reporter.stage_started("download");
+    let downloaded_path = if segmented {
+        download_span.in_scope(|| info!(
+            path = %video_path.display(),
+            segment_size_mb = extended_config.download_segment_size_mb,
+            "⬇️  [视频处理] 开始分段下载视频文件（边下载边处理）"
+        ));
+        let start = oss_client
+            .download_progressive(bucket, &object_key, Some(&endpoint), &video_path, segment_size, DOWNLOAD_CONCURRENCY)
+            .instrument(download_span.clone());
+        let started = with_timeout(Stage::Download, timeouts.download, start)
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|r| r.map_err(anyhow::Error::from));
+        let (file, task) = match started {
+            Ok(started) => started,
+            Err(e) => {
+                error!(error = %e, "❌ [视频处理] 启动分段下载失败");
+                notify_failure(&extended_config.process, bucket, &object_key, "download", &e, final_attempt).await;
+                return Err((failure_status(&e), format!("下载文件失败: {}", e)));
+            }
+        };
+        // 下载超时后让等待数据的帧提取立即失败，而不是一直阻塞
+        let timeout_file = file.clone();
+        let task = watchdog::guard_task(Stage::Download, timeouts.download, download_start, task, move |e| {
+            timeout_file.fail(e.to_string());
+        });
+        download_span.in_scope(|| info!(
+            segment_count = file.segment_count(),
+            "✅ [视频处理] 分段下载已启动，后台下载的同时开始处理"
+        ));
+        let path = file.path().to_path_buf();
+        _download_ticker = Some(report_download_progress(file.clone(), reporter.clone()));
+        progressive_download = Some((file, task));
+        path
+    } else {
+        // 尝试从 OSS 下载（使用 internal endpoint）
+        download_span.in_scope(|| info!(path = %video_path.display(), "⬇️  [视频处理] 开始下载视频文件"));
+        let download = oss_client
+            .download_file(bucket, &object_key, Some(&endpoint), &video_path)
+            .instrument(download_span.clone());
+        let downloaded = with_timeout(Stage::Download, timeouts.download, download)
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|r| r.map_err(anyhow::Error::from));
+        let downloaded_path = match downloaded {
+            Ok(path) => path,
+            Err(e) => {
+                error!(error = %e, "❌ [视频处理] 下载文件失败");
+                notify_failure(&extended_config.process, bucket, &object_key, "download", &e, final_attempt).await;
+                return Err((failure_status(&e), format!("下载文件失败: {}", e)));
+            }
+        };
+        download_duration = download_start.elapsed();
+        let file_size_mb = object_size as f64 / 1024.0 / 1024.0;
+        // 验证下载的文件
+        let actual_size_bytes = std::fs::metadata(&downloaded_path).map(|m| m.len()).unwrap_or(0);
+        download_bytes = actual_size_bytes;
+        reporter.update(&StageProgress::new("download", actual_size_bytes, Some(actual_size_bytes), ProgressUnit::Bytes));
+        download_span.record("duration_ms", download_duration.as_millis() as u64);
+        telemetry::record_transfer("download", actual_size_bytes, download_duration);
+        download_span.in_scope(|| info!(
+            actual_size_bytes,
+            speed_mb_s = file_size_mb / download_duration.as_secs_f64().max(0.001),
+            "✅ [视频处理] 文件下载成功"
+        ));
+        downloaded_path
+    };
 
     // 创建输出目录
     info!("📁 [视频处理] 创建输出目录...");
@@ -282,178 +589,406 @@ async fn handle_oss_event_internal(
     info!("✅ [视频处理] 输出目录创建成功: {}", output_dir.display());
     
     // 使用扩展配置中的处理配置
-    info!("⚙️  [视频处理] 使用处理配置...");
-    let config = extended_config.process.clone();
-    info!("📋 [视频处理] 处理配置:");
-    info!("  • 场景检测阈值: {:.2}", config.threshold);
-    info!("  • 最小场景持续时间: {:.2}秒", config.min_scene_duration);
-    info!("  • 帧采样率: {:.2} fps", config.sample_rate);
-    
-    info!("🎞️  [视频处理] 开始视频拉片处理...");
-    info!("  • 输入文件: {}", downloaded_path.display());
-    info!("  • 输出目录: {}", output_dir.display());
+    // webhook 在上传完成后调用，以便回调数据包含下载和上传耗时
+    let mut config = extended_config.process.clone();
+    let webhook_url = config.webhook_url.take();
+    // 失败回调由 handler 调用，以便同样覆盖下载阶段的失败
+    config.webhook_failure_url = None;
+    // 进度回调由 handler 创建，以便带上任务 ID 并覆盖下载和上传阶段
+    config.webhook_progress_url = None;
+    let mut options = ProcessOptions::default().with_progress(reporter.clone());
+    if !final_attempt {
+        options = options.without_failure_webhook();
+    }
+    if !etag.is_empty() {
+        options = options.with_source_hash(output_naming::etag_hash(&etag));
+    }
+    info!(
+        threshold = config.threshold,
+        min_scene_duration = config.min_scene_duration,
+        sample_rate = config.sample_rate,
+        "🎞️  [视频处理] 开始视频拉片处理"
+    );
     let video_process_start = std::time::Instant::now();
-    let process_result = process_video(&downloaded_path, &output_dir, config)
-        .await
-        .map_err(|e| {
+    let process_result = match progressive_download {
+        Some((ref file, _)) => Pipeline::for_config(&config).run_source(file.path(), Some(file.clone()), &output_dir, config, options).await,
+        None => Pipeline::for_config(&config).run_source(&downloaded_path, None, &output_dir, config, options).await,
+    };
+    let mut process_result = match process_result {
+        Ok(result) => result,
+        Err(mut e) => {
+            if let Some((_, task)) = progressive_download {
+                // 下载超时导致帧提取失败时，以下载超时作为失败原因
+                if task.is_finished() {
+                    if let Ok(Err(download_error)) = task.await {
+                        if download_error.as_timeout().is_some() {
+                            e = download_error.into();
+                        }
+                    }
+                } else {
+                    task.abort();
+                }
+            }
+            // 探测后发现时长超出上限：拒绝处理，不算失败，也不重试
+            if let Some(rejection) = preflight::as_rejection(&e) {
+                return Ok(rejected_response(rejection.clone()));
+            }
             error!("❌ [视频处理] 处理视频失败: path={}, error={}", downloaded_path.display(), e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("处理视频失败: {}", e))
-        })?;
+            database::record_failure(&extended_config.database, &source, &e.to_string()).await;
+            // 处理阶段的错误带有具体的失败阶段，只有替换为下载错误时才使用 download
+            notify_failure(&extended_config.process, bucket, &object_key, "download", &e, final_attempt).await;
+            if final_attempt {
+                let notification = notify::Notification::failure(format!("{}/{}", bucket, object_key), e.to_string());
+                notify::send(&extended_config.notify, &notification).await;
+            }
+            return Err((failure_status(&e), format!("处理视频失败: {}", e)));
+        }
+    };
     let video_process_duration = video_process_start.elapsed();
-    info!("✅ [视频处理] 视频处理完成");
-    info!("  • 处理耗时: {:.2}秒", video_process_duration.as_secs_f64());
-    info!("  • 检测到场景数: {}", process_result.metadata.scene_count);
-    info!("  • 提取关键帧数: {}", process_result.keyframe_files.len());
-    info!("  • 音频文件: {}", process_result.audio_file);
+    // 按内容寻址时输出在 {源文件哈希}/{配置指纹}/ 子目录下
+    let output_dir = process_result.output_dir.clone();
+    
+    // 分段下载：确认后台下载任务成功结束（音频提取前已等待文件下载完成）
+    if let Some((file, task)) = progressive_download {
+        let finished = task.instrument(download_span.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("下载任务异常终止: {}", e))
+            .and_then(|r| r.map_err(anyhow::Error::from));
+        if let Err(e) = finished {
+            error!(error = %e, "❌ [视频处理] 分段下载失败");
+            notify_failure(&extended_config.process, bucket, &object_key, "download", &e, final_attempt).await;
+            return Err((failure_status(&e), format!("下载文件失败: {}", e)));
+        }
+        download_duration = file.download_duration().unwrap_or_else(|| download_start.elapsed());
+        download_bytes = file.total_size();
+        let file_size_mb = file.total_size() as f64 / 1024.0 / 1024.0;
+        download_span.record("duration_ms", download_duration.as_millis() as u64);
+        telemetry::record_transfer("download", file.total_size(), download_duration);
+        download_span.in_scope(|| info!(
+            speed_mb_s = file_size_mb / download_duration.as_secs_f64().max(0.001),
+            "✅ [视频处理] 分段下载完成（与处理重叠）"
+        ));
+    }
+
+    // 内容安全检测标记了该视频且配置了拦截时，不上传处理结果
+    let upload_blocked = extended_config.process.safety.block_upload
+        && process_result.metadata.safety.as_ref().is_some_and(|s| s.flagged);
 
     // 上传处理结果到目标 bucket（如果配置了目标 bucket）
-    let (uploaded_files, upload_duration) = if let (Some(dest_bucket), Some(dest_region)) = (
+    let (uploaded_files, upload_duration, upload_bytes, upload_location) = if upload_blocked {
+        let summary = process_result.metadata.safety.as_ref();
+        warn!(
+            max_label = summary.and_then(|s| s.max_label.as_deref()),
+            max_score = summary.map(|s| s.max_score),
+            "🚫 [视频处理] 内容安全检测未通过，跳过上传"
+        );
+        (None, None, None, None)
+    } else if let (Some(dest_bucket), Some(dest_region)) = (
         extended_config.destination_bucket.clone(),
         extended_config.destination_region.clone(),
     ) {
-        info!("⬆️  [视频处理] 开始上传处理结果到目标 bucket");
-        info!("  • 目标 Bucket: {}", dest_bucket);
-        info!("  • 目标 Region: {}", dest_region);
-        let upload_start = std::time::Instant::now();
-        
-        // 构建目标 endpoint
-        let dest_endpoint = format!("oss-{}-internal.aliyuncs.com", dest_region);
+        let upload_span = info_span!(
+            "upload",
+            bucket = %dest_bucket,
+            region = %dest_region,
+            uploaded = Empty,
+            failed = Empty,
+            duration_ms = Empty,
+        );
+        // 启用检查点时，超时重试不再重复上传已上传的文件
+        let mut checkpoint = crate::checkpoint::Checkpoint::open(&output_dir).filter(|_| extended_config.process.checkpoint);
+        let upload = async {
+            info!("⬆️  [视频处理] 开始上传处理结果到目标 bucket");
+            let upload_start = std::time::Instant::now();
         
-        // 构建目标路径前缀（保持源文件的目录结构）
-        let dest_prefix = extended_config.destination_prefix.clone()
-            .unwrap_or_else(|| {
-                // 默认使用源文件的目录部分作为前缀
-                PathBuf::from(&object_key)
-                    .parent()
-                    .and_then(|p| p.to_str())
-                    .map(|s| s.to_string())
-                    .unwrap_or_else(|| "processed".to_string())
-            });
+            // 构建目标 endpoint
+            let dest_endpoint = format!("oss-{}-internal.aliyuncs.com", dest_region);
         
-        let mut uploaded = Vec::new();
-        let mut upload_errors = Vec::new();
+            let dest_prefix = match (&process_result.metadata.source_hash, &process_result.metadata.config_fingerprint) {
+                (Some(hash), Some(fingerprint)) => format!(
+                    "{}/{}",
+                    dest_prefix(&extended_config, &object_key, &etag),
+                    output_naming::content_path(hash, fingerprint)
+                ),
+                _ => dest_prefix(&extended_config, &object_key, &etag),
+            };
+            info!("  • 目标路径: oss://{}/{} (命名方式: {})", dest_bucket, dest_prefix, extended_config.output_naming.as_str());
         
-        // 上传关键帧
-        // keyframe_files 是文件名列表，需要与 output_dir 组合成完整路径
-        for keyframe_filename in &process_result.keyframe_files {
-            let keyframe_path = output_dir.join(keyframe_filename);
-            if keyframe_path.exists() {
-                let keyframe_name = keyframe_path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("keyframe.jpg");
-                let dest_key = format!("{}/keyframes/{}", dest_prefix, keyframe_name);
-                
-                match oss_client.upload_file(
-                    &dest_bucket,
-                    &dest_key,
-                    &keyframe_path,
-                    Some(&dest_endpoint),
-                ).await {
-                    Ok(_) => {
-                        info!("已上传关键帧: {} -> {}", keyframe_path.display(), dest_key);
-                        uploaded.push(dest_key.clone());
-                    }
-                    Err(e) => {
-                        let error_msg = format!("上传关键帧失败 {}: {}", dest_key, e);
+            // 本地文件名（相对输出目录） -> 目标 key
+            let mut uploaded = BTreeMap::new();
+            let mut uploaded_bytes = 0;
+            let mut upload_errors = Vec::new();
+
+            // 进度按已处理（成功或失败）的文件数报告
+            reporter.stage_started("upload");
+            let upload_total = if process_result.archive_file.is_some() {
+                1
+            } else {
+                process_result.keyframe_files.len()
+                    + usize::from(!process_result.audio_file.is_empty())
+                    + usize::from(process_result.metadata.embeddings.is_some())
+                    + process_result.metadata.sprite.as_ref().map_or(0, |_| 2)
+                    + usize::from(process_result.metadata.loudness.is_some())
+                    + process_result.metadata.scenes.iter().filter(|scene| scene.clip_file.is_some()).count()
+                    + process_result.metadata.scenes.iter().filter(|scene| scene.preview_file.is_some()).count()
+                    + process_result.metadata.exports.len()
+                    + usize::from(process_result.metadata.fingerprint_file.is_some())
+                    + 1
+            };
+            let report_upload = |done: usize| {
+                reporter.update(&StageProgress::new("upload", done as u64, Some(upload_total as u64), ProgressUnit::Files));
+            };
+
+            // 关键帧、音频、拼图等文件并发上传，元数据文件最后上传（目标位置有元数据文件即表示结果完整）；
+            // 打包输出目录时只上传打包文件
+            let mut tasks = Vec::new();
+            let mut last = Vec::new();
+            if let Some(ref archive_file) = process_result.archive_file {
+                let dest_key = format!("{}/{}", dest_prefix, archive_file);
+                last.push(UploadTask::new("打包文件", archive_file.clone(), output_dir.join(archive_file), dest_key));
+            } else {
+                // keyframe_files 是文件名列表，需要与 output_dir 组合成完整路径
+                for keyframe_filename in &process_result.keyframe_files {
+                    let keyframe_path = output_dir.join(keyframe_filename);
+                    if keyframe_path.exists() {
+                        let keyframe_name = keyframe_path.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("keyframe.jpg");
+                        let dest_key = format!("{}/keyframes/{}", dest_prefix, keyframe_name);
+                        tasks.push(UploadTask::new("关键帧", keyframe_filename.clone(), &keyframe_path, dest_key));
+                    } else {
+                        let error_msg = format!("关键帧文件不存在: {}", keyframe_path.display());
                         error!("{}", error_msg);
                         upload_errors.push(error_msg);
                     }
                 }
-            } else {
-                let error_msg = format!("关键帧文件不存在: {}", keyframe_path.display());
-                error!("{}", error_msg);
-                upload_errors.push(error_msg);
-            }
-        }
-        
-        // 上传音频文件
-        // audio_file 是文件名，需要与 output_dir 组合成完整路径
-        let audio_path = output_dir.join(&process_result.audio_file);
-        if audio_path.exists() {
-            let audio_name = audio_path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("audio.aac");
-            let dest_key = format!("{}/{}", dest_prefix, audio_name);
-            
-            match oss_client.upload_file(
-                &dest_bucket,
-                &dest_key,
-                &audio_path,
-                Some(&dest_endpoint),
-            ).await {
-                Ok(_) => {
-                    info!("已上传音频文件: {} -> {}", audio_path.display(), dest_key);
-                    uploaded.push(dest_key.clone());
-                }
-                Err(e) => {
-                    let error_msg = format!("上传音频文件失败 {}: {}", dest_key, e);
+
+                // audio_file 是文件名，需要与 output_dir 组合成完整路径
+                let audio_path = output_dir.join(&process_result.audio_file);
+                if process_result.audio_file.is_empty() {
+                    info!("输入没有音频，跳过上传音频文件");
+                } else if audio_path.exists() {
+                    let audio_name = audio_path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("audio.aac");
+                    let dest_key = format!("{}/{}", dest_prefix, audio_name);
+                    tasks.push(UploadTask::new("音频文件", process_result.audio_file.clone(), &audio_path, dest_key));
+                } else {
+                    let error_msg = format!("音频文件不存在: {}", audio_path.display());
                     error!("{}", error_msg);
                     upload_errors.push(error_msg);
                 }
-            }
-        } else {
-            let error_msg = format!("音频文件不存在: {}", audio_path.display());
-            error!("{}", error_msg);
-            upload_errors.push(error_msg);
-        }
-        
-        // 上传元数据文件
-        let metadata_path = output_dir.join("metadata.json");
-        if metadata_path.exists() {
-            let dest_key = format!("{}/metadata.json", dest_prefix);
-            
-            match oss_client.upload_file(
-                &dest_bucket,
-                &dest_key,
-                &metadata_path,
-                Some(&dest_endpoint),
-            ).await {
-                Ok(_) => {
-                    info!("已上传元数据文件: {} -> {}", metadata_path.display(), dest_key);
-                    uploaded.push(dest_key.clone());
+
+                let metadata = &process_result.metadata;
+                let mut push_file = |label: &'static str, file: &String, dest_key: String| {
+                    tasks.push(UploadTask::new(label, file.clone(), output_dir.join(file), dest_key));
+                };
+                if let Some(ref index) = metadata.embeddings {
+                    push_file("图像向量文件", &index.file, format!("{}/{}", dest_prefix, index.file));
+                }
+                if let Some(ref sheet) = metadata.sprite {
+                    for file in [&sheet.image, &sheet.vtt] {
+                        push_file("缩略图拼图文件", file, format!("{}/{}", dest_prefix, file));
+                    }
+                }
+                for clip_file in metadata.scenes.iter().filter_map(|scene| scene.clip_file.as_ref()) {
+                    push_file("场景片段", clip_file, format!("{}/clips/{}", dest_prefix, clip_file));
+                }
+                // 场景预览动图与关键帧放在同一目录
+                for preview_file in metadata.scenes.iter().filter_map(|scene| scene.preview_file.as_ref()) {
+                    push_file("场景预览", preview_file, format!("{}/keyframes/{}", dest_prefix, preview_file));
+                }
+                if let Some(ref loudness) = metadata.loudness {
+                    push_file("音频波形文件", &loudness.waveform, format!("{}/{}", dest_prefix, loudness.waveform));
+                }
+                for file in &metadata.exports {
+                    push_file("场景列表文件", file, format!("{}/{}", dest_prefix, file));
+                }
+                if let Some(ref file) = metadata.fingerprint_file {
+                    push_file("视频指纹文件", file, format!("{}/{}", dest_prefix, file));
                 }
-                Err(e) => {
-                    let error_msg = format!("上传元数据文件失败 {}: {}", dest_key, e);
+
+                let metadata_path = output_dir.join(&process_result.metadata_file);
+                if metadata_path.exists() {
+                    let dest_key = format!("{}/{}", dest_prefix, process_result.metadata_file);
+                    last.push(UploadTask::new("元数据文件", process_result.metadata_file.clone(), metadata_path, dest_key));
+                } else {
+                    let error_msg = format!("元数据文件不存在: {}", metadata_path.display());
                     error!("{}", error_msg);
                     upload_errors.push(error_msg);
                 }
             }
-        } else {
-            let error_msg = format!("元数据文件不存在: {}", metadata_path.display());
-            error!("{}", error_msg);
-            upload_errors.push(error_msg);
-        }
+
+            // 启用检查点时跳过已上传的文件
+            if let Some(checkpoint) = checkpoint.as_ref() {
+                for list in [&mut tasks, &mut last] {
+                    list.retain(|task| {
+                        if !checkpoint.is_uploaded(&task.object_key, &task.path) {
+                            return true;
+                        }
+                        debug!("♻️  [检查点] 文件已上传，跳过: {}", task.object_key);
+                        uploaded.insert(task.name.clone(), task.object_key.clone());
+                        uploaded_bytes += resource::disk_usage(&task.path);
+                        false
+                    });
+                }
+            }
+
+            let mut done = uploaded.len() + upload_errors.len();
+            report_upload(done);
+            let mut on_done = |task: &UploadTask, result: &crate::error::Result<()>| {
+                if let (Ok(()), Some(checkpoint)) = (result, checkpoint.as_mut()) {
+                    checkpoint.mark_uploaded(&task.object_key, &task.path);
+                }
+                done += 1;
+                report_upload(done);
+            };
+            let concurrency = extended_config.upload_concurrency;
+            info!("  • 并发上传 {} 个文件（并发数: {}）", tasks.len() + last.len(), concurrency);
+            let mut report = oss_client.upload_files(&dest_bucket, &tasks, Some(&dest_endpoint), concurrency, &mut on_done).await;
+            upload_errors.append(&mut report.errors);
+            // 其他文件有上传失败时不上传元数据文件，避免目标位置的结果看起来完整
+            if upload_errors.is_empty() {
+                let last_report = oss_client.upload_files(&dest_bucket, &last, Some(&dest_endpoint), 1, &mut on_done).await;
+                report.uploaded.extend(last_report.uploaded);
+                report.bytes += last_report.bytes;
+                upload_errors.extend(last_report.errors);
+            } else if !last.is_empty() {
+                warn!("⚠️  [视频处理] 有文件上传失败，跳过上传元数据文件");
+            }
+            uploaded.extend(report.uploaded);
+            uploaded_bytes += report.bytes;
+            report_upload(upload_total);
         
-        // 记录上传结果
-        let upload_duration = upload_start.elapsed();
-        if !upload_errors.is_empty() {
-            warn!("⚠️  [视频处理] 部分文件上传失败，共 {} 个错误", upload_errors.len());
-            for err in &upload_errors {
-                warn!("  • {}", err);
+            // 记录上传结果
+            let upload_duration = upload_start.elapsed();
+            if !upload_errors.is_empty() {
+                warn!("⚠️  [视频处理] 部分文件上传失败，共 {} 个错误", upload_errors.len());
+                for err in &upload_errors {
+                    warn!("  • {}", err);
+                }
+            } else if extended_config.clean_stale_outputs && extended_config.output_naming != OutputNaming::Flat {
+                // 删除输出目录下本次未生成的旧文件（例如之前用不同配置处理时多出的关键帧）
+                clean_stale_outputs(&oss_client, &dest_bucket, &dest_prefix, &dest_endpoint, &uploaded).await;
             }
-        }
         
-        info!("✅ [视频处理] 上传完成");
-        info!("  • 上传耗时: {:.2}秒", upload_duration.as_secs_f64());
-        info!("  • 成功: {} 个文件", uploaded.len());
-        info!("  • 失败: {} 个文件", upload_errors.len());
+            let span = Span::current();
+            span.record("uploaded", uploaded.len());
+            span.record("failed", upload_errors.len());
+            span.record("duration_ms", upload_duration.as_millis() as u64);
+            telemetry::record_transfer("upload", uploaded_bytes, upload_duration);
+            if upload_errors.is_empty() {
+                info!("✅ [视频处理] 上传完成");
+            }
         
-        (Some(uploaded), Some(upload_duration))
+            let report = UploadReport { uploaded, bytes: uploaded_bytes, errors: upload_errors };
+            (report, upload_duration, (dest_bucket, dest_prefix))
+        }
+        .instrument(upload_span);
+        let (report, upload_duration, location) = with_timeout(Stage::Upload, timeouts.upload, upload)
+            .await
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 上传处理结果超时");
+                telemetry::record_job("failure");
+                telemetry::record_failure("upload_timeout");
+                (StatusCode::GATEWAY_TIMEOUT, format!("上传处理结果失败: {}", e))
+            })?;
+        // 有文件上传失败时返回 5xx（包含所有失败文件的错误），由重试重新处理和上传
+        let uploaded_bytes = report.bytes;
+        match report.into_result() {
+            Ok(uploaded) => (Some(uploaded), Some(upload_duration), Some(uploaded_bytes), Some(location)),
+            Err(e) => {
+                let e = anyhow::Error::from(e);
+                error!(error = %e, "❌ [视频处理] 上传处理结果失败");
+                notify_failure(&extended_config.process, bucket, &object_key, "upload", &e, final_attempt).await;
+                return Err((failure_status(&e), format!("上传处理结果失败: {}", e)));
+            }
+        }
     } else {
         info!("ℹ️  [视频处理] 未配置目标 bucket，跳过上传");
-        (None, None)
+        (None, None, None, None)
     };
     
     let total_duration = process_start_time.elapsed();
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    info!("🎉 [视频处理] 全部处理完成");
-    info!("  • 总耗时: {:.2}秒", total_duration.as_secs_f64());
-    info!("  • 下载耗时: {:.2}秒", download_duration.as_secs_f64());
-    info!("  • 处理耗时: {:.2}秒", video_process_duration.as_secs_f64());
-    if let Some(duration) = upload_duration {
-        info!("  • 上传耗时: {:.2}秒", duration.as_secs_f64());
+    let timings = &mut process_result.timings;
+    timings.download_ms = Some(download_duration.as_millis() as u64);
+    timings.download_bytes = Some(download_bytes);
+    timings.upload_ms = upload_duration.map(|d| d.as_millis() as u64);
+    timings.upload_bytes = upload_bytes;
+    timings.upload_files = uploaded_files.as_ref().map(|files| files.len());
+    timings.total_ms = total_duration.as_millis() as u64;
+    info!(
+        total_ms = total_duration.as_millis() as u64,
+        download_ms = download_duration.as_millis() as u64,
+        process_ms = video_process_duration.as_millis() as u64,
+        upload_ms = upload_duration.map(|d| d.as_millis() as u64),
+        scene_count = process_result.metadata.scene_count,
+        keyframe_count = process_result.keyframe_files.len(),
+        peak_rss = %format_mb(process_result.resources.peak_rss_bytes),
+        peak_disk = %format_mb(process_result.resources.peak_disk_bytes),
+        "🎉 [视频处理] 全部处理完成"
+    );
+    telemetry::record_job("success");
+
+    // 记录任务、场景和产物到数据库（如果配置了）
+    let artifacts = match (&uploaded_files, &upload_location) {
+        (Some(uploaded), Some((dest_bucket, _))) => uploaded
+            .values()
+            .map(|key| database::Artifact::new(format!("oss://{}/{}", dest_bucket, key), None))
+            .collect(),
+        _ => database::local_artifacts(&process_result),
+    };
+    database::record_success(
+        &extended_config.database,
+        &process_result,
+        &format!("oss://{}/{}", bucket, object_key),
+        &config_fingerprint,
+        &artifacts,
+    )
+    .await;
+
+    // 导出场景向量到向量数据库（如果配置了，内容安全检测未通过的视频不导出）
+    if extended_config.vector_store.endpoint.is_some() && !upload_blocked {
+        let export_span = info_span!(
+            "vector_export",
+            kind = extended_config.vector_store.kind.as_str(),
+            collection = %extended_config.vector_store.collection,
+        );
+        let keyframe_prefix = upload_location
+            .as_ref()
+            .map(|(dest_bucket, dest_prefix)| format!("oss://{}/{}/keyframes", dest_bucket, dest_prefix));
+        match vector_store::export_scenes(&extended_config.vector_store, &process_result, &source, keyframe_prefix.as_deref())
+            .instrument(export_span.clone())
+            .await
+        {
+            Ok(points) => export_span.in_scope(|| info!(points, "✅ [视频处理] 场景向量导出完成")),
+            Err(e) => export_span.in_scope(|| warn!(error = %e, "⚠️  [视频处理] 场景向量导出失败")),
+        }
+    }
+
+    // 调用 webhook 回调（如果配置了）
+    if let Some(ref webhook_url) = webhook_url {
+        let delivery = processor::notify_webhook(webhook_url, &extended_config.process.webhook, &process_result).await;
+        process_result.webhook = Some(delivery);
     }
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+    // 发送群机器人通知（如果配置了）
+    if extended_config.notify.is_enabled() {
+        let outputs = match upload_location {
+            Some((ref dest_bucket, ref dest_prefix)) => format!("oss://{}/{}", dest_bucket, dest_prefix),
+            None => output_dir.to_string_lossy().to_string(),
+        };
+        let notification = notify::Notification::success(format!("{}/{}", bucket, object_key), &process_result, outputs);
+        notify::send(&extended_config.notify, &notification).await;
+    }
+
+    // 为已上传的输出生成预签名下载地址（如果配置了）
+    let presigned_urls = match (&uploaded_files, &upload_location, &extended_config.destination_region) {
+        (Some(uploaded), Some((dest_bucket, _)), Some(dest_region)) if extended_config.presign_expires_secs > 0 => {
+            presign_outputs(&oss_client, uploaded, dest_bucket, dest_region, extended_config.presign_expires_secs)
+        }
+        _ => BTreeMap::new(),
+    };
 
     // 清理临时目录（可选，函数计算会自动清理）
     // 如果需要保留文件用于调试，可以注释掉下面的代码
@@ -469,20 +1004,71 @@ async fn handle_oss_event_internal(
             process_result.metadata.scene_count,
             if uploaded_files.is_some() {
                 "，已上传到目标 bucket"
+            } else if upload_blocked {
+                "，内容安全检测未通过，未上传"
             } else {
                 ""
             }
         ),
         result: Some(ProcessResult {
+            job_id: Some(process_result.job_id.clone()),
             video_file: downloaded_path.to_string_lossy().to_string(),
             output_dir: output_dir.to_string_lossy().to_string(),
             scene_count: process_result.metadata.scene_count,
             keyframes: process_result.keyframe_files.clone(),
             audio_file: process_result.audio_file.clone(),
-            metadata_file: "metadata.json".to_string(),
+            metadata_file: process_result.metadata_file.clone(),
+            archive: process_result.archive_file.as_ref().map(|file| match &upload_location {
+                Some((dest_bucket, dest_prefix)) => format!("oss://{}/{}/{}", dest_bucket, dest_prefix, file),
+                None => output_dir.join(file).to_string_lossy().to_string(),
+            }),
+            resources: Some(process_result.resources.clone()),
+            timings: Some(process_result.timings.clone()),
+            upload_blocked,
+            webhook: process_result.webhook.clone(),
+            outputs: match (&uploaded_files, &upload_location) {
+                (Some(uploaded), Some((dest_bucket, _))) => uploaded
+                    .iter()
+                    .map(|(file, key)| (file.clone(), format!("oss://{}/{}", dest_bucket, key)))
+                    .collect(),
+                _ => BTreeMap::new(),
+            },
+            urls: presigned_urls,
         }),
+        events: Vec::new(),
+        rejection: None,
     };
 
+    // 发布处理结果到 Kafka（如果配置了）
+    if let Some(ref result) = response.result {
+        let source = format!("{}/{}", bucket, object_key);
+        let message = kafka::ResultMessage::success(&source, result, &process_result.metadata);
+        kafka::publish_result(&extended_config.kafka, &source, &message).await;
+    }
+
+    // 写入结果缓存（上传失败时已在上面返回错误，不会缓存不完整的结果）
+    if let (Some(ref cache), Some(ref result)) = (&result_cache, &response.result) {
+        // 打包输出目录时元数据只在打包文件中
+        let metadata_location = match (&result.archive, upload_location) {
+            (Some(archive), _) => archive.clone(),
+            (None, Some((ref dest_bucket, ref dest_prefix))) => {
+                format!("oss://{}/{}/{}", dest_bucket, dest_prefix, process_result.metadata_file)
+            }
+            (None, None) => output_dir.join(&process_result.metadata_file).to_string_lossy().to_string(),
+        };
+        let entry = CacheEntry {
+            etag: etag.clone(),
+            config_fingerprint: config_fingerprint.clone(),
+            source: cache_source,
+            metadata_location,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            result: result.clone(),
+        };
+        if let Err(e) = cache.store(&oss_client, &entry).await {
+            warn!("⚠️  [视频处理] 写入结果缓存失败: {}", e);
+        }
+    }
+
     // 记录处理完成后的详细输出日志
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     info!("[OSS Event] 处理完成");
@@ -499,15 +1085,20 @@ async fn handle_oss_event_internal(
         }
     }
     info!("  • 音频文件: {}", process_result.audio_file);
-    info!("  • 元数据文件: metadata.json");
+    info!("  • 元数据文件: {}", process_result.metadata_file);
+    if let Some(ref archive_file) = process_result.archive_file {
+        info!("  • 打包文件: {}", archive_file);
+    }
     if let Some(ref uploaded) = uploaded_files {
         info!("  • 已上传文件数: {}", uploaded.len());
         if !uploaded.is_empty() {
             info!("  • 上传文件列表:");
-            for (idx, file) in uploaded.iter().enumerate() {
-                info!("    {}. {}", idx + 1, file);
+            for (idx, (file, key)) in uploaded.iter().enumerate() {
+                info!("    {}. {} -> {}", idx + 1, file, key);
             }
         }
+    } else if upload_blocked {
+        info!("  • 上传状态: 内容安全检测未通过，未上传");
     } else {
         info!("  • 上传状态: 未配置目标 bucket，未上传");
     }
@@ -538,38 +1129,370 @@ pub async fn health_check() -> ResponseJson<JsonResponse> {
     })
 }
 
-/// 检查文件扩展名是否为视频文件
-fn is_video_file(filename: &str) -> bool {
-    let filename_lower = filename.to_lowercase();
-    let video_extensions = [
-        "mp4", "avi", "mov", "mkv", "wmv", "flv", "webm", "m4v",
-        "mpg", "mpeg", "3gp", "3g2", "asf", "rm", "rmvb", "vob",
-        "ts", "mts", "m2ts", "f4v", "ogv", "divx", "xvid",
-    ];
-    
-    if let Some(ext) = PathBuf::from(&filename_lower)
-        .extension()
-        .and_then(|e| e.to_str())
-    {
-        video_extensions.contains(&ext)
+/// 解析符号链接，返回目标对象的 key、大小和 ETag
+async fn resolve_symlink(bucket: &str, object_key: &str, region: &str) -> crate::error::Result<(String, u64, String)> {
+    let endpoint = format!("oss-{}-internal.aliyuncs.com", region);
+    let client = OssClient::new()?;
+    let target = client.get_symlink(bucket, object_key, Some(&endpoint)).await?;
+    let size = client.object_size(bucket, &target, Some(&endpoint)).await?;
+    let etag = client.head_object(bucket, &target, Some(&endpoint)).await?
+        .ok_or_else(|| crate::error::VideoParseError::storage_status(format!("符号链接目标不存在: {}", target)))?;
+    Ok((target, size, etag))
+}
+
+/// 输入超出大小或时长上限时的响应：`success` 为 false，`rejection` 中为拒绝原因
+fn rejected_response(rejection: Rejection) -> ResponseJson<ProcessResponse> {
+    warn!(
+        reason = rejection.reason.as_str(),
+        actual = rejection.actual,
+        limit = rejection.limit,
+        "🚫 [视频处理] {}，拒绝处理", rejection
+    );
+    telemetry::record_job("rejected");
+    ResponseJson(ProcessResponse {
+        success: false,
+        message: format!("{}，已拒绝处理", rejection),
+        result: None,
+        events: Vec::new(),
+        rejection: Some(rejection),
+    })
+}
+
+/// 目标位置已有元数据文件，且其中记录的源文件哈希与当前 ETag 相同
+///
+/// 元数据文件只在其他输出文件全部上传成功后上传；没有 `source_hash` 的元数据（旧版本写出，可能不完整）视为不匹配
+async fn existing_metadata_matches(
+    oss_client: &OssClient,
+    bucket: &str,
+    metadata_key: &str,
+    endpoint: &str,
+    etag: &str,
+    temp_dir: &std::path::Path,
+) -> crate::error::Result<bool> {
+    if oss_client.head_object(bucket, metadata_key, Some(endpoint)).await?.is_none() {
+        return Ok(false);
+    }
+    let local_path = temp_dir.join(".existing-metadata.json");
+    oss_client.download_file(bucket, metadata_key, Some(endpoint), &local_path).await?;
+    let content = std::fs::read_to_string(&local_path);
+    let _ = std::fs::remove_file(&local_path);
+    let Some(metadata) = content.ok().and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok()) else {
+        return Ok(false);
+    };
+    let source_hash = metadata.get("source_hash").and_then(|hash| hash.as_str());
+    if source_hash != Some(output_naming::etag_hash(etag).as_str()) {
+        debug!(source_hash, "目标位置的元数据不属于当前 ETag，重新处理");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// 视频输出在目标 bucket 中的路径前缀
+///
+/// 未配置 destination_prefix 时使用源文件的目录部分，再按 output_naming 分目录，避免共用前缀的视频互相覆盖输出
+fn dest_prefix(config: &ExtendedConfig, object_key: &str, etag: &str) -> String {
+    let base_prefix = config.destination_prefix.clone()
+        .unwrap_or_else(|| {
+            PathBuf::from(object_key)
+                .parent()
+                .and_then(|p| p.to_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "processed".to_string())
+        });
+    config.output_naming.dest_prefix(&base_prefix, object_key, etag)
+}
+
+/// 删除 `{dest_prefix}/` 下不在本次上传结果中的对象，失败只记录警告
+async fn clean_stale_outputs(
+    client: &OssClient,
+    bucket: &str,
+    dest_prefix: &str,
+    endpoint: &str,
+    uploaded: &BTreeMap<String, String>,
+) {
+    let prefix = format!("{}/", dest_prefix.trim_end_matches('/'));
+    let current: std::collections::HashSet<&String> = uploaded.values().collect();
+    let stale: Vec<String> = match client.list_all_objects(bucket, &prefix, Some(endpoint)).await {
+        Ok(objects) => objects.into_iter().map(|o| o.key).filter(|key| !current.contains(key)).collect(),
+        Err(e) => {
+            warn!(error = %e, "⚠️  [视频处理] 列举旧输出文件失败，跳过清理");
+            return;
+        }
+    };
+    if stale.is_empty() {
+        return;
+    }
+    match client.delete_objects(bucket, &stale, Some(endpoint)).await {
+        Ok(deleted) => info!(deleted, prefix = %prefix, "🧹 [视频处理] 已清理旧输出文件"),
+        Err(e) => warn!(error = %e, "⚠️  [视频处理] 清理旧输出文件失败"),
+    }
+}
+
+/// 为已上传的输出文件生成预签名下载地址：本地文件名 -> URL
+///
+/// 使用目标 region 的外网 endpoint，单个文件签名失败只记录警告
+fn presign_outputs(
+    client: &OssClient,
+    uploaded: &BTreeMap<String, String>,
+    bucket: &str,
+    region: &str,
+    expires_secs: u64,
+) -> BTreeMap<String, String> {
+    let endpoint = format!("oss-{}.aliyuncs.com", region);
+    let expires_in = std::time::Duration::from_secs(expires_secs);
+    uploaded
+        .iter()
+        .filter_map(|(file, key)| match client.presign_get(bucket, key, Some(&endpoint), expires_in) {
+            Ok(url) => Some((file.clone(), url)),
+            Err(e) => {
+                warn!(file = %file, error = %e, "⚠️  [视频处理] 生成预签名地址失败");
+                None
+            }
+        })
+        .collect()
+}
+
+/// 处理失败时的响应状态码：阶段超时返回 504，其他错误返回 500
+fn failure_status(error: &anyhow::Error) -> StatusCode {
+    if watchdog::as_timeout(error).is_some() {
+        StatusCode::GATEWAY_TIMEOUT
     } else {
-        false
+        StatusCode::INTERNAL_SERVER_ERROR
     }
 }
 
+/// 分段下载期间每秒报告一次已下载的字节数，下载完成或返回的守卫被丢弃时停止
+fn report_download_progress(file: std::sync::Arc<crate::progressive::ProgressiveFile>, reporter: std::sync::Arc<dyn ProgressReporter>) -> tokio_util::sync::DropGuard {
+    let stop = tokio_util::sync::CancellationToken::new();
+    let stopped = stop.clone();
+    tokio::spawn(async move {
+        loop {
+            let done = file.download_duration().is_some();
+            reporter.update(&StageProgress::new("download", file.downloaded(), Some(file.total_size()), ProgressUnit::Bytes));
+            if done {
+                break;
+            }
+            tokio::select! {
+                _ = stopped.cancelled() => break,
+                _ = tokio::time::sleep(std::time::Duration::from_secs(1)) => {}
+            }
+        }
+    });
+    stop.drop_guard()
+}
+
+/// Prometheus 指标 Handler
+pub async fn handle_metrics() -> Response {
+    ([(axum::http::header::CONTENT_TYPE, metrics::CONTENT_TYPE)], Metrics::shared().render()).into_response()
+}
+
+/// 处理队列已满：返回 429，`Retry-After` 为建议的重试等待秒数
+fn queue_full_response(full: QueueFull) -> Response {
+    let body = JsonResponse {
+        success: false,
+        message: full.to_string(),
+        data: Some(serde_json::json!({
+            "running": full.running,
+            "queued": full.queued,
+            "retry_after": full.retry_after.as_secs(),
+        })),
+    };
+    (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, full.retry_after.as_secs().to_string())], ResponseJson(body)).into_response()
+}
+
+/// 同步处理端点的并发限制中间件：与异步任务共用 `[jobs]` 的并发名额，
+/// 没有空闲名额时排队等待，等待的请求数达到 `max_queued` 后直接返回 429
+pub async fn limit_concurrency(request: Request, next: Next) -> Response {
+    match JobManager::shared().acquire().await {
+        Ok(_permit) => next.run(request).await,
+        Err(full) => {
+            warn!(path = %request.uri().path(), "⚠️  [任务队列] {}", full);
+            queue_full_response(full)
+        }
+    }
+}
+
+/// 记录失败指标并调用失败回调（如果配置了），`stage` 为失败发生的阶段
+async fn notify_failure(config: &ProcessConfig, bucket: &str, object_key: &str, stage: &'static str, error: &anyhow::Error, final_attempt: bool) {
+    let report = FailureReport::new(stage, error);
+    telemetry::record_job("failure");
+    telemetry::record_failure(&report.error.code);
+    if let Some(url) = config.failure_webhook_url().filter(|_| final_attempt) {
+        processor::notify_failure_webhook(url, &config.webhook, &format!("oss://{}/{}", bucket, object_key), &report).await;
+    }
+}
+
+/// 任务日志查询 Handler
+///
+/// `id` 可以是 OSS 事件的 request_id（函数计算请求 ID），也可以是处理结果中的 job_id
+pub async fn handle_job_logs(
+    Path(job_id): Path<String>,
+) -> Result<ResponseJson<JobLogSnapshot>, (StatusCode, String)> {
+    JobLogStore::shared()
+        .get(&job_id)
+        .map(ResponseJson)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("任务日志不存在或已过期: {}", job_id)))
+}
+
+/// 任务进度查询 Handler
+///
+/// `id` 为 OSS 事件的 request_id（与 `/jobs/{id}` 的任务 ID 相同），只保留最近的任务
+pub async fn handle_job_progress(
+    Path(job_id): Path<String>,
+) -> Result<ResponseJson<JobProgress>, (StatusCode, String)> {
+    ProgressStore::shared()
+        .get(&job_id)
+        .map(ResponseJson)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("任务进度不存在或已过期: {}", job_id)))
+}
+
+/// 任务实时进度 WebSocket Handler
+///
+/// 连接后先推送当前状态，之后推送阶段、进度和日志事件（JSON 文本消息），
+/// 处理结束时推送 finished 事件并关闭连接
+pub async fn handle_job_ws(
+    Path(job_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, (StatusCode, String)> {
+    if ProgressStore::shared().get(&job_id).is_none() && JobManager::shared().get(&job_id).is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("任务不存在或已过期: {}", job_id)));
+    }
+    Ok(ws.on_upgrade(move |socket| stream_job_events(socket, job_id)))
+}
+
+async fn stream_job_events(mut socket: WebSocket, job_id: String) {
+    let store = ProgressStore::shared();
+    // 先订阅再取快照，避免漏掉两者之间的事件
+    let mut events = store.subscribe();
+    debug!(job_id = %job_id, "🔌 [WebSocket] 订阅任务进度");
+    if let Some(snapshot) = store.get(&job_id) {
+        let event = ProgressEvent::snapshot(&snapshot);
+        let finished = event.outcome.is_some();
+        if !send_event(&mut socket, &event).await || finished {
+            let _ = socket.send(Message::Close(None)).await;
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.job_id == job_id => {
+                    let finished = event.event == "finished";
+                    if !send_event(&mut socket, &event).await || finished {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(job_id = %job_id, skipped, "⚠️  [WebSocket] 推送跟不上，已丢弃部分事件");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+    debug!(job_id = %job_id, "🔌 [WebSocket] 任务进度推送结束");
+}
+
+async fn send_event(socket: &mut WebSocket, event: &ProgressEvent) -> bool {
+    match serde_json::to_string(event) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// 任务查询 Handler
+///
+/// 先查询进程内任务队列（`/invoke` 提交的任务），不存在时查询 PostgreSQL 中的任务记录
+/// （任务状态、各场景元数据和产物位置）
+pub async fn handle_job(
+    Path(job_id): Path<String>,
+) -> Result<Response, (StatusCode, String)> {
+    if let Some(job) = JobManager::shared().get(&job_id) {
+        return Ok(ResponseJson(job).into_response());
+    }
+    let extended_config = ConfigLoader::load_extended_config(None).unwrap_or_default();
+    if extended_config.database.url.is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("任务不存在: {}", job_id)));
+    }
+    match database::fetch_job(&extended_config.database, &job_id).await {
+        Ok(Some(record)) => Ok(ResponseJson(record).into_response()),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("任务不存在: {}", job_id))),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "❌ [数据库] 查询任务记录失败");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("查询任务记录失败: {}", e)))
+        }
+    }
+}
+
+/// 任务列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct JobListParams {
+    /// 只列出指定状态的任务（queued | running | done | failed）
+    pub state: Option<String>,
+}
+
+/// 进程内任务列表 Handler
+pub async fn handle_jobs(
+    Query(params): Query<JobListParams>,
+) -> Result<ResponseJson<JobList>, (StatusCode, String)> {
+    let state = params.state
+        .map(|state| state.parse::<JobState>())
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok(ResponseJson(JobManager::shared().list(state)))
+}
+
+/// 任务的本地输出目录
+///
+/// `job_id` 为处理结果中的任务 ID（处理完成时记录了实际输出目录）或异步任务 ID
+fn job_output_dir(job_id: &str) -> Option<PathBuf> {
+    output_naming::OutputDirs::shared()
+        .get(job_id)
+        .or_else(|| JobManager::shared().get(job_id).and_then(|job| job.result).map(|result| PathBuf::from(result.output_dir)))
+}
+
+/// 读取任务输出目录中的文件（GET /outputs/{job_id}/{filename}），不需要 OSS 权限即可查看关键帧等结果
+///
+/// 按扩展名返回 Content-Type，支持 Range 请求；文件名不能跳出输出目录
+pub async fn handle_output_file(
+    Path((job_id, file)): Path<(String, String)>,
+    request: Request,
+) -> Result<Response, (StatusCode, String)> {
+    let Some(path) = job_output_dir(&job_id).and_then(|dir| output_naming::resolve_output_file(&dir, &file)) else {
+        warn!(job_id = %job_id, file = %file, "⚠️  [Outputs] 输出文件不存在或路径不合法");
+        return Err((StatusCode::NOT_FOUND, format!("输出文件不存在: {}/{}", job_id, file)));
+    };
+    debug!(job_id = %job_id, path = %path.display(), "读取输出文件");
+    let mut service = tower_http::services::ServeFile::new(path);
+    let response = match tower::Service::call(&mut service, request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    };
+    Ok(response.map(axum::body::Body::new))
+}
+
 /// 直接处理请求（支持本地文件路径或OSS事件）
 #[derive(Debug, Deserialize)]
 pub struct DirectProcessRequest {
-    /// 视频文件路径（本地路径或OSS路径）
+    /// 视频文件路径（本地路径、OSS路径或 HTTP / HTTPS URL）
     pub input: String,
     /// 输出目录（可选，默认使用临时目录）
     pub output: Option<String>,
+    /// 速度预设（fast | balanced | accurate）
+    pub preset: Option<Preset>,
     /// 场景变化检测阈值
     pub threshold: Option<f64>,
     /// 最小场景持续时间（秒）
     pub min_scene_duration: Option<f64>,
     /// 帧采样率
     pub sample_rate: Option<f64>,
+    /// 处理完成后把输出目录打包为单个文件（zip | tar.gz）
+    pub archive: Option<ArchiveFormat>,
     /// 是否为OSS路径（如果为true，会从OSS下载）
     pub is_oss_path: Option<bool>,
     /// OSS bucket（如果is_oss_path为true，需要提供）
@@ -578,10 +1501,33 @@ pub struct DirectProcessRequest {
     pub oss_region: Option<String>,
 }
 
-/// 直接处理视频的 Handler（支持本地文件和OSS文件）
+/// 直接处理时存放下载文件的临时目录（按函数计算请求 ID 或时间戳 + UUID 区分）
+fn direct_temp_dir() -> Result<PathBuf, (StatusCode, String)> {
+    let request_id = std::env::var("FC_REQUEST_ID")
+        .unwrap_or_else(|_| {
+            format!("{}_{}", 
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+                uuid::Uuid::new_v4().to_string()
+            )
+        });
+    let temp_dir = std::env::temp_dir().join("video-parse").join(&request_id);
+    std::fs::create_dir_all(&temp_dir)
+        .map_err(|e| {
+            error!("[Direct Process] 创建临时目录失败: {} (路径: {})", e, temp_dir.display());
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("创建临时目录失败: {}", e))
+        })?;
+    Ok(temp_dir)
+}
+
+/// 直接处理视频的 Handler（支持本地文件、OSS文件和 HTTP / HTTPS URL）
+#[instrument(name = "direct_process", skip_all, fields(input = %request.input))]
 pub async fn handle_direct_process(
     Json(request): Json<DirectProcessRequest>,
 ) -> Result<ResponseJson<ProcessResponse>, (StatusCode, String)> {
+    let _active_job = Metrics::shared().active_job();
     // 记录接收到的请求详情
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     info!("[Direct Process] 收到直接处理请求");
@@ -593,6 +1539,12 @@ pub async fn handle_direct_process(
         info!("  • OSS Bucket: {:?}", request.oss_bucket);
         info!("  • OSS Region: {:?}", request.oss_region);
     }
+    if let Some(preset) = request.preset {
+        info!("  • 预设: {}", preset);
+    }
+    if let Some(archive) = request.archive {
+        info!("  • 打包格式: {}", archive.as_str());
+    }
     if request.threshold.is_some() || request.min_scene_duration.is_some() || request.sample_rate.is_some() {
         info!("  • 自定义参数:");
         if let Some(t) = request.threshold {
@@ -608,7 +1560,34 @@ pub async fn handle_direct_process(
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     
     // 确定输入文件路径
-    let input_path = if request.is_oss_path.unwrap_or(false) {
+    // 任务来源，写入数据库任务记录
+    let source = match (request.is_oss_path.unwrap_or(false), &request.oss_bucket) {
+        (true, Some(bucket)) => format!("oss://{}/{}", bucket, request.input),
+        _ => request.input.clone(),
+    };
+    let extended_config = ConfigLoader::load_extended_config(None).unwrap_or_default();
+
+    let (input_path, download) = if url_input::is_http_url(&request.input) {
+        // HTTP / HTTPS URL，流式下载到临时目录
+        let temp_dir = direct_temp_dir()?;
+        let max_bytes = extended_config.url_max_size_mb.saturating_mul(1024 * 1024);
+        info!("[Direct Process] 开始下载URL: {} (上限 {} MB)", request.input, extended_config.url_max_size_mb);
+        let download_start = std::time::Instant::now();
+        let video_path = url_input::download_url(&request.input, &temp_dir, max_bytes)
+            .await
+            .map_err(|e| {
+                error!("[Direct Process] 下载URL失败: url={}, error={}", request.input, e);
+                let status = match e {
+                    VideoParseError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::BAD_GATEWAY,
+                };
+                (status, format!("下载文件失败: {}", e))
+            })?;
+        info!("[Direct Process] 文件下载成功: {}", video_path.display());
+        let download = (download_start.elapsed(), resource::disk_usage(&video_path));
+
+        (video_path, Some(download))
+    } else if request.is_oss_path.unwrap_or(false) {
         // OSS路径，需要下载
         let bucket = request.oss_bucket.ok_or_else(|| {
             (StatusCode::BAD_REQUEST, "OSS路径需要提供 oss_bucket".to_string())
@@ -618,40 +1597,23 @@ pub async fn handle_direct_process(
         })?;
         
         // 创建临时目录
-        let request_id = std::env::var("FC_REQUEST_ID")
-            .unwrap_or_else(|_| {
-                format!("{}_{}", 
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    uuid::Uuid::new_v4().to_string()
-                )
-            });
-        let temp_dir = std::env::temp_dir().join("video-parse").join(&request_id);
-        std::fs::create_dir_all(&temp_dir)
-            .map_err(|e| {
-                error!("[Direct Process] 创建临时目录失败: {} (路径: {})", e, temp_dir.display());
-                (StatusCode::INTERNAL_SERVER_ERROR, format!("创建临时目录失败: {}", e))
-            })?;
+        let temp_dir = direct_temp_dir()?;
         
         // 下载文件
         let oss_client = OssClient::new()
+            .map(|client| client.with_download_retries(extended_config.download_max_retries))
             .map_err(|e| {
                 error!("[Direct Process] 创建 OSS 客户端失败: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("创建 OSS 客户端失败: {}", e))
             })?;
         
-        let input_path_buf = PathBuf::from(&request.input);
-        let video_filename = input_path_buf
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("video.mp4");
-        let video_path = temp_dir.join(video_filename);
+        let video_filename = output_naming::local_file_name(&request.input);
+        let video_path = temp_dir.join(&video_filename);
         
         let endpoint = format!("oss-{}-internal.aliyuncs.com", region);
         
         info!("[Direct Process] 开始下载OSS文件: bucket={}, key={}, endpoint={}", bucket, request.input, endpoint);
+        let download_start = std::time::Instant::now();
         oss_client
             .download_file(&bucket, &request.input, Some(&endpoint), &video_path)
             .await
@@ -660,11 +1622,12 @@ pub async fn handle_direct_process(
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("下载文件失败: {}", e))
             })?;
         info!("[Direct Process] 文件下载成功: {}", video_path.display());
+        let download = (download_start.elapsed(), resource::disk_usage(&video_path));
         
-        video_path
+        (video_path, Some(download))
     } else {
         // 本地路径
-        PathBuf::from(&request.input)
+        (PathBuf::from(&request.input), None)
     };
     
     // 检查文件是否存在
@@ -701,25 +1664,48 @@ pub async fn handle_direct_process(
             (StatusCode::INTERNAL_SERVER_ERROR, format!("创建输出目录失败: {}", e))
         })?;
     
-    // 构建配置：优先级为 请求参数 > 环境变量 > 配置文件 > 默认值
-    let config = ConfigLoader::load_config(
+    // 构建配置：优先级为 请求参数 > 环境变量 > 配置文件 > 预设 > 默认值
+    // webhook_url 从配置文件或环境变量读取
+    let config = ConfigLoader::load_config_with_overrides(
         None,
-        request.threshold,
-        request.min_scene_duration,
-        request.sample_rate,
-        None, // webhook_url 从配置文件或环境变量读取
+        ConfigOverrides {
+            preset: request.preset,
+            threshold: request.threshold,
+            min_scene_duration: request.min_scene_duration,
+            sample_rate: request.sample_rate,
+            archive: request.archive,
+            ..Default::default()
+        },
     )
     .unwrap_or_else(|_| ProcessConfig::default());
     
     // 处理视频
     info!("[Direct Process] 开始处理视频: {}", input_path.display());
-    let process_result = process_video(&input_path, &output_dir, config)
-        .await
-        .map_err(|e| {
+    let config_fingerprint = config.fingerprint();
+    let mut process_result = match Pipeline::for_config(&config).run_source(&input_path, None, &output_dir, config, ProcessOptions::default()).await {
+        Ok(result) => result,
+        Err(e) => {
+            if let Some(rejection) = preflight::as_rejection(&e) {
+                return Ok(rejected_response(rejection.clone()));
+            }
             error!("[Direct Process] 处理视频失败: path={}, error={}", input_path.display(), e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("处理视频失败: {}", e))
-        })?;
+            database::record_failure(&extended_config.database, &source, &e.to_string()).await;
+            notify::send(&extended_config.notify, &notify::Notification::failure(&source, e.to_string())).await;
+            return Err((failure_status(&e), format!("处理视频失败: {}", e)));
+        }
+    };
+    if let Some((duration, bytes)) = download {
+        let timings = &mut process_result.timings;
+        timings.download_ms = Some(duration.as_millis() as u64);
+        timings.download_bytes = Some(bytes);
+        timings.total_ms += duration.as_millis() as u64;
+    }
     info!("[Direct Process] 视频处理完成: 场景数={}", process_result.metadata.scene_count);
+    let output_dir = process_result.output_dir.clone();
+    let artifacts = database::local_artifacts(&process_result);
+    database::record_success(&extended_config.database, &process_result, &source, &config_fingerprint, &artifacts).await;
+    let outputs = output_dir.to_string_lossy().to_string();
+    notify::send(&extended_config.notify, &notify::Notification::success(&source, &process_result, outputs)).await;
     
     // 构建响应
     let response = ProcessResponse {
@@ -729,13 +1715,23 @@ pub async fn handle_direct_process(
             process_result.metadata.scene_count
         ),
         result: Some(ProcessResult {
+            job_id: Some(process_result.job_id.clone()),
             video_file: input_path.to_string_lossy().to_string(),
             output_dir: output_dir.to_string_lossy().to_string(),
             scene_count: process_result.metadata.scene_count,
             keyframes: process_result.keyframe_files.clone(),
             audio_file: process_result.audio_file.clone(),
-            metadata_file: "metadata.json".to_string(),
+            metadata_file: process_result.metadata_file.clone(),
+            archive: process_result.archive_file.as_ref().map(|file| output_dir.join(file).to_string_lossy().to_string()),
+            resources: Some(process_result.resources.clone()),
+            timings: Some(process_result.timings.clone()),
+            upload_blocked: false,
+            webhook: process_result.webhook.clone(),
+            outputs: BTreeMap::new(),
+            urls: BTreeMap::new(),
         }),
+        events: Vec::new(),
+        rejection: None,
     };
     
     // 记录处理完成后的详细输出日志
@@ -754,7 +1750,10 @@ pub async fn handle_direct_process(
         }
     }
     info!("  • 音频文件: {}", process_result.audio_file);
-    info!("  • 元数据文件: metadata.json");
+    info!("  • 元数据文件: {}", process_result.metadata_file);
+    if let Some(ref archive_file) = process_result.archive_file {
+        info!("  • 打包文件: {}", archive_file);
+    }
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     
     Ok(ResponseJson(response))
@@ -765,9 +1764,11 @@ pub async fn handle_direct_process(
 pub struct ProcessQueryParams {
     pub input: String,
     pub output: Option<String>,
+    pub preset: Option<Preset>,
     pub threshold: Option<f64>,
     pub min_scene_duration: Option<f64>,
     pub sample_rate: Option<f64>,
+    pub archive: Option<ArchiveFormat>,
 }
 
 /// 通过查询参数处理视频（GET请求，方便测试）
@@ -797,9 +1798,11 @@ pub async fn handle_process_query(
     let request = DirectProcessRequest {
         input: params.input,
         output: params.output,
+        preset: params.preset,
         threshold: params.threshold,
         min_scene_duration: params.min_scene_duration,
         sample_rate: params.sample_rate,
+        archive: params.archive,
         is_oss_path: Some(false),
         oss_bucket: None,
         oss_region: None,
@@ -808,6 +1811,136 @@ pub async fn handle_process_query(
     handle_direct_process(Json(request)).await
 }
 
+/// 视频探测的查询参数
+#[derive(Debug, Deserialize)]
+pub struct ProbeQueryParams {
+    /// 本地视频文件路径、HTTP / HTTPS URL 或 `oss://bucket/key`
+    pub input: String,
+    /// 估算处理工作量使用的预设和采样率
+    pub preset: Option<Preset>,
+    pub sample_rate: Option<f64>,
+}
+
+/// 探测视频信息（GET /probe?input=..），不下载整个文件也不提取帧
+///
+/// 返回时长、分辨率、帧率、编码、码率、音频声道和容器格式，以及按当前配置估算的工作量和输入上限检查结果。
+/// `oss://` 输入生成预签名 URL 后由 FFmpeg 直接读取
+pub async fn handle_probe(
+    Query(params): Query<ProbeQueryParams>,
+) -> Result<ResponseJson<preflight::ProbeReport>, (StatusCode, String)> {
+    info!(input = %params.input, "🔍 [Probe] 收到视频探测请求");
+    // HTTP / HTTPS 输入由 FFmpeg 直接读取，同样不允许指向内网或本机地址
+    if url_input::is_http_url(&params.input) {
+        let url = reqwest::Url::parse(params.input.trim())
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("无效的 URL: {} ({})", params.input, e)))?;
+        if let Err(e) = url_input::check_public_url(&url).await {
+            warn!(error = %e, "⚠️  [Probe] 拒绝探测该 URL");
+            return Err((StatusCode::BAD_REQUEST, e.to_string()));
+        }
+    }
+    let input = match crate::webhook::parse_oss_location(&params.input) {
+        Some((bucket, key)) => OssClient::new()
+            .and_then(|client| client.presign_get(bucket, key, None, std::time::Duration::from_secs(PROBE_URL_EXPIRES_SECS)))
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("生成预签名 URL 失败: {}", e)))?,
+        None => params.input.clone(),
+    };
+    let config = ConfigLoader::load_config_with_overrides(
+        None,
+        ConfigOverrides {
+            preset: params.preset,
+            sample_rate: params.sample_rate,
+            ..Default::default()
+        },
+    )
+    .unwrap_or_else(|_| ProcessConfig::default());
+    let result = tokio::task::spawn_blocking(move || preflight::probe(&input, &config))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("探测任务异常终止: {}", e)))?;
+
+    match result {
+        Ok(mut report) => {
+            // 不返回带签名的 URL
+            report.input = params.input;
+            info!(
+                duration = report.media.duration,
+                media_kind = report.media_kind.as_str(),
+                rejected = report.rejection.is_some(),
+                "✅ [Probe] 视频探测完成"
+            );
+            Ok(ResponseJson(report))
+        }
+        Err(e) => {
+            warn!(error = %e, "⚠️  [Probe] 视频探测失败");
+            let status = match e {
+                VideoParseError::InvalidInput(_) | VideoParseError::NoVideoStream | VideoParseError::Decode { .. } => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+/// 单帧提取的查询参数
+#[derive(Debug, Deserialize)]
+pub struct FrameQueryParams {
+    /// 本地视频文件路径
+    pub input: String,
+    /// 时间点（秒）
+    pub t: f64,
+    /// 图片格式：jpeg（默认）/ png / webp / avif
+    pub format: Option<String>,
+    /// 最大宽度（像素），更宽的帧按比例缩小
+    pub width: Option<u32>,
+}
+
+/// 提取视频任意时间点的单帧（GET /frame?input=..&t=..），直接返回图片
+///
+/// 响应头 `x-frame-time` 为帧的实际时间戳（秒）
+pub async fn handle_frame(
+    Query(params): Query<FrameQueryParams>,
+) -> Result<Response, (StatusCode, String)> {
+    info!(input = %params.input, t = params.t, "🖼️  [Frame] 收到单帧提取请求");
+    let format = match params.format.as_deref() {
+        Some(format) => format.parse::<KeyframeFormat>().map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        None => KeyframeFormat::Jpeg,
+    };
+    let image_config = KeyframeImageConfig {
+        format: format.effective(),
+        max_width: params.width,
+        ..Default::default()
+    };
+    let encode_config = image_config.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let (time, image) = VideoProcessor::new(&params.input)?.extract_frame_at(params.t)?;
+        let mut bytes = Vec::new();
+        encode_config.encode(&image, &mut bytes).map_err(VideoParseError::Other)?;
+        Ok::<_, VideoParseError>((time, bytes))
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("单帧提取任务异常终止: {}", e)))?;
+
+    match result {
+        Ok((time, bytes)) => {
+            info!(time, bytes = bytes.len(), "✅ [Frame] 单帧提取完成");
+            Ok((
+                [
+                    (axum::http::header::CONTENT_TYPE, image_config.format.mime_type().to_string()),
+                    (axum::http::header::HeaderName::from_static("x-frame-time"), format!("{:.3}", time)),
+                ],
+                bytes,
+            ).into_response())
+        }
+        Err(e) => {
+            warn!(error = %e, "⚠️  [Frame] 单帧提取失败");
+            let status = match e {
+                VideoParseError::InvalidInput(_) | VideoParseError::NoVideoStream => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
+
 /// 函数计算初始化端点
 /// 函数计算在启动时会调用此端点进行初始化
 pub async fn handle_initialize(
@@ -846,7 +1979,7 @@ pub async fn handle_initialize(
 pub async fn handle_invoke(
     headers: HeaderMap,
     body: Bytes,
-) -> Result<ResponseJson<JsonResponse>, (StatusCode, String)> {
+) -> Result<ResponseJson<JsonResponse>, Response> {
     // 从请求头读取请求ID
     let request_id = headers
         .get("x-fc-request-id")
@@ -892,35 +2025,19 @@ pub async fn handle_invoke(
                     info!("  • 源IP: {}", event_item.request_parameters.source_ip_address);
                     debug!("完整事件数据: {:?}", event);
                     
-                    // 检查文件类型
-                    if !is_video_file(object_key) {
-                        info!("文件 {} 不是视频文件，跳过处理", object_key);
+                    // 与 /process 使用相同的检查，异步任务启动前先跳过不需要处理的事件（多事件请求全部需要跳过时才跳过）
+                    let skip_reasons: Option<Vec<String>> = event.events.iter().map(OssEventItem::skip_reason).collect();
+                    if let Some(reason) = skip_reasons.map(|reasons| reasons.join("；")) {
+                        info!("{}，跳过处理", reason);
                         info!("FC Invoke End RequestId: {}", request_id);
                         info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
                         return Ok(ResponseJson(JsonResponse {
                             success: true,
-                            message: format!("文件 {} 不是视频文件，已跳过处理", object_key),
-                            data: Some(serde_json::json!({
-                                "request_id": request_id,
-                                "object_key": object_key,
-                                "file_type": "non-video",
-                                "skipped": true,
-                                "timestamp": chrono::Utc::now().to_rfc3339(),
-                            })),
-                        }));
-                    }
-                    
-                    // 只处理 ObjectCreated 事件
-                    if !event_item.event_name.starts_with("ObjectCreated") {
-                        info!("事件类型 {} 不是 ObjectCreated，跳过处理", event_item.event_name);
-                        info!("FC Invoke End RequestId: {}", request_id);
-                        info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-                        return Ok(ResponseJson(JsonResponse {
-                            success: true,
-                            message: format!("事件类型 {} 不是 ObjectCreated，已跳过处理", event_item.event_name),
+                            message: format!("{}，已跳过处理", reason),
                             data: Some(serde_json::json!({
                                 "request_id": request_id,
                                 "event_name": event_item.event_name,
+                                "object_key": object_key,
                                 "skipped": true,
                                 "timestamp": chrono::Utc::now().to_rfc3339(),
                             })),
@@ -936,14 +2053,19 @@ pub async fn handle_invoke(
                         event_item.oss.object.size,
                         event_item.oss.object.size as f64 / 1024.0 / 1024.0);
                     
-                    // 调用内部处理函数（异步处理，不阻塞响应）
-                    // 注意：这里我们启动一个异步任务来处理，立即返回 JSON 响应
+                    // 作为任务提交到进程内任务队列（异步处理，不阻塞响应），立即返回 JSON 响应
+                    // 没有请求 ID 时生成一个，作为任务 ID 查询状态和日志
+                    let job_id = if request_id == "unknown" {
+                        uuid::Uuid::new_v4().to_string()
+                    } else {
+                        request_id.to_string()
+                    };
                     let event_clone = event.clone();
-                    let request_id_clone = request_id.to_string();
+                    let request_id_clone = job_id.clone();
                     let bucket_clone = event_item.oss.bucket.name.clone();
                     let object_key_clone = object_key.to_string();
                     
-                    tokio::spawn(async move {
+                    let submitted = JobManager::shared().submit(job_id, &event_item.oss.bucket.name, object_key, move || async move {
                         info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
                         info!("🚀 [异步任务] 开始处理视频 RequestId: {}", request_id_clone);
                         info!("  • Bucket: {}", bucket_clone);
@@ -965,6 +2087,11 @@ pub async fn handle_invoke(
                                     info!("  • 输出目录: {}", result.output_dir);
                                 }
                                 info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+                                if response.0.success {
+                                    Ok(response.0.result)
+                                } else {
+                                    Err(response.0.message)
+                                }
                             }
                             Err((status_code, error_msg)) => {
                                 let duration = start_time.elapsed();
@@ -976,11 +2103,33 @@ pub async fn handle_invoke(
                                 error!("  • Bucket: {}", bucket_clone);
                                 error!("  • Object Key: {}", object_key_clone);
                                 error!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+                                Err(error_msg)
                             }
                         }
                     });
+                    let job = match submitted {
+                        Ok(job) => job,
+                        Err(full) => {
+                            warn!("⚠️  {}，拒绝任务", full);
+                            info!("FC Invoke End RequestId: {}", request_id);
+                            info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+                            return Err(queue_full_response(full));
+                        }
+                    };
                     
-                    info!("✅ 已启动异步处理任务，任务ID: {}", request_id);
+                    info!("✅ 已提交异步处理任务，任务ID: {}，状态: {}", job.id, job.state.as_str());
+                    info!("FC Invoke End RequestId: {}", request_id);
+                    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+                    return Ok(ResponseJson(JsonResponse {
+                        success: true,
+                        message: "任务已提交".to_string(),
+                        data: Some(serde_json::json!({
+                            "request_id": request_id,
+                            "job_id": job.id,
+                            "state": job.state,
+                            "timestamp": chrono::Utc::now().to_rfc3339(),
+                        })),
+                    }));
                 } else {
                     warn!("OSS 事件列表为空");
                 }
@@ -0,0 +1,241 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// 默认每个任务最多保留的日志行数
+const DEFAULT_MAX_LINES_PER_JOB: usize = 2000;
+/// 默认最多保留日志的任务数量（超出后淘汰最早的任务）
+const DEFAULT_MAX_JOBS: usize = 200;
+
+/// span 中标识任务的字段名
+const JOB_ID_FIELDS: &[&str] = &["request_id", "job_id"];
+
+/// 按任务缓存的日志
+///
+/// 每个任务一个有上限的环形缓冲区，超出上限时丢弃最早的行；
+/// 任务数量也有上限，超出后淘汰最早创建的任务
+pub struct JobLogStore {
+    inner: Mutex<StoreInner>,
+    max_jobs: usize,
+    max_lines_per_job: usize,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    jobs: HashMap<String, JobLog>,
+    order: VecDeque<String>,
+}
+
+#[derive(Default)]
+struct JobLog {
+    lines: VecDeque<String>,
+    dropped: usize,
+}
+
+/// 任务日志快照
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JobLogSnapshot {
+    /// 任务 ID（request_id 或 job_id）
+    pub job_id: String,
+    /// 因超出缓冲区上限被丢弃的行数
+    pub dropped: usize,
+    /// 日志行
+    pub lines: Vec<String>,
+}
+
+impl JobLogStore {
+    pub fn new(max_jobs: usize, max_lines_per_job: usize) -> Self {
+        Self {
+            inner: Mutex::new(StoreInner::default()),
+            max_jobs: max_jobs.max(1),
+            max_lines_per_job: max_lines_per_job.max(1),
+        }
+    }
+
+    /// 进程内共享的任务日志（由 [`JobLogLayer`] 写入）
+    pub fn shared() -> &'static JobLogStore {
+        static SHARED: OnceLock<JobLogStore> = OnceLock::new();
+        SHARED.get_or_init(|| JobLogStore::new(DEFAULT_MAX_JOBS, DEFAULT_MAX_LINES_PER_JOB))
+    }
+
+    /// 追加一行日志
+    pub fn append(&self, job_id: &str, line: String) {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.jobs.contains_key(job_id) {
+            while inner.order.len() >= self.max_jobs {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.jobs.remove(&oldest);
+                }
+            }
+            inner.order.push_back(job_id.to_string());
+        }
+        let log = inner.jobs.entry(job_id.to_string()).or_default();
+        if log.lines.len() >= self.max_lines_per_job {
+            log.lines.pop_front();
+            log.dropped += 1;
+        }
+        log.lines.push_back(line);
+    }
+
+    /// 获取任务的日志快照（任务不存在或已被淘汰时返回 None）
+    pub fn get(&self, job_id: &str) -> Option<JobLogSnapshot> {
+        let inner = self.inner.lock().unwrap();
+        inner.jobs.get(job_id).map(|log| JobLogSnapshot {
+            job_id: job_id.to_string(),
+            dropped: log.dropped,
+            lines: log.lines.iter().cloned().collect(),
+        })
+    }
+}
+
+/// 把日志按任务写入 [`JobLogStore::shared`] 的 tracing layer
+///
+/// 事件所在 span（及其父 span）带有 `request_id` 或 `job_id` 字段时，
+/// 日志行写入对应任务的缓冲区；同时带有两个字段时两个任务都会记录
+pub struct JobLogLayer;
+
+/// span 扩展：该 span 上记录的任务 ID
+struct JobIds(Vec<String>);
+
+impl<S> Layer<S> for JobLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = JobIdVisitor::default();
+        attrs.record(&mut visitor);
+        if let (false, Some(span)) = (visitor.0.is_empty(), ctx.span(id)) {
+            span.extensions_mut().insert(JobIds(visitor.0));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = JobIdVisitor::default();
+        values.record(&mut visitor);
+        if visitor.0.is_empty() {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            match extensions.get_mut::<JobIds>() {
+                Some(ids) => ids.0.extend(visitor.0),
+                None => extensions.insert(JobIds(visitor.0)),
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        let mut job_ids = Vec::new();
+        for span in scope {
+            if let Some(ids) = span.extensions().get::<JobIds>() {
+                job_ids.extend(ids.0.iter().cloned());
+            }
+        }
+        if job_ids.is_empty() {
+            return;
+        }
+
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:>5} {}: {}{}",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            metadata.level(),
+            metadata.target(),
+            visitor.message,
+            visitor.fields,
+        );
+
+        // 同时推送给 `GET /jobs/{id}/ws` 的订阅方
+        let store = JobLogStore::shared();
+        let progress = crate::progress::ProgressStore::shared();
+        for job_id in job_ids {
+            progress.log(&job_id, &line);
+            store.append(&job_id, line.clone());
+        }
+    }
+}
+
+/// 收集 span 上的任务 ID 字段
+#[derive(Default)]
+struct JobIdVisitor(Vec<String>);
+
+impl JobIdVisitor {
+    fn push(&mut self, field: &Field, value: String) {
+        if JOB_ID_FIELDS.contains(&field.name()) && !value.is_empty() {
+            self.0.push(value);
+        }
+    }
+}
+
+impl Visit for JobIdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, format!("{:?}", value));
+    }
+}
+
+/// 把事件格式化为 `消息 key=value ...`
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_are_bounded() {
+        let store = JobLogStore::new(10, 2);
+        for i in 0..5 {
+            store.append("job", format!("line {}", i));
+        }
+        let snapshot = store.get("job").unwrap();
+        assert_eq!(snapshot.lines, vec!["line 3", "line 4"]);
+        assert_eq!(snapshot.dropped, 3);
+    }
+
+    #[test]
+    fn test_oldest_job_evicted() {
+        let store = JobLogStore::new(2, 10);
+        store.append("a", "x".to_string());
+        store.append("b", "x".to_string());
+        store.append("c", "x".to_string());
+        assert!(store.get("a").is_none());
+        assert!(store.get("b").is_some());
+        assert!(store.get("c").is_some());
+    }
+}
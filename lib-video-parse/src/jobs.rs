@@ -0,0 +1,364 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::oss_event::ProcessResult;
+
+/// 进程内任务队列配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobsConfig {
+    /// 同时处理的任务数，其余任务排队等待
+    pub max_concurrent: usize,
+    /// 最多保留的已结束任务数（超出后淘汰最早提交的任务）
+    pub max_retained: usize,
+    /// 没有空闲名额时最多等待的任务数（异步任务和同步处理请求合计），超出后拒绝新任务
+    pub max_queued: usize,
+    /// 拒绝时建议客户端等待的时间（秒），作为 429 响应的 Retry-After
+    pub retry_after_secs: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            max_retained: 200,
+            max_queued: 8,
+            retry_after_secs: 30,
+        }
+    }
+}
+
+/// 处理队列已满：同时处理的任务数和等待的任务数都已达到上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("处理队列已满（{running} 个任务处理中，{queued} 个等待），请 {} 秒后重试", retry_after.as_secs())]
+pub struct QueueFull {
+    pub running: usize,
+    pub queued: usize,
+    /// 建议客户端等待的时间
+    pub retry_after: Duration,
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// 等待并发名额
+    Queued,
+    /// 正在处理
+    Running,
+    /// 处理成功
+    Done,
+    /// 处理失败
+    Failed,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done => "done",
+            JobState::Failed => "failed",
+        }
+    }
+
+    /// 任务是否已结束
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed)
+    }
+}
+
+impl std::str::FromStr for JobState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "queued" => Ok(JobState::Queued),
+            "running" => Ok(JobState::Running),
+            "done" => Ok(JobState::Done),
+            "failed" => Ok(JobState::Failed),
+            other => Err(format!("未知的任务状态: {}（可选值: queued, running, done, failed）", other)),
+        }
+    }
+}
+
+/// 进程内任务
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    /// 任务 ID（函数计算请求 ID，与 `/jobs/{id}/logs` 的 ID 相同）
+    pub id: String,
+    pub state: JobState,
+    pub bucket: String,
+    pub object_key: String,
+    /// 提交时间（RFC 3339）
+    pub submitted_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 处理结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ProcessResult>,
+}
+
+/// `GET /jobs` 的响应
+#[derive(Debug, Clone, Serialize)]
+pub struct JobList {
+    /// 同时处理的任务数上限
+    pub max_concurrent: usize,
+    pub running: usize,
+    pub queued: usize,
+    /// 任务列表，最近提交的在前
+    pub jobs: Vec<Job>,
+}
+
+/// 进程内任务管理器
+///
+/// 异步处理的 OSS 事件作为任务提交，通过信号量限制同时处理的任务数，
+/// 避免同一个函数计算实例同时处理过多视频；已结束的任务保留有限数量供查询
+pub struct JobManager {
+    inner: Mutex<ManagerInner>,
+    permits: Arc<Semaphore>,
+    /// 正在等待并发名额的任务数
+    waiting: Arc<AtomicUsize>,
+    max_concurrent: usize,
+    max_retained: usize,
+    max_queued: usize,
+    retry_after: Duration,
+}
+
+/// 等待并发名额的登记，取得名额或放弃等待（请求被取消）时注销
+struct Waiting(Arc<AtomicUsize>);
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct ManagerInner {
+    jobs: HashMap<String, Job>,
+    /// 按提交顺序排列的任务 ID
+    order: VecDeque<String>,
+}
+
+impl JobManager {
+    pub fn new(config: &JobsConfig) -> Self {
+        let max_concurrent = config.max_concurrent.max(1);
+        Self {
+            inner: Mutex::new(ManagerInner::default()),
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            waiting: Arc::new(AtomicUsize::new(0)),
+            max_concurrent,
+            max_retained: config.max_retained.max(1),
+            max_queued: config.max_queued,
+            retry_after: Duration::from_secs(config.retry_after_secs.max(1)),
+        }
+    }
+
+    /// 登记等待并发名额；没有空闲名额且等待的任务数已达 `max_queued` 时返回 [`QueueFull`]
+    fn reserve(&self) -> Result<Waiting, QueueFull> {
+        let waiting = self.waiting.fetch_add(1, Ordering::SeqCst) + 1;
+        let guard = Waiting(self.waiting.clone());
+        let available = self.permits.available_permits();
+        if waiting > available + self.max_queued {
+            let running = self.max_concurrent - available;
+            drop(guard);
+            tracing::warn!(running, queued = waiting - 1, "⚠️  [任务队列] 处理队列已满，拒绝新任务");
+            return Err(QueueFull { running, queued: waiting - 1, retry_after: self.retry_after });
+        }
+        Ok(guard)
+    }
+
+    /// 同步处理请求取得并发名额，与异步任务共用同一组名额；队列已满时立即返回 [`QueueFull`]
+    ///
+    /// 返回的名额在处理结束（drop）时归还
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, QueueFull> {
+        let waiting = self.reserve()?;
+        let permit = self.permits.clone().acquire_owned().await.expect("信号量不会被关闭");
+        drop(waiting);
+        Ok(permit)
+    }
+
+    /// 进程内共享的任务管理器，首次使用时按扩展配置创建
+    pub fn shared() -> &'static JobManager {
+        static SHARED: OnceLock<JobManager> = OnceLock::new();
+        SHARED.get_or_init(|| {
+            let config = crate::config::ConfigLoader::load_extended_config(None)
+                .map(|c| c.jobs)
+                .unwrap_or_default();
+            JobManager::new(&config)
+        })
+    }
+
+    /// 提交任务，立即返回；取得并发名额后执行 `task`
+    ///
+    /// `task` 返回 `Err` 时任务记为失败。同一 ID 的任务尚未结束时（函数计算重试同一请求）
+    /// 不会重复提交，直接返回已有的任务；等待的任务数已达上限时返回 [`QueueFull`]
+    pub fn submit<F, Fut>(&'static self, id: String, bucket: &str, object_key: &str, task: F) -> Result<Job, QueueFull>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<ProcessResult>, String>> + Send + 'static,
+    {
+        let (job, waiting) = {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(existing) = inner.jobs.get(&id).filter(|job| !job.state.is_finished()) {
+                tracing::warn!(job_id = %id, state = existing.state.as_str(), "⚠️  [任务队列] 任务已在队列中，不重复提交");
+                return Ok(existing.clone());
+            }
+            let waiting = self.reserve()?;
+            let job = Job {
+                id: id.clone(),
+                state: JobState::Queued,
+                bucket: bucket.to_string(),
+                object_key: object_key.to_string(),
+                submitted_at: chrono::Utc::now().to_rfc3339(),
+                started_at: None,
+                finished_at: None,
+                error: None,
+                result: None,
+            };
+            inner.order.retain(|existing| existing != &id);
+            inner.order.push_back(id.clone());
+            inner.jobs.insert(id.clone(), job.clone());
+            self.evict(&mut inner);
+            (job, waiting)
+        };
+        tracing::info!(job_id = %id, available = self.permits.available_permits(), "📥 [任务队列] 任务已提交");
+
+        let permits = self.permits.clone();
+        tokio::spawn(async move {
+            let _permit = match permits.acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+            drop(waiting);
+            self.update(&id, |job| {
+                job.state = JobState::Running;
+                job.started_at = Some(chrono::Utc::now().to_rfc3339());
+            });
+            let outcome = task().await;
+            self.update(&id, |job| {
+                job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                match outcome {
+                    Ok(result) => {
+                        job.state = JobState::Done;
+                        job.result = result;
+                    }
+                    Err(error) => {
+                        job.state = JobState::Failed;
+                        job.error = Some(error);
+                    }
+                }
+            });
+        });
+        Ok(job)
+    }
+
+    /// 查询任务
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.inner.lock().unwrap().jobs.get(id).cloned()
+    }
+
+    /// 列出任务（最近提交的在前），`state` 为 None 时列出全部
+    pub fn list(&self, state: Option<JobState>) -> JobList {
+        let inner = self.inner.lock().unwrap();
+        let count = |state| inner.jobs.values().filter(|job| job.state == state).count();
+        JobList {
+            max_concurrent: self.max_concurrent,
+            running: count(JobState::Running),
+            queued: count(JobState::Queued),
+            jobs: inner.order
+                .iter()
+                .rev()
+                .filter_map(|id| inner.jobs.get(id))
+                .filter(|job| state.is_none_or(|state| job.state == state))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.inner.lock().unwrap().jobs.get_mut(id) {
+            f(job);
+        }
+    }
+
+    /// 淘汰最早提交的已结束任务，排队和运行中的任务不会被淘汰
+    fn evict(&self, inner: &mut ManagerInner) {
+        let ManagerInner { jobs, order } = inner;
+        let mut finished = order
+            .iter()
+            .filter(|id| jobs.get(*id).is_some_and(|job| job.state.is_finished()))
+            .count();
+        order.retain(|id| {
+            if finished <= self.max_retained || !jobs.get(id).is_some_and(|job| job.state.is_finished()) {
+                return true;
+            }
+            finished -= 1;
+            jobs.remove(id);
+            false
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_until(manager: &JobManager, id: &str, state: JobState) -> Job {
+        for _ in 0..200 {
+            if let Some(job) = manager.get(id).filter(|job| job.state == state) {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("任务 {} 未进入 {} 状态: {:?}", id, state.as_str(), manager.get(id));
+    }
+
+    #[tokio::test]
+    async fn test_bounded_concurrency() {
+        let manager: &'static JobManager =
+            Box::leak(Box::new(JobManager::new(&JobsConfig { max_concurrent: 1, max_retained: 1, max_queued: 1, ..JobsConfig::default() })));
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+        manager.submit("a".to_string(), "bucket", "a.mp4", move || async move {
+            released.await.ok();
+            Ok(None)
+        }).unwrap();
+        manager.submit("b".to_string(), "bucket", "b.mp4", || async { Err("解码失败".to_string()) }).unwrap();
+        wait_until(manager, "a", JobState::Running).await;
+        // 并发上限为 1，第二个任务排队
+        assert_eq!(manager.get("b").unwrap().state, JobState::Queued);
+        // 未结束的任务不重复提交
+        assert_eq!(manager.submit("a".to_string(), "bucket", "a.mp4", || async { Ok(None) }).unwrap().state, JobState::Running);
+        // 等待的任务数已达上限，新任务和同步处理请求都被拒绝
+        let full = manager.submit("x".to_string(), "bucket", "x.mp4", || async { Ok(None) }).unwrap_err();
+        assert_eq!((full.running, full.queued, full.retry_after), (1, 1, Duration::from_secs(30)));
+        assert!(manager.get("x").is_none());
+        assert!(manager.acquire().await.is_err());
+        let list = manager.list(None);
+        assert_eq!((list.running, list.queued), (1, 1));
+        assert_eq!(list.jobs.iter().map(|job| job.id.as_str()).collect::<Vec<_>>(), ["b", "a"]);
+
+        release.send(()).unwrap();
+        wait_until(manager, "a", JobState::Done).await;
+        let failed = wait_until(manager, "b", JobState::Failed).await;
+        assert_eq!(failed.error.as_deref(), Some("解码失败"));
+        assert!(failed.started_at.is_some() && failed.finished_at.is_some());
+
+        // 只保留 1 个已结束任务，再提交时淘汰最早的
+        manager.submit("c".to_string(), "bucket", "c.mp4", || async { Ok(None) }).unwrap();
+        assert!(manager.get("a").is_none());
+        assert_eq!(manager.list(Some(JobState::Failed)).jobs.len(), 1);
+        assert_eq!("RUNNING".parse::<JobState>(), Ok(JobState::Running));
+    }
+}
@@ -0,0 +1,176 @@
+use serde::Serialize;
+use crate::metadata::VideoMetadata;
+use crate::oss_event::ProcessResult;
+
+/// Kafka 结果发布配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaConfig {
+    /// broker 列表（未设置时不发布），例如 kafka-1:9092,kafka-2:9092
+    pub brokers: Option<String>,
+    /// 结果 topic
+    pub topic: String,
+    /// 额外的 librdkafka 客户端参数（例如 security.protocol=SASL_PLAINTEXT）
+    pub properties: Vec<(String, String)>,
+}
+
+impl Default for KafkaConfig {
+    fn default() -> Self {
+        Self {
+            brokers: None,
+            topic: "video-parse-results".to_string(),
+            properties: Vec::new(),
+        }
+    }
+}
+
+/// 发布到 Kafka 的结果消息
+#[derive(Debug, Serialize)]
+pub struct ResultMessage<'a> {
+    /// 处理状态
+    pub status: &'a str,
+    /// 源文件，bucket/object key
+    pub source: &'a str,
+    /// 处理结果（与 HTTP 响应中的 result 相同）
+    pub result: &'a ProcessResult,
+    /// 视频元数据
+    pub metadata: &'a VideoMetadata,
+    /// 发布时间戳
+    pub timestamp: String,
+}
+
+impl<'a> ResultMessage<'a> {
+    pub fn success(source: &'a str, result: &'a ProcessResult, metadata: &'a VideoMetadata) -> Self {
+        Self {
+            status: "success",
+            source,
+            result,
+            metadata,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// 把处理结果发布到 Kafka，消息 key 为 `bucket/object key`，发布失败只记录警告
+pub async fn publish_result(config: &KafkaConfig, key: &str, message: &ResultMessage<'_>) {
+    if config.brokers.is_none() {
+        return;
+    }
+    let payload = match serde_json::to_vec(message) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!(error = %e, "⚠️  [Kafka] 序列化结果消息失败");
+            return;
+        }
+    };
+    match imp::send(config, key, &payload).await {
+        Ok(()) => tracing::info!(topic = %config.topic, key, "✅ [Kafka] 处理结果已发布"),
+        Err(e) => tracing::warn!(topic = %config.topic, key, error = %e, "⚠️  [Kafka] 发布处理结果失败"),
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod imp {
+    use super::KafkaConfig;
+    use anyhow::{Context, Result};
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use rdkafka::ClientConfig;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    /// 发送队列已满时的等待时间
+    const QUEUE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    static PRODUCER: OnceLock<FutureProducer> = OnceLock::new();
+
+    /// 进程内共享的 producer，第一次发布时创建
+    fn producer(config: &KafkaConfig) -> Result<&'static FutureProducer> {
+        if let Some(producer) = PRODUCER.get() {
+            return Ok(producer);
+        }
+        let brokers = config.brokers.as_deref().context("未配置 Kafka broker")?;
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "30000");
+        for (key, value) in &config.properties {
+            client_config.set(key, value);
+        }
+        let producer: FutureProducer = client_config.create().context("创建 Kafka producer 失败")?;
+        Ok(PRODUCER.get_or_init(|| producer))
+    }
+
+    pub async fn send(config: &KafkaConfig, key: &str, payload: &[u8]) -> Result<()> {
+        let record = FutureRecord::to(&config.topic).key(key).payload(payload);
+        producer(config)?
+            .send(record, QUEUE_TIMEOUT)
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Kafka 投递失败: {}", e))?;
+        Ok(())
+    }
+}
+
+/// 未启用 `kafka` feature 时配置了 broker 只记录警告
+#[cfg(not(feature = "kafka"))]
+mod imp {
+    use super::KafkaConfig;
+    use anyhow::Result;
+
+    pub async fn send(_config: &KafkaConfig, _key: &str, _payload: &[u8]) -> Result<()> {
+        anyhow::bail!("已配置 Kafka，但未启用 kafka feature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_message() {
+        let result = ProcessResult {
+            job_id: Some("job-1".to_string()),
+            video_file: "/tmp/a.mp4".to_string(),
+            output_dir: "/tmp/out".to_string(),
+            scene_count: 0,
+            keyframes: Vec::new(),
+            audio_file: "audio.aac".to_string(),
+            metadata_file: "metadata.json".to_string(),
+            archive: None,
+            resources: None,
+            timings: None,
+            upload_blocked: false,
+            webhook: None,
+            outputs: Default::default(),
+            urls: Default::default(),
+        };
+        let metadata = VideoMetadata {
+            input_video: "/tmp/a.mp4".to_string(),
+            total_duration: 1.0,
+            fps: 25.0,
+            media_kind: Default::default(),
+            limitations: Vec::new(),
+            resolution: "1920x1080".to_string(),
+            storage_resolution: None,
+            sample_aspect_ratio: None,
+            scene_count: 0,
+            audio_file: "audio.aac".to_string(),
+            scenes: Vec::new(),
+            safety: None,
+            faces: None,
+            embeddings: None,
+            sprite: None,
+            loudness: None,
+            audio_segments: Vec::new(),
+            exports: Vec::new(),
+            fingerprint_file: None,
+            source_hash: None,
+            config_fingerprint: None,
+            metadata_file: "metadata.json".to_string(),
+        };
+        let message = ResultMessage::success("videos/a.mp4", &result, &metadata);
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["source"], "videos/a.mp4");
+        assert_eq!(json["result"]["job_id"], "job-1");
+        assert_eq!(json["metadata"]["resolution"], "1920x1080");
+    }
+}
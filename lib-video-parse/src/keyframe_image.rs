@@ -0,0 +1,203 @@
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use image::{imageops::FilterType, DynamicImage, ImageEncoder};
+
+/// 关键帧图片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyframeFormat {
+    /// JPEG（默认）
+    #[default]
+    Jpeg,
+    /// PNG（无损，忽略质量）
+    Png,
+    /// WebP，未启用 `webp` feature 时为无损编码（忽略质量）
+    Webp,
+    /// AVIF，需要以 `--features avif` 编译，未启用时使用 JPEG
+    Avif,
+}
+
+impl KeyframeFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyframeFormat::Jpeg => "jpeg",
+            KeyframeFormat::Png => "png",
+            KeyframeFormat::Webp => "webp",
+            KeyframeFormat::Avif => "avif",
+        }
+    }
+
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            KeyframeFormat::Jpeg => "jpg",
+            other => other.as_str(),
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            KeyframeFormat::Jpeg => "image/jpeg",
+            KeyframeFormat::Png => "image/png",
+            KeyframeFormat::Webp => "image/webp",
+            KeyframeFormat::Avif => "image/avif",
+        }
+    }
+
+    /// 按文件扩展名判断格式，无法识别时为 JPEG
+    pub fn from_file_name(file_name: &str) -> Self {
+        let extension = Path::new(file_name).extension().and_then(|e| e.to_str()).unwrap_or_default();
+        extension.parse().unwrap_or_default()
+    }
+
+    /// 实际使用的格式：未启用 `avif` feature 时 AVIF 使用 JPEG
+    pub fn effective(self) -> Self {
+        if self == KeyframeFormat::Avif && !cfg!(feature = "avif") {
+            KeyframeFormat::Jpeg
+        } else {
+            self
+        }
+    }
+}
+
+impl FromStr for KeyframeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "jpeg" | "jpg" => Ok(KeyframeFormat::Jpeg),
+            "png" => Ok(KeyframeFormat::Png),
+            "webp" => Ok(KeyframeFormat::Webp),
+            "avif" => Ok(KeyframeFormat::Avif),
+            other => Err(format!("未知的关键帧格式: {}（可选值: jpeg, png, webp, avif）", other)),
+        }
+    }
+}
+
+/// 关键帧图片的格式、质量和尺寸
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyframeImageConfig {
+    pub format: KeyframeFormat,
+    /// 有损编码质量（1-100，JPEG / 有损 WebP / AVIF）
+    pub quality: u8,
+    /// 最大宽度（像素），更宽的关键帧按比例缩小
+    pub max_width: Option<u32>,
+    /// 最大高度（像素），更高的关键帧按比例缩小
+    pub max_height: Option<u32>,
+}
+
+impl Default for KeyframeImageConfig {
+    fn default() -> Self {
+        Self {
+            format: KeyframeFormat::Jpeg,
+            // 与 image 库保存 JPEG 时的默认质量相同
+            quality: 75,
+            max_width: None,
+            max_height: None,
+        }
+    }
+}
+
+impl KeyframeImageConfig {
+    /// 第 `index` 个关键帧的文件名：`keyframe_0000.jpg`
+    pub fn file_name(&self, index: usize) -> String {
+        format!("keyframe_{:04}.{}", index, self.format.effective().extension())
+    }
+
+    /// 按最大宽高等比缩小（不放大），不需要缩小时不复制
+    pub fn resize<'a>(&self, image: &'a DynamicImage) -> Cow<'a, DynamicImage> {
+        let max_width = self.max_width.filter(|&w| w > 0).unwrap_or(u32::MAX);
+        let max_height = self.max_height.filter(|&h| h > 0).unwrap_or(u32::MAX);
+        if image.width() <= max_width && image.height() <= max_height {
+            return Cow::Borrowed(image);
+        }
+        Cow::Owned(image.resize(max_width.min(image.width()), max_height.min(image.height()), FilterType::Lanczos3))
+    }
+
+    /// 缩放并按配置的格式保存关键帧
+    pub fn save(&self, image: &DynamicImage, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path).context(format!("创建关键帧文件失败: {}", path.display()))?;
+        self.encode(image, BufWriter::new(file))
+            .context(format!("编码关键帧失败: {}", path.display()))
+    }
+
+    /// 缩放并按配置的格式编码关键帧，写入 `writer`
+    pub fn encode(&self, image: &DynamicImage, mut writer: impl Write) -> Result<()> {
+        let image = self.resize(image);
+        let quality = self.quality.clamp(1, 100);
+        match self.format.effective() {
+            KeyframeFormat::Jpeg => {
+                // JPEG 不支持透明通道
+                let image = match image.color() {
+                    image::ColorType::L8 | image::ColorType::Rgb8 => image,
+                    _ => Cow::Owned(DynamicImage::ImageRgb8(image.to_rgb8())),
+                };
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, quality)
+                    .write_image(image.as_bytes(), image.width(), image.height(), image.color())
+            }
+            KeyframeFormat::Png => image::codecs::png::PngEncoder::new(&mut writer)
+                .write_image(image.as_bytes(), image.width(), image.height(), image.color()),
+            KeyframeFormat::Webp => {
+                let image = DynamicImage::ImageRgb8(image.to_rgb8());
+                #[cfg(feature = "webp")]
+                #[allow(deprecated)]
+                let encoder = image::codecs::webp::WebPEncoder::new_with_quality(
+                    &mut writer,
+                    image::codecs::webp::WebPQuality::lossy(quality),
+                );
+                #[cfg(not(feature = "webp"))]
+                let encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut writer);
+                encoder.write_image(image.as_bytes(), image.width(), image.height(), image.color())
+            }
+            #[cfg(feature = "avif")]
+            KeyframeFormat::Avif => {
+                let image = DynamicImage::ImageRgb8(image.to_rgb8());
+                image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut writer, 8, quality)
+                    .write_image(image.as_bytes(), image.width(), image.height(), image.color())
+            }
+            #[cfg(not(feature = "avif"))]
+            KeyframeFormat::Avif => unreachable!("未启用 avif feature 时 AVIF 使用 JPEG"),
+        }?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_keyframe() {
+        let dir = std::env::temp_dir().join(format!("video-parse-keyframe-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let frame = DynamicImage::ImageRgb8(image::RgbImage::from_fn(320, 180, |x, y| image::Rgb([x as u8, y as u8, 90])));
+
+        let config = KeyframeImageConfig { max_width: Some(160), ..Default::default() };
+        assert_eq!(config.file_name(3), "keyframe_0003.jpg");
+        config.save(&frame, &dir.join(config.file_name(0))).unwrap();
+        let saved = image::open(dir.join("keyframe_0000.jpg")).unwrap();
+        assert_eq!((saved.width(), saved.height()), (160, 90));
+
+        let config = KeyframeImageConfig { format: KeyframeFormat::Webp, max_height: Some(120), ..Default::default() };
+        config.save(&frame, &dir.join(config.file_name(1))).unwrap();
+        let saved = image::open(dir.join("keyframe_0001.webp")).unwrap();
+        assert_eq!((saved.width(), saved.height()), (213, 120));
+
+        // 小于上限时不放大
+        let config = KeyframeImageConfig { format: KeyframeFormat::Png, max_width: Some(1920), ..Default::default() };
+        assert!(matches!(config.resize(&frame), Cow::Borrowed(_)));
+        config.save(&frame, &dir.join(config.file_name(2))).unwrap();
+        assert_eq!(image::open(dir.join("keyframe_0002.png")).unwrap().width(), 320);
+
+        let mut encoded = Vec::new();
+        KeyframeImageConfig::default().encode(&frame, &mut encoded).unwrap();
+        assert_eq!(image::load_from_memory(&encoded).unwrap().width(), 320);
+
+        assert_eq!(KeyframeFormat::from_file_name("keyframe_0001.webp").mime_type(), "image/webp");
+        assert_eq!("JPG".parse::<KeyframeFormat>(), Ok(KeyframeFormat::Jpeg));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
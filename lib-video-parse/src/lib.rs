@@ -1,18 +1,113 @@
 pub mod scene_detector;
 pub mod video_processor;
 pub mod audio_extractor;
+pub mod audio_analyzer;
 pub mod metadata;
 pub mod oss_event;
 pub mod oss_client;
+pub mod url_input;
 pub mod processor;
 pub mod handler;
 pub mod config;
+pub mod simd;
+pub mod cache;
+pub mod preset;
+pub mod progressive;
+pub mod resource;
+pub mod frame_pool;
+pub mod telemetry;
+pub mod metrics;
+pub mod auth;
+pub mod batch;
+pub mod job_logs;
+pub mod jobs;
+pub mod progress;
+pub mod watchdog;
+pub mod ocr;
+pub mod faces;
+pub mod shot_type;
+pub mod safety;
+pub mod embeddings;
+pub mod vector_store;
+pub mod enrichment;
+pub mod annotator;
+pub mod sprite;
+pub mod clips;
+pub mod preview;
+pub mod audio_boundary;
+pub mod vad;
+pub mod checkpoint;
+pub mod keyframe_image;
+pub mod redis_worker;
+pub mod database;
+pub mod kafka;
+pub mod notify;
+pub mod failure;
+pub mod webhook;
+pub mod dead_letter;
+pub mod error;
+pub mod config_builder;
+pub mod pipeline;
+pub mod pipeline_builder;
+pub mod preflight;
+pub mod output_naming;
+pub mod archive;
+pub mod fingerprint;
+pub mod viewer;
+pub mod reprocess;
+pub mod decoder;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 
-pub use video_processor::VideoProcessor;
-pub use scene_detector::SceneDetector;
-pub use audio_extractor::AudioExtractor;
-pub use metadata::{SceneMetadata, VideoMetadata};
-pub use oss_event::{OssEvent, OssEventItem, ProcessResponse, ProcessResult};
-pub use oss_client::OssClient;
-pub use processor::{ProcessConfig, ProcessOutput, process_video};
-pub use config::{ConfigLoader, ExtendedConfig};
\ No newline at end of file
+pub use video_processor::{AudioStreamInfo, FrameIter, HwAccel, MediaInfo, VideoProcessor, VideoProbe, VideoStreamInfo, SampleAspectRatio};
+pub use scene_detector::{AdaptiveThreshold, DetectionAlgorithm, FrameFeatures, SceneCut, SceneDetector, SceneStream, ThresholdMode, TransitionType};
+pub use audio_extractor::{AudioCodec, AudioConfig, AudioExtractor};
+pub use audio_analyzer::{LoudnessStats, Waveform};
+pub use metadata::{ColorStats, ExportFormat, MediaKind, MetadataExporter, PaletteColor, SceneMetadata, VideoMetadata};
+pub use oss_event::{EventResult, EventStatus, OssEvent, OssEventItem, ProcessResponse, ProcessResult};
+pub use oss_client::{MultipartConfig, OssClient, UploadReport, UploadTask};
+pub use processor::{ProcessConfig, ProcessOptions, ProcessOutput, process_video, process_video_with_options, process_progressive_video};
+pub use config::{ConfigLoader, ConfigOverrides, ExtendedConfig};
+pub use cache::{CacheEntry, ResultCache};
+pub use preset::Preset;
+pub use resource::{ResourceUsage, StageUsage, TimingBreakdown};
+pub use frame_pool::FramePool;
+pub use telemetry::TelemetryConfig;
+pub use job_logs::{JobLogSnapshot, JobLogStore};
+pub use jobs::{Job, JobList, JobManager, JobState, JobsConfig, QueueFull};
+pub use progress::{JobProgress, ProgressEvent, ProgressReporter, ProgressStore, ProgressUnit, StageProgress, WebhookProgress};
+pub use watchdog::{Stage, StageTimeoutError, StageTimeouts};
+pub use preflight::{InputLimits, RejectReason, Rejection};
+pub use ocr::{OcrConfig, TesseractRecognizer, TextRecognizer};
+pub use faces::{FaceBox, FaceConfig, FaceSummary};
+pub use shot_type::ShotType;
+pub use safety::{SafetyConfig, SafetyScores, SafetySummary};
+pub use embeddings::{EmbeddingConfig, EmbeddingsIndex, SceneEmbedding};
+pub use vector_store::{VectorStoreConfig, VectorStoreKind};
+pub use enrichment::{EnrichmentConfig, SceneEnrichment};
+pub use annotator::HttpAnnotator;
+pub use sprite::{SpriteConfig, SpriteSheet};
+pub use clips::{ClipConfig, ClipMode};
+pub use preview::{PreviewConfig, PreviewFormat};
+pub use audio_boundary::AudioBoundaryConfig;
+pub use vad::{AudioSegment, SegmentKind, VadConfig};
+pub use keyframe_image::{KeyframeFormat, KeyframeImageConfig};
+pub use redis_worker::RedisWorkerConfig;
+pub use database::{Artifact, DatabaseConfig, JobRecord};
+pub use kafka::KafkaConfig;
+pub use notify::{Notification, NotifyConfig};
+pub use failure::{FailureReport, StageFailure};
+pub use webhook::{WebhookDelivery, WebhookConfig};
+pub use dead_letter::{DeadLetter, RetryConfig};
+pub use error::VideoParseError;
+pub use config_builder::ProcessConfigBuilder;
+pub use pipeline::{ArtifactWriter, Detector, FrameSource, KeyframeAnnotator, KeyframeSelector, NoopAnnotator, Pipeline, Publisher, SpreadFrameSelector};
+pub use pipeline_builder::{OssStorage, PipelineOutput, VideoParsePipeline};
+pub use tokio_util::sync::CancellationToken;
+pub use output_naming::{FileNaming, OutputNaming};
+pub use archive::ArchiveFormat;
+pub use fingerprint::{compare_fingerprints, SceneFingerprint, VideoFingerprint};
+pub use decoder::DecoderBackend;
+pub use reprocess::{reprocess, KnownScenesDetector};
+#[cfg(feature = "gstreamer")]
+pub use decoder::GstreamerFrameSource;
\ No newline at end of file
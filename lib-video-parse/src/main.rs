@@ -1,7 +1,7 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use video_parse::{ProcessConfig, process_video, config::ConfigLoader};
+use std::path::{Path, PathBuf};
+use video_parse::{ArchiveFormat, ExportFormat, ProcessConfig, ProcessOptions, CancellationToken, batch::{self, BatchOptions}, Preset, process_video_with_options, telemetry, webhook, config::{ConfigLoader, ConfigOverrides}};
 
 /// 视频拉片工具 - 分析视频内容，提取关键帧和场景信息
 #[derive(Parser, Debug)]
@@ -24,11 +24,17 @@ enum Commands {
         #[arg(short, long, default_value = "./output")]
         output: String,
 
-        /// 配置文件路径（可选，支持 .ini 格式）
+        /// 配置文件路径（可选，支持 .ini / .toml / .yaml 格式）
         /// 优先级：命令行参数 > 环境变量 > 配置文件 > 默认值
         #[arg(long)]
         config: Option<PathBuf>,
 
+        /// 速度预设：fast | balanced | accurate
+        /// 统一设置分析分辨率、采样率、差异度量和 seek 精度，单独指定的参数优先
+        /// 可通过环境变量 VIDEO_PARSE_PRESET 或配置文件设置
+        #[arg(long)]
+        preset: Option<Preset>,
+
         /// 场景变化检测阈值 (0.0-1.0)，值越大越敏感
         /// 可通过环境变量 VIDEO_PARSE_THRESHOLD 或配置文件设置
         #[arg(long)]
@@ -43,49 +49,334 @@ enum Commands {
         /// 可通过环境变量 VIDEO_PARSE_SAMPLE_RATE 或配置文件设置
         #[arg(long)]
         sample_rate: Option<f64>,
+
+        /// 额外导出的场景列表格式，逗号分隔：edl | csv | srt | youtube | ffmetadata
+        /// 可通过环境变量 VIDEO_PARSE_EXPORT_FORMATS 或配置文件设置
+        #[arg(long, value_delimiter = ',')]
+        export_formats: Option<Vec<ExportFormat>>,
+
+        /// 处理完成后把输出目录打包为单个文件：zip | tar.gz
+        /// 可通过环境变量 VIDEO_PARSE_ARCHIVE 或配置文件设置
+        #[arg(long)]
+        archive: Option<ArchiveFormat>,
+
+        /// 在输出目录生成结果查看页 index.html，用浏览器打开即可浏览场景和关键帧
+        /// 可通过环境变量 VIDEO_PARSE_VIEWER 或配置文件设置
+        #[arg(long)]
+        viewer: bool,
+
+        /// 整体处理超时（秒），超时后停止处理；按 Ctrl-C 取消处理
+        #[arg(long)]
+        timeout: Option<u64>,
+    },
+    /// 批量模式：递归处理目录下的所有视频文件
+    Batch {
+        /// 输入目录
+        #[arg(long)]
+        input_dir: PathBuf,
+
+        /// 输出目录，每个视频输出到按相对路径命名的子目录，汇总报告写入 batch_report.json
+        #[arg(long, default_value = "./output")]
+        output_dir: PathBuf,
+
+        /// 同时处理的视频数
+        #[arg(short = 'j', long, default_value_t = 1)]
+        parallelism: usize,
+
+        /// 配置文件路径（可选，支持 .ini / .toml / .yaml 格式）
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// 速度预设：fast | balanced | accurate
+        #[arg(long)]
+        preset: Option<Preset>,
+
+        /// 场景变化检测阈值 (0.0-1.0)，值越大越敏感
+        #[arg(long)]
+        threshold: Option<f64>,
+
+        /// 最小场景持续时间（秒）
+        #[arg(long)]
+        min_scene_duration: Option<f64>,
+
+        /// 帧采样率（每秒采样多少帧用于分析）
+        #[arg(long)]
+        sample_rate: Option<f64>,
+
+        /// 额外导出的场景列表格式，逗号分隔：edl | csv | srt | youtube | ffmetadata
+        #[arg(long, value_delimiter = ',')]
+        export_formats: Option<Vec<ExportFormat>>,
+
+        /// 把每个视频的输出目录打包为单个文件：zip | tar.gz
+        #[arg(long)]
+        archive: Option<ArchiveFormat>,
+
+        /// 单个视频的处理超时（秒）；按 Ctrl-C 取消全部处理
+        #[arg(long)]
+        timeout: Option<u64>,
     },
     /// Web 服务模式：启动 HTTP 服务器处理 OSS event
     Serve {
         /// 监听地址（默认从环境变量 FC_SERVER_PORT 读取，如果不存在则使用 0.0.0.0:9000）
         #[arg(short, long)]
         bind: Option<String>,
+
+        /// 任务来源：http（HTTP 服务）| redis（从 Redis 队列取任务，需要 redis feature）
+        /// Redis 连接和队列通过 REDIS_URL / REDIS_QUEUE / REDIS_RESULT_KEY 或配置文件 [redis] 节设置
+        #[arg(long, value_enum, default_value_t = ServeSource::Http)]
+        source: ServeSource,
     },
+    /// 探测视频信息：时长、分辨率、帧率、编码、码率、音频声道和容器格式，不提取帧
+    /// 同时按配置估算采样帧数和输出大小，检查输入大小和时长上限，以 JSON 输出
+    Probe {
+        /// 输入视频文件路径或 HTTP / HTTPS URL
+        #[arg(short, long)]
+        input: String,
+
+        /// 配置文件路径（可选，用于估算处理工作量和检查输入上限）
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// 速度预设：fast | balanced | accurate
+        #[arg(long)]
+        preset: Option<Preset>,
+
+        /// 帧采样率（每秒采样多少帧用于分析）
+        #[arg(long)]
+        sample_rate: Option<f64>,
+    },
+    /// 按已有的 metadata.json 重新处理：复用之前检测到的场景边界，按新的参数重新生成关键帧、片段、拼图等，跳过场景检测
+    Reprocess {
+        /// 之前处理结果的 metadata.json
+        #[arg(long)]
+        metadata: PathBuf,
+
+        /// 输入视频文件路径（默认使用元数据中的 input_video）
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        /// 输出目录（默认为 metadata.json 所在目录）
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// 配置文件路径（可选，支持 .ini / .toml / .yaml 格式）
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// 每个场景保存的关键帧数
+        #[arg(long, default_value_t = 1)]
+        keyframes_per_scene: usize,
+
+        /// 额外导出的场景列表格式，逗号分隔：edl | csv | srt | youtube | ffmetadata
+        #[arg(long, value_delimiter = ',')]
+        export_formats: Option<Vec<ExportFormat>>,
+
+        /// 处理完成后把输出目录打包为单个文件：zip | tar.gz
+        #[arg(long)]
+        archive: Option<ArchiveFormat>,
+
+        /// 整体处理超时（秒）
+        #[arg(long)]
+        timeout: Option<u64>,
+    },
+    /// 比较两个视频指纹文件（fingerprint.json），输出相似度（0-1），用于识别重复上传的内容
+    CompareFingerprints {
+        /// 第一个指纹文件
+        a: PathBuf,
+
+        /// 第二个指纹文件
+        b: PathBuf,
+    },
+    /// 重新投递重试用尽后保存下来的 webhook 回调
+    ReplayWebhooks {
+        /// 保存目录（默认使用配置中的 webhook_spool_dir）
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+}
+
+/// 服务模式的任务来源
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ServeSource {
+    Http,
+    Redis,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 初始化日志
-    tracing_subscriber::fmt::init();
-
     let args = Args::parse();
 
+    // 初始化日志（配置了 OTLP 端点时同时导出 trace 和指标）
+    let extended_config = ConfigLoader::load_extended_config(None).unwrap_or_default();
+    let _telemetry = telemetry::init(
+        &extended_config.log_level,
+        extended_config.log_format,
+        &extended_config.telemetry,
+    )
+        .context("初始化日志失败")?;
+
     match args.command {
         Commands::Process {
             input,
             output,
             config: config_file,
+            preset,
             threshold,
             min_scene_duration,
             sample_rate,
+            export_formats,
+            archive,
+            viewer,
+            timeout,
         } => {
             // CLI 模式：从配置文件、环境变量和命令行参数加载配置
-            let config = ConfigLoader::load_config(
+            // webhook_url 从配置文件或环境变量读取
+            let config = ConfigLoader::load_config_with_overrides(
                 config_file.as_deref(),
-                threshold,
-                min_scene_duration,
-                sample_rate,
-                None, // webhook_url 从配置文件或环境变量读取
+                ConfigOverrides {
+                    preset,
+                    threshold,
+                    min_scene_duration,
+                    sample_rate,
+                    export_formats,
+                    archive,
+                    viewer: viewer.then_some(true),
+                    ..Default::default()
+                },
             )
             .context("加载配置失败")?;
             
-            println!("使用配置: threshold={:.2}, min_scene_duration={:.2}s, sample_rate={:.2} fps",
-                config.threshold, config.min_scene_duration, config.sample_rate);
+            println!("使用配置: threshold={:.2} ({}), min_scene_duration={:.2}s, sample_rate={:.2} fps",
+                config.threshold, config.threshold_mode.as_str(), config.min_scene_duration, config.sample_rate);
+            println!("          analysis_width={}, algorithm={}, metrics={}, seek={}, hwaccel={}",
+                config.analysis_width.map(|w| w.to_string()).unwrap_or_else(|| "原始分辨率".to_string()),
+                config.detection_algorithm.as_str(), config.metrics.names(), config.seek_mode.as_str(), config.hwaccel.as_str());
             
-            process_video(&input, &output, config)
+            let cancel = CancellationToken::new();
+            let mut options = ProcessOptions::default().with_cancel(cancel.clone());
+            if let Some(secs) = timeout {
+                options = options.with_timeout(std::time::Duration::from_secs(secs));
+            }
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    eprintln!("收到 Ctrl-C，正在取消处理...");
+                    cancel.cancel();
+                }
+            });
+            let viewer = config.viewer;
+            let result = process_video_with_options(&input, &output, config, options)
                 .await
                 .context("处理视频失败")?;
+            if viewer {
+                println!("结果查看页: {}", result.output_dir.join(video_parse::viewer::VIEWER_FILE).display());
+            }
+            if let Some(archive_file) = &result.archive_file {
+                println!("输出目录已打包: {}", result.output_dir.join(archive_file).display());
+            }
         }
-        Commands::Serve { bind } => {
+        Commands::Batch {
+            input_dir,
+            output_dir,
+            parallelism,
+            config: config_file,
+            preset,
+            threshold,
+            min_scene_duration,
+            sample_rate,
+            export_formats,
+            archive,
+            timeout,
+        } => {
+            let config = ConfigLoader::load_config_with_overrides(
+                config_file.as_deref(),
+                ConfigOverrides {
+                    preset,
+                    threshold,
+                    min_scene_duration,
+                    sample_rate,
+                    export_formats,
+                    archive,
+                    ..Default::default()
+                },
+            )
+            .context("加载配置失败")?;
+
+            let cancel = CancellationToken::new();
+            let options = BatchOptions {
+                parallelism,
+                timeout: timeout.map(std::time::Duration::from_secs),
+                cancel: cancel.clone(),
+            };
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    eprintln!("收到 Ctrl-C，正在取消批量处理...");
+                    cancel.cancel();
+                }
+            });
+            let report = batch::run_batch(&input_dir, &output_dir, config, options)
+                .await
+                .context("批量处理失败")?;
+            println!("批量处理完成: 共 {}，成功 {}，失败 {}（报告: {}）",
+                report.total, report.succeeded, report.failed, output_dir.join(batch::BATCH_REPORT_FILE).display());
+            if report.failed > 0 {
+                anyhow::bail!("{} 个视频处理失败", report.failed);
+            }
+        }
+        Commands::Probe { input, config: config_file, preset, sample_rate } => {
+            let config = ConfigLoader::load_config_with_overrides(
+                config_file.as_deref(),
+                ConfigOverrides {
+                    preset,
+                    sample_rate,
+                    ..Default::default()
+                },
+            )
+            .context("加载配置失败")?;
+            let report = video_parse::preflight::probe(&input, &config).context("探测视频失败")?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Commands::Reprocess { metadata, input, output, config: config_file, keyframes_per_scene, export_formats, archive, timeout } => {
+            let config = ConfigLoader::load_config_with_overrides(
+                config_file.as_deref(),
+                ConfigOverrides {
+                    export_formats,
+                    archive,
+                    ..Default::default()
+                },
+            )
+            .context("加载配置失败")?;
+            let output = output.unwrap_or_else(|| {
+                metadata.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf()
+            });
+            let mut options = ProcessOptions::default();
+            if let Some(secs) = timeout {
+                options = options.with_timeout(std::time::Duration::from_secs(secs));
+            }
+            let result = video_parse::reprocess(&metadata, input.as_deref(), &output, config, keyframes_per_scene, options)
+                .await
+                .context("重新处理失败")?;
+            println!("重新处理完成: {} 个场景，输出目录 {}", result.metadata.scene_count, result.output_dir.display());
+        }
+        Commands::CompareFingerprints { a, b } => {
+            let a = video_parse::fingerprint::load(&a)?;
+            let b = video_parse::fingerprint::load(&b)?;
+            println!("{:.4}", video_parse::compare_fingerprints(&a, &b));
+        }
+        Commands::ReplayWebhooks { dir } => {
+            let webhook_config = extended_config.process.webhook;
+            let dir = dir
+                .or_else(|| webhook_config.spool_dir.clone())
+                .unwrap_or_else(webhook::default_spool_dir);
+            let (delivered, failed) = webhook::replay(&webhook_config, &dir)
+                .await
+                .context("重新投递 webhook 失败")?;
+            println!("重新投递完成: 送达 {}，未送达 {}（目录: {}）", delivered, failed, dir.display());
+        }
+        Commands::Serve { source: ServeSource::Redis, .. } => {
+            // Redis 队列 worker 模式
+            video_parse::redis_worker::run(&extended_config.redis).await?;
+        }
+        Commands::Serve { bind, source: ServeSource::Http } => {
             // Web 服务模式
             // 优先使用命令行参数，其次使用环境变量 FC_SERVER_PORT，最后使用默认值 9000
             let bind_addr = bind.unwrap_or_else(|| {
@@ -93,14 +384,15 @@ async fn main() -> Result<()> {
                     .map(|port| format!("0.0.0.0:{}", port))
                     .unwrap_or_else(|_| "0.0.0.0:9000".to_string())
             });
-            start_web_server(&bind_addr).await?;
+            start_web_server(&bind_addr, extended_config.auth.clone()).await?;
         }
     }
 
     Ok(())
 }
 
-async fn start_web_server(bind: &str) -> Result<()> {
+async fn start_web_server(bind: &str, auth: video_parse::auth::AuthConfig) -> Result<()> {
+    let auth = std::sync::Arc::new(auth);
     use axum::{
         routing::{get, post, put, delete, patch, head, options, MethodRouter},
         Router,
@@ -135,11 +427,31 @@ async fn start_web_server(bind: &str) -> Result<()> {
         // 函数计算调用端点（接受任何HTTP方法）
         .route("/invoke", invoke_route)
         // OSS事件处理端点（函数计算模式，接受任何HTTP方法以兼容不同调用方式）
-        .route("/process", process_any_route)
+        .route("/process", process_any_route.layer(axum::middleware::from_fn(handler::limit_concurrency)))
         // 直接处理端点（支持本地文件和OSS文件）
-        .route("/process/direct", post(handler::handle_direct_process))
+        .route("/process/direct", post(handler::handle_direct_process).layer(axum::middleware::from_fn(handler::limit_concurrency)))
         // 查询参数处理端点（GET请求，方便测试）
-        .route("/process/query", get(handler::handle_process_query))
+        .route("/process/query", get(handler::handle_process_query).layer(axum::middleware::from_fn(handler::limit_concurrency)))
+        // 视频信息探测端点（不提取帧）
+        .route("/probe", get(handler::handle_probe))
+        // 单帧提取端点（直接返回图片）
+        .route("/frame", get(handler::handle_frame))
+        // 进程内任务列表
+        .route("/jobs", get(handler::handle_jobs))
+        // 任务查询端点（进程内任务队列，其次 PostgreSQL 任务记录）
+        .route("/jobs/:id", get(handler::handle_job))
+        // 任务日志查询端点
+        .route("/jobs/:id/logs", get(handler::handle_job_logs))
+        // 任务进度查询端点
+        .route("/jobs/:id/progress", get(handler::handle_job_progress))
+        // 任务实时进度（WebSocket）
+        .route("/jobs/:id/ws", get(handler::handle_job_ws))
+        // 任务输出文件（关键帧、元数据等）
+        .route("/outputs/:job_id/*file", get(handler::handle_output_file))
+        // Prometheus 指标端点
+        .route("/metrics", get(handler::handle_metrics))
+        .layer(axum::middleware::from_fn_with_state(auth.clone(), video_parse::auth::require_auth))
+        .layer(axum::middleware::from_fn(telemetry::trace_http))
         .layer(CorsLayer::permissive());
 
     let listener = tokio::net::TcpListener::bind(bind)
@@ -147,6 +459,16 @@ async fn start_web_server(bind: &str) -> Result<()> {
         .context(format!("绑定地址失败: {}", bind))?;
 
     tracing::info!("Web 服务器启动在: http://{}", bind);
+    if auth.enabled() {
+        tracing::info!(
+            api_keys = auth.api_keys.len(),
+            signature = auth.signature_secret.is_some(),
+            public_paths = %auth.public_paths.join(","),
+            "🔒 已启用接口认证"
+        );
+    } else {
+        tracing::warn!("⚠️  未配置接口认证（AUTH_API_KEYS / AUTH_SIGNATURE_SECRET），所有端点均可匿名访问");
+    }
     tracing::info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     tracing::info!("可用端点:");
     tracing::info!("  • 健康检查: GET  http://{}/health", bind);
@@ -155,6 +477,14 @@ async fn start_web_server(bind: &str) -> Result<()> {
     tracing::info!("  • OSS事件处理: ANY http://{}/process", bind);
     tracing::info!("  • 直接处理: POST http://{}/process/direct", bind);
     tracing::info!("  • 查询处理: GET  http://{}/process/query?input=<path>", bind);
+    tracing::info!("  • 单帧提取: GET  http://{}/frame?input=<path>&t=<秒>", bind);
+    tracing::info!("  • 任务列表: GET  http://{}/jobs?state=<state>", bind);
+    tracing::info!("  • 任务状态: GET  http://{}/jobs/<id>", bind);
+    tracing::info!("  • 任务日志: GET  http://{}/jobs/<id>/logs", bind);
+    tracing::info!("  • 任务进度: GET  http://{}/jobs/<id>/progress", bind);
+    tracing::info!("  • 实时进度: WS   ws://{}/jobs/<id>/ws", bind);
+    tracing::info!("  • 输出文件: GET  http://{}/outputs/<id>/<filename>", bind);
+    tracing::info!("  • Prometheus 指标: GET  http://{}/metrics", bind);
     tracing::info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
     axum::serve(listener, app)
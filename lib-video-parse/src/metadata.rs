@@ -1,7 +1,19 @@
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::str::FromStr;
+use crate::faces::{FaceBox, FaceSummary};
+use crate::safety::{SafetyScores, SafetySummary};
+use crate::embeddings::EmbeddingsIndex;
+use crate::sprite::SpriteSheet;
+use crate::audio_analyzer::LoudnessStats;
+use crate::vad::AudioSegment;
+use crate::scene_detector::TransitionType;
+use crate::shot_type::ShotType;
 
 /// 单个场景的元数据
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SceneMetadata {
     /// 场景编号（从 0 开始）
     pub scene_id: usize,
@@ -13,6 +25,133 @@ pub struct SceneMetadata {
     pub end_time: f64,
     /// 场景持续时间（秒）
     pub duration: f64,
+    /// 关键帧中识别出的文字（启用 OCR 时）。旧版本写入的 `text` 字段读取时仍然兼容
+    #[serde(default, alias = "text", skip_serializing_if = "Option::is_none")]
+    pub ocr_text: Option<String>,
+    /// 关键帧中的人脸数量（启用人脸检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub face_count: Option<usize>,
+    /// 关键帧中的人脸边界框
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub faces: Vec<FaceBox>,
+    /// 关键帧内容安全分类结果（启用内容安全检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety: Option<SafetyScores>,
+    /// 外部模型生成的关键帧描述（启用关键帧标注时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// 外部模型生成的标签
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// 外部模型返回的其他字段
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+    /// 运动强度（0 到 1）：场景内相邻采样帧差异的平均值，场景内只有一个采样帧时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motion_score: Option<f64>,
+    /// 场景片段文件名（启用片段导出时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clip_file: Option<String>,
+    /// 场景预览动图文件名（启用预览动图时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_file: Option<String>,
+    /// 关键帧与该场景（scene_id）的关键帧近似重复时，`keyframe_file` 指向该场景的关键帧（启用关键帧去重时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<usize>,
+    /// 关键帧的主色调、平均亮度和对比度（启用场景颜色统计时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<ColorStats>,
+    /// 场景开始处的转场类型（启用渐变转场检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition_type: Option<TransitionType>,
+    /// 同一场景的其他关键帧文件名（每个场景保存多张关键帧时）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_keyframes: Vec<String>,
+    /// 关键帧分类标签（流水线添加了关键帧分类器时）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// 景别：特写、中景或全景（启用景别估计时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shot_type: Option<ShotType>,
+    /// 场景开始处切点的置信度（切点所在帧与前一帧的差异，0 到 1），第一个场景为空
+    ///
+    /// 可用于过滤低置信度的切点，或不重新检测而按更高的阈值合并场景
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cut_score: Option<f64>,
+}
+
+#[cfg(test)]
+impl SceneMetadata {
+    /// 测试用场景：关键帧按编号命名，其余字段为默认值
+    pub(crate) fn fixture(scene_id: usize, start_time: f64, end_time: f64) -> Self {
+        Self {
+            scene_id,
+            keyframe_file: format!("keyframe_{:04}.jpg", scene_id),
+            start_time,
+            end_time,
+            duration: end_time - start_time,
+            ..Default::default()
+        }
+    }
+}
+
+/// 关键帧的颜色统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorStats {
+    /// 主色调（k-means 聚类中心），按占比从高到低
+    pub palette: Vec<PaletteColor>,
+    /// 平均亮度（0 到 1，按 Rec.709 系数计算）
+    pub brightness: f64,
+    /// 对比度（0 到 1）：亮度的标准差
+    pub contrast: f64,
+}
+
+/// 调色板中的一种颜色
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaletteColor {
+    /// `#rrggbb`
+    pub hex: String,
+    pub rgb: [u8; 3],
+    /// 属于该颜色的像素占比（0 到 1）
+    pub ratio: f64,
+}
+
+/// 输入的媒体类型
+///
+/// OSS 触发无法保证上传的都是视频，纯音频和单张图片只执行适用的部分处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    /// 视频（默认）
+    #[default]
+    Video,
+    /// 只有音频流：只提取音频，没有场景和关键帧
+    AudioOnly,
+    /// 单张图片：输出一个关键帧，没有音频
+    StillImage,
+}
+
+impl MediaKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaKind::Video => "video",
+            MediaKind::AudioOnly => "audio_only",
+            MediaKind::StillImage => "still_image",
+        }
+    }
+
+    pub fn is_video(&self) -> bool {
+        *self == MediaKind::Video
+    }
+
+    /// 相比视频缺少的处理结果，记录在元数据中
+    pub fn limitations(&self) -> Vec<String> {
+        match self {
+            MediaKind::Video => Vec::new(),
+            MediaKind::AudioOnly => vec!["输入只有音频流，未进行场景检测和关键帧提取".to_string()],
+            MediaKind::StillImage => vec!["输入为单张图片，只输出一个关键帧，没有音频".to_string()],
+        }
+    }
 }
 
 /// 整个视频的元数据
@@ -20,16 +159,413 @@ pub struct SceneMetadata {
 pub struct VideoMetadata {
     /// 输入视频文件路径
     pub input_video: String,
+    /// 输入的媒体类型（不是视频时）
+    #[serde(default, skip_serializing_if = "MediaKind::is_video")]
+    pub media_kind: MediaKind,
+    /// 因输入不是视频而缺少的处理结果
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub limitations: Vec<String>,
     /// 视频总时长（秒）
     pub total_duration: f64,
     /// 视频帧率
     pub fps: f64,
-    /// 视频分辨率（宽x高）
+    /// 视频显示分辨率（宽x高，已按像素宽高比缩放）
     pub resolution: String,
+    /// 存储分辨率（宽x高，非方形像素时与显示分辨率不同）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_resolution: Option<String>,
+    /// 像素宽高比（SAR，例如 4:3，非方形像素时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_aspect_ratio: Option<String>,
     /// 检测到的场景数量
     pub scene_count: usize,
-    /// 音频文件路径
+    /// 音频文件路径（没有音频时为空）
     pub audio_file: String,
     /// 场景列表
     pub scenes: Vec<SceneMetadata>,
-}
\ No newline at end of file
+    /// 内容安全汇总（启用内容安全检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety: Option<SafetySummary>,
+    /// 人脸汇总：有人物出现的场景（启用人脸检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faces: Option<FaceSummary>,
+    /// 关键帧图像向量文件（启用图像向量时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embeddings: Option<EmbeddingsIndex>,
+    /// 关键帧缩略图拼图（启用拼图时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprite: Option<SpriteSheet>,
+    /// 音频响度统计（启用波形分析且有音频时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loudness: Option<LoudnessStats>,
+    /// 语音段和静音段（启用语音活动检测且有音频时）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audio_segments: Vec<AudioSegment>,
+    /// 场景列表的其他导出格式文件（EDL / CSV / SRT）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exports: Vec<String>,
+    /// 视频指纹文件（启用视频指纹时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint_file: Option<String>,
+    /// 源文件哈希（ETag 或 SHA-256，按内容寻址或调用方提供了 ETag 时），用于判断目标位置的结果是否属于同一内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
+    /// 配置指纹（按内容寻址时），与源文件哈希一起可以作为结果缓存的键
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_fingerprint: Option<String>,
+    /// 元数据文件名（按文件名模板生成，不写入元数据）
+    #[serde(skip)]
+    pub metadata_file: String,
+}
+
+/// 场景列表的导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// CMX3600 EDL，可导入 Premiere / DaVinci Resolve
+    Edl,
+    /// CSV 场景表
+    Csv,
+    /// SRT 字幕，每个场景一条，作为章节标记
+    Srt,
+    /// YouTube 视频简介中的章节列表（`00:00 场景 1`）
+    Youtube,
+    /// FFmpeg 元数据文件（FFMETADATA1）中的章节，可以写回 MP4
+    Ffmetadata,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Edl => "edl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Srt => "srt",
+            ExportFormat::Youtube => "youtube",
+            ExportFormat::Ffmetadata => "ffmetadata",
+        }
+    }
+
+    /// 导出文件名：`scenes.edl`、`scenes.csv`、`scenes.srt`，章节为 `chapters.txt`、`chapters.ffmetadata`
+    pub fn file_name(&self) -> String {
+        match self {
+            ExportFormat::Youtube => "chapters.txt".to_string(),
+            ExportFormat::Ffmetadata => "chapters.ffmetadata".to_string(),
+            _ => format!("scenes.{}", self.as_str()),
+        }
+    }
+
+    /// 解析逗号分隔的格式列表（忽略重复项）
+    pub fn parse_list(value: &str) -> Result<Vec<ExportFormat>, String> {
+        let mut formats = Vec::new();
+        for item in value.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            let format: ExportFormat = item.parse()?;
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        Ok(formats)
+    }
+
+    /// 逗号分隔的格式名
+    pub fn names(formats: &[ExportFormat]) -> String {
+        formats.iter().map(|f| f.as_str()).collect::<Vec<_>>().join(",")
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "edl" => Ok(ExportFormat::Edl),
+            "csv" => Ok(ExportFormat::Csv),
+            "srt" => Ok(ExportFormat::Srt),
+            "youtube" => Ok(ExportFormat::Youtube),
+            "ffmetadata" => Ok(ExportFormat::Ffmetadata),
+            other => Err(format!("未知的导出格式: {}（可选值: edl, csv, srt, youtube, ffmetadata）", other)),
+        }
+    }
+}
+
+/// 把场景列表导出为剪辑软件可以导入的格式，与 metadata.json 一起写入输出目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataExporter {
+    pub format: ExportFormat,
+}
+
+impl MetadataExporter {
+    pub fn new(format: ExportFormat) -> Self {
+        Self { format }
+    }
+
+    pub fn file_name(&self) -> String {
+        self.format.file_name()
+    }
+
+    /// 按格式序列化场景列表
+    pub fn render(&self, metadata: &VideoMetadata) -> String {
+        match self.format {
+            ExportFormat::Edl => render_edl(metadata),
+            ExportFormat::Csv => render_csv(metadata),
+            ExportFormat::Srt => render_srt(metadata),
+            ExportFormat::Youtube => render_youtube_chapters(metadata),
+            ExportFormat::Ffmetadata => render_ffmetadata(metadata),
+        }
+    }
+
+    pub fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+        let path = output_dir.join(self.file_name());
+        std::fs::write(&path, self.render(metadata)).context(format!("写入导出文件失败: {}", path.display()))
+    }
+}
+
+/// 场景的显示名称：有描述时使用描述，否则为 `场景 N`（从 1 开始）
+fn scene_title(scene: &SceneMetadata) -> String {
+    scene
+        .caption
+        .as_deref()
+        .map(|caption| caption.trim().replace(['\r', '\n'], " "))
+        .filter(|caption| !caption.is_empty())
+        .unwrap_or_else(|| format!("场景 {}", scene.scene_id + 1))
+}
+
+/// SMPTE 时间码 `HH:MM:SS:FF`（非丢帧，帧率取整）
+fn timecode(seconds: f64, fps: f64) -> String {
+    let fps = (fps.round() as u64).max(1);
+    let frames = (seconds.max(0.0) * fps as f64).round() as u64;
+    let (total_seconds, frame) = (frames / fps, frames % fps);
+    format!("{:02}:{:02}:{:02}:{:02}", total_seconds / 3600, total_seconds / 60 % 60, total_seconds % 60, frame)
+}
+
+/// CMX3600 EDL：每个场景一个事件，源时间码与录制时间码相同
+fn render_edl(metadata: &VideoMetadata) -> String {
+    let clip_name = Path::new(&metadata.input_video)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| metadata.input_video.clone());
+    let title = Path::new(&clip_name).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut edl = format!("TITLE: {}\nFCM: NON-DROP FRAME\n", title);
+    for (index, scene) in metadata.scenes.iter().enumerate() {
+        let (start, end) = (timecode(scene.start_time, metadata.fps), timecode(scene.end_time, metadata.fps));
+        let _ = write!(
+            edl,
+            "\n{:03}  AX       V     C        {} {} {} {}\n* FROM CLIP NAME: {}\n* COMMENT: {}\n",
+            index + 1, start, end, start, end, clip_name, scene_title(scene)
+        );
+    }
+    edl
+}
+
+/// CSV 字段：包含逗号、引号或换行时加引号，内部引号写两次
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_csv(metadata: &VideoMetadata) -> String {
+    let mut csv = String::from("scene_id,start_time,end_time,duration,start_timecode,end_timecode,keyframe_file,clip_file,caption,ocr_text\n");
+    for scene in &metadata.scenes {
+        let _ = writeln!(
+            csv,
+            "{},{:.3},{:.3},{:.3},{},{},{},{},{},{}",
+            scene.scene_id,
+            scene.start_time,
+            scene.end_time,
+            scene.duration,
+            timecode(scene.start_time, metadata.fps),
+            timecode(scene.end_time, metadata.fps),
+            csv_field(&scene.keyframe_file),
+            csv_field(scene.clip_file.as_deref().unwrap_or_default()),
+            csv_field(scene.caption.as_deref().unwrap_or_default()),
+            csv_field(scene.ocr_text.as_deref().unwrap_or_default()),
+        );
+    }
+    csv
+}
+
+/// SRT 时间格式：`HH:MM:SS,mmm`
+fn srt_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!("{:02}:{:02}:{:02},{:03}", millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+}
+
+fn render_srt(metadata: &VideoMetadata) -> String {
+    let mut srt = String::new();
+    for (index, scene) in metadata.scenes.iter().enumerate() {
+        let _ = write!(
+            srt,
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1, srt_time(scene.start_time), srt_time(scene.end_time), scene_title(scene)
+        );
+    }
+    srt
+}
+
+/// YouTube 章节之间的最短间隔（秒）
+const YOUTUBE_MIN_CHAPTER: f64 = 10.0;
+/// YouTube 识别章节列表所需的最少章节数
+const YOUTUBE_MIN_CHAPTERS: usize = 3;
+
+/// YouTube 章节时间：`MM:SS`，视频超过一小时时为 `H:MM:SS`
+fn youtube_time(seconds: f64, total_duration: f64) -> String {
+    let secs = seconds.max(0.0).floor() as u64;
+    if total_duration >= 3600.0 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// YouTube 章节列表，粘贴到视频简介即可生成章节
+///
+/// YouTube 要求第一个章节从 00:00 开始、每个章节至少 10 秒：第一个章节固定从 0 开始，
+/// 距上一个章节不足 10 秒的场景并入上一个章节（不足 10 秒的结尾场景同样并入），
+/// 并入的场景的描述不会出现在章节名中。合并后不足 3 个章节时 YouTube 不会识别，返回空内容
+fn render_youtube_chapters(metadata: &VideoMetadata) -> String {
+    let mut chapters: Vec<(f64, String)> = Vec::new();
+    for scene in &metadata.scenes {
+        let start = if chapters.is_empty() { 0.0 } else { scene.start_time };
+        let long_enough = chapters.last().is_none_or(|(last, _)| start - last >= YOUTUBE_MIN_CHAPTER)
+            && metadata.total_duration - start >= YOUTUBE_MIN_CHAPTER;
+        if chapters.is_empty() || long_enough {
+            chapters.push((start, scene_title(scene)));
+        }
+    }
+    if chapters.len() < YOUTUBE_MIN_CHAPTERS {
+        tracing::warn!(
+            chapters = chapters.len(),
+            scenes = metadata.scenes.len(),
+            "⚠️  [导出] 合并短场景后不足 {} 个章节，YouTube 不会识别，章节文件为空",
+            YOUTUBE_MIN_CHAPTERS
+        );
+        return String::new();
+    }
+    chapters
+        .iter()
+        .map(|(start, title)| format!("{} {}\n", youtube_time(*start, metadata.total_duration), title))
+        .collect()
+}
+
+/// FFMETADATA 中需要转义的字符：`=`、`;`、`#`、`\` 和换行前加 `\`
+fn ffmetadata_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// FFMETADATA1 章节文件（时间基 1/1000），用
+/// `ffmpeg -i input.mp4 -i chapters.ffmetadata -map_metadata 1 -map_chapters 1 -codec copy output.mp4` 写回视频
+fn render_ffmetadata(metadata: &VideoMetadata) -> String {
+    let title = Path::new(&metadata.input_video).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut ffmetadata = format!(";FFMETADATA1\ntitle={}\n", ffmetadata_escape(&title));
+    for scene in &metadata.scenes {
+        let start = (scene.start_time.max(0.0) * 1000.0).round() as u64;
+        let end = ((scene.end_time * 1000.0).round() as u64).max(start);
+        let _ = write!(
+            ffmetadata,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            start, end, ffmetadata_escape(&scene_title(scene))
+        );
+    }
+    ffmetadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_formats() {
+        let metadata = VideoMetadata {
+            input_video: "/data/videos/demo.mp4".to_string(),
+            media_kind: MediaKind::Video,
+            limitations: Vec::new(),
+            total_duration: 3725.5,
+            fps: 25.0,
+            resolution: "1920x1080".to_string(),
+            storage_resolution: None,
+            sample_aspect_ratio: None,
+            scene_count: 2,
+            audio_file: "audio.aac".to_string(),
+            scenes: vec![
+                SceneMetadata::fixture(0, 0.0, 5.52),
+                SceneMetadata { caption: Some("海边, \"日落\"".to_string()), ..SceneMetadata::fixture(1, 5.52, 3725.5) },
+            ],
+            safety: None,
+            faces: None,
+            embeddings: None,
+            sprite: None,
+            loudness: None,
+            audio_segments: Vec::new(),
+            exports: Vec::new(),
+            fingerprint_file: None,
+            source_hash: None,
+            config_fingerprint: None,
+            metadata_file: "metadata.json".to_string(),
+        };
+
+        let edl = MetadataExporter::new(ExportFormat::Edl).render(&metadata);
+        assert!(edl.starts_with("TITLE: demo\nFCM: NON-DROP FRAME\n"));
+        assert!(edl.contains("\n001  AX       V     C        00:00:00:00 00:00:05:13 00:00:00:00 00:00:05:13\n* FROM CLIP NAME: demo.mp4\n"));
+        assert!(edl.contains("\n002  AX       V     C        00:00:05:13 01:02:05:13"));
+
+        let csv = MetadataExporter::new(ExportFormat::Csv).render(&metadata);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.ends_with("1,5.520,3725.500,3719.980,00:00:05:13,01:02:05:13,keyframe_0001.jpg,,\"海边, \"\"日落\"\"\",\n"));
+
+        let srt = MetadataExporter::new(ExportFormat::Srt).render(&metadata);
+        assert!(srt.starts_with("1\n00:00:00,000 --> 00:00:05,520\n场景 1\n\n2\n00:00:05,520 --> 01:02:05,500\n海边, \"日落\"\n"));
+
+        let youtube = MetadataExporter::new(ExportFormat::Youtube).render(&metadata);
+        // 不足 3 个章节时 YouTube 不识别，不输出章节
+        assert_eq!(youtube, "");
+        // 距上一章节或视频结尾不足 10 秒的场景并入上一章节
+        let short = VideoMetadata {
+            total_duration: 95.0,
+            scenes: vec![
+                SceneMetadata::fixture(0, 0.4, 12.0),
+                SceneMetadata { caption: Some("路口".to_string()), ..SceneMetadata::fixture(1, 12.0, 15.0) },
+                SceneMetadata { caption: Some("被合并".to_string()), ..SceneMetadata::fixture(2, 15.0, 40.0) },
+                SceneMetadata::fixture(3, 40.0, 90.0),
+                SceneMetadata::fixture(4, 90.0, 95.0),
+            ],
+            ..metadata.clone()
+        };
+        let youtube = MetadataExporter::new(ExportFormat::Youtube).render(&short);
+        // 并入上一章节的场景的描述不出现在章节名中
+        assert_eq!(youtube, "00:00 场景 1\n00:12 路口\n00:40 场景 4\n");
+        assert!(!youtube.contains("被合并"));
+
+        let ffmetadata = MetadataExporter::new(ExportFormat::Ffmetadata).render(&metadata);
+        assert!(ffmetadata.starts_with(";FFMETADATA1\ntitle=demo\n\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=5520\ntitle=场景 1\n"));
+        assert!(ffmetadata.ends_with("START=5520\nEND=3725500\ntitle=海边, \"日落\"\n"));
+        assert_eq!(ffmetadata_escape("a=b;c#d\\e"), "a\\=b\\;c\\#d\\\\e");
+
+        assert_eq!(ExportFormat::parse_list("EDL, srt,edl"), Ok(vec![ExportFormat::Edl, ExportFormat::Srt]));
+        assert!(ExportFormat::parse_list("xml").is_err());
+        assert_eq!(ExportFormat::Csv.file_name(), "scenes.csv");
+        assert_eq!(ExportFormat::Youtube.file_name(), "chapters.txt");
+    }
+
+    #[test]
+    fn test_scene_ocr_text() {
+        let scene = SceneMetadata { ocr_text: Some("第三章".to_string()), ..SceneMetadata::fixture(0, 0.0, 5.0) };
+        let value = serde_json::to_value(&scene).unwrap();
+        assert_eq!(value["ocr_text"], "第三章");
+        assert!(value.get("text").is_none());
+
+        // 旧版本 metadata.json 中的 text 字段仍然可以读取
+        let mut legacy = value;
+        legacy.as_object_mut().unwrap().remove("ocr_text");
+        legacy["text"] = serde_json::json!("旧字段");
+        let scene: SceneMetadata = serde_json::from_value(legacy).unwrap();
+        assert_eq!(scene.ocr_text.as_deref(), Some("旧字段"));
+    }
+}
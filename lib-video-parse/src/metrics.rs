@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Prometheus 文本格式的 Content-Type
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 耗时直方图的桶上限（秒）
+const DURATION_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0];
+
+/// 累积直方图
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// 与 `DURATION_BUCKETS` 一一对应的累积计数
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; DURATION_BUCKETS.len()];
+        }
+        for (bucket, &upper) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if value <= upper {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// 任务数（按结果：success | failure | cached | skipped | rejected）
+    jobs: BTreeMap<&'static str, u64>,
+    /// 失败数（按错误码，例如 `download_timeout`）
+    failures: BTreeMap<String, u64>,
+    /// 处理阶段耗时（按阶段）
+    stage_duration: BTreeMap<&'static str, Histogram>,
+    /// OSS 传输字节数（按方向：download | upload）
+    transfer_bytes: BTreeMap<&'static str, u64>,
+    /// OSS 传输耗时（按方向）
+    transfer_duration: BTreeMap<&'static str, Histogram>,
+}
+
+/// 进程内指标，由 `GET /metrics` 以 Prometheus 文本格式输出
+///
+/// 由 [`crate::telemetry`] 的 `record_*` 函数写入，与 OTLP 指标导出互不影响
+#[derive(Debug, Default)]
+pub struct Metrics {
+    inner: Mutex<Inner>,
+    /// 正在处理的任务数
+    active_jobs: AtomicI64,
+}
+
+/// 正在处理的任务守卫，析构时活动任务数减一
+pub struct ActiveJob<'a>(&'a Metrics);
+
+impl Drop for ActiveJob<'_> {
+    fn drop(&mut self) {
+        self.0.active_jobs.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Metrics {
+    /// 进程内共享的指标
+    pub fn shared() -> &'static Metrics {
+        static SHARED: OnceLock<Metrics> = OnceLock::new();
+        SHARED.get_or_init(Metrics::default)
+    }
+
+    /// 开始一个任务，活动任务数加一直到返回的守卫被丢弃
+    pub fn active_job(&self) -> ActiveJob<'_> {
+        self.active_jobs.fetch_add(1, Ordering::Relaxed);
+        ActiveJob(self)
+    }
+
+    pub fn record_job(&self, outcome: &'static str) {
+        *self.inner.lock().unwrap().jobs.entry(outcome).or_default() += 1;
+    }
+
+    pub fn record_failure(&self, code: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.failures.get_mut(code) {
+            Some(count) => *count += 1,
+            None => {
+                inner.failures.insert(code.to_string(), 1);
+            }
+        }
+    }
+
+    pub fn record_stage(&self, stage: &'static str, duration: Duration) {
+        self.inner.lock().unwrap().stage_duration.entry(stage).or_default().observe(duration.as_secs_f64());
+    }
+
+    pub fn record_transfer(&self, direction: &'static str, bytes: u64, duration: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.transfer_bytes.entry(direction).or_default() += bytes;
+        inner.transfer_duration.entry(direction).or_default().observe(duration.as_secs_f64());
+    }
+
+    /// 按 Prometheus 文本格式（0.0.4）输出全部指标
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        write_header(&mut out, "video_parse_jobs_total", "counter", "处理任务数（按结果）");
+        for (outcome, count) in &inner.jobs {
+            let _ = writeln!(out, "video_parse_jobs_total{{outcome=\"{}\"}} {}", outcome, count);
+        }
+        write_header(&mut out, "video_parse_failures_total", "counter", "处理失败数（按错误码）");
+        for (code, count) in &inner.failures {
+            let _ = writeln!(out, "video_parse_failures_total{{code=\"{}\"}} {}", escape_label(code), count);
+        }
+        write_header(&mut out, "video_parse_active_jobs", "gauge", "正在处理的任务数");
+        let _ = writeln!(out, "video_parse_active_jobs {}", self.active_jobs.load(Ordering::Relaxed));
+        write_header(&mut out, "video_parse_stage_duration_seconds", "histogram", "处理阶段耗时");
+        for (stage, histogram) in &inner.stage_duration {
+            write_histogram(&mut out, "video_parse_stage_duration_seconds", "stage", stage, histogram);
+        }
+        write_header(&mut out, "video_parse_transfer_bytes_total", "counter", "OSS 传输字节数（按方向）");
+        for (direction, bytes) in &inner.transfer_bytes {
+            let _ = writeln!(out, "video_parse_transfer_bytes_total{{direction=\"{}\"}} {}", direction, bytes);
+        }
+        write_header(&mut out, "video_parse_transfer_duration_seconds", "histogram", "OSS 传输耗时");
+        for (direction, histogram) in &inner.transfer_duration {
+            write_histogram(&mut out, "video_parse_transfer_duration_seconds", "direction", direction, histogram);
+        }
+        out
+    }
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn write_histogram(out: &mut String, name: &str, label: &str, value: &str, histogram: &Histogram) {
+    for (count, upper) in histogram.buckets.iter().zip(DURATION_BUCKETS) {
+        let _ = writeln!(out, "{}_bucket{{{}=\"{}\",le=\"{}\"}} {}", name, label, value, upper, count);
+    }
+    let _ = writeln!(out, "{}_bucket{{{}=\"{}\",le=\"+Inf\"}} {}", name, label, value, histogram.count);
+    let _ = writeln!(out, "{}_sum{{{}=\"{}\"}} {}", name, label, value, histogram.sum);
+    let _ = writeln!(out, "{}_count{{{}=\"{}\"}} {}", name, label, value, histogram.count);
+}
+
+/// 转义标签值中的反斜杠、双引号和换行
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = Metrics::default();
+        metrics.record_job("success");
+        metrics.record_job("success");
+        metrics.record_failure("download_timeout");
+        metrics.record_stage("detect_scenes", Duration::from_millis(700));
+        metrics.record_stage("detect_scenes", Duration::from_secs(45));
+        metrics.record_transfer("download", 1024, Duration::from_secs(2));
+        let active = metrics.active_job();
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE video_parse_jobs_total counter\n"));
+        assert!(text.contains("video_parse_jobs_total{outcome=\"success\"} 2\n"));
+        assert!(text.contains("video_parse_failures_total{code=\"download_timeout\"} 1\n"));
+        assert!(text.contains("video_parse_active_jobs 1\n"));
+        assert!(text.contains("video_parse_stage_duration_seconds_bucket{stage=\"detect_scenes\",le=\"0.5\"} 0\n"));
+        assert!(text.contains("video_parse_stage_duration_seconds_bucket{stage=\"detect_scenes\",le=\"1\"} 1\n"));
+        assert!(text.contains("video_parse_stage_duration_seconds_bucket{stage=\"detect_scenes\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("video_parse_stage_duration_seconds_sum{stage=\"detect_scenes\"} 45.7\n"));
+        assert!(text.contains("video_parse_transfer_bytes_total{direction=\"download\"} 1024\n"));
+
+        drop(active);
+        assert!(metrics.render().contains("video_parse_active_jobs 0\n"));
+        assert_eq!(escape_label("a\"b"), "a\\\"b");
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::time::Duration;
+use crate::processor::ProcessOutput;
+
+/// 单个通知请求的超时时间
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 即时通讯群机器人通知配置
+///
+/// 与 webhook 回调（给程序消费的完整结果）不同，这里只发送给人看的简短卡片
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotifyConfig {
+    /// 钉钉群机器人 Webhook 地址
+    pub dingtalk_webhook: Option<String>,
+    /// Slack Incoming Webhook 地址
+    pub slack_webhook: Option<String>,
+    /// 飞书群机器人 Webhook 地址
+    pub feishu_webhook: Option<String>,
+}
+
+impl NotifyConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.dingtalk_webhook.is_some() || self.slack_webhook.is_some() || self.feishu_webhook.is_some()
+    }
+}
+
+/// 一条处理完成或失败的通知
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// 视频名称（bucket/key 或本地路径）
+    pub video: String,
+    /// 处理结果
+    pub outcome: NotifyOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotifyOutcome {
+    Success {
+        scene_count: usize,
+        /// 视频时长（秒）
+        duration: f64,
+        /// 处理耗时（毫秒）
+        elapsed_ms: u64,
+        /// 输出位置（上传后的 OSS 路径或本地目录）
+        outputs: String,
+    },
+    Failure {
+        error: String,
+    },
+}
+
+impl Notification {
+    pub fn success(video: impl Into<String>, output: &ProcessOutput, outputs: impl Into<String>) -> Self {
+        Self {
+            video: video.into(),
+            outcome: NotifyOutcome::Success {
+                scene_count: output.metadata.scene_count,
+                duration: output.metadata.total_duration,
+                elapsed_ms: output.timings.total_ms,
+                outputs: outputs.into(),
+            },
+        }
+    }
+
+    pub fn failure(video: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            video: video.into(),
+            outcome: NotifyOutcome::Failure { error: error.into() },
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self.outcome, NotifyOutcome::Success { .. })
+    }
+
+    pub fn title(&self) -> &'static str {
+        if self.is_success() {
+            "✅ 视频拉片完成"
+        } else {
+            "❌ 视频拉片失败"
+        }
+    }
+
+    /// 卡片正文，每项一行
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("视频: {}", self.video)];
+        match &self.outcome {
+            NotifyOutcome::Success { scene_count, duration, elapsed_ms, outputs } => {
+                lines.push(format!("场景数: {}", scene_count));
+                lines.push(format!("视频时长: {:.1}s", duration));
+                lines.push(format!("处理耗时: {:.1}s", *elapsed_ms as f64 / 1000.0));
+                lines.push(format!("输出: {}", outputs));
+            }
+            NotifyOutcome::Failure { error } => {
+                lines.push(format!("错误: {}", error));
+            }
+        }
+        lines
+    }
+
+    /// 钉钉 markdown 消息
+    pub fn dingtalk_payload(&self) -> Value {
+        let text = std::iter::once(format!("### {}", self.title()))
+            .chain(self.lines().into_iter().map(|line| format!("- {}", line)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        json!({
+            "msgtype": "markdown",
+            "markdown": { "title": self.title(), "text": text },
+        })
+    }
+
+    /// Slack 消息（mrkdwn）
+    pub fn slack_payload(&self) -> Value {
+        let text = std::iter::once(format!("*{}*", self.title()))
+            .chain(self.lines())
+            .collect::<Vec<_>>()
+            .join("\n");
+        json!({ "text": text })
+    }
+
+    /// 飞书消息卡片，成功为绿色标题，失败为红色
+    pub fn feishu_payload(&self) -> Value {
+        json!({
+            "msg_type": "interactive",
+            "card": {
+                "header": {
+                    "title": { "tag": "plain_text", "content": self.title() },
+                    "template": if self.is_success() { "green" } else { "red" },
+                },
+                "elements": [
+                    { "tag": "markdown", "content": self.lines().join("\n") },
+                ],
+            },
+        })
+    }
+}
+
+/// 向所有配置的群机器人发送通知，失败只记录警告
+pub async fn send(config: &NotifyConfig, notification: &Notification) {
+    if !config.is_enabled() {
+        return;
+    }
+    let client = reqwest::Client::new();
+    let channels = [
+        ("钉钉", &config.dingtalk_webhook, notification.dingtalk_payload()),
+        ("Slack", &config.slack_webhook, notification.slack_payload()),
+        ("飞书", &config.feishu_webhook, notification.feishu_payload()),
+    ];
+    for (channel, url, payload) in channels {
+        let Some(url) = url else {
+            continue;
+        };
+        match post(&client, url, &payload).await {
+            Ok(()) => tracing::info!(channel, "✅ [通知] 群机器人通知已发送"),
+            Err(e) => tracing::warn!(channel, error = %e, "⚠️  [通知] 群机器人通知发送失败"),
+        }
+    }
+}
+
+async fn post(client: &reqwest::Client, url: &str, payload: &Value) -> Result<()> {
+    let response = client
+        .post(url)
+        .json(payload)
+        .timeout(NOTIFY_TIMEOUT)
+        .send()
+        .await
+        .context("请求失败")?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!("返回错误状态: {} - {}", status, body);
+    }
+    // 钉钉和飞书的接口总是返回 200，错误通过响应中的 errcode / code 表示
+    if let Ok(result) = serde_json::from_str::<Value>(&body) {
+        let code = result.get("errcode").or_else(|| result.get("code")).and_then(Value::as_i64);
+        if let Some(code) = code.filter(|&code| code != 0) {
+            let message = result.get("errmsg").or_else(|| result.get("msg")).and_then(Value::as_str);
+            anyhow::bail!("返回错误: {} {}", code, message.unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_payloads() {
+        let failure = Notification::failure("videos/a.mp4", "解码失败");
+        assert_eq!(failure.lines(), vec!["视频: videos/a.mp4", "错误: 解码失败"]);
+        assert_eq!(failure.feishu_payload()["card"]["header"]["template"], "red");
+        assert_eq!(failure.dingtalk_payload()["msgtype"], "markdown");
+
+        let success = Notification {
+            video: "videos/a.mp4".to_string(),
+            outcome: NotifyOutcome::Success {
+                scene_count: 12,
+                duration: 120.5,
+                elapsed_ms: 8_000,
+                outputs: "oss://dest/videos/a".to_string(),
+            },
+        };
+        let text = success.slack_payload()["text"].as_str().unwrap().to_string();
+        assert!(text.starts_with("*✅ 视频拉片完成*"));
+        assert!(text.contains("场景数: 12"));
+        assert!(text.contains("输出: oss://dest/videos/a"));
+    }
+}
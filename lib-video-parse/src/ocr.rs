@@ -0,0 +1,202 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use crate::metadata::SceneMetadata;
+
+/// OCR 配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OcrConfig {
+    /// 是否识别关键帧中的文字
+    pub enabled: bool,
+    /// tesseract 语言包，多个用 + 连接
+    pub languages: String,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            languages: "chi_sim+eng".to_string(),
+        }
+    }
+}
+
+/// 文字识别后端：识别关键帧图片中的文字（tesseract、远程 OCR API 等）
+///
+/// 返回整理后的文字，没有文字时返回空字符串
+#[async_trait]
+pub trait TextRecognizer: Send + Sync {
+    /// 名称，用于日志
+    fn name(&self) -> &str;
+    async fn recognize(&self, keyframe_path: &Path) -> Result<String>;
+}
+
+/// 使用 tesseract 命令行识别文字（可通过 TESSERACT_PATH 指定路径），需要以 `--features ocr` 编译
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TesseractRecognizer {
+    /// tesseract 语言包，多个用 + 连接
+    pub languages: String,
+}
+
+#[cfg(feature = "ocr")]
+#[async_trait]
+impl TextRecognizer for TesseractRecognizer {
+    fn name(&self) -> &str {
+        "tesseract"
+    }
+
+    async fn recognize(&self, keyframe_path: &Path) -> Result<String> {
+        let (languages, keyframe_path) = (self.languages.clone(), keyframe_path.to_path_buf());
+        crate::processor::run_blocking("文字识别", move || recognize_image(&languages, &keyframe_path)).await
+    }
+}
+
+/// 配置开启文字识别时使用的默认后端；未启用 `ocr` feature 时没有默认后端
+pub fn default_recognizer(config: &OcrConfig) -> Option<Arc<dyn TextRecognizer>> {
+    #[cfg(feature = "ocr")]
+    {
+        Some(Arc::new(TesseractRecognizer { languages: config.languages.clone() }))
+    }
+    #[cfg(not(feature = "ocr"))]
+    {
+        let _ = config;
+        tracing::warn!("⚠️  已启用文字识别，但未启用 ocr feature，跳过");
+        None
+    }
+}
+
+/// 识别每个场景关键帧中的文字（标题、字幕、幻灯片等），写入 `SceneMetadata.ocr_text`
+///
+/// 重复关键帧（`duplicate_of`）复用原关键帧的识别结果。单帧识别失败只记录警告。返回识别出文字的场景数
+pub async fn recognize_scenes(recognizer: &dyn TextRecognizer, output_dir: &Path, scenes: &mut [SceneMetadata]) -> Result<usize> {
+    for index in 0..scenes.len() {
+        if let Some(original) = scenes[index].duplicate_of {
+            if let Some(text) = scenes.iter().find(|s| s.scene_id == original).map(|s| s.ocr_text.clone()) {
+                scenes[index].ocr_text = text;
+                continue;
+            }
+        }
+        let keyframe_path = output_dir.join(&scenes[index].keyframe_file);
+        match recognizer.recognize(&keyframe_path).await {
+            Ok(text) if !text.is_empty() => {
+                tracing::debug!(scene_id = scenes[index].scene_id, chars = text.chars().count(), "识别到关键帧文字");
+                scenes[index].ocr_text = Some(text);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(
+                    scene_id = scenes[index].scene_id,
+                    recognizer = recognizer.name(),
+                    error = %e,
+                    "⚠️  [文字识别] 关键帧文字识别失败"
+                );
+            }
+        }
+    }
+    Ok(scenes.iter().filter(|s| s.ocr_text.is_some()).count())
+}
+
+/// 识别单张图片中的文字
+#[cfg(feature = "ocr")]
+fn recognize_image(languages: &str, image_path: &Path) -> Result<String> {
+    use anyhow::Context;
+    use std::process::Command;
+
+    let tesseract = std::env::var("TESSERACT_PATH").unwrap_or_else(|_| "tesseract".to_string());
+    let output = Command::new(&tesseract)
+        .arg(image_path)
+        .arg("stdout")
+        .args(["-l", languages])
+        .args(["--psm", "3"])
+        .output()
+        .context(format!("执行 tesseract 失败: {}", tesseract))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "tesseract 返回错误状态: {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(normalize_text(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 整理识别结果：去掉空行和行首尾空白，合并连续空白，去掉 tesseract 在汉字之间插入的空格
+pub fn normalize_text(text: &str) -> String {
+    text.lines()
+        .map(normalize_line)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn normalize_line(line: &str) -> String {
+    let mut result = String::new();
+    let mut pending_space = false;
+    for c in line.trim().chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && !(is_cjk(c) && result.chars().last().is_some_and(is_cjk)) {
+            result.push(' ');
+        }
+        pending_space = false;
+        result.push(c);
+    }
+    result
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0x3000..=0x303F | 0xFF00..=0xFFEF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        let raw = "  第 一 课  函数与闭包 \n\n\nChapter 1: Intro \n\u{c}";
+        assert_eq!(normalize_text(raw), "第一课函数与闭包\nChapter 1: Intro");
+    }
+
+    /// 把文件名当作识别结果
+    struct FileNameRecognizer;
+
+    #[async_trait]
+    impl TextRecognizer for FileNameRecognizer {
+        fn name(&self) -> &str {
+            "file_name"
+        }
+
+        async fn recognize(&self, keyframe_path: &Path) -> Result<String> {
+            match keyframe_path.file_stem().and_then(|s| s.to_str()) {
+                Some("broken") => anyhow::bail!("无法识别"),
+                Some("blank") => Ok(String::new()),
+                Some(stem) => Ok(normalize_text(stem)),
+                None => Ok(String::new()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recognize_scenes() {
+        let scene = |scene_id: usize, keyframe_file: &str, duplicate_of: Option<usize>| SceneMetadata {
+            keyframe_file: keyframe_file.to_string(),
+            duplicate_of,
+            ..SceneMetadata::fixture(scene_id, 0.0, 1.0)
+        };
+        let mut scenes = vec![
+            scene(0, "slide 1.jpg", None),
+            scene(1, "blank.jpg", None),
+            scene(2, "broken.jpg", None),
+            scene(3, "slide 1.jpg", Some(0)),
+        ];
+        let recognized = recognize_scenes(&FileNameRecognizer, Path::new("/tmp"), &mut scenes).await.unwrap();
+        assert_eq!(recognized, 2);
+        let texts: Vec<Option<&str>> = scenes.iter().map(|s| s.ocr_text.as_deref()).collect();
+        assert_eq!(texts, [Some("slide 1"), None, None, Some("slide 1")]);
+    }
+}
@@ -1,9 +1,203 @@
-use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 use std::fs;
 use ali_oss_rs::Client;
 use ali_oss_rs::object::ObjectOperations;
 use ali_oss_rs::object_common::PutObjectOptions;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use futures::stream::{FuturesUnordered, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use crate::progressive::ProgressiveFile;
+use crate::error::{Result, VideoParseError};
+
+/// 下载请求临时失败（超时、连接失败、5xx）时的默认重试次数
+const DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+/// OSS 分片上传的分片数上限
+const MAX_PARTS: u64 = 10_000;
+/// OSS 分片上传的最小分片大小（最后一个分片除外）
+const MIN_PART_SIZE: u64 = 100 * 1024;
+/// 单次列举和批量删除的对象数上限
+const MAX_BATCH_KEYS: usize = 1000;
+/// 同时上传的处理结果文件数（默认值）
+pub const DEFAULT_UPLOAD_CONCURRENCY: usize = 8;
+
+/// 列举对象时返回的对象信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectSummary {
+    pub key: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    pub etag: String,
+    /// 最后修改时间（ISO 8601）
+    pub last_modified: String,
+}
+
+/// 一页列举结果
+#[derive(Debug, Clone, Default)]
+pub struct ObjectListing {
+    pub objects: Vec<ObjectSummary>,
+    /// 下一页的 continuation token，为 None 时已列举完
+    pub next_continuation_token: Option<String>,
+}
+
+/// 大文件分片上传配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultipartConfig {
+    /// 文件达到该大小（MB）时使用分片上传，0 表示总是使用
+    pub threshold_mb: u64,
+    /// 分片大小（MB），分片数超过 10000 时自动增大
+    pub part_size_mb: u64,
+    /// 同时上传的分片数
+    pub concurrency: usize,
+    /// 每个分片失败后的最多重试次数
+    pub max_retries: u32,
+}
+
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        Self {
+            threshold_mb: 32,
+            part_size_mb: 8,
+            concurrency: 4,
+            max_retries: 3,
+        }
+    }
+}
+
+impl MultipartConfig {
+    /// 文件的实际分片大小：不小于 100KB，分片数不超过 10000
+    fn part_size(&self, file_size: u64) -> u64 {
+        (self.part_size_mb * 1024 * 1024)
+            .max(MIN_PART_SIZE)
+            .max(file_size.div_ceil(MAX_PARTS))
+    }
+}
+
+/// 一个待上传的处理结果文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadTask {
+    /// 文件类型（日志和错误信息中使用，例如 "关键帧"）
+    pub label: &'static str,
+    /// 本地文件名（相对输出目录），作为上传结果的键
+    pub name: String,
+    pub path: PathBuf,
+    pub object_key: String,
+}
+
+impl UploadTask {
+    pub fn new(label: &'static str, name: impl Into<String>, path: impl Into<PathBuf>, object_key: impl Into<String>) -> Self {
+        Self { label, name: name.into(), path: path.into(), object_key: object_key.into() }
+    }
+}
+
+/// 一批文件的上传结果
+#[derive(Debug, Clone, Default)]
+pub struct UploadReport {
+    /// 已上传的文件：本地文件名 → 对象键
+    pub uploaded: BTreeMap<String, String>,
+    /// 已上传的字节数
+    pub bytes: u64,
+    /// 每个上传失败的文件一条错误信息
+    pub errors: Vec<String>,
+}
+
+impl UploadReport {
+    /// 有文件上传失败时把所有错误合并为一个上传错误
+    pub fn into_result(self) -> Result<BTreeMap<String, String>> {
+        if self.errors.is_empty() {
+            return Ok(self.uploaded);
+        }
+        Err(VideoParseError::Upload {
+            message: format!("{} 个文件上传失败: {}", self.errors.len(), self.errors.join("; ")),
+            source: None,
+        })
+    }
+}
+
+/// 最多同时执行 `concurrency` 个上传，单个文件失败不影响其他文件；
+/// 按完成顺序对每个文件调用 `on_done`（记录检查点、报告进度等）
+async fn upload_concurrently<'a, F, Fut>(
+    tasks: &'a [UploadTask],
+    concurrency: usize,
+    upload: F,
+    mut on_done: impl FnMut(&UploadTask, &Result<()>),
+) -> UploadReport
+where
+    F: Fn(&'a UploadTask) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut pending = tasks.iter();
+    let mut running = FuturesUnordered::new();
+    let mut report = UploadReport::default();
+    loop {
+        while running.len() < concurrency.max(1) {
+            let Some(task) = pending.next() else { break };
+            let upload = upload(task);
+            running.push(async move { (task, upload.await) });
+        }
+        let Some((task, result)) = running.next().await else { break };
+        match &result {
+            Ok(()) => {
+                tracing::info!("已上传{}: {} -> {}", task.label, task.path.display(), task.object_key);
+                report.uploaded.insert(task.name.clone(), task.object_key.clone());
+                report.bytes += crate::resource::disk_usage(&task.path);
+            }
+            Err(e) => {
+                let error_msg = format!("上传{}失败 {}: {}", task.label, task.object_key, e);
+                tracing::error!("{}", error_msg);
+                report.errors.push(error_msg);
+            }
+        }
+        on_done(task, &result);
+    }
+    report
+}
+
+/// 未完成的分片上传，保存在本地文件旁边（`<文件名>.upload.json`），
+/// 上传中断后再次上传同一文件时只上传缺少的分片
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UploadState {
+    bucket: String,
+    object_key: String,
+    upload_id: String,
+    file_size: u64,
+    part_size: u64,
+}
+
+impl UploadState {
+    fn path(file_path: &Path) -> PathBuf {
+        let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".upload.json");
+        file_path.with_file_name(name)
+    }
+
+    /// 读取与本次上传匹配的记录（目标或文件大小变化时不续传）
+    fn load(file_path: &Path, bucket: &str, object_key: &str, file_size: u64, part_size: u64) -> Option<Self> {
+        let state: Self = serde_json::from_slice(&fs::read(Self::path(file_path)).ok()?).ok()?;
+        (state.bucket == bucket && state.object_key == object_key && state.file_size == file_size && state.part_size == part_size)
+            .then_some(state)
+    }
+
+    fn save(&self, file_path: &Path) {
+        let result = serde_json::to_vec(self)
+            .map_err(std::io::Error::other)
+            .and_then(|data| fs::write(Self::path(file_path), data));
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "⚠️  [OSS] 保存分片上传记录失败，中断后无法续传");
+        }
+    }
+
+    fn remove(file_path: &Path) {
+        let _ = fs::remove_file(Self::path(file_path));
+    }
+}
 
 /// OSS 客户端，用于下载和上传文件
 /// 
@@ -13,6 +207,7 @@ use ali_oss_rs::object_common::PutObjectOptions;
 /// - ALIBABA_CLOUD_ACCESS_KEY_ID
 /// - ALIBABA_CLOUD_ACCESS_KEY_SECRET  
 /// - ALIBABA_CLOUD_SECURITY_TOKEN
+#[derive(Clone)]
 pub struct OssClient {
     /// Access Key ID
     access_key_id: String,
@@ -20,6 +215,10 @@ pub struct OssClient {
     access_key_secret: String,
     /// Security Token（STS 临时凭证，可选）
     security_token: Option<String>,
+    /// 大文件分片上传配置
+    multipart: MultipartConfig,
+    /// 下载请求临时失败时的最多重试次数
+    download_retries: u32,
 }
 
 impl OssClient {
@@ -32,18 +231,44 @@ impl OssClient {
     pub fn new() -> Result<Self> {
         // 从环境变量获取凭证
         let access_key_id = std::env::var("ALIBABA_CLOUD_ACCESS_KEY_ID")
-            .context("未找到 ALIBABA_CLOUD_ACCESS_KEY_ID 环境变量")?;
+            .map_err(|_| VideoParseError::Config("未找到 ALIBABA_CLOUD_ACCESS_KEY_ID 环境变量".to_string()))?;
         let access_key_secret = std::env::var("ALIBABA_CLOUD_ACCESS_KEY_SECRET")
-            .context("未找到 ALIBABA_CLOUD_ACCESS_KEY_SECRET 环境变量")?;
+            .map_err(|_| VideoParseError::Config("未找到 ALIBABA_CLOUD_ACCESS_KEY_SECRET 环境变量".to_string()))?;
         let security_token = std::env::var("ALIBABA_CLOUD_SECURITY_TOKEN").ok();
 
         Ok(Self {
             access_key_id,
             access_key_secret,
             security_token,
+            multipart: MultipartConfig::default(),
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
         })
     }
 
+    /// 测试用客户端（固定凭证，不读取环境变量）
+    #[cfg(test)]
+    pub(crate) fn for_test() -> Self {
+        Self {
+            access_key_id: "test-access-key-id".to_string(),
+            access_key_secret: "test-access-key-secret".to_string(),
+            security_token: None,
+            multipart: MultipartConfig::default(),
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+        }
+    }
+
+    /// 设置下载请求临时失败（超时、连接失败、5xx、429）时的最多重试次数
+    pub fn with_download_retries(mut self, retries: u32) -> Self {
+        self.download_retries = retries;
+        self
+    }
+
+    /// 设置大文件分片上传配置
+    pub fn with_multipart(mut self, multipart: MultipartConfig) -> Self {
+        self.multipart = multipart;
+        self
+    }
+
     /// 从 endpoint 提取 region
     /// 
     /// 例如：oss-cn-hangzhou-internal.aliyuncs.com -> cn-hangzhou
@@ -94,12 +319,16 @@ impl OssClient {
     }
 
     /// 从 OSS 下载文件
+    ///
+    /// 响应边接收边写入磁盘（先写入 `<文件名>.part`，完成后改名），不会把整个文件读入内存。
+    /// 超时、连接中断和 5xx 时按指数退避重试，已写入的部分通过 Range 请求续传
     /// 
     /// # 参数
     /// - `bucket`: OSS bucket 名称
     /// - `object_key`: OSS 对象键（文件路径）
     /// - `endpoint`: OSS endpoint（推荐使用 internal endpoint，格式: oss-{region}-internal.aliyuncs.com）
     /// - `output_path`: 本地保存路径
+    #[tracing::instrument(skip_all, fields(bucket = %bucket, key = %object_key))]
     pub async fn download_file(
         &self,
         bucket: &str,
@@ -112,38 +341,131 @@ impl OssClient {
         // 确保父目录存在
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)
-                .context("创建输出目录失败")?;
+                .map_err(|e| VideoParseError::io("创建输出目录失败", e))?;
         }
 
         // 构建 endpoint（优先使用 internal endpoint）
-        let ep: String = endpoint.map(|s| s.to_string()).unwrap_or_else(|| {
-            std::env::var("OSS_ENDPOINT")
-                .unwrap_or_else(|_| "oss-cn-hangzhou.aliyuncs.com".to_string())
-        });
+        let ep = Self::resolve_endpoint(endpoint);
 
         tracing::info!("正在从 OSS 下载文件: bucket={}, key={}, endpoint={}", bucket, object_key, ep);
 
-        // 创建 OSS 客户端
-        let client = self.create_client(&ep)?;
+        let mut part_name = output_path.file_name().unwrap_or_default().to_os_string();
+        part_name.push(".part");
+        let part_path = output_path.with_file_name(part_name);
+        let mut file = tokio::fs::File::create(&part_path)
+            .await
+            .map_err(|e| VideoParseError::io("创建下载文件失败", e))?;
 
-        // 下载文件到本地路径
-        client
-            .get_object_to_file(bucket, object_key, output_path, None)
+        let mut download = PartialDownload::default();
+        let mut attempt = 0;
+        loop {
+            let before = download.written;
+            match self.stream_object(bucket, object_key, &ep, &mut file, &mut download).await {
+                Ok(()) => break,
+                Err(RequestError::Transient(e)) if attempt < self.download_retries || download.written > before => {
+                    // 有新数据写入时重新计算重试次数，长时间下载中偶尔的中断不会耗尽重试
+                    attempt = if download.written > before { 0 } else { attempt + 1 };
+                    let backoff = retry_backoff(attempt.saturating_sub(1));
+                    tracing::warn!(
+                        attempt, written = download.written, error = %e,
+                        "⚠️  [OSS] 下载中断，{:?} 后从已下载位置续传", backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => {
+                    drop(file);
+                    let _ = tokio::fs::remove_file(&part_path).await;
+                    return Err(e.into_inner().into_download());
+                }
+            }
+        }
+        file.flush()
+            .await
+            .map_err(|e| VideoParseError::io("写入下载文件失败", e))?;
+        drop(file);
+        tokio::fs::rename(&part_path, output_path)
             .await
-            .context("下载文件失败")?;
+            .map_err(|e| VideoParseError::io("保存下载文件失败", e))?;
 
-        tracing::info!("文件已下载到: {}", output_path.display());
+        tracing::info!("文件已下载到: {} ({} 字节)", output_path.display(), download.written);
 
         Ok(output_path.to_path_buf())
     }
 
+    /// 从 `download.written` 处开始下载对象并追加写入 `file`
+    ///
+    /// 续传时带上首次响应的 ETag（If-Match），对象在下载期间被修改时从头重新下载
+    async fn stream_object(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: &str,
+        file: &mut tokio::fs::File,
+        download: &mut PartialDownload,
+    ) -> std::result::Result<(), RequestError> {
+        let mut request = self.signed_request(reqwest::Method::GET, bucket, object_key, endpoint, &[])
+            .map_err(RequestError::Fatal)?;
+        if download.written > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", download.written));
+            if let Some(ref etag) = download.etag {
+                request = request.header(reqwest::header::IF_MATCH, etag);
+            }
+        }
+        let mut response = request.send()
+            .await
+            .map_err(|e| RequestError::send("下载文件失败", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && download.written > 0 {
+            // 中断时恰好已写完全部数据
+            return Ok(());
+        }
+        let restart = status == reqwest::StatusCode::PRECONDITION_FAILED
+            || (status == reqwest::StatusCode::OK && download.written > 0);
+        if restart {
+            // 对象已被修改，或服务端忽略了 Range：丢弃已下载的部分
+            file.set_len(0).await.map_err(|e| RequestError::Fatal(VideoParseError::io("写入下载文件失败", e)))?;
+            file.seek(std::io::SeekFrom::Start(0)).await.map_err(|e| RequestError::Fatal(VideoParseError::io("写入下载文件失败", e)))?;
+            *download = PartialDownload::default();
+            if status == reqwest::StatusCode::PRECONDITION_FAILED {
+                return Err(RequestError::Transient(VideoParseError::storage_status("下载期间对象已被修改，重新下载")));
+            }
+        }
+        if !status.is_success() {
+            return Err(RequestError::status(format!("下载文件失败: bucket={}, key={}", bucket, object_key), status));
+        }
+        if download.etag.is_none() {
+            download.etag = response.headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+        }
+
+        let expected = response.content_length().map(|len| download.written + len);
+        while let Some(chunk) = response.chunk().await.map_err(|e| RequestError::send("读取下载数据失败", e))? {
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| RequestError::Fatal(VideoParseError::io("写入下载文件失败", e)))?;
+            download.written += chunk.len() as u64;
+        }
+        match expected {
+            Some(expected) if download.written < expected => Err(RequestError::Transient(VideoParseError::storage_status(
+                format!("下载数据不完整: {}/{} 字节", download.written, expected)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     /// 上传文件到 OSS
+    ///
+    /// 文件达到分片上传阈值时使用 [`upload_multipart`](Self::upload_multipart)
     /// 
     /// # 参数
     /// - `bucket`: OSS bucket 名称
     /// - `object_key`: OSS 对象键（文件路径）
     /// - `file_path`: 本地文件路径
     /// - `endpoint`: OSS endpoint（推荐使用 internal endpoint）
+    #[tracing::instrument(skip_all, fields(bucket = %bucket, key = %object_key))]
     pub async fn upload_file(
         &self,
         bucket: &str,
@@ -155,7 +477,7 @@ impl OssClient {
         
         // 检查文件是否存在
         if !file_path.exists() {
-            anyhow::bail!("文件不存在: {}", file_path.display());
+            return Err(VideoParseError::InvalidInput(format!("文件不存在: {}", file_path.display())));
         }
 
         // 构建 endpoint（优先使用 internal endpoint）
@@ -164,6 +486,13 @@ impl OssClient {
                 .unwrap_or_else(|_| "oss-cn-hangzhou.aliyuncs.com".to_string())
         });
 
+        let file_size = fs::metadata(file_path)
+            .map_err(|e| VideoParseError::io("读取文件信息失败", e))?
+            .len();
+        if file_size >= self.multipart.threshold_mb * 1024 * 1024 {
+            return self.upload_multipart(bucket, object_key, file_path, Some(&ep)).await;
+        }
+
         tracing::info!("正在上传文件到 OSS: {} -> bucket={}, key={}, endpoint={}", 
             file_path.display(), bucket, object_key, ep);
 
@@ -181,13 +510,244 @@ impl OssClient {
         client
             .put_object_from_file(bucket, object_key, file_path, Some(options))
             .await
-            .context("上传文件失败")?;
+            .map_err(|e| VideoParseError::storage("上传文件失败", e).into_upload())?;
 
         tracing::info!("文件已上传到 OSS: bucket={}, key={}", bucket, object_key);
 
         Ok(())
     }
 
+    /// 分片上传文件到 OSS
+    ///
+    /// 分片按文件偏移读取，由多个并发请求上传，单个分片失败时按指数退避重试。
+    /// 上传中断时保留上传记录，再次上传同一文件时通过 ListParts 查询已上传的分片并跳过
+    #[tracing::instrument(skip_all, fields(bucket = %bucket, key = %object_key))]
+    pub async fn upload_multipart(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        file_path: impl AsRef<Path>,
+        endpoint: Option<&str>,
+    ) -> Result<()> {
+        let file_path = file_path.as_ref();
+        let ep = Self::resolve_endpoint(endpoint);
+        let file_size = fs::metadata(file_path)
+            .map_err(|e| VideoParseError::io("读取文件信息失败", e))?
+            .len();
+        let part_size = self.multipart.part_size(file_size);
+        let part_count = file_size.div_ceil(part_size).max(1);
+
+        // 续传：沿用上次的上传，跳过已上传且大小一致的分片
+        let mut completed = BTreeMap::new();
+        let resumed = match UploadState::load(file_path, bucket, object_key, file_size, part_size) {
+            Some(state) => match self.list_parts(bucket, object_key, &ep, &state.upload_id).await {
+                Ok(parts) => {
+                    for (number, etag, size) in parts {
+                        if number <= part_count && size == part_len(file_size, part_size, number) {
+                            completed.insert(number, etag);
+                        }
+                    }
+                    Some(state)
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "⚠️  [OSS] 查询已上传分片失败，重新开始分片上传");
+                    None
+                }
+            },
+            None => None,
+        };
+        let state = match resumed {
+            Some(state) => state,
+            None => {
+                let upload_id = self.initiate_multipart(bucket, object_key, &ep).await.map_err(VideoParseError::into_upload)?;
+                let state = UploadState {
+                    bucket: bucket.to_string(),
+                    object_key: object_key.to_string(),
+                    upload_id,
+                    file_size,
+                    part_size,
+                };
+                state.save(file_path);
+                state
+            }
+        };
+
+        tracing::info!(
+            "正在分片上传文件到 OSS: {} -> bucket={}, key={}, endpoint={}, size={}, parts={}, resumed_parts={}",
+            file_path.display(), bucket, object_key, ep, file_size, part_count, completed.len()
+        );
+
+        let pending: VecDeque<u64> = (1..=part_count).filter(|n| !completed.contains_key(n)).collect();
+        let queue = Arc::new(Mutex::new(pending));
+        let mut workers = Vec::new();
+        for _ in 0..self.multipart.concurrency.max(1) {
+            let client = self.clone();
+            let queue = Arc::clone(&queue);
+            let (bucket, object_key, ep, upload_id) =
+                (bucket.to_string(), object_key.to_string(), ep.clone(), state.upload_id.clone());
+            let file_path = file_path.to_path_buf();
+            workers.push(tokio::spawn(async move {
+                let mut etags = Vec::new();
+                loop {
+                    let Some(number) = queue.lock().unwrap().pop_front() else { break };
+                    let offset = (number - 1) * part_size;
+                    let data = read_part(&file_path, offset, part_len(file_size, part_size, number)).await?;
+                    let etag = client.upload_part_with_retry(&bucket, &object_key, &ep, &upload_id, number, data).await?;
+                    etags.push((number, etag));
+                }
+                Ok::<_, VideoParseError>(etags)
+            }));
+        }
+        let mut first_error = None;
+        for worker in workers {
+            match worker.await {
+                Ok(Ok(etags)) => completed.extend(etags),
+                Ok(Err(e)) => {
+                    // 其他分片继续上传完，下次续传时跳过
+                    first_error.get_or_insert(e);
+                }
+                Err(e) => {
+                    first_error.get_or_insert(VideoParseError::Other(anyhow::anyhow!("分片上传任务异常终止: {}", e)));
+                }
+            }
+        }
+        if let Some(e) = first_error {
+            tracing::warn!(uploaded = completed.len(), parts = part_count, "⚠️  [OSS] 分片上传未完成，保留上传记录供续传");
+            return Err(e.into_upload());
+        }
+
+        self.complete_multipart(bucket, object_key, &ep, &state.upload_id, &completed)
+            .await
+            .map_err(VideoParseError::into_upload)?;
+        UploadState::remove(file_path);
+        tracing::info!("文件已分片上传到 OSS: bucket={}, key={}", bucket, object_key);
+        Ok(())
+    }
+
+    /// 并发上传多个文件，最多同时上传 `concurrency` 个
+    ///
+    /// 单个文件失败不影响其他文件，错误汇总在返回的 [`UploadReport`] 中；
+    /// 每个文件完成（成功或失败）后按完成顺序调用 `on_done`
+    pub async fn upload_files(
+        &self,
+        bucket: &str,
+        tasks: &[UploadTask],
+        endpoint: Option<&str>,
+        concurrency: usize,
+        on_done: impl FnMut(&UploadTask, &Result<()>),
+    ) -> UploadReport {
+        upload_concurrently(
+            tasks,
+            concurrency,
+            |task| self.upload_file(bucket, &task.object_key, &task.path, endpoint),
+            on_done,
+        )
+        .await
+    }
+
+    /// 初始化分片上传，返回 UploadId
+    async fn initiate_multipart(&self, bucket: &str, object_key: &str, endpoint: &str) -> Result<String> {
+        let content_type = self.guess_content_type(object_key);
+        let response = self.signed_request_with(
+            reqwest::Method::POST, bucket, object_key, endpoint, &[], &[("uploads", None)], Some(content_type), None,
+        )?
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .send()
+            .await
+            .map_err(|e| VideoParseError::storage("初始化分片上传失败", e))?;
+        let body = response_text(response, "初始化分片上传失败").await?;
+        let upload_id = xml_values(&body, "UploadId").next().map(str::to_string);
+        upload_id.ok_or_else(|| VideoParseError::storage_status("初始化分片上传失败: 响应中缺少 UploadId"))
+    }
+
+    /// 查询已上传的分片，返回 (分片号, ETag, 大小)
+    async fn list_parts(&self, bucket: &str, object_key: &str, endpoint: &str, upload_id: &str) -> Result<Vec<(u64, String, u64)>> {
+        let mut parts = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let mut query = vec![("uploadId", Some(upload_id.to_string()))];
+            if let Some(ref marker) = marker {
+                query.push(("part-number-marker", Some(marker.clone())));
+            }
+            let response = self.signed_request_with(reqwest::Method::GET, bucket, object_key, endpoint, &[], &query, None, None)?
+                .send()
+                .await
+                .map_err(|e| VideoParseError::storage("查询已上传分片失败", e))?;
+            let body = response_text(response, "查询已上传分片失败").await?;
+            parts.extend(parse_parts(&body));
+            let truncated = xml_values(&body, "IsTruncated").next() == Some("true");
+            marker = xml_values(&body, "NextPartNumberMarker").next().map(str::to_string);
+            if !truncated || marker.is_none() {
+                return Ok(parts);
+            }
+        }
+    }
+
+    /// 上传一个分片，失败时按指数退避重试，返回分片的 ETag
+    async fn upload_part_with_retry(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: &str,
+        upload_id: &str,
+        number: u64,
+        data: Vec<u8>,
+    ) -> Result<String> {
+        let mut attempt = 0;
+        loop {
+            let query = [("partNumber", Some(number.to_string())), ("uploadId", Some(upload_id.to_string()))];
+            let result = async {
+                let response = self.signed_request_with(reqwest::Method::PUT, bucket, object_key, endpoint, &[], &query, None, None)?
+                    .body(data.clone())
+                    .send()
+                    .await
+                    .map_err(|e| VideoParseError::storage(format!("上传分片失败: part={}", number), e))?;
+                if !response.status().is_success() {
+                    return Err(VideoParseError::storage_status(format!(
+                        "上传分片失败: part={}, status={}", number, response.status()
+                    )));
+                }
+                response.headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+                    .ok_or_else(|| VideoParseError::storage_status(format!("上传分片失败: part={}, 响应中缺少 ETag", number)))
+            }.await;
+            match result {
+                Ok(etag) => return Ok(etag),
+                Err(e) if attempt < self.multipart.max_retries => {
+                    let backoff = retry_backoff(attempt);
+                    attempt += 1;
+                    tracing::warn!(part = number, attempt, error = %e, "⚠️  [OSS] 分片上传失败，{:?} 后重试", backoff);
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 按分片号顺序合并分片
+    async fn complete_multipart(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: &str,
+        upload_id: &str,
+        parts: &BTreeMap<u64, String>,
+    ) -> Result<()> {
+        let body: String = parts.iter()
+            .map(|(number, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", number, etag))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", body);
+        let query = [("uploadId", Some(upload_id.to_string()))];
+        let response = self.signed_request_with(reqwest::Method::POST, bucket, object_key, endpoint, &[], &query, None, None)?
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| VideoParseError::storage("合并分片失败", e))?;
+        response_text(response, "合并分片失败").await.map(|_| ())
+    }
+
     /// 检查 OSS 对象是否存在并获取元数据
     /// 
     /// # 参数
@@ -230,15 +790,465 @@ impl OssClient {
                 if error_str.contains("404") || error_str.contains("NoSuchKey") || error_str.contains("not found") {
                     Ok(None)
                 } else {
-                    Err(e).context("检查对象失败")
+                    Err(VideoParseError::storage("检查对象失败", e))
+                }
+            }
+        }
+    }
+
+    /// 获取 OSS 对象大小（字节）
+    pub async fn object_size(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+    ) -> Result<u64> {
+        let ep = Self::resolve_endpoint(endpoint);
+        with_retry(self.download_retries, "获取对象信息", || async {
+            let response = self.signed_request(reqwest::Method::HEAD, bucket, object_key, &ep, &[])
+                .map_err(RequestError::Fatal)?
+                .send()
+                .await
+                .map_err(|e| RequestError::send("获取对象信息失败", e))?;
+
+            if !response.status().is_success() {
+                return Err(RequestError::status(
+                    format!("获取对象信息失败: bucket={}, key={}", bucket, object_key), response.status()
+                ));
+            }
+
+            response.headers()
+                .get(reqwest::header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| RequestError::Fatal(VideoParseError::storage_status("响应中缺少 Content-Length")))
+        }).await
+    }
+
+    /// 解析符号链接，返回目标对象的 key
+    ///
+    /// OSS 在 `x-oss-symlink-target` 响应头中返回 URL 编码的目标 key
+    pub async fn get_symlink(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+    ) -> Result<String> {
+        let ep = Self::resolve_endpoint(endpoint);
+        let query = [("symlink", None)];
+        with_retry(self.download_retries, "解析符号链接", || async {
+            let response = self.signed_request_with(reqwest::Method::GET, bucket, object_key, &ep, &[], &query, None, None)
+                .map_err(RequestError::Fatal)?
+                .send()
+                .await
+                .map_err(|e| RequestError::send("解析符号链接失败", e))?;
+
+            if !response.status().is_success() {
+                return Err(RequestError::status(
+                    format!("解析符号链接失败: bucket={}, key={}", bucket, object_key), response.status()
+                ));
+            }
+
+            response.headers()
+                .get("x-oss-symlink-target")
+                .and_then(|v| v.to_str().ok())
+                .and_then(percent_decode)
+                .filter(|target| !target.is_empty())
+                .ok_or_else(|| RequestError::Fatal(VideoParseError::storage_status("响应中缺少 x-oss-symlink-target")))
+        }).await
+    }
+
+    /// 列举 bucket 中以 `prefix` 开头的对象（ListObjectsV2），每次最多返回 `max_keys` 个
+    ///
+    /// 传入上一页的 `next_continuation_token` 继续列举
+    pub async fn list_objects(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        endpoint: Option<&str>,
+        continuation_token: Option<&str>,
+        max_keys: usize,
+    ) -> Result<ObjectListing> {
+        let ep = Self::resolve_endpoint(endpoint);
+        let sub_resources: Vec<(&str, Option<String>)> = continuation_token
+            .map(|token| ("continuation-token", Some(token.to_string())))
+            .into_iter()
+            .collect();
+        let max_keys = max_keys.clamp(1, MAX_BATCH_KEYS).to_string();
+        with_retry(self.download_retries, "列举对象", || async {
+            let response = self.signed_request_with(reqwest::Method::GET, bucket, "", &ep, &[], &sub_resources, None, None)
+                .map_err(RequestError::Fatal)?
+                .query(&[("list-type", "2"), ("encoding-type", "url"), ("prefix", prefix), ("max-keys", max_keys.as_str())])
+                .send()
+                .await
+                .map_err(|e| RequestError::send("列举对象失败", e))?;
+
+            if !response.status().is_success() {
+                return Err(RequestError::status(
+                    format!("列举对象失败: bucket={}, prefix={}", bucket, prefix), response.status()
+                ));
+            }
+            let body = response.text()
+                .await
+                .map_err(|e| RequestError::send("读取列举结果失败", e))?;
+            Ok(parse_listing(&body))
+        }).await
+    }
+
+    /// 列举 bucket 中以 `prefix` 开头的全部对象，自动翻页
+    pub async fn list_all_objects(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        endpoint: Option<&str>,
+    ) -> Result<Vec<ObjectSummary>> {
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let page = self.list_objects(bucket, prefix, endpoint, token.as_deref(), MAX_BATCH_KEYS).await?;
+            objects.extend(page.objects);
+            match page.next_continuation_token {
+                Some(next) => token = Some(next),
+                None => return Ok(objects),
+            }
+        }
+    }
+
+    /// 删除单个对象，对象不存在时同样返回成功
+    pub async fn delete_object(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+    ) -> Result<()> {
+        let ep = Self::resolve_endpoint(endpoint);
+        with_retry(self.download_retries, "删除对象", || async {
+            let response = self.signed_request(reqwest::Method::DELETE, bucket, object_key, &ep, &[])
+                .map_err(RequestError::Fatal)?
+                .send()
+                .await
+                .map_err(|e| RequestError::send("删除对象失败", e))?;
+
+            if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+                return Err(RequestError::status(
+                    format!("删除对象失败: bucket={}, key={}", bucket, object_key), response.status()
+                ));
+            }
+            Ok(())
+        }).await?;
+        tracing::debug!("已删除 OSS 对象: bucket={}, key={}", bucket, object_key);
+        Ok(())
+    }
+
+    /// 批量删除对象（DeleteMultipleObjects），每批最多 1000 个，返回已删除的对象数
+    ///
+    /// 部分对象删除失败时返回错误，错误信息中包含失败的 key
+    pub async fn delete_objects(
+        &self,
+        bucket: &str,
+        object_keys: &[String],
+        endpoint: Option<&str>,
+    ) -> Result<usize> {
+        let ep = Self::resolve_endpoint(endpoint);
+        let query = [("delete", None)];
+        let mut deleted = 0;
+        for batch in object_keys.chunks(MAX_BATCH_KEYS) {
+            let body = delete_request_body(batch);
+            let content_md5 = base64::engine::general_purpose::STANDARD.encode(Md5::digest(body.as_bytes()));
+            let body = with_retry(self.download_retries, "批量删除对象", || async {
+                let response = self.signed_request_with(
+                    reqwest::Method::POST, bucket, "", &ep, &[], &query, Some("application/xml"), Some(&content_md5),
+                )
+                    .map_err(RequestError::Fatal)?
+                    .header(reqwest::header::CONTENT_TYPE, "application/xml")
+                    .header("Content-MD5", content_md5.as_str())
+                    .body(body.clone())
+                    .send()
+                    .await
+                    .map_err(|e| RequestError::send("批量删除对象失败", e))?;
+
+                if !response.status().is_success() {
+                    return Err(RequestError::status(format!("批量删除对象失败: bucket={}", bucket), response.status()));
                 }
+                response.text()
+                    .await
+                    .map_err(|e| RequestError::send("读取批量删除结果失败", e))
+            }).await?;
+
+            let (ok, failed) = parse_delete_result(&body);
+            deleted += ok;
+            if !failed.is_empty() {
+                return Err(VideoParseError::storage_status(format!(
+                    "批量删除对象失败: bucket={}, 失败 {} 个: {}", bucket, failed.len(), failed.join(", ")
+                )));
             }
         }
+        tracing::info!("已批量删除 OSS 对象: bucket={}, count={}", bucket, deleted);
+        Ok(deleted)
+    }
+
+    /// 按字节范围下载 OSS 对象的一部分
+    ///
+    /// `start` 和 `end` 都包含在内（与 HTTP Range 一致），临时失败时按指数退避重试
+    pub async fn get_object_range(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<u8>> {
+        let ep = Self::resolve_endpoint(endpoint);
+        with_retry(self.download_retries, "下载分段", || async {
+            let response = self.signed_request(reqwest::Method::GET, bucket, object_key, &ep, &[])
+                .map_err(RequestError::Fatal)?
+                .header(reqwest::header::RANGE, format!("bytes={}-{}", start, end))
+                .send()
+                .await
+                .map_err(|e| RequestError::send(format!("下载分段失败: bytes={}-{}", start, end), e))?;
+
+            if !response.status().is_success() {
+                return Err(RequestError::status(
+                    format!("下载分段失败: bucket={}, key={}, bytes={}-{}", bucket, object_key, start, end),
+                    response.status(),
+                ));
+            }
+
+            let bytes = response.bytes()
+                .await
+                .map_err(|e| RequestError::send(format!("读取分段数据失败: bytes={}-{}", start, end), e))?;
+            if bytes.len() as u64 != end - start + 1 {
+                return Err(RequestError::Transient(VideoParseError::storage_status(format!(
+                    "分段数据不完整: bytes={}-{}, 收到 {} 字节", start, end, bytes.len()
+                ))));
+            }
+            Ok(bytes.to_vec())
+        }).await.map_err(VideoParseError::into_download)
+    }
+
+    /// 分段下载 OSS 对象，返回可立即读取的 `ProgressiveFile` 和后台下载任务
+    ///
+    /// 下载在后台由 `concurrency` 个并发请求完成，读取方通过 `ProgressiveFile`
+    /// 等待所需的分段，不必等整个文件下载完成
+    pub async fn download_progressive(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+        output_path: impl AsRef<Path>,
+        segment_size: u64,
+        concurrency: usize,
+    ) -> Result<(Arc<ProgressiveFile>, tokio::task::JoinHandle<Result<()>>)> {
+        let ep = Self::resolve_endpoint(endpoint);
+        let total_size = self.object_size(bucket, object_key, Some(&ep)).await.map_err(VideoParseError::into_download)?;
+        let file = ProgressiveFile::create(output_path, total_size, segment_size)?;
+
+        tracing::info!(
+            "正在分段下载 OSS 文件: bucket={}, key={}, endpoint={}, size={}, segments={}",
+            bucket, object_key, ep, total_size, file.segment_count()
+        );
+
+        let mut workers = Vec::new();
+        for _ in 0..concurrency.max(1) {
+            let client = self.clone();
+            let file = Arc::clone(&file);
+            let (bucket, object_key, ep) = (bucket.to_string(), object_key.to_string(), ep.clone());
+            workers.push(tokio::spawn(async move {
+                while let Some(index) = file.claim_next_segment() {
+                    let (start, end) = file.segment_range(index);
+                    let result = match client.get_object_range(&bucket, &object_key, Some(&ep), start, end).await {
+                        Ok(data) => file.write_segment(index, &data).map_err(VideoParseError::from),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        file.fail(e.to_string());
+                        return Err(e);
+                    }
+                }
+                Ok::<(), VideoParseError>(())
+            }));
+        }
+
+        let task_file = Arc::clone(&file);
+        let task = tokio::spawn(async move {
+            for worker in workers {
+                match worker.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => return Err(e),
+                    Err(e) => {
+                        task_file.fail(format!("下载任务异常终止: {}", e));
+                        return Err(VideoParseError::Other(anyhow::anyhow!("下载任务异常终止: {}", e)));
+                    }
+                }
+            }
+            tracing::info!("文件已下载到: {}", task_file.path().display());
+            Ok(())
+        });
+
+        Ok((file, task))
+    }
+
+    /// 构建带签名的 OSS REST 请求（SDK 未覆盖的操作使用）
+    ///
+    /// 使用 OSS V1 签名，存在 STS 临时凭证时附带 x-oss-security-token
+    fn signed_request(
+        &self,
+        method: reqwest::Method,
+        bucket: &str,
+        object_key: &str,
+        endpoint: &str,
+        oss_headers: &[(&str, String)],
+    ) -> Result<reqwest::RequestBuilder> {
+        self.signed_request_with(method, bucket, object_key, endpoint, oss_headers, &[], None, None)
+    }
+
+    /// 带子资源（如 `uploadId`、`partNumber`）、Content-Type 和 Content-MD5 的签名请求
+    ///
+    /// 子资源同时作为查询参数和签名中的 CanonicalizedResource，设置了 `content_type` / `content_md5` 时调用方须发送相同的请求头
+    #[allow(clippy::too_many_arguments)]
+    fn signed_request_with(
+        &self,
+        method: reqwest::Method,
+        bucket: &str,
+        object_key: &str,
+        endpoint: &str,
+        oss_headers: &[(&str, String)],
+        sub_resources: &[(&str, Option<String>)],
+        content_type: Option<&str>,
+        content_md5: Option<&str>,
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut url = Self::object_url(bucket, object_key, endpoint)?;
+        for (key, value) in sub_resources {
+            match value {
+                Some(value) => url.query_pairs_mut().append_pair(key, value),
+                None => url.query_pairs_mut().append_key_only(key),
+            };
+        }
+
+        let mut headers: Vec<(String, String)> = oss_headers.iter()
+            .map(|(k, v)| (k.to_lowercase(), v.clone()))
+            .collect();
+        if let Some(ref token) = self.security_token {
+            headers.push(("x-oss-security-token".to_string(), token.clone()));
+        }
+        headers.sort();
+
+        let date = chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        let canonical_headers: String = headers.iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v))
+            .collect();
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}\n{}/{}/{}{}",
+            method.as_str(), content_md5.unwrap_or(""), content_type.unwrap_or(""), date, canonical_headers, bucket, object_key,
+            canonical_sub_resources(sub_resources)
+        );
+
+        let signature = self.sign(&string_to_sign)?;
+
+        let mut request = reqwest::Client::new()
+            .request(method, url)
+            .header(reqwest::header::DATE, date)
+            .header(reqwest::header::AUTHORIZATION, format!("OSS {}:{}", self.access_key_id, signature));
+        for (k, v) in headers {
+            request = request.header(k, v);
+        }
+        Ok(request)
+    }
+
+    /// 生成对象的预签名下载 URL，无需 OSS 凭证即可在 `expires_in` 内直接访问
+    pub fn presign_get(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+        expires_in: Duration,
+    ) -> Result<String> {
+        let endpoint = Self::resolve_endpoint(endpoint);
+        let expires = chrono::Utc::now().timestamp() + expires_in.as_secs() as i64;
+        self.presign_at(reqwest::Method::GET, bucket, object_key, &endpoint, expires, None)
+    }
+
+    /// 生成对象的预签名上传 URL，客户端在 `expires_in` 内可直接 PUT 上传
+    ///
+    /// 设置了 `content_type` 时，上传请求必须携带相同的 Content-Type
+    pub fn presign_put(
+        &self,
+        bucket: &str,
+        object_key: &str,
+        endpoint: Option<&str>,
+        expires_in: Duration,
+        content_type: Option<&str>,
+    ) -> Result<String> {
+        let endpoint = Self::resolve_endpoint(endpoint);
+        let expires = chrono::Utc::now().timestamp() + expires_in.as_secs() as i64;
+        self.presign_at(reqwest::Method::PUT, bucket, object_key, &endpoint, expires, content_type)
+    }
+
+    /// 以 Unix 时间戳 `expires` 为过期时间生成 OSS V1 签名 URL
+    ///
+    /// 存在 STS 临时凭证时 `security-token` 同时作为查询参数和签名子资源
+    fn presign_at(
+        &self,
+        method: reqwest::Method,
+        bucket: &str,
+        object_key: &str,
+        endpoint: &str,
+        expires: i64,
+        content_type: Option<&str>,
+    ) -> Result<String> {
+        let sub_resources: Vec<(&str, Option<String>)> = self.security_token.iter()
+            .map(|token| ("security-token", Some(token.clone())))
+            .collect();
+        let string_to_sign = format!(
+            "{}\n\n{}\n{}\n/{}/{}{}",
+            method.as_str(), content_type.unwrap_or(""), expires, bucket, object_key,
+            canonical_sub_resources(&sub_resources)
+        );
+        let signature = self.sign(&string_to_sign)?;
+
+        let mut url = Self::object_url(bucket, object_key, endpoint)?;
+        url.query_pairs_mut()
+            .append_pair("OSSAccessKeyId", &self.access_key_id)
+            .append_pair("Expires", &expires.to_string())
+            .append_pair("Signature", &signature);
+        if let Some(ref token) = self.security_token {
+            url.query_pairs_mut().append_pair("security-token", token);
+        }
+        Ok(url.to_string())
+    }
+
+    /// 对象的访问地址：`https://{bucket}.{endpoint}/{object_key}`，`object_key` 为空时为 bucket 地址
+    fn object_url(bucket: &str, object_key: &str, endpoint: &str) -> Result<reqwest::Url> {
+        let endpoint = endpoint.trim_start_matches("http://").trim_start_matches("https://");
+        let mut url = reqwest::Url::parse(&format!("https://{}.{}", bucket, endpoint))
+            .map_err(|_| VideoParseError::Config(format!("无效的 OSS endpoint: {}", endpoint)))?;
+        if !object_key.is_empty() {
+            url.path_segments_mut()
+                .map_err(|_| VideoParseError::Config(format!("无效的 OSS endpoint: {}", endpoint)))?
+                .extend(object_key.split('/'));
+        }
+        Ok(url)
+    }
+
+    /// HMAC-SHA1 签名并 Base64 编码
+    fn sign(&self, string_to_sign: &str) -> Result<String> {
+        let mut mac = Hmac::<Sha1>::new_from_slice(self.access_key_secret.as_bytes())
+            .map_err(|_| VideoParseError::Config("初始化签名失败".to_string()))?;
+        mac.update(string_to_sign.as_bytes());
+        Ok(base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+    }
+
+    /// 未指定 endpoint 时使用环境变量 OSS_ENDPOINT 或默认值
+    fn resolve_endpoint(endpoint: Option<&str>) -> String {
+        endpoint.map(|s| s.to_string()).unwrap_or_else(|| {
+            std::env::var("OSS_ENDPOINT")
+                .unwrap_or_else(|_| "oss-cn-hangzhou.aliyuncs.com".to_string())
+        })
     }
 
     /// 根据文件扩展名猜测 Content-Type
     fn guess_content_type(&self, object_key: &str) -> &'static str {
-        let ext = std::path::Path::new(object_key)
+        let ext = std::path::Path::new(crate::output_naming::key_file_name(object_key))
             .extension()
             .and_then(|e| e.to_str())
             .map(|s| s.to_lowercase())
@@ -248,9 +1258,14 @@ impl OssClient {
             "jpg" | "jpeg" => "image/jpeg",
             "png" => "image/png",
             "gif" => "image/gif",
+            "webp" => "image/webp",
+            "avif" => "image/avif",
             "mp4" => "video/mp4",
             "mp3" => "audio/mpeg",
             "aac" => "audio/aac",
+            "opus" => "audio/opus",
+            "wav" => "audio/wav",
+            "flac" => "audio/flac",
             "json" => "application/json",
             "txt" => "text/plain",
             "html" => "text/html",
@@ -262,3 +1277,330 @@ impl OssClient {
         }
     }
 }
+
+/// 续传中的下载：已写入的字节数和首次响应的 ETag
+#[derive(Debug, Default)]
+struct PartialDownload {
+    written: u64,
+    etag: Option<String>,
+}
+
+/// OSS 请求失败：临时错误（超时、连接中断、5xx、429）可以重试，其余错误立即返回
+enum RequestError {
+    Transient(VideoParseError),
+    Fatal(VideoParseError),
+}
+
+impl RequestError {
+    fn send(message: impl Into<String>, e: reqwest::Error) -> Self {
+        let transient = e.is_timeout() || e.is_connect() || e.is_request() || e.is_body();
+        let error = VideoParseError::storage(message, e);
+        if transient { Self::Transient(error) } else { Self::Fatal(error) }
+    }
+
+    fn status(message: String, status: reqwest::StatusCode) -> Self {
+        let error = VideoParseError::storage_status(format!("{}, status={}", message, status));
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            Self::Transient(error)
+        } else {
+            Self::Fatal(error)
+        }
+    }
+
+    fn into_inner(self) -> VideoParseError {
+        match self {
+            Self::Transient(e) | Self::Fatal(e) => e,
+        }
+    }
+}
+
+/// 第 `attempt` 次重试前的等待时间：1、2、4……秒，最长 30 秒
+fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_secs((1u64 << attempt.min(5)).min(30))
+}
+
+/// 执行请求，临时失败时按指数退避最多重试 `max_retries` 次
+async fn with_retry<T, F, Fut>(max_retries: u32, what: &str, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, RequestError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(RequestError::Transient(e)) if attempt < max_retries => {
+                attempt += 1;
+                let backoff = retry_backoff(attempt - 1);
+                tracing::warn!(attempt, error = %e, "⚠️  [OSS] {}失败，{:?} 后重试", what, backoff);
+                tokio::time::sleep(backoff).await;
+            }
+            Err(e) => return Err(e.into_inner()),
+        }
+    }
+}
+
+/// 签名中的子资源部分：按名称排序，如 `?partNumber=1&uploadId=xxx`
+fn canonical_sub_resources(sub_resources: &[(&str, Option<String>)]) -> String {
+    let mut sorted: Vec<_> = sub_resources.iter().collect();
+    sorted.sort_by_key(|(key, _)| *key);
+    let joined: Vec<String> = sorted.iter()
+        .map(|(key, value)| match value {
+            Some(value) => format!("{}={}", key, value),
+            None => key.to_string(),
+        })
+        .collect();
+    if joined.is_empty() { String::new() } else { format!("?{}", joined.join("&")) }
+}
+
+/// 解码 URL 编码的字符串（`%XX`），编码无效时返回 None
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// 分片号（从 1 开始）对应的分片大小
+fn part_len(file_size: u64, part_size: u64, number: u64) -> u64 {
+    file_size.saturating_sub((number - 1) * part_size).min(part_size)
+}
+
+/// 读取文件中的一个分片
+async fn read_part(path: &Path, offset: u64, len: u64) -> Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| VideoParseError::io("打开上传文件失败", e))?;
+    file.seek(std::io::SeekFrom::Start(offset))
+        .await
+        .map_err(|e| VideoParseError::io("读取上传文件失败", e))?;
+    let mut data = vec![0; len as usize];
+    file.read_exact(&mut data)
+        .await
+        .map_err(|e| VideoParseError::io("读取上传文件失败", e))?;
+    Ok(data)
+}
+
+/// 读取响应内容，状态码不是 2xx 时返回错误
+async fn response_text(response: reqwest::Response, message: &str) -> Result<String> {
+    let status = response.status();
+    let body = response.text()
+        .await
+        .map_err(|e| VideoParseError::storage(message, e))?;
+    if !status.is_success() {
+        let code = xml_values(&body, "Code").next().unwrap_or_default();
+        return Err(VideoParseError::storage_status(format!("{}: status={}, code={}", message, status, code)));
+    }
+    Ok(body)
+}
+
+/// 依次取出 XML 中 `<tag>` 元素的文本（OSS 响应结构简单，不需要完整的 XML 解析）
+fn xml_values<'a>(xml: &'a str, tag: &str) -> impl Iterator<Item = &'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut rest = xml;
+    std::iter::from_fn(move || {
+        let start = rest.find(&open)? + open.len();
+        let end = start + rest[start..].find(&close)?;
+        let value = &rest[start..end];
+        rest = &rest[end + close.len()..];
+        Some(value)
+    })
+}
+
+/// 解析 ListParts 响应中的 (分片号, ETag, 大小)
+fn parse_parts(xml: &str) -> Vec<(u64, String, u64)> {
+    xml_values(xml, "Part")
+        .filter_map(|part| {
+            let number = xml_values(part, "PartNumber").next()?.parse().ok()?;
+            let etag = xml_values(part, "ETag").next()?.replace("&quot;", "\"");
+            let size = xml_values(part, "Size").next()?.parse().ok()?;
+            Some((number, etag, size))
+        })
+        .collect()
+}
+
+/// 解析 ListObjectsV2 响应（encoding-type=url，key 为 URL 编码）
+fn parse_listing(xml: &str) -> ObjectListing {
+    let objects = xml_values(xml, "Contents")
+        .filter_map(|contents| {
+            let key = percent_decode(xml_values(contents, "Key").next()?)?;
+            let size = xml_values(contents, "Size").next()?.parse().ok()?;
+            let etag = xml_values(contents, "ETag").next().unwrap_or_default().replace("&quot;", "\"");
+            let last_modified = xml_values(contents, "LastModified").next().unwrap_or_default().to_string();
+            Some(ObjectSummary { key, size, etag, last_modified })
+        })
+        .collect();
+    let truncated = xml_values(xml, "IsTruncated").next() == Some("true");
+    let next_continuation_token = xml_values(xml, "NextContinuationToken")
+        .next()
+        .filter(|_| truncated)
+        .and_then(percent_decode)
+        .filter(|token| !token.is_empty());
+    ObjectListing { objects, next_continuation_token }
+}
+
+/// 批量删除的请求体（非 quiet 模式，响应中返回每个已删除的 key）
+fn delete_request_body(object_keys: &[String]) -> String {
+    let objects: String = object_keys.iter()
+        .map(|key| format!("<Object><Key>{}</Key></Object>", xml_escape(key)))
+        .collect();
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><Delete><Quiet>false</Quiet>{}</Delete>", objects)
+}
+
+/// 解析批量删除响应，返回 (已删除数, 删除失败的 key)
+fn parse_delete_result(xml: &str) -> (usize, Vec<String>) {
+    let deleted = xml_values(xml, "Deleted").count();
+    let failed = xml_values(xml, "Error")
+        .filter_map(|error| xml_values(error, "Key").next())
+        .map(str::to_string)
+        .collect();
+    (deleted, failed)
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_plan() {
+        let config = MultipartConfig { part_size_mb: 1, ..MultipartConfig::default() };
+        let mb = 1024 * 1024;
+        assert_eq!(config.part_size(10 * mb), mb);
+        // 分片数不超过 10000
+        assert_eq!(config.part_size(20_000 * mb), 2 * mb);
+        assert_eq!(part_len(5 * mb / 2, mb, 3), mb / 2);
+        assert_eq!(canonical_sub_resources(&[("uploadId", Some("u1".to_string())), ("partNumber", Some("2".to_string()))]), "?partNumber=2&uploadId=u1");
+        assert_eq!(canonical_sub_resources(&[("uploads", None)]), "?uploads");
+
+        let xml = r#"<ListPartsResult><UploadId>u1</UploadId><IsTruncated>false</IsTruncated>
+            <Part><PartNumber>1</PartNumber><ETag>&quot;A1&quot;</ETag><Size>1048576</Size></Part>
+            <Part><PartNumber>3</PartNumber><ETag>"C3"</ETag><Size>524288</Size></Part></ListPartsResult>"#;
+        assert_eq!(xml_values(xml, "UploadId").next(), Some("u1"));
+        assert_eq!(parse_parts(xml), vec![(1, "\"A1\"".to_string(), mb), (3, "\"C3\"".to_string(), mb / 2)]);
+    }
+
+    #[test]
+    fn test_retry_classification() {
+        let transient = |status| matches!(RequestError::status(String::new(), status), RequestError::Transient(_));
+        assert!(transient(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(transient(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!transient(reqwest::StatusCode::FORBIDDEN));
+        assert!(!transient(reqwest::StatusCode::NOT_FOUND));
+        assert_eq!(retry_backoff(0), Duration::from_secs(1));
+        assert_eq!(retry_backoff(3), Duration::from_secs(8));
+        assert_eq!(retry_backoff(10), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_presign() {
+        let client = OssClient {
+            access_key_id: "44CF9590006BF252F707".to_string(),
+            access_key_secret: "OtxrzxIsfpFnA6Gu7IFdmb5yFOMP37ENeMAEPW".to_string(),
+            security_token: None,
+            multipart: MultipartConfig::default(),
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+        };
+        // OSS 文档中的 URL 签名示例
+        let url = client
+            .presign_at(reqwest::Method::GET, "oss-example", "oss-api.pdf", "oss-cn-hangzhou.aliyuncs.com", 1141889120, None)
+            .unwrap();
+        assert_eq!(
+            url,
+            "https://oss-example.oss-cn-hangzhou.aliyuncs.com/oss-api.pdf?OSSAccessKeyId=44CF9590006BF252F707&Expires=1141889120&Signature=WRB%2F13Mwu86gmjvnIjhCgCNgikU%3D"
+        );
+
+        let sts = OssClient { security_token: Some("token".to_string()), ..client };
+        let url = sts
+            .presign_at(reqwest::Method::PUT, "oss-example", "a b/c.jpg", "https://oss-cn-hangzhou.aliyuncs.com", 1141889120, Some("image/jpeg"))
+            .unwrap();
+        assert!(url.starts_with("https://oss-example.oss-cn-hangzhou.aliyuncs.com/a%20b/c.jpg?OSSAccessKeyId="));
+        assert!(url.ends_with("&security-token=token"));
+    }
+
+    #[test]
+    fn test_list_and_delete_xml() {
+        let xml = r#"<ListBucketResult><Name>b</Name><Prefix>videos/</Prefix><IsTruncated>true</IsTruncated>
+            <Contents><Key>videos/%E8%A7%86%E9%A2%91.mp4</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><ETag>&quot;E1&quot;</ETag><Size>1024</Size></Contents>
+            <Contents><Key>videos/b.mp4</Key><LastModified>2024-01-02T00:00:00.000Z</LastModified><ETag>"E2"</ETag><Size>2048</Size></Contents>
+            <NextContinuationToken>CgJiYw--</NextContinuationToken></ListBucketResult>"#;
+        let listing = parse_listing(xml);
+        assert_eq!(listing.objects.len(), 2);
+        assert_eq!(listing.objects[0].key, "videos/视频.mp4");
+        assert_eq!(listing.objects[0].etag, "\"E1\"");
+        assert_eq!(listing.objects[1].size, 2048);
+        assert_eq!(listing.next_continuation_token.as_deref(), Some("CgJiYw--"));
+        let last = parse_listing("<ListBucketResult><IsTruncated>false</IsTruncated></ListBucketResult>");
+        assert!(last.objects.is_empty() && last.next_continuation_token.is_none());
+
+        let body = delete_request_body(&["a&b.jpg".to_string(), "c.jpg".to_string()]);
+        assert!(body.contains("<Object><Key>a&amp;b.jpg</Key></Object><Object><Key>c.jpg</Key></Object>"));
+        let result = "<DeleteResult><Deleted><Key>c.jpg</Key></Deleted><Error><Key>a.jpg</Key><Code>AccessDenied</Code></Error></DeleteResult>";
+        assert_eq!(parse_delete_result(result), (1, vec!["a.jpg".to_string()]));
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("videos/a.mp4").as_deref(), Some("videos/a.mp4"));
+        assert_eq!(percent_decode("videos/%E8%A7%86%E9%A2%91%20a.mp4").as_deref(), Some("videos/视频 a.mp4"));
+        assert_eq!(percent_decode("videos/%2"), None);
+        assert_eq!(percent_decode("videos/%zz.mp4"), None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_concurrently() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let tasks: Vec<UploadTask> = (0..10)
+            .map(|i| UploadTask::new("关键帧", format!("keyframe_{}.jpg", i), format!("/nonexistent/keyframe_{}.jpg", i), format!("out/keyframes/keyframe_{}.jpg", i)))
+            .collect();
+        let (active, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let mut done = Vec::new();
+        let report = upload_concurrently(
+            &tasks,
+            3,
+            |task| {
+                let (active, peak) = (&active, &peak);
+                async move {
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    if task.name == "keyframe_4.jpg" {
+                        return Err(VideoParseError::storage_status("HTTP 503"));
+                    }
+                    Ok(())
+                }
+            },
+            |task, result| done.push((task.name.clone(), result.is_ok())),
+        )
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(done.len(), 10);
+        assert_eq!(report.uploaded.len(), 9);
+        assert_eq!(report.uploaded["keyframe_0.jpg"], "out/keyframes/keyframe_0.jpg");
+        assert_eq!(report.errors, vec!["上传关键帧失败 out/keyframes/keyframe_4.jpg: HTTP 503".to_string()]);
+        let err = report.into_result().unwrap_err();
+        assert_eq!(err.kind(), "upload_failed");
+        assert!(err.to_string().starts_with("1 个文件上传失败: "));
+    }
+}
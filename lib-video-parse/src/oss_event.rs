@@ -1,5 +1,10 @@
+use std::collections::BTreeMap;
+use std::fmt;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde::de::{self, Visitor};
+use crate::resource::{ResourceUsage, TimingBreakdown};
+use crate::webhook::WebhookDelivery;
+use crate::preflight::Rejection;
 
 /// 默认空字符串（用于 serde default）
 fn default_empty_string() -> String {
@@ -90,6 +95,21 @@ pub struct OssEventItem {
     pub user_identity: UserIdentity,
 }
 
+impl OssEventItem {
+    /// 不需要处理的事件返回跳过原因：非 ObjectCreated 事件，或上传的不是视频文件
+    ///
+    /// 所有入口（/invoke、/process 等）共用这一检查
+    pub fn skip_reason(&self) -> Option<String> {
+        if !self.event_name.starts_with("ObjectCreated") {
+            return Some(format!("事件类型 {} 不是 ObjectCreated", self.event_name));
+        }
+        if !crate::preflight::is_video_file(&self.oss.object.key) {
+            return Some(format!("文件 {} 不是视频文件", self.oss.object.key));
+        }
+        None
+    }
+}
+
 /// OSS 信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OssInfo {
@@ -199,11 +219,95 @@ pub struct ProcessResponse {
     /// 处理结果信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<ProcessResult>,
+
+    /// 多事件请求中每个事件的处理结果（按事件顺序，单事件请求为空）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventResult>,
+
+    /// 输入超出大小或时长上限被拒绝处理时的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection: Option<Rejection>,
+}
+
+/// 多事件请求中一个事件的处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+    /// 处理成功
+    Success,
+    /// 事件被跳过（不是视频文件、结果已存在等）
+    Skipped,
+    /// 输入超出大小或时长上限被拒绝处理
+    Rejected,
+    /// 处理失败
+    Failure,
+}
+
+impl EventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventStatus::Success => "success",
+            EventStatus::Skipped => "skipped",
+            EventStatus::Rejected => "rejected",
+            EventStatus::Failure => "failure",
+        }
+    }
+}
+
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 多事件请求中一个事件的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventResult {
+    /// 事件在 `events` 中的下标
+    pub index: usize,
+    /// `oss://bucket/key`
+    pub source: String,
+    pub status: EventStatus,
+    /// 失败时的 HTTP 状态码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ProcessResult>,
+}
+
+impl ProcessResponse {
+    /// 汇总多事件请求的结果：全部成功或跳过时 `success` 为 true，部分失败时为 false 并在消息中说明
+    pub fn from_events(events: Vec<EventResult>) -> Self {
+        let count = |status: EventStatus| events.iter().filter(|e| e.status == status).count();
+        // 超出输入上限被拒绝的事件按跳过统计
+        let (succeeded, skipped, failed) = (
+            count(EventStatus::Success),
+            count(EventStatus::Skipped) + count(EventStatus::Rejected),
+            count(EventStatus::Failure),
+        );
+        let message = if failed == 0 {
+            format!("{} 个事件处理完成：成功 {}，跳过 {}", events.len(), succeeded, skipped)
+        } else {
+            format!("{} 个事件中 {} 个处理失败（成功 {}，跳过 {}），部分事件未处理完成", events.len(), failed, succeeded, skipped)
+        };
+        Self {
+            success: failed == 0,
+            message,
+            result: None,
+            events,
+            rejection: None,
+        }
+    }
 }
 
 /// 处理结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessResult {
+    /// 任务 ID（可通过 GET /jobs/{id}/logs 查询任务日志）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+
     /// 视频文件路径
     pub video_file: String,
     
@@ -221,4 +325,85 @@ pub struct ProcessResult {
     
     /// 元数据文件
     pub metadata_file: String,
-}
\ No newline at end of file
+
+    /// 输出目录的打包文件（配置了 archive 时）：已上传时为 oss://bucket/key，否则为本地路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+
+    /// 峰值内存、临时磁盘占用及各阶段资源占用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceUsage>,
+
+    /// 分阶段耗时和传输量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<TimingBreakdown>,
+
+    /// 内容安全检测未通过而跳过了上传
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub upload_blocked: bool,
+
+    /// webhook 投递记录（各次尝试的状态码和错误，未送达时保存的文件）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookDelivery>,
+
+    /// 已上传的输出文件：本地文件名（相对输出目录） -> 目标位置（oss://bucket/key）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outputs: BTreeMap<String, String>,
+
+    /// 输出文件的预签名下载地址：本地文件名 -> URL（配置了 presign_expires_secs 时返回）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub urls: BTreeMap<String, String>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_item(event_name: &str, key: &str) -> OssEventItem {
+        serde_json::from_value(serde_json::json!({
+            "eventName": event_name,
+            "eventSource": "acs:oss",
+            "eventTime": "2023-01-01T00:00:00.000Z",
+            "eventVersion": "1.0",
+            "oss": {
+                "bucket": { "arn": "acs:oss:cn-hangzhou:123:source", "name": "source", "ownerIdentity": "123" },
+                "object": { "key": key, "size": 1024, "eTag": "abc" },
+                "ossSchemaVersion": "1.0",
+                "ruleId": "rule",
+            },
+            "region": "cn-hangzhou",
+            "requestParameters": { "sourceIPAddress": "127.0.0.1" },
+            "responseElements": { "requestId": "req" },
+            "userIdentity": { "principalId": "123" },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_skip_reason() {
+        assert_eq!(event_item("ObjectCreated:Put", "videos/a.mp4").skip_reason(), None);
+        assert!(event_item("ObjectRemoved:DeleteObject", "videos/a.mp4").skip_reason().is_some());
+        let reason = event_item("ObjectCreated:Put", "docs/readme.txt").skip_reason().unwrap();
+        assert!(reason.contains("不是视频文件"));
+    }
+
+    #[test]
+    fn test_multi_event_response() {
+        let event = |index: usize, status: EventStatus| EventResult {
+            index,
+            source: format!("oss://source/videos/{}.mp4", index),
+            status,
+            status_code: (status == EventStatus::Failure).then_some(500),
+            message: String::new(),
+            result: None,
+        };
+        let response = ProcessResponse::from_events(vec![event(0, EventStatus::Success), event(1, EventStatus::Skipped)]);
+        assert!(response.success);
+        let response = ProcessResponse::from_events(vec![event(0, EventStatus::Success), event(1, EventStatus::Failure), event(2, EventStatus::Success)]);
+        assert!(!response.success);
+        assert!(response.message.contains("3 个事件中 1 个处理失败"));
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["events"][1]["status"], "failure");
+        assert_eq!(json["events"][1]["status_code"], 500);
+        assert!(json["events"][0].get("status_code").is_none());
+    }
+}
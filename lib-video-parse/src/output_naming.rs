@@ -0,0 +1,453 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+/// 内容哈希在路径中保留的长度
+const HASH_LEN: usize = 8;
+/// 最多记录输出目录的任务数量（超出后淘汰最早的任务）
+const MAX_OUTPUT_DIRS: usize = 1000;
+
+/// 上传到目标 bucket 时输出文件的命名方式
+///
+/// 多个视频共用同一个目标前缀时，`flat` 下各自的 keyframe_0000.jpg、audio.aac、metadata.json 会互相覆盖
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputNaming {
+    /// 直接放在目标前缀下（旧的行为）
+    Flat,
+    /// 按视频文件名（不含扩展名）分目录：`{prefix}/{stem}/`
+    Stem,
+    /// 按视频文件名和内容哈希分目录（默认）：`{prefix}/{stem}-{hash}/`
+    #[default]
+    StemHash,
+}
+
+impl OutputNaming {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputNaming::Flat => "flat",
+            OutputNaming::Stem => "stem",
+            OutputNaming::StemHash => "stem_hash",
+        }
+    }
+
+    /// 视频输出所在的目标路径前缀
+    ///
+    /// 内容哈希取 OSS ETag（文件内容的 MD5）的前 8 位，ETag 为空时退化为 `stem`。
+    /// 前缀中的 `\`（Windows 上填写的路径）按 `/` 处理
+    pub fn dest_prefix(&self, prefix: &str, object_key: &str, etag: &str) -> String {
+        let prefix = normalize_key(prefix);
+        let prefix = prefix.trim_end_matches('/');
+        let namespace = match self {
+            OutputNaming::Flat => return prefix.to_string(),
+            OutputNaming::Stem => video_stem(object_key),
+            OutputNaming::StemHash => {
+                let hash: String = etag
+                    .chars()
+                    .filter(char::is_ascii_hexdigit)
+                    .take(HASH_LEN)
+                    .collect::<String>()
+                    .to_lowercase();
+                if hash.is_empty() {
+                    video_stem(object_key)
+                } else {
+                    format!("{}-{}", video_stem(object_key), hash)
+                }
+            }
+        };
+        if prefix.is_empty() {
+            namespace
+        } else {
+            format!("{}/{}", prefix, namespace)
+        }
+    }
+}
+
+impl FromStr for OutputNaming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "flat" => Ok(OutputNaming::Flat),
+            "stem" => Ok(OutputNaming::Stem),
+            "stem_hash" | "stem-hash" => Ok(OutputNaming::StemHash),
+            other => Err(format!("未知的输出命名方式: {}（可选值: flat, stem, stem_hash）", other)),
+        }
+    }
+}
+
+/// 输出文件名模板
+///
+/// 模板中的变量写作 `{name}`，数字变量可以指定补零宽度 `{name:04}`：
+/// - `{stem}`：输入文件名（不含扩展名）
+/// - `{ext}`：扩展名（随关键帧格式、音频编码变化）
+/// - `{index}`：关键帧序号，`{scene_id}`：场景编号，`{start_ms}` / `{end_ms}`：场景起止时间（毫秒），只用于关键帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileNaming {
+    /// 关键帧文件名，默认 `keyframe_{index:04}.{ext}`
+    pub keyframe: String,
+    /// 音频文件名，默认 `audio.{ext}`
+    pub audio: String,
+    /// 元数据文件名，默认 `metadata.json`
+    pub metadata: String,
+}
+
+impl Default for FileNaming {
+    fn default() -> Self {
+        Self {
+            keyframe: "keyframe_{index:04}.{ext}".to_string(),
+            audio: "audio.{ext}".to_string(),
+            metadata: "metadata.json".to_string(),
+        }
+    }
+}
+
+/// 关键帧文件名模板的变量
+#[derive(Debug, Clone, Copy)]
+pub struct KeyframeName {
+    pub index: usize,
+    pub scene_id: usize,
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+/// 模板变量的值
+enum TemplateValue<'a> {
+    Text(&'a str),
+    Number(u64),
+}
+
+impl FileNaming {
+    /// 代入输入文件名：返回的模板中 `{stem}` 已替换为输入文件名（不含扩展名）
+    pub fn for_input(&self, input: &Path) -> Self {
+        let stem = input.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+        // 文件名中的 `{` `}` 会被当作模板变量
+        let stem: String = stem.chars().map(|c| if c == '{' || c == '}' { '_' } else { c }).collect();
+        let stem = if stem.is_empty() { "video".to_string() } else { stem };
+        let render = |template: &str| {
+            render(template, &|name| (name == "stem").then_some(TemplateValue::Text(&stem)), true)
+                .unwrap_or_else(|_| template.to_string())
+        };
+        Self { keyframe: render(&self.keyframe), audio: render(&self.audio), metadata: render(&self.metadata) }
+    }
+
+    /// 关键帧文件名
+    pub fn keyframe_file(&self, keyframe: &KeyframeName, ext: &str) -> String {
+        let (start_ms, end_ms) = ((keyframe.start_time * 1000.0).round() as u64, (keyframe.end_time * 1000.0).round() as u64);
+        render_file_name(&self.keyframe, &|name| match name {
+            "index" => Some(TemplateValue::Number(keyframe.index as u64)),
+            "scene_id" => Some(TemplateValue::Number(keyframe.scene_id as u64)),
+            "start_ms" => Some(TemplateValue::Number(start_ms)),
+            "end_ms" => Some(TemplateValue::Number(end_ms)),
+            "ext" => Some(TemplateValue::Text(ext)),
+            _ => None,
+        })
+    }
+
+    /// 音频文件名
+    pub fn audio_file(&self, ext: &str) -> String {
+        render_file_name(&self.audio, &|name| (name == "ext").then_some(TemplateValue::Text(ext)))
+    }
+
+    /// 元数据文件名
+    pub fn metadata_file(&self) -> String {
+        render_file_name(&self.metadata, &|name| (name == "ext").then_some(TemplateValue::Text("json")))
+    }
+
+    /// 检查模板：变量和补零宽度有效、生成的是文件名（不含路径分隔符），关键帧模板需要区分不同场景
+    pub fn validate(&self) -> Result<(), String> {
+        let sample = |name: &str| match name {
+            "stem" | "ext" => Some(TemplateValue::Text("x")),
+            _ => None,
+        };
+        let keyframe_vars = |name: &str| match name {
+            "index" | "scene_id" | "start_ms" | "end_ms" => Some(TemplateValue::Number(0)),
+            _ => sample(name),
+        };
+        for (key, template, vars) in [
+            ("naming_keyframe", &self.keyframe, &keyframe_vars as &dyn Fn(&str) -> Option<TemplateValue<'static>>),
+            ("naming_audio", &self.audio, &sample),
+            ("naming_metadata", &self.metadata, &sample),
+        ] {
+            let name = render(template, vars, false).map_err(|e| format!("{} 无效: {}: {}", key, template, e))?;
+            if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+                return Err(format!("{} 必须生成文件名（不能包含路径分隔符）: {}", key, template));
+            }
+        }
+        if !["{index", "{scene_id", "{start_ms"].iter().any(|var| self.keyframe.contains(var)) {
+            return Err(format!("naming_keyframe 需要包含 {{index}}、{{scene_id}} 或 {{start_ms}}，否则关键帧会互相覆盖: {}", self.keyframe));
+        }
+        Ok(())
+    }
+}
+
+/// 同一场景的其他关键帧文件名：在主关键帧的扩展名前加序号，`keyframe_0003.jpg` → `keyframe_0003_1.jpg`
+pub fn extra_keyframe_file(keyframe_file: &str, index: usize) -> String {
+    match keyframe_file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}_{}.{}", stem, index, ext),
+        _ => format!("{}_{}", keyframe_file, index),
+    }
+}
+
+/// 生成文件名：未代入输入文件名时 `{stem}` 为 `video`，无效的变量保留原样（配置在构建时已检查）
+fn render_file_name<'a>(template: &str, vars: &dyn Fn(&str) -> Option<TemplateValue<'a>>) -> String {
+    let vars = |name: &str| vars(name).or_else(|| (name == "stem").then_some(TemplateValue::Text("video")));
+    render(template, &vars, true).unwrap_or_else(|_| template.to_string())
+}
+
+/// 代入模板变量；`keep_unknown` 为 true 时未知的变量原样保留，否则返回错误
+fn render<'a>(template: &str, vars: &dyn Fn(&str) -> Option<TemplateValue<'a>>, keep_unknown: bool) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        output.push_str(&rest[..open]);
+        let close = rest[open..].find('}').ok_or_else(|| "缺少 }".to_string())? + open;
+        let placeholder = &rest[open..=close];
+        let (name, width) = match rest[open + 1..close].split_once(':') {
+            Some((name, width)) => {
+                let width: usize = width.parse().map_err(|_| format!("无效的补零宽度: {}", placeholder))?;
+                (name, width)
+            }
+            None => (&rest[open + 1..close], 0),
+        };
+        match vars(name) {
+            Some(TemplateValue::Number(value)) => output.push_str(&format!("{:0width$}", value, width = width)),
+            Some(TemplateValue::Text(value)) => output.push_str(value),
+            None if keep_unknown => output.push_str(placeholder),
+            None => return Err(format!("未知的变量: {}", placeholder)),
+        }
+        rest = &rest[close + 1..];
+    }
+    if rest.contains('}') {
+        return Err("缺少 {".to_string());
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// 按内容寻址的输出子目录：`{源文件哈希}/{配置指纹}`
+///
+/// 同一视频用不同参数处理时输出不会互相覆盖，相同的视频和参数总是对应同一目录
+pub fn content_path(source_hash: &str, config_fingerprint: &str) -> String {
+    format!("{}/{}", source_hash, config_fingerprint)
+}
+
+/// ETag 作为源文件哈希：去掉引号并转小写（分片上传的 ETag 带 `-分片数` 后缀）
+pub fn etag_hash(etag: &str) -> String {
+    etag.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// 文件内容的 SHA-256（十六进制），没有 ETag 时作为源文件哈希
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 把 Windows 路径分隔符 `\` 换成对象键使用的 `/`
+pub fn normalize_key(key: &str) -> String {
+    key.replace('\\', "/")
+}
+
+/// 对象键的最后一段（文件名）
+///
+/// 不使用 `Path` 解析，`/` 和 `\` 在所有平台上都作为分隔符，结果不随运行平台变化
+pub fn key_file_name(object_key: &str) -> &str {
+    object_key.rsplit(['/', '\\']).next().unwrap_or(object_key)
+}
+
+/// 下载到本地时使用的文件名：替换 Windows 文件名中不允许的字符，对象键以分隔符结尾时为 `video.mp4`
+pub fn local_file_name(object_key: &str) -> String {
+    let name: String = key_file_name(object_key)
+        .chars()
+        .map(|c| if c.is_control() || "<>:\"|?*".contains(c) { '_' } else { c })
+        .collect();
+    // Windows 不允许文件名以点或空格结尾
+    let name = name.trim_end_matches(['.', ' ']);
+    if name.is_empty() {
+        "video.mp4".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// 任务 ID → 实际输出目录（含按内容寻址的子目录），处理完成时记录，
+/// 供 GET /outputs/{job_id}/{filename} 查找输出文件
+pub struct OutputDirs {
+    inner: Mutex<(HashMap<String, PathBuf>, VecDeque<String>)>,
+    max_jobs: usize,
+}
+
+impl OutputDirs {
+    pub fn new(max_jobs: usize) -> Self {
+        Self { inner: Mutex::new(Default::default()), max_jobs: max_jobs.max(1) }
+    }
+
+    /// 进程内共享的输出目录记录（由 Pipeline 在处理完成时写入）
+    pub fn shared() -> &'static OutputDirs {
+        static SHARED: OnceLock<OutputDirs> = OnceLock::new();
+        SHARED.get_or_init(|| OutputDirs::new(MAX_OUTPUT_DIRS))
+    }
+
+    /// 记录任务的输出目录
+    pub fn record(&self, job_id: &str, output_dir: &Path) {
+        let mut inner = self.inner.lock().unwrap();
+        let (dirs, order) = &mut *inner;
+        if !dirs.contains_key(job_id) {
+            while order.len() >= self.max_jobs {
+                if let Some(oldest) = order.pop_front() {
+                    dirs.remove(&oldest);
+                }
+            }
+            order.push_back(job_id.to_string());
+        }
+        dirs.insert(job_id.to_string(), output_dir.to_path_buf());
+    }
+
+    /// 任务的输出目录（未记录或已被淘汰时返回 None）
+    pub fn get(&self, job_id: &str) -> Option<PathBuf> {
+        self.inner.lock().unwrap().0.get(job_id).cloned()
+    }
+}
+
+/// 输出目录中的文件路径，用于通过 HTTP 读取处理结果
+///
+/// `file` 为以 `/` 分隔的相对路径，只允许普通的路径段（不能包含 `..`、绝对路径或 `\`）；
+/// 解析符号链接后仍须位于 `output_dir` 下，文件不存在时返回 None
+pub fn resolve_output_file(output_dir: &Path, file: &str) -> Option<PathBuf> {
+    let segments: Vec<&str> = file.split('/').collect();
+    let valid = segments.iter().all(|segment| {
+        !segment.is_empty() && *segment != "." && *segment != ".." && !segment.contains(['\\', ':', '\0'])
+    });
+    if !valid {
+        return None;
+    }
+    let root = output_dir.canonicalize().ok()?;
+    let path = segments.iter().fold(root.clone(), |path, segment| path.join(segment)).canonicalize().ok()?;
+    (path.starts_with(&root) && path.is_file()).then_some(path)
+}
+
+/// 视频文件名（不含扩展名）
+fn video_stem(object_key: &str) -> String {
+    let name = key_file_name(object_key);
+    let stem = match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => name,
+    };
+    if stem.is_empty() { "video".to_string() } else { stem.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dest_prefix() {
+        let etag = "\"5EB63BBBE01EEED093CB22BB8F5ACDC3\"";
+        assert_eq!(OutputNaming::Flat.dest_prefix("processed/", "videos/a.mp4", etag), "processed");
+        assert_eq!(OutputNaming::Stem.dest_prefix("processed", "videos/a.mp4", etag), "processed/a");
+        assert_eq!(
+            OutputNaming::StemHash.dest_prefix("processed", "videos/a.mp4", etag),
+            "processed/a-5eb63bbb"
+        );
+        // 不同内容的同名视频不会互相覆盖
+        assert_ne!(
+            OutputNaming::StemHash.dest_prefix("processed", "a/clip.mp4", "111111111"),
+            OutputNaming::StemHash.dest_prefix("processed", "b/clip.mp4", "222222222")
+        );
+        assert_eq!(OutputNaming::StemHash.dest_prefix("", "clip.mp4", ""), "clip");
+        // Windows 风格的前缀和对象键
+        assert_eq!(OutputNaming::Stem.dest_prefix("processed\\daily\\", "videos\\a.mp4", ""), "processed/daily/a");
+        assert_eq!(local_file_name("videos/10:30 <b>.mp4"), "10_30 _b_.mp4");
+        assert_eq!(local_file_name("videos/"), "video.mp4");
+        assert_eq!("stem-hash".parse::<OutputNaming>(), Ok(OutputNaming::StemHash));
+        assert!("uuid".parse::<OutputNaming>().is_err());
+    }
+
+    #[test]
+    fn test_file_naming_templates() {
+        let keyframe = KeyframeName { index: 2, scene_id: 3, start_time: 12.3456, end_time: 15.0 };
+        let default = FileNaming::default().for_input(Path::new("/tmp/a.mp4"));
+        assert_eq!(default.keyframe_file(&keyframe, "jpg"), "keyframe_0002.jpg");
+        assert_eq!(default.audio_file("aac"), "audio.aac");
+        assert_eq!(default.metadata_file(), "metadata.json");
+        assert_eq!(extra_keyframe_file("keyframe_0002.jpg", 1), "keyframe_0002_1.jpg");
+
+        let naming = FileNaming {
+            keyframe: "{stem}_scene{scene_id:04}_{start_ms}.{ext}".to_string(),
+            audio: "{stem}.{ext}".to_string(),
+            metadata: "{stem}.{ext}".to_string(),
+        };
+        assert!(naming.validate().is_ok());
+        let naming = naming.for_input(Path::new("videos/clip{1}.mov"));
+        assert_eq!(naming.keyframe_file(&keyframe, "webp"), "clip_1__scene0003_12346.webp");
+        assert_eq!(naming.audio_file("opus"), "clip_1_.opus");
+        assert_eq!(naming.metadata_file(), "clip_1_.json");
+
+        let invalid = |keyframe: &str| FileNaming { keyframe: keyframe.to_string(), ..Default::default() }.validate();
+        assert!(invalid("keyframe_{frame}.{ext}").is_err());
+        assert!(invalid("keyframe_{index:x}.{ext}").is_err());
+        assert!(invalid("{stem}/keyframe_{index}.{ext}").is_err());
+        // 所有关键帧同名
+        assert!(invalid("{stem}.{ext}").is_err());
+    }
+
+    #[test]
+    fn test_content_path() {
+        assert_eq!(etag_hash("\"5EB63BBBE01EEED093CB22BB8F5ACDC3\""), "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert_eq!(etag_hash("\"3858F62230AC3C915F300C664312C11F-9\""), "3858f62230ac3c915f300c664312c11f-9");
+        assert_eq!(content_path("5eb63bbb", "00ff"), "5eb63bbb/00ff");
+
+        let path = std::env::temp_dir().join(format!("video-parse-sha256-{}", std::process::id()));
+        std::fs::write(&path, b"hello world").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_resolve_output_file() {
+        let root = std::env::temp_dir().join(format!("video-parse-outputs-{}", uuid::Uuid::new_v4()));
+        let output_dir = root.join("job").join("output");
+        std::fs::create_dir_all(output_dir.join("clips")).unwrap();
+        std::fs::write(output_dir.join("keyframe_0001.jpg"), b"jpeg").unwrap();
+        std::fs::write(output_dir.join("clips").join("scene_1.mp4"), b"mp4").unwrap();
+        std::fs::write(root.join("job").join("video.mp4"), b"source").unwrap();
+
+        assert!(resolve_output_file(&output_dir, "keyframe_0001.jpg").is_some());
+        assert!(resolve_output_file(&output_dir, "clips/scene_1.mp4").is_some());
+        // 目录、不存在的文件和输出目录以外的文件
+        assert!(resolve_output_file(&output_dir, "clips").is_none());
+        assert!(resolve_output_file(&output_dir, "missing.jpg").is_none());
+        for file in ["../video.mp4", "clips/../../video.mp4", "/etc/passwd", "..\\video.mp4", "./keyframe_0001.jpg", ""] {
+            assert!(resolve_output_file(&output_dir, file).is_none(), "{}", file);
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("job").join("video.mp4"), output_dir.join("link.mp4")).unwrap();
+            assert!(resolve_output_file(&output_dir, "link.mp4").is_none());
+        }
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_output_dirs() {
+        let dirs = OutputDirs::new(2);
+        dirs.record("job-1", Path::new("/tmp/a/output"));
+        dirs.record("job-2", Path::new("/tmp/b/output/5eb63bbb/fp1"));
+        dirs.record("job-1", Path::new("/tmp/a/output2"));
+        assert_eq!(dirs.get("job-1"), Some(PathBuf::from("/tmp/a/output2")));
+        assert_eq!(dirs.get("job-2"), Some(PathBuf::from("/tmp/b/output/5eb63bbb/fp1")));
+        // 超出上限时淘汰最早的任务
+        dirs.record("job-3", Path::new("/tmp/c/output"));
+        assert_eq!(dirs.get("job-1"), None);
+        assert!(dirs.get("job-3").is_some());
+        assert_eq!(dirs.get("missing"), None);
+    }
+}
@@ -0,0 +1,1839 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use image::DynamicImage;
+use tracing::{info, info_span, warn, error, debug, instrument, Instrument, Span};
+use tracing::field::Empty;
+use crate::{VideoProcessor, AudioExtractor};
+use crate::video_processor::{SampleAspectRatio, VideoProbe};
+use crate::metadata::{MediaKind, MetadataExporter, SceneMetadata, VideoMetadata};
+use crate::processor::{self, ProcessConfig, ProcessOptions, ProcessOutput, run_blocking};
+use crate::progressive::ProgressiveFile;
+use crate::frame_pool::FramePool;
+use crate::telemetry;
+use crate::watchdog::{with_timeout, Stage};
+use crate::archive::{self, ArchiveFormat};
+use crate::ocr::TextRecognizer;
+use crate::{ocr, faces, shot_type, fingerprint, viewer, safety, embeddings, enrichment, annotator, sprite, clips, preview, audio_analyzer, audio_boundary, vad, preflight, decoder, scene_detector, output_naming};
+use crate::scene_detector::{SceneDetection, TransitionType};
+use crate::failure::{FailureReport, Progress};
+use crate::checkpoint::{Checkpoint, DetectionCheckpoint};
+use crate::progress::{self, ProgressReporter, ProgressUnit, StageProgress, WebhookProgress};
+use crate::error::VideoParseError;
+use crate::resource::{ResourceMonitor, TimingBreakdown, disk_usage, format_mb};
+use tokio_util::sync::CancellationToken;
+
+/// 待处理的视频：本地文件，或正在分段下载的文件
+#[derive(Clone)]
+pub struct VideoInput {
+    pub path: PathBuf,
+    /// 正在分段下载的文件（边下载边处理时设置）
+    pub progressive: Option<Arc<ProgressiveFile>>,
+}
+
+/// 帧来源提取的视频信息和采样帧
+pub struct DecodedVideo {
+    pub fps: f64,
+    /// 存储分辨率
+    pub width: u32,
+    pub height: u32,
+    /// 像素宽高比，非方形像素时帧已按它缩放到显示分辨率
+    pub sample_aspect_ratio: SampleAspectRatio,
+    /// 输入的媒体类型，纯音频时没有帧
+    pub media_kind: MediaKind,
+    /// 总时长（秒），未设置时取最后一个采样帧的时间
+    pub duration: Option<f64>,
+    /// (时间戳(秒), 图像)
+    pub frames: Vec<(f64, DynamicImage)>,
+}
+
+/// 帧来源逐帧提取的视频信息和采样帧
+pub struct FrameStream {
+    pub fps: f64,
+    /// 存储分辨率
+    pub width: u32,
+    pub height: u32,
+    /// 像素宽高比，非方形像素时帧已按它缩放到显示分辨率
+    pub sample_aspect_ratio: SampleAspectRatio,
+    /// 总时长（秒），未设置时取最后一个采样帧的时间
+    pub duration: Option<f64>,
+    /// 按时间顺序产生的 (时间戳(秒), 图像)
+    pub frames: Box<dyn Iterator<Item = Result<(f64, DynamicImage)>>>,
+}
+
+/// 帧来源：打开视频并按配置的采样率提取帧
+///
+/// 在阻塞线程池中调用
+pub trait FrameSource: Send + Sync {
+    /// 解码前检查输入，默认不做检查
+    fn preflight(&self, _input: &VideoInput, _output_dir: &Path, _config: &ProcessConfig) -> Result<()> {
+        Ok(())
+    }
+
+    fn extract(&self, input: &VideoInput, config: &ProcessConfig) -> Result<DecodedVideo>;
+
+    /// 逐帧提取视频帧，流水线边提取边检测场景，不在内存中保留全部采样帧
+    ///
+    /// 返回 None 时（默认，或输入不是视频）使用 [`extract`](Self::extract) 一次性提取。
+    /// 实现此方法时需要同时实现 [`extract_at`](Self::extract_at)
+    fn stream(&self, _input: &VideoInput, _config: &ProcessConfig) -> Result<Option<FrameStream>> {
+        Ok(None)
+    }
+
+    /// 重新提取指定时间点（之前采样得到的帧时间戳）的帧，流式处理时用于读取选中的关键帧
+    fn extract_at(&self, _input: &VideoInput, _times: &[f64], _config: &ProcessConfig) -> Result<Vec<(f64, DynamicImage)>> {
+        anyhow::bail!("帧来源不支持按时间点提取帧")
+    }
+}
+
+/// 场景检测：返回每个场景的起始时间（秒），第一个场景从 0 开始
+///
+/// 在阻塞线程池中调用
+pub trait Detector: Send + Sync {
+    fn detect(&self, frames: &[(f64, DynamicImage)], fps: f64, config: &ProcessConfig) -> Result<Vec<f64>>;
+
+    /// 场景起始时间和每帧与前一帧的差异（用于计算场景的运动强度，并由关键帧选择复用）
+    ///
+    /// 默认不返回差异，场景没有 `motion_score`，默认的关键帧选择需要重新计算全部帧差异
+    fn detect_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        fps: f64,
+        config: &ProcessConfig,
+    ) -> Result<(Vec<f64>, Vec<f64>)> {
+        Ok((self.detect(frames, fps, config)?, Vec::new()))
+    }
+
+    /// 场景起始时间、每帧与前一帧的差异和每个场景开始处的转场类型（与场景起始时间一一对应）
+    ///
+    /// 默认不识别转场类型，场景没有 `transition_type`
+    fn detect_with_transitions(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        fps: f64,
+        config: &ProcessConfig,
+    ) -> Result<SceneDetection> {
+        let (scene_changes, differences) = self.detect_with_differences(frames, fps, config)?;
+        let transitions = vec![None; scene_changes.len()];
+        Ok((scene_changes, differences, transitions))
+    }
+}
+
+/// 关键帧选择：为每个场景选择关键帧并保存到输出目录，返回场景元数据
+///
+/// 在阻塞线程池中调用
+pub trait KeyframeSelector: Send + Sync {
+    fn select(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        scene_changes: &[f64],
+        total_duration: f64,
+        output_dir: &Path,
+        config: &ProcessConfig,
+    ) -> Result<Vec<SceneMetadata>>;
+
+    /// 同 [`select`](KeyframeSelector::select)，另外传入场景检测时计算的相邻帧差异（`differences[i]` 为第 i 帧与前一帧的差异，
+    /// 场景检测不返回差异时为空），可以复用而不必重新计算
+    ///
+    /// 默认忽略差异，调用 `select`
+    fn select_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        differences: &[f64],
+        scene_changes: &[f64],
+        total_duration: f64,
+        output_dir: &Path,
+        config: &ProcessConfig,
+    ) -> Result<Vec<SceneMetadata>> {
+        let _ = differences;
+        self.select(frames, scene_changes, total_duration, output_dir, config)
+    }
+}
+
+/// 输出文件：根据视频元数据在输出目录中写出文件（metadata.json、字幕、剪辑列表等）
+///
+/// 在阻塞线程池中调用，任一输出失败时处理失败
+pub trait ArtifactWriter: Send + Sync {
+    /// 名称，用于日志
+    fn name(&self) -> &str;
+    fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()>;
+}
+
+/// 结果发布：处理完成后把结果发送到外部（webhook、消息队列等）
+///
+/// 发布失败只记录警告，不影响处理结果
+#[async_trait]
+pub trait Publisher: Send + Sync {
+    /// 名称，用于日志
+    fn name(&self) -> &str;
+    async fn publish(&self, output: &mut ProcessOutput, config: &ProcessConfig) -> Result<()>;
+}
+
+/// 关键帧分类：为每个关键帧给出标签（ONNX 模型、远程分类 API 等），合并到场景的 `tags`
+///
+/// 在关键帧标注之后逐个关键帧调用，`timestamp` 为场景开始时间（秒）。标注失败只记录警告
+#[async_trait]
+pub trait KeyframeAnnotator: Send + Sync {
+    /// 名称，用于日志
+    fn name(&self) -> &str;
+    async fn annotate(&self, image: &DynamicImage, timestamp: f64) -> Result<Vec<String>>;
+}
+
+/// 不产生任何标签的关键帧分类器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopAnnotator;
+
+#[async_trait]
+impl KeyframeAnnotator for NoopAnnotator {
+    fn name(&self) -> &str {
+        "noop"
+    }
+
+    async fn annotate(&self, _image: &DynamicImage, _timestamp: f64) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+/// 使用 FFmpeg 解码的帧来源（默认）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FfmpegFrameSource;
+
+impl FrameSource for FfmpegFrameSource {
+    /// 探测容器和视频流并检查磁盘空间；分段下载时文件尚不完整，跳过检查
+    fn preflight(&self, input: &VideoInput, output_dir: &Path, config: &ProcessConfig) -> Result<()> {
+        if input.progressive.is_some() {
+            return Ok(());
+        }
+        preflight::check(&input.path, output_dir, config)
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 输入预检失败");
+                e
+            })?;
+        Ok(())
+    }
+
+    fn extract(&self, input: &VideoInput, config: &ProcessConfig) -> Result<DecodedVideo> {
+        let processor = Self::processor(input, config)?;
+        let media_kind = Self::media_kind(&processor)?;
+        match media_kind {
+            MediaKind::Video => {}
+            MediaKind::AudioOnly => {
+                info!("🎵 [视频处理] 输入只有音频流，跳过帧提取");
+                return Ok(DecodedVideo {
+                    fps: 0.0,
+                    width: 0,
+                    height: 0,
+                    sample_aspect_ratio: SampleAspectRatio::SQUARE,
+                    media_kind,
+                    duration: Some(processor.duration()?),
+                    frames: Vec::new(),
+                });
+            }
+            MediaKind::StillImage => {
+                info!("🖼️  [视频处理] 输入为单张图片，作为唯一的关键帧");
+                if let Some(ref file) = input.progressive {
+                    file.wait_complete()?;
+                }
+                let image = image::open(&input.path)
+                    .with_context(|| format!("读取图片失败: {}", input.path.display()))?;
+                return Ok(DecodedVideo {
+                    fps: 0.0,
+                    width: image.width(),
+                    height: image.height(),
+                    sample_aspect_ratio: SampleAspectRatio::SQUARE,
+                    media_kind,
+                    duration: Some(0.0),
+                    frames: vec![(0.0, image)],
+                });
+            }
+        }
+
+        let VideoProbe { fps, width, height, sample_aspect_ratio, .. } = Self::probe(&processor)?;
+        let frames = processor.extract_frames(Some(config.sample_rate))
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 提取视频帧失败");
+                e
+            })?;
+        Ok(DecodedVideo { fps, width, height, sample_aspect_ratio, media_kind, duration: None, frames })
+    }
+
+    /// 纯音频和单张图片返回 None，由 `extract` 处理
+    fn stream(&self, input: &VideoInput, config: &ProcessConfig) -> Result<Option<FrameStream>> {
+        let processor = Self::processor(input, config)?;
+        if Self::media_kind(&processor)? != MediaKind::Video {
+            return Ok(None);
+        }
+        let VideoProbe { fps, width, height, sample_aspect_ratio, .. } = Self::probe(&processor)?;
+        let frames = processor.extract_frames_iter(Some(config.sample_rate))
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 提取视频帧失败");
+                e
+            })?;
+        Ok(Some(FrameStream {
+            fps,
+            width,
+            height,
+            sample_aspect_ratio,
+            duration: None,
+            frames: Box::new(frames.map(|frame| {
+                frame.map_err(|e| {
+                    error!(error = %e, "❌ [视频处理] 提取视频帧失败");
+                    e.into()
+                })
+            })),
+        }))
+    }
+
+    fn extract_at(&self, input: &VideoInput, times: &[f64], config: &ProcessConfig) -> Result<Vec<(f64, DynamicImage)>> {
+        let processor = Self::processor(input, config)?;
+        processor.extract_frames_at(times, config.sample_rate)
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 重新提取关键帧失败");
+                e.into()
+            })
+    }
+}
+
+impl FfmpegFrameSource {
+    fn processor(input: &VideoInput, config: &ProcessConfig) -> Result<VideoProcessor> {
+        let processor = match input.progressive {
+            Some(ref file) => VideoProcessor::from_progressive(file.clone()),
+            None => VideoProcessor::new(&input.path),
+        };
+        Ok(processor
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 初始化视频处理器失败");
+                e
+            })?
+            .with_seek_mode(config.seek_mode)
+            .with_hwaccel(config.hwaccel))
+    }
+
+    fn media_kind(processor: &VideoProcessor) -> Result<MediaKind> {
+        Ok(processor.media_kind()
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 获取视频信息失败");
+                e
+            })?)
+    }
+
+    /// 获取视频信息并记录到当前 span
+    fn probe(processor: &VideoProcessor) -> Result<VideoProbe> {
+        let probe = processor.probe()
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 获取视频信息失败");
+                e
+            })?;
+        let VideoProbe { fps, width, height, sample_aspect_ratio, .. } = probe;
+        let span = Span::current();
+        span.record("resolution", format!("{}x{}", width, height));
+        span.record("fps", fps);
+        if !sample_aspect_ratio.is_square() {
+            let (display_width, display_height) = sample_aspect_ratio.display_size(width, height);
+            info!(
+                sar = %sample_aspect_ratio,
+                display_resolution = %format!("{}x{}", display_width, display_height),
+                "📐 [视频处理] 非方形像素，按显示分辨率提取帧"
+            );
+        }
+        Ok(probe)
+    }
+}
+
+/// 按配置的阈值和度量项比较相邻帧的场景检测（默认）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SceneChangeDetector;
+
+impl Detector for SceneChangeDetector {
+    fn detect(&self, frames: &[(f64, DynamicImage)], fps: f64, config: &ProcessConfig) -> Result<Vec<f64>> {
+        self.detect_with_differences(frames, fps, config)
+            .map(|(scene_changes, _)| scene_changes)
+    }
+
+    fn detect_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        fps: f64,
+        config: &ProcessConfig,
+    ) -> Result<(Vec<f64>, Vec<f64>)> {
+        self.detect_with_transitions(frames, fps, config)
+            .map(|(scene_changes, differences, _)| (scene_changes, differences))
+    }
+
+    fn detect_with_transitions(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        fps: f64,
+        config: &ProcessConfig,
+    ) -> Result<SceneDetection> {
+        config.scene_detector()
+            .detect_scenes_with_transitions(frames, fps)
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 场景检测失败");
+                e.into()
+            })
+    }
+}
+
+/// 每个场景选择中间区域最稳定的一帧作为关键帧（默认）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StableFrameSelector;
+
+impl KeyframeSelector for StableFrameSelector {
+    fn select(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        scene_changes: &[f64],
+        total_duration: f64,
+        output_dir: &Path,
+        config: &ProcessConfig,
+    ) -> Result<Vec<SceneMetadata>> {
+        self.select_with_differences(frames, &[], scene_changes, total_duration, output_dir, config)
+    }
+
+    fn select_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        differences: &[f64],
+        scene_changes: &[f64],
+        total_duration: f64,
+        output_dir: &Path,
+        config: &ProcessConfig,
+    ) -> Result<Vec<SceneMetadata>> {
+        processor::extract_keyframes(frames, differences, scene_changes, total_duration, output_dir, config)
+    }
+}
+
+/// 每个场景保存多张关键帧
+///
+/// 主关键帧（`keyframe_file`）与 [`StableFrameSelector`] 相同；场景均匀分成 `per_scene` 段，
+/// 中间一段使用主关键帧，其余各段取最接近中点的采样帧写入 `extra_keyframes`。场景内采样帧不足时少于 `per_scene` 张
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadFrameSelector {
+    /// 每个场景的关键帧数
+    pub per_scene: usize,
+}
+
+impl KeyframeSelector for SpreadFrameSelector {
+    fn select(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        scene_changes: &[f64],
+        total_duration: f64,
+        output_dir: &Path,
+        config: &ProcessConfig,
+    ) -> Result<Vec<SceneMetadata>> {
+        self.select_with_differences(frames, &[], scene_changes, total_duration, output_dir, config)
+    }
+
+    fn select_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        differences: &[f64],
+        scene_changes: &[f64],
+        total_duration: f64,
+        output_dir: &Path,
+        config: &ProcessConfig,
+    ) -> Result<Vec<SceneMetadata>> {
+        let mut scenes = StableFrameSelector.select_with_differences(frames, differences, scene_changes, total_duration, output_dir, config)?;
+        for scene in &mut scenes {
+            for (index, (_, image)) in spread_frames(frames, scene.start_time, scene.end_time, self.per_scene).into_iter().enumerate() {
+                let file_name = output_naming::extra_keyframe_file(&scene.keyframe_file, index + 1);
+                config.keyframe_image.save(image, &output_dir.join(&file_name))
+                    .with_context(|| format!("保存关键帧失败: {}", file_name))?;
+                scene.extra_keyframes.push(file_name);
+            }
+        }
+        Ok(scenes)
+    }
+}
+
+/// `[start, end)` 均匀分成 `count` 段，除中间一段外每段取最接近中点的采样帧（同一帧只取一次）
+fn spread_frames(frames: &[(f64, DynamicImage)], start: f64, end: f64, count: usize) -> Vec<&(f64, DynamicImage)> {
+    let scene_frames: Vec<&(f64, DynamicImage)> = frames.iter().filter(|(t, _)| *t >= start && *t < end).collect();
+    let mut picked: Vec<usize> = Vec::new();
+    for segment in (0..count).filter(|segment| *segment != count / 2) {
+        let target = start + (end - start) * (segment as f64 + 0.5) / count as f64;
+        let nearest = (0..scene_frames.len())
+            .min_by(|a, b| (scene_frames[*a].0 - target).abs().total_cmp(&(scene_frames[*b].0 - target).abs()));
+        if let Some(index) = nearest.filter(|index| !picked.contains(index)) {
+            picked.push(index);
+        }
+    }
+    picked.into_iter().map(|index| scene_frames[index]).collect()
+}
+
+/// 写出元数据文件（默认），文件名为 `VideoMetadata.metadata_file`（默认 `metadata.json`）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetadataWriter;
+
+impl ArtifactWriter for MetadataWriter {
+    fn name(&self) -> &str {
+        "metadata.json"
+    }
+
+    fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+        let file_name = if metadata.metadata_file.is_empty() { "metadata.json" } else { &metadata.metadata_file };
+        let metadata_path = output_dir.join(file_name);
+        let metadata_json = serde_json::to_string_pretty(metadata)
+            .map_err(|e| {
+                error!(error = %e, "❌ [视频处理] 序列化元数据失败");
+                anyhow::anyhow!("序列化元数据失败: {}", e)
+            })?;
+        std::fs::write(&metadata_path, metadata_json)
+            .map_err(|e| {
+                error!(path = %metadata_path.display(), error = %e, "❌ [视频处理] 写入元数据文件失败");
+                anyhow::anyhow!("写入元数据文件失败: {}", e)
+            })
+    }
+}
+
+/// 写出 EDL / CSV / SRT 场景列表（由 `ProcessConfig.export_formats` 启用）
+impl ArtifactWriter for MetadataExporter {
+    fn name(&self) -> &str {
+        self.format.as_str()
+    }
+
+    fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+        MetadataExporter::write(self, metadata, output_dir)
+    }
+}
+
+/// 把输出目录打包为单个文件（由 `ProcessConfig.archive` 启用，在其他输出文件之后写出）
+impl ArtifactWriter for ArchiveFormat {
+    fn name(&self) -> &str {
+        self.as_str()
+    }
+
+    fn write(&self, _metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+        archive::write_archive(output_dir, *self)?;
+        Ok(())
+    }
+}
+
+/// 写出视频指纹 fingerprint.json（启用视频指纹时）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FingerprintWriter;
+
+impl ArtifactWriter for FingerprintWriter {
+    fn name(&self) -> &str {
+        fingerprint::FINGERPRINT_FILE
+    }
+
+    fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+        let fingerprint = fingerprint::write(metadata, output_dir)?;
+        debug!(scenes = fingerprint.scenes.len(), "🔏 [视频处理] 视频指纹已生成");
+        Ok(())
+    }
+}
+
+/// 写出结果查看页 index.html（启用查看页时）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ViewerWriter;
+
+impl ArtifactWriter for ViewerWriter {
+    fn name(&self) -> &str {
+        viewer::VIEWER_FILE
+    }
+
+    fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+        viewer::write(metadata, output_dir)?;
+        debug!("🖥️  [视频处理] 结果查看页已生成");
+        Ok(())
+    }
+}
+
+/// 调用配置的 webhook_url（默认，未配置时不发送）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebhookPublisher;
+
+#[async_trait]
+impl Publisher for WebhookPublisher {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn publish(&self, output: &mut ProcessOutput, config: &ProcessConfig) -> Result<()> {
+        if let Some(ref webhook_url) = config.webhook_url {
+            output.webhook = Some(processor::notify_webhook(webhook_url, &config.webhook, output).await);
+        }
+        Ok(())
+    }
+}
+
+/// 视频处理流水线
+///
+/// 固定的阶段顺序为：帧提取 → 场景检测 → 关键帧选择 → 可选的关键帧分析（OCR、人脸等，由配置开启）
+/// → 关键帧分类 → 音频提取 → 输出文件 → 结果发布。帧来源、场景检测和关键帧选择可以替换，关键帧分类、输出文件和发布可以追加
+///
+/// 使用默认的场景检测和关键帧选择、且帧来源支持逐帧提取（[`FrameSource::stream`]）时，边提取边检测场景，
+/// 只保留采样时间戳和相邻帧差异，选出关键帧后按时间点重新提取，内存占用与视频时长无关。
+/// 替换场景检测或关键帧选择后，这些组件需要全部采样帧，帧一次性提取到内存中
+#[derive(Clone)]
+pub struct Pipeline {
+    frame_source: Arc<dyn FrameSource>,
+    detector: Arc<dyn Detector>,
+    keyframe_selector: Arc<dyn KeyframeSelector>,
+    writers: Vec<Arc<dyn ArtifactWriter>>,
+    publishers: Vec<Arc<dyn Publisher>>,
+    annotators: Vec<Arc<dyn KeyframeAnnotator>>,
+    /// 文字识别后端（未设置时按配置使用 tesseract）
+    text_recognizer: Option<Arc<dyn TextRecognizer>>,
+    /// 是否逐帧处理（使用默认的场景检测和关键帧选择时）
+    streaming: bool,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self {
+            frame_source: Arc::new(FfmpegFrameSource),
+            detector: Arc::new(SceneChangeDetector),
+            keyframe_selector: Arc::new(StableFrameSelector),
+            writers: vec![Arc::new(MetadataWriter)],
+            publishers: vec![Arc::new(WebhookPublisher)],
+            annotators: Vec::new(),
+            text_recognizer: None,
+            streaming: true,
+        }
+    }
+}
+
+impl Pipeline {
+    /// 默认流水线（与 [`process_video`](crate::process_video) 相同）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 默认流水线，帧来源使用配置的解码后端
+    pub fn for_config(config: &ProcessConfig) -> Self {
+        Self { frame_source: decoder::frame_source(config.decoder), ..Self::default() }
+    }
+
+    /// 替换帧来源
+    pub fn with_frame_source(mut self, frame_source: impl FrameSource + 'static) -> Self {
+        self.frame_source = Arc::new(frame_source);
+        self
+    }
+
+    /// 替换场景检测
+    pub fn with_detector(mut self, detector: impl Detector + 'static) -> Self {
+        self.detector = Arc::new(detector);
+        self.streaming = false;
+        self
+    }
+
+    /// 替换关键帧选择
+    pub fn with_keyframe_selector(mut self, selector: impl KeyframeSelector + 'static) -> Self {
+        self.keyframe_selector = Arc::new(selector);
+        self.streaming = false;
+        self
+    }
+
+    /// 追加输出文件，在 metadata.json 之后按添加顺序写出
+    pub fn with_writer(mut self, writer: impl ArtifactWriter + 'static) -> Self {
+        self.writers.push(Arc::new(writer));
+        self
+    }
+
+    /// 追加结果发布，在 webhook 之后按添加顺序发布
+    pub fn with_publisher(mut self, publisher: impl Publisher + 'static) -> Self {
+        self.publishers.push(Arc::new(publisher));
+        self
+    }
+
+    /// 替换文字识别后端（例如远程 OCR API），设置后不论配置的 `ocr` 是否开启都识别关键帧文字
+    pub fn with_text_recognizer(mut self, recognizer: impl TextRecognizer + 'static) -> Self {
+        self.text_recognizer = Some(Arc::new(recognizer));
+        self
+    }
+
+    /// 追加关键帧分类器，按添加顺序调用（默认没有分类器，不执行分类阶段）
+    pub fn with_annotator(mut self, annotator: impl KeyframeAnnotator + 'static) -> Self {
+        self.annotators.push(Arc::new(annotator));
+        self
+    }
+
+    /// 不发布结果（包括默认的 webhook），由调用方自行发布
+    pub fn without_publishers(mut self) -> Self {
+        self.publishers.clear();
+        self
+    }
+
+    /// 处理视频文件
+    pub async fn run(
+        &self,
+        input_video_path: impl AsRef<Path>,
+        output_dir: impl AsRef<Path>,
+        config: ProcessConfig,
+    ) -> Result<ProcessOutput, VideoParseError> {
+        self.run_with_options(input_video_path, output_dir, config, ProcessOptions::default()).await
+    }
+
+    /// 处理视频文件，可以设置整体超时和取消
+    pub async fn run_with_options(
+        &self,
+        input_video_path: impl AsRef<Path>,
+        output_dir: impl AsRef<Path>,
+        config: ProcessConfig,
+        options: ProcessOptions,
+    ) -> Result<ProcessOutput, VideoParseError> {
+        self.run_source(input_video_path.as_ref(), None, output_dir.as_ref(), config, options)
+            .await
+            .map_err(VideoParseError::from)
+    }
+
+    /// 处理正在分段下载的视频文件
+    pub async fn run_progressive(
+        &self,
+        input: Arc<ProgressiveFile>,
+        output_dir: impl AsRef<Path>,
+        config: ProcessConfig,
+    ) -> Result<ProcessOutput, VideoParseError> {
+        let input_video_path = input.path().to_path_buf();
+        self.run_source(&input_video_path, Some(input), output_dir.as_ref(), config, ProcessOptions::default())
+            .await
+            .map_err(VideoParseError::from)
+    }
+
+    /// 处理视频，失败时错误中带有失败阶段和部分结果（[`StageFailure`](crate::failure::StageFailure)），handler 用于构建失败回调
+    #[instrument(
+        name = "process_video",
+        skip_all,
+        fields(
+            job_id = Empty,
+            input = %input_video_path.display(),
+            output_dir = %output_dir.display(),
+            progressive = progressive.is_some()
+        )
+    )]
+    pub(crate) async fn run_source(
+        &self,
+        input_video_path: &Path,
+        progressive: Option<Arc<ProgressiveFile>>,
+        output_dir: &Path,
+        config: ProcessConfig,
+        options: ProcessOptions,
+    ) -> Result<ProcessOutput> {
+        let failure_webhook_url = config.failure_webhook_url().filter(|_| !options.skip_failure_webhook).map(str::to_string);
+        let webhook_config = config.webhook.clone();
+        // 配置了进度回调 URL 时，进度同时发送到 webhook
+        let webhook_progress = WebhookProgress::from_config(&config, None, &input_video_path.to_string_lossy());
+        let mut progress = Progress::new("prepare").with_reporter(progress::combine(options.progress.clone(), webhook_progress));
+        progress.partial.output_dir = Some(output_dir.to_string_lossy().to_string());
+        let input = VideoInput { path: input_video_path.to_path_buf(), progressive };
+        let execute = with_timeout(Stage::Process, options.timeout, self.execute(input, output_dir, config, options.source_hash.clone(), &mut progress));
+        let result = tokio::select! {
+            result = execute => result.map_err(anyhow::Error::from).and_then(|r| r),
+            _ = cancelled(options.cancel.as_ref()) => Err(VideoParseError::Cancelled.into()),
+        };
+        match result {
+            Ok(output) => Ok(output),
+            Err(e) => {
+                if options.cancel.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                    warn!(
+                        stage = progress.stage(),
+                        completed_stages = ?progress.partial.completed_stages,
+                        "⏹️  [视频处理] 处理已取消"
+                    );
+                }
+                let error = progress.fail(e);
+                // 调用失败回调（如果配置了）
+                if let Some(ref url) = failure_webhook_url {
+                    let report = FailureReport::new("process", &error);
+                    processor::notify_failure_webhook(url, &webhook_config, &input_video_path.to_string_lossy(), &report).await;
+                }
+                Err(error)
+            }
+        }
+    }
+
+    async fn execute(
+        &self,
+        input: VideoInput,
+        output_dir: &Path,
+        config: ProcessConfig,
+        source_hash: Option<String>,
+        progress: &mut Progress,
+    ) -> Result<ProcessOutput> {
+        let total_start = Instant::now();
+        // 任务 ID，日志按该 ID 缓存，可通过 GET /jobs/{id}/logs 查询
+        let job_id = uuid::Uuid::new_v4().to_string();
+        Span::current().record("job_id", job_id.as_str());
+        let mut monitor = ResourceMonitor::start();
+        let input_video_path = input.path.clone();
+        let disk_bytes = || disk_usage(&input_video_path) + disk_usage(output_dir);
+        // 按内容寻址：输出放在 {源文件哈希}/{配置指纹}/ 下
+        let content_hashes = if config.content_addressed {
+            let source_hash = match source_hash.clone() {
+                Some(hash) => hash,
+                None => {
+                    let (path, progressive) = (input.path.clone(), input.progressive.clone());
+                    run_blocking("计算文件哈希", move || {
+                        if let Some(file) = progressive {
+                            file.wait_complete()?;
+                        }
+                        output_naming::sha256_file(&path).context("计算文件哈希失败")
+                    }).await?
+                }
+            };
+            Some((source_hash, config.fingerprint()))
+        } else {
+            None
+        };
+        let content_dir = content_hashes.as_ref()
+            .map(|(hash, fingerprint)| output_dir.join(output_naming::content_path(hash, fingerprint)));
+        let output_dir = content_dir.as_deref().unwrap_or(output_dir);
+        progress.partial.output_dir = Some(output_dir.to_string_lossy().to_string());
+        // 文件名模板中的 {stem} 替换为输入文件名
+        config.naming.validate().map_err(|e| anyhow::anyhow!(e))?;
+        let config = Arc::new(ProcessConfig { naming: config.naming.for_input(&input.path), ..config });
+        info!("🎬 [视频处理] 开始处理视频");
+
+        // 创建输出目录
+        std::fs::create_dir_all(output_dir)
+            .context("创建输出目录失败")?;
+
+        // 检查点（可选）：重新处理同一输入时跳过上次已完成的阶段
+        let mut checkpoint = config.checkpoint.then(|| {
+            let input_size = input.progressive.as_ref()
+                .map(|file| file.total_size())
+                .unwrap_or_else(|| std::fs::metadata(&input.path).map(|m| m.len()).unwrap_or(0));
+            Checkpoint::load(output_dir, &Checkpoint::key(input_size, &config.fingerprint()))
+        });
+        let resumed_detection = checkpoint.as_ref().and_then(|c| c.detection.clone());
+        let detection_resumed = resumed_detection.is_some();
+
+        // 解码前检查输入是否为可读取的视频，以及输出目录的磁盘空间
+        progress.begin("preflight");
+        let (frame_source, stage_input, stage_output_dir, stage_config) =
+            (self.frame_source.clone(), input.clone(), output_dir.to_path_buf(), config.clone());
+        run_blocking("输入预检", move || frame_source.preflight(&stage_input, &stage_output_dir, &stage_config))
+            .await?;
+
+        // 1-3. 初始化视频处理器、获取视频信息并提取视频帧
+        // 解码和图像转换是 CPU 密集型操作，放到阻塞线程池中执行，避免占用 tokio 工作线程
+        let extract_span = info_span!(
+            "extract_frames",
+            sample_rate = config.sample_rate,
+            seek_mode = config.seek_mode.as_str(),
+            hwaccel = config.hwaccel.as_str(),
+            resolution = Empty,
+            fps = Empty,
+            frame_count = Empty,
+            duration_ms = Empty,
+        );
+        progress.begin("extract_frames");
+        monitor.begin_stage("视频帧提取");
+        let extract_start = Instant::now();
+        let extracted = match resumed_detection {
+            Some(detection) => {
+                extract_span.in_scope(|| info!(
+                    scene_count = detection.scene_changes.len(),
+                    "♻️  [视频处理] 从检查点恢复场景检测结果，跳过帧提取和场景检测"
+                ));
+                Extracted::Streamed(StreamedVideo::from(detection))
+            }
+            None => {
+                let (frame_source, stage_input, stage_config) = (self.frame_source.clone(), input.clone(), config.clone());
+                let streaming = self.streaming;
+                let reporter = progress.reporter();
+                let extract = run_blocking("视频帧提取", move || {
+                    if streaming {
+                        if let Some(stream) = frame_source.stream(&stage_input, &stage_config)? {
+                            return detect_stream(stream, &stage_config, reporter.as_deref()).map(Extracted::Streamed);
+                        }
+                    }
+                    frame_source.extract(&stage_input, &stage_config).map(Extracted::Frames)
+                }).instrument(extract_span.clone());
+                with_timeout(Stage::Extract, config.timeouts.extract, extract).await??
+            }
+        };
+        let (fps, width, height, sample_aspect_ratio, media_kind, duration, frame_count, streamed_detect) = match extracted {
+            Extracted::Frames(ref video) => (
+                video.fps, video.width, video.height, video.sample_aspect_ratio, video.media_kind, video.duration,
+                video.frames.len(), Duration::ZERO,
+            ),
+            Extracted::Streamed(ref video) => (
+                video.fps, video.width, video.height, video.sample_aspect_ratio, MediaKind::Video, video.duration,
+                video.times.len(), video.detect_duration,
+            ),
+        };
+        if !media_kind.is_video() {
+            warn!(media_kind = media_kind.as_str(), "⚠️  [视频处理] 输入不是视频，只执行适用的处理步骤");
+        }
+        let (display_width, display_height) = sample_aspect_ratio.display_size(width, height);
+        // 逐帧处理时场景检测与帧提取交替进行，检测耗时计入场景检测阶段
+        let extract_duration = extract_start.elapsed().saturating_sub(streamed_detect);
+        monitor.end_stage(disk_bytes());
+        let avg_frame_ms = if frame_count == 0 { 0.0 } else { extract_duration.as_millis() as f64 / frame_count as f64 };
+        progress.report(StageProgress::new("extract_frames", frame_count as u64, Some(frame_count as u64), ProgressUnit::Frames));
+        extract_span.record("frame_count", frame_count);
+        extract_span.record("duration_ms", extract_duration.as_millis() as u64);
+        telemetry::record_stage("extract_frames", extract_duration);
+        extract_span.in_scope(|| info!(avg_frame_ms, "✅ [视频处理] 提取视频帧完成"));
+
+        // 4. 检测场景变化
+        let scene_span = info_span!(
+            "detect_scenes",
+            algorithm = config.detection_algorithm.as_str(),
+            threshold_mode = config.threshold_mode.as_str(),
+            metrics = %config.metrics.names(),
+            analysis_width = ?config.analysis_width,
+            scene_count = Empty,
+            duration_ms = Empty,
+        );
+        progress.begin("detect_scenes");
+        monitor.begin_stage("场景检测");
+        let scene_start = Instant::now();
+        let streamed = matches!(extracted, Extracted::Streamed(_));
+        let (frames, times, scene_changes, differences, transitions) = match extracted {
+            Extracted::Frames(video) => {
+                let frames = video.frames;
+                let (detector, stage_config) = (self.detector.clone(), config.clone());
+                let detect = run_blocking("场景检测", move || {
+                    // 纯音频没有帧，不做场景检测
+                    let (scene_changes, differences, transitions) = if frames.is_empty() {
+                        (Vec::new(), Vec::new(), Vec::new())
+                    } else {
+                        detector.detect_with_transitions(&frames, fps, &stage_config)?
+                    };
+                    Ok((frames, scene_changes, differences, transitions))
+                }).instrument(scene_span.clone());
+                let (frames, scene_changes, differences, transitions) = with_timeout(Stage::Detect, config.timeouts.detect, detect).await??;
+                let times: Vec<f64> = frames.iter().map(|(t, _)| *t).collect();
+                (frames, times, scene_changes, differences, transitions)
+            }
+            // 已在帧提取时逐帧检测，没有保留帧图像
+            Extracted::Streamed(video) => (Vec::new(), video.times, video.scene_changes, video.differences, video.transitions),
+        };
+        // 场景数不在目标范围内时在帧差异上重新查找阈值（可选）
+        let (scene_changes, transitions) = match &config.target_scene_range {
+            Some(range) if !detection_resumed && !times.is_empty() && !range.contains(&scene_changes.len()) => {
+                if differences.len() == times.len() {
+                    let (threshold, fitted) = scene_detector::fit_scene_count(&times, &differences, config.min_scene_duration, range);
+                    scene_span.in_scope(|| info!(
+                        before = scene_changes.len(),
+                        after = fitted.len(),
+                        threshold,
+                        range = %format!("{}-{}", range.start(), range.end()),
+                        "🎯 [视频处理] 场景数不在目标范围内，已调整阈值"
+                    ));
+                    // 保留原有切点的转场类型，新增的切点视为硬切
+                    let fitted_transitions = fitted
+                        .iter()
+                        .enumerate()
+                        .map(|(i, time)| match scene_changes.iter().position(|t| t == time) {
+                            Some(original) => transitions.get(original).copied().flatten(),
+                            None => (i > 0).then_some(TransitionType::Cut),
+                        })
+                        .collect();
+                    (fitted, fitted_transitions)
+                } else {
+                    scene_span.in_scope(|| warn!("⚠️  [视频处理] 场景检测没有返回帧差异，无法按目标场景数调整阈值"));
+                    (scene_changes, transitions)
+                }
+            }
+            _ => (scene_changes, transitions),
+        };
+        // 切点置信度按校准前的切点（采样帧时间）计算，与场景一一对应
+        let cut_scores = scene_detector::cut_scores(&times, &differences, &scene_changes);
+        // 按音频校准切点（可选）：失败时使用原始切点，不影响处理
+        let mut scene_changes = scene_changes;
+        if config.audio_boundary.enabled && media_kind == MediaKind::Video && scene_changes.len() > 1 && !detection_resumed {
+            let (boundary_input, boundary_config, changes) = (input.clone(), config.audio_boundary.clone(), scene_changes.clone());
+            let refine = run_blocking("音频边界校准", move || {
+                // 音频由 ffmpeg 命令行解码，需要完整的文件
+                if let Some(file) = boundary_input.progressive {
+                    info!("⏳ [视频处理] 等待视频文件下载完成后校准场景边界...");
+                    file.wait_complete()?;
+                }
+                let envelope = audio_analyzer::energy_envelope(&boundary_input.path, audio_boundary::ENVELOPE_HOP)?;
+                Ok(audio_boundary::refine_boundaries(&changes, &envelope, audio_boundary::ENVELOPE_HOP, &boundary_config))
+            }).instrument(scene_span.clone());
+            match refine.await {
+                Ok((refined, moved)) => {
+                    scene_span.in_scope(|| info!(moved, window = config.audio_boundary.window, "🎧 [视频处理] 按音频校准场景边界完成"));
+                    scene_changes = refined;
+                }
+                Err(e) => scene_span.in_scope(|| warn!(error = %e, "⚠️  [视频处理] 按音频校准场景边界失败，使用原始切点")),
+            }
+        }
+        let scene_duration = scene_start.elapsed() + streamed_detect;
+        monitor.end_stage(disk_bytes());
+        scene_span.record("scene_count", scene_changes.len());
+        progress.partial.scene_count = Some(scene_changes.len());
+        progress.report(StageProgress::new("detect_scenes", scene_changes.len() as u64, Some(scene_changes.len() as u64), ProgressUnit::Scenes));
+        scene_span.record("duration_ms", scene_duration.as_millis() as u64);
+        telemetry::record_stage("detect_scenes", scene_duration);
+        scene_span.in_scope(|| info!("✅ [视频处理] 场景检测完成"));
+        if let Some(checkpoint) = checkpoint.as_mut().filter(|_| !detection_resumed && media_kind == MediaKind::Video) {
+            checkpoint.detection = Some(DetectionCheckpoint {
+                fps,
+                width,
+                height,
+                sample_aspect_ratio,
+                duration,
+                times: times.clone(),
+                differences: differences.clone(),
+                scene_changes: scene_changes.clone(),
+                transitions: transitions.clone(),
+            });
+            checkpoint.persist();
+        }
+
+        // 5. 提取关键帧并保存
+        let total_duration = duration.unwrap_or_else(|| times.last().copied().unwrap_or(0.0));
+
+        // 检查是否有提取的帧（纯音频除外）
+        if times.is_empty() && media_kind != MediaKind::AudioOnly {
+            error!("❌ [视频处理] 没有提取到任何视频帧，无法提取关键帧");
+            anyhow::bail!("没有提取到任何视频帧，无法提取关键帧");
+        }
+
+        // 关键帧选择需要计算帧差异并编码 JPEG，同样放到阻塞线程池中执行
+        let keyframe_span = info_span!("keyframes", keyframe_count = Empty, duration_ms = Empty);
+        if config.keyframe_image.format.effective() != config.keyframe_image.format {
+            warn!("⚠️  已选择 AVIF 关键帧格式，但未启用 avif feature，使用 JPEG");
+        }
+        progress.begin("keyframes");
+        monitor.begin_stage("关键帧提取");
+        let keyframe_start = Instant::now();
+        let resumed_scenes = checkpoint.as_ref().and_then(|c| c.resumable_scenes(output_dir));
+        let keyframes_resumed = resumed_scenes.is_some();
+        let (selector, frame_source, stage_input, stage_config) =
+            (self.keyframe_selector.clone(), self.frame_source.clone(), input.clone(), config.clone());
+        let output_owned = output_dir.to_path_buf();
+        let select_keyframes = run_blocking("关键帧提取", move || {
+            let result = if times.is_empty() {
+                Ok(Vec::new())
+            } else if streamed {
+                // 按相邻帧差异选出关键帧，再按时间点重新提取
+                let plans = processor::plan_keyframes(&times, &differences, &scene_changes, total_duration);
+                let targets: Vec<f64> = plans.iter().map(|plan| plan.frame_time).collect();
+                frame_source.extract_at(&stage_input, &targets, &stage_config).and_then(|keyframes| {
+                    let result = processor::save_planned_keyframes(&plans, &keyframes, &output_owned, &stage_config.keyframe_image, &stage_config.naming);
+                    FramePool::shared().recycle_images(keyframes.into_iter().map(|(_, img)| img));
+                    result
+                })
+            } else {
+                selector.select_with_differences(&frames, &differences, &scene_changes, total_duration, &output_owned, &stage_config)
+            };
+            let result = result.map(|mut scenes| {
+                // 复用场景检测时计算的相邻帧差异
+                for scene in &mut scenes {
+                    scene.motion_score = scene_detector::motion_score(&times, &differences, scene.start_time, scene.end_time);
+                    scene.transition_type = transitions.get(scene.scene_id).copied().flatten();
+                    scene.cut_score = cut_scores.get(scene.scene_id).copied().flatten();
+                }
+                if let Some(similarity) = stage_config.dedupe_keyframes {
+                    let merged = processor::dedupe_keyframes(&output_owned, &mut scenes, similarity);
+                    info!(merged, similarity, "♻️  [视频处理] 关键帧去重完成");
+                }
+                if let Some(palette) = stage_config.color_palette {
+                    let analyzed = processor::analyze_colors(&output_owned, &mut scenes, palette);
+                    info!(analyzed, palette, "🎨 [视频处理] 场景颜色统计完成");
+                }
+                scenes
+            });
+            // 帧图像不再使用，像素缓冲区归还缓冲池供后续任务复用
+            FramePool::shared().recycle_images(frames.into_iter().map(|(_, img)| img));
+            result
+        }).instrument(keyframe_span.clone());
+        let mut scenes_metadata = match resumed_scenes {
+            Some(scenes) => {
+                keyframe_span.in_scope(|| info!("♻️  [视频处理] 从检查点恢复关键帧，跳过关键帧提取"));
+                scenes
+            }
+            None => select_keyframes.await?,
+        };
+        if let Some(checkpoint) = checkpoint.as_mut().filter(|c| !keyframes_resumed && c.detection.is_some()) {
+            // 关键帧重新生成后，之前上传的文件不再有效
+            checkpoint.scenes = Some(scenes_metadata.clone());
+            checkpoint.uploaded.clear();
+            checkpoint.persist();
+        }
+        // 去重合并的场景复用其他场景的关键帧，文件只列出一次
+        let keyframe_files: Vec<String> = scenes_metadata.iter()
+            .flat_map(|scene| {
+                scene.duplicate_of.is_none().then_some(&scene.keyframe_file).into_iter().chain(&scene.extra_keyframes).cloned()
+            })
+            .collect();
+        let keyframe_duration = keyframe_start.elapsed();
+        monitor.end_stage(disk_bytes());
+        keyframe_span.record("keyframe_count", keyframe_files.len());
+        progress.partial.keyframes = keyframe_files.clone();
+        progress.report(StageProgress::new("keyframes", keyframe_files.len() as u64, Some(keyframe_files.len() as u64), ProgressUnit::Files));
+        keyframe_span.record("duration_ms", keyframe_duration.as_millis() as u64);
+        telemetry::record_stage("keyframes", keyframe_duration);
+        keyframe_span.in_scope(|| info!("✅ [视频处理] 关键帧提取完成"));
+
+        // 识别关键帧中的文字（可选）
+        let mut ocr_duration = None;
+        let recognizer = match self.text_recognizer {
+            Some(ref recognizer) => Some(recognizer.clone()),
+            None if config.ocr.enabled => ocr::default_recognizer(&config.ocr),
+            None => None,
+        };
+        if let Some(recognizer) = recognizer {
+            let ocr_span = info_span!("ocr", recognizer = recognizer.name(), recognized = Empty, duration_ms = Empty);
+            progress.begin("ocr");
+            monitor.begin_stage("文字识别");
+            let ocr_start = Instant::now();
+            let recognized = ocr::recognize_scenes(recognizer.as_ref(), output_dir, &mut scenes_metadata)
+                .instrument(ocr_span.clone())
+                .await?;
+            let duration = ocr_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            ocr_span.record("recognized", recognized);
+            ocr_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("ocr", duration);
+            ocr_span.in_scope(|| info!("✅ [视频处理] 文字识别完成"));
+            ocr_duration = Some(duration);
+        }
+
+        // 检测关键帧中的人脸（可选）
+        let mut faces_duration = None;
+        if config.faces.enabled {
+            let faces_span = info_span!("faces", scenes_with_faces = Empty, duration_ms = Empty);
+            progress.begin("faces");
+            monitor.begin_stage("人脸检测");
+            let faces_start = Instant::now();
+            let face_config = config.faces.clone();
+            let output_owned = output_dir.to_path_buf();
+            let (scenes, with_faces) = run_blocking("人脸检测", move || {
+                let with_faces = faces::detect_scenes(&face_config, &output_owned, &mut scenes_metadata)?;
+                Ok((scenes_metadata, with_faces))
+            }).instrument(faces_span.clone()).await?;
+            scenes_metadata = scenes;
+            let duration = faces_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            faces_span.record("scenes_with_faces", with_faces);
+            faces_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("faces", duration);
+            faces_span.in_scope(|| info!("✅ [视频处理] 人脸检测完成"));
+            faces_duration = Some(duration);
+        }
+
+        // 估计场景景别（可选），在人脸检测之后以便参考人脸大小
+        if config.shot_type {
+            let output_owned = output_dir.to_path_buf();
+            let (scenes, classified) = run_blocking("景别估计", move || {
+                let classified = shot_type::classify_scenes(&output_owned, &mut scenes_metadata);
+                Ok((scenes_metadata, classified))
+            }).await?;
+            scenes_metadata = scenes;
+            info!(classified, "🎬 [视频处理] 景别估计完成");
+        }
+
+        // 关键帧内容安全分类（可选）
+        let mut safety_duration = None;
+        if config.safety.enabled {
+            let safety_span = info_span!("safety", flagged_scenes = Empty, duration_ms = Empty);
+            progress.begin("safety");
+            monitor.begin_stage("内容安全检测");
+            let safety_start = Instant::now();
+            let safety_config = config.safety.clone();
+            let output_owned = output_dir.to_path_buf();
+            let (scenes, flagged) = run_blocking("内容安全检测", move || {
+                let flagged = safety::classify_scenes(&safety_config, &output_owned, &mut scenes_metadata)?;
+                Ok((scenes_metadata, flagged))
+            }).instrument(safety_span.clone()).await?;
+            scenes_metadata = scenes;
+            let duration = safety_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            safety_span.record("flagged_scenes", flagged);
+            safety_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("safety", duration);
+            safety_span.in_scope(|| info!("✅ [视频处理] 内容安全检测完成"));
+            safety_duration = Some(duration);
+        }
+
+        // 计算关键帧图像向量（可选）
+        let mut embeddings_duration = None;
+        let mut embeddings_index = None;
+        if config.embeddings.enabled {
+            let embeddings_span = info_span!("embeddings", count = Empty, dimensions = Empty, duration_ms = Empty);
+            progress.begin("embeddings");
+            monitor.begin_stage("图像向量");
+            let embeddings_start = Instant::now();
+            let embedding_config = config.embeddings.clone();
+            let output_owned = output_dir.to_path_buf();
+            let (scenes, index) = run_blocking("图像向量", move || {
+                let index = embeddings::embed_scenes(&embedding_config, &output_owned, &scenes_metadata)?;
+                Ok((scenes_metadata, index))
+            }).instrument(embeddings_span.clone()).await?;
+            scenes_metadata = scenes;
+            let duration = embeddings_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            if let Some(ref index) = index {
+                embeddings_span.record("count", index.count);
+                embeddings_span.record("dimensions", index.dimensions);
+            }
+            embeddings_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("embeddings", duration);
+            embeddings_span.in_scope(|| info!("✅ [视频处理] 图像向量计算完成"));
+            embeddings_duration = Some(duration);
+            embeddings_index = index;
+        }
+
+        // 调用外部模型标注关键帧（可选）
+        let mut enrichment_duration = None;
+        if config.enrichment.is_enabled() {
+            let enrichment_span = info_span!("enrichment", enriched = Empty, duration_ms = Empty);
+            progress.begin("enrichment");
+            monitor.begin_stage("关键帧标注");
+            let enrichment_start = Instant::now();
+            let enriched = enrichment::enrich_scenes(&config.enrichment, &job_id, output_dir, &mut scenes_metadata)
+                .instrument(enrichment_span.clone())
+                .await?;
+            let duration = enrichment_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            enrichment_span.record("enriched", enriched);
+            enrichment_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("enrichment", duration);
+            enrichment_span.in_scope(|| info!("✅ [视频处理] 关键帧标注完成"));
+            enrichment_duration = Some(duration);
+        }
+
+        // 关键帧分类标签（流水线添加了分类器时）
+        let mut annotation_duration = None;
+        if !self.annotators.is_empty() {
+            let annotation_span = info_span!("annotation", annotators = self.annotators.len(), tagged = Empty, duration_ms = Empty);
+            progress.begin("annotation");
+            monitor.begin_stage("关键帧分类");
+            let annotation_start = Instant::now();
+            let tagged = annotator::annotate_scenes(&self.annotators, output_dir, &mut scenes_metadata)
+                .instrument(annotation_span.clone())
+                .await?;
+            let duration = annotation_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            annotation_span.record("tagged", tagged);
+            annotation_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("annotation", duration);
+            annotation_span.in_scope(|| info!("✅ [视频处理] 关键帧分类完成"));
+            annotation_duration = Some(duration);
+        }
+
+        // 生成关键帧缩略图拼图和 WebVTT（可选）
+        let mut sprite_duration = None;
+        let mut sprite_sheet = None;
+        if config.sprite.enabled {
+            let sprite_span = info_span!("sprite", tiles = Empty, duration_ms = Empty);
+            progress.begin("sprite");
+            monitor.begin_stage("缩略图拼图");
+            let sprite_start = Instant::now();
+            let sprite_config = config.sprite.clone();
+            let output_owned = output_dir.to_path_buf();
+            let (scenes, sheet) = run_blocking("缩略图拼图", move || {
+                let sheet = sprite::write_sprite(&sprite_config, &output_owned, &scenes_metadata)?;
+                Ok((scenes_metadata, sheet))
+            }).instrument(sprite_span.clone()).await?;
+            scenes_metadata = scenes;
+            let duration = sprite_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            sprite_span.record("tiles", sheet.as_ref().map_or(0, |sheet| sheet.tiles));
+            sprite_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("sprite", duration);
+            sprite_span.in_scope(|| info!("✅ [视频处理] 缩略图拼图生成完成"));
+            sprite_duration = Some(duration);
+            sprite_sheet = sheet;
+        }
+
+        // 导出每个场景的视频片段（可选，只对视频）
+        let mut clips_duration = None;
+        if config.clips.enabled && media_kind == MediaKind::Video && !scenes_metadata.is_empty() {
+            let clips_span = info_span!("clips", mode = config.clips.mode.as_str(), clip_count = Empty, duration_ms = Empty);
+            progress.begin("clips");
+            monitor.begin_stage("场景片段导出");
+            let clips_start = Instant::now();
+            let (clip_config, clip_input, output_owned) = (config.clips.clone(), input.clone(), output_dir.to_path_buf());
+            let (scenes, exported) = run_blocking("场景片段导出", move || {
+                // 片段由 ffmpeg 命令行切割，需要完整的文件
+                if let Some(file) = clip_input.progressive {
+                    info!("⏳ [视频处理] 等待视频文件下载完成后导出场景片段...");
+                    file.wait_complete()?;
+                }
+                let exported = clips::export_clips(&clip_config, &clip_input.path, &output_owned, &mut scenes_metadata)?;
+                Ok((scenes_metadata, exported))
+            }).instrument(clips_span.clone()).await?;
+            scenes_metadata = scenes;
+            let duration = clips_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            clips_span.record("clip_count", exported);
+            clips_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("clips", duration);
+            clips_span.in_scope(|| info!("✅ [视频处理] 场景片段导出完成"));
+            clips_duration = Some(duration);
+        }
+
+        // 生成每个场景的预览动图（可选，只对视频）
+        let mut preview_duration = None;
+        if config.preview.enabled && media_kind == MediaKind::Video && !scenes_metadata.is_empty() {
+            let preview_span = info_span!("preview", format = config.preview.format.as_str(), preview_count = Empty, duration_ms = Empty);
+            progress.begin("preview");
+            monitor.begin_stage("预览动图生成");
+            let preview_start = Instant::now();
+            let (preview_config, preview_input, output_owned) = (config.preview.clone(), input.clone(), output_dir.to_path_buf());
+            let (scenes, generated) = run_blocking("预览动图生成", move || {
+                // 预览由 ffmpeg 命令行渲染，需要完整的文件
+                if let Some(file) = preview_input.progressive {
+                    info!("⏳ [视频处理] 等待视频文件下载完成后生成预览动图...");
+                    file.wait_complete()?;
+                }
+                let generated = preview::generate_previews(&preview_config, &preview_input.path, &output_owned, &mut scenes_metadata)?;
+                Ok((scenes_metadata, generated))
+            }).instrument(preview_span.clone()).await?;
+            scenes_metadata = scenes;
+            let duration = preview_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            preview_span.record("preview_count", generated);
+            preview_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("preview", duration);
+            preview_span.in_scope(|| info!("✅ [视频处理] 预览动图生成完成"));
+            preview_duration = Some(duration);
+        }
+
+        // 6. 提取音频（单张图片没有音频，音频编码为 none 时不提取）
+        let audio_name = config.audio.file_name(&config.naming).filter(|_| media_kind != MediaKind::StillImage);
+        let has_audio = audio_name.is_some();
+        let audio_filename = audio_name.as_deref().unwrap_or_default();
+        let audio_path = output_dir.join(audio_filename);
+        let audio_span = info_span!("audio", audio_file = %audio_path.display(), duration_ms = Empty);
+        progress.begin("audio");
+        monitor.begin_stage("音频提取");
+        let audio_start = Instant::now();
+        let (audio_input, audio_config) = (input.clone(), config.audio.clone());
+        let audio_path_owned = audio_path.clone();
+        let extract_audio = run_blocking("音频提取", move || {
+            // 音频由 ffmpeg 命令行提取，需要完整的文件
+            if let Some(file) = audio_input.progressive {
+                info!("⏳ [视频处理] 等待视频文件下载完成后提取音频...");
+                file.wait_complete()?;
+            }
+            let audio_extractor = AudioExtractor::new(&audio_input.path)
+                .map_err(|e| {
+                    error!(error = %e, "❌ [视频处理] 创建音频提取器失败");
+                    e
+                })?;
+            audio_extractor.extract_with_config(&audio_path_owned, &audio_config)
+                .map_err(|e| {
+                    error!(error = %e, "❌ [视频处理] 提取音频失败");
+                    e.into()
+                })
+        }).instrument(audio_span.clone());
+        let audio_resumed = has_audio
+            && audio_path.exists()
+            && checkpoint.as_ref().is_some_and(|c| c.audio_file.as_deref() == Some(audio_filename));
+        if audio_resumed {
+            audio_span.in_scope(|| info!("♻️  [视频处理] 从检查点恢复音频文件，跳过音频提取"));
+        } else if has_audio {
+            extract_audio.await?;
+            if let Some(checkpoint) = checkpoint.as_mut() {
+                checkpoint.audio_file = Some(audio_filename.to_string());
+                checkpoint.persist();
+            }
+        } else if media_kind == MediaKind::StillImage {
+            audio_span.in_scope(|| info!("🖼️  [视频处理] 输入为单张图片，跳过音频提取"));
+        } else {
+            audio_span.in_scope(|| info!("🔇 [视频处理] 音频编码为 none，跳过音频提取"));
+        }
+        let audio_duration = audio_start.elapsed();
+        monitor.end_stage(disk_bytes());
+        audio_span.record("duration_ms", audio_duration.as_millis() as u64);
+        telemetry::record_stage("audio", audio_duration);
+        audio_span.in_scope(|| info!("✅ [视频处理] 音频提取完成"));
+
+        // 6.1 音频波形和响度分析（可选）
+        let mut waveform_duration = None;
+        let mut loudness = None;
+        if config.waveform && has_audio {
+            let waveform_span = info_span!("waveform", integrated_lufs = Empty, duration_ms = Empty);
+            progress.begin("waveform");
+            monitor.begin_stage("音频波形分析");
+            let waveform_start = Instant::now();
+            let (waveform_audio, output_owned) = (audio_path.clone(), output_dir.to_path_buf());
+            let stats = run_blocking("音频波形分析", move || audio_analyzer::analyze_audio(&waveform_audio, &output_owned))
+                .instrument(waveform_span.clone())
+                .await?;
+            let duration = waveform_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            if let Some(lufs) = stats.integrated_lufs {
+                waveform_span.record("integrated_lufs", lufs);
+            }
+            waveform_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("waveform", duration);
+            waveform_span.in_scope(|| info!(peak_dbfs = stats.peak_dbfs, "✅ [视频处理] 音频波形分析完成"));
+            waveform_duration = Some(duration);
+            loudness = Some(stats);
+        }
+
+        // 6.2 语音活动检测（可选）
+        let mut vad_duration = None;
+        let mut audio_segments = Vec::new();
+        if config.vad.enabled && has_audio {
+            let vad_span = info_span!("vad", segment_count = Empty, duration_ms = Empty);
+            progress.begin("vad");
+            monitor.begin_stage("语音活动检测");
+            let vad_start = Instant::now();
+            let (vad_audio, vad_config) = (audio_path.clone(), config.vad.clone());
+            let detect = run_blocking("语音活动检测", move || {
+                let envelope = audio_analyzer::energy_envelope(&vad_audio, vad::VAD_HOP)?;
+                Ok(vad::detect_segments(&envelope, vad::VAD_HOP, &vad_config))
+            }).instrument(vad_span.clone());
+            // 语音检测失败不影响处理结果
+            match detect.await {
+                Ok(segments) => audio_segments = segments,
+                Err(e) => vad_span.in_scope(|| warn!(error = %e, "⚠️  [视频处理] 语音活动检测失败")),
+            }
+            let duration = vad_start.elapsed();
+            monitor.end_stage(disk_bytes());
+            let speech = audio_segments.iter().filter(|s| s.kind == vad::SegmentKind::Speech).count();
+            vad_span.record("segment_count", audio_segments.len());
+            vad_span.record("duration_ms", duration.as_millis() as u64);
+            telemetry::record_stage("vad", duration);
+            vad_span.in_scope(|| info!(speech, "✅ [视频处理] 语音活动检测完成"));
+            vad_duration = Some(duration);
+        }
+
+        // 7. 生成元数据并写出输出文件
+        progress.begin("metadata");
+        monitor.begin_stage("元数据生成");
+        let metadata_start = Instant::now();
+        let metadata = VideoMetadata {
+            input_video: input.path.to_string_lossy().to_string(),
+            media_kind,
+            limitations: media_kind.limitations(),
+            total_duration,
+            fps,
+            resolution: format!("{}x{}", display_width, display_height),
+            storage_resolution: (!sample_aspect_ratio.is_square()).then(|| format!("{}x{}", width, height)),
+            sample_aspect_ratio: (!sample_aspect_ratio.is_square()).then(|| sample_aspect_ratio.to_string()),
+            scene_count: scenes_metadata.len(),
+            audio_file: audio_filename.to_string(),
+            safety: config.safety.summarize(&scenes_metadata),
+            faces: faces::summarize(&scenes_metadata),
+            embeddings: embeddings_index,
+            sprite: sprite_sheet,
+            loudness,
+            audio_segments,
+            exports: config.export_formats.iter().map(|format| format.file_name()).collect(),
+            fingerprint_file: config.fingerprint.then(|| fingerprint::FINGERPRINT_FILE.to_string()),
+            scenes: scenes_metadata,
+            source_hash: content_hashes.as_ref().map(|(hash, _)| hash.clone()).or(source_hash),
+            config_fingerprint: content_hashes.map(|(_, fingerprint)| fingerprint),
+            metadata_file: config.naming.metadata_file(),
+        };
+
+        let mut writers = self.writers.clone();
+        writers.extend(
+            config.export_formats.iter().map(|&format| Arc::new(MetadataExporter::new(format)) as Arc<dyn ArtifactWriter>),
+        );
+        if config.fingerprint {
+            writers.push(Arc::new(FingerprintWriter));
+        }
+        if config.viewer {
+            writers.push(Arc::new(ViewerWriter));
+        }
+        writers.extend(config.archive.map(|format| Arc::new(format) as Arc<dyn ArtifactWriter>));
+        let writer_count = writers.len();
+        let output_owned = output_dir.to_path_buf();
+        let metadata = run_blocking("元数据生成", move || {
+            for writer in &writers {
+                writer.write(&metadata, &output_owned)
+                    .context(format!("写出 {} 失败", writer.name()))?;
+            }
+            Ok(metadata)
+        }).await?;
+        monitor.end_stage(disk_bytes());
+        let metadata_duration = metadata_start.elapsed();
+        debug!(
+            writers = writer_count,
+            duration_ms = metadata_duration.as_millis() as u64,
+            "✅ [视频处理] 元数据生成完成"
+        );
+
+        // 总结
+        let total_duration = total_start.elapsed();
+        let resources = monitor.finish();
+        info!(
+            total_ms = total_duration.as_millis() as u64,
+            extract_ms = extract_duration.as_millis() as u64,
+            detect_ms = scene_duration.as_millis() as u64,
+            keyframes_ms = keyframe_duration.as_millis() as u64,
+            audio_ms = audio_duration.as_millis() as u64,
+            metadata_ms = metadata_duration.as_millis() as u64,
+            scene_count = metadata.scene_count,
+            keyframe_count = keyframe_files.len(),
+            peak_rss_bytes = resources.peak_rss_bytes,
+            peak_disk_bytes = resources.peak_disk_bytes,
+            suggested_memory_mb = resources.suggested_memory_mb(),
+            "🎉 [视频处理] 处理完成"
+        );
+        for stage in &resources.stages {
+            debug!(
+                stage = %stage.stage,
+                peak_rss = %format_mb(stage.peak_rss_bytes),
+                allocated = %format_mb(stage.allocated_bytes),
+                disk = %format_mb(stage.disk_bytes),
+                "💾 [视频处理] 阶段资源占用"
+            );
+        }
+
+        let timings = TimingBreakdown {
+            extract_ms: extract_duration.as_millis() as u64,
+            detect_ms: scene_duration.as_millis() as u64,
+            keyframe_ms: keyframe_duration.as_millis() as u64,
+            ocr_ms: ocr_duration.map(|d| d.as_millis() as u64),
+            faces_ms: faces_duration.map(|d| d.as_millis() as u64),
+            safety_ms: safety_duration.map(|d| d.as_millis() as u64),
+            embeddings_ms: embeddings_duration.map(|d| d.as_millis() as u64),
+            enrichment_ms: enrichment_duration.map(|d| d.as_millis() as u64),
+            annotation_ms: annotation_duration.map(|d| d.as_millis() as u64),
+            sprite_ms: sprite_duration.map(|d| d.as_millis() as u64),
+            clips_ms: clips_duration.map(|d| d.as_millis() as u64),
+            preview_ms: preview_duration.map(|d| d.as_millis() as u64),
+            waveform_ms: waveform_duration.map(|d| d.as_millis() as u64),
+            vad_ms: vad_duration.map(|d| d.as_millis() as u64),
+            audio_ms: audio_duration.as_millis() as u64,
+            metadata_ms: metadata_duration.as_millis() as u64,
+            process_ms: total_duration.as_millis() as u64,
+            total_ms: total_duration.as_millis() as u64,
+            ..Default::default()
+        };
+
+        // 记录实际输出目录，可通过 GET /outputs/{job_id}/{filename} 读取输出文件
+        output_naming::OutputDirs::shared().record(&job_id, output_dir);
+
+        let mut result = ProcessOutput {
+            job_id,
+            output_dir: output_dir.to_path_buf(),
+            metadata,
+            keyframe_files,
+            audio_file: audio_filename.to_string(),
+            metadata_file: config.naming.metadata_file(),
+            archive_file: config.archive.map(|format| format.file_name()),
+            resources,
+            timings,
+            webhook: None,
+        };
+
+        // 发布结果（默认为 webhook 回调，如果配置了）
+        for publisher in &self.publishers {
+            if let Err(e) = publisher.publish(&mut result, &config).await {
+                warn!(publisher = publisher.name(), error = %e, "⚠️  [视频处理] 发布处理结果失败");
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// 帧提取阶段的结果
+enum Extracted {
+    /// 全部采样帧
+    Frames(DecodedVideo),
+    /// 逐帧检测场景后的结果，不包含帧图像
+    Streamed(StreamedVideo),
+}
+
+/// 逐帧检测场景后保留的视频信息、采样时间戳、相邻帧差异和场景起始时间
+struct StreamedVideo {
+    fps: f64,
+    width: u32,
+    height: u32,
+    sample_aspect_ratio: SampleAspectRatio,
+    duration: Option<f64>,
+    times: Vec<f64>,
+    differences: Vec<f64>,
+    scene_changes: Vec<f64>,
+    transitions: Vec<Option<TransitionType>>,
+    /// 场景检测累计耗时
+    detect_duration: Duration,
+}
+
+impl From<DetectionCheckpoint> for StreamedVideo {
+    fn from(detection: DetectionCheckpoint) -> Self {
+        Self {
+            fps: detection.fps,
+            width: detection.width,
+            height: detection.height,
+            sample_aspect_ratio: detection.sample_aspect_ratio,
+            duration: detection.duration,
+            times: detection.times,
+            differences: detection.differences,
+            scene_changes: detection.scene_changes,
+            transitions: detection.transitions,
+            detect_duration: Duration::ZERO,
+        }
+    }
+}
+
+/// 边提取边检测场景，每帧检测后立即把像素缓冲区归还缓冲池
+///
+/// 每提取一帧向 `reporter` 报告一次帧数
+fn detect_stream(stream: FrameStream, config: &ProcessConfig, reporter: Option<&dyn ProgressReporter>) -> Result<StreamedVideo> {
+    let detector = config.scene_detector();
+    let mut scenes = detector.stream();
+    let mut times = Vec::new();
+    let mut detect_duration = Duration::ZERO;
+    let pool = FramePool::shared();
+    // 目标时间点数，找不到帧的时间点会被跳过，实际帧数可能更少
+    let expected = stream.frames.size_hint().1.map(|n| n as u64);
+    for frame in stream.frames {
+        let (time, image) = frame?;
+        let detect_start = Instant::now();
+        scenes.push(time, &image);
+        detect_duration += detect_start.elapsed();
+        times.push(time);
+        pool.recycle_image(image);
+        if let Some(reporter) = reporter {
+            let total = expected.map(|n| n.max(times.len() as u64));
+            reporter.update(&StageProgress::new("extract_frames", times.len() as u64, total, ProgressUnit::Frames));
+        }
+    }
+    let (scene_changes, differences, transitions) = if times.is_empty() {
+        (Vec::new(), Vec::new(), Vec::new())
+    } else {
+        scenes.finish_with_transitions()
+    };
+    Ok(StreamedVideo {
+        fps: stream.fps,
+        width: stream.width,
+        height: stream.height,
+        sample_aspect_ratio: stream.sample_aspect_ratio,
+        duration: stream.duration,
+        times,
+        differences,
+        scene_changes,
+        transitions,
+        detect_duration,
+    })
+}
+
+/// 等待取消令牌被取消，未设置令牌时永远不会完成
+async fn cancelled(cancel: Option<&CancellationToken>) {
+    match cancel {
+        Some(cancel) => cancel.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每 10 秒切分一个场景的检测
+    struct FixedDetector;
+
+    impl Detector for FixedDetector {
+        fn detect(&self, frames: &[(f64, DynamicImage)], _fps: f64, _config: &ProcessConfig) -> Result<Vec<f64>> {
+            let end = frames.last().map(|(t, _)| *t).unwrap_or(0.0);
+            Ok((0..).map(|i| i as f64 * 10.0).take_while(|t| *t <= end).collect())
+        }
+    }
+
+    struct CountWriter;
+
+    impl ArtifactWriter for CountWriter {
+        fn name(&self) -> &str {
+            "count.txt"
+        }
+
+        fn write(&self, metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+            std::fs::write(output_dir.join("count.txt"), metadata.scene_count.to_string())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pipeline_stages() {
+        let pipeline = Pipeline::new()
+            .with_detector(FixedDetector)
+            .with_writer(CountWriter)
+            .without_publishers();
+        assert_eq!(pipeline.writers.len(), 2);
+        assert!(pipeline.publishers.is_empty());
+
+        let frames: Vec<_> = (0..=25)
+            .map(|t| (t as f64, DynamicImage::new_rgb8(4, 4)))
+            .collect();
+        let config = ProcessConfig::default();
+        let scenes = pipeline.detector.detect(&frames, 1.0, &config).unwrap();
+        assert_eq!(scenes, vec![0.0, 10.0, 20.0]);
+
+        let dir = std::env::temp_dir().join(format!("video-parse-pipeline-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let scenes = pipeline.keyframe_selector
+            .select(&frames, &scenes, 25.0, &dir, &config)
+            .unwrap();
+        assert_eq!(scenes.len(), 3);
+        assert!(dir.join(&scenes[0].keyframe_file).exists());
+
+        // 最后一个场景只有 20-24 秒的 5 帧，两侧分段的中点 20.83 / 24.17 附近各取一帧
+        let spread = SpreadFrameSelector { per_scene: 3 }
+            .select(&frames, &[0.0, 10.0, 20.0], 25.0, &dir, &config)
+            .unwrap();
+        assert_eq!(spread[2].extra_keyframes, vec!["keyframe_0002_1.jpg", "keyframe_0002_2.jpg"]);
+        assert!(dir.join("keyframe_0002_2.jpg").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// 输入为单张图片的帧来源
+    struct StillImageSource;
+
+    impl FrameSource for StillImageSource {
+        fn extract(&self, _input: &VideoInput, _config: &ProcessConfig) -> Result<DecodedVideo> {
+            Ok(DecodedVideo {
+                fps: 0.0,
+                width: 8,
+                height: 8,
+                sample_aspect_ratio: SampleAspectRatio::SQUARE,
+                media_kind: MediaKind::StillImage,
+                duration: Some(0.0),
+                frames: vec![(0.0, DynamicImage::new_rgb8(8, 8))],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_still_image_input() {
+        let pipeline = Pipeline::new().with_frame_source(StillImageSource).without_publishers();
+        let dir = std::env::temp_dir().join(format!("video-parse-still-{}", uuid::Uuid::new_v4()));
+        let output = pipeline.run("cover.jpg", &dir, ProcessConfig::default()).await.unwrap();
+        assert_eq!(output.metadata.media_kind, MediaKind::StillImage);
+        assert_eq!(output.metadata.scene_count, 1);
+        assert!(output.audio_file.is_empty());
+        assert!(!output.metadata.limitations.is_empty());
+        assert!(dir.join(&output.keyframe_files[0]).exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_output() {
+        let pipeline = Pipeline::new().with_frame_source(StillImageSource).without_publishers();
+        let dir = std::env::temp_dir().join(format!("video-parse-content-{}", uuid::Uuid::new_v4()));
+        let config = ProcessConfig { content_addressed: true, ..ProcessConfig::default() };
+        let fingerprint = config.fingerprint();
+        let options = ProcessOptions::default().with_source_hash("5eb63bbb");
+        let output = pipeline.run_with_options("cover.jpg", &dir, config, options).await.unwrap();
+        assert_eq!(output.output_dir, dir.join("5eb63bbb").join(&fingerprint));
+        assert!(output.output_dir.join("metadata.json").exists());
+        assert_eq!(output.metadata.source_hash.as_deref(), Some("5eb63bbb"));
+        assert_eq!(output.metadata.config_fingerprint, Some(fingerprint));
+
+        // 不按内容寻址时仍记录调用方提供的源文件哈希，用于判断目标位置的结果是否属于同一内容
+        let flat_dir = dir.join("flat");
+        let options = ProcessOptions::default().with_source_hash("5eb63bbb");
+        let output = pipeline.run_with_options("cover.jpg", &flat_dir, ProcessConfig::default(), options).await.unwrap();
+        assert_eq!(output.output_dir, flat_dir);
+        assert_eq!(output.metadata.source_hash.as_deref(), Some("5eb63bbb"));
+        assert_eq!(output.metadata.config_fingerprint, None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// 前 10 秒为深蓝色场景、后 10 秒为黄色场景的帧来源，支持逐帧提取
+    struct TwoSceneSource;
+
+    impl TwoSceneSource {
+        fn frame(time: f64) -> (f64, DynamicImage) {
+            let pixel = if time < 10.0 { [10 + time as u8, 10, 60] } else { [250, 220 + time as u8, 40] };
+            (time, DynamicImage::ImageRgb8(image::RgbImage::from_pixel(16, 16, image::Rgb(pixel))))
+        }
+    }
+
+    impl FrameSource for TwoSceneSource {
+        fn extract(&self, _input: &VideoInput, _config: &ProcessConfig) -> Result<DecodedVideo> {
+            Ok(DecodedVideo {
+                fps: 1.0,
+                width: 16,
+                height: 16,
+                sample_aspect_ratio: SampleAspectRatio::SQUARE,
+                media_kind: MediaKind::Video,
+                duration: Some(20.0),
+                frames: (0..20).map(|t| Self::frame(t as f64)).collect(),
+            })
+        }
+
+        fn stream(&self, _input: &VideoInput, _config: &ProcessConfig) -> Result<Option<FrameStream>> {
+            Ok(Some(FrameStream {
+                fps: 1.0,
+                width: 16,
+                height: 16,
+                sample_aspect_ratio: SampleAspectRatio::SQUARE,
+                duration: Some(20.0),
+                frames: Box::new((0..20).map(|t| Ok(Self::frame(t as f64)))),
+            }))
+        }
+
+        fn extract_at(&self, _input: &VideoInput, times: &[f64], _config: &ProcessConfig) -> Result<Vec<(f64, DynamicImage)>> {
+            Ok(times.iter().map(|&t| Self::frame(t)).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_matches_batch() {
+        let dir = std::env::temp_dir().join(format!("video-parse-stream-{}", uuid::Uuid::new_v4()));
+        // 帧来源是合成的，不提取音频
+        let config = || ProcessConfig {
+            audio: crate::AudioConfig { codec: crate::AudioCodec::None, ..Default::default() },
+            ..ProcessConfig::default()
+        };
+        let run = |pipeline: Pipeline, dir: PathBuf| async move {
+            let input = VideoInput { path: PathBuf::from("two-scenes.mp4"), progressive: None };
+            let mut progress = Progress::new("prepare");
+            pipeline.without_publishers().execute(input, &dir, config(), None, &mut progress).await.unwrap()
+        };
+        let streamed = run(Pipeline::new().with_frame_source(TwoSceneSource), dir.join("stream")).await;
+        let batch = run(
+            Pipeline::new().with_frame_source(TwoSceneSource).with_keyframe_selector(StableFrameSelector),
+            dir.join("batch"),
+        ).await;
+        assert_eq!(streamed.metadata.scene_count, 2);
+        assert_eq!(streamed.audio_file, "");
+        let times = |output: &ProcessOutput| -> Vec<(f64, f64)> {
+            output.metadata.scenes.iter().map(|scene| (scene.start_time, scene.end_time)).collect()
+        };
+        assert_eq!(times(&streamed), times(&batch));
+        assert_eq!(streamed.keyframe_files, batch.keyframe_files);
+        for keyframe in &streamed.keyframe_files {
+            let (a, b) = (streamed.output_dir.join(keyframe), batch.output_dir.join(keyframe));
+            assert_eq!(std::fs::read(a).unwrap(), std::fs::read(b).unwrap());
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// 提取帧需要一段时间的帧来源
+    struct SlowFrameSource;
+
+    impl FrameSource for SlowFrameSource {
+        fn extract(&self, _input: &VideoInput, _config: &ProcessConfig) -> Result<DecodedVideo> {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            anyhow::bail!("不应等到帧提取完成")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_cancel() {
+        let pipeline = Pipeline::new().with_frame_source(SlowFrameSource);
+        let dir = std::env::temp_dir().join(format!("video-parse-cancel-{}", uuid::Uuid::new_v4()));
+
+        let options = ProcessOptions::default().with_timeout(std::time::Duration::from_millis(20));
+        let error = pipeline
+            .run_with_options("missing.mp4", &dir, ProcessConfig::default(), options)
+            .await
+            .unwrap_err();
+        assert_eq!(error.as_timeout().unwrap().stage, Stage::Process);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let options = ProcessOptions::default().with_cancel(cancel);
+        let error = pipeline
+            .run_with_options("missing.mp4", &dir, ProcessConfig::default(), options)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, VideoParseError::Cancelled));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
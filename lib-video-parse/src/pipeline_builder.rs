@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+use crate::config_builder::ProcessConfigBuilder;
+use crate::error::{Result, VideoParseError};
+use crate::oss_client::{OssClient, UploadTask, DEFAULT_UPLOAD_CONCURRENCY};
+use crate::pipeline::{Pipeline, SpreadFrameSelector};
+use crate::preset::Preset;
+use crate::processor::{ProcessConfig, ProcessOptions, ProcessOutput};
+use crate::progress::ProgressReporter;
+use crate::{output_naming, url_input, webhook};
+
+/// 未设置输入大小上限时，HTTP / HTTPS 输入允许下载的最大字节数（与 `url_max_size_mb` 的默认值相同）
+const DEFAULT_URL_MAX_BYTES: u64 = 2048 * 1024 * 1024;
+
+/// 输出文件的 OSS 上传位置
+///
+/// 可以从 `oss://bucket/prefix` 解析
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OssStorage {
+    pub bucket: String,
+    /// 对象键前缀（不含首尾的 `/`），为空时上传到 bucket 根目录
+    pub prefix: String,
+    /// OSS endpoint，未设置时使用 OSS_ENDPOINT 环境变量
+    pub endpoint: Option<String>,
+    /// 同时上传的文件数
+    pub concurrency: usize,
+}
+
+impl OssStorage {
+    pub fn new(bucket: impl Into<String>) -> Self {
+        Self { bucket: bucket.into(), prefix: String::new(), endpoint: None, concurrency: DEFAULT_UPLOAD_CONCURRENCY }
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// 使用 region 的外网 endpoint（`oss-{region}.aliyuncs.com`）
+    pub fn with_region(mut self, region: &str) -> Self {
+        self.endpoint = Some(format!("oss-{}.aliyuncs.com", region));
+        self
+    }
+
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// 输出文件上传后的对象键
+    pub fn object_key(&self, file_name: &str) -> String {
+        if self.prefix.is_empty() {
+            file_name.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_name)
+        }
+    }
+}
+
+impl FromStr for OssStorage {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (bucket, prefix) = webhook::parse_oss_location(s)
+            .ok_or_else(|| format!("无效的 OSS 位置: {}（格式: oss://bucket/prefix）", s))?;
+        Ok(Self::new(bucket).with_prefix(prefix))
+    }
+}
+
+/// [`VideoParsePipeline::run`] 的结果
+#[derive(Debug, Clone)]
+pub struct PipelineOutput {
+    pub output: ProcessOutput,
+    /// 已上传的文件：文件名 → 对象键（设置了上传位置时）
+    pub uploaded: BTreeMap<String, String>,
+}
+
+/// 嵌入调用方使用的处理入口：链式设置输入、参数、输出目录和上传位置后调用 [`run`](Self::run)
+///
+/// 例如 `VideoParsePipeline::new(input).threshold(0.3).keyframes_per_scene(3).output(dir).storage(oss).run().await`。
+/// 输入可以是本地文件、HTTP / HTTPS URL 或 `oss://bucket/key`，远程输入下载到临时目录，处理完成后删除。
+/// 参数在 `run()` 时按 [`ProcessConfig::validate`] 检查
+pub struct VideoParsePipeline {
+    input: String,
+    output_dir: Option<PathBuf>,
+    config: ProcessConfigBuilder,
+    keyframes_per_scene: usize,
+    storage: Option<OssStorage>,
+    pipeline: Option<Pipeline>,
+    options: ProcessOptions,
+}
+
+impl VideoParsePipeline {
+    /// 使用默认配置处理 `input`
+    pub fn new(input: impl Into<String>) -> Self {
+        Self {
+            input: input.into(),
+            output_dir: None,
+            config: ProcessConfigBuilder::default(),
+            keyframes_per_scene: 1,
+            storage: None,
+            pipeline: None,
+            options: ProcessOptions::default(),
+        }
+    }
+
+    /// 使用预设配置，会覆盖之前设置的处理参数
+    pub fn preset(mut self, preset: Preset) -> Self {
+        self.config = ProcessConfigBuilder::from_preset(preset);
+        self
+    }
+
+    /// 使用已有的配置（例如 [`ProcessConfig::from_env_and_file`]），会覆盖之前设置的处理参数
+    pub fn config(mut self, config: ProcessConfig) -> Self {
+        self.config = config.into();
+        self
+    }
+
+    /// 通过 [`ProcessConfigBuilder`] 设置其他处理参数
+    pub fn configure(mut self, f: impl FnOnce(ProcessConfigBuilder) -> ProcessConfigBuilder) -> Self {
+        self.config = f(self.config);
+        self
+    }
+
+    /// 场景变化检测阈值（0-1）
+    pub fn threshold(self, threshold: f64) -> Self {
+        self.configure(|config| config.threshold(threshold))
+    }
+
+    /// 最小场景持续时间（秒）
+    pub fn min_scene_duration(self, seconds: f64) -> Self {
+        self.configure(|config| config.min_scene_duration(seconds))
+    }
+
+    /// 帧采样率（每秒采样帧数）
+    pub fn sample_rate(self, sample_rate: f64) -> Self {
+        self.configure(|config| config.sample_rate(sample_rate))
+    }
+
+    /// 每个场景保存的关键帧数，默认 1
+    ///
+    /// 大于 1 时使用 [`SpreadFrameSelector`]，采样帧一次性提取到内存中（不逐帧处理）
+    pub fn keyframes_per_scene(mut self, count: usize) -> Self {
+        self.keyframes_per_scene = count;
+        self
+    }
+
+    /// 输出目录，未设置时使用系统临时目录下的 `video-parse/{随机 ID}`
+    pub fn output(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    /// 处理完成后把输出目录中的文件上传到 OSS（凭证从 ALIBABA_CLOUD_* 环境变量读取）
+    pub fn storage(mut self, storage: OssStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// 使用自定义的处理流水线（替换帧来源、场景检测，追加输出文件等）
+    pub fn pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    /// 处理的整体超时时间（不含下载和上传）
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options = self.options.with_timeout(timeout);
+        self
+    }
+
+    pub fn cancel(mut self, cancel: CancellationToken) -> Self {
+        self.options = self.options.with_cancel(cancel);
+        self
+    }
+
+    /// 各阶段进度的接收方
+    pub fn progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.options = self.options.with_progress(progress);
+        self
+    }
+
+    /// 下载输入（远程输入时）、处理视频并上传输出文件（设置了上传位置时）
+    ///
+    /// 上传任一文件失败时返回 [`VideoParseError::Upload`]，输出目录中的文件保留
+    pub async fn run(self) -> Result<PipelineOutput> {
+        if self.keyframes_per_scene == 0 {
+            return Err(VideoParseError::Config("keyframes_per_scene 必须大于 0".to_string()));
+        }
+        let config = self.config.build()?;
+        let output_dir = self.output_dir.unwrap_or_else(|| {
+            std::env::temp_dir().join("video-parse").join(uuid::Uuid::new_v4().to_string())
+        });
+        std::fs::create_dir_all(&output_dir)
+            .map_err(|e| VideoParseError::io(format!("创建输出目录失败: {}", output_dir.display()), e))?;
+
+        let mut pipeline = self.pipeline.unwrap_or_else(|| Pipeline::for_config(&config));
+        if self.keyframes_per_scene > 1 {
+            pipeline = pipeline.with_keyframe_selector(SpreadFrameSelector { per_scene: self.keyframes_per_scene });
+        }
+
+        let download_dir = is_remote(&self.input)
+            .then(|| std::env::temp_dir().join(format!("video-parse-input-{}", uuid::Uuid::new_v4())));
+        let result = async {
+            let input = match &download_dir {
+                Some(dir) => fetch_input(&self.input, dir, &config).await?,
+                None => PathBuf::from(&self.input),
+            };
+            pipeline.run_with_options(&input, &output_dir, config, self.options).await
+        }
+        .await;
+        if let Some(dir) = &download_dir {
+            tokio::fs::remove_dir_all(dir).await.ok();
+        }
+        let output = result?;
+
+        let uploaded = match &self.storage {
+            Some(storage) => upload_outputs(storage, &output).await?,
+            None => BTreeMap::new(),
+        };
+        Ok(PipelineOutput { output, uploaded })
+    }
+}
+
+/// 输入是否需要先下载
+fn is_remote(input: &str) -> bool {
+    url_input::is_http_url(input) || input.trim_start().starts_with("oss://")
+}
+
+/// 下载远程输入到 `dir`，返回本地文件路径
+async fn fetch_input(input: &str, dir: &Path, config: &ProcessConfig) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| VideoParseError::io(format!("创建下载目录失败: {}", dir.display()), e))?;
+    if url_input::is_http_url(input) {
+        let max_bytes = config.limits.max_input_size_bytes.unwrap_or(DEFAULT_URL_MAX_BYTES);
+        return url_input::download_url(input, dir, max_bytes).await;
+    }
+    let (bucket, key) = webhook::parse_oss_location(input)
+        .filter(|(_, key)| !key.is_empty())
+        .ok_or_else(|| VideoParseError::InvalidInput(format!("无效的 OSS 输入: {}（格式: oss://bucket/key）", input)))?;
+    let path = dir.join(output_naming::local_file_name(key));
+    OssClient::new()?.download_file(bucket, key, None, &path).await
+}
+
+/// 并发上传输出目录中的文件（不含子目录和检查点等隐藏文件），返回文件名 → 对象键
+///
+/// 元数据文件最后上传；有文件上传失败时其他文件仍会上传，但不上传元数据文件，返回汇总所有失败文件的错误。
+/// 打包了输出目录时只上传打包文件
+async fn upload_outputs(storage: &OssStorage, output: &ProcessOutput) -> Result<BTreeMap<String, String>> {
+    let client = OssClient::new()?;
+    let output_dir = output.output_dir.as_path();
+    let entries = std::fs::read_dir(output_dir)
+        .map_err(|e| VideoParseError::io(format!("读取输出目录失败: {}", output_dir.display()), e))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')))
+        .collect();
+    files.sort();
+    if let Some(archive_file) = &output.archive_file {
+        files.retain(|path| path.file_name().is_some_and(|name| name.to_string_lossy() == archive_file.as_str()));
+    }
+
+    let (metadata, tasks): (Vec<UploadTask>, Vec<UploadTask>) = files
+        .into_iter()
+        .map(|path| {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let key = storage.object_key(&file_name);
+            UploadTask::new("输出文件", file_name, path, key)
+        })
+        .partition(|task| task.name == output.metadata_file);
+
+    let endpoint = storage.endpoint.as_deref();
+    let mut report = client.upload_files(&storage.bucket, &tasks, endpoint, storage.concurrency, |_, _| {}).await;
+    // 目标位置有元数据文件即表示结果完整，其他文件上传失败时不上传
+    if report.errors.is_empty() {
+        let last = client.upload_files(&storage.bucket, &metadata, endpoint, 1, |_, _| {}).await;
+        report.uploaded.extend(last.uploaded);
+        report.errors.extend(last.errors);
+    }
+    let uploaded = report.into_result()?;
+    info!(bucket = %storage.bucket, prefix = %storage.prefix, files = uploaded.len(), "✅ [视频处理] 输出文件已上传到 OSS");
+    Ok(uploaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::MediaKind;
+    use crate::pipeline::{DecodedVideo, FrameSource, VideoInput};
+    use crate::video_processor::SampleAspectRatio;
+    use image::DynamicImage;
+
+    /// 输入为单张图片的帧来源
+    struct StillImageSource;
+
+    impl FrameSource for StillImageSource {
+        fn extract(&self, _input: &VideoInput, _config: &ProcessConfig) -> anyhow::Result<DecodedVideo> {
+            Ok(DecodedVideo {
+                fps: 0.0,
+                width: 8,
+                height: 8,
+                sample_aspect_ratio: SampleAspectRatio::SQUARE,
+                media_kind: MediaKind::StillImage,
+                duration: Some(0.0),
+                frames: vec![(0.0, DynamicImage::new_rgb8(8, 8))],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_video_parse_pipeline() {
+        let storage: OssStorage = "oss://media/processed/".parse().unwrap();
+        assert_eq!(storage, OssStorage::new("media").with_prefix("processed"));
+        assert_eq!(storage.object_key("metadata.json"), "processed/metadata.json");
+        assert!("media/processed".parse::<OssStorage>().is_err());
+        assert!(is_remote("oss://media/a.mp4"));
+        assert!(!is_remote("/data/a.mp4"));
+
+        assert!(matches!(
+            VideoParsePipeline::new("cover.jpg").keyframes_per_scene(0).run().await,
+            Err(VideoParseError::Config(_))
+        ));
+        assert!(matches!(
+            VideoParsePipeline::new("cover.jpg").threshold(1.5).run().await,
+            Err(VideoParseError::Config(_))
+        ));
+
+        let dir = std::env::temp_dir().join(format!("video-parse-builder-{}", uuid::Uuid::new_v4()));
+        let result = VideoParsePipeline::new("cover.jpg")
+            .pipeline(Pipeline::new().with_frame_source(StillImageSource).without_publishers())
+            .threshold(0.3)
+            .keyframes_per_scene(3)
+            .output(&dir)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(result.output.metadata.scene_count, 1);
+        assert!(result.uploaded.is_empty());
+        assert!(dir.join(&result.output.keyframe_files[0]).exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
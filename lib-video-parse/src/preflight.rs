@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::error::{Result, VideoParseError};
+use crate::metadata::MediaKind;
+use crate::processor::ProcessConfig;
+use crate::video_processor::{MediaInfo, SampleAspectRatio, VideoProbe, VideoProcessor};
+
+/// 每个关键帧 JPEG 的估算大小（字节/像素）
+const KEYFRAME_BYTES_PER_PIXEL: f64 = 0.15;
+/// 提取音频的估算码率（192 kbps，字节/秒）
+const AUDIO_BYTES_PER_SECOND: f64 = 192_000.0 / 8.0;
+/// 元数据等小文件预留的空间
+const RESERVED_BYTES: u64 = 1024 * 1024;
+
+/// 输入文件大小和时长上限（不影响处理结果，不计入配置指纹）
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InputLimits {
+    /// 输入文件的最大字节数（None 表示不限制）
+    pub max_input_size_bytes: Option<u64>,
+    /// 视频的最长时长（秒，None 表示不限制）
+    pub max_duration_seconds: Option<f64>,
+}
+
+impl InputLimits {
+    /// 检查输入文件大小，超过上限时返回拒绝原因
+    pub fn check_size(&self, size: u64) -> Result<(), Rejection> {
+        match self.max_input_size_bytes {
+            Some(limit) if size > limit => Err(Rejection {
+                reason: RejectReason::InputTooLarge,
+                actual: size as f64,
+                limit: limit as f64,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// 检查视频时长，超过上限时返回拒绝原因
+    pub fn check_duration(&self, duration: f64) -> Result<(), Rejection> {
+        match self.max_duration_seconds {
+            Some(limit) if duration > limit => Err(Rejection {
+                reason: RejectReason::DurationTooLong,
+                actual: duration,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 拒绝处理的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// 输入文件超过 `max_input_size_bytes`
+    InputTooLarge,
+    /// 视频时长超过 `max_duration_seconds`
+    DurationTooLong,
+}
+
+impl RejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectReason::InputTooLarge => "input_too_large",
+            RejectReason::DurationTooLong => "duration_too_long",
+        }
+    }
+}
+
+/// 输入超出限制被拒绝处理，`actual` 和 `limit` 的单位为字节（文件大小）或秒（时长）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rejection {
+    pub reason: RejectReason,
+    pub actual: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            RejectReason::InputTooLarge => write!(
+                f,
+                "输入文件大小 {:.1} MB 超过上限 {:.1} MB",
+                self.actual / 1024.0 / 1024.0,
+                self.limit / 1024.0 / 1024.0
+            ),
+            RejectReason::DurationTooLong => write!(
+                f,
+                "视频时长 {:.1} 秒超过上限 {:.1} 秒",
+                self.actual, self.limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+/// 错误（包括 [`crate::failure::StageFailure`] 包装的错误）是否为超出输入限制的拒绝
+pub fn as_rejection(error: &anyhow::Error) -> Option<&Rejection> {
+    let error = match error.downcast_ref::<crate::failure::StageFailure>() {
+        Some(failure) => &failure.error,
+        None => error,
+    };
+    error.downcast_ref::<Rejection>().or_else(|| match error.downcast_ref::<VideoParseError>() {
+        Some(VideoParseError::Rejected(rejection)) => Some(rejection),
+        _ => None,
+    })
+}
+
+/// 根据扩展名判断是否为视频文件
+pub fn is_video_file(filename: &str) -> bool {
+    let filename_lower = filename.to_lowercase();
+    let video_extensions = [
+        "mp4", "avi", "mov", "mkv", "wmv", "flv", "webm", "m4v",
+        "mpg", "mpeg", "3gp", "3g2", "asf", "rm", "rmvb", "vob",
+        "ts", "mts", "m2ts", "f4v", "ogv", "divx", "xvid",
+    ];
+
+    if let Some(ext) = PathBuf::from(&filename_lower)
+        .extension()
+        .and_then(|e| e.to_str())
+    {
+        video_extensions.contains(&ext)
+    } else {
+        false
+    }
+}
+
+/// 解码前检查输入：探测容器和媒体流，检查大小和时长上限，并确认输出目录所在的文件系统有足够空间
+///
+/// 帧在内存中处理，只需为关键帧、音频和元数据预留磁盘空间
+pub fn check(input: &Path, output_dir: &Path, config: &ProcessConfig) -> Result<MediaKind> {
+    let input_size = std::fs::metadata(input).map(|m| m.len()).unwrap_or(0);
+    config.limits.check_size(input_size)?;
+    let not_readable = |e: VideoParseError| match e {
+        VideoParseError::Decode { message, source } => VideoParseError::InvalidInput(format!(
+            "不是可读取的媒体文件: {} ({}: {})",
+            input.display(),
+            message,
+            source
+        )),
+        e => e,
+    };
+    let processor = VideoProcessor::new(input)?;
+    let kind = processor.media_kind().map_err(not_readable)?;
+    let required = match kind {
+        MediaKind::Video => {
+            let probe = processor.probe().map_err(not_readable)?;
+            if probe.width == 0 || probe.height == 0 {
+                return Err(VideoParseError::InvalidInput(format!(
+                    "视频流分辨率无效: {}x{}",
+                    probe.width, probe.height
+                )));
+            }
+            config.limits.check_duration(probe.duration)?;
+            tracing::debug!(
+                duration = probe.duration,
+                resolution = %format!("{}x{}", probe.width, probe.height),
+                has_audio = probe.has_audio,
+                "输入预检通过"
+            );
+            estimate_output_bytes(&probe, config)
+        }
+        // 音频输出不超过输入文件大小，图片只输出一个关键帧
+        MediaKind::AudioOnly | MediaKind::StillImage => {
+            tracing::debug!(media_kind = kind.as_str(), "输入预检通过");
+            input_size + RESERVED_BYTES
+        }
+    };
+    ensure_free_space(output_dir, required)?;
+    Ok(kind)
+}
+
+/// 按当前配置处理视频的预估工作量
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProcessEstimate {
+    /// 场景检测需要解码的采样帧数
+    pub sampled_frames: u64,
+    /// 场景数上限（受 `min_scene_duration` 限制）
+    pub max_scenes: u64,
+    /// 输出占用的磁盘空间（字节）
+    pub output_bytes: u64,
+}
+
+impl ProcessEstimate {
+    pub fn new(probe: &VideoProbe, config: &ProcessConfig) -> Self {
+        let sampled_frames = (probe.duration * config.sample_rate).ceil().max(1.0);
+        let scenes = if config.min_scene_duration > 0.0 {
+            sampled_frames.min((probe.duration / config.min_scene_duration).ceil() + 1.0)
+        } else {
+            sampled_frames
+        };
+        let (width, height) = probe.display_size();
+        let keyframe_bytes = width as f64 * height as f64 * KEYFRAME_BYTES_PER_PIXEL;
+        let audio_bytes = if probe.has_audio { probe.duration * AUDIO_BYTES_PER_SECOND } else { 0.0 };
+        Self {
+            sampled_frames: sampled_frames as u64,
+            max_scenes: scenes as u64,
+            output_bytes: (scenes * keyframe_bytes + audio_bytes) as u64 + RESERVED_BYTES,
+        }
+    }
+}
+
+/// 估算处理输出占用的磁盘空间（字节）
+pub fn estimate_output_bytes(probe: &VideoProbe, config: &ProcessConfig) -> u64 {
+    ProcessEstimate::new(probe, config).output_bytes
+}
+
+/// 输入探测报告：媒体信息、处理预估和输入限制检查结果（`video-parse probe` 和 `GET /probe` 的输出）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeReport {
+    /// 输入（本地路径或 URL）
+    pub input: String,
+    pub media_kind: MediaKind,
+    /// 本地文件大小（字节，URL 输入为 None）
+    pub size_bytes: Option<u64>,
+    #[serde(flatten)]
+    pub media: MediaInfo,
+    /// 按当前配置处理的预估（只有视频输入）
+    pub estimate: Option<ProcessEstimate>,
+    /// 超出输入大小或时长上限时的拒绝原因，为空表示可以处理
+    pub rejection: Option<Rejection>,
+}
+
+/// 探测输入并按配置估算处理工作量，不解码帧；`input` 可以是本地路径或 HTTP / HTTPS URL
+pub fn probe(input: &str, config: &ProcessConfig) -> Result<ProbeReport> {
+    let processor = VideoProcessor::new(input)?;
+    let media = processor.media_info()?;
+    let media_kind = processor.media_kind()?;
+    let size_bytes = (!crate::url_input::is_http_url(input))
+        .then(|| std::fs::metadata(input).map(|m| m.len()).ok())
+        .flatten();
+    let estimate = media.video.as_ref().filter(|_| media_kind.is_video()).map(|video| {
+        let probe = VideoProbe {
+            duration: media.duration,
+            fps: video.fps,
+            width: video.display_width,
+            height: video.display_height,
+            sample_aspect_ratio: SampleAspectRatio::SQUARE,
+            has_audio: media.audio.is_some(),
+        };
+        ProcessEstimate::new(&probe, config)
+    });
+    let rejection = size_bytes
+        .map_or(Ok(()), |size| config.limits.check_size(size))
+        .and_then(|_| if media_kind.is_video() { config.limits.check_duration(media.duration) } else { Ok(()) })
+        .err();
+    Ok(ProbeReport { input: input.to_string(), media_kind, size_bytes, media, estimate, rejection })
+}
+
+/// 确认目录所在的文件系统至少有 `required` 字节可用空间
+///
+/// 无法获取可用空间时（非 Unix 平台等）不做限制
+pub fn ensure_free_space(dir: &Path, required: u64) -> Result<()> {
+    let Some(available) = available_space(dir) else {
+        tracing::debug!(dir = %dir.display(), "无法获取可用磁盘空间，跳过检查");
+        return Ok(());
+    };
+    if available < required {
+        return Err(VideoParseError::io(
+            format!(
+                "临时目录空间不足: {} 需要 {:.1} MB，可用 {:.1} MB",
+                dir.display(),
+                required as f64 / 1024.0 / 1024.0,
+                available as f64 / 1024.0 / 1024.0
+            ),
+            io::Error::new(io::ErrorKind::StorageFull, "磁盘空间不足"),
+        ));
+    }
+    Ok(())
+}
+
+/// 目录所在文件系统的可用空间（字节），目录尚未创建时使用最近的已存在的上级目录
+#[cfg(unix)]
+pub fn available_space(dir: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = dir.ancestors().find(|path| path.exists())?;
+    let path = CString::new(existing.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: path 是以 NUL 结尾的字符串，stat 在调用成功后已初始化
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preflight_checks() {
+        assert!(is_video_file("videos/A.MP4"));
+        assert!(!is_video_file("videos/cover.jpg"));
+        assert!(!is_video_file("videos/README"));
+
+        let probe = VideoProbe {
+            duration: 60.0,
+            fps: 25.0,
+            width: 1920,
+            height: 1080,
+            sample_aspect_ratio: SampleAspectRatio::SQUARE,
+            has_audio: false,
+        };
+        let config = ProcessConfig { sample_rate: 1.0, min_scene_duration: 0.0, ..Default::default() };
+        let estimate = estimate_output_bytes(&probe, &config);
+        assert_eq!(estimate, (60.0 * 1920.0 * 1080.0 * KEYFRAME_BYTES_PER_PIXEL) as u64 + RESERVED_BYTES);
+        // 最小场景时长限制了场景数量上限
+        let limited = ProcessConfig { min_scene_duration: 10.0, ..config.clone() };
+        assert!(estimate_output_bytes(&probe, &limited) < estimate);
+
+        let limits = InputLimits { max_input_size_bytes: Some(1024), max_duration_seconds: Some(3600.0) };
+        assert!(limits.check_size(1024).is_ok());
+        assert_eq!(limits.check_size(2048).unwrap_err().reason, RejectReason::InputTooLarge);
+        let rejection = limits.check_duration(4.0 * 3600.0).unwrap_err();
+        assert_eq!(rejection.to_string(), "视频时长 14400.0 秒超过上限 3600.0 秒");
+        let error = crate::failure::Progress::new("preflight").fail(VideoParseError::from(rejection.clone()).into());
+        assert_eq!(as_rejection(&error), Some(&rejection));
+        assert!(InputLimits::default().check_duration(f64::MAX).is_ok());
+
+        let dir = std::env::temp_dir().join("video-parse-preflight").join("missing");
+        assert!(ensure_free_space(&dir, 0).is_ok());
+        if available_space(&dir).is_some() {
+            let error = ensure_free_space(&dir, u64::MAX).unwrap_err();
+            assert!(matches!(error, VideoParseError::Io { .. }));
+        }
+    }
+}
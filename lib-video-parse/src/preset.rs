@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use crate::processor::ProcessConfig;
+use crate::scene_detector::DetectionMetrics;
+use crate::video_processor::SeekMode;
+
+/// 内置速度预设
+///
+/// 一次性选定分析分辨率、采样率、启用的差异度量和 seek 精度，
+/// 不需要逐个理解这些参数就能在速度和质量之间取舍
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// 快速：低分辨率分析、较低采样率、只用直方图/像素/边缘差异、关键帧 seek
+    Fast,
+    /// 均衡：中等分辨率分析、全部差异度量、就近帧 seek
+    Balanced,
+    /// 精确：原始分辨率分析、更高采样率、全部差异度量、精确 seek
+    Accurate,
+}
+
+impl Preset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Preset::Fast => "fast",
+            Preset::Balanced => "balanced",
+            Preset::Accurate => "accurate",
+        }
+    }
+
+    /// 预设对应的处理配置（阈值和最小场景时长使用默认值）
+    pub fn config(&self) -> ProcessConfig {
+        let base = ProcessConfig::default();
+        match self {
+            Preset::Fast => ProcessConfig {
+                sample_rate: 0.5,
+                analysis_width: Some(320),
+                metrics: DetectionMetrics {
+                    region: false,
+                    edge: true,
+                    hsv: false,
+                    gradient: false,
+                    traditional: true,
+                },
+                seek_mode: SeekMode::Keyframe,
+                ..base
+            },
+            Preset::Balanced => ProcessConfig {
+                sample_rate: 1.0,
+                analysis_width: Some(640),
+                metrics: DetectionMetrics::all(),
+                seek_mode: SeekMode::Nearest,
+                ..base
+            },
+            Preset::Accurate => ProcessConfig {
+                sample_rate: 2.0,
+                analysis_width: None,
+                metrics: DetectionMetrics::all(),
+                seek_mode: SeekMode::Exact,
+                ..base
+            },
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fast" => Ok(Preset::Fast),
+            "balanced" => Ok(Preset::Balanced),
+            "accurate" => Ok(Preset::Accurate),
+            other => Err(format!("未知的预设: {}（可选值: fast, balanced, accurate）", other)),
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use crate::audio_extractor::ffmpeg_binary;
+use crate::metadata::SceneMetadata;
+
+/// 场景预览动图格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewFormat {
+    /// GIF（兼容性最好，默认）
+    #[default]
+    Gif,
+    /// 动态 WebP（体积更小，需要 ffmpeg 带 libwebp）
+    Webp,
+}
+
+impl PreviewFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreviewFormat::Gif => "gif",
+            PreviewFormat::Webp => "webp",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+impl FromStr for PreviewFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "gif" => Ok(PreviewFormat::Gif),
+            "webp" => Ok(PreviewFormat::Webp),
+            other => Err(format!("未知的预览动图格式: {}（可选值: gif, webp）", other)),
+        }
+    }
+}
+
+/// 场景预览动图配置
+///
+/// 为每个场景生成一段循环播放的短动图，供 CMS 悬停预览
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewConfig {
+    /// 是否生成预览动图
+    pub enabled: bool,
+    pub format: PreviewFormat,
+    /// 预览时长（秒），场景更短时取场景时长
+    pub duration: f64,
+    /// 预览帧率
+    pub fps: u32,
+    /// 预览宽度（像素），高度按比例缩放
+    pub width: u32,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: PreviewFormat::Gif,
+            duration: 2.0,
+            fps: 8,
+            width: 320,
+        }
+    }
+}
+
+/// 第 `index` 个场景预览的文件名：`preview_0000.gif`
+pub fn preview_file_name(index: usize, format: PreviewFormat) -> String {
+    format!("preview_{:04}.{}", index, format.extension())
+}
+
+/// 为每个场景生成预览动图，文件名写入场景的 `preview_file`
+///
+/// 单个场景生成失败只记录警告，对应场景没有 `preview_file`；返回生成成功的预览数
+pub fn generate_previews(config: &PreviewConfig, input: &Path, output_dir: &Path, scenes: &mut [SceneMetadata]) -> Result<usize> {
+    let mut generated = 0;
+    for (index, scene) in scenes.iter_mut().enumerate() {
+        let file_name = preview_file_name(index, config.format);
+        let preview_path = output_dir.join(&file_name);
+        let duration = config.duration.min(scene.duration);
+        match render(config, input, &preview_path, scene.start_time, duration) {
+            Ok(()) => {
+                tracing::debug!(scene_id = scene.scene_id, preview = %file_name, "💾 [预览动图] 已生成场景预览");
+                scene.preview_file = Some(file_name);
+                generated += 1;
+            }
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [预览动图] 生成场景预览失败");
+                std::fs::remove_file(&preview_path).ok();
+            }
+        }
+    }
+    Ok(generated)
+}
+
+/// 预览的 ffmpeg 滤镜：降帧率、缩放，GIF 额外生成调色板以减少色带
+fn filter(config: &PreviewConfig) -> String {
+    let scale = format!("fps={},scale={}:-2:flags=lanczos", config.fps.max(1), config.width.max(2));
+    match config.format {
+        PreviewFormat::Gif => format!("{},split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer", scale),
+        PreviewFormat::Webp => scale,
+    }
+}
+
+/// 用 ffmpeg 命令行把 `[start, start + duration)` 渲染为循环播放的动图
+fn render(config: &PreviewConfig, input: &Path, output: &Path, start: f64, duration: f64) -> Result<()> {
+    let ffmpeg = ffmpeg_binary();
+    let mut command = Command::new(&ffmpeg);
+    command
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-ss")
+        .arg(format!("{:.3}", start.max(0.0)))
+        .arg("-t")
+        .arg(format!("{:.3}", duration.max(0.001)))
+        .arg("-i")
+        .arg(input)
+        .arg("-map")
+        .arg("0:v:0")
+        .arg("-an")
+        .arg("-sn")
+        .arg("-dn")
+        .arg("-vf")
+        .arg(filter(config));
+    if config.format == PreviewFormat::Webp {
+        command
+            .arg("-c:v")
+            .arg("libwebp")
+            .arg("-quality")
+            .arg("70");
+    }
+    let status = command
+        .arg("-loop")
+        .arg("0")
+        .arg("-y")
+        .arg(output)
+        .status()
+        .with_context(|| format!("执行 ffmpeg 命令失败: {}（请安装 FFmpeg 或设置 FFMPEG_PATH）", ffmpeg.display()))?;
+    if !status.success() {
+        anyhow::bail!("ffmpeg 生成预览动图失败: {}", status);
+    }
+    if std::fs::metadata(output).map(|m| m.len()).unwrap_or(0) == 0 {
+        anyhow::bail!("预览文件为空");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_format() {
+        assert_eq!(preview_file_name(7, PreviewFormat::Webp), "preview_0007.webp");
+        assert_eq!("GIF".parse::<PreviewFormat>(), Ok(PreviewFormat::Gif));
+        assert!("apng".parse::<PreviewFormat>().is_err());
+        let config = PreviewConfig { format: PreviewFormat::Webp, ..PreviewConfig::default() };
+        assert_eq!(filter(&config), "fps=8,scale=320:-2:flags=lanczos");
+        assert!(filter(&PreviewConfig::default()).contains("paletteuse"));
+    }
+}
@@ -1,11 +1,43 @@
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
 use image::DynamicImage;
-use tracing::{info, warn, error, debug};
-use crate::{VideoProcessor, SceneDetector, AudioExtractor, metadata::VideoMetadata};
+use rayon::prelude::*;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn, error, debug, Span};
+use crate::{SceneDetector, FrameFeatures, metadata::{ExportFormat, VideoMetadata}};
+use crate::scene_detector::{AdaptiveThreshold, DetectionAlgorithm, DetectionMetrics, ThresholdMode};
+use crate::video_processor::{HwAccel, SeekMode};
+use crate::decoder::DecoderBackend;
+use crate::progressive::ProgressiveFile;
+use crate::progress::ProgressReporter;
+use crate::watchdog::StageTimeouts;
+use crate::preflight::InputLimits;
+use crate::ocr::OcrConfig;
+use crate::faces::FaceConfig;
+use crate::safety::SafetyConfig;
+use crate::embeddings::EmbeddingConfig;
+use crate::enrichment::EnrichmentConfig;
+use crate::sprite::SpriteConfig;
+use crate::clips::ClipConfig;
+use crate::preview::PreviewConfig;
+use crate::audio_boundary::AudioBoundaryConfig;
+use crate::audio_extractor::AudioConfig;
+use crate::vad::VadConfig;
+use crate::keyframe_image::KeyframeImageConfig;
+use crate::output_naming::{FileNaming, KeyframeName};
+use crate::archive::ArchiveFormat;
+use crate::failure::FailureReport;
+use crate::error::VideoParseError;
+use crate::pipeline::Pipeline;
+use crate::webhook::{self, WebhookDelivery, WebhookConfig};
+use crate::resource::{ResourceUsage, TimingBreakdown};
 
 /// 视频处理配置
+///
+/// 库的调用方使用 [`ProcessConfig::builder`] 构建，构建时检查参数
 #[derive(Debug, Clone)]
 pub struct ProcessConfig {
     /// 场景变化检测阈值
@@ -16,6 +48,82 @@ pub struct ProcessConfig {
     pub sample_rate: f64,
     /// Webhook URL（处理完成后回调）
     pub webhook_url: Option<String>,
+    /// 失败回调 URL（未设置时失败回调也发送到 webhook_url）
+    pub webhook_failure_url: Option<String>,
+    /// 进度回调 URL（处理过程中定期发送各阶段进度）
+    pub webhook_progress_url: Option<String>,
+    /// webhook 投递重试和回调数据格式（不影响处理结果，不计入配置指纹）
+    pub webhook: WebhookConfig,
+    /// 分析分辨率（帧宽度，None 表示使用原始分辨率）
+    pub analysis_width: Option<u32>,
+    /// 参与场景检测的差异度量项
+    pub metrics: DetectionMetrics,
+    /// 帧差异算法
+    pub detection_algorithm: DetectionAlgorithm,
+    /// 场景切换阈值模式
+    pub threshold_mode: ThresholdMode,
+    /// 自适应阈值参数（`threshold_mode` 为 adaptive 时生效）
+    pub adaptive_threshold: AdaptiveThreshold,
+    /// 帧提取时的 seek 精度
+    pub seek_mode: SeekMode,
+    /// 帧解码后端
+    pub decoder: DecoderBackend,
+    /// 硬件解码（只对 FFmpeg 解码后端生效）
+    pub hwaccel: HwAccel,
+    /// 各阶段超时时间（不影响处理结果，不计入配置指纹）
+    pub timeouts: StageTimeouts,
+    /// 输入文件大小和时长上限（不影响处理结果，不计入配置指纹）
+    pub limits: InputLimits,
+    /// 关键帧文字识别
+    pub ocr: OcrConfig,
+    /// 关键帧人脸检测
+    pub faces: FaceConfig,
+    /// 关键帧内容安全检测
+    pub safety: SafetyConfig,
+    /// 关键帧图像向量
+    pub embeddings: EmbeddingConfig,
+    /// 外部模型关键帧标注
+    pub enrichment: EnrichmentConfig,
+    /// 关键帧图片格式、质量和尺寸
+    pub keyframe_image: KeyframeImageConfig,
+    /// 关键帧、音频和元数据的文件名模板
+    pub naming: FileNaming,
+    /// 关键帧缩略图拼图
+    pub sprite: SpriteConfig,
+    /// 场景片段导出
+    pub clips: ClipConfig,
+    /// 场景预览动图
+    pub preview: PreviewConfig,
+    /// 音频波形和响度分析
+    pub waveform: bool,
+    /// 关键帧去重的相似度阈值（None 表示不去重）
+    pub dedupe_keyframes: Option<f64>,
+    /// 除 metadata.json 外额外导出的场景列表格式（EDL / CSV / SRT）
+    pub export_formats: Vec<ExportFormat>,
+    /// 场景颜色统计的主色调数量（None 表示不统计）
+    pub color_palette: Option<usize>,
+    /// 按音频校准场景边界
+    pub audio_boundary: AudioBoundaryConfig,
+    /// 识别渐隐、渐显和叠化等渐变转场，写入场景的 `transition_type`
+    pub detect_transitions: bool,
+    /// 目标场景数范围：检测到的场景数不在范围内时在帧差异上重新查找阈值（None 表示不限制）
+    pub target_scene_range: Option<RangeInclusive<usize>>,
+    /// 估计每个场景的景别（特写、中景、全景），写入场景的 `shot_type`
+    pub shot_type: bool,
+    /// 生成视频指纹（每个场景关键帧的感知哈希和亮度直方图），写入 fingerprint.json
+    pub fingerprint: bool,
+    /// 在输出目录生成结果查看页 index.html（场景时间线、关键帧和音频播放器）
+    pub viewer: bool,
+    /// 音频输出的编码、码率、采样率和声道
+    pub audio: AudioConfig,
+    /// 语音活动检测
+    pub vad: VadConfig,
+    /// 在输出目录写入检查点，重新处理同一输入时从上次完成的阶段继续（不影响处理结果）
+    pub checkpoint: bool,
+    /// 输出放在 `{源文件哈希}/{配置指纹}/` 子目录下，并在元数据中记录这两个哈希（不影响处理结果）
+    pub content_addressed: bool,
+    /// 处理完成后把输出目录打包为单个 .zip / .tar.gz 文件（None 表示不打包）
+    pub archive: Option<ArchiveFormat>,
 }
 
 impl ProcessConfig {
@@ -24,6 +132,152 @@ impl ProcessConfig {
         use crate::config::ConfigLoader;
         ConfigLoader::load_config(config_file, None, None, None, None)
     }
+
+    /// 失败回调地址：`webhook_failure_url`，未设置时使用 `webhook_url`
+    pub fn failure_webhook_url(&self) -> Option<&str> {
+        self.webhook_failure_url.as_deref().or(self.webhook_url.as_deref())
+    }
+
+    /// 按配置创建场景检测器
+    pub fn scene_detector(&self) -> SceneDetector {
+        SceneDetector::new(self.threshold, self.min_scene_duration)
+            .with_metrics(self.metrics)
+            .with_analysis_width(self.analysis_width)
+            .with_algorithm(self.detection_algorithm)
+            .with_adaptive_threshold((self.threshold_mode == ThresholdMode::Adaptive).then_some(self.adaptive_threshold))
+            .with_transitions(self.detect_transitions)
+    }
+
+    /// 配置指纹：只包含影响处理结果的参数，用于结果缓存的键
+    pub fn fingerprint(&self) -> String {
+        let mut canonical = format!(
+            "threshold={};min_scene_duration={};sample_rate={};analysis_width={:?};metrics={};seek_mode={}",
+            self.threshold,
+            self.min_scene_duration,
+            self.sample_rate,
+            self.analysis_width,
+            self.metrics.names(),
+            self.seek_mode.as_str(),
+        );
+        // 未启用的可选阶段和默认的解码后端不计入，保持已有缓存的指纹不变
+        if self.detection_algorithm != DetectionAlgorithm::Metrics {
+            canonical.push_str(&format!(";detection_algorithm={}", self.detection_algorithm.as_str()));
+        }
+        if self.threshold_mode == ThresholdMode::Adaptive {
+            canonical.push_str(&format!(
+                ";threshold_mode=adaptive:{}:{}",
+                self.adaptive_threshold.k, self.adaptive_threshold.window
+            ));
+        }
+        if self.decoder != DecoderBackend::Ffmpeg {
+            canonical.push_str(&format!(";decoder={}", self.decoder.as_str()));
+        }
+        if self.hwaccel != HwAccel::None {
+            canonical.push_str(&format!(";hwaccel={}", self.hwaccel.as_str()));
+        }
+        if self.ocr.enabled {
+            canonical.push_str(&format!(";ocr={}", self.ocr.languages));
+        }
+        if self.faces.enabled {
+            canonical.push_str(&format!(
+                ";faces={:?}:{}",
+                self.faces.model_path, self.faces.min_confidence
+            ));
+        }
+        if self.safety.enabled {
+            canonical.push_str(&format!(
+                ";safety={:?}:{}:{}:{}",
+                self.safety.model_path,
+                self.safety.labels.join(","),
+                self.safety.flagged_labels.join(","),
+                self.safety.threshold
+            ));
+        }
+        if self.embeddings.enabled {
+            canonical.push_str(&format!(
+                ";embeddings={:?}:{}",
+                self.embeddings.model_path, self.embeddings.input_size
+            ));
+        }
+        if let Some(ref endpoint) = self.enrichment.endpoint {
+            canonical.push_str(&format!(";enrichment={}", endpoint));
+        }
+        if self.keyframe_image != KeyframeImageConfig::default() {
+            canonical.push_str(&format!(
+                ";keyframe_image={}:{}:{:?}x{:?}",
+                self.keyframe_image.format.effective().as_str(),
+                self.keyframe_image.quality,
+                self.keyframe_image.max_width,
+                self.keyframe_image.max_height
+            ));
+        }
+        if self.naming != FileNaming::default() {
+            canonical.push_str(&format!(
+                ";naming={}:{}:{}",
+                self.naming.keyframe, self.naming.audio, self.naming.metadata
+            ));
+        }
+        if self.sprite.enabled {
+            canonical.push_str(&format!(
+                ";sprite={}:{}x{}:{}",
+                self.sprite.columns, self.sprite.tile_width, self.sprite.tile_height, self.sprite.timestamps
+            ));
+        }
+        if self.clips.enabled {
+            canonical.push_str(&format!(";clips={}", self.clips.mode.as_str()));
+        }
+        if self.preview.enabled {
+            canonical.push_str(&format!(
+                ";preview={}:{}s:{}fps:{}",
+                self.preview.format.as_str(), self.preview.duration, self.preview.fps, self.preview.width
+            ));
+        }
+        if self.waveform {
+            canonical.push_str(";waveform");
+        }
+        if let Some(similarity) = self.dedupe_keyframes {
+            canonical.push_str(&format!(";dedupe_keyframes={}", similarity));
+        }
+        if !self.export_formats.is_empty() {
+            canonical.push_str(&format!(";export_formats={}", ExportFormat::names(&self.export_formats)));
+        }
+        if let Some(format) = self.archive {
+            canonical.push_str(&format!(";archive={}", format.as_str()));
+        }
+        if let Some(palette) = self.color_palette {
+            canonical.push_str(&format!(";color_palette={}", palette));
+        }
+        if self.audio_boundary.enabled {
+            canonical.push_str(&format!(
+                ";audio_boundary={}/{}", self.audio_boundary.window, self.audio_boundary.silence_dbfs
+            ));
+        }
+        if self.detect_transitions {
+            canonical.push_str(";detect_transitions");
+        }
+        if let Some(range) = &self.target_scene_range {
+            canonical.push_str(&format!(";target_scene_range={}-{}", range.start(), range.end()));
+        }
+        if self.shot_type {
+            canonical.push_str(";shot_type");
+        }
+        if self.fingerprint {
+            canonical.push_str(";fingerprint");
+        }
+        if self.viewer {
+            canonical.push_str(";viewer");
+        }
+        if self.vad.enabled {
+            canonical.push_str(&format!(";vad={}/{}", self.vad.threshold_dbfs, self.vad.min_silence));
+        }
+        if self.audio != AudioConfig::default() {
+            canonical.push_str(&format!(
+                ";audio={}:{:?}:{:?}:{:?}",
+                self.audio.codec.as_str(), self.audio.bitrate_kbps, self.audio.sample_rate, self.audio.channels
+            ));
+        }
+        format!("{:016x}", crate::cache::fnv1a64(canonical.as_bytes()))
+    }
 }
 
 impl Default for ProcessConfig {
@@ -33,13 +287,110 @@ impl Default for ProcessConfig {
             min_scene_duration: 0.8,
             sample_rate: 0.5,
             webhook_url: None,
+            webhook_failure_url: None,
+            webhook_progress_url: None,
+            webhook: WebhookConfig::default(),
+            analysis_width: None,
+            metrics: DetectionMetrics::all(),
+            detection_algorithm: DetectionAlgorithm::Metrics,
+            threshold_mode: ThresholdMode::Fixed,
+            adaptive_threshold: AdaptiveThreshold::default(),
+            seek_mode: SeekMode::Nearest,
+            decoder: DecoderBackend::Ffmpeg,
+            hwaccel: HwAccel::None,
+            timeouts: StageTimeouts::default(),
+            limits: InputLimits::default(),
+            ocr: OcrConfig::default(),
+            faces: FaceConfig::default(),
+            safety: SafetyConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            enrichment: EnrichmentConfig::default(),
+            keyframe_image: KeyframeImageConfig::default(),
+            naming: FileNaming::default(),
+            sprite: SpriteConfig::default(),
+            clips: ClipConfig::default(),
+            preview: PreviewConfig::default(),
+            waveform: false,
+            dedupe_keyframes: None,
+            export_formats: Vec::new(),
+            color_palette: None,
+            audio_boundary: AudioBoundaryConfig::default(),
+            detect_transitions: false,
+            target_scene_range: None,
+            shot_type: false,
+            fingerprint: false,
+            viewer: false,
+            audio: AudioConfig::default(),
+            vad: VadConfig::default(),
+            checkpoint: false,
+            content_addressed: false,
+            archive: None,
         }
     }
 }
 
+/// 单次处理的运行控制：整体超时和取消（不影响处理结果，不计入配置指纹）
+///
+/// 超时或取消时立即返回，已完成的阶段和部分结果通过失败回调报告。
+/// 已在阻塞线程池中执行的阶段无法被中断，会继续运行到结束
+#[derive(Clone, Default)]
+pub struct ProcessOptions {
+    /// 整体超时时间（None 表示不限制），超时返回 [`VideoParseError::Timeout`]
+    pub timeout: Option<Duration>,
+    /// 取消令牌，取消后返回 [`VideoParseError::Cancelled`]
+    pub cancel: Option<CancellationToken>,
+    /// 各阶段进度的接收方
+    pub progress: Option<Arc<dyn ProgressReporter>>,
+    /// 失败时不发送失败回调（调用方还会重试时使用）
+    pub skip_failure_webhook: bool,
+    /// 源文件哈希（例如 OSS ETag），按内容寻址时使用；未设置时计算文件的 SHA-256
+    pub source_hash: Option<String>,
+}
+
+impl std::fmt::Debug for ProcessOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessOptions")
+            .field("timeout", &self.timeout)
+            .field("cancel", &self.cancel)
+            .field("progress", &self.progress.is_some())
+            .field("skip_failure_webhook", &self.skip_failure_webhook)
+            .field("source_hash", &self.source_hash)
+            .finish()
+    }
+}
+
+impl ProcessOptions {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn without_failure_webhook(mut self) -> Self {
+        self.skip_failure_webhook = true;
+        self
+    }
+
+    pub fn with_source_hash(mut self, source_hash: impl Into<String>) -> Self {
+        self.source_hash = Some(source_hash.into());
+        self
+    }
+}
+
 /// 处理结果
 #[derive(Debug, Clone)]
 pub struct ProcessOutput {
+    /// 任务 ID
+    pub job_id: String,
     /// 输出目录
     pub output_dir: PathBuf,
     /// 元数据
@@ -48,353 +399,391 @@ pub struct ProcessOutput {
     pub keyframe_files: Vec<String>,
     /// 音频文件
     pub audio_file: String,
+    /// 元数据文件
+    pub metadata_file: String,
+    /// 输出目录的打包文件（配置了 archive 时）
+    pub archive_file: Option<String>,
+    /// 峰值内存、临时磁盘占用及各阶段资源占用
+    pub resources: ResourceUsage,
+    /// 分阶段耗时（OSS 事件处理时由调用方补充下载和上传）
+    pub timings: TimingBreakdown,
+    /// webhook 投递记录（配置了 webhook_url 时）
+    pub webhook: Option<WebhookDelivery>,
 }
 
 /// 处理视频文件
+///
+/// 解码、场景检测、关键帧编码和音频提取都在阻塞线程池中执行，不会占用 tokio 工作线程。
+/// 需要替换或追加处理阶段时使用 [`Pipeline`]
 pub async fn process_video(
     input_video_path: impl AsRef<Path>,
     output_dir: impl AsRef<Path>,
     config: ProcessConfig,
-) -> Result<ProcessOutput> {
-    let input_video_path = input_video_path.as_ref();
-    let output_dir = output_dir.as_ref();
-
-    let total_start = Instant::now();
-    info!("🎬 [视频处理] 开始处理视频: {}", input_video_path.display());
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    
-    // 创建输出目录
-    let dir_start = Instant::now();
-    std::fs::create_dir_all(output_dir)
-        .context("创建输出目录失败")?;
-    let dir_duration = dir_start.elapsed();
-    info!("✅ [视频处理] 创建输出目录完成，耗时: {:.2}秒", dir_duration.as_secs_f64());
-
-    // 1. 初始化视频处理器
-    let init_start = Instant::now();
-    let processor = VideoProcessor::new(input_video_path)
-        .map_err(|e| {
-            error!("❌ [视频处理] 初始化视频处理器失败: {}", e);
-            e
-        })?;
-    let init_duration = init_start.elapsed();
-    info!("✅ [视频处理] 初始化视频处理器完成，耗时: {:.2}秒", init_duration.as_secs_f64());
-    
-    // 2. 获取视频信息
-    let info_start = Instant::now();
-    let (fps, width, height) = processor.get_video_info()
-        .map_err(|e| {
-            error!("❌ [视频处理] 获取视频信息失败: {}", e);
-            e
-        })?;
-    let info_duration = info_start.elapsed();
-    info!("✅ [视频处理] 获取视频信息完成，耗时: {:.2}秒", info_duration.as_secs_f64());
-    info!("  • 分辨率: {}x{}", width, height);
-    info!("  • 帧率: {:.2} fps", fps);
-
-    // 3. 提取视频帧
-    let extract_start = Instant::now();
-    info!("⏳ [视频处理] 正在提取视频帧（采样率: {:.1} fps）...", config.sample_rate);
-    let frames = processor.extract_frames(Some(config.sample_rate))
-        .map_err(|e| {
-            error!("❌ [视频处理] 提取视频帧失败: {}", e);
-            e
-        })?;
-    let extract_duration = extract_start.elapsed();
-    let avg_frame_time = if frames.len() > 0 { extract_duration.as_millis() as f64 / frames.len() as f64 } else { 0.0 };
-    info!("✅ [视频处理] 提取视频帧完成，耗时: {:.2}秒", extract_duration.as_secs_f64());
-    info!("  • 提取帧数: {} 帧", frames.len());
-    info!("  • 平均每帧耗时: {:.2}ms", avg_frame_time);
-
-    // 4. 检测场景变化
-    let scene_start = Instant::now();
-    info!("⏳ [视频处理] 正在检测场景变化...");
-    let detector = SceneDetector::new(config.threshold, config.min_scene_duration);
-    let scene_changes = detector.detect_scenes(&frames, fps)
-        .map_err(|e| {
-            error!("❌ [视频处理] 场景检测失败: {}", e);
-            e
-        })?;
-    let scene_duration = scene_start.elapsed();
-    let avg_scene_time = if scene_changes.len() > 0 { scene_duration.as_millis() as f64 / scene_changes.len() as f64 } else { 0.0 };
-    info!("✅ [视频处理] 场景检测完成，耗时: {:.2}秒", scene_duration.as_secs_f64());
-    info!("  • 检测到场景数: {} 个", scene_changes.len());
-    info!("  • 平均每场景耗时: {:.2}ms", avg_scene_time);
-
-    // 5. 提取关键帧并保存
-    let keyframe_start = Instant::now();
-    info!("⏳ [视频处理] 正在提取并保存关键帧...");
-    let mut scenes_metadata = Vec::new();
-    let mut keyframe_files = Vec::new();
-    let total_duration = frames.last().map(|(t, _)| *t).unwrap_or(0.0);
-    
-    // 检查是否有提取的帧
-    if frames.is_empty() {
-        error!("❌ [视频处理] 没有提取到任何视频帧，无法提取关键帧");
-        anyhow::bail!("没有提取到任何视频帧，无法提取关键帧");
+) -> Result<ProcessOutput, VideoParseError> {
+    Pipeline::for_config(&config).run(input_video_path, output_dir, config).await
+}
+
+/// 处理视频文件，可以设置整体超时和取消
+pub async fn process_video_with_options(
+    input_video_path: impl AsRef<Path>,
+    output_dir: impl AsRef<Path>,
+    config: ProcessConfig,
+    options: ProcessOptions,
+) -> Result<ProcessOutput, VideoParseError> {
+    Pipeline::for_config(&config).run_with_options(input_video_path, output_dir, config, options).await
+}
+
+/// 处理正在分段下载的视频文件
+///
+/// 帧提取在已下载的部分上立即开始，音频提取等待整个文件下载完成后进行
+pub async fn process_progressive_video(
+    input: Arc<ProgressiveFile>,
+    output_dir: impl AsRef<Path>,
+    config: ProcessConfig,
+) -> Result<ProcessOutput, VideoParseError> {
+    Pipeline::for_config(&config).run_progressive(input, output_dir, config).await
+}
+
+/// 调用 webhook 回调，失败时按指数退避重试，仍未送达只记录警告，不影响处理结果
+///
+/// OSS 事件处理时由 handler 在上传完成后调用，回调数据包含下载和上传耗时。返回投递记录
+pub async fn notify_webhook(webhook_url: &str, config: &WebhookConfig, result: &ProcessOutput) -> WebhookDelivery {
+    let payload = WebhookPayload {
+        status: "success".to_string(),
+        input_video: result.metadata.input_video.clone(),
+        output_dir: result.output_dir.to_string_lossy().to_string(),
+        scene_count: result.metadata.scene_count,
+        keyframe_count: result.keyframe_files.len(),
+        audio_file: result.audio_file.clone(),
+        metadata: result.metadata.clone(),
+        resources: result.resources.clone(),
+        timings: result.timings.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let delivery = webhook::deliver(config, webhook_url, &payload).await;
+    if delivery.delivered {
+        info!(attempts = delivery.attempts.len(), "✅ [视频处理] Webhook 回调成功");
+    } else {
+        warn!(attempts = delivery.attempts.len(), "⚠️  [视频处理] Webhook 回调失败");
+    }
+    delivery
+}
+
+/// 调用失败回调，重试规则与 webhook 回调相同，仍未送达只记录警告
+///
+/// 回调数据包含错误码、失败阶段、已完成的部分结果和重试建议
+pub async fn notify_failure_webhook(webhook_url: &str, config: &WebhookConfig, input_video: &str, report: &FailureReport) {
+    let payload = FailureWebhookPayload {
+        status: "failure",
+        input_video,
+        report,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let delivery = webhook::deliver(config, webhook_url, &payload).await;
+    if delivery.delivered {
+        info!(code = %report.error.code, "✅ [视频处理] 失败回调成功");
+    } else {
+        warn!(code = %report.error.code, attempts = delivery.attempts.len(), "⚠️  [视频处理] 失败回调失败");
     }
-    
-    // 创建场景检测器用于计算帧差异
-    let detector = SceneDetector::new(config.threshold, config.min_scene_duration);
-    let mut keyframe_counter = 0;
-    
+}
+
+/// 为每个场景选择并保存关键帧，返回场景元数据
+///
+/// `differences[i]` 为第 i 帧与前一帧的差异，与帧数一致时直接复用（场景检测时已计算），
+/// 否则并行计算全部相邻帧差异。选帧策略与 [`plan_keyframes`] 相同，关键帧并行编码
+pub(crate) fn extract_keyframes(
+    frames: &[(f64, DynamicImage)],
+    differences: &[f64],
+    scene_changes: &[f64],
+    total_duration: f64,
+    output_dir: &Path,
+    config: &ProcessConfig,
+) -> Result<Vec<crate::metadata::SceneMetadata>> {
+    let times: Vec<f64> = frames.iter().map(|(t, _)| *t).collect();
+    let computed;
+    let differences = if differences.len() == frames.len() {
+        differences
+    } else {
+        debug!(frames = frames.len(), differences = differences.len(), "没有可复用的帧差异，重新计算");
+        computed = frame_differences(frames, &config.scene_detector());
+        &computed
+    };
+    let plans = plan_keyframes(&times, differences, scene_changes, total_duration);
+    save_planned_keyframes(&plans, frames, output_dir, &config.keyframe_image, &config.naming)
+}
+
+/// 并行计算每帧与前一帧的差异（第一帧为 0）
+fn frame_differences(frames: &[(f64, DynamicImage)], detector: &SceneDetector) -> Vec<f64> {
+    let features: Vec<FrameFeatures> = frames.par_iter().map(|(_, img)| detector.features(img)).collect();
+    std::iter::once(0.0)
+        .chain(features.windows(2).map(|pair| detector.calculate_features_difference(&pair[0], &pair[1])))
+        .take(frames.len())
+        .collect()
+}
+
+/// 流式处理时为场景选定的关键帧
+pub(crate) struct KeyframePlan {
+    pub scene_id: usize,
+    pub start_time: f64,
+    pub end_time: f64,
+    /// 选中的采样帧时间戳（秒）
+    pub frame_time: f64,
+}
+
+/// 为每个场景选择关键帧，不需要帧图像
+///
+/// `times` 为采样帧的时间戳，`differences[i]` 为第 i 帧与前一帧的差异（场景检测时已计算）
+pub(crate) fn plan_keyframes(
+    times: &[f64],
+    differences: &[f64],
+    scene_changes: &[f64],
+    total_duration: f64,
+) -> Vec<KeyframePlan> {
+    let nearest = |indices: &mut dyn Iterator<Item = usize>, target: f64| {
+        indices.min_by(|&a, &b| (times[a] - target).abs().total_cmp(&(times[b] - target).abs()))
+    };
+    let difference = |idx: usize| differences.get(idx).copied().unwrap_or(0.0);
+    let mut plans = Vec::new();
+
     for (i, &scene_start) in scene_changes.iter().enumerate() {
-        // 确定场景结束时间
-        let scene_end = if i + 1 < scene_changes.len() {
-            scene_changes[i + 1]
-        } else {
-            total_duration
-        };
-        
+        let scene_end = scene_changes.get(i + 1).copied().unwrap_or(total_duration);
         let duration = scene_end - scene_start;
-        
-        // 找到属于当前场景的所有帧
-        let scene_frames: Vec<(usize, &(f64, DynamicImage))> = frames.iter()
-            .enumerate()
-            .filter(|(_, (t, _))| *t >= scene_start && *t < scene_end)
+        let scene_frames: Vec<usize> = (0..times.len())
+            .filter(|&idx| times[idx] >= scene_start && times[idx] < scene_end)
             .collect();
-        
-        if scene_frames.is_empty() {
-            // 如果没有找到帧，使用场景开始时间附近的帧
-            let fallback_idx = frames.iter()
-                .enumerate()
-                .min_by(|(_, (t1, _)), (_, (t2, _))| {
-                    ((*t1 - scene_start).abs()).partial_cmp(&((*t2 - scene_start).abs())).unwrap()
-                })
-                .map(|(idx, _)| idx);
-            
-            // 如果找不到回退帧，跳过这个场景
-            let fallback_idx = match fallback_idx {
-                Some(idx) => idx,
-                None => {
-                    warn!("⚠️  [视频处理] 场景 {}: 没有找到合适的帧，跳过", i);
-                    continue;
-                }
-            };
-            
-            let (_keyframe_time, keyframe_img) = &frames[fallback_idx];
-            let keyframe_filename = format!("keyframe_{:04}.jpg", keyframe_counter);
-            let keyframe_path = output_dir.join(&keyframe_filename);
-            keyframe_img.save(&keyframe_path)
-                .context(format!("保存关键帧失败: {}", keyframe_filename))?;
-            
-            keyframe_files.push(keyframe_filename.clone());
-            scenes_metadata.push(crate::metadata::SceneMetadata {
-                scene_id: i,
-                keyframe_file: keyframe_filename,
-                start_time: scene_start,
-                end_time: scene_end,
-                duration,
-            });
-            keyframe_counter += 1;
-            continue;
-        }
-        
-        // 每个场景只提取1个关键帧
-        // 策略：在场景中间区域（30%-70%）选择最稳定的帧（与相邻帧差异最小）
-        let scene_mid_start = scene_start + duration * 0.3;
-        let scene_mid_end = scene_start + duration * 0.7;
-        
-        // 找到中间区域的帧
-        let mid_region_frames: Vec<(usize, &(f64, DynamicImage))> = scene_frames.iter()
-            .filter(|(_, (t, _))| *t >= scene_mid_start && *t <= scene_mid_end)
-            .cloned()
+
+        // 场景中间区域（30%-70%）的帧
+        let mid_region_frames: Vec<usize> = scene_frames.iter()
+            .copied()
+            .filter(|&idx| times[idx] >= scene_start + duration * 0.3 && times[idx] <= scene_start + duration * 0.7)
             .collect();
-        
-        let keyframe_idx = if mid_region_frames.is_empty() {
-            // 如果中间区域没有帧，选择场景中间位置的帧
-            let target_time = scene_start + duration * 0.5;
-            scene_frames.iter()
-                .min_by(|(_, (t1, _)), (_, (t2, _))| {
-                    ((*t1 - target_time).abs()).partial_cmp(&((*t2 - target_time).abs())).unwrap()
-                })
-                .map(|(idx, _)| *idx)
-                .unwrap_or_else(|| {
-                    // 如果找不到最接近的帧，使用第一个帧
-                    scene_frames.first()
-                        .map(|(idx, _)| *idx)
-                        .unwrap_or_else(|| {
-                            // 如果 scene_frames 也为空（理论上不应该发生），使用第一个全局帧
-                            warn!("⚠️  [视频处理] 场景 {}: 没有找到合适的帧，使用第一个全局帧", i);
-                            0
-                        })
-                })
-        } else if mid_region_frames.len() == 1 {
-            // 如果只有一个帧，直接使用
-            mid_region_frames[0].0
+
+        let keyframe_idx = if scene_frames.is_empty() {
+            // 场景内没有帧，使用场景开始时间附近的帧
+            nearest(&mut (0..times.len()), scene_start)
+        } else if mid_region_frames.is_empty() {
+            nearest(&mut scene_frames.iter().copied(), scene_start + duration * 0.5)
         } else {
-            // 在中间区域选择最稳定的帧（与前后帧差异最小）
-            let mut best_idx = mid_region_frames[0].0;
+            // 选择与场景内前后帧平均差异最小（最稳定）的帧
+            let mut best_idx = mid_region_frames[0];
             let mut min_avg_diff = f64::MAX;
-            
-            for (frame_idx, (_, _)) in mid_region_frames.iter() {
-                let frame_idx_in_all = *frame_idx;
-                
-                // 计算与前后帧的平均差异
+            for &idx in &mid_region_frames {
                 let mut diffs = Vec::new();
-                
-                // 与前一个帧的差异
-                if frame_idx_in_all > 0 {
-                    let prev_frame = &frames[frame_idx_in_all - 1];
-                    if prev_frame.0 >= scene_start {
-                        let diff = detector.calculate_frame_difference(
-                            &prev_frame.1,
-                            &frames[frame_idx_in_all].1
-                        );
-                        diffs.push(diff);
-                    }
+                if idx > 0 && times[idx - 1] >= scene_start {
+                    diffs.push(difference(idx));
                 }
-                
-                // 与后一个帧的差异
-                if frame_idx_in_all + 1 < frames.len() {
-                    let next_frame = &frames[frame_idx_in_all + 1];
-                    if next_frame.0 < scene_end {
-                        let diff = detector.calculate_frame_difference(
-                            &frames[frame_idx_in_all].1,
-                            &next_frame.1
-                        );
-                        diffs.push(diff);
-                    }
+                if idx + 1 < times.len() && times[idx + 1] < scene_end {
+                    diffs.push(difference(idx + 1));
                 }
-                
-                // 计算平均差异
                 let avg_diff = if diffs.is_empty() {
                     f64::MAX
                 } else {
                     diffs.iter().sum::<f64>() / diffs.len() as f64
                 };
-                
-                // 选择差异最小的帧（最稳定的帧）
                 if avg_diff < min_avg_diff {
                     min_avg_diff = avg_diff;
-                    best_idx = frame_idx_in_all;
+                    best_idx = idx;
                 }
             }
-            
-            best_idx
+            Some(best_idx)
         };
-        
-        let (_keyframe_time, keyframe_img) = &frames[keyframe_idx];
-        
-        // 保存关键帧图片
-        let keyframe_filename = format!("keyframe_{:04}.jpg", keyframe_counter);
+
+        match keyframe_idx {
+            Some(idx) => plans.push(KeyframePlan {
+                scene_id: i,
+                start_time: scene_start,
+                end_time: scene_end,
+                frame_time: times[idx],
+            }),
+            None => warn!("⚠️  [视频处理] 场景 {}: 没有找到合适的帧，跳过", i),
+        }
+    }
+
+    plans
+}
+
+/// 保存按计划重新提取的关键帧，返回场景元数据
+///
+/// `frames` 为按 `plans` 的时间点提取的帧，每个场景使用时间戳最接近的一帧。各场景的关键帧并行编码
+pub(crate) fn save_planned_keyframes(
+    plans: &[KeyframePlan],
+    frames: &[(f64, DynamicImage)],
+    output_dir: &Path,
+    keyframe_image: &KeyframeImageConfig,
+    naming: &FileNaming,
+) -> Result<Vec<crate::metadata::SceneMetadata>> {
+    plans.par_iter().enumerate().map(|(keyframe_counter, plan)| {
+        let (_, keyframe_img) = frames.iter()
+            .min_by(|(t1, _), (t2, _)| (t1 - plan.frame_time).abs().total_cmp(&(t2 - plan.frame_time).abs()))
+            .context("重新提取关键帧失败：没有解码到帧")?;
+
+        let keyframe_filename = naming.keyframe_file(
+            &KeyframeName { index: keyframe_counter, scene_id: plan.scene_id, start_time: plan.start_time, end_time: plan.end_time },
+            keyframe_image.format.effective().extension(),
+        );
         let keyframe_path = output_dir.join(&keyframe_filename);
-        keyframe_img.save(&keyframe_path)
+        keyframe_image.save(keyframe_img, &keyframe_path)
             .map_err(|e| {
                 error!("❌ [视频处理] 保存关键帧失败: {} - {}", keyframe_filename, e);
                 anyhow::anyhow!("保存关键帧失败: {} - {}", keyframe_filename, e)
             })?;
-        
-        keyframe_files.push(keyframe_filename.clone());
-        debug!("💾 [视频处理] 已保存关键帧: {} (场景 {}, 时间: {:.2}s)", 
-            keyframe_filename, i, scene_start);
-        
-        // 场景元数据
-        scenes_metadata.push(crate::metadata::SceneMetadata {
-            scene_id: i,
+        debug!("💾 [视频处理] 已保存关键帧: {} (场景 {}, 时间: {:.2}s)",
+            keyframe_filename, plan.scene_id, plan.start_time);
+
+        Ok(crate::metadata::SceneMetadata {
+            scene_id: plan.scene_id,
             keyframe_file: keyframe_filename,
-            start_time: scene_start,
-            end_time: scene_end,
-            duration,
-        });
-        
-        keyframe_counter += 1;
+            start_time: plan.start_time,
+            end_time: plan.end_time,
+            duration: plan.end_time - plan.start_time,
+            ..Default::default()
+        })
+    }).collect()
+}
+
+/// 合并近似重复的关键帧：与之前保留的某个关键帧的感知哈希相似度（1 - 汉明距离 / 64）不低于
+/// `similarity` 时删除该关键帧文件，场景改用保留的关键帧，并在 `duplicate_of` 中记录对应的场景
+///
+/// 返回合并的关键帧数，读取失败的关键帧保留
+pub(crate) fn dedupe_keyframes(output_dir: &Path, scenes: &mut [crate::metadata::SceneMetadata], similarity: f64) -> usize {
+    // 已保留的关键帧：(场景 ID, 关键帧文件, 感知哈希)
+    let mut kept: Vec<(usize, String, u64)> = Vec::new();
+    let mut merged = 0;
+    for scene in scenes.iter_mut() {
+        let keyframe_path = output_dir.join(&scene.keyframe_file);
+        let hash = match image::open(&keyframe_path) {
+            Ok(image) => FrameFeatures::hashed(&image, DetectionAlgorithm::PHash).hash().unwrap_or_default(),
+            Err(e) => {
+                warn!(scene_id = scene.scene_id, error = %e, "⚠️  [关键帧去重] 读取关键帧失败，保留");
+                continue;
+            }
+        };
+        let original = kept
+            .iter()
+            .find(|(_, _, kept_hash)| 1.0 - (kept_hash ^ hash).count_ones() as f64 / 64.0 >= similarity);
+        match original {
+            Some((scene_id, keyframe_file, _)) => {
+                debug!(scene_id = scene.scene_id, duplicate_of = scene_id, "♻️  [关键帧去重] 合并近似重复的关键帧");
+                std::fs::remove_file(&keyframe_path).ok();
+                scene.keyframe_file = keyframe_file.clone();
+                scene.duplicate_of = Some(*scene_id);
+                merged += 1;
+            }
+            None => kept.push((scene.scene_id, scene.keyframe_file.clone(), hash)),
+        }
     }
-    let keyframe_duration = keyframe_start.elapsed();
-    let avg_keyframe_time = if keyframe_files.len() > 0 { keyframe_duration.as_millis() as f64 / keyframe_files.len() as f64 } else { 0.0 };
-    info!("✅ [视频处理] 关键帧提取完成，耗时: {:.2}秒", keyframe_duration.as_secs_f64());
-    info!("  • 提取关键帧数: {} 个", keyframe_files.len());
-    info!("  • 平均每帧耗时: {:.2}ms", avg_keyframe_time);
-
-    // 6. 提取音频
-    let audio_start = Instant::now();
-    info!("⏳ [视频处理] 正在提取音频...");
-    let audio_filename = "audio.aac";
-    let audio_path = output_dir.join(&audio_filename);
-    let audio_extractor = AudioExtractor::new(input_video_path)
-        .map_err(|e| {
-            error!("❌ [视频处理] 创建音频提取器失败: {}", e);
-            e
-        })?;
-    audio_extractor.extract_to_file(&audio_path)
-        .map_err(|e| {
-            error!("❌ [视频处理] 提取音频失败: {}", e);
-            e
-        })?;
-    let audio_duration = audio_start.elapsed();
-    info!("✅ [视频处理] 音频提取完成，耗时: {:.2}秒", audio_duration.as_secs_f64());
-    info!("  • 音频文件: {}", audio_path.display());
-
-    // 7. 生成元数据 JSON
-    let metadata_start = Instant::now();
-    info!("⏳ [视频处理] 正在生成元数据...");
-    let metadata = VideoMetadata {
-        input_video: input_video_path.to_string_lossy().to_string(),
-        total_duration,
-        fps,
-        resolution: format!("{}x{}", width, height),
-        scene_count: scenes_metadata.len(),
-        audio_file: audio_filename.to_string(),
-        scenes: scenes_metadata,
-    };
-    
-    let metadata_path = output_dir.join("metadata.json");
-    let metadata_json = serde_json::to_string_pretty(&metadata)
-        .map_err(|e| {
-            error!("❌ [视频处理] 序列化元数据失败: {}", e);
-            anyhow::anyhow!("序列化元数据失败: {}", e)
-        })?;
-    std::fs::write(&metadata_path, metadata_json)
-        .map_err(|e| {
-            error!("❌ [视频处理] 写入元数据文件失败: {} - {}", metadata_path.display(), e);
-            anyhow::anyhow!("写入元数据文件失败: {}", e)
-        })?;
-    let metadata_duration = metadata_start.elapsed();
-    info!("✅ [视频处理] 元数据生成完成，耗时: {:.2}秒", metadata_duration.as_secs_f64());
-    info!("  • 元数据文件: {}", metadata_path.display());
-    
-    // 总结
-    let total_duration = total_start.elapsed();
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    info!("🎉 [视频处理] 处理完成！总耗时: {:.2}秒 ({:.0}ms)", 
-        total_duration.as_secs_f64(), 
-        total_duration.as_millis());
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    info!("📊 [视频处理] 性能统计:");
-    let extract_percent = extract_duration.as_secs_f64() / total_duration.as_secs_f64() * 100.0;
-    let scene_percent = scene_duration.as_secs_f64() / total_duration.as_secs_f64() * 100.0;
-    let keyframe_percent = keyframe_duration.as_secs_f64() / total_duration.as_secs_f64() * 100.0;
-    let audio_percent = audio_duration.as_secs_f64() / total_duration.as_secs_f64() * 100.0;
-    let metadata_percent = metadata_duration.as_secs_f64() / total_duration.as_secs_f64() * 100.0;
-    info!("   • 视频帧提取: {:.2}秒 ({:.1}%)", extract_duration.as_secs_f64(), extract_percent);
-    info!("   • 场景检测: {:.2}秒 ({:.1}%)", scene_duration.as_secs_f64(), scene_percent);
-    info!("   • 关键帧提取: {:.2}秒 ({:.1}%)", keyframe_duration.as_secs_f64(), keyframe_percent);
-    info!("   • 音频提取: {:.2}秒 ({:.1}%)", audio_duration.as_secs_f64(), audio_percent);
-    info!("   • 元数据生成: {:.2}秒 ({:.1}%)", metadata_duration.as_secs_f64(), metadata_percent);
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    info!("📁 [视频处理] 输出目录: {}", output_dir.display());
-    info!("📸 [视频处理] 关键帧数量: {}", metadata.scene_count);
-    info!("🎵 [视频处理] 音频文件: {}", audio_filename);
-    info!("✅ [视频处理] 视频处理成功完成");
-
-    let result = ProcessOutput {
-        output_dir: output_dir.to_path_buf(),
-        metadata: metadata.clone(),
-        keyframe_files: keyframe_files.clone(),
-        audio_file: audio_filename.to_string(),
-    };
+    merged
+}
 
-    // 调用 webhook 回调（如果配置了）
-    if let Some(webhook_url) = &config.webhook_url {
-        info!("⏳ [视频处理] 正在调用 Webhook 回调...");
-        if let Err(e) = call_webhook(webhook_url, &result, &metadata).await {
-            warn!("⚠️  [视频处理] Webhook 回调失败: {}", e);
-        } else {
-            info!("✅ [视频处理] Webhook 回调成功");
+/// 颜色统计前把关键帧缩小到的最大边长（像素）
+const COLOR_SAMPLE_SIZE: u32 = 64;
+/// k-means 最大迭代次数
+const COLOR_KMEANS_ITERATIONS: usize = 10;
+
+/// 统计每个场景关键帧的主色调（k-means）、平均亮度和对比度，写入 `colors`
+///
+/// 返回统计成功的场景数，读取失败的关键帧跳过；去重合并的场景复用同一关键帧的结果
+pub(crate) fn analyze_colors(output_dir: &Path, scenes: &mut [crate::metadata::SceneMetadata], palette_size: usize) -> usize {
+    let mut computed: std::collections::HashMap<String, crate::metadata::ColorStats> = std::collections::HashMap::new();
+    let mut analyzed = 0;
+    for scene in scenes.iter_mut() {
+        if let Some(stats) = computed.get(&scene.keyframe_file) {
+            scene.colors = Some(stats.clone());
+            analyzed += 1;
+            continue;
+        }
+        match image::open(output_dir.join(&scene.keyframe_file)) {
+            Ok(image) => {
+                let stats = color_stats(&image, palette_size);
+                computed.insert(scene.keyframe_file.clone(), stats.clone());
+                scene.colors = Some(stats);
+                analyzed += 1;
+            }
+            Err(e) => warn!(scene_id = scene.scene_id, error = %e, "⚠️  [颜色统计] 读取关键帧失败，跳过"),
         }
     }
+    analyzed
+}
+
+/// 计算一张图片的主色调、平均亮度和对比度
+///
+/// 聚类中心按亮度分位数初始化，结果是确定的
+pub(crate) fn color_stats(image: &DynamicImage, palette_size: usize) -> crate::metadata::ColorStats {
+    let pixels: Vec<[f64; 3]> = image.thumbnail(COLOR_SAMPLE_SIZE, COLOR_SAMPLE_SIZE)
+        .to_rgb8()
+        .pixels()
+        .map(|p| [p[0] as f64, p[1] as f64, p[2] as f64])
+        .collect();
+    let luma = |p: &[f64; 3]| (0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]) / 255.0;
+    let count = pixels.len().max(1) as f64;
+    let brightness = pixels.iter().map(luma).sum::<f64>() / count;
+    let contrast = (pixels.iter().map(|p| (luma(p) - brightness).powi(2)).sum::<f64>() / count).sqrt();
 
-    Ok(result)
+    let mut sorted = pixels.clone();
+    sorted.sort_by(|a, b| luma(a).total_cmp(&luma(b)));
+    let k = palette_size.clamp(1, sorted.len().max(1));
+    let mut centers: Vec<[f64; 3]> = (0..k)
+        .filter_map(|i| sorted.get((2 * i + 1) * sorted.len() / (2 * k)).copied())
+        .collect();
+    let mut assignments = vec![0usize; pixels.len()];
+    for _ in 0..COLOR_KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (pixel, assignment) in pixels.iter().zip(assignments.iter_mut()) {
+            let nearest = (0..centers.len())
+                .min_by(|&a, &b| squared_distance(pixel, &centers[a]).total_cmp(&squared_distance(pixel, &centers[b])))
+                .unwrap_or(0);
+            changed |= nearest != *assignment;
+            *assignment = nearest;
+        }
+        let mut sums = vec![([0.0; 3], 0usize); centers.len()];
+        for (pixel, &assignment) in pixels.iter().zip(&assignments) {
+            let (sum, n) = &mut sums[assignment];
+            (0..3).for_each(|c| sum[c] += pixel[c]);
+            *n += 1;
+        }
+        // 没有像素的聚类保留原中心
+        for (center, (sum, n)) in centers.iter_mut().zip(sums) {
+            if n > 0 {
+                *center = sum.map(|v| v / n as f64);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut counts = vec![0usize; centers.len()];
+    assignments.iter().for_each(|&a| counts[a] += 1);
+    let mut palette: Vec<crate::metadata::PaletteColor> = centers.iter().zip(&counts)
+        .filter(|(_, &n)| n > 0)
+        .map(|(center, &n)| {
+            let rgb = center.map(|v| v.round().clamp(0.0, 255.0) as u8);
+            crate::metadata::PaletteColor {
+                hex: format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]),
+                rgb,
+                ratio: n as f64 / count,
+            }
+        })
+        .collect();
+    palette.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+    crate::metadata::ColorStats { palette, brightness, contrast }
+}
+
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|c| (a[c] - b[c]).powi(2)).sum()
+}
+
+/// 在阻塞线程池中执行 CPU 密集型阶段
+pub(crate) async fn run_blocking<T, F>(stage: &'static str, f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    // 阻塞线程池中的任务不会自动继承当前 span，需要显式进入
+    let span = Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+        .await
+        .map_err(|e| anyhow::anyhow!("{}任务异常终止: {}", stage, e))?
 }
 
 /// Webhook 回调数据结构
@@ -414,55 +803,119 @@ struct WebhookPayload {
     audio_file: String,
     /// 视频元数据
     metadata: VideoMetadata,
+    /// 资源占用
+    resources: ResourceUsage,
+    /// 分阶段耗时和传输量
+    timings: TimingBreakdown,
     /// 处理时间戳
     timestamp: String,
 }
 
-/// 调用 webhook 回调
-async fn call_webhook(
-    webhook_url: &str,
-    result: &ProcessOutput,
-    metadata: &VideoMetadata,
-) -> Result<()> {
-    use chrono::Utc;
+/// 失败回调数据结构
+#[derive(Debug, serde::Serialize)]
+struct FailureWebhookPayload<'a> {
+    /// 处理状态，固定为 failure
+    status: &'static str,
+    /// 输入视频路径
+    input_video: &'a str,
+    /// 错误信息、部分结果和重试建议
+    #[serde(flatten)]
+    report: &'a FailureReport,
+    /// 时间戳
+    timestamp: String,
+}
 
-    let timestamp = Utc::now().to_rfc3339();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::SceneMetadata;
 
-    let payload = WebhookPayload {
-        status: "success".to_string(),
-        input_video: metadata.input_video.clone(),
-        output_dir: result.output_dir.to_string_lossy().to_string(),
-        scene_count: metadata.scene_count,
-        keyframe_count: result.keyframe_files.len(),
-        audio_file: result.audio_file.clone(),
-        metadata: metadata.clone(),
-        timestamp,
-    };
+    #[test]
+    fn test_extract_keyframes() {
+        let dir = std::env::temp_dir().join(format!("video-parse-keyframes-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let levels = [10u8, 60, 100, 140, 250, 30, 80, 120, 160, 220];
+        let frames: Vec<(f64, DynamicImage)> = levels.iter().enumerate()
+            .map(|(i, &level)| (i as f64, DynamicImage::ImageLuma8(image::GrayImage::from_pixel(32, 32, image::Luma([level])))))
+            .collect();
+        let config = ProcessConfig::default();
+        let keyframe_level = |file: &str| image::open(dir.join(file)).unwrap().to_luma8().get_pixel(16, 16)[0] as i32;
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(webhook_url)
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(30))
-        .send()
-        .await
-        .context("Webhook 请求失败")?;
+        // 没有场景检测的差异时重新计算：中间区域的帧与前后帧差异相同，取第一帧
+        let scenes = extract_keyframes(&frames, &[], &[0.0, 5.0], 10.0, &dir, &config).unwrap();
+        let files: Vec<&str> = scenes.iter().map(|s| s.keyframe_file.as_str()).collect();
+        assert_eq!(files, ["keyframe_0000.jpg", "keyframe_0001.jpg"]);
+        assert!((keyframe_level(files[0]) - 100).abs() <= 2);
+        assert!((keyframe_level(files[1]) - 120).abs() <= 2);
+        assert_eq!((scenes[1].start_time, scenes[1].end_time), (5.0, 10.0));
 
-    let status = response.status();
-    if status.is_success() {
-        tracing::info!("Webhook 回调成功: {}", webhook_url);
-    } else {
-        let error_text = response.text().await.unwrap_or_default();
-        tracing::warn!(
-            "Webhook 回调返回错误状态: {} - {}",
-            status,
-            error_text
-        );
-        return Err(anyhow::anyhow!(
-            "Webhook 返回错误状态: {}",
-            status
-        ));
+        // 复用场景检测的差异：第 2 帧与前一帧差异大，改选第 3 帧
+        let differences = [0.0, 0.0, 0.9, 0.1, 0.1, 1.0, 0.0, 0.0, 0.0, 0.0];
+        let scenes = extract_keyframes(&frames, &differences, &[0.0, 5.0], 10.0, &dir, &config).unwrap();
+        assert!((keyframe_level(&scenes[0].keyframe_file) - 140).abs() <= 2);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_reused_differences_match_detection() {
+        // 关键帧选择复用场景检测的差异，两者必须一致
+        let frames: Vec<(f64, DynamicImage)> = (0..8u8)
+            .map(|i| (i as f64, DynamicImage::ImageRgb8(image::RgbImage::from_fn(16, 16, |x, y| {
+                image::Rgb([i * 30, (x as u8) * 8, (y as u8 + i) * 6])
+            }))))
+            .collect();
+        let detector = ProcessConfig::default().scene_detector();
+        let (_, detected) = detector.detect_scenes_with_differences(&frames, 1.0).unwrap();
+        assert_eq!(detected, frame_differences(&frames, &detector));
+    }
+
+    #[test]
+    fn test_dedupe_keyframes() {
+        let dir = std::env::temp_dir().join(format!("video-parse-dedupe-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let talking_head = |offset: u8| image::RgbImage::from_fn(64, 64, |x, y| {
+            let level = if (x / 16 + y / 16) % 2 == 0 { 40 } else { 200 };
+            image::Rgb([level + offset, level, level])
+        });
+        talking_head(0).save(dir.join("keyframe_0000.jpg")).unwrap();
+        image::RgbImage::from_fn(64, 64, |x, _| image::Rgb([(x * 4) as u8, 0, 0])).save(dir.join("keyframe_0001.jpg")).unwrap();
+        talking_head(6).save(dir.join("keyframe_0002.jpg")).unwrap();
+        let mut scenes: Vec<SceneMetadata> = (0..3).map(|i| SceneMetadata::fixture(i, i as f64, i as f64 + 1.0)).collect();
+
+        assert_eq!(dedupe_keyframes(&dir, &mut scenes, 0.9), 1);
+        assert_eq!(scenes[1].duplicate_of, None);
+        assert_eq!(scenes[2].duplicate_of, Some(0));
+        assert_eq!(scenes[2].keyframe_file, "keyframe_0000.jpg");
+        assert!(!dir.join("keyframe_0002.jpg").exists());
+        std::fs::remove_dir_all(&dir).ok();
     }
 
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn test_color_stats() {
+        // 上方 3/4 红色，下方 1/4 黑色
+        let image = DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |_, y| {
+            if y < 48 { image::Rgb([200, 20, 20]) } else { image::Rgb([0, 0, 0]) }
+        }));
+        let stats = color_stats(&image, 3);
+        assert_eq!(stats.palette.len(), 2);
+        assert_eq!((stats.palette[0].hex.as_str(), stats.palette[0].rgb), ("#c81414", [200, 20, 20]));
+        assert!((stats.palette[0].ratio - 0.75).abs() < 1e-9);
+        assert_eq!(stats.palette[1].hex, "#000000");
+        let red_luma = (0.2126 * 200.0 + 0.7152 * 20.0 + 0.0722 * 20.0) / 255.0;
+        assert!((stats.brightness - red_luma * 0.75).abs() < 1e-9);
+        assert!((stats.contrast - red_luma * 0.75f64.sqrt() * 0.5).abs() < 1e-9);
+
+        let dir = std::env::temp_dir().join(format!("video-parse-colors-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        image.save(dir.join("keyframe_0000.png")).unwrap();
+        let mut scenes: Vec<SceneMetadata> = ["keyframe_0000.png", "keyframe_0000.png", "missing.png"]
+            .iter()
+            .enumerate()
+            .map(|(i, file)| SceneMetadata { keyframe_file: file.to_string(), ..SceneMetadata::fixture(i, i as f64, i as f64 + 1.0) })
+            .collect();
+        assert_eq!(analyze_colors(&dir, &mut scenes, 3), 2);
+        assert_eq!(scenes[1].colors, scenes[0].colors);
+        assert!(scenes[2].colors.is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
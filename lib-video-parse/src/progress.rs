@@ -0,0 +1,443 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// 默认最多保留进度的任务数量（超出后淘汰最早的任务）
+const DEFAULT_MAX_JOBS: usize = 200;
+/// 实时事件的缓冲数量，订阅方处理不过来时丢弃最早的事件
+const EVENT_CAPACITY: usize = 1024;
+
+/// 进度的计量单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressUnit {
+    /// 下载的字节数
+    Bytes,
+    /// 提取的采样帧数
+    Frames,
+    /// 检测到的场景数
+    Scenes,
+    /// 保存或上传的文件数
+    Files,
+}
+
+/// 阶段内的进度
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageProgress {
+    pub stage: &'static str,
+    /// 已完成的数量
+    pub current: u64,
+    /// 总数（未知时为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    pub unit: ProgressUnit,
+}
+
+impl StageProgress {
+    pub fn new(stage: &'static str, current: u64, total: Option<u64>, unit: ProgressUnit) -> Self {
+        Self { stage, current, total, unit }
+    }
+
+    /// 完成百分比（总数未知时为 None）
+    pub fn percent(&self) -> Option<f64> {
+        self.total.map(|total| if total == 0 { 100.0 } else { (self.current as f64 / total as f64 * 100.0).min(100.0) })
+    }
+
+    /// 阶段内的工作是否已全部完成
+    pub fn is_complete(&self) -> bool {
+        self.total.is_some_and(|total| self.current >= total)
+    }
+}
+
+/// 处理进度的接收方，通过 [`ProcessOptions::with_progress`](crate::ProcessOptions::with_progress) 注入
+///
+/// 在 tokio 工作线程和阻塞线程池中都会被调用，调用频率可能很高（每个采样帧一次），实现应尽快返回
+pub trait ProgressReporter: Send + Sync {
+    /// 进入新的处理阶段（download、extract_frames、detect_scenes、keyframes、upload 等）
+    fn stage_started(&self, _stage: &'static str) {}
+
+    /// 阶段内的进度更新
+    fn update(&self, progress: &StageProgress);
+}
+
+/// 同时通知多个接收方
+impl ProgressReporter for Vec<Arc<dyn ProgressReporter>> {
+    fn stage_started(&self, stage: &'static str) {
+        for reporter in self {
+            reporter.stage_started(stage);
+        }
+    }
+
+    fn update(&self, progress: &StageProgress) {
+        for reporter in self {
+            reporter.update(progress);
+        }
+    }
+}
+
+/// 合并两个可选的接收方
+pub fn combine(
+    a: Option<Arc<dyn ProgressReporter>>,
+    b: Option<Arc<dyn ProgressReporter>>,
+) -> Option<Arc<dyn ProgressReporter>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(Arc::new(vec![a, b])),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 任务进度快照，`GET /jobs/{id}/progress` 的响应
+#[derive(Debug, Clone, Serialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    /// 当前阶段
+    pub stage: &'static str,
+    /// 各阶段最近一次的进度，按开始顺序排列
+    pub stages: Vec<StageProgress>,
+    /// 最近一次更新的时间（RFC 3339）
+    pub updated_at: String,
+    /// 处理结束后为 success | failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<&'static str>,
+}
+
+/// 实时进度事件，由 `GET /jobs/{id}/ws` 推送
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    pub job_id: String,
+    /// stage（进入新阶段）| progress（阶段内进度）| log（日志行）| finished（处理结束）
+    pub event: &'static str,
+    /// 当前阶段
+    pub stage: &'static str,
+    /// 阶段完成百分比（总数未知时没有）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+    /// 阶段内的进度（progress 事件）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<StageProgress>,
+    /// 日志行、处理结果或错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// finished 事件为 success | failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<&'static str>,
+    pub timestamp: String,
+}
+
+impl ProgressEvent {
+    fn new(job_id: &str, event: &'static str, stage: &'static str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            event,
+            stage,
+            percent: None,
+            progress: None,
+            message: None,
+            outcome: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// 连接建立时发送的当前状态：已结束的任务为 finished，否则为当前阶段
+    pub fn snapshot(progress: &JobProgress) -> Self {
+        let mut event = Self::new(&progress.job_id, if progress.outcome.is_some() { "finished" } else { "stage" }, progress.stage);
+        event.progress = progress.stages.iter().find(|stage| stage.stage == progress.stage).cloned();
+        event.percent = event.progress.as_ref().and_then(StageProgress::percent);
+        event.outcome = progress.outcome;
+        event
+    }
+}
+
+/// 按任务保存的最新进度
+///
+/// 任务数量有上限，超出后淘汰最早开始的任务
+pub struct ProgressStore {
+    inner: Mutex<StoreInner>,
+    max_jobs: usize,
+    /// 所有任务共用的实时事件通道，订阅方按 job_id 过滤
+    events: broadcast::Sender<ProgressEvent>,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    jobs: HashMap<String, JobProgress>,
+    order: VecDeque<String>,
+}
+
+impl ProgressStore {
+    pub fn new(max_jobs: usize) -> Self {
+        Self {
+            inner: Mutex::new(StoreInner::default()),
+            max_jobs: max_jobs.max(1),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+
+    /// 进程内共享的任务进度
+    pub fn shared() -> &'static ProgressStore {
+        static SHARED: OnceLock<ProgressStore> = OnceLock::new();
+        SHARED.get_or_init(|| ProgressStore::new(DEFAULT_MAX_JOBS))
+    }
+
+    /// 把进度写入 `job_id` 的接收方
+    pub fn reporter(&'static self, job_id: impl Into<String>) -> Arc<dyn ProgressReporter> {
+        Arc::new(StoreReporter { store: self, job_id: job_id.into() })
+    }
+
+    /// 获取任务的进度快照（任务不存在或已被淘汰时返回 None）
+    pub fn get(&self, job_id: &str) -> Option<JobProgress> {
+        self.inner.lock().unwrap().jobs.get(job_id).cloned()
+    }
+
+    /// 订阅所有任务的实时事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgressEvent> {
+        self.events.subscribe()
+    }
+
+    /// 推送任务的一行日志（没有订阅方时忽略）
+    pub fn log(&self, job_id: &str, line: &str) {
+        if self.events.receiver_count() == 0 {
+            return;
+        }
+        let stage = self.inner.lock().unwrap().jobs.get(job_id).map_or("prepare", |job| job.stage);
+        let mut event = ProgressEvent::new(job_id, "log", stage);
+        event.message = Some(line.to_string());
+        self.publish(event);
+    }
+
+    /// 记录任务处理结束
+    pub fn finish(&self, job_id: &str, success: bool, message: Option<String>) {
+        let outcome = if success { "success" } else { "failure" };
+        let mut stage = "prepare";
+        self.with_job(job_id, |job| {
+            job.outcome = Some(outcome);
+            stage = job.stage;
+        });
+        let mut event = ProgressEvent::new(job_id, "finished", stage);
+        event.outcome = Some(outcome);
+        event.message = message;
+        self.publish(event);
+    }
+
+    /// 丢弃时记录处理结束的守卫，调用 [`FinishGuard::succeed`] 前丢弃记为失败
+    pub fn finish_guard(&'static self, job_id: impl Into<String>) -> FinishGuard {
+        FinishGuard { store: self, job_id: job_id.into(), success: false }
+    }
+
+    fn publish(&self, event: ProgressEvent) {
+        // 没有订阅方时发送失败，忽略即可
+        let _ = self.events.send(event);
+    }
+
+    fn with_job(&self, job_id: &str, f: impl FnOnce(&mut JobProgress)) {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.jobs.contains_key(job_id) {
+            while inner.order.len() >= self.max_jobs {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.jobs.remove(&oldest);
+                }
+            }
+            inner.order.push_back(job_id.to_string());
+        }
+        let job = inner.jobs.entry(job_id.to_string()).or_insert_with(|| JobProgress {
+            job_id: job_id.to_string(),
+            stage: "prepare",
+            stages: Vec::new(),
+            updated_at: String::new(),
+            outcome: None,
+        });
+        f(job);
+        job.updated_at = chrono::Utc::now().to_rfc3339();
+    }
+}
+
+/// 处理结束守卫，见 [`ProgressStore::finish_guard`]
+pub struct FinishGuard {
+    store: &'static ProgressStore,
+    job_id: String,
+    success: bool,
+}
+
+impl FinishGuard {
+    /// 标记处理成功
+    pub fn succeed(&mut self) {
+        self.success = true;
+    }
+}
+
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        let message = (!self.success).then(|| "处理失败，详见任务日志".to_string());
+        self.store.finish(&self.job_id, self.success, message);
+    }
+}
+
+struct StoreReporter {
+    store: &'static ProgressStore,
+    job_id: String,
+}
+
+impl ProgressReporter for StoreReporter {
+    fn stage_started(&self, stage: &'static str) {
+        self.store.with_job(&self.job_id, |job| job.stage = stage);
+        self.store.publish(ProgressEvent::new(&self.job_id, "stage", stage));
+    }
+
+    fn update(&self, progress: &StageProgress) {
+        self.store.with_job(&self.job_id, |job| {
+            match job.stages.iter_mut().find(|existing| existing.stage == progress.stage) {
+                Some(existing) => *existing = progress.clone(),
+                None => job.stages.push(progress.clone()),
+            }
+        });
+        if self.store.events.receiver_count() > 0 {
+            let mut event = ProgressEvent::new(&self.job_id, "progress", progress.stage);
+            event.percent = progress.percent();
+            event.progress = Some(progress.clone());
+            self.store.publish(event);
+        }
+    }
+}
+
+/// 定期把进度 POST 到 webhook 的接收方
+///
+/// 同一阶段内最多每 `interval` 发送一次，进入新阶段和阶段完成时立即发送。
+/// 进度回调只尝试一次，失败只记录警告，不重试也不保存
+pub struct WebhookProgress {
+    url: String,
+    job_id: Option<String>,
+    source: String,
+    interval: Duration,
+    secret: Option<String>,
+    client: reqwest::Client,
+    runtime: tokio::runtime::Handle,
+    last_sent: Mutex<Option<(&'static str, Instant)>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProgressPayload<'a> {
+    event: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    job_id: Option<&'a str>,
+    source: &'a str,
+    #[serde(flatten)]
+    progress: &'a StageProgress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percent: Option<f64>,
+    timestamp: String,
+}
+
+impl WebhookProgress {
+    /// 配置了 `webhook_progress_url` 时创建，需要在 tokio 运行时中调用
+    pub fn from_config(config: &crate::ProcessConfig, job_id: Option<String>, source: &str) -> Option<Arc<dyn ProgressReporter>> {
+        let url = config.webhook_progress_url.clone()?;
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        Some(Arc::new(Self {
+            url,
+            job_id,
+            source: source.to_string(),
+            interval: config.webhook.progress_interval,
+            secret: config.webhook.secret.clone(),
+            client: reqwest::Client::new(),
+            runtime,
+            last_sent: Mutex::new(None),
+        }))
+    }
+
+    /// 是否需要发送：进入新阶段、阶段完成或距上次发送超过间隔
+    fn should_send(&self, progress: &StageProgress) -> bool {
+        let mut last_sent = self.last_sent.lock().unwrap();
+        let due = match *last_sent {
+            Some((stage, at)) => stage != progress.stage || progress.is_complete() || at.elapsed() >= self.interval,
+            None => true,
+        };
+        if due {
+            *last_sent = Some((progress.stage, Instant::now()));
+        }
+        due
+    }
+}
+
+impl ProgressReporter for WebhookProgress {
+    fn update(&self, progress: &StageProgress) {
+        if !self.should_send(progress) {
+            return;
+        }
+        let payload = ProgressPayload {
+            event: "progress",
+            job_id: self.job_id.as_deref(),
+            source: &self.source,
+            progress,
+            percent: progress.percent(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let body = match serde_json::to_value(&payload) {
+            Ok(body) => body,
+            Err(_) => return,
+        };
+        let request = crate::webhook::signed_post(&self.client, &self.url, self.secret.as_deref(), &body)
+            .timeout(Duration::from_secs(10));
+        let stage = progress.stage;
+        self.runtime.spawn(async move {
+            match request.send().await.and_then(|response| response.error_for_status()) {
+                Ok(_) => tracing::debug!(stage, "📤 [Webhook] 进度回调已发送"),
+                Err(e) => tracing::warn!(stage, error = %e, "⚠️  [Webhook] 进度回调发送失败"),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_reporter() {
+        let store: &'static ProgressStore = Box::leak(Box::new(ProgressStore::new(1)));
+        let reporter = store.reporter("a");
+        reporter.stage_started("download");
+        reporter.update(&StageProgress::new("download", 50, Some(200), ProgressUnit::Bytes));
+        reporter.update(&StageProgress::new("download", 200, Some(200), ProgressUnit::Bytes));
+        reporter.stage_started("extract_frames");
+        reporter.update(&StageProgress::new("extract_frames", 3, None, ProgressUnit::Frames));
+
+        let progress = store.get("a").unwrap();
+        assert_eq!(progress.stage, "extract_frames");
+        assert_eq!(progress.stages.len(), 2);
+        assert_eq!(progress.stages[0].percent(), Some(100.0));
+        assert!(progress.stages[0].is_complete());
+        assert_eq!(progress.stages[1].percent(), None);
+
+        // 超出任务数上限时淘汰最早的任务
+        store.reporter("b").stage_started("download");
+        assert!(store.get("a").is_none());
+        assert!(store.get("b").is_some());
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let store: &'static ProgressStore = Box::leak(Box::new(ProgressStore::new(10)));
+        let mut events = store.subscribe();
+        let reporter = store.reporter("a");
+        reporter.stage_started("download");
+        reporter.update(&StageProgress::new("download", 50, Some(200), ProgressUnit::Bytes));
+        store.log("a", "INFO 开始下载");
+        drop(store.finish_guard("a"));
+
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.job_id.as_str(), event.event, event.stage), ("a", "stage", "download"));
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.event, event.percent), ("progress", Some(25.0)));
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.event, event.message.as_deref()), ("log", Some("INFO 开始下载")));
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.event, event.stage, event.outcome), ("finished", "download", Some("failure")));
+
+        let snapshot = ProgressEvent::snapshot(&store.get("a").unwrap());
+        assert_eq!((snapshot.event, snapshot.percent), ("finished", Some(25.0)));
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["progress"]["unit"], "bytes");
+    }
+}
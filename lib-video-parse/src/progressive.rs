@@ -0,0 +1,422 @@
+//! 边下载边处理
+//!
+//! OSS 源文件按固定大小分段下载到本地文件，FFmpeg 通过自定义 AVIO 读取该文件：
+//! 读到尚未下载的分段时阻塞等待，并把该分段提示给下载方优先下载（例如 moov 在文件末尾的 MP4），
+//! 其余分段按文件顺序下载，使帧提取与下载这两个最耗时的阶段重叠执行。
+
+use anyhow::{Context, Result};
+use ffmpeg_next as ffmpeg;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::raw::{c_int, c_void};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// FFmpeg 自定义 AVIO 的读缓冲区大小
+const AVIO_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 分段状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentState {
+    Pending,
+    Downloading,
+    Done,
+}
+
+struct ProgressState {
+    segments: Vec<SegmentState>,
+    /// 读取方正在等待的分段（下载方优先下载）
+    wanted: Option<usize>,
+    /// 已下载字节数
+    downloaded: u64,
+    /// 下载失败原因
+    failed: Option<String>,
+    /// 全部分段下载完成的耗时
+    completed_in: Option<Duration>,
+}
+
+/// 正在分段下载的本地文件
+pub struct ProgressiveFile {
+    path: PathBuf,
+    total_size: u64,
+    segment_size: u64,
+    started: Instant,
+    state: Mutex<ProgressState>,
+    cond: Condvar,
+}
+
+impl ProgressiveFile {
+    /// 创建本地文件并预分配空间
+    pub fn create(path: impl AsRef<Path>, total_size: u64, segment_size: u64) -> Result<Arc<Self>> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("创建输出目录失败")?;
+        }
+
+        let file = std::fs::File::create(&path)
+            .context(format!("创建文件失败: {}", path.display()))?;
+        file.set_len(total_size)
+            .context(format!("预分配文件空间失败: {}", path.display()))?;
+
+        let segment_size = segment_size.max(1);
+        let segment_count = total_size.div_ceil(segment_size) as usize;
+        let completed_in = if segment_count == 0 { Some(Duration::ZERO) } else { None };
+
+        Ok(Arc::new(Self {
+            path,
+            total_size,
+            segment_size,
+            started: Instant::now(),
+            state: Mutex::new(ProgressState {
+                segments: vec![SegmentState::Pending; segment_count],
+                wanted: None,
+                downloaded: 0,
+                failed: None,
+                completed_in,
+            }),
+            cond: Condvar::new(),
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    pub fn segment_size(&self) -> u64 {
+        self.segment_size
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.lock().segments.len()
+    }
+
+    /// 已下载字节数
+    pub fn downloaded(&self) -> u64 {
+        self.lock().downloaded
+    }
+
+    /// 全部下载完成的耗时（尚未完成时返回 None）
+    pub fn download_duration(&self) -> Option<Duration> {
+        self.lock().completed_in
+    }
+
+    /// 分段对应的字节范围 [start, end]（闭区间，与 HTTP Range 一致）
+    pub fn segment_range(&self, index: usize) -> (u64, u64) {
+        let start = index as u64 * self.segment_size;
+        let end = (start + self.segment_size).min(self.total_size) - 1;
+        (start, end)
+    }
+
+    /// 领取下一个待下载的分段：优先读取方正在等待的分段，其次按文件顺序
+    pub fn claim_next_segment(&self) -> Option<usize> {
+        let mut state = self.lock();
+        if state.failed.is_some() {
+            return None;
+        }
+
+        let next = state.wanted
+            .filter(|&i| state.segments[i] == SegmentState::Pending)
+            .or_else(|| state.segments.iter().position(|s| *s == SegmentState::Pending))?;
+        state.segments[next] = SegmentState::Downloading;
+        Some(next)
+    }
+
+    /// 写入已下载的分段数据
+    pub fn write_segment(&self, index: usize, data: &[u8]) -> Result<()> {
+        use std::io::Write;
+
+        let (start, end) = self.segment_range(index);
+        if data.len() as u64 != end - start + 1 {
+            anyhow::bail!(
+                "分段 {} 大小不符: 期望 {} bytes, 实际 {} bytes",
+                index, end - start + 1, data.len()
+            );
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .context(format!("打开文件失败: {}", self.path.display()))?;
+        file.seek(SeekFrom::Start(start))
+            .and_then(|_| file.write_all(data))
+            .context(format!("写入分段 {} 失败", index))?;
+
+        let mut state = self.lock();
+        state.segments[index] = SegmentState::Done;
+        state.downloaded += data.len() as u64;
+        if state.segments.iter().all(|s| *s == SegmentState::Done) {
+            state.completed_in = Some(self.started.elapsed());
+        }
+        drop(state);
+        self.cond.notify_all();
+        Ok(())
+    }
+
+    /// 标记下载失败，唤醒所有等待中的读取方
+    pub fn fail(&self, reason: impl Into<String>) {
+        let mut state = self.lock();
+        if state.failed.is_none() {
+            state.failed = Some(reason.into());
+        }
+        drop(state);
+        self.cond.notify_all();
+    }
+
+    /// 阻塞等待 [offset, offset + len) 范围内的数据下载完成
+    pub fn wait_available(&self, offset: u64, len: u64) -> Result<()> {
+        if len == 0 || offset >= self.total_size {
+            return Ok(());
+        }
+
+        let first = (offset / self.segment_size) as usize;
+        let last = ((offset + len).min(self.total_size) - 1) / self.segment_size;
+        let mut state = self.lock();
+        loop {
+            if let Some(ref reason) = state.failed {
+                anyhow::bail!("分段下载失败: {}", reason);
+            }
+            match (first..=last as usize).find(|&i| state.segments[i] != SegmentState::Done) {
+                None => return Ok(()),
+                Some(missing) => {
+                    state.wanted = Some(missing);
+                    state = self.cond.wait(state).unwrap_or_else(|e| e.into_inner());
+                }
+            }
+        }
+    }
+
+    /// 阻塞等待整个文件下载完成
+    pub fn wait_complete(&self) -> Result<()> {
+        self.wait_available(0, self.total_size)
+    }
+
+    /// 通过自定义 AVIO 打开 FFmpeg 输入，读到未下载的部分时阻塞等待
+    pub fn open_input(self: &Arc<Self>) -> Result<ProgressiveInput> {
+        use ffmpeg::sys;
+
+        let handle = std::fs::File::open(&self.path)
+            .context(format!("打开文件失败: {}", self.path.display()))?;
+        let reader = Box::into_raw(Box::new(ReaderState {
+            file: Arc::clone(self),
+            handle,
+            pos: 0,
+        }));
+
+        // SAFETY: 按 FFmpeg 自定义 IO 的约定分配和释放资源；
+        // reader 在 ProgressiveInput 析构（关闭输入之后）时才释放，回调期间始终有效
+        unsafe {
+            let mut buffer = sys::av_malloc(AVIO_BUFFER_SIZE) as *mut u8;
+            if buffer.is_null() {
+                drop(Box::from_raw(reader));
+                anyhow::bail!("分配 AVIO 缓冲区失败");
+            }
+
+            let avio = sys::avio_alloc_context(
+                buffer,
+                AVIO_BUFFER_SIZE as c_int,
+                0,
+                reader as *mut c_void,
+                Some(read_packet),
+                None,
+                Some(seek),
+            );
+            if avio.is_null() {
+                sys::av_freep(&mut buffer as *mut *mut u8 as *mut c_void);
+                drop(Box::from_raw(reader));
+                anyhow::bail!("创建 AVIO 上下文失败");
+            }
+
+            let mut ctx = sys::avformat_alloc_context();
+            if ctx.is_null() {
+                free_avio(avio);
+                drop(Box::from_raw(reader));
+                anyhow::bail!("创建 AVFormatContext 失败");
+            }
+            (*ctx).pb = avio;
+
+            // 打开失败时 FFmpeg 会释放 ctx，但不会释放自定义的 pb
+            let ret = sys::avformat_open_input(&mut ctx, std::ptr::null(), std::ptr::null(), std::ptr::null_mut());
+            if ret < 0 {
+                free_avio(avio);
+                drop(Box::from_raw(reader));
+                anyhow::bail!("无法打开视频文件: {}", ffmpeg::Error::from(ret));
+            }
+
+            let ret = sys::avformat_find_stream_info(ctx, std::ptr::null_mut());
+            if ret < 0 {
+                sys::avformat_close_input(&mut ctx);
+                free_avio(avio);
+                drop(Box::from_raw(reader));
+                anyhow::bail!("读取流信息失败: {}", ffmpeg::Error::from(ret));
+            }
+
+            Ok(ProgressiveInput {
+                input: Some(ffmpeg::format::context::Input::wrap(ctx)),
+                avio,
+                reader,
+            })
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 通过自定义 AVIO 读取 `ProgressiveFile` 的 FFmpeg 输入
+///
+/// 可以像 `ffmpeg::format::context::Input` 一样使用（Deref），析构时释放自定义 IO 资源
+pub struct ProgressiveInput {
+    input: Option<ffmpeg::format::context::Input>,
+    avio: *mut ffmpeg::sys::AVIOContext,
+    reader: *mut ReaderState,
+}
+
+impl std::ops::Deref for ProgressiveInput {
+    type Target = ffmpeg::format::context::Input;
+
+    fn deref(&self) -> &Self::Target {
+        self.input.as_ref().expect("输入已关闭")
+    }
+}
+
+impl std::ops::DerefMut for ProgressiveInput {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.input.as_mut().expect("输入已关闭")
+    }
+}
+
+impl Drop for ProgressiveInput {
+    fn drop(&mut self) {
+        // 先关闭输入（不会释放自定义 pb），再释放 AVIO 和读取状态
+        drop(self.input.take());
+        // SAFETY: avio 和 reader 由 open_input 创建，只在这里释放一次
+        unsafe {
+            free_avio(self.avio);
+            drop(Box::from_raw(self.reader));
+        }
+    }
+}
+
+/// 自定义 AVIO 的读取状态
+struct ReaderState {
+    file: Arc<ProgressiveFile>,
+    handle: std::fs::File,
+    pos: u64,
+}
+
+unsafe fn free_avio(mut avio: *mut ffmpeg::sys::AVIOContext) {
+    // 缓冲区可能已被 FFmpeg 重新分配，需要通过 avio->buffer 释放
+    ffmpeg::sys::av_freep(&mut (*avio).buffer as *mut *mut u8 as *mut c_void);
+    ffmpeg::sys::avio_context_free(&mut avio);
+}
+
+unsafe extern "C" fn read_packet(opaque: *mut c_void, buf: *mut u8, buf_size: c_int) -> c_int {
+    let state = &mut *(opaque as *mut ReaderState);
+    let total = state.file.total_size();
+    if state.pos >= total || buf_size <= 0 {
+        return ffmpeg::Error::Eof.into();
+    }
+
+    let len = (buf_size as u64).min(total - state.pos);
+    if let Err(e) = state.file.wait_available(state.pos, len) {
+        tracing::error!("❌ [分段下载] 读取等待失败: {}", e);
+        return ffmpeg::Error::External.into();
+    }
+
+    let out = std::slice::from_raw_parts_mut(buf, len as usize);
+    let read = state.handle
+        .seek(SeekFrom::Start(state.pos))
+        .and_then(|_| state.handle.read(out));
+    match read {
+        Ok(0) => ffmpeg::Error::Eof.into(),
+        Ok(n) => {
+            state.pos += n as u64;
+            n as c_int
+        }
+        Err(e) => {
+            tracing::error!("❌ [分段下载] 读取本地文件失败: {}", e);
+            ffmpeg::Error::External.into()
+        }
+    }
+}
+
+unsafe extern "C" fn seek(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let state = &mut *(opaque as *mut ReaderState);
+    let total = state.file.total_size() as i64;
+
+    if whence & ffmpeg::sys::AVSEEK_SIZE as c_int != 0 {
+        return total;
+    }
+
+    let base = match whence & !(ffmpeg::sys::AVSEEK_FORCE as c_int) {
+        0 => 0,                      // SEEK_SET
+        1 => state.pos as i64,       // SEEK_CUR
+        2 => total,                  // SEEK_END
+        _ => return -1,
+    };
+    let target = base + offset;
+    if target < 0 {
+        return -1;
+    }
+    state.pos = target as u64;
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join("video-parse-test")
+            .join(format!("{}-{}", name, uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_segments_written_out_of_order() {
+        let path = temp_path("progressive");
+        let file = ProgressiveFile::create(&path, 10, 4).unwrap();
+        assert_eq!(file.segment_count(), 3);
+        assert_eq!(file.segment_range(2), (8, 9));
+
+        // 读取方等待最后一个分段时，下载方优先领取该分段
+        file.lock().wanted = Some(2);
+        assert_eq!(file.claim_next_segment(), Some(2));
+        file.write_segment(2, &[8, 9]).unwrap();
+        file.wait_available(8, 2).unwrap();
+        assert!(file.download_duration().is_none());
+
+        assert_eq!(file.claim_next_segment(), Some(0));
+        assert_eq!(file.claim_next_segment(), Some(1));
+        file.write_segment(1, &[4, 5, 6, 7]).unwrap();
+        file.write_segment(0, &[0, 1, 2, 3]).unwrap();
+        file.wait_complete().unwrap();
+        assert_eq!(file.downloaded(), 10);
+        assert!(file.download_duration().is_some());
+        assert_eq!(std::fs::read(&path).unwrap(), (0..10).collect::<Vec<u8>>());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_failure_wakes_waiting_reader() {
+        let path = temp_path("progressive-fail");
+        let file = ProgressiveFile::create(&path, 8, 4).unwrap();
+
+        let waiter = {
+            let file = Arc::clone(&file);
+            std::thread::spawn(move || file.wait_complete())
+        };
+        file.fail("network error");
+        assert!(waiter.join().unwrap().is_err());
+        assert_eq!(file.claim_next_segment(), None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::handler::DirectProcessRequest;
+use crate::oss_event::ProcessResult;
+
+/// Redis 队列 worker 配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedisWorkerConfig {
+    /// Redis 连接地址，例如 redis://:password@127.0.0.1:6379/0
+    pub url: String,
+    /// 任务队列（list，生产者 LPUSH，worker BRPOP）
+    pub queue: String,
+    /// 结果队列（list，worker LPUSH）
+    pub result_key: String,
+}
+
+impl Default for RedisWorkerConfig {
+    fn default() -> Self {
+        Self {
+            url: "redis://127.0.0.1:6379".to_string(),
+            queue: "video-parse:jobs".to_string(),
+            result_key: "video-parse:results".to_string(),
+        }
+    }
+}
+
+/// 队列中的任务：与 `POST /process/direct` 的请求体相同，可额外带一个任务标识
+#[derive(Debug, Deserialize)]
+pub struct QueueJob {
+    /// 调用方的任务标识，原样写入结果
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub request: DirectProcessRequest,
+}
+
+/// 写入结果队列的处理结果
+#[derive(Debug, Serialize)]
+pub struct QueueResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub success: bool,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ProcessResult>,
+    pub finished_at: String,
+}
+
+/// 处理一条任务消息
+///
+/// 使用与 `POST /process/direct` 相同的流程，无法解析的消息也会写入一条失败结果
+pub async fn process_message(payload: &str) -> QueueResult {
+    let finished = |id, success, message, result| QueueResult {
+        id,
+        success,
+        message,
+        result,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let job: QueueJob = match serde_json::from_str(payload) {
+        Ok(job) => job,
+        Err(e) => {
+            tracing::error!(error = %e, "❌ [Redis Worker] 任务消息格式错误");
+            return finished(None, false, format!("任务消息格式错误: {}", e), None);
+        }
+    };
+    let id = job.id;
+    match crate::handler::handle_direct_process(axum::extract::Json(job.request)).await {
+        Ok(response) => {
+            let response = response.0;
+            finished(id, response.success, response.message, response.result)
+        }
+        Err((status, message)) => {
+            tracing::error!(status = %status, error = %message, "❌ [Redis Worker] 任务处理失败");
+            finished(id, false, message, None)
+        }
+    }
+}
+
+/// 从 Redis list 中逐个取出任务并处理，直到收到 Ctrl-C
+///
+/// 需要以 `--features redis` 编译。连接断开时每秒重试一次
+#[cfg(feature = "redis")]
+pub async fn run(config: &RedisWorkerConfig) -> Result<()> {
+    use anyhow::Context;
+    use redis::AsyncCommands;
+    use std::time::Duration;
+
+    /// BRPOP 的阻塞时间（秒），超时后重新检查是否需要退出
+    const POP_TIMEOUT_SECS: f64 = 5.0;
+
+    let client = redis::Client::open(config.url.as_str()).context("Redis 连接地址无效")?;
+    tracing::info!(queue = %config.queue, result_key = %config.result_key, "🚀 [Redis Worker] 开始监听任务队列");
+
+    let mut connection = None;
+    loop {
+        let conn = match connection {
+            Some(ref mut conn) => conn,
+            None => match client.get_multiplexed_async_connection().await {
+                Ok(conn) => connection.insert(conn),
+                Err(e) => {
+                    tracing::warn!(error = %e, "⚠️  [Redis Worker] 连接 Redis 失败，1 秒后重试");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+        };
+
+        let popped: redis::RedisResult<Option<(String, String)>> = tokio::select! {
+            popped = conn.brpop(&config.queue, POP_TIMEOUT_SECS) => popped,
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("🛑 [Redis Worker] 收到退出信号，停止监听");
+                return Ok(());
+            }
+        };
+        let payload = match popped {
+            Ok(Some((_, payload))) => payload,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️  [Redis Worker] 读取任务队列失败，重新连接");
+                connection = None;
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        let result = process_message(&payload).await;
+        let result_json = serde_json::to_string(&result)?;
+        if let Err(e) = conn.lpush::<_, _, ()>(&config.result_key, &result_json).await {
+            tracing::error!(error = %e, id = ?result.id, "❌ [Redis Worker] 写入结果队列失败");
+            connection = None;
+        }
+    }
+}
+
+/// 未启用 `redis` feature 时无法以 Redis worker 模式运行
+#[cfg(not(feature = "redis"))]
+pub async fn run(_config: &RedisWorkerConfig) -> Result<()> {
+    anyhow::bail!("未启用 redis feature，无法以 Redis 队列模式运行（请以 --features redis 编译）")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_invalid_message() {
+        let job: QueueJob = serde_json::from_str(r#"{"id":"job-1","input":"/data/a.mp4","preset":"fast"}"#).unwrap();
+        assert_eq!(job.id.as_deref(), Some("job-1"));
+        assert_eq!(job.request.input, "/data/a.mp4");
+
+        let result = process_message("not json").await;
+        assert!(!result.success);
+        assert!(result.id.is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! 基于已有的 metadata.json 重新处理：复用之前检测到的场景边界，按新的参数重新生成关键帧、片段和拼图等，跳过场景检测
+
+use anyhow::Context;
+use image::DynamicImage;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::error::VideoParseError;
+use crate::metadata::{MediaKind, VideoMetadata};
+use crate::pipeline::{Detector, Pipeline, SpreadFrameSelector};
+use crate::processor::{ProcessConfig, ProcessOptions, ProcessOutput};
+use crate::scene_detector::{DetectionAlgorithm, FrameFeatures, SceneDetection, TransitionType};
+
+/// 使用已知场景边界的场景检测
+///
+/// 不计算完整的帧特征，只用 dHash 计算相邻帧差异，供关键帧选择和运动强度使用
+#[derive(Debug, Clone)]
+pub struct KnownScenesDetector {
+    scene_changes: Vec<f64>,
+    transitions: Vec<Option<TransitionType>>,
+}
+
+impl KnownScenesDetector {
+    /// 使用元数据中各场景的开始时间和转场类型
+    pub fn from_metadata(metadata: &VideoMetadata) -> Self {
+        Self {
+            scene_changes: metadata.scenes.iter().map(|scene| scene.start_time).collect(),
+            transitions: metadata.scenes.iter().map(|scene| scene.transition_type).collect(),
+        }
+    }
+}
+
+impl Detector for KnownScenesDetector {
+    fn detect(&self, _frames: &[(f64, DynamicImage)], _fps: f64, _config: &ProcessConfig) -> anyhow::Result<Vec<f64>> {
+        Ok(self.scene_changes.clone())
+    }
+
+    fn detect_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        _fps: f64,
+        _config: &ProcessConfig,
+    ) -> anyhow::Result<(Vec<f64>, Vec<f64>)> {
+        let hashes: Vec<u64> = frames
+            .par_iter()
+            .map(|(_, image)| FrameFeatures::hashed(image, DetectionAlgorithm::DHash).hash().unwrap_or_default())
+            .collect();
+        let differences = std::iter::once(0.0)
+            .chain(hashes.windows(2).map(|pair| (pair[0] ^ pair[1]).count_ones() as f64 / 64.0))
+            .take(frames.len())
+            .collect();
+        Ok((self.scene_changes.clone(), differences))
+    }
+
+    fn detect_with_transitions(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        fps: f64,
+        config: &ProcessConfig,
+    ) -> anyhow::Result<SceneDetection> {
+        let (scene_changes, differences) = self.detect_with_differences(frames, fps, config)?;
+        Ok((scene_changes, differences, self.transitions.clone()))
+    }
+}
+
+/// 读取 metadata.json
+pub fn load_metadata(path: &Path) -> anyhow::Result<VideoMetadata> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("读取元数据失败: {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("解析元数据失败: {}", path.display()))
+}
+
+/// 按已有的元数据重新处理视频，场景边界与之前的结果相同
+///
+/// `input` 为空时使用元数据中的 `input_video`；`keyframes_per_scene` 大于 1 时使用 [`SpreadFrameSelector`]。
+/// 场景边界已经确定，不再按音频校准或按目标场景数调整阈值，也不使用检查点
+pub async fn reprocess(
+    metadata_path: &Path,
+    input: Option<&Path>,
+    output_dir: &Path,
+    mut config: ProcessConfig,
+    keyframes_per_scene: usize,
+    options: ProcessOptions,
+) -> Result<ProcessOutput, VideoParseError> {
+    if keyframes_per_scene == 0 {
+        return Err(VideoParseError::Config("keyframes_per_scene 必须大于 0".to_string()));
+    }
+    let metadata = load_metadata(metadata_path)?;
+    if metadata.media_kind != MediaKind::Video || metadata.scenes.is_empty() {
+        return Err(VideoParseError::InvalidInput(format!(
+            "元数据中没有视频场景，无法重新处理: {}", metadata_path.display()
+        )));
+    }
+    let input = input.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(&metadata.input_video));
+    if !input.exists() {
+        return Err(VideoParseError::InvalidInput(format!(
+            "输入视频不存在: {}（可以用 --input 指定）", input.display()
+        )));
+    }
+
+    config.audio_boundary.enabled = false;
+    config.target_scene_range = None;
+    config.checkpoint = false;
+    tracing::info!(
+        metadata = %metadata_path.display(),
+        scene_count = metadata.scenes.len(),
+        keyframes_per_scene,
+        "♻️  [重新处理] 复用已有的场景边界"
+    );
+
+    let mut pipeline = Pipeline::for_config(&config).with_detector(KnownScenesDetector::from_metadata(&metadata));
+    if keyframes_per_scene > 1 {
+        pipeline = pipeline.with_keyframe_selector(SpreadFrameSelector { per_scene: keyframes_per_scene });
+    }
+    pipeline.run_with_options(&input, output_dir, config, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::SceneMetadata;
+
+    #[test]
+    fn test_known_scenes_detector() {
+        let json = serde_json::json!({
+            "input_video": "demo.mp4",
+            "total_duration": 6.0,
+            "fps": 25.0,
+            "resolution": "16x16",
+            "scene_count": 2,
+            "audio_file": "",
+            "scenes": [
+                SceneMetadata::fixture(0, 0.0, 2.5),
+                SceneMetadata { transition_type: Some(TransitionType::Dissolve), ..SceneMetadata::fixture(1, 2.5, 6.0) },
+            ],
+        });
+        let metadata: VideoMetadata = serde_json::from_value(json).unwrap();
+        let detector = KnownScenesDetector::from_metadata(&metadata);
+
+        let frames: Vec<(f64, DynamicImage)> = (0..6u8)
+            .map(|i| {
+                let image = image::GrayImage::from_fn(16, 16, |x, _| image::Luma([if x < 8 { i * 40 } else { 255 - i * 40 }]));
+                (i as f64, DynamicImage::ImageLuma8(image))
+            })
+            .collect();
+        let (scene_changes, differences, transitions) =
+            detector.detect_with_transitions(&frames, 1.0, &ProcessConfig::default()).unwrap();
+        assert_eq!(scene_changes, [0.0, 2.5]);
+        assert_eq!(transitions, [None, Some(TransitionType::Dissolve)]);
+        assert_eq!(differences.len(), frames.len());
+        assert_eq!(differences[0], 0.0);
+        assert!(differences.iter().all(|d| (0.0..=1.0).contains(d)));
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 内存采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 单个处理阶段的资源占用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageUsage {
+    /// 阶段名称
+    pub stage: String,
+    /// 阶段开始时的 RSS（字节）
+    pub rss_start_bytes: u64,
+    /// 阶段结束时的 RSS（字节）
+    pub rss_end_bytes: u64,
+    /// 阶段内的峰值 RSS（字节）
+    pub peak_rss_bytes: u64,
+    /// 阶段内新增的内存占用（峰值 RSS 减去开始时的 RSS）
+    pub allocated_bytes: u64,
+    /// 阶段结束时的临时磁盘占用（字节）
+    pub disk_bytes: u64,
+}
+
+/// 一次处理任务的资源占用汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// 峰值 RSS（字节）
+    pub peak_rss_bytes: u64,
+    /// 峰值临时磁盘占用（字节，输入文件 + 输出目录）
+    pub peak_disk_bytes: u64,
+    /// 各阶段资源占用
+    pub stages: Vec<StageUsage>,
+}
+
+impl ResourceUsage {
+    /// 建议的函数计算内存规格（MB）：峰值 RSS 留 30% 余量后向上取整到 64MB
+    pub fn suggested_memory_mb(&self) -> u64 {
+        let with_headroom = self.peak_rss_bytes as f64 * 1.3 / 1024.0 / 1024.0;
+        ((with_headroom / 64.0).ceil() as u64).max(1) * 64
+    }
+}
+
+/// 一次处理任务的分阶段耗时和传输量
+///
+/// 本地处理时只有处理阶段的耗时；OSS 事件处理时还包含下载和上传
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimingBreakdown {
+    /// 下载耗时（毫秒，分段下载时与处理重叠）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_ms: Option<u64>,
+    /// 帧提取耗时（毫秒）
+    pub extract_ms: u64,
+    /// 场景检测耗时（毫秒）
+    pub detect_ms: u64,
+    /// 关键帧保存耗时（毫秒）
+    pub keyframe_ms: u64,
+    /// 文字识别耗时（毫秒，启用 OCR 时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_ms: Option<u64>,
+    /// 人脸检测耗时（毫秒，启用人脸检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faces_ms: Option<u64>,
+    /// 内容安全检测耗时（毫秒，启用内容安全检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety_ms: Option<u64>,
+    /// 图像向量计算耗时（毫秒，启用图像向量时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embeddings_ms: Option<u64>,
+    /// 关键帧标注耗时（毫秒，启用关键帧标注时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment_ms: Option<u64>,
+    /// 关键帧分类耗时（毫秒，添加了关键帧分类器时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation_ms: Option<u64>,
+    /// 缩略图拼图耗时（毫秒，启用拼图时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprite_ms: Option<u64>,
+    /// 场景片段导出耗时（毫秒，启用片段导出时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clips_ms: Option<u64>,
+    /// 场景预览动图生成耗时（毫秒，启用预览动图时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_ms: Option<u64>,
+    /// 音频波形分析耗时（毫秒，启用波形分析时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waveform_ms: Option<u64>,
+    /// 语音活动检测耗时（毫秒，启用语音活动检测时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vad_ms: Option<u64>,
+    /// 音频提取耗时（毫秒）
+    pub audio_ms: u64,
+    /// 元数据生成耗时（毫秒）
+    pub metadata_ms: u64,
+    /// 视频处理总耗时（毫秒，不含下载和上传）
+    pub process_ms: u64,
+    /// 上传耗时（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_ms: Option<u64>,
+    /// 任务总耗时（毫秒）
+    pub total_ms: u64,
+    /// 下载字节数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_bytes: Option<u64>,
+    /// 上传字节数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_bytes: Option<u64>,
+    /// 上传文件数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_files: Option<usize>,
+}
+
+/// 资源占用监视器
+///
+/// 后台线程按固定间隔采样进程 RSS，记录整个任务和当前阶段的峰值。
+/// RSS 是进程级别的，服务模式下并发处理多个任务时各任务的数值会相互叠加
+pub struct ResourceMonitor {
+    peak: Arc<AtomicU64>,
+    stage_peak: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    sampler: Option<JoinHandle<()>>,
+    current_stage: Option<(String, u64)>,
+    peak_disk: u64,
+    stages: Vec<StageUsage>,
+}
+
+impl ResourceMonitor {
+    /// 启动监视器
+    pub fn start() -> Self {
+        let rss = current_rss_bytes().unwrap_or(0);
+        let peak = Arc::new(AtomicU64::new(rss));
+        let stage_peak = Arc::new(AtomicU64::new(rss));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let sampler = {
+            let peak = peak.clone();
+            let stage_peak = stage_peak.clone();
+            let stop = stop.clone();
+            std::thread::Builder::new()
+                .name("resource-monitor".to_string())
+                .spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        if let Some(rss) = current_rss_bytes() {
+                            peak.fetch_max(rss, Ordering::Relaxed);
+                            stage_peak.fetch_max(rss, Ordering::Relaxed);
+                        }
+                        std::thread::sleep(SAMPLE_INTERVAL);
+                    }
+                })
+                .ok()
+        };
+
+        Self {
+            peak,
+            stage_peak,
+            stop,
+            sampler,
+            current_stage: None,
+            peak_disk: 0,
+            stages: Vec::new(),
+        }
+    }
+
+    /// 开始一个阶段（未结束的上一个阶段会以 0 磁盘占用结束）
+    pub fn begin_stage(&mut self, stage: &str) {
+        if self.current_stage.is_some() {
+            self.end_stage(0);
+        }
+        let rss = self.sample();
+        self.stage_peak.store(rss, Ordering::Relaxed);
+        self.current_stage = Some((stage.to_string(), rss));
+    }
+
+    /// 结束当前阶段并记录阶段结束时的临时磁盘占用
+    pub fn end_stage(&mut self, disk_bytes: u64) {
+        let Some((stage, rss_start)) = self.current_stage.take() else {
+            return;
+        };
+        let rss_end = self.sample();
+        let peak_rss = self.stage_peak.load(Ordering::Relaxed).max(rss_end);
+        self.peak_disk = self.peak_disk.max(disk_bytes);
+        self.stages.push(StageUsage {
+            stage,
+            rss_start_bytes: rss_start,
+            rss_end_bytes: rss_end,
+            peak_rss_bytes: peak_rss,
+            allocated_bytes: peak_rss.saturating_sub(rss_start),
+            disk_bytes,
+        });
+    }
+
+    /// 停止采样并返回汇总结果
+    pub fn finish(mut self) -> ResourceUsage {
+        if self.current_stage.is_some() {
+            self.end_stage(0);
+        }
+        self.sample();
+        self.shutdown();
+        ResourceUsage {
+            peak_rss_bytes: self.peak.load(Ordering::Relaxed),
+            peak_disk_bytes: self.peak_disk,
+            stages: std::mem::take(&mut self.stages),
+        }
+    }
+
+    /// 立即采样一次，避免遗漏两次采样之间的短暂峰值
+    fn sample(&self) -> u64 {
+        let rss = current_rss_bytes().unwrap_or(0);
+        self.peak.fetch_max(rss, Ordering::Relaxed);
+        self.stage_peak.fetch_max(rss, Ordering::Relaxed);
+        rss
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.sampler.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ResourceMonitor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 当前进程的 RSS（字节），仅支持 Linux，其他平台返回 None
+pub fn current_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find(|line| line.starts_with("VmRSS:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// 路径占用的磁盘大小（目录递归统计，不存在时为 0）
+pub fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// 字节数格式化为 MB
+pub fn format_mb(bytes: u64) -> String {
+    format!("{:.1}MB", bytes as f64 / 1024.0 / 1024.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_records_stages() {
+        let mut monitor = ResourceMonitor::start();
+        monitor.begin_stage("alloc");
+        let buffer = vec![1u8; 32 * 1024 * 1024];
+        std::hint::black_box(&buffer);
+        monitor.end_stage(123);
+        drop(buffer);
+        monitor.begin_stage("idle");
+        let usage = monitor.finish();
+
+        assert_eq!(usage.stages.len(), 2);
+        assert_eq!(usage.stages[0].stage, "alloc");
+        assert_eq!(usage.stages[0].disk_bytes, 123);
+        assert_eq!(usage.peak_disk_bytes, 123);
+        assert!(usage.peak_rss_bytes >= usage.stages[0].peak_rss_bytes);
+        if cfg!(target_os = "linux") {
+            assert!(usage.stages[0].allocated_bytes >= 16 * 1024 * 1024);
+        }
+    }
+}
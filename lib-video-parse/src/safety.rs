@@ -0,0 +1,245 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::metadata::SceneMetadata;
+
+/// 内容安全检测配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetyConfig {
+    /// 是否对关键帧做内容安全分类
+    pub enabled: bool,
+    /// 分类模型路径（ONNX，输入 1x224x224x3 或 1x3x224x224 的 RGB 图像，输出各类别概率）
+    pub model_path: Option<PathBuf>,
+    /// 模型输出的类别名称（按输出顺序）
+    pub labels: Vec<String>,
+    /// 需要拦截的类别
+    pub flagged_labels: Vec<String>,
+    /// 拦截阈值：任一需要拦截的类别得分不低于该值时标记
+    pub threshold: f32,
+    /// 视频被标记时是否跳过上传处理结果
+    pub block_upload: bool,
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        // 默认类别与常见的开源 NSFW 分类模型（GantMan/nsfw_model）一致
+        Self {
+            enabled: false,
+            model_path: None,
+            labels: parse_labels("drawings,hentai,neutral,porn,sexy"),
+            flagged_labels: parse_labels("hentai,porn,sexy"),
+            threshold: 0.8,
+            block_upload: false,
+        }
+    }
+}
+
+/// 解析逗号分隔的类别列表
+pub fn parse_labels(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .collect()
+}
+
+/// 单个关键帧的分类结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetyScores {
+    /// 各类别得分
+    pub scores: BTreeMap<String, f32>,
+    /// 是否被标记
+    pub flagged: bool,
+}
+
+/// 整个视频的内容安全汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetySummary {
+    /// 是否有关键帧被标记
+    pub flagged: bool,
+    /// 需要拦截的类别中的最高得分
+    pub max_score: f32,
+    /// 最高得分对应的类别
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_label: Option<String>,
+    /// 被标记的场景编号
+    pub flagged_scenes: Vec<usize>,
+}
+
+impl SafetyConfig {
+    /// 按模型输出的概率生成分类结果
+    pub fn score(&self, probabilities: &[f32]) -> SafetyScores {
+        let scores: BTreeMap<String, f32> = self
+            .labels
+            .iter()
+            .zip(probabilities)
+            .map(|(label, p)| (label.clone(), *p))
+            .collect();
+        let flagged = self
+            .flagged_labels
+            .iter()
+            .any(|label| scores.get(label).is_some_and(|s| *s >= self.threshold));
+        SafetyScores { scores, flagged }
+    }
+
+    /// 汇总各场景的分类结果（没有场景被分类时返回 None）
+    pub fn summarize(&self, scenes: &[SceneMetadata]) -> Option<SafetySummary> {
+        let mut summary = SafetySummary {
+            flagged: false,
+            max_score: 0.0,
+            max_label: None,
+            flagged_scenes: Vec::new(),
+        };
+        let mut classified = false;
+        for scene in scenes {
+            let Some(ref safety) = scene.safety else {
+                continue;
+            };
+            classified = true;
+            if safety.flagged {
+                summary.flagged = true;
+                summary.flagged_scenes.push(scene.scene_id);
+            }
+            for label in &self.flagged_labels {
+                if let Some(&score) = safety.scores.get(label) {
+                    if score > summary.max_score {
+                        summary.max_score = score;
+                        summary.max_label = Some(label.clone());
+                    }
+                }
+            }
+        }
+        classified.then_some(summary)
+    }
+}
+
+/// 对每个场景的关键帧做内容安全分类，写入 `SceneMetadata.safety`
+///
+/// 需要以 `--features safety` 编译，并能加载 onnxruntime 动态库（通过 ORT_DYLIB_PATH 指定路径）。
+/// 单帧分类失败只记录警告。返回被标记的场景数
+#[cfg(feature = "safety")]
+pub fn classify_scenes(config: &SafetyConfig, output_dir: &Path, scenes: &mut [SceneMetadata]) -> Result<usize> {
+    use anyhow::Context;
+
+    let model_path = config.model_path.as_ref().context("未配置内容安全模型路径（safety_model）")?;
+    let mut classifier = onnx::Classifier::load(model_path)?;
+    let mut flagged = 0;
+    for scene in scenes.iter_mut() {
+        let keyframe_path = output_dir.join(&scene.keyframe_file);
+        let result = image::open(&keyframe_path)
+            .context(format!("读取关键帧失败: {}", keyframe_path.display()))
+            .and_then(|image| classifier.classify(&image));
+        match result {
+            Ok(probabilities) => {
+                let scores = config.score(&probabilities);
+                if scores.flagged {
+                    flagged += 1;
+                    tracing::warn!(scene_id = scene.scene_id, scores = ?scores.scores, "🚫 [内容安全] 关键帧被标记");
+                }
+                scene.safety = Some(scores);
+            }
+            Err(e) => {
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [内容安全] 关键帧分类失败");
+            }
+        }
+    }
+    Ok(flagged)
+}
+
+/// 未启用 `safety` feature 时跳过内容安全检测
+#[cfg(not(feature = "safety"))]
+pub fn classify_scenes(_config: &SafetyConfig, _output_dir: &Path, _scenes: &mut [SceneMetadata]) -> Result<usize> {
+    tracing::warn!("⚠️  已启用内容安全检测，但未启用 safety feature，跳过");
+    Ok(0)
+}
+
+#[cfg(feature = "safety")]
+mod onnx {
+    use anyhow::{Context, Result};
+    use image::{imageops::FilterType, DynamicImage};
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use std::path::Path;
+
+    /// 模型输入边长
+    const INPUT_SIZE: usize = 224;
+
+    /// 图像分类模型
+    ///
+    /// 根据模型输入形状自动选择 NHWC（Keras 导出）或 NCHW（PyTorch 导出）布局，像素缩放到 0.0-1.0
+    pub struct Classifier {
+        session: Session,
+        channels_first: bool,
+    }
+
+    impl Classifier {
+        pub fn load(model_path: &Path) -> Result<Self> {
+            let session = Session::builder()
+                .and_then(|b| b.with_intra_threads(1))
+                .and_then(|b| b.commit_from_file(model_path))
+                .context(format!("加载内容安全模型失败: {}", model_path.display()))?;
+            let channels_first = session
+                .inputs
+                .first()
+                .and_then(|input| input.input_type.tensor_shape())
+                .is_some_and(|shape| shape.get(1) == Some(&3));
+            Ok(Self { session, channels_first })
+        }
+
+        /// 返回各类别概率（模型输出未归一化时做 softmax）
+        pub fn classify(&mut self, image: &DynamicImage) -> Result<Vec<f32>> {
+            let resized = image
+                .resize_exact(INPUT_SIZE as u32, INPUT_SIZE as u32, FilterType::Triangle)
+                .to_rgb8();
+            let plane = INPUT_SIZE * INPUT_SIZE;
+            let mut input = vec![0f32; plane * 3];
+            for (i, pixel) in resized.pixels().enumerate() {
+                for c in 0..3 {
+                    let index = if self.channels_first { c * plane + i } else { i * 3 + c };
+                    input[index] = pixel[c] as f32 / 255.0;
+                }
+            }
+            let shape = if self.channels_first {
+                [1, 3, INPUT_SIZE, INPUT_SIZE]
+            } else {
+                [1, INPUT_SIZE, INPUT_SIZE, 3]
+            };
+            let tensor = Tensor::from_array((shape, input))?;
+            let outputs = self.session.run(ort::inputs![tensor])?;
+            let (_, logits) = outputs[0].try_extract_tensor::<f32>()?;
+            Ok(normalize(logits))
+        }
+    }
+
+    fn normalize(values: &[f32]) -> Vec<f32> {
+        let sum: f32 = values.iter().sum();
+        if values.iter().all(|v| (0.0..=1.0).contains(v)) && (sum - 1.0).abs() < 0.01 {
+            return values.to_vec();
+        }
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let exp: Vec<f32> = values.iter().map(|v| (v - max).exp()).collect();
+        let total: f32 = exp.iter().sum();
+        exp.iter().map(|v| v / total).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_and_summarize() {
+        let config = SafetyConfig::default();
+        let safe = config.score(&[0.05, 0.0, 0.9, 0.02, 0.03]);
+        let unsafe_ = config.score(&[0.0, 0.02, 0.08, 0.85, 0.05]);
+        assert!(!safe.flagged);
+        assert!(unsafe_.flagged);
+
+        let scene = |scene_id, safety| SceneMetadata { safety: Some(safety), ..SceneMetadata::fixture(scene_id, 0.0, 1.0) };
+        let summary = config.summarize(&[scene(0, safe), scene(1, unsafe_)]).unwrap();
+        assert!(summary.flagged);
+        assert_eq!(summary.flagged_scenes, vec![1]);
+        assert_eq!(summary.max_label.as_deref(), Some("porn"));
+    }
+}
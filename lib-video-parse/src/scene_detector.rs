@@ -1,5 +1,332 @@
 use image::{DynamicImage, GrayImage};
-use anyhow::Result;
+use crate::error::Result;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use crate::simd;
+
+/// 参与帧差异计算的度量项
+///
+/// 关闭的度量项不计入加权组合（其余项按权重重新归一化），提取帧特征时也会跳过对应的计算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectionMetrics {
+    /// 区域分割分析（权重 25%）
+    pub region: bool,
+    /// Sobel 边缘差异（权重 25%）
+    pub edge: bool,
+    /// HSV 颜色空间差异（权重 20%）
+    pub hsv: bool,
+    /// 梯度差异（权重 15%）
+    pub gradient: bool,
+    /// 直方图 + 像素差异（权重 15%）
+    pub traditional: bool,
+}
+
+impl DetectionMetrics {
+    /// 启用全部度量项
+    pub fn all() -> Self {
+        Self {
+            region: true,
+            edge: true,
+            hsv: true,
+            gradient: true,
+            traditional: true,
+        }
+    }
+
+    /// 以逗号分隔的名称列出启用的度量项（用于日志和配置指纹）
+    pub fn names(&self) -> String {
+        [
+            (self.region, "region"),
+            (self.edge, "edge"),
+            (self.hsv, "hsv"),
+            (self.gradient, "gradient"),
+            (self.traditional, "traditional"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+impl Default for DetectionMetrics {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// 帧差异算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetectionAlgorithm {
+    /// 区域/边缘/HSV/梯度/直方图加权组合（默认），按 [`DetectionMetrics`] 启用各项
+    #[default]
+    Metrics,
+    /// 感知哈希：32x32 灰度图做 DCT，取低频 8x8 系数与中位数比较
+    PHash,
+    /// 差值哈希：9x8 灰度图相邻像素比较
+    DHash,
+}
+
+impl DetectionAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetectionAlgorithm::Metrics => "metrics",
+            DetectionAlgorithm::PHash => "phash",
+            DetectionAlgorithm::DHash => "dhash",
+        }
+    }
+
+    /// 是否为感知哈希类算法
+    pub fn is_hash(&self) -> bool {
+        !matches!(self, DetectionAlgorithm::Metrics)
+    }
+}
+
+impl std::str::FromStr for DetectionAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "metrics" => Ok(DetectionAlgorithm::Metrics),
+            "phash" => Ok(DetectionAlgorithm::PHash),
+            "dhash" => Ok(DetectionAlgorithm::DHash),
+            other => Err(format!("未知的检测算法: {}（可选值: metrics, phash, dhash）", other)),
+        }
+    }
+}
+
+/// 场景切换的判定方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThresholdMode {
+    /// 差异超过固定阈值 `threshold` 时切换（默认）
+    #[default]
+    Fixed,
+    /// 差异超过最近若干帧差异的均值 + k·标准差时切换，适应不同亮度和运动强度的视频
+    Adaptive,
+}
+
+impl ThresholdMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThresholdMode::Fixed => "fixed",
+            ThresholdMode::Adaptive => "adaptive",
+        }
+    }
+}
+
+impl std::str::FromStr for ThresholdMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fixed" => Ok(ThresholdMode::Fixed),
+            "adaptive" => Ok(ThresholdMode::Adaptive),
+            other => Err(format!("未知的阈值模式: {}（可选值: fixed, adaptive）", other)),
+        }
+    }
+}
+
+/// 自适应阈值参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveThreshold {
+    /// 标准差倍数 k
+    pub k: f64,
+    /// 参与统计的最近帧数
+    pub window: usize,
+}
+
+impl Default for AdaptiveThreshold {
+    fn default() -> Self {
+        Self { k: 3.0, window: 30 }
+    }
+}
+
+/// 自适应模式开始生效前需要的帧差异数，不足时使用固定阈值
+const ADAPTIVE_MIN_SAMPLES: usize = 5;
+/// 自适应模式下判定为切换的最小差异，避免静止画面的微小噪声被判为切换
+const ADAPTIVE_MIN_DIFFERENCE: f64 = 0.05;
+
+/// 灰度不超过该值的像素视为黑色
+const BLACK_PIXEL_LEVEL: usize = 32;
+/// 黑色像素占比不低于该值的帧视为黑场
+const BLACK_FRAME_RATIO: f64 = 0.98;
+/// 相邻帧平均亮度下降超过该值时视为在变暗
+const FADE_LUMA_STEP: f64 = 0.01;
+/// 进入黑场前至少连续变暗（或离开黑场后连续变亮）的帧数，达到时视为渐隐（渐显）
+const FADE_MIN_FRAMES: usize = 2;
+/// 叠化：相邻帧差异不低于阈值的该比例时视为渐变中
+const DISSOLVE_MIN_RATIO: f64 = 0.3;
+/// 叠化至少持续的帧数
+const DISSOLVE_MIN_FRAMES: usize = 3;
+
+/// 场景开始处的转场类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionType {
+    /// 硬切
+    Cut,
+    /// 从黑场渐显
+    FadeIn,
+    /// 前一场景渐隐到黑场后切入
+    FadeOut,
+    /// 叠化：前后两个场景在多帧内逐渐过渡
+    Dissolve,
+}
+
+impl TransitionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransitionType::Cut => "cut",
+            TransitionType::FadeIn => "fade_in",
+            TransitionType::FadeOut => "fade_out",
+            TransitionType::Dissolve => "dissolve",
+        }
+    }
+}
+
+/// 场景检测结果：场景起始时间、每帧与前一帧的差异和每个场景开始处的转场类型
+pub type SceneDetection = (Vec<f64>, Vec<f64>, Vec<Option<TransitionType>>);
+
+/// 检测到的场景切点（第一个场景的开始不算切点）
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SceneCut {
+    /// 切点时间（秒），即新场景的开始时间
+    pub time: f64,
+    /// 置信度：切点所在帧与前一帧的差异（0 到 1），越高越确定是镜头切换
+    pub score: f64,
+    /// 单项差异超过阈值的度量项；哈希算法下为算法名，叠化按渐变前后两帧计算
+    pub contributing_metrics: Vec<String>,
+}
+
+/// 单帧的派生特征（灰度图、直方图、边缘图、HSV 采样）
+///
+/// 每帧只计算一次，作为"前一帧"和"当前帧"参与两次比较时复用，
+/// 避免每次比较都重新做灰度/RGB 转换
+pub struct FrameFeatures {
+    /// 灰度图
+    gray: GrayImage,
+    /// 256 级灰度直方图
+    histogram: [u32; 256],
+    /// Sobel 边缘强度（内部像素）
+    edges: Vec<u16>,
+    /// 边缘强度总和
+    edge_sum: u64,
+    /// 中心差分梯度幅值（内部像素）
+    gradients: Vec<u16>,
+    /// HSV 采样（每 2x2 像素采样一次）
+    hsv: Vec<(f64, f64, f64)>,
+    /// 64 位感知哈希（只在哈希算法下计算）
+    hash: Option<u64>,
+}
+
+impl FrameFeatures {
+    /// 从帧图像计算全部特征（原始分辨率）
+    pub fn from_image(image: &DynamicImage) -> Self {
+        Self::compute(image, &DetectionMetrics::all(), None)
+    }
+
+    /// 按启用的度量项计算特征
+    ///
+    /// 设置了 `analysis_width` 且帧宽度更大时，先等比缩小到该宽度再计算
+    pub fn compute(image: &DynamicImage, metrics: &DetectionMetrics, analysis_width: Option<u32>) -> Self {
+        let image = match analysis_width {
+            Some(target) if target > 0 && image.width() > target => {
+                let height = ((image.height() as u64 * target as u64) / image.width() as u64).max(1) as u32;
+                Cow::Owned(image.thumbnail_exact(target, height))
+            }
+            _ => Cow::Borrowed(image),
+        };
+
+        let gray = image.to_luma8();
+        let histogram = simd::histogram(gray.as_raw());
+
+        let edges = if metrics.edge {
+            simd::sobel_magnitudes(gray.as_raw(), gray.width() as usize, gray.height() as usize)
+        } else {
+            Vec::new()
+        };
+        let edge_sum = edges.iter().map(|&e| e as u64).sum();
+
+        let gradients = if metrics.gradient {
+            simd::gradient_magnitudes(gray.as_raw(), gray.width() as usize, gray.height() as usize)
+        } else {
+            Vec::new()
+        };
+
+        let mut hsv = Vec::new();
+        if metrics.hsv {
+            let rgb = image.to_rgb8();
+            hsv.reserve((rgb.width().div_ceil(2) * rgb.height().div_ceil(2)) as usize);
+            // 隔行、每行隔一个像素采样，直接遍历原始像素行
+            let row_len = (rgb.width() as usize * 3).max(1);
+            for row in rgb.as_raw().chunks_exact(row_len).step_by(2) {
+                hsv.extend(row.chunks(6).map(|p| rgb_to_hsv(p[0], p[1], p[2])));
+            }
+        }
+
+        Self {
+            gray,
+            histogram,
+            edges,
+            edge_sum,
+            gradients,
+            hsv,
+            hash: None,
+        }
+    }
+
+    /// 只计算感知哈希，帧先缩小到哈希尺寸，其余特征不计算
+    pub fn hashed(image: &DynamicImage, algorithm: DetectionAlgorithm) -> Self {
+        let (gray, hash) = match algorithm {
+            DetectionAlgorithm::DHash => {
+                let gray = image.thumbnail_exact(9, 8).to_luma8();
+                let hash = dhash(&gray);
+                (gray, hash)
+            }
+            _ => {
+                let gray = image.thumbnail_exact(32, 32).to_luma8();
+                let hash = phash(&gray);
+                (gray, hash)
+            }
+        };
+        Self {
+            histogram: simd::histogram(gray.as_raw()),
+            gray,
+            edges: Vec::new(),
+            edge_sum: 0,
+            gradients: Vec::new(),
+            hsv: Vec::new(),
+            hash: Some(hash),
+        }
+    }
+
+    /// 感知哈希（按 [`FrameFeatures::hashed`] 计算时为 Some）
+    pub fn hash(&self) -> Option<u64> {
+        self.hash
+    }
+
+    pub fn width(&self) -> u32 {
+        self.gray.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.gray.height()
+    }
+
+    fn same_size(&self, other: &FrameFeatures) -> bool {
+        self.width() == other.width() && self.height() == other.height()
+    }
+
+    /// 平均亮度（0 到 1）和是否为黑场
+    fn luma(&self) -> (f64, bool) {
+        let total = self.histogram.iter().map(|&n| n as u64).sum::<u64>().max(1) as f64;
+        let mean = self.histogram.iter().enumerate().map(|(level, &n)| level as f64 * n as f64).sum::<f64>() / total / 255.0;
+        let black = self.histogram[..=BLACK_PIXEL_LEVEL].iter().map(|&n| n as u64).sum::<u64>() as f64 / total;
+        (mean, black >= BLACK_FRAME_RATIO)
+    }
+}
 
 /// 场景检测器，用于检测视频中的镜头切换点
 pub struct SceneDetector {
@@ -7,6 +334,16 @@ pub struct SceneDetector {
     threshold: f64,
     /// 最小场景持续时间（秒）
     min_scene_duration: f64,
+    /// 启用的差异度量项
+    metrics: DetectionMetrics,
+    /// 分析分辨率（帧宽度，None 表示使用原始分辨率）
+    analysis_width: Option<u32>,
+    /// 帧差异算法
+    algorithm: DetectionAlgorithm,
+    /// 自适应阈值参数（None 表示使用固定阈值）
+    adaptive: Option<AdaptiveThreshold>,
+    /// 是否检测渐隐、渐显和叠化等渐变转场
+    transitions: bool,
 }
 
 impl SceneDetector {
@@ -14,7 +351,50 @@ impl SceneDetector {
         Self {
             threshold,
             min_scene_duration,
+            metrics: DetectionMetrics::all(),
+            analysis_width: None,
+            algorithm: DetectionAlgorithm::Metrics,
+            adaptive: None,
+            transitions: false,
+        }
+    }
+
+    /// 设置启用的差异度量项
+    pub fn with_metrics(mut self, metrics: DetectionMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// 设置分析分辨率（帧宽度），更宽的帧会先缩小再计算差异
+    pub fn with_analysis_width(mut self, analysis_width: Option<u32>) -> Self {
+        self.analysis_width = analysis_width;
+        self
+    }
+
+    /// 设置帧差异算法，哈希算法忽略度量项和分析分辨率
+    pub fn with_algorithm(mut self, algorithm: DetectionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// 使用自适应阈值（None 表示使用固定阈值）
+    pub fn with_adaptive_threshold(mut self, adaptive: Option<AdaptiveThreshold>) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// 检测渐隐、渐显（经过黑场）和叠化等渐变转场；不启用时只检测硬切
+    pub fn with_transitions(mut self, transitions: bool) -> Self {
+        self.transitions = transitions;
+        self
+    }
+
+    /// 按检测器的算法、度量项和分析分辨率计算帧特征
+    pub fn features(&self, image: &DynamicImage) -> FrameFeatures {
+        if self.algorithm.is_hash() {
+            return FrameFeatures::hashed(image, self.algorithm);
         }
+        FrameFeatures::compute(image, &self.metrics, self.analysis_width)
     }
 
     /// 计算两帧之间的差异度（高级算法）
@@ -27,51 +407,89 @@ impl SceneDetector {
     /// 4. 梯度分析：比较图像梯度分布
     /// 5. 传统方法：直方图和像素差异
     pub fn calculate_frame_difference(&self, frame1: &DynamicImage, frame2: &DynamicImage) -> f64 {
-        // 将图像转换为灰度图
-        let gray1 = frame1.to_luma8();
-        let gray2 = frame2.to_luma8();
-        
+        self.calculate_features_difference(&self.features(frame1), &self.features(frame2))
+    }
+
+    /// 基于预先计算的帧特征计算差异度，结果与 `calculate_frame_difference` 相同
+    ///
+    /// 两帧都带有感知哈希时，差异度为哈希的汉明距离除以 64
+    pub fn calculate_features_difference(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> f64 {
+        if let (Some(hash1), Some(hash2)) = (frame1.hash, frame2.hash) {
+            return (hash1 ^ hash2).count_ones() as f64 / 64.0;
+        }
+        let mut weighted_sum = 0.0;
+        let mut total_weight = 0.0;
+        for (_, difference, weight) in self.metric_differences(frame1, frame2) {
+            weighted_sum += difference * weight;
+            total_weight += weight;
+        }
+
+        // 加权组合所有启用的差异度量（按启用项的权重和归一化）
+        if total_weight > 0.0 {
+            weighted_sum / total_weight
+        } else {
+            0.0
+        }
+    }
+
+    /// 各启用度量项的名称、差异和权重；两帧都带有感知哈希时只有一项（权重 1）
+    pub fn metric_differences(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> Vec<(&'static str, f64, f64)> {
+        if let (Some(hash1), Some(hash2)) = (frame1.hash, frame2.hash) {
+            return vec![(self.algorithm.as_str(), (hash1 ^ hash2).count_ones() as f64 / 64.0, 1.0)];
+        }
+        let metrics = &self.metrics;
+        let mut differences = Vec::with_capacity(5);
+
         // 1. 区域分割分析（权重 25%）
         // 区分中心区域（主体）和边缘区域（背景）
-        let region_diff = self.calculate_region_difference(&gray1, &gray2);
-        
+        if metrics.region {
+            differences.push(("region", self.calculate_region_difference(&frame1.gray, &frame2.gray), 0.25));
+        }
+
         // 2. 边缘检测差异（权重 25%）
         // 使用Sobel算子检测边缘，比较边缘信息变化
-        let edge_diff = self.calculate_edge_difference(&gray1, &gray2);
-        
+        if metrics.edge {
+            differences.push(("edge", self.calculate_edge_difference(frame1, frame2), 0.25));
+        }
+
         // 3. HSV颜色空间分析（权重 20%）
         // 比较色调和饱和度的变化，对背景变化敏感
-        let hsv_diff = self.calculate_hsv_difference(frame1, frame2);
-        
+        if metrics.hsv {
+            differences.push(("hsv", self.calculate_hsv_difference(frame1, frame2), 0.20));
+        }
+
         // 4. 梯度分析（权重 15%）
         // 比较图像梯度分布的变化
-        let gradient_diff = self.calculate_gradient_difference(&gray1, &gray2);
-        
+        if metrics.gradient {
+            differences.push(("gradient", self.calculate_gradient_difference(frame1, frame2), 0.15));
+        }
+
         // 5. 传统方法（权重 15%）
         // 直方图差异和像素差异的组合
-        let hist_diff = self.calculate_histogram_difference(&gray1, &gray2);
-        let pixel_diff = self.calculate_pixel_difference(&gray1, &gray2);
-        let traditional_diff = hist_diff * 0.6 + pixel_diff * 0.4;
-        
-        // 加权组合所有差异度量
-        region_diff * 0.25 + edge_diff * 0.25 + hsv_diff * 0.20 + gradient_diff * 0.15 + traditional_diff * 0.15
+        if metrics.traditional {
+            let hist_diff = self.calculate_histogram_difference(frame1, frame2);
+            let pixel_diff = self.calculate_pixel_difference(&frame1.gray, &frame2.gray);
+            differences.push(("traditional", hist_diff * 0.6 + pixel_diff * 0.4, 0.15));
+        }
+        differences
+    }
+
+    /// 单项差异超过阈值的度量项
+    fn contributing_metrics(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> Vec<String> {
+        self.metric_differences(frame1, frame2)
+            .into_iter()
+            .filter(|(_, difference, _)| *difference > self.threshold)
+            .map(|(name, _, _)| name.to_string())
+            .collect()
     }
 
     /// 计算直方图差异
-    fn calculate_histogram_difference(&self, img1: &GrayImage, img2: &GrayImage) -> f64 {
-        let mut hist1 = [0u32; 256];
-        let mut hist2 = [0u32; 256];
-        
-        for pixel in img1.pixels() {
-            hist1[pixel[0] as usize] += 1;
-        }
-        
-        for pixel in img2.pixels() {
-            hist2[pixel[0] as usize] += 1;
-        }
+    fn calculate_histogram_difference(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> f64 {
+        let hist1 = &frame1.histogram;
+        let hist2 = &frame2.histogram;
         
         // 归一化直方图
-        let total_pixels = img1.width() * img1.height() as u32;
+        let total_pixels = frame1.width() * frame1.height();
         let mut diff = 0.0;
         
         for i in 0..256 {
@@ -89,12 +507,8 @@ impl SceneDetector {
             return 1.0;
         }
         
-        let mut diff_sum = 0u64;
         let total_pixels = img1.width() * img1.height();
-        
-        for (p1, p2) in img1.pixels().zip(img2.pixels()) {
-            diff_sum += (p1[0] as i32 - p2[0] as i32).abs() as u64;
-        }
+        let diff_sum = simd::sum_abs_diff(img1.as_raw(), img2.as_raw());
         
         diff_sum as f64 / (total_pixels as f64 * 255.0)
     }
@@ -118,27 +532,29 @@ impl SceneDetector {
         
         let mut center_diff_sum = 0u64;
         let mut edge_diff_sum = 0u64;
-        let mut center_pixels = 0u32;
-        let mut edge_pixels = 0u32;
+        let row_len = width as usize;
+        let (cx0, cx1) = (center_x_start as usize, center_x_end as usize);
+        let raw1 = img1.as_raw();
+        let raw2 = img2.as_raw();
         
+        // 逐行计算：中心行拆分为 [左边缘 | 中心 | 右边缘] 三段，其余行整行属于边缘区域
         for y in 0..height {
-            for x in 0..width {
-                let p1 = img1.get_pixel(x, y)[0];
-                let p2 = img2.get_pixel(x, y)[0];
-                let diff = (p1 as i32 - p2 as i32).abs() as u64;
-                
-                // 判断是否在中心区域
-                if x >= center_x_start && x < center_x_end && 
-                   y >= center_y_start && y < center_y_end {
-                    center_diff_sum += diff;
-                    center_pixels += 1;
-                } else {
-                    edge_diff_sum += diff;
-                    edge_pixels += 1;
-                }
+            let row_start = y as usize * row_len;
+            let row1 = &raw1[row_start..row_start + row_len];
+            let row2 = &raw2[row_start..row_start + row_len];
+            
+            if y >= center_y_start && y < center_y_end {
+                center_diff_sum += simd::sum_abs_diff(&row1[cx0..cx1], &row2[cx0..cx1]);
+                edge_diff_sum += simd::sum_abs_diff(&row1[..cx0], &row2[..cx0]);
+                edge_diff_sum += simd::sum_abs_diff(&row1[cx1..], &row2[cx1..]);
+            } else {
+                edge_diff_sum += simd::sum_abs_diff(row1, row2);
             }
         }
         
+        let center_pixels = (center_x_end - center_x_start) * (center_y_end - center_y_start);
+        let edge_pixels = width * height - center_pixels;
+        
         // 计算中心区域和边缘区域的差异
         let center_diff = if center_pixels > 0 {
             center_diff_sum as f64 / (center_pixels as f64 * 255.0)
@@ -165,69 +581,19 @@ impl SceneDetector {
 
     /// 计算边缘差异（使用Sobel算子）
     /// 边缘变化更能反映场景切换
-    fn calculate_edge_difference(&self, img1: &GrayImage, img2: &GrayImage) -> f64 {
-        if img1.width() != img2.width() || img1.height() != img2.height() {
+    fn calculate_edge_difference(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> f64 {
+        if !frame1.same_size(frame2) {
             return 1.0;
         }
-
-        let width = img1.width();
-        let height = img1.height();
-        
-        // Sobel算子（简化版，只计算x和y方向的梯度）
-        let sobel_x: [[i32; 3]; 3] = [
-            [-1, 0, 1],
-            [-2, 0, 2],
-            [-1, 0, 1],
-        ];
         
-        let sobel_y: [[i32; 3]; 3] = [
-            [-1, -2, -1],
-            [ 0,  0,  0],
-            [ 1,  2,  1],
-        ];
-        
-        // 计算边缘强度
-        let mut edge1_sum = 0u64;
-        let mut edge2_sum = 0u64;
-        let mut edge_diff_sum = 0u64;
-        let mut edge_pixels = 0u32;
-        
-        for y in 1..(height - 1) {
-            for x in 1..(width - 1) {
-                // 计算img1的边缘强度
-                let mut gx1 = 0i32;
-                let mut gy1 = 0i32;
-                for ky in 0..3 {
-                    for kx in 0..3 {
-                        let px = (x + kx) as i32 - 1;
-                        let py = (y + ky) as i32 - 1;
-                        let pixel = img1.get_pixel(px as u32, py as u32)[0] as i32;
-                        gx1 += pixel * sobel_x[ky as usize][kx as usize];
-                        gy1 += pixel * sobel_y[ky as usize][kx as usize];
-                    }
-                }
-                let edge1 = ((gx1 * gx1 + gy1 * gy1) as f64).sqrt() as u32;
-                
-                // 计算img2的边缘强度
-                let mut gx2 = 0i32;
-                let mut gy2 = 0i32;
-                for ky in 0..3 {
-                    for kx in 0..3 {
-                        let px = (x + kx) as i32 - 1;
-                        let py = (y + ky) as i32 - 1;
-                        let pixel = img2.get_pixel(px as u32, py as u32)[0] as i32;
-                        gx2 += pixel * sobel_x[ky as usize][kx as usize];
-                        gy2 += pixel * sobel_y[ky as usize][kx as usize];
-                    }
-                }
-                let edge2 = ((gx2 * gx2 + gy2 * gy2) as f64).sqrt() as u32;
-                
-                edge1_sum += edge1 as u64;
-                edge2_sum += edge2 as u64;
-                edge_diff_sum += (edge1 as i32 - edge2 as i32).abs() as u64;
-                edge_pixels += 1;
-            }
-        }
+        // Sobel算子（x和y方向的梯度）的边缘强度已在提取帧特征时由 SIMD 内核计算
+        let (edges1, edges2) = (&frame1.edges, &frame2.edges);
+        let (edge1_sum, edge2_sum) = (frame1.edge_sum, frame2.edge_sum);
+        let edge_diff_sum: u64 = edges1.iter()
+            .zip(edges2.iter())
+            .map(|(&e1, &e2)| e1.abs_diff(e2) as u64)
+            .sum();
+        let edge_pixels = edges1.len();
         
         if edge_pixels == 0 {
             return 0.0;
@@ -240,41 +606,29 @@ impl SceneDetector {
 
     /// 计算HSV颜色空间差异
     /// 对色调和饱和度的变化敏感，能检测背景颜色变化
-    fn calculate_hsv_difference(&self, img1: &DynamicImage, img2: &DynamicImage) -> f64 {
-        let rgb1 = img1.to_rgb8();
-        let rgb2 = img2.to_rgb8();
-        
-        if rgb1.width() != rgb2.width() || rgb1.height() != rgb2.height() {
+    fn calculate_hsv_difference(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> f64 {
+        if !frame1.same_size(frame2) {
             return 1.0;
         }
         
-        let width = rgb1.width();
-        let height = rgb1.height();
+        let width = frame1.width();
+        let height = frame1.height();
         let mut hue_diff_sum = 0.0;
         let mut sat_diff_sum = 0.0;
         let mut val_diff_sum = 0.0;
         
-        // 采样计算（每4个像素采样一次，提高性能）
-        for y in (0..height).step_by(2) {
-            for x in (0..width).step_by(2) {
-                let p1 = rgb1.get_pixel(x, y);
-                let p2 = rgb2.get_pixel(x, y);
-                
-                // RGB转HSV
-                let (h1, s1, v1) = self.rgb_to_hsv(p1[0], p1[1], p1[2]);
-                let (h2, s2, v2) = self.rgb_to_hsv(p2[0], p2[1], p2[2]);
-                
-                // 计算色调差异（考虑色环的循环性）
-                let mut hue_diff = (h1 - h2).abs();
-                if hue_diff > 180.0 {
-                    hue_diff = 360.0 - hue_diff;
-                }
-                hue_diff_sum += hue_diff / 180.0; // 归一化到0-1
-                
-                // 计算饱和度和亮度差异
-                sat_diff_sum += (s1 - s2).abs();
-                val_diff_sum += (v1 - v2).abs();
+        // 采样计算（每4个像素采样一次，提高性能），HSV 采样值已在提取帧特征时计算
+        for (&(h1, s1, v1), &(h2, s2, v2)) in frame1.hsv.iter().zip(frame2.hsv.iter()) {
+            // 计算色调差异（考虑色环的循环性）
+            let mut hue_diff = (h1 - h2).abs();
+            if hue_diff > 180.0 {
+                hue_diff = 360.0 - hue_diff;
             }
+            hue_diff_sum += hue_diff / 180.0; // 归一化到0-1
+            
+            // 计算饱和度和亮度差异
+            sat_diff_sum += (s1 - s2).abs();
+            val_diff_sum += (v1 - v2).abs();
         }
         
         let sample_count = ((width / 2) * (height / 2)) as f64;
@@ -286,73 +640,19 @@ impl SceneDetector {
         hue_diff * 0.5 + sat_diff * 0.3 + val_diff * 0.2
     }
 
-    /// RGB转HSV辅助函数
-    fn rgb_to_hsv(&self, r: u8, g: u8, b: u8) -> (f64, f64, f64) {
-        let r = r as f64 / 255.0;
-        let g = g as f64 / 255.0;
-        let b = b as f64 / 255.0;
-        
-        let max = r.max(g.max(b));
-        let min = r.min(g.min(b));
-        let delta = max - min;
-        
-        // 色调
-        let h = if delta == 0.0 {
-            0.0
-        } else if max == r {
-            60.0 * (((g - b) / delta) % 6.0)
-        } else if max == g {
-            60.0 * (((b - r) / delta) + 2.0)
-        } else {
-            60.0 * (((r - g) / delta) + 4.0)
-        };
-        
-        let h = if h < 0.0 { h + 360.0 } else { h };
-        
-        // 饱和度
-        let s = if max == 0.0 { 0.0 } else { delta / max };
-        
-        // 亮度
-        let v = max;
-        
-        (h, s, v)
-    }
-
     /// 计算梯度差异
-    /// 比较图像梯度分布的变化
-    fn calculate_gradient_difference(&self, img1: &GrayImage, img2: &GrayImage) -> f64 {
-        if img1.width() != img2.width() || img1.height() != img2.height() {
+    /// 比较图像梯度分布的变化，梯度幅值已在提取帧特征时计算
+    fn calculate_gradient_difference(&self, frame1: &FrameFeatures, frame2: &FrameFeatures) -> f64 {
+        if !frame1.same_size(frame2) {
             return 1.0;
         }
 
-        let width = img1.width();
-        let height = img1.height();
-        
-        let mut gradient_diff_sum = 0u64;
-        let mut gradient_pixels = 0u32;
-        
-        // 计算梯度（使用简单的差分）
-        for y in 1..(height - 1) {
-            for x in 1..(width - 1) {
-                // img1的梯度
-                let gx1 = img1.get_pixel(x + 1, y)[0] as i32 - img1.get_pixel(x - 1, y)[0] as i32;
-                let gy1 = img1.get_pixel(x, y + 1)[0] as i32 - img1.get_pixel(x, y - 1)[0] as i32;
-                let grad1 = ((gx1 * gx1 + gy1 * gy1) as f64).sqrt() as u32;
-                
-                // img2的梯度
-                let gx2 = img2.get_pixel(x + 1, y)[0] as i32 - img2.get_pixel(x - 1, y)[0] as i32;
-                let gy2 = img2.get_pixel(x, y + 1)[0] as i32 - img2.get_pixel(x, y - 1)[0] as i32;
-                let grad2 = ((gx2 * gx2 + gy2 * gy2) as f64).sqrt() as u32;
-                
-                gradient_diff_sum += (grad1 as i32 - grad2 as i32).abs() as u64;
-                gradient_pixels += 1;
-            }
-        }
-        
+        let gradient_pixels = frame1.gradients.len().min(frame2.gradients.len());
         if gradient_pixels == 0 {
             return 0.0;
         }
-        
+        let gradient_diff_sum = simd::sum_abs_diff_u16(&frame1.gradients, &frame2.gradients);
+
         // 归一化
         gradient_diff_sum as f64 / (gradient_pixels as f64 * 255.0 * 2.0_f64.sqrt())
     }
@@ -364,30 +664,423 @@ impl SceneDetector {
         frames: &[(f64, DynamicImage)],
         fps: f64,
     ) -> Result<Vec<f64>> {
-        if frames.len() < 2 {
-            return Ok(vec![0.0]);
+        self.detect_scenes_with_differences(frames, fps)
+            .map(|(scene_changes, _)| scene_changes)
+    }
+
+    /// 检测场景变化点，同时返回每帧与前一帧的差异（第一帧为 0，长度与 `frames` 相同）
+    pub fn detect_scenes_with_differences(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        _fps: f64,
+    ) -> Result<(Vec<f64>, Vec<f64>)> {
+        self.detect_scenes_with_transitions(frames, _fps)
+            .map(|(scene_changes, differences, _)| (scene_changes, differences))
+    }
+
+    /// 检测场景变化点，同时返回帧差异和每个场景开始处的转场类型（与场景起始时间一一对应）
+    pub fn detect_scenes_with_transitions(
+        &self,
+        frames: &[(f64, DynamicImage)],
+        _fps: f64,
+    ) -> Result<SceneDetection> {
+        let mut stream = self.stream();
+        for (time, image) in frames {
+            stream.push(*time, image);
         }
+        Ok(stream.finish_with_transitions())
+    }
 
-        let mut scene_changes = vec![0.0]; // 第一个场景从 0 开始
-        let _min_frame_interval = (self.min_scene_duration * fps) as usize;
+    /// 检测场景切点，带有置信度和超过阈值的度量项
+    pub fn detect_cuts(&self, frames: &[(f64, DynamicImage)]) -> Result<Vec<SceneCut>> {
+        let mut stream = self.stream();
+        for (time, image) in frames {
+            stream.push(*time, image);
+        }
+        Ok(stream.finish_with_cuts().1)
+    }
 
-        for i in 1..frames.len() {
-            let prev_idx = i - 1;
-            let diff = self.calculate_frame_difference(&frames[prev_idx].1, &frames[i].1);
-            
-            // 检查是否超过阈值且满足最小时间间隔
-            if diff > self.threshold {
-                let last_change = *scene_changes.last().unwrap();
-                let time_since_last = frames[i].0 - last_change;
-                
-                if time_since_last >= self.min_scene_duration {
-                    scene_changes.push(frames[i].0);
-                }
+    /// 逐帧检测场景变化，帧可以在加入后立即释放
+    pub fn stream(&self) -> SceneStream<'_> {
+        SceneStream {
+            detector: self,
+            prev_features: None,
+            scene_changes: vec![0.0], // 第一个场景从 0 开始
+            transitions: vec![None],
+            differences: Vec::new(),
+            cuts: Vec::new(),
+            recent: VecDeque::new(),
+            prev_luma: None,
+            darkening: 0,
+            brightening: None,
+            black: None,
+            leading_black: true,
+            gradual: None,
+        }
+    }
+}
+
+/// 逐帧进行的场景检测，由 [`SceneDetector::stream`] 创建
+///
+/// 只保留前一帧的特征，结果与 [`SceneDetector::detect_scenes_with_differences`] 相同
+pub struct SceneStream<'a> {
+    detector: &'a SceneDetector,
+    prev_features: Option<FrameFeatures>,
+    scene_changes: Vec<f64>,
+    differences: Vec<f64>,
+    /// 已检测到的切点（不含第一个场景的开始）
+    cuts: Vec<SceneCut>,
+    /// 自适应模式下最近的帧内差异（不含判定为切换的差异）
+    recent: VecDeque<f64>,
+    /// 与 `scene_changes` 一一对应的转场类型（第一个场景只在从黑场渐显时有值）
+    transitions: Vec<Option<TransitionType>>,
+    /// 前一帧的平均亮度
+    prev_luma: Option<f64>,
+    /// 到前一帧为止连续变暗的帧数
+    darkening: usize,
+    /// 离开黑场后仍在变亮时为 Some：离开黑场时的场景在 `transitions` 中的下标和已连续变亮的帧数
+    ///
+    /// 变亮过程中不判定切换和叠化
+    brightening: Option<(usize, usize)>,
+    /// 处于黑场中时为 Some，值为进入黑场前是否渐隐
+    black: Option<bool>,
+    /// 视频从开头到当前帧是否都是黑场
+    leading_black: bool,
+    /// 渐变中的帧：渐变开始前一帧的特征和渐变中各帧的时间、与前一帧的差异
+    gradual: Option<(FrameFeatures, Vec<(f64, f64)>)>,
+}
+
+impl SceneStream<'_> {
+    /// 按时间顺序加入下一帧，返回与前一帧的差异（第一帧为 0）
+    pub fn push(&mut self, time: f64, image: &DynamicImage) -> f64 {
+        // 每帧特征只计算一次，当前帧的特征在下一轮作为前一帧复用
+        let features = self.detector.features(image);
+        let (luma, black) = features.luma();
+        let Some(prev) = self.prev_features.take() else {
+            self.differences.push(0.0);
+            self.black = black.then_some(false);
+            self.leading_black = black;
+            self.prev_luma = Some(luma);
+            self.prev_features = Some(features);
+            return 0.0;
+        };
+        let diff = self.detector.calculate_features_difference(&prev, &features);
+        self.differences.push(diff);
+
+        // 检查是否超过阈值且满足最小时间间隔
+        let cut = self.is_cut(diff);
+        if self.detector.transitions {
+            self.track_transition(time, diff, cut, luma, black, prev, &features);
+        } else if cut {
+            self.add_change(time, TransitionType::Cut, diff, &prev, &features);
+        }
+        self.prev_features = Some(features);
+        diff
+    }
+
+    /// 满足最小时间间隔时记录场景变化，`before` / `after` 为判定切换时比较的两帧
+    fn add_change(&mut self, time: f64, transition: TransitionType, score: f64, before: &FrameFeatures, after: &FrameFeatures) {
+        let last_change = *self.scene_changes.last().unwrap();
+        if time - last_change >= self.detector.min_scene_duration {
+            self.scene_changes.push(time);
+            self.transitions.push(Some(transition));
+            self.cuts.push(SceneCut {
+                time,
+                score,
+                contributing_metrics: self.detector.contributing_metrics(before, after),
+            });
+        }
+    }
+
+    /// 跟踪黑场和多帧渐变，`prev` 为前一帧的特征
+    ///
+    /// - 黑场中不判定切换，离开黑场时记录一次场景变化：离开后连续变亮为渐显，
+    ///   否则进入黑场前连续变暗为渐隐，都不是时为硬切；视频开头的黑场不单独作为场景，渐显时标记第一个场景
+    /// - 连续多帧差异偏高但都未达到阈值，且渐变前后两帧的差异超过阈值时视为叠化，切点取渐变的中间帧
+    #[allow(clippy::too_many_arguments)]
+    fn track_transition(&mut self, time: f64, diff: f64, cut: bool, luma: f64, black: bool, prev: FrameFeatures, current: &FrameFeatures) {
+        let darkening = self.prev_luma.is_some_and(|prev_luma| luma < prev_luma - FADE_LUMA_STEP);
+        let brightening = self.prev_luma.is_some_and(|prev_luma| luma > prev_luma + FADE_LUMA_STEP);
+        if !brightening {
+            self.brightening = None;
+        }
+        if black {
+            if self.black.is_none() {
+                self.black = Some(self.darkening + 1 >= FADE_MIN_FRAMES && darkening);
+                self.gradual = None;
+            }
+        } else if let Some(faded_out) = self.black.take() {
+            let before = self.transitions.len();
+            if !self.leading_black {
+                self.add_change(time, if faded_out { TransitionType::FadeOut } else { TransitionType::Cut }, diff, &prev, current);
+            }
+            // 没有新增场景时渐显属于当前场景
+            let index = if self.transitions.len() > before { before } else { before - 1 };
+            self.brightening = brightening.then_some((index, 1));
+        } else if let Some((index, ref mut frames)) = self.brightening {
+            *frames += 1;
+            if *frames >= FADE_MIN_FRAMES {
+                self.transitions[index] = Some(TransitionType::FadeIn);
+            }
+        } else if cut {
+            self.gradual = None;
+            self.add_change(time, TransitionType::Cut, diff, &prev, current);
+        } else if diff >= self.detector.threshold * DISSOLVE_MIN_RATIO {
+            match self.gradual {
+                Some((_, ref mut frames)) => frames.push((time, diff)),
+                None => self.gradual = Some((prev, vec![(time, diff)])),
+            }
+        } else {
+            self.end_gradual(&prev);
+        }
+        self.leading_black &= black;
+        self.darkening = if darkening { self.darkening + 1 } else { 0 };
+        self.prev_luma = Some(luma);
+    }
+
+    /// 渐变结束，`last` 为渐变中的最后一帧
+    fn end_gradual(&mut self, last: &FrameFeatures) {
+        let Some((start, frames)) = self.gradual.take() else {
+            return;
+        };
+        if frames.len() >= DISSOLVE_MIN_FRAMES && self.detector.calculate_features_difference(&start, last) > self.detector.threshold {
+            let (time, diff) = frames[frames.len() / 2];
+            self.add_change(time, TransitionType::Dissolve, diff, &start, last);
+        }
+    }
+
+    /// 差异是否达到切换条件；自适应模式下同时更新最近差异的统计窗口
+    fn is_cut(&mut self, diff: f64) -> bool {
+        let Some(adaptive) = self.detector.adaptive else {
+            return diff > self.detector.threshold;
+        };
+        let warming_up = self.recent.len() < ADAPTIVE_MIN_SAMPLES;
+        let cut = if warming_up {
+            diff > self.detector.threshold
+        } else {
+            let n = self.recent.len() as f64;
+            let mean = self.recent.iter().sum::<f64>() / n;
+            let variance = self.recent.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n;
+            diff > ADAPTIVE_MIN_DIFFERENCE && diff > mean + adaptive.k * variance.sqrt()
+        };
+        // 统计窗口积累足够的帧之后，切换本身的差异不再计入，窗口只反映镜头内的运动强度
+        if warming_up || !cut {
+            self.recent.push_back(diff);
+            if self.recent.len() > adaptive.window.max(ADAPTIVE_MIN_SAMPLES) {
+                self.recent.pop_front();
+            }
+        }
+        cut
+    }
+
+    /// 已检测到的场景起始时间
+    pub fn scene_changes(&self) -> &[f64] {
+        &self.scene_changes
+    }
+
+    /// 已检测到的场景开始处的转场类型，与 [`scene_changes`](Self::scene_changes) 一一对应
+    pub fn transitions(&self) -> &[Option<TransitionType>] {
+        &self.transitions
+    }
+
+    /// 已检测到的切点，与 [`scene_changes`](Self::scene_changes) 第一个之后的元素一一对应
+    pub fn cuts(&self) -> &[SceneCut] {
+        &self.cuts
+    }
+
+    /// 场景起始时间和每帧与前一帧的差异
+    pub fn finish(self) -> (Vec<f64>, Vec<f64>) {
+        let (scene_changes, differences, _) = self.finish_with_transitions();
+        (scene_changes, differences)
+    }
+
+    /// 场景起始时间、每帧与前一帧的差异和每个场景开始处的转场类型
+    pub fn finish_with_transitions(self) -> SceneDetection {
+        self.finish_with_cuts().0
+    }
+
+    /// 同 [`finish_with_transitions`](Self::finish_with_transitions)，另外返回全部切点
+    pub fn finish_with_cuts(mut self) -> (SceneDetection, Vec<SceneCut>) {
+        // 渐变持续到最后一帧
+        if let Some(last) = self.prev_features.take() {
+            self.end_gradual(&last);
+        }
+        ((self.scene_changes, self.differences, self.transitions), self.cuts)
+    }
+}
+
+/// 场景的运动强度：场景 `[start, end)` 内相邻两帧差异的平均值
+///
+/// `times` 为采样帧的时间戳，`differences[i]` 为第 i 帧与前一帧的差异。跨越场景边界的差异
+/// （镜头切换本身）不计入，场景内不足两帧时返回 None
+pub fn motion_score(times: &[f64], differences: &[f64], start: f64, end: f64) -> Option<f64> {
+    let in_scene = |t: f64| t >= start && t < end;
+    let scene_differences: Vec<f64> = (1..times.len().min(differences.len()))
+        .filter(|&i| in_scene(times[i - 1]) && in_scene(times[i]))
+        .map(|i| differences[i])
+        .collect();
+    if scene_differences.is_empty() {
+        return None;
+    }
+    Some(scene_differences.iter().sum::<f64>() / scene_differences.len() as f64)
+}
+
+/// 每个场景开始处切点的置信度：切点所在帧与前一帧的差异，与 `scene_changes` 一一对应
+///
+/// 第一个场景和切点不在采样帧上（自定义检测器）时为 None
+pub fn cut_scores(times: &[f64], differences: &[f64], scene_changes: &[f64]) -> Vec<Option<f64>> {
+    scene_changes
+        .iter()
+        .enumerate()
+        .map(|(index, &change)| {
+            let i = times.partition_point(|&t| t < change - 1e-6);
+            let on_frame = index > 0 && i > 0 && times.get(i).is_some_and(|&t| (t - change).abs() <= 1e-6);
+            if on_frame { differences.get(i).copied() } else { None }
+        })
+        .collect()
+}
+
+/// 按固定阈值从帧差异序列得到场景起始时间，与固定阈值模式的逐帧检测相同（不识别渐变转场）
+pub fn cuts_at_threshold(times: &[f64], differences: &[f64], threshold: f64, min_scene_duration: f64) -> Vec<f64> {
+    let mut scene_changes = vec![0.0];
+    for (i, &time) in times.iter().enumerate().skip(1) {
+        let last_change = *scene_changes.last().unwrap();
+        if differences.get(i).is_some_and(|&d| d > threshold) && time - last_change >= min_scene_duration {
+            scene_changes.push(time);
+        }
+    }
+    scene_changes
+}
+
+/// 在帧差异序列上查找阈值，使场景数落在 `range` 内，返回阈值和对应的场景起始时间
+///
+/// 场景数只在阈值越过某个帧差异时变化，因此在全部不同的差异值上二分查找；
+/// 没有阈值能满足时返回场景数最接近范围的结果
+pub fn fit_scene_count(
+    times: &[f64],
+    differences: &[f64],
+    min_scene_duration: f64,
+    range: &RangeInclusive<usize>,
+) -> (f64, Vec<f64>) {
+    let mut candidates: Vec<f64> = differences.iter().skip(1).copied().filter(|d| d.is_finite()).collect();
+    candidates.push(0.0);
+    candidates.sort_by(f64::total_cmp);
+    candidates.dedup();
+
+    let distance = |count: usize| range.start().saturating_sub(count) + count.saturating_sub(*range.end());
+    let (mut low, mut high) = (0, candidates.len() - 1);
+    let mut best: Option<(usize, f64, Vec<f64>)> = None;
+    loop {
+        let mid = (low + high) / 2;
+        let threshold = candidates[mid];
+        let scene_changes = cuts_at_threshold(times, differences, threshold, min_scene_duration);
+        let count = scene_changes.len();
+        if best.as_ref().is_none_or(|(d, _, _)| distance(count) < *d) {
+            best = Some((distance(count), threshold, scene_changes));
+        }
+        // 阈值越高场景越少
+        if count > *range.end() && mid < high {
+            low = mid + 1;
+        } else if count < *range.start() && mid > low {
+            high = mid - 1;
+        } else {
+            break;
+        }
+    }
+    let (_, threshold, scene_changes) = best.unwrap();
+    (threshold, scene_changes)
+}
+
+/// 解析目标场景数范围：`20-60`（也可以写 `20..=60`），省略一端表示不限制，例如 `20-`、`-60`
+pub fn parse_scene_range(value: &str) -> Option<RangeInclusive<usize>> {
+    let value = value.trim();
+    let (min, max) = value.split_once("..=").or_else(|| value.split_once('-'))?;
+    let bound = |s: &str, default: usize| {
+        let s = s.trim();
+        if s.is_empty() { Some(default) } else { s.parse().ok() }
+    };
+    let range = bound(min, 0)?..=bound(max, usize::MAX)?;
+    (!range.is_empty() && *range.end() > 0).then_some(range)
+}
+
+/// 差值哈希：9x8 灰度图每行相邻像素比较，左侧更亮时该位为 1
+fn dhash(gray: &GrayImage) -> u64 {
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if gray.get_pixel(x, y)[0] > gray.get_pixel(x + 1, y)[0] {
+                hash |= 1;
             }
         }
+    }
+    hash
+}
+
+/// 感知哈希：32x32 灰度图做二维 DCT，取左上角 8x8 低频系数，
+/// 大于中位数（不含直流分量）的位为 1
+fn phash(gray: &GrayImage) -> u64 {
+    const N: usize = 32;
+    const LOW: usize = 8;
+    let pixels = gray.as_raw();
+    let cosines: Vec<f64> = (0..LOW)
+        .flat_map(|u| (0..N).map(move |x| (std::f64::consts::PI * (2 * x + 1) as f64 * u as f64 / (2 * N) as f64).cos()))
+        .collect();
+
+    // 先对每行做 DCT，只保留前 8 个系数；再对这 8 列做 DCT
+    let mut rows = [[0.0f64; LOW]; N];
+    for (y, row) in rows.iter_mut().enumerate() {
+        for (u, coefficient) in row.iter_mut().enumerate() {
+            *coefficient = (0..N).map(|x| pixels[y * N + x] as f64 * cosines[u * N + x]).sum();
+        }
+    }
+    let mut low = [0.0f64; LOW * LOW];
+    for v in 0..LOW {
+        for u in 0..LOW {
+            low[v * LOW + u] = (0..N).map(|y| rows[y][u] * cosines[v * N + y]).sum();
+        }
+    }
+
+    let mut sorted = low[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+    low.iter().fold(0u64, |hash, &c| (hash << 1) | (c > median) as u64)
+}
 
-        Ok(scene_changes)
+/// RGB转HSV辅助函数
+fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    // 灰色像素没有色调和饱和度，跳过浮点运算
+    if r == g && g == b {
+        return (0.0, 0.0, r as f64 / 255.0);
     }
+    let r = r as f64 / 255.0;
+    let g = g as f64 / 255.0;
+    let b = b as f64 / 255.0;
+    
+    let max = r.max(g.max(b));
+    let min = r.min(g.min(b));
+    let delta = max - min;
+    
+    // 色调
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * (((g - b) / delta) % 6.0)
+    } else if max == g {
+        60.0 * (((b - r) / delta) + 2.0)
+    } else {
+        60.0 * (((r - g) / delta) + 4.0)
+    };
+    
+    let h = if h < 0.0 { h + 360.0 } else { h };
+    
+    // 饱和度
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+    
+    // 亮度
+    let v = max;
+    
+    (h, s, v)
 }
 
 #[cfg(test)]
@@ -410,4 +1103,215 @@ mod tests {
         let diff = detector.calculate_frame_difference(&img1, &img2);
         assert!(diff > 0.0);
     }
+
+    #[test]
+    fn test_features_difference_matches_frame_difference() {
+        let detector = SceneDetector::new(0.3, 1.0);
+        
+        let img1 = DynamicImage::ImageRgb8(
+            ImageBuffer::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 80]))
+        );
+        let img2 = DynamicImage::ImageRgb8(
+            ImageBuffer::from_fn(64, 48, |x, y| image::Rgb([200, (x * 3) as u8, (y * 2) as u8]))
+        );
+        
+        let features1 = FrameFeatures::from_image(&img1);
+        let features2 = FrameFeatures::from_image(&img2);
+        assert_eq!(
+            detector.calculate_features_difference(&features1, &features2),
+            detector.calculate_frame_difference(&img1, &img2)
+        );
+    }
+
+    #[test]
+    fn test_scene_stream_matches_batch() {
+        let detector = SceneDetector::new(0.3, 1.0);
+        let frames: Vec<(f64, DynamicImage)> = (0..8)
+            .map(|i| {
+                let level = if i < 4 { 40 + i as u8 } else { 220 - i as u8 };
+                (i as f64 * 0.5, DynamicImage::ImageLuma8(ImageBuffer::from_fn(32, 32, |_, _| image::Luma([level]))))
+            })
+            .collect();
+
+        let mut stream = detector.stream();
+        for (time, image) in &frames {
+            stream.push(*time, image);
+        }
+        assert_eq!(stream.scene_changes(), &[0.0, 2.0]);
+        assert_eq!(stream.finish(), detector.detect_scenes_with_differences(&frames, 2.0).unwrap());
+        assert_eq!(detector.stream().finish(), (vec![0.0], Vec::new()));
+    }
+
+    #[test]
+    fn test_detect_cuts() {
+        let detector = SceneDetector::new(0.3, 1.0);
+        let frames: Vec<(f64, DynamicImage)> = (0..8)
+            .map(|i| {
+                let level = if i < 4 { 40 + i as u8 } else { 220 - i as u8 };
+                (i as f64 * 0.5, DynamicImage::ImageLuma8(ImageBuffer::from_fn(32, 32, |_, _| image::Luma([level]))))
+            })
+            .collect();
+
+        let cuts = detector.detect_cuts(&frames).unwrap();
+        let (scene_changes, differences) = detector.detect_scenes_with_differences(&frames, 2.0).unwrap();
+        assert_eq!(cuts.len(), 1);
+        assert_eq!(cuts[0].time, 2.0);
+        assert_eq!(cuts[0].score, differences[4]);
+        assert!(cuts[0].contributing_metrics.contains(&"traditional".to_string()));
+        assert!(!cuts[0].contributing_metrics.contains(&"hsv".to_string()));
+
+        // 切点置信度与场景一一对应，第一个场景和不在采样帧上的切点没有置信度
+        let times: Vec<f64> = frames.iter().map(|(t, _)| *t).collect();
+        assert_eq!(cut_scores(&times, &differences, &scene_changes), [None, Some(cuts[0].score)]);
+        assert_eq!(cut_scores(&times, &differences, &[0.0, 2.2]), [None, None]);
+    }
+
+    #[test]
+    fn test_fit_scene_count() {
+        // 每 2 秒一个候选切点，差异从 0.9 递减到 0.1，其余帧差异 0.02
+        let times: Vec<f64> = (0..20).map(|t| t as f64).collect();
+        let mut differences = vec![0.02; 20];
+        differences[0] = 0.0;
+        for (k, i) in (2..20).step_by(2).enumerate() {
+            differences[i] = 0.9 - k as f64 * 0.1;
+        }
+        assert_eq!(cuts_at_threshold(&times, &differences, 0.35, 0.0).len(), 7);
+
+        let (threshold, scene_changes) = fit_scene_count(&times, &differences, 0.0, &(3..=4));
+        assert!((3..=4).contains(&scene_changes.len()), "{:?}", scene_changes);
+        assert_eq!(scene_changes, cuts_at_threshold(&times, &differences, threshold, 0.0));
+        // 范围内的场景数无法达到（10 个场景之后只能每帧一个场景）时取最接近的结果
+        let (_, scene_changes) = fit_scene_count(&times, &differences, 0.0, &(12..=15));
+        assert_eq!(scene_changes.len(), 10);
+        let (_, scene_changes) = fit_scene_count(&times, &differences, 0.0, &(30..=40));
+        assert_eq!(scene_changes.len(), 20);
+
+        assert_eq!(parse_scene_range("20-60"), Some(20..=60));
+        assert_eq!(parse_scene_range("20..=60"), Some(20..=60));
+        assert_eq!(parse_scene_range(" -60"), Some(0..=60));
+        assert_eq!(parse_scene_range("20-"), Some(20..=usize::MAX));
+        assert_eq!(parse_scene_range("60-20"), None);
+        assert_eq!(parse_scene_range(""), None);
+    }
+
+    #[test]
+    fn test_adaptive_threshold() {
+        // 暗场影片：镜头内差异约 0.02，切换的差异 0.15 低于固定阈值
+        let differences = [0.02, 0.03, 0.02, 0.025, 0.02, 0.03, 0.15, 0.02, 0.03];
+        let fixed = SceneDetector::new(0.35, 0.0);
+        let mut stream = fixed.stream();
+        assert!(differences.iter().all(|&d| !stream.is_cut(d)));
+
+        let adaptive = SceneDetector::new(0.35, 0.0).with_adaptive_threshold(Some(AdaptiveThreshold::default()));
+        let mut stream = adaptive.stream();
+        let cuts: Vec<bool> = differences.iter().map(|&d| stream.is_cut(d)).collect();
+        assert_eq!(cuts, [false, false, false, false, false, false, true, false, false]);
+        // 切换的差异不计入统计窗口
+        assert_eq!(stream.recent.len(), 8);
+
+        // 运动剧烈的 vlog：镜头内差异在 0.2-0.4 之间波动，超过固定阈值的 0.4 不应判为切换
+        let mut stream = adaptive.stream();
+        let busy = [0.2, 0.34, 0.25, 0.33, 0.22, 0.4, 0.3, 0.9];
+        let cuts: Vec<bool> = busy.iter().map(|&d| stream.is_cut(d)).collect();
+        assert_eq!(cuts, [false, false, false, false, false, false, false, true]);
+        assert_eq!("Adaptive".parse::<ThresholdMode>(), Ok(ThresholdMode::Adaptive));
+    }
+
+    #[test]
+    fn test_hash_detection() {
+        // 8x8 像素块组成的伪随机画面，`seed` 不同时为不同镜头，`noise` 为逐像素噪声
+        let scene = |seed: u32, noise: u8| {
+            DynamicImage::ImageLuma8(ImageBuffer::from_fn(128, 96, move |x, y| {
+                let block = (x / 8 + y / 8 * 16 + seed * 997).wrapping_mul(2_654_435_761);
+                image::Luma([(block >> 24) as u8 / 2 + 40 + ((x + y) % 2) as u8 * noise])
+            }))
+        };
+
+        for algorithm in [DetectionAlgorithm::PHash, DetectionAlgorithm::DHash] {
+            let detector = SceneDetector::new(0.3, 1.0).with_algorithm(algorithm);
+            // 轻微噪声几乎不影响哈希，完全不同的画面距离很大
+            assert!(detector.calculate_frame_difference(&scene(1, 0), &scene(1, 3)) < 0.1);
+            assert!(detector.calculate_frame_difference(&scene(1, 0), &scene(2, 0)) > 0.3);
+
+            let frames: Vec<(f64, DynamicImage)> = (0..6)
+                .map(|i| (i as f64, scene(if i < 3 { 1 } else { 2 }, i as u8)))
+                .collect();
+            let (scene_changes, _) = detector.detect_scenes_with_differences(&frames, 1.0).unwrap();
+            assert_eq!(scene_changes, vec![0.0, 3.0]);
+        }
+        assert_eq!("pHash".parse::<DetectionAlgorithm>(), Ok(DetectionAlgorithm::PHash));
+        assert!("sobel".parse::<DetectionAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_transitions() {
+        // 三种画面，亮度乘以 gain，t 为 0 到 1 时从 a 叠化到 b
+        let pattern = |kind: u32, x: u32, y: u32| -> [f64; 3] {
+            match kind {
+                0 => [(x * 4) as f64, (y * 4) as f64, 160.0],
+                1 => [220.0, 255.0 - (x * 3) as f64, (y * 2) as f64],
+                _ => [((x + y) * 2) as f64, 60.0, 255.0 - (y * 4) as f64],
+            }
+        };
+        let frame = |a: u32, b: u32, t: f64, gain: f64| {
+            DynamicImage::ImageRgb8(ImageBuffer::from_fn(64, 64, |x, y| {
+                let (pa, pb) = (pattern(a, x, y), pattern(b, x, y));
+                image::Rgb(std::array::from_fn(|c| ((pa[c] * (1.0 - t) + pb[c] * t) * gain) as u8))
+            }))
+        };
+        let mut frames = Vec::new();
+        // 开头从黑场渐显
+        for gain in [0.0, 0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 1.0] {
+            frames.push(frame(0, 0, 0.0, gain));
+        }
+        // 渐隐到黑场，再切入第二个场景
+        for gain in [0.7, 0.4, 0.1, 0.0, 0.0] {
+            frames.push(frame(0, 0, 0.0, gain));
+        }
+        for _ in 0..4 {
+            frames.push(frame(1, 1, 0.0, 1.0));
+        }
+        // 硬切到第三个场景
+        for _ in 0..4 {
+            frames.push(frame(2, 2, 0.0, 1.0));
+        }
+        // 用 10 帧叠化回第二个场景
+        for step in 1..=10 {
+            frames.push(frame(2, 1, step as f64 / 11.0, 1.0));
+        }
+        for _ in 0..4 {
+            frames.push(frame(1, 1, 0.0, 1.0));
+        }
+        let frames: Vec<(f64, DynamicImage)> = frames.into_iter().enumerate().map(|(i, image)| (i as f64, image)).collect();
+
+        let detector = SceneDetector::new(0.3, 1.0).with_transitions(true);
+        let (scene_changes, _, transitions) = detector.detect_scenes_with_transitions(&frames, 1.0).unwrap();
+        assert_eq!(scene_changes, vec![0.0, 13.0, 17.0, 24.0]);
+        assert_eq!(
+            transitions,
+            vec![
+                Some(TransitionType::FadeIn),
+                Some(TransitionType::FadeOut),
+                Some(TransitionType::Cut),
+                Some(TransitionType::Dissolve),
+            ]
+        );
+
+        // 不检测渐变转场时，叠化中的帧差异都未达到阈值，黑场前后按硬切处理
+        let (scene_changes, _, transitions) = SceneDetector::new(0.3, 1.0).detect_scenes_with_transitions(&frames, 1.0).unwrap();
+        assert!(!scene_changes.contains(&24.0));
+        assert!(transitions[1..].iter().all(|t| *t == Some(TransitionType::Cut)));
+        assert_eq!(transitions[0], None);
+    }
+
+    #[test]
+    fn test_motion_score() {
+        let times = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let differences = [0.0, 0.2, 0.9, 0.4, 0.6];
+        // 第二个场景从 2.0 开始，2.0 处的差异是镜头切换，不计入
+        assert!((motion_score(&times, &differences, 0.0, 2.0).unwrap() - 0.2).abs() < 1e-9);
+        assert!((motion_score(&times, &differences, 2.0, 5.0).unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(motion_score(&times, &differences, 4.0, 5.0), None);
+        assert_eq!(motion_score(&times, &[], 0.0, 5.0), None);
+    }
 }
\ No newline at end of file
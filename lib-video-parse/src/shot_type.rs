@@ -0,0 +1,155 @@
+//! 景别估计：根据关键帧的边缘密度分布（以及人脸检测结果）把场景标记为特写、中景或全景，用于拉片标注
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::faces::FaceBox;
+use crate::metadata::SceneMetadata;
+
+/// 估计前把关键帧缩小到的最大边长（像素）
+const SAMPLE_SIZE: u32 = 160;
+/// 相邻像素亮度差之和超过该值视为边缘
+const EDGE_THRESHOLD: i32 = 48;
+/// 最大人脸高度达到画面高度的该比例时为特写
+const CLOSE_UP_FACE_HEIGHT: f32 = 0.35;
+/// 最大人脸高度达到画面高度的该比例时为中景
+const MEDIUM_FACE_HEIGHT: f32 = 0.12;
+
+/// 景别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShotType {
+    /// 特写 / 近景
+    CloseUp,
+    /// 中景
+    Medium,
+    /// 全景 / 远景
+    Wide,
+}
+
+impl ShotType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShotType::CloseUp => "close_up",
+            ShotType::Medium => "medium",
+            ShotType::Wide => "wide",
+        }
+    }
+}
+
+/// 关键帧的边缘密度分布
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EdgeDensity {
+    /// 整帧的边缘像素占比
+    overall: f64,
+    /// 中央区域（宽高各占一半）的边缘像素占比
+    center: f64,
+    /// 中央区域以外的边缘像素占比
+    border: f64,
+}
+
+fn edge_density(image: &DynamicImage) -> EdgeDensity {
+    let gray = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE).to_luma8();
+    let (width, height) = gray.dimensions();
+    let (mut center_edges, mut center_total, mut border_edges, mut border_total) = (0u64, 0u64, 0u64, 0u64);
+    for y in 0..height.saturating_sub(1) {
+        for x in 0..width.saturating_sub(1) {
+            let value = gray.get_pixel(x, y)[0] as i32;
+            let gradient = (gray.get_pixel(x + 1, y)[0] as i32 - value).abs()
+                + (gray.get_pixel(x, y + 1)[0] as i32 - value).abs();
+            let edge = (gradient > EDGE_THRESHOLD) as u64;
+            let in_center = x >= width / 4 && x < width * 3 / 4 && y >= height / 4 && y < height * 3 / 4;
+            if in_center {
+                center_edges += edge;
+                center_total += 1;
+            } else {
+                border_edges += edge;
+                border_total += 1;
+            }
+        }
+    }
+    let ratio = |edges: u64, total: u64| if total == 0 { 0.0 } else { edges as f64 / total as f64 };
+    EdgeDensity {
+        overall: ratio(center_edges + border_edges, center_total + border_total),
+        center: ratio(center_edges, center_total),
+        border: ratio(border_edges, border_total),
+    }
+}
+
+/// 估计一帧的景别
+///
+/// 有人脸时按最大人脸的高度判断；否则按边缘密度：细节少、或细节集中在画面中央而背景虚化时为特写，
+/// 细节多且均匀分布在整个画面时为全景，其余为中景
+pub fn estimate(image: &DynamicImage, faces: &[FaceBox]) -> ShotType {
+    if let Some(face_height) = faces.iter().map(|face| face.height).reduce(f32::max) {
+        return if face_height >= CLOSE_UP_FACE_HEIGHT {
+            ShotType::CloseUp
+        } else if face_height >= MEDIUM_FACE_HEIGHT {
+            ShotType::Medium
+        } else {
+            ShotType::Wide
+        };
+    }
+    let density = edge_density(image);
+    if density.overall < 0.02 || (density.border < 0.03 && density.center >= density.border * 3.0) {
+        ShotType::CloseUp
+    } else if density.overall >= 0.10 && density.center <= density.border * 1.5 {
+        ShotType::Wide
+    } else {
+        ShotType::Medium
+    }
+}
+
+/// 估计每个场景关键帧的景别，写入 `shot_type`
+///
+/// 返回估计成功的场景数，读取失败的关键帧跳过；去重合并的场景复用同一关键帧的结果
+pub(crate) fn classify_scenes(output_dir: &Path, scenes: &mut [SceneMetadata]) -> usize {
+    let mut computed: HashMap<String, ShotType> = HashMap::new();
+    let mut classified = 0;
+    for scene in scenes.iter_mut() {
+        if let Some(shot_type) = computed.get(&scene.keyframe_file) {
+            scene.shot_type = Some(*shot_type);
+            classified += 1;
+            continue;
+        }
+        match image::open(output_dir.join(&scene.keyframe_file)) {
+            Ok(image) => {
+                let shot_type = estimate(&image, &scene.faces);
+                tracing::debug!(scene_id = scene.scene_id, shot_type = shot_type.as_str(), "估计场景景别");
+                computed.insert(scene.keyframe_file.clone(), shot_type);
+                scene.shot_type = Some(shot_type);
+                classified += 1;
+            }
+            Err(e) => tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [景别估计] 读取关键帧失败，跳过"),
+        }
+    }
+    classified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Luma, GrayImage};
+
+    #[test]
+    fn test_estimate_shot_type() {
+        // 整个画面都是细密纹理（人群、街景）
+        let wide = GrayImage::from_fn(160, 90, |x, y| Luma([if (x / 3 + y / 3) % 2 == 0 { 30 } else { 220 }]));
+        assert_eq!(estimate(&DynamicImage::ImageLuma8(wide), &[]), ShotType::Wide);
+
+        // 只有画面中央有细节，背景平滑
+        let close_up = GrayImage::from_fn(160, 90, |x, y| {
+            let in_center = (60..100).contains(&x) && (30..60).contains(&y);
+            Luma([if in_center && (x / 3 + y / 3) % 2 == 0 { 220 } else { 90 }])
+        });
+        let close_up = DynamicImage::ImageLuma8(close_up);
+        assert_eq!(estimate(&close_up, &[]), ShotType::CloseUp);
+
+        // 有人脸时按人脸高度判断
+        let face = |height| FaceBox { x: 0.4, y: 0.3, width: height * 0.6, height, confidence: 0.9 };
+        assert_eq!(estimate(&close_up, &[face(0.05)]), ShotType::Wide);
+        assert_eq!(estimate(&close_up, &[face(0.05), face(0.2)]), ShotType::Medium);
+        assert_eq!(serde_json::to_string(&ShotType::CloseUp).unwrap(), "\"close_up\"");
+    }
+}
@@ -0,0 +1,342 @@
+//! 帧差异计算的 SIMD 内核
+//!
+//! 像素差异、直方图统计、Sobel 边缘强度和梯度幅值是场景检测中每帧开销最大的循环。
+//! x86_64 上运行时检测 CPU 特性，优先使用 AVX2，其次 SSE2；其他平台回退到标量实现。
+
+/// 计算两段字节数据的绝对差之和（SAD）
+///
+/// 两段数据长度不同时只比较公共前缀部分
+pub fn sum_abs_diff(a: &[u8], b: &[u8]) -> u64 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: 已在运行时确认 CPU 支持 AVX2
+            return unsafe { x86::sum_abs_diff_avx2(a, b) };
+        }
+        // SAFETY: SSE2 是 x86_64 的基线指令集
+        return unsafe { x86::sum_abs_diff_sse2(a, b) };
+    }
+
+    #[allow(unreachable_code)]
+    sum_abs_diff_scalar(a, b)
+}
+
+/// 标量版本的 SAD（也用于处理 SIMD 循环的尾部）
+pub fn sum_abs_diff_scalar(a: &[u8], b: &[u8]) -> u64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| x.abs_diff(y) as u64)
+        .sum()
+}
+
+/// 统计 256 级灰度直方图
+///
+/// 使用 4 组交错的子直方图打断相邻像素写同一个桶时的存储-加载依赖，
+/// 最后再合并为一个直方图
+pub fn histogram(data: &[u8]) -> [u32; 256] {
+    let mut sub = [[0u32; 256]; 4];
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        sub[0][chunk[0] as usize] += 1;
+        sub[1][chunk[1] as usize] += 1;
+        sub[2][chunk[2] as usize] += 1;
+        sub[3][chunk[3] as usize] += 1;
+    }
+    for &v in chunks.remainder() {
+        sub[0][v as usize] += 1;
+    }
+
+    let mut hist = [0u32; 256];
+    for (i, bucket) in hist.iter_mut().enumerate() {
+        *bucket = sub[0][i] + sub[1][i] + sub[2][i] + sub[3][i];
+    }
+    hist
+}
+
+/// 计算灰度图的 Sobel 边缘强度
+///
+/// 返回 (width-2)*(height-2) 个内部像素的梯度幅值（按行排列），
+/// 图像宽或高小于 3 时返回空向量
+pub fn sobel_magnitudes(data: &[u8], width: usize, height: usize) -> Vec<u16> {
+    if width < 3 || height < 3 || data.len() < width * height {
+        return Vec::new();
+    }
+
+    let inner_width = width - 2;
+    let mut out = vec![0u16; inner_width * (height - 2)];
+
+    for y in 1..(height - 1) {
+        let r0 = &data[(y - 1) * width..y * width];
+        let r1 = &data[y * width..(y + 1) * width];
+        let r2 = &data[(y + 1) * width..(y + 2) * width];
+        let row_out = &mut out[(y - 1) * inner_width..y * inner_width];
+
+        #[allow(unused_mut)]
+        let mut start = 1;
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                // SAFETY: 已在运行时确认 CPU 支持 AVX2
+                start = unsafe { x86::sobel_row_avx2(r0, r1, r2, row_out) };
+            }
+        }
+
+        sobel_row_scalar(r0, r1, r2, row_out, start);
+    }
+
+    out
+}
+
+/// 标量版本的 Sobel 行计算，从第 `start` 列开始处理到行尾（不含最后一列）
+fn sobel_row_scalar(r0: &[u8], r1: &[u8], r2: &[u8], out: &mut [u16], start: usize) {
+    let width = r1.len();
+    for x in start..(width - 1) {
+        let gx = (r0[x + 1] as i32 - r0[x - 1] as i32)
+            + 2 * (r1[x + 1] as i32 - r1[x - 1] as i32)
+            + (r2[x + 1] as i32 - r2[x - 1] as i32);
+        let gy = (r2[x - 1] as i32 + 2 * r2[x] as i32 + r2[x + 1] as i32)
+            - (r0[x - 1] as i32 + 2 * r0[x] as i32 + r0[x + 1] as i32);
+        out[x - 1] = ((gx * gx + gy * gy) as f64).sqrt() as u16;
+    }
+}
+
+/// 计算灰度图的中心差分梯度幅值，布局与 [`sobel_magnitudes`] 相同
+pub fn gradient_magnitudes(data: &[u8], width: usize, height: usize) -> Vec<u16> {
+    if width < 3 || height < 3 || data.len() < width * height {
+        return Vec::new();
+    }
+
+    let inner_width = width - 2;
+    let mut out = vec![0u16; inner_width * (height - 2)];
+
+    for y in 1..(height - 1) {
+        let r0 = &data[(y - 1) * width..y * width];
+        let r1 = &data[y * width..(y + 1) * width];
+        let r2 = &data[(y + 1) * width..(y + 2) * width];
+        let row_out = &mut out[(y - 1) * inner_width..y * inner_width];
+
+        #[allow(unused_mut)]
+        let mut start = 1;
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                // SAFETY: 已在运行时确认 CPU 支持 AVX2
+                start = unsafe { x86::gradient_row_avx2(r0, r1, r2, row_out) };
+            }
+        }
+
+        gradient_row_scalar(r0, r1, r2, row_out, start);
+    }
+
+    out
+}
+
+/// 标量版本的梯度行计算，从第 `start` 列开始处理到行尾（不含最后一列）
+fn gradient_row_scalar(r0: &[u8], r1: &[u8], r2: &[u8], out: &mut [u16], start: usize) {
+    let width = r1.len();
+    for x in start..(width - 1) {
+        let gx = r1[x + 1] as i32 - r1[x - 1] as i32;
+        let gy = r2[x] as i32 - r0[x] as i32;
+        out[x - 1] = ((gx * gx + gy * gy) as f64).sqrt() as u16;
+    }
+}
+
+/// 计算两段 u16 数据的绝对差之和，两段数据长度不同时只比较公共前缀部分
+///
+/// 按块在 u32 中累加（每块不会溢出），便于自动向量化
+pub fn sum_abs_diff_u16(a: &[u16], b: &[u16]) -> u64 {
+    const BLOCK: usize = 4096;
+    a.chunks(BLOCK)
+        .zip(b.chunks(BLOCK))
+        .map(|(x, y)| x.iter().zip(y).map(|(&p, &q)| p.abs_diff(q) as u32).sum::<u32>() as u64)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::sum_abs_diff_scalar;
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sum_abs_diff_avx2(a: &[u8], b: &[u8]) -> u64 {
+        let len = a.len();
+        let mut acc = _mm256_setzero_si256();
+        let mut i = 0;
+        while i + 32 <= len {
+            let va = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+            let vb = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+            acc = _mm256_add_epi64(acc, _mm256_sad_epu8(va, vb));
+            i += 32;
+        }
+
+        let mut lanes = [0u64; 4];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, acc);
+        lanes.iter().sum::<u64>() + sum_abs_diff_scalar(&a[i..], &b[i..])
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn sum_abs_diff_sse2(a: &[u8], b: &[u8]) -> u64 {
+        let len = a.len();
+        let mut acc = _mm_setzero_si128();
+        let mut i = 0;
+        while i + 16 <= len {
+            let va = _mm_loadu_si128(a.as_ptr().add(i) as *const __m128i);
+            let vb = _mm_loadu_si128(b.as_ptr().add(i) as *const __m128i);
+            acc = _mm_add_epi64(acc, _mm_sad_epu8(va, vb));
+            i += 16;
+        }
+
+        let mut lanes = [0u64; 2];
+        _mm_storeu_si128(lanes.as_mut_ptr() as *mut __m128i, acc);
+        lanes[0] + lanes[1] + sum_abs_diff_scalar(&a[i..], &b[i..])
+    }
+
+    /// 读取 8 个字节并扩展为 8 个 i32
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn load8(row: &[u8], offset: usize) -> __m256i {
+        _mm256_cvtepu8_epi32(_mm_loadl_epi64(row.as_ptr().add(offset) as *const __m128i))
+    }
+
+    /// 每次处理 8 个像素，返回下一个尚未处理的列号，剩余部分交给标量实现
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn gradient_row_avx2(r0: &[u8], r1: &[u8], r2: &[u8], out: &mut [u16]) -> usize {
+        let width = r1.len();
+        let mut x = 1;
+
+        // 需要读取 [x-1, x+8] 共 10 个字节，保证 x + 9 <= width
+        while x + 9 <= width {
+            let gx = _mm256_sub_epi32(load8(r1, x + 1), load8(r1, x - 1));
+            let gy = _mm256_sub_epi32(load8(r2, x), load8(r0, x));
+
+            // gx² + gy² 最大约 13 万，可以在 f32 中精确表示，截断后与 f64 结果一致
+            let mag2 = _mm256_add_epi32(_mm256_mullo_epi32(gx, gx), _mm256_mullo_epi32(gy, gy));
+            let mag = _mm256_cvttps_epi32(_mm256_sqrt_ps(_mm256_cvtepi32_ps(mag2)));
+
+            // 幅值不超过 361，饱和打包为 u16 不会截断；packus 按 128 位通道交错，先调整顺序
+            let packed = _mm256_permute4x64_epi64(_mm256_packus_epi32(mag, mag), 0b1000);
+            _mm_storeu_si128(out.as_mut_ptr().add(x - 1) as *mut __m128i, _mm256_castsi256_si128(packed));
+
+            x += 8;
+        }
+
+        x
+    }
+
+    /// 每次处理 8 个像素，返回下一个尚未处理的列号，剩余部分交给标量实现
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sobel_row_avx2(r0: &[u8], r1: &[u8], r2: &[u8], out: &mut [u16]) -> usize {
+        let width = r1.len();
+        let two = _mm256_set1_epi32(2);
+        let mut x = 1;
+
+        // 需要读取 [x-1, x+8] 共 10 个字节，保证 x + 9 <= width
+        while x + 9 <= width {
+            let (r0m, r0c, r0p) = (load8(r0, x - 1), load8(r0, x), load8(r0, x + 1));
+            let (r1m, r1p) = (load8(r1, x - 1), load8(r1, x + 1));
+            let (r2m, r2c, r2p) = (load8(r2, x - 1), load8(r2, x), load8(r2, x + 1));
+
+            let gx = _mm256_add_epi32(
+                _mm256_add_epi32(_mm256_sub_epi32(r0p, r0m), _mm256_sub_epi32(r2p, r2m)),
+                _mm256_mullo_epi32(two, _mm256_sub_epi32(r1p, r1m)),
+            );
+            let bottom = _mm256_add_epi32(_mm256_add_epi32(r2m, r2p), _mm256_mullo_epi32(two, r2c));
+            let top = _mm256_add_epi32(_mm256_add_epi32(r0m, r0p), _mm256_mullo_epi32(two, r0c));
+            let gy = _mm256_sub_epi32(bottom, top);
+
+            // gx² + gy² 最大约 2.1M，可以在 f32 中精确表示，截断后与 f64 结果一致
+            let mag2 = _mm256_add_epi32(_mm256_mullo_epi32(gx, gx), _mm256_mullo_epi32(gy, gy));
+            let mag = _mm256_cvttps_epi32(_mm256_sqrt_ps(_mm256_cvtepi32_ps(mag2)));
+
+            let mut lanes = [0i32; 8];
+            _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, mag);
+            for (k, &m) in lanes.iter().enumerate() {
+                out[x - 1 + k] = m as u16;
+            }
+
+            x += 8;
+        }
+
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 简单的线性同余生成器，避免为测试引入随机数依赖
+    fn pseudo_random_bytes(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sum_abs_diff_matches_scalar() {
+        for len in [0, 1, 15, 16, 31, 32, 33, 1000] {
+            let a = pseudo_random_bytes(len, 1);
+            let b = pseudo_random_bytes(len, 2);
+            assert_eq!(sum_abs_diff(&a, &b), sum_abs_diff_scalar(&a, &b));
+        }
+    }
+
+    #[test]
+    fn test_sobel_matches_scalar() {
+        let (width, height) = (37, 5);
+        let data = pseudo_random_bytes(width * height, 3);
+        let simd = sobel_magnitudes(&data, width, height);
+
+        let mut scalar = vec![0u16; (width - 2) * (height - 2)];
+        for y in 1..(height - 1) {
+            sobel_row_scalar(
+                &data[(y - 1) * width..y * width],
+                &data[y * width..(y + 1) * width],
+                &data[(y + 1) * width..(y + 2) * width],
+                &mut scalar[(y - 1) * (width - 2)..y * (width - 2)],
+                1,
+            );
+        }
+        assert_eq!(simd, scalar);
+    }
+
+    #[test]
+    fn test_gradient_matches_reference() {
+        let (width, height) = (37, 6);
+        let data = pseudo_random_bytes(width * height, 5);
+        let pixel = |x: usize, y: usize| data[y * width + x] as i32;
+        let mut expected = Vec::new();
+        for y in 1..(height - 1) {
+            for x in 1..(width - 1) {
+                let (gx, gy) = (pixel(x + 1, y) - pixel(x - 1, y), pixel(x, y + 1) - pixel(x, y - 1));
+                expected.push(((gx * gx + gy * gy) as f64).sqrt() as u16);
+            }
+        }
+        let gradients = gradient_magnitudes(&data, width, height);
+        assert_eq!(gradients, expected);
+        assert!(gradient_magnitudes(&data, 2, 3).is_empty());
+
+        let shifted: Vec<u16> = gradients.iter().map(|&g| g.saturating_sub(7)).collect();
+        let expected_sad: u64 = gradients.iter().zip(&shifted).map(|(&a, &b)| a.abs_diff(b) as u64).sum();
+        assert_eq!(sum_abs_diff_u16(&gradients, &shifted), expected_sad);
+    }
+
+    #[test]
+    fn test_histogram_counts_all_pixels() {
+        let data = pseudo_random_bytes(1003, 4);
+        let hist = histogram(&data);
+        assert_eq!(hist.iter().sum::<u32>(), 1003);
+        assert!(hist[data[0] as usize] > 0);
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+use image::{imageops::FilterType, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use crate::metadata::SceneMetadata;
+
+/// 拼图图片文件名
+pub const SPRITE_IMAGE_FILE: &str = "sprite.jpg";
+/// 拼图坐标 WebVTT 文件名
+pub const SPRITE_VTT_FILE: &str = "sprite.vtt";
+
+/// JPEG 单边最大像素数
+const MAX_JPEG_DIMENSION: u32 = 65_535;
+/// 拼图 JPEG 质量
+const JPEG_QUALITY: u8 = 85;
+
+/// 缩略图拼图（contact sheet）配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteConfig {
+    /// 是否生成拼图
+    pub enabled: bool,
+    /// 每行的缩略图数
+    pub columns: u32,
+    /// 缩略图宽度（像素）
+    pub tile_width: u32,
+    /// 缩略图高度（像素）
+    pub tile_height: u32,
+    /// 是否在缩略图左下角绘制场景开始时间
+    pub timestamps: bool,
+}
+
+impl Default for SpriteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            columns: 5,
+            tile_width: 160,
+            tile_height: 90,
+            timestamps: true,
+        }
+    }
+}
+
+/// 解析缩略图尺寸（`宽x高`，例如 `160x90`）
+pub fn parse_tile_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.trim().to_lowercase().split_once('x')
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// 拼图输出，写入 `VideoMetadata.sprite`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteSheet {
+    /// 拼图图片文件名
+    pub image: String,
+    /// WebVTT 文件名，每个场景一条 cue，内容为 `sprite.jpg#xywh=x,y,w,h`
+    pub vtt: String,
+    pub columns: u32,
+    pub rows: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// 拼图包含的场景数（超出 JPEG 尺寸上限的场景不包含）
+    pub tiles: usize,
+}
+
+/// 把各场景的关键帧按顺序拼成一张图片，并写出时间到拼图坐标的 WebVTT 文件，
+/// 供播放器拖动进度条时显示预览
+///
+/// 没有场景时不生成，返回 None
+pub fn write_sprite(config: &SpriteConfig, output_dir: &Path, scenes: &[SceneMetadata]) -> Result<Option<SpriteSheet>> {
+    if scenes.is_empty() {
+        return Ok(None);
+    }
+    let (tile_width, tile_height) = (config.tile_width.max(1), config.tile_height.max(1));
+    let columns = config.columns.clamp(1, MAX_JPEG_DIMENSION / tile_width);
+    let max_tiles = (columns * (MAX_JPEG_DIMENSION / tile_height)) as usize;
+    if scenes.len() > max_tiles {
+        tracing::warn!(scenes = scenes.len(), max_tiles, "⚠️  [拼图] 场景数超过拼图尺寸上限，只包含前面的场景");
+    }
+    let scenes = &scenes[..scenes.len().min(max_tiles)];
+    let rows = (scenes.len() as u32).div_ceil(columns);
+
+    let mut sheet = RgbImage::new(columns * tile_width, rows * tile_height);
+    let mut vtt = String::from("WEBVTT\n");
+    for (index, scene) in scenes.iter().enumerate() {
+        let (x, y) = ((index as u32 % columns) * tile_width, (index as u32 / columns) * tile_height);
+        let keyframe_path = output_dir.join(&scene.keyframe_file);
+        match image::open(&keyframe_path) {
+            Ok(keyframe) => {
+                let mut tile = keyframe.resize_to_fill(tile_width, tile_height, FilterType::Triangle).to_rgb8();
+                if config.timestamps {
+                    draw_timestamp(&mut tile, scene.start_time);
+                }
+                image::imageops::replace(&mut sheet, &tile, x as i64, y as i64);
+            }
+            Err(e) => {
+                // 缺少的关键帧留空，不影响其他场景
+                tracing::warn!(scene_id = scene.scene_id, error = %e, "⚠️  [拼图] 读取关键帧失败");
+            }
+        }
+        let _ = write!(
+            vtt,
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            vtt_time(scene.start_time), vtt_time(scene.end_time), SPRITE_IMAGE_FILE, x, y, tile_width, tile_height
+        );
+    }
+
+    let file = std::fs::File::create(output_dir.join(SPRITE_IMAGE_FILE)).context("创建拼图文件失败")?;
+    let mut writer = std::io::BufWriter::new(file);
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY)
+        .encode(&sheet, sheet.width(), sheet.height(), image::ColorType::Rgb8)
+        .context("保存拼图失败")?;
+    std::fs::write(output_dir.join(SPRITE_VTT_FILE), vtt).context("保存拼图 WebVTT 文件失败")?;
+
+    Ok(Some(SpriteSheet {
+        image: SPRITE_IMAGE_FILE.to_string(),
+        vtt: SPRITE_VTT_FILE.to_string(),
+        columns,
+        rows,
+        tile_width,
+        tile_height,
+        tiles: scenes.len(),
+    }))
+}
+
+/// WebVTT 时间格式：`HH:MM:SS.mmm`
+fn vtt_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!("{:02}:{:02}:{:02}.{:03}", millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+}
+
+/// 缩略图上显示的时间：`M:SS`，超过一小时为 `H:MM:SS`
+fn overlay_time(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// 3x5 点阵字形（数字和冒号），每行低 3 位从左到右
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        _ => [0; 5],
+    }
+}
+
+/// 在缩略图左下角绘制时间，底色压暗以保证可读
+fn draw_timestamp(tile: &mut RgbImage, seconds: f64) {
+    let text = overlay_time(seconds);
+    // 缩略图高度 90 时字高 10 像素
+    let scale = (tile.height() / 45).max(1);
+    let (glyph_width, glyph_height) = (3 * scale, 5 * scale);
+    let padding = scale;
+    let box_width = (text.len() as u32 * (glyph_width + scale) + padding).min(tile.width());
+    let box_height = (glyph_height + 2 * padding).min(tile.height());
+    let top = tile.height() - box_height;
+
+    for y in top..tile.height() {
+        for x in 0..box_width {
+            let Rgb([r, g, b]) = *tile.get_pixel(x, y);
+            tile.put_pixel(x, y, Rgb([r / 3, g / 3, b / 3]));
+        }
+    }
+    for (i, c) in text.chars().enumerate() {
+        let left = padding + i as u32 * (glyph_width + scale);
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (x, y) = (left + col * scale + dx, top + padding + row as u32 * scale + dy);
+                        if x < tile.width() && y < tile.height() {
+                            tile.put_pixel(x, y, Rgb([255, 255, 255]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_sprite() {
+        let dir = std::env::temp_dir().join(format!("video-parse-sprite-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        RgbImage::from_pixel(320, 180, Rgb([200, 40, 40])).save(dir.join("keyframe_0000.jpg")).unwrap();
+        RgbImage::from_pixel(320, 180, Rgb([40, 200, 40])).save(dir.join("keyframe_0001.jpg")).unwrap();
+        let scenes = [
+            SceneMetadata::fixture(0, 0.0, 5.5),
+            SceneMetadata::fixture(1, 5.5, 65.0),
+            SceneMetadata::fixture(2, 65.0, 3725.25),
+        ];
+        let config = SpriteConfig { enabled: true, columns: 2, ..SpriteConfig::default() };
+
+        let sheet = write_sprite(&config, &dir, &scenes).unwrap().unwrap();
+        assert_eq!((sheet.columns, sheet.rows, sheet.tiles), (2, 2, 3));
+        let image = image::open(dir.join(SPRITE_IMAGE_FILE)).unwrap();
+        assert_eq!((image.width(), image.height()), (320, 180));
+        let vtt = std::fs::read_to_string(dir.join(SPRITE_VTT_FILE)).unwrap();
+        assert!(vtt.starts_with("WEBVTT\n"));
+        assert!(vtt.contains("00:00:05.500 --> 00:01:05.000\nsprite.jpg#xywh=160,0,160,90"));
+        assert!(vtt.contains("00:01:05.000 --> 01:02:05.250\nsprite.jpg#xywh=0,90,160,90"));
+        assert_eq!(overlay_time(3725.25), "1:02:05");
+        assert_eq!(parse_tile_size("240X135"), Some((240, 135)));
+        assert_eq!(parse_tile_size("0x90"), None);
+        assert!(write_sprite(&config, &dir, &[]).unwrap().is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
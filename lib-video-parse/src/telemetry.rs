@@ -0,0 +1,591 @@
+use anyhow::Result;
+use axum::{extract::Request, middleware::Next, response::Response};
+use crate::job_logs::JobLogLayer;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::field::{Empty, Field, Visit};
+use tracing::{info_span, Event, Instrument, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
+
+/// 处理阶段的 span 名称，JSON 日志中作为 stage 字段输出
+const STAGE_SPANS: &[&str] = &[
+    "download",
+    "extract_frames",
+    "detect_scenes",
+    "keyframes",
+    "ocr",
+    "faces",
+    "safety",
+    "embeddings",
+    "enrichment",
+    "audio",
+    "upload",
+    "vector_export",
+    "webhook",
+];
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// 便于阅读的文本格式（默认）
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象，便于日志系统解析
+    Json,
+}
+
+impl LogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("未知的日志格式: {}（可选值: text, json）", other)),
+        }
+    }
+}
+
+/// OTLP 传输协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OtlpProtocol {
+    /// gRPC（默认端口 4317）
+    #[default]
+    Grpc,
+    /// HTTP + protobuf（默认端口 4318）
+    HttpProtobuf,
+}
+
+impl OtlpProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OtlpProtocol::Grpc => "grpc",
+            OtlpProtocol::HttpProtobuf => "http/protobuf",
+        }
+    }
+}
+
+impl FromStr for OtlpProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "grpc" => Ok(OtlpProtocol::Grpc),
+            "http" | "http/protobuf" => Ok(OtlpProtocol::HttpProtobuf),
+            other => Err(format!("未知的 OTLP 协议: {}（可选值: grpc, http/protobuf）", other)),
+        }
+    }
+}
+
+impl fmt::Display for OtlpProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// OpenTelemetry 导出配置
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// OTLP 端点（未设置时不导出）
+    pub otlp_endpoint: Option<String>,
+    /// OTLP 传输协议
+    pub otlp_protocol: OtlpProtocol,
+    /// 导出请求附带的头（例如 SLS 的 x-sls-otel-project / x-sls-otel-ak-id）
+    pub otlp_headers: Vec<(String, String)>,
+    /// 上报的服务名
+    pub service_name: String,
+    /// 是否同时导出指标
+    pub export_metrics: bool,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            otlp_protocol: OtlpProtocol::Grpc,
+            otlp_headers: Vec::new(),
+            service_name: "video-parse".to_string(),
+            export_metrics: true,
+        }
+    }
+}
+
+/// 解析 `key1=value1,key2=value2` 格式的头列表（与 OTEL_EXPORTER_OTLP_HEADERS 格式一致）
+pub fn parse_headers(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some((key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// 遥测守卫，析构时刷新并关闭导出器
+#[derive(Default)]
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+    #[cfg(feature = "otel")]
+    meter_provider: Option<opentelemetry_sdk::metrics::SdkMeterProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        {
+            if let Some(provider) = self.tracer_provider.take() {
+                if let Err(e) = provider.shutdown() {
+                    eprintln!("关闭 OTLP trace 导出器失败: {}", e);
+                }
+            }
+            if let Some(provider) = self.meter_provider.take() {
+                if let Err(e) = provider.shutdown() {
+                    eprintln!("关闭 OTLP metric 导出器失败: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// 初始化日志和 OTLP 导出
+///
+/// 日志按 `log_format` 输出到标准输出，并按任务缓存供 `/jobs/{id}/logs` 查询；配置了 OTLP 端点且启用 `otel` feature 时，
+/// span 同时导出到 OTLP 端点，指标按固定周期导出
+pub fn init(log_level: &str, log_format: LogFormat, config: &TelemetryConfig) -> Result<TelemetryGuard> {
+    let level = log_level.parse::<LevelFilter>().unwrap_or(LevelFilter::INFO);
+    let fmt_layer: Box<dyn Layer<Registry> + Send + Sync> = match log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().with_filter(level).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonLogFormat)
+            .with_filter(level)
+            .boxed(),
+    };
+
+    #[cfg(feature = "otel")]
+    {
+        let (otel_layer, guard) = match config.otlp_endpoint {
+            Some(ref endpoint) => {
+                let (tracer, guard) = otel::init_providers(endpoint, config)?;
+                let layer = tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(level);
+                (Some(layer), guard)
+            }
+            None => (None, TelemetryGuard::default()),
+        };
+
+        tracing_subscriber::registry()
+            .with(fmt_layer)
+            .with(JobLogLayer.with_filter(level))
+            .with(otel_layer)
+            .try_init()
+            .map_err(|e| anyhow::anyhow!("初始化日志失败: {}", e))?;
+
+        if let Some(ref endpoint) = config.otlp_endpoint {
+            tracing::info!(
+                endpoint = %endpoint,
+                protocol = %config.otlp_protocol,
+                metrics = config.export_metrics,
+                "📡 OTLP 导出已启用"
+            );
+        }
+        Ok(guard)
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        tracing_subscriber::registry()
+            .with(fmt_layer)
+            .with(JobLogLayer.with_filter(level))
+            .try_init()
+            .map_err(|e| anyhow::anyhow!("初始化日志失败: {}", e))?;
+
+        if config.otlp_endpoint.is_some() {
+            tracing::warn!("⚠️  已配置 OTLP 端点，但未启用 otel feature，忽略导出配置");
+        }
+        Ok(TelemetryGuard::default())
+    }
+}
+
+/// JSON 日志格式
+///
+/// 每个事件输出一行 JSON：时间、级别、target、去掉装饰字符后的消息、所在处理阶段，
+/// 以及所在 span（request_id、job_id、bucket、key、duration_ms 等）和事件本身的字段。
+/// 只有装饰字符的横幅行不输出
+struct JsonLogFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonLogFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut fields = serde_json::Map::new();
+        let mut stage = None;
+
+        // 外层 span 的字段先写入，内层同名字段覆盖
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                if STAGE_SPANS.contains(&span.name()) {
+                    stage = Some(span.name());
+                }
+                let extensions = span.extensions();
+                if let Some(formatted) = extensions.get::<FormattedFields<N>>() {
+                    if let Ok(serde_json::Value::Object(map)) = serde_json::from_str(formatted.fields.as_str()) {
+                        fields.extend(map);
+                    }
+                }
+            }
+        }
+
+        event.record(&mut JsonVisitor(&mut fields));
+
+        let message = fields
+            .remove("message")
+            .and_then(|v| v.as_str().map(sanitize_message))
+            .unwrap_or_default();
+        if message.is_empty() && fields.is_empty() {
+            return Ok(());
+        }
+
+        let metadata = event.metadata();
+        fields.insert("timestamp".to_string(), chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true).into());
+        fields.insert("level".to_string(), metadata.level().as_str().into());
+        fields.insert("target".to_string(), metadata.target().into());
+        fields.insert("message".to_string(), message.into());
+        if let Some(stage) = stage {
+            fields.insert("stage".to_string(), stage.into());
+        }
+
+        writeln!(writer, "{}", serde_json::Value::Object(fields))
+    }
+}
+
+/// 把事件字段收集到 JSON 对象
+struct JsonVisitor<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+/// 去掉消息中的 emoji、框线和项目符号等装饰字符
+///
+/// 只剩装饰字符的横幅行返回空字符串
+pub fn sanitize_message(message: &str) -> String {
+    let cleaned: String = message
+        .chars()
+        .filter(|c| !is_decorative(*c))
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_decorative(c: char) -> bool {
+    matches!(c as u32,
+        0x2022              // •
+        | 0x2300..=0x23FF   // ⏳ 等技术符号
+        | 0x2500..=0x257F   // ━ 等框线
+        | 0x2600..=0x27BF   // ✅ ❌ ⚠ ⚙ ♻ 等杂项符号
+        | 0x2B00..=0x2BFF   // ⬆ ⬇ 等箭头
+        | 0xFE0F            // emoji 变体选择符
+        | 0x1F000..=0x1FAFF // emoji
+    )
+}
+
+/// HTTP 请求追踪中间件
+///
+/// 为每个请求创建 `http_request` span；启用 `otel` feature 时从请求头（W3C traceparent）
+/// 中提取上游的 trace 上下文作为父 span
+pub async fn trace_http(request: Request, next: Next) -> Response {
+    let span = info_span!(
+        "http_request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        status = Empty,
+    );
+
+    #[cfg(feature = "otel")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&otel::HeaderExtractor(request.headers()))
+        });
+        span.set_parent(parent);
+    }
+
+    let response = next.run(request).instrument(span.clone()).await;
+    span.record("status", response.status().as_u16());
+    response
+}
+
+/// 记录处理阶段耗时指标
+pub fn record_stage(stage: &'static str, duration: Duration) {
+    crate::metrics::Metrics::shared().record_stage(stage, duration);
+    #[cfg(feature = "otel")]
+    otel::instruments()
+        .stage_duration
+        .record(duration.as_secs_f64(), &[opentelemetry::KeyValue::new("stage", stage)]);
+}
+
+/// 记录 OSS 传输指标（direction: download | upload）
+pub fn record_transfer(direction: &'static str, bytes: u64, duration: Duration) {
+    crate::metrics::Metrics::shared().record_transfer(direction, bytes, duration);
+    #[cfg(feature = "otel")]
+    {
+        let attrs = [opentelemetry::KeyValue::new("direction", direction)];
+        let instruments = otel::instruments();
+        instruments.transfer_bytes.add(bytes, &attrs);
+        instruments.transfer_duration.record(duration.as_secs_f64(), &attrs);
+    }
+}
+
+/// 记录任务结果指标（outcome: success | failure | cached | skipped | rejected）
+pub fn record_job(outcome: &'static str) {
+    crate::metrics::Metrics::shared().record_job(outcome);
+    #[cfg(feature = "otel")]
+    otel::instruments()
+        .jobs
+        .add(1, &[opentelemetry::KeyValue::new("outcome", outcome)]);
+}
+
+/// 记录失败指标（code: 失败报告中的错误码，例如 `download_timeout`）
+pub fn record_failure(code: &str) {
+    crate::metrics::Metrics::shared().record_failure(code);
+    #[cfg(feature = "otel")]
+    otel::instruments()
+        .failures
+        .add(1, &[opentelemetry::KeyValue::new("code", code.to_string())]);
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use super::{OtlpProtocol, TelemetryConfig, TelemetryGuard};
+    use anyhow::{Context, Result};
+    use opentelemetry::metrics::{Counter, Histogram};
+    use opentelemetry::propagation::Extractor;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::{WithExportConfig, WithHttpConfig, WithTonicConfig};
+    use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+    use opentelemetry_sdk::{runtime, Resource};
+    use std::sync::OnceLock;
+
+    /// 从 axum 请求头中读取 trace 上下文
+    pub(super) struct HeaderExtractor<'a>(pub &'a axum::http::HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    /// 指标
+    pub(super) struct Instruments {
+        pub stage_duration: Histogram<f64>,
+        pub transfer_bytes: Counter<u64>,
+        pub transfer_duration: Histogram<f64>,
+        pub jobs: Counter<u64>,
+        pub failures: Counter<u64>,
+    }
+
+    pub(super) fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+        INSTRUMENTS.get_or_init(|| {
+            let meter = opentelemetry::global::meter("video-parse");
+            Instruments {
+                stage_duration: meter
+                    .f64_histogram("video_parse.stage.duration")
+                    .with_unit("s")
+                    .with_description("处理阶段耗时")
+                    .build(),
+                transfer_bytes: meter
+                    .u64_counter("video_parse.oss.transfer.bytes")
+                    .with_unit("By")
+                    .with_description("OSS 传输字节数")
+                    .build(),
+                transfer_duration: meter
+                    .f64_histogram("video_parse.oss.transfer.duration")
+                    .with_unit("s")
+                    .with_description("OSS 传输耗时")
+                    .build(),
+                jobs: meter
+                    .u64_counter("video_parse.jobs")
+                    .with_description("处理任务数")
+                    .build(),
+                failures: meter
+                    .u64_counter("video_parse.failures")
+                    .with_description("处理失败数")
+                    .build(),
+            }
+        })
+    }
+
+    /// HTTP 协议下各信号的完整路径（gRPC 直接使用端点）
+    fn signal_endpoint(endpoint: &str, protocol: OtlpProtocol, path: &str) -> String {
+        match protocol {
+            OtlpProtocol::Grpc => endpoint.to_string(),
+            OtlpProtocol::HttpProtobuf => {
+                let base = endpoint.trim_end_matches('/');
+                if base.ends_with(path) {
+                    base.to_string()
+                } else {
+                    format!("{}{}", base, path)
+                }
+            }
+        }
+    }
+
+    fn tonic_metadata(headers: &[(String, String)]) -> Result<tonic::metadata::MetadataMap> {
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        for (key, value) in headers {
+            let key = tonic::metadata::MetadataKey::from_bytes(key.to_lowercase().as_bytes())
+                .context(format!("无效的 OTLP 头名称: {}", key))?;
+            let value = value
+                .parse()
+                .context(format!("无效的 OTLP 头取值: {}", key))?;
+            metadata.insert(key, value);
+        }
+        Ok(metadata)
+    }
+
+    pub(super) fn init_providers(endpoint: &str, config: &TelemetryConfig) -> Result<(Tracer, TelemetryGuard)> {
+        let resource = Resource::new(vec![
+            KeyValue::new("service.name", config.service_name.clone()),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]);
+        let headers: std::collections::HashMap<String, String> =
+            config.otlp_headers.iter().cloned().collect();
+
+        let span_endpoint = signal_endpoint(endpoint, config.otlp_protocol, "/v1/traces");
+        let span_exporter = match config.otlp_protocol {
+            OtlpProtocol::Grpc => opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(span_endpoint)
+                .with_metadata(tonic_metadata(&config.otlp_headers)?)
+                .build(),
+            OtlpProtocol::HttpProtobuf => opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(span_endpoint)
+                .with_headers(headers.clone())
+                .build(),
+        }
+        .context("创建 OTLP trace 导出器失败")?;
+
+        let tracer_provider = TracerProvider::builder()
+            .with_batch_exporter(span_exporter, runtime::Tokio)
+            .with_resource(resource.clone())
+            .build();
+        let tracer = tracer_provider.tracer("video-parse");
+        opentelemetry::global::set_tracer_provider(tracer_provider.clone());
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let meter_provider = if config.export_metrics {
+            let metric_endpoint = signal_endpoint(endpoint, config.otlp_protocol, "/v1/metrics");
+            let metric_exporter = match config.otlp_protocol {
+                OtlpProtocol::Grpc => opentelemetry_otlp::MetricExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(metric_endpoint)
+                    .with_metadata(tonic_metadata(&config.otlp_headers)?)
+                    .build(),
+                OtlpProtocol::HttpProtobuf => opentelemetry_otlp::MetricExporter::builder()
+                    .with_http()
+                    .with_endpoint(metric_endpoint)
+                    .with_headers(headers)
+                    .build(),
+            }
+            .context("创建 OTLP metric 导出器失败")?;
+
+            let reader = PeriodicReader::builder(metric_exporter, runtime::Tokio).build();
+            let provider = SdkMeterProvider::builder()
+                .with_reader(reader)
+                .with_resource(resource)
+                .build();
+            opentelemetry::global::set_meter_provider(provider.clone());
+            Some(provider)
+        } else {
+            None
+        };
+
+        Ok((
+            tracer,
+            TelemetryGuard {
+                tracer_provider: Some(tracer_provider),
+                meter_provider,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_message() {
+        assert_eq!(sanitize_message("✅ [视频处理] 提取视频帧完成"), "[视频处理] 提取视频帧完成");
+        assert_eq!(sanitize_message("⚠️  [视频处理] Webhook 回调失败"), "[视频处理] Webhook 回调失败");
+        assert_eq!(sanitize_message("  • 输入文件: a.mp4"), "输入文件: a.mp4");
+        assert_eq!(sanitize_message("━━━━━━━━━━━━━━━━"), "");
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let headers = parse_headers("x-sls-otel-project=demo, x-sls-otel-instance-id = trace ,invalid,=empty");
+        assert_eq!(
+            headers,
+            vec![
+                ("x-sls-otel-project".to_string(), "demo".to_string()),
+                ("x-sls-otel-instance-id".to_string(), "trace".to_string()),
+            ]
+        );
+    }
+}
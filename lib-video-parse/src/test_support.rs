@@ -0,0 +1,304 @@
+use std::f64::consts::PI;
+use std::path::Path;
+use ffmpeg_next as ffmpeg;
+use ffmpeg::{codec, encoder, format, frame, ChannelLayout, Packet, Rational};
+use crate::error::{Result, VideoParseError};
+
+/// 合成视频中的一个场景：纯色画面，可选的移动方块和正弦音调
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyntheticScene {
+    /// 持续时间（秒）
+    pub duration: f64,
+    /// 画面颜色（RGB）
+    pub color: [u8; 3],
+    /// 画面中是否有水平移动的白色方块
+    pub motion: bool,
+    /// 音调频率（Hz），None 表示静音
+    pub tone_hz: Option<f64>,
+}
+
+impl SyntheticScene {
+    pub fn new(duration: f64, color: [u8; 3]) -> Self {
+        Self { duration, color, motion: false, tone_hz: None }
+    }
+
+    pub fn with_motion(mut self) -> Self {
+        self.motion = true;
+        self
+    }
+
+    pub fn with_tone(mut self, hz: f64) -> Self {
+        self.tone_hz = Some(hz);
+        self
+    }
+}
+
+/// 已知切点、颜色和音调的合成视频，用于验证场景检测
+///
+/// 只使用 FFmpeg 内置的编码器（MPEG-4 Part 2 视频、PCM 音频），写入 Matroska 容器
+#[derive(Debug, Clone)]
+pub struct SyntheticVideo {
+    pub width: u32,
+    pub height: u32,
+    pub fps: i32,
+    /// 是否包含音频流
+    pub audio: bool,
+    /// 音频采样率
+    pub sample_rate: i32,
+    pub scenes: Vec<SyntheticScene>,
+}
+
+impl SyntheticVideo {
+    /// 320x240、25fps、44.1kHz 单声道音频
+    pub fn new(scenes: Vec<SyntheticScene>) -> Self {
+        Self { width: 320, height: 240, fps: 25, audio: true, sample_rate: 44_100, scenes }
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn without_audio(mut self) -> Self {
+        self.audio = false;
+        self
+    }
+
+    /// 总时长（秒）
+    pub fn duration(&self) -> f64 {
+        self.scenes.iter().map(|scene| scene.duration).sum()
+    }
+
+    /// 各场景的起始时间（秒），即期望检测到的切点
+    pub fn cut_points(&self) -> Vec<f64> {
+        self.scenes
+            .iter()
+            .scan(0.0, |start, scene| {
+                let current = *start;
+                *start += scene.duration;
+                Some(current)
+            })
+            .collect()
+    }
+
+    /// 时间 `t`（秒）所在的场景
+    fn scene_at(&self, t: f64) -> Option<(&SyntheticScene, f64)> {
+        let mut start = 0.0;
+        for scene in &self.scenes {
+            if t < start + scene.duration {
+                return Some((scene, t - start));
+            }
+            start += scene.duration;
+        }
+        None
+    }
+
+    /// 编码写入文件（建议使用 .mkv 扩展名）
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let fail = |message: &str| {
+            let message = format!("生成测试视频失败: {}: {}", path.display(), message);
+            move |e: ffmpeg::Error| VideoParseError::decode(message, e)
+        };
+        if !self.width.is_multiple_of(2) || !self.height.is_multiple_of(2) {
+            return Err(VideoParseError::InvalidInput(format!(
+                "测试视频分辨率必须为偶数: {}x{}",
+                self.width, self.height
+            )));
+        }
+        ffmpeg::init().map_err(fail("初始化 FFmpeg 失败"))?;
+
+        let mut octx = format::output(path).map_err(fail("创建输出文件失败"))?;
+        let global_header = octx.format().flags().contains(format::Flags::GLOBAL_HEADER);
+
+        // 视频流
+        let video_codec = encoder::find(codec::Id::MPEG4)
+            .ok_or_else(|| VideoParseError::InvalidInput("FFmpeg 缺少 MPEG-4 编码器".to_string()))?;
+        let video_index = octx.add_stream(video_codec).map_err(fail("添加视频流失败"))?.index();
+        let mut video = codec::context::Context::new_with_codec(video_codec)
+            .encoder()
+            .video()
+            .map_err(fail("创建视频编码器失败"))?;
+        let video_time_base = Rational(1, self.fps);
+        video.set_width(self.width);
+        video.set_height(self.height);
+        video.set_format(format::Pixel::YUV420P);
+        video.set_time_base(video_time_base);
+        video.set_frame_rate(Some(Rational(self.fps, 1)));
+        // 每秒一个关键帧，seek 到任意采样点都不需要解码太多帧
+        video.set_gop(self.fps as u32);
+        video.set_bit_rate(1_000_000);
+        if global_header {
+            video.set_flags(codec::Flags::GLOBAL_HEADER);
+        }
+        let mut video = video.open_as(video_codec).map_err(fail("打开视频编码器失败"))?;
+        if let Some(mut stream) = octx.stream_mut(video_index) {
+            stream.set_parameters(&video);
+            stream.set_time_base(video_time_base);
+        }
+
+        // 音频流
+        let audio_time_base = Rational(1, self.sample_rate);
+        let mut audio = if self.audio {
+            let audio_codec = encoder::find(codec::Id::PCM_S16LE)
+                .ok_or_else(|| VideoParseError::InvalidInput("FFmpeg 缺少 PCM 编码器".to_string()))?;
+            let audio_index = octx.add_stream(audio_codec).map_err(fail("添加音频流失败"))?.index();
+            let mut audio = codec::context::Context::new_with_codec(audio_codec)
+                .encoder()
+                .audio()
+                .map_err(fail("创建音频编码器失败"))?;
+            audio.set_rate(self.sample_rate);
+            audio.set_channel_layout(ChannelLayout::MONO);
+            audio.set_format(format::Sample::I16(format::sample::Type::Packed));
+            audio.set_time_base(audio_time_base);
+            if global_header {
+                audio.set_flags(codec::Flags::GLOBAL_HEADER);
+            }
+            let audio = audio.open_as(audio_codec).map_err(fail("打开音频编码器失败"))?;
+            if let Some(mut stream) = octx.stream_mut(audio_index) {
+                stream.set_parameters(&audio);
+                stream.set_time_base(audio_time_base);
+            }
+            Some((audio, audio_index))
+        } else {
+            None
+        };
+
+        octx.write_header().map_err(fail("写入文件头失败"))?;
+        // 写入文件头后容器可能调整了时间基
+        let stream_time_base = |index: usize| octx.stream(index).map(|s| s.time_base());
+        let video_stream_time_base = stream_time_base(video_index).unwrap_or(video_time_base);
+        let audio_stream_time_base = audio
+            .as_ref()
+            .and_then(|(_, index)| stream_time_base(*index))
+            .unwrap_or(audio_time_base);
+
+        let total_frames = (self.duration() * self.fps as f64).round() as i64;
+        let mut picture = frame::Video::new(format::Pixel::YUV420P, self.width, self.height);
+        for index in 0..total_frames {
+            let t = index as f64 / self.fps as f64;
+            if let Some((scene, offset)) = self.scene_at(t) {
+                self.draw(&mut picture, scene, offset);
+            }
+            picture.set_pts(Some(index));
+            video.send_frame(&picture).map_err(fail("编码视频帧失败"))?;
+            write_packets(&mut video, video_index, video_time_base, video_stream_time_base, &mut octx)
+                .map_err(fail("写入视频数据失败"))?;
+        }
+        video.send_eof().map_err(fail("编码视频帧失败"))?;
+        write_packets(&mut video, video_index, video_time_base, video_stream_time_base, &mut octx)
+            .map_err(fail("写入视频数据失败"))?;
+
+        if let Some((ref mut audio, audio_index)) = audio {
+            const SAMPLES_PER_FRAME: usize = 1024;
+            let total_samples = (self.duration() * self.sample_rate as f64).round() as usize;
+            let mut written = 0;
+            while written < total_samples {
+                let samples = SAMPLES_PER_FRAME.min(total_samples - written);
+                let mut chunk = frame::Audio::new(
+                    format::Sample::I16(format::sample::Type::Packed),
+                    samples,
+                    ChannelLayout::MONO,
+                );
+                chunk.set_rate(self.sample_rate as u32);
+                for (i, sample) in chunk.plane_mut::<i16>(0).iter_mut().enumerate() {
+                    let t = (written + i) as f64 / self.sample_rate as f64;
+                    *sample = match self.scene_at(t).and_then(|(scene, _)| scene.tone_hz) {
+                        Some(hz) => ((2.0 * PI * hz * t).sin() * i16::MAX as f64 * 0.5) as i16,
+                        None => 0,
+                    };
+                }
+                chunk.set_pts(Some(written as i64));
+                audio.send_frame(&chunk).map_err(fail("编码音频失败"))?;
+                write_packets(audio, audio_index, audio_time_base, audio_stream_time_base, &mut octx)
+                    .map_err(fail("写入音频数据失败"))?;
+                written += samples;
+            }
+            audio.send_eof().map_err(fail("编码音频失败"))?;
+            write_packets(audio, audio_index, audio_time_base, audio_stream_time_base, &mut octx)
+                .map_err(fail("写入音频数据失败"))?;
+        }
+
+        octx.write_trailer().map_err(fail("写入文件尾失败"))?;
+        Ok(())
+    }
+
+    /// 按 BT.601 把场景颜色填入 YUV420P 帧，需要时画出移动方块
+    fn draw(&self, picture: &mut frame::Video, scene: &SyntheticScene, offset: f64) {
+        let (y, u, v) = rgb_to_yuv(scene.color);
+        let (width, height) = (self.width as usize, self.height as usize);
+        // 方块边长为画面高度的 1/4，每秒移动半个画面宽度，到右边缘后折返
+        let size = height / 4;
+        let span = (width - size) as f64;
+        let travel = (offset * width as f64 / 2.0) % (2.0 * span);
+        let box_x = (if travel <= span { travel } else { 2.0 * span - travel }) as usize;
+        let box_y = (height - size) / 2;
+        let in_box = |x: usize, y: usize| {
+            scene.motion && (box_x..box_x + size).contains(&x) && (box_y..box_y + size).contains(&y)
+        };
+
+        let stride = picture.stride(0);
+        let luma = picture.data_mut(0);
+        for row in 0..height {
+            for col in 0..width {
+                luma[row * stride + col] = if in_box(col, row) { 235 } else { y };
+            }
+        }
+        for (plane, value) in [(1, u), (2, v)] {
+            let stride = picture.stride(plane);
+            let chroma = picture.data_mut(plane);
+            for row in 0..height / 2 {
+                for col in 0..width / 2 {
+                    chroma[row * stride + col] = if in_box(col * 2, row * 2) { 128 } else { value };
+                }
+            }
+        }
+    }
+}
+
+/// 取出编码器中已完成的数据包并写入容器
+fn write_packets(
+    encoder: &mut encoder::Encoder,
+    stream_index: usize,
+    encoder_time_base: Rational,
+    stream_time_base: Rational,
+    octx: &mut format::context::Output,
+) -> std::result::Result<(), ffmpeg::Error> {
+    let mut packet = Packet::empty();
+    while encoder.receive_packet(&mut packet).is_ok() {
+        packet.set_stream(stream_index);
+        packet.rescale_ts(encoder_time_base, stream_time_base);
+        packet.write_interleaved(octx)?;
+    }
+    Ok(())
+}
+
+/// BT.601 有限范围的 RGB 到 YUV 转换
+fn rgb_to_yuv([r, g, b]: [u8; 3]) -> (u8, u8, u8) {
+    let (r, g, b) = (r as f64, g as f64, b as f64);
+    let y = 16.0 + 0.257 * r + 0.504 * g + 0.098 * b;
+    let u = 128.0 - 0.148 * r - 0.291 * g + 0.439 * b;
+    let v = 128.0 + 0.439 * r - 0.368 * g - 0.071 * b;
+    (y.round() as u8, u.round() as u8, v.round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_layout() {
+        let video = SyntheticVideo::new(vec![
+            SyntheticScene::new(2.0, [255, 0, 0]),
+            SyntheticScene::new(3.0, [0, 255, 0]).with_motion(),
+            SyntheticScene::new(1.5, [0, 0, 255]).with_tone(440.0),
+        ]);
+        assert_eq!(video.cut_points(), vec![0.0, 2.0, 5.0]);
+        assert_eq!(video.duration(), 6.5);
+        assert_eq!(video.scene_at(4.9).map(|(scene, offset)| (scene.motion, (offset * 10.0).round())), Some((true, 29.0)));
+        assert!(video.scene_at(6.5).is_none());
+        assert_eq!(rgb_to_yuv([0, 0, 0]), (16, 128, 128));
+        assert_eq!(rgb_to_yuv([255, 255, 255]).0, 235);
+    }
+}
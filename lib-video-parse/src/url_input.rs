@@ -0,0 +1,213 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use crate::error::{Result, VideoParseError};
+use crate::output_naming;
+
+/// 连接远程服务器的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 5;
+
+/// 输入是否为 HTTP / HTTPS URL
+pub fn is_http_url(input: &str) -> bool {
+    let lower = input.trim_start().to_ascii_lowercase();
+    lower.starts_with("https://") || lower.starts_with("http://")
+}
+
+/// 下载到本地时使用的文件名：取 URL 路径的最后一段，没有时为 `video.mp4`
+pub fn url_file_name(url: &reqwest::Url) -> String {
+    output_naming::local_file_name(url.path())
+}
+
+/// Content-Type 是否可能是媒体文件
+///
+/// 未返回 Content-Type 或为通用二进制类型时放行，由后续的媒体类型探测判断
+fn is_media_content_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    mime.is_empty()
+        || mime.starts_with("video/")
+        || mime.starts_with("audio/")
+        || mime.starts_with("image/")
+        || mime == "application/octet-stream"
+        || mime == "binary/octet-stream"
+}
+
+/// 不允许服务端访问的地址：回环、私有网段、链路本地、未指定、广播 / 组播，
+/// 以及 100.64.0.0/10（阿里云元数据服务 100.100.100.200 位于其中）
+fn is_blocked_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_blocked_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// 校验 URL 只指向公网地址：协议为 HTTP / HTTPS，主机解析出的所有地址都不在 [`is_blocked_ip`] 中，
+/// 返回解析出的地址，连接时固定使用这些地址（避免校验后 DNS 结果被改写）
+pub async fn check_public_url(url: &reqwest::Url) -> Result<Vec<SocketAddr>> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(VideoParseError::InvalidInput(format!("只支持 HTTP / HTTPS URL: {}", url)));
+    }
+    let port = url.port_or_known_default().unwrap_or(80);
+    let host = url.host_str()
+        .ok_or_else(|| VideoParseError::InvalidInput(format!("URL 缺少主机名: {}", url)))?;
+    let addrs: Vec<SocketAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| VideoParseError::Download { message: format!("解析域名失败: {}", host), source: Some(Box::new(e)) })?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(VideoParseError::InvalidInput(format!("域名没有解析到地址: {}", url)));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| is_blocked_ip(addr.ip())) {
+        return Err(VideoParseError::InvalidInput(format!("不允许访问内网或本机地址 {}: {}", addr.ip(), url)));
+    }
+    Ok(addrs)
+}
+
+/// 请求 URL，手动跟随重定向，每一跳都重新校验目标地址
+async fn get_public(mut url: reqwest::Url) -> Result<(reqwest::Url, reqwest::Response)> {
+    for _ in 0..=MAX_REDIRECTS {
+        let addrs = check_public_url(&url).await?;
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some(domain) = url.domain() {
+            builder = builder.resolve_to_addrs(domain, &addrs);
+        }
+        let client = builder.build().map_err(|e| download_error("创建 HTTP 客户端失败".to_string(), e))?;
+        let response = client.get(url.clone())
+            .send()
+            .await
+            .map_err(|e| download_error(format!("下载 URL 失败: {}", url), e))?;
+        if !response.status().is_redirection() {
+            let response = response.error_for_status().map_err(|e| download_error(format!("下载 URL 失败: {}", url), e))?;
+            return Ok((url, response));
+        }
+        let location = response.headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| VideoParseError::Download { message: format!("重定向缺少 Location: {}", url), source: None })?;
+        url = url.join(location)
+            .map_err(|e| VideoParseError::InvalidInput(format!("无效的重定向地址: {} ({})", location, e)))?;
+        tracing::debug!(url = %url, "跟随重定向");
+    }
+    Err(VideoParseError::Download { message: format!("重定向次数超过 {} 次: {}", MAX_REDIRECTS, url), source: None })
+}
+
+fn download_error(message: String, source: reqwest::Error) -> VideoParseError {
+    VideoParseError::Download { message, source: Some(Box::new(source)) }
+}
+
+/// 流式下载 HTTP / HTTPS URL 到 `dir`，返回本地文件路径
+///
+/// URL 或重定向目标指向内网、本机地址，响应的 Content-Type 不是媒体类型、Content-Length 或实际下载量超过
+/// `max_bytes` 时返回 [`VideoParseError::InvalidInput`]，已写入的部分文件会被删除
+pub async fn download_url(url: &str, dir: &Path, max_bytes: u64) -> Result<PathBuf> {
+    let url = reqwest::Url::parse(url.trim())
+        .map_err(|e| VideoParseError::InvalidInput(format!("无效的 URL: {} ({})", url, e)))?;
+    let (url, mut response) = get_public(url).await?;
+
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !is_media_content_type(content_type) {
+        return Err(VideoParseError::InvalidInput(format!("URL 不是视频文件（Content-Type: {}）: {}", content_type, url)));
+    }
+    if let Some(len) = response.content_length().filter(|len| *len > max_bytes) {
+        return Err(VideoParseError::InvalidInput(format!("文件大小 {} 字节超过限制 {} 字节: {}", len, max_bytes, url)));
+    }
+
+    let path = dir.join(url_file_name(&url));
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .map_err(|e| VideoParseError::io(format!("创建下载文件失败: {}", path.display()), e))?;
+    let mut written = 0u64;
+    let result = async {
+        while let Some(chunk) = response.chunk().await.map_err(|e| download_error(format!("读取下载数据失败: {}", url), e))? {
+            written += chunk.len() as u64;
+            if written > max_bytes {
+                return Err(VideoParseError::InvalidInput(format!("文件大小超过限制 {} 字节: {}", max_bytes, url)));
+            }
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| VideoParseError::io("写入下载文件失败", e))?;
+        }
+        file.flush().await.map_err(|e| VideoParseError::io("写入下载文件失败", e))
+    }
+    .await;
+    if let Err(e) = result {
+        drop(file);
+        tokio::fs::remove_file(&path).await.ok();
+        return Err(e);
+    }
+
+    tracing::info!("文件已下载到: {} ({} 字节)", path.display(), written);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_input() {
+        assert!(is_http_url("https://cdn.example.com/a.mp4"));
+        assert!(is_http_url("HTTP://cdn.example.com/a.mp4"));
+        assert!(!is_http_url("/data/videos/a.mp4"));
+        assert!(!is_http_url("oss://bucket/a.mp4"));
+
+        let url = reqwest::Url::parse("https://cdn.example.com/videos/demo.mp4?token=abc").unwrap();
+        assert_eq!(url_file_name(&url), "demo.mp4");
+        assert_eq!(url_file_name(&reqwest::Url::parse("https://cdn.example.com/").unwrap()), "video.mp4");
+
+        assert!(is_media_content_type("video/mp4"));
+        assert!(is_media_content_type("application/octet-stream; charset=binary"));
+        assert!(is_media_content_type(""));
+        assert!(!is_media_content_type("text/html; charset=utf-8"));
+    }
+
+    #[test]
+    fn test_blocked_ip() {
+        for ip in ["127.0.0.1", "10.0.0.8", "172.16.3.4", "192.168.1.1", "169.254.169.254", "100.100.100.200", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(is_blocked_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["8.8.8.8", "47.96.1.1", "2408:4000::1"] {
+            assert!(!is_blocked_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_internal_url() {
+        let dir = std::env::temp_dir();
+        for url in ["http://127.0.0.1/a.mp4", "http://100.100.100.200/latest/meta-data/", "http://[::1]:9000/a.mp4", "http://localhost/a.mp4"] {
+            let error = download_url(url, &dir, 1024).await.unwrap_err();
+            assert!(matches!(error, VideoParseError::InvalidInput(_)), "{}: {}", url, error);
+        }
+        assert!(matches!(download_url("ftp://cdn.example.com/a.mp4", &dir, 1024).await, Err(VideoParseError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// 语音检测的能量包络时间分辨率（秒，与常见 VAD 的 20ms 帧相同）
+pub const VAD_HOP: f64 = 0.02;
+/// 短于该时长（秒）的语音段视为噪声（咔嗒声、按键声等）
+const MIN_SPEECH: f64 = 0.1;
+
+/// 语音活动检测配置
+///
+/// 按音频能量把整段音频分为语音段和静音段，适合画面切换很少的播客、访谈类视频
+#[derive(Debug, Clone, PartialEq)]
+pub struct VadConfig {
+    /// 是否检测
+    pub enabled: bool,
+    /// 不低于该电平（dBFS）视为有声音
+    pub threshold_dbfs: f64,
+    /// 短于该时长（秒）的停顿并入前后的语音段
+    pub min_silence: f64,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_dbfs: -35.0,
+            min_silence: 0.3,
+        }
+    }
+}
+
+/// 音频片段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    Speech,
+    Silence,
+}
+
+/// 音频片段，写入 `VideoMetadata.audio_segments`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioSegment {
+    pub kind: SegmentKind,
+    /// 开始时间（秒）
+    pub start: f64,
+    /// 结束时间（秒）
+    pub end: f64,
+}
+
+/// 按能量包络划分语音段和静音段，片段首尾相接覆盖整段音频
+///
+/// `envelope` 为每 `hop` 秒一个点的 RMS 电平（dBFS）。先把短于 `min_silence` 的停顿并入语音，
+/// 再把短于 0.1 秒的语音段当作静音，最后合并相邻的同类片段
+pub fn detect_segments(envelope: &[f64], hop: f64, config: &VadConfig) -> Vec<AudioSegment> {
+    if envelope.is_empty() || hop <= 0.0 {
+        return Vec::new();
+    }
+    let mut runs: Vec<(SegmentKind, usize)> = Vec::new();
+    for &level in envelope {
+        let kind = if level >= config.threshold_dbfs { SegmentKind::Speech } else { SegmentKind::Silence };
+        push_run(&mut runs, kind, 1);
+    }
+
+    let min_silence = (config.min_silence / hop).round() as usize;
+    let min_speech = (MIN_SPEECH / hop).round() as usize;
+    let last = runs.len() - 1;
+    let runs = relabel(runs, |i, (kind, len)| kind == SegmentKind::Silence && i > 0 && i < last && len < min_silence);
+    let runs = relabel(runs, |_, (kind, len)| kind == SegmentKind::Speech && len < min_speech);
+
+    let mut segments = Vec::with_capacity(runs.len());
+    let mut offset = 0;
+    for (kind, len) in runs {
+        segments.push(AudioSegment { kind, start: round_ms(offset as f64 * hop), end: round_ms((offset + len) as f64 * hop) });
+        offset += len;
+    }
+    segments
+}
+
+/// 追加一段，与上一段类型相同时合并
+fn push_run(runs: &mut Vec<(SegmentKind, usize)>, kind: SegmentKind, len: usize) {
+    match runs.last_mut() {
+        Some((last, total)) if *last == kind => *total += len,
+        _ => runs.push((kind, len)),
+    }
+}
+
+/// 翻转满足 `flip` 的片段类型并合并相邻的同类片段
+fn relabel(runs: Vec<(SegmentKind, usize)>, flip: impl Fn(usize, (SegmentKind, usize)) -> bool) -> Vec<(SegmentKind, usize)> {
+    let mut merged = Vec::with_capacity(runs.len());
+    for (i, (kind, len)) in runs.into_iter().enumerate() {
+        let kind = match (flip(i, (kind, len)), kind) {
+            (true, SegmentKind::Speech) => SegmentKind::Silence,
+            (true, SegmentKind::Silence) => SegmentKind::Speech,
+            (false, kind) => kind,
+        };
+        push_run(&mut merged, kind, len);
+    }
+    merged
+}
+
+fn round_ms(seconds: f64) -> f64 {
+    (seconds * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_segments() {
+        // 0-1 秒静音，1-3 秒语音（中间 2.0-2.2 秒短暂停顿），3.5 秒处 40ms 咔嗒声，其余静音
+        let envelope: Vec<f64> = (0..200)
+            .map(|idx| match idx {
+                50..=99 | 110..=149 | 175..=176 => -20.0,
+                _ => -60.0,
+            })
+            .collect();
+        let segments = detect_segments(&envelope, VAD_HOP, &VadConfig { enabled: true, ..VadConfig::default() });
+        let spans: Vec<_> = segments.iter().map(|s| (s.kind, s.start, s.end)).collect();
+        assert_eq!(spans, vec![
+            (SegmentKind::Silence, 0.0, 1.0),
+            (SegmentKind::Speech, 1.0, 3.0),
+            (SegmentKind::Silence, 3.0, 4.0),
+        ]);
+        assert!(detect_segments(&[], VAD_HOP, &VadConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use std::str::FromStr;
+use std::time::Duration;
+use crate::embeddings::SceneEmbedding;
+use crate::metadata::SceneMetadata;
+use crate::processor::ProcessOutput;
+
+/// 单次写入的点数
+const BATCH_SIZE: usize = 64;
+/// 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 向量数据库类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorStoreKind {
+    /// Qdrant（REST API）
+    #[default]
+    Qdrant,
+    /// Milvus（RESTful API v2）
+    Milvus,
+}
+
+impl VectorStoreKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VectorStoreKind::Qdrant => "qdrant",
+            VectorStoreKind::Milvus => "milvus",
+        }
+    }
+}
+
+impl FromStr for VectorStoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "qdrant" => Ok(VectorStoreKind::Qdrant),
+            "milvus" => Ok(VectorStoreKind::Milvus),
+            other => Err(anyhow::anyhow!("未知的向量数据库类型: {}（可选 qdrant, milvus）", other)),
+        }
+    }
+}
+
+/// 向量数据库导出配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorStoreConfig {
+    /// 向量数据库类型
+    pub kind: VectorStoreKind,
+    /// 服务地址（未设置时不导出），例如 http://qdrant:6333 或 http://milvus:19530
+    pub endpoint: Option<String>,
+    /// 集合名称（不存在时按向量维度自动创建）
+    pub collection: String,
+    /// API Key（Qdrant 的 api-key 头，Milvus 的 Bearer token）
+    pub api_key: Option<String>,
+}
+
+impl Default for VectorStoreConfig {
+    fn default() -> Self {
+        Self {
+            kind: VectorStoreKind::default(),
+            endpoint: None,
+            collection: "video_scenes".to_string(),
+            api_key: None,
+        }
+    }
+}
+
+/// 场景点的 ID：由视频来源和场景编号确定，重复处理同一视频时覆盖已有的点
+///
+/// 取 63 位，同时满足 Qdrant 的无符号整数 ID 和 Milvus 的 Int64 主键
+pub fn point_id(source: &str, scene_id: usize) -> u64 {
+    crate::cache::fnv1a64(format!("{}#{}", source, scene_id).as_bytes()) & (i64::MAX as u64)
+}
+
+/// 场景点的附加字段
+fn scene_payload(source: &str, job_id: &str, keyframe: &str, scene: &SceneMetadata) -> Map<String, Value> {
+    let mut payload = Map::new();
+    payload.insert("source".to_string(), json!(source));
+    payload.insert("job_id".to_string(), json!(job_id));
+    payload.insert("scene_id".to_string(), json!(scene.scene_id));
+    payload.insert("keyframe".to_string(), json!(keyframe));
+    payload.insert("start_time".to_string(), json!(scene.start_time));
+    payload.insert("end_time".to_string(), json!(scene.end_time));
+    payload.insert("duration".to_string(), json!(scene.duration));
+    if let Some(ref text) = scene.ocr_text {
+        payload.insert("text".to_string(), json!(text));
+    }
+    if let Some(face_count) = scene.face_count {
+        payload.insert("face_count".to_string(), json!(face_count));
+    }
+    payload
+}
+
+/// 把各场景的向量和元数据写入向量数据库
+///
+/// `source` 标识视频来源（例如 `oss://bucket/key`），`keyframe_prefix` 为关键帧上传后的路径前缀
+/// （未上传时使用本地路径）。未启用图像向量时直接返回 0。返回写入的点数
+pub async fn export_scenes(
+    config: &VectorStoreConfig,
+    output: &ProcessOutput,
+    source: &str,
+    keyframe_prefix: Option<&str>,
+) -> Result<usize> {
+    let Some(ref endpoint) = config.endpoint else {
+        return Ok(0);
+    };
+    let Some(ref index) = output.metadata.embeddings else {
+        tracing::warn!("⚠️  [向量导出] 已配置向量数据库，但未计算图像向量（embeddings），跳过");
+        return Ok(0);
+    };
+
+    let embeddings_path = output.output_dir.join(&index.file);
+    let content = tokio::fs::read_to_string(&embeddings_path)
+        .await
+        .context(format!("读取向量文件失败: {}", embeddings_path.display()))?;
+    let embeddings = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<SceneEmbedding>)
+        .collect::<Result<Vec<_>, _>>()
+        .context("解析向量文件失败")?;
+
+    let points: Vec<(u64, Vec<f32>, Map<String, Value>)> = embeddings
+        .into_iter()
+        .filter_map(|embedding| {
+            let scene = output.metadata.scenes.iter().find(|s| s.scene_id == embedding.scene_id)?;
+            let keyframe = match keyframe_prefix {
+                Some(prefix) => format!("{}/{}", prefix, embedding.keyframe_file),
+                None => output.output_dir.join(&embedding.keyframe_file).to_string_lossy().to_string(),
+            };
+            let mut payload = scene_payload(source, &output.job_id, &keyframe, scene);
+            payload.insert("model".to_string(), json!(index.model));
+            Some((point_id(source, scene.scene_id), embedding.embedding, payload))
+        })
+        .collect();
+    if points.is_empty() {
+        return Ok(0);
+    }
+
+    let client = VectorStoreClient {
+        http: reqwest::Client::new(),
+        config,
+        endpoint: endpoint.trim_end_matches('/'),
+    };
+    client.ensure_collection(index.dimensions).await?;
+    for batch in points.chunks(BATCH_SIZE) {
+        client.upsert(batch).await?;
+    }
+    Ok(points.len())
+}
+
+struct VectorStoreClient<'a> {
+    http: reqwest::Client,
+    config: &'a VectorStoreConfig,
+    endpoint: &'a str,
+}
+
+impl VectorStoreClient<'_> {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self.http
+            .request(method, format!("{}{}", self.endpoint, path))
+            .timeout(REQUEST_TIMEOUT);
+        match (&self.config.api_key, self.config.kind) {
+            (Some(key), VectorStoreKind::Qdrant) => builder.header("api-key", key),
+            (Some(key), VectorStoreKind::Milvus) => builder.bearer_auth(key),
+            (None, _) => builder,
+        }
+    }
+
+    /// 集合不存在时按向量维度创建（余弦距离）
+    async fn ensure_collection(&self, dimensions: usize) -> Result<()> {
+        let collection = &self.config.collection;
+        match self.config.kind {
+            VectorStoreKind::Qdrant => {
+                let path = format!("/collections/{}", collection);
+                let response = self.request(reqwest::Method::GET, &path).send().await
+                    .context("查询 Qdrant 集合失败")?;
+                if response.status() != reqwest::StatusCode::NOT_FOUND {
+                    check_status(response, "查询 Qdrant 集合").await?;
+                    return Ok(());
+                }
+                tracing::info!(collection = %collection, dimensions, "📦 [向量导出] 创建 Qdrant 集合");
+                let body = json!({ "vectors": { "size": dimensions, "distance": "Cosine" } });
+                let response = self.request(reqwest::Method::PUT, &path).json(&body).send().await
+                    .context("创建 Qdrant 集合失败")?;
+                check_status(response, "创建 Qdrant 集合").await?;
+            }
+            VectorStoreKind::Milvus => {
+                let body = json!({ "collectionName": collection });
+                let data = self.milvus("/v2/vectordb/collections/has", &body).await?;
+                if data.get("has").and_then(Value::as_bool) == Some(true) {
+                    return Ok(());
+                }
+                tracing::info!(collection = %collection, dimensions, "📦 [向量导出] 创建 Milvus 集合");
+                let body = json!({
+                    "collectionName": collection,
+                    "dimension": dimensions,
+                    "metricType": "COSINE",
+                    "idType": "Int64",
+                    "primaryFieldName": "id",
+                    "vectorFieldName": "vector",
+                });
+                self.milvus("/v2/vectordb/collections/create", &body).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert(&self, points: &[(u64, Vec<f32>, Map<String, Value>)]) -> Result<()> {
+        match self.config.kind {
+            VectorStoreKind::Qdrant => {
+                let points: Vec<Value> = points
+                    .iter()
+                    .map(|(id, vector, payload)| json!({ "id": id, "vector": vector, "payload": payload }))
+                    .collect();
+                let path = format!("/collections/{}/points?wait=true", self.config.collection);
+                let response = self.request(reqwest::Method::PUT, &path)
+                    .json(&json!({ "points": points }))
+                    .send()
+                    .await
+                    .context("写入 Qdrant 失败")?;
+                check_status(response, "写入 Qdrant").await?;
+            }
+            VectorStoreKind::Milvus => {
+                // 快速创建的集合开启了动态字段，附加字段直接作为实体字段写入
+                let data: Vec<Value> = points
+                    .iter()
+                    .map(|(id, vector, payload)| {
+                        let mut entity = payload.clone();
+                        entity.insert("id".to_string(), json!(id));
+                        entity.insert("vector".to_string(), json!(vector));
+                        Value::Object(entity)
+                    })
+                    .collect();
+                let body = json!({ "collectionName": self.config.collection, "data": data });
+                self.milvus("/v2/vectordb/entities/upsert", &body).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Milvus 的接口总是返回 200，错误通过响应中的 code 表示
+    async fn milvus(&self, path: &str, body: &Value) -> Result<Value> {
+        let response = self.request(reqwest::Method::POST, path).json(body).send().await
+            .context(format!("Milvus 请求失败: {}", path))?;
+        let response = check_status(response, "Milvus 请求").await?;
+        let result: Value = response.json().await.context("解析 Milvus 响应失败")?;
+        match result.get("code").and_then(Value::as_i64) {
+            Some(0) | None => Ok(result.get("data").cloned().unwrap_or(Value::Null)),
+            Some(code) => Err(anyhow::anyhow!(
+                "Milvus 返回错误: {} {}",
+                code,
+                result.get("message").and_then(Value::as_str).unwrap_or_default()
+            )),
+        }
+    }
+}
+
+async fn check_status(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let error_text = response.text().await.unwrap_or_default();
+    Err(anyhow::anyhow!("{}返回错误状态: {} - {}", action, status, error_text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_id_is_stable() {
+        let a = point_id("oss://videos/a.mp4", 3);
+        assert_eq!(a, point_id("oss://videos/a.mp4", 3));
+        assert_ne!(a, point_id("oss://videos/a.mp4", 4));
+        assert_ne!(a, point_id("oss://videos/b.mp4", 3));
+        assert!(a <= i64::MAX as u64);
+        assert_eq!("Milvus".parse::<VectorStoreKind>().unwrap(), VectorStoreKind::Milvus);
+    }
+}
@@ -1,18 +1,273 @@
 use ffmpeg_next as ffmpeg;
 use image::DynamicImage;
-use anyhow::{Context, Result};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Instant;
 use std::io::{self, Write};
+use crate::progressive::{ProgressiveFile, ProgressiveInput};
+use crate::frame_pool::FramePool;
+use crate::error::{Result, VideoParseError};
+use crate::metadata::MediaKind;
+
+/// 帧提取时的 seek 精度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekMode {
+    /// 直接使用 seek 到的关键帧，不再向后解码（最快，时间点可能偏差一个 GOP）
+    Keyframe,
+    /// 在采样间隔一半的范围内寻找最接近的帧（默认）
+    Nearest,
+    /// 从关键帧向后解码到目标时间点的第一帧（最慢，时间点精确）
+    Exact,
+}
+
+impl SeekMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeekMode::Keyframe => "keyframe",
+            SeekMode::Nearest => "nearest",
+            SeekMode::Exact => "exact",
+        }
+    }
+
+    /// 每个采样点最多读取的数据包数
+    fn max_packets(&self) -> usize {
+        match self {
+            SeekMode::Keyframe | SeekMode::Nearest => 50,
+            // 精确 seek 需要从关键帧一直解码到目标帧，放宽到足以覆盖较长的 GOP
+            SeekMode::Exact => 1000,
+        }
+    }
+}
+
+/// 帧提取时使用的硬件解码
+///
+/// 设备不可用（没有驱动、FFmpeg 未编译对应的 hwaccel）时自动回退到软件解码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HwAccel {
+    /// 软件解码（默认）
+    #[default]
+    None,
+    /// 按平台依次尝试可用的设备：macOS 上为 VideoToolbox，其他平台为 NVDEC、VAAPI
+    Auto,
+    /// Linux VAAPI（Intel / AMD）
+    Vaapi,
+    /// NVIDIA NVDEC（CUDA）
+    Nvdec,
+    /// macOS VideoToolbox
+    VideoToolbox,
+}
+
+impl HwAccel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HwAccel::None => "none",
+            HwAccel::Auto => "auto",
+            HwAccel::Vaapi => "vaapi",
+            HwAccel::Nvdec => "nvdec",
+            HwAccel::VideoToolbox => "videotoolbox",
+        }
+    }
+
+    /// 依次尝试创建的硬件设备
+    fn device_types(&self) -> Vec<HwAccel> {
+        match self {
+            HwAccel::None => Vec::new(),
+            HwAccel::Auto if cfg!(target_os = "macos") => vec![HwAccel::VideoToolbox],
+            HwAccel::Auto => vec![HwAccel::Nvdec, HwAccel::Vaapi],
+            device => vec![*device],
+        }
+    }
+
+    fn av_device_type(&self) -> ffmpeg::sys::AVHWDeviceType {
+        use ffmpeg::sys::AVHWDeviceType;
+        match self {
+            HwAccel::None | HwAccel::Auto => AVHWDeviceType::AV_HWDEVICE_TYPE_NONE,
+            HwAccel::Vaapi => AVHWDeviceType::AV_HWDEVICE_TYPE_VAAPI,
+            HwAccel::Nvdec => AVHWDeviceType::AV_HWDEVICE_TYPE_CUDA,
+            HwAccel::VideoToolbox => AVHWDeviceType::AV_HWDEVICE_TYPE_VIDEOTOOLBOX,
+        }
+    }
+}
+
+impl FromStr for HwAccel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "off" | "false" | "" => Ok(HwAccel::None),
+            "auto" => Ok(HwAccel::Auto),
+            "vaapi" => Ok(HwAccel::Vaapi),
+            "nvdec" | "cuda" => Ok(HwAccel::Nvdec),
+            "videotoolbox" | "vt" => Ok(HwAccel::VideoToolbox),
+            other => Err(format!("未知的硬件解码: {}（可选值: none, auto, vaapi, nvdec, videotoolbox）", other)),
+        }
+    }
+}
+
+/// 像素宽高比（SAR）
+///
+/// 变形宽银幕（anamorphic）等素材的像素不是方形，存储分辨率需要按 SAR 缩放后才是正确的显示比例
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SampleAspectRatio {
+    pub num: u32,
+    pub den: u32,
+}
+
+impl SampleAspectRatio {
+    /// 方形像素（1:1）
+    pub const SQUARE: Self = Self { num: 1, den: 1 };
+
+    /// 约分后的 SAR，未设置（0:x）或无效时视为方形像素
+    pub fn new(num: i32, den: i32) -> Self {
+        if num <= 0 || den <= 0 {
+            return Self::SQUARE;
+        }
+        let (num, den) = (num as u32, den as u32);
+        let (mut a, mut b) = (num, den);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        Self { num: num / a, den: den / a }
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.num == self.den
+    }
+
+    /// 按 SAR 缩放宽度得到显示分辨率，高度不变；宽度取偶数以兼容 YUV 编码
+    pub fn display_size(&self, width: u32, height: u32) -> (u32, u32) {
+        if self.is_square() {
+            return (width, height);
+        }
+        let display_width = (width as f64 * self.num as f64 / self.den as f64).round() as u32;
+        ((display_width / 2 * 2).max(2), height)
+    }
+}
+
+impl Default for SampleAspectRatio {
+    fn default() -> Self {
+        Self::SQUARE
+    }
+}
+
+impl std::fmt::Display for SampleAspectRatio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.num, self.den)
+    }
+}
+
+/// 视频探测结果：只读取容器和流信息，不解码帧
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoProbe {
+    /// 时长（秒，容器未记录时为 0）
+    pub duration: f64,
+    pub fps: f64,
+    /// 存储分辨率（解码后的帧大小）
+    pub width: u32,
+    pub height: u32,
+    /// 像素宽高比，显示分辨率见 [`VideoProbe::display_size`]
+    pub sample_aspect_ratio: SampleAspectRatio,
+    /// 是否包含音频流
+    pub has_audio: bool,
+}
+
+/// 媒体详细信息：容器、视频流和音频流的参数，只读取文件头，不解码帧
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MediaInfo {
+    /// 容器格式（FFmpeg 解复用器名称，如 `mov,mp4,m4a,3gp,3g2,mj2`）
+    pub format: String,
+    /// 容器格式说明
+    pub format_long_name: String,
+    /// 时长（秒，容器未记录时为 0）
+    pub duration: f64,
+    /// 总码率（bps，容器未记录时为 None）
+    pub bit_rate: Option<u64>,
+    pub video: Option<VideoStreamInfo>,
+    pub audio: Option<AudioStreamInfo>,
+}
+
+/// 视频流参数
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct VideoStreamInfo {
+    /// 编码格式（如 `h264`、`hevc`）
+    pub codec: String,
+    /// 存储分辨率
+    pub width: u32,
+    pub height: u32,
+    /// 按 SAR 缩放后的显示分辨率
+    pub display_width: u32,
+    pub display_height: u32,
+    /// 像素宽高比（`num:den`）
+    pub sample_aspect_ratio: String,
+    pub fps: f64,
+    /// 像素格式（如 `yuv420p`）
+    pub pixel_format: Option<String>,
+    /// 码率（bps，未记录时为 None）
+    pub bit_rate: Option<u64>,
+    /// 容器记录的帧数（未记录时为 None）
+    pub frame_count: Option<u64>,
+}
+
+/// 音频流参数
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AudioStreamInfo {
+    /// 编码格式（如 `aac`、`opus`）
+    pub codec: String,
+    /// 采样率（Hz）
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// 码率（bps，未记录时为 None）
+    pub bit_rate: Option<u64>,
+}
+
+impl VideoProbe {
+    /// 按 SAR 缩放后的显示分辨率
+    pub fn display_size(&self) -> (u32, u32) {
+        self.sample_aspect_ratio.display_size(self.width, self.height)
+    }
+}
+
+/// 已打开的 FFmpeg 输入（本地文件或正在分段下载的文件）
+enum OpenedInput {
+    File(ffmpeg::format::context::Input),
+    Progressive(ProgressiveInput),
+}
+
+impl std::ops::Deref for OpenedInput {
+    type Target = ffmpeg::format::context::Input;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            OpenedInput::File(input) => input,
+            OpenedInput::Progressive(input) => input,
+        }
+    }
+}
+
+impl std::ops::DerefMut for OpenedInput {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            OpenedInput::File(input) => input,
+            OpenedInput::Progressive(input) => input,
+        }
+    }
+}
 
 /// 视频处理器，负责解码视频并提取帧
 pub struct VideoProcessor {
     input_path: String,
+    seek_mode: SeekMode,
+    hwaccel: HwAccel,
+    /// 正在分段下载的输入（边下载边处理时设置）
+    progressive: Option<Arc<ProgressiveFile>>,
+    /// 帧缓冲池
+    pool: Arc<FramePool>,
 }
 
 impl VideoProcessor {
     pub fn new(input_path: impl AsRef<Path>) -> Result<Self> {
-        ffmpeg::init().context("初始化 FFmpeg 失败")?;
+        ffmpeg::init().map_err(|e| VideoParseError::decode("初始化 FFmpeg 失败", e))?;
         
         // 设置 FFmpeg 日志级别为 ERROR，抑制警告和信息消息
         // 日志级别：panic, fatal, error, warning, info, verbose, debug, trace
@@ -23,25 +278,163 @@ impl VideoProcessor {
         
         Ok(Self {
             input_path: input_path.as_ref().to_string_lossy().to_string(),
+            seek_mode: SeekMode::Nearest,
+            hwaccel: HwAccel::None,
+            progressive: None,
+            pool: FramePool::shared(),
         })
     }
 
-    /// 获取视频信息
+    /// 从正在分段下载的文件创建视频处理器，读到尚未下载的部分时等待
+    pub fn from_progressive(file: Arc<ProgressiveFile>) -> Result<Self> {
+        let mut processor = Self::new(file.path())?;
+        processor.progressive = Some(file);
+        Ok(processor)
+    }
+
+    /// 打开输入
+    fn open_input(&self) -> Result<OpenedInput> {
+        match self.progressive {
+            Some(ref file) => Ok(OpenedInput::Progressive(file.open_input()?)),
+            None => {
+                // HTTP / HTTPS URL 由 FFmpeg 直接读取（只探测时不需要下载整个文件）
+                if !crate::url_input::is_http_url(&self.input_path) && !Path::new(&self.input_path).exists() {
+                    return Err(VideoParseError::InvalidInput(format!("视频文件不存在: {}", self.input_path)));
+                }
+                Ok(OpenedInput::File(
+                    ffmpeg::format::input(&self.input_path)
+                        .map_err(|e| VideoParseError::decode("无法打开视频文件", e))?,
+                ))
+            }
+        }
+    }
+
+    /// 设置帧提取时的 seek 精度
+    pub fn with_seek_mode(mut self, seek_mode: SeekMode) -> Self {
+        self.seek_mode = seek_mode;
+        self
+    }
+
+    /// 设置帧提取时使用的硬件解码，设备不可用时回退到软件解码
+    pub fn with_hwaccel(mut self, hwaccel: HwAccel) -> Self {
+        self.hwaccel = hwaccel;
+        self
+    }
+
+    /// 使用指定的帧缓冲池（默认使用进程内共享的缓冲池）
+    pub fn with_frame_pool(mut self, pool: Arc<FramePool>) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// 获取视频信息：帧率和存储分辨率
     pub fn get_video_info(&self) -> Result<(f64, u32, u32)> {
-        let ictx = ffmpeg::format::input(&self.input_path)
-            .context("无法打开视频文件")?;
-        
+        let ictx = self.open_input()?;
+        let (fps, width, height, _) = Self::video_info(&ictx)?;
+        Ok((fps, width, height))
+    }
+
+    /// 探测视频时长、分辨率、帧率和音频流，不解码帧
+    pub fn probe(&self) -> Result<VideoProbe> {
+        let ictx = self.open_input()?;
+        let (fps, width, height, sample_aspect_ratio) = Self::video_info(&ictx)?;
+        let duration = Self::container_duration(&ictx);
+        let has_audio = ictx.streams().best(ffmpeg::media::Type::Audio).is_some();
+        Ok(VideoProbe { duration, fps, width, height, sample_aspect_ratio, has_audio })
+    }
+
+    /// 探测容器、视频流和音频流的详细参数，不解码帧；纯音频和单张图片也适用
+    pub fn media_info(&self) -> Result<MediaInfo> {
+        let ictx = self.open_input()?;
+        let format = ictx.format();
+        let positive = |value: i64| u64::try_from(value).ok().filter(|v| *v > 0);
+        let video = match ictx.streams().best(ffmpeg::media::Type::Video) {
+            Some(stream) => {
+                let (fps, width, height, sample_aspect_ratio) = Self::video_info(&ictx)?;
+                let decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+                    .and_then(|context| context.decoder().video())
+                    .map_err(|e| VideoParseError::decode("无法创建视频解码器", e))?;
+                let (display_width, display_height) = sample_aspect_ratio.display_size(width, height);
+                Some(VideoStreamInfo {
+                    codec: stream.parameters().id().name().to_string(),
+                    width,
+                    height,
+                    display_width,
+                    display_height,
+                    sample_aspect_ratio: sample_aspect_ratio.to_string(),
+                    fps,
+                    pixel_format: decoder.format().descriptor().map(|descriptor| descriptor.name().to_string()),
+                    bit_rate: Some(decoder.bit_rate() as u64).filter(|rate| *rate > 0),
+                    frame_count: positive(stream.frames()),
+                })
+            }
+            None => None,
+        };
+        let audio = match ictx.streams().best(ffmpeg::media::Type::Audio) {
+            Some(stream) => {
+                let decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+                    .and_then(|context| context.decoder().audio())
+                    .map_err(|e| VideoParseError::decode("无法创建音频解码器", e))?;
+                Some(AudioStreamInfo {
+                    codec: stream.parameters().id().name().to_string(),
+                    sample_rate: decoder.rate(),
+                    channels: decoder.channels(),
+                    bit_rate: Some(decoder.bit_rate() as u64).filter(|rate| *rate > 0),
+                })
+            }
+            None => None,
+        };
+        Ok(MediaInfo {
+            format: format.name().to_string(),
+            format_long_name: format.description().to_string(),
+            duration: Self::container_duration(&ictx),
+            bit_rate: positive(ictx.bit_rate()),
+            video,
+            audio,
+        })
+    }
+
+    /// 容器记录的时长（秒），纯音频输入也适用
+    pub fn duration(&self) -> Result<f64> {
+        let ictx = self.open_input()?;
+        Ok(Self::container_duration(&ictx))
+    }
+
+    fn container_duration(ictx: &ffmpeg::format::context::Input) -> f64 {
+        ictx.duration().max(0) as f64 / ffmpeg::ffi::AV_TIME_BASE as f64
+    }
+
+    /// 判断输入的媒体类型：视频、纯音频或单张图片
+    pub fn media_kind(&self) -> Result<MediaKind> {
+        let ictx = self.open_input()?;
+        if ictx.streams().best(ffmpeg::media::Type::Video).is_some() {
+            // 图片由 image2 或 *_pipe（png_pipe、jpeg_pipe 等）解复用器打开
+            let format = ictx.format();
+            let format_name = format.name();
+            if format_name == "image2" || format_name.ends_with("_pipe") {
+                return Ok(MediaKind::StillImage);
+            }
+            return Ok(MediaKind::Video);
+        }
+        if ictx.streams().best(ffmpeg::media::Type::Audio).is_some() {
+            return Ok(MediaKind::AudioOnly);
+        }
+        Err(VideoParseError::InvalidInput("未找到视频流或音频流".to_string()))
+    }
+
+    /// 从已打开的输入读取帧率、存储分辨率和像素宽高比
+    fn video_info(ictx: &ffmpeg::format::context::Input) -> Result<(f64, u32, u32, SampleAspectRatio)> {
         let video_stream = ictx
             .streams()
             .best(ffmpeg::media::Type::Video)
-            .context("未找到视频流")?;
+            .ok_or(VideoParseError::NoVideoStream)?;
         
         let decoder_context = ffmpeg::codec::context::Context::from_parameters(video_stream.parameters())
-            .context("无法创建解码器上下文")?;
+            .map_err(|e| VideoParseError::decode("无法创建解码器上下文", e))?;
         
         let decoder = decoder_context.decoder()
             .video()
-            .context("无法创建视频解码器")?;
+            .map_err(|e| VideoParseError::decode("无法创建视频解码器", e))?;
         
         let fps = video_stream.avg_frame_rate();
         let fps_value = if fps.denominator() > 0 {
@@ -50,268 +443,600 @@ impl VideoProcessor {
             30.0 // 默认值
         };
         
-        Ok((fps_value, decoder.width(), decoder.height()))
+        let sar = decoder.aspect_ratio();
+        let sar = SampleAspectRatio::new(sar.numerator(), sar.denominator());
+        Ok((fps_value, decoder.width(), decoder.height(), sar))
     }
 
     /// 提取视频帧（优化版：使用 seek 跳转，大幅加速）
-    /// 返回 (时间戳(秒), 图像) 的向量
+    /// 返回 (时间戳(秒), 图像) 的向量，非方形像素的视频按 SAR 缩放到显示分辨率
+    ///
+    /// 所有采样帧同时保存在内存中，长视频使用 [`extract_frames_iter`](Self::extract_frames_iter)
     pub fn extract_frames(&self, sample_rate: Option<f64>) -> Result<Vec<(f64, DynamicImage)>> {
+        self.extract_frames_iter(sample_rate)?.collect()
+    }
+
+    /// 按采样率逐帧提取视频帧，每次迭代 seek 并解码一个采样点
+    ///
+    /// 帧按时间顺序产生，调用方处理完一帧后即可释放，内存占用与视频时长无关
+    pub fn extract_frames_iter(&self, sample_rate: Option<f64>) -> Result<FrameIter> {
         // 先获取视频信息
         let (fps_value, _width, _height) = self.get_video_info()?;
-        
-        // 打开视频文件
-        let mut ictx = ffmpeg::format::input(&self.input_path)
-            .context("无法打开视频文件")?;
-        
-        let video_stream_index = ictx
-            .streams()
-            .best(ffmpeg::media::Type::Video)
-            .context("未找到视频流")?
-            .index();
-        
-        // 保存 video_stream_index 和 time_base，避免借用问题
-        let time_base = ictx.stream(video_stream_index).unwrap().time_base();
-        
+        let fps_value = if fps_value > 0.0 { fps_value } else { 30.0 };
+
+        let ictx = self.open_input()?;
         // 获取视频时长（秒）
         let duration = ictx.duration() as f64 / ffmpeg::ffi::AV_TIME_BASE as f64;
-        
+
         // 采样率：如果指定了，使用指定的；否则使用 fps
         let sample_rate = sample_rate.unwrap_or(fps_value);
-        
+
         // 计算需要提取的时间点
         let frame_interval = 1.0 / sample_rate; // 每帧之间的时间间隔（秒）
         let num_frames = (duration / frame_interval).ceil() as usize;
-        
-        // 创建解码器上下文的辅助函数（避免重复代码）
-        let create_decoder_context = || -> Result<ffmpeg::codec::context::Context> {
-            Ok(ffmpeg::codec::context::Context::from_parameters(
-                ictx.stream(video_stream_index).unwrap().parameters()
-            ).context("无法创建解码器上下文")?)
-        };
-        
-        let decoder_context = create_decoder_context()?;
-        
-        // 禁用硬件加速，直接使用软件解码
-        // 硬件加速在某些情况下不稳定，特别是使用 seek 的场景
-        
-        let mut decoder = decoder_context.decoder()
-            .video()
-            .context("无法创建视频解码器")?;
-        
-        // 创建缩放器（软件解码）
-        let input_format = decoder.format();
-        
-        let mut scaler = ffmpeg::software::scaling::Context::get(
-            input_format,
-            decoder.width(),
-            decoder.height(),
-            ffmpeg::format::Pixel::RGB24,
-            decoder.width(),
-            decoder.height(),
-            ffmpeg::software::scaling::Flags::BILINEAR,
-        ).context("无法创建缩放器")?;
-        
-        let mut frames = Vec::new();
-        
-        // 进度跟踪
-        let extract_start_time = Instant::now();
-        let progress_interval = (num_frames / 20).max(1); // 每5%显示一次进度条
-        let log_interval = (num_frames / 10).max(1); // 每10%输出一次详细日志
-        let mut last_log_time = Instant::now();
-        let mut last_log_frame = 0;
-        
+        let targets: Vec<f64> = (0..num_frames)
+            .map(|i| i as f64 * frame_interval)
+            .take_while(|&t| t < duration) // 如果超过视频时长，停止
+            .collect();
+
         println!("   📊 帧提取参数:");
-        println!("      • 预计提取帧数: {} 帧", num_frames);
+        println!("      • 预计提取帧数: {} 帧", targets.len());
         println!("      • 视频时长: {:.2}秒", duration);
         println!("      • 采样间隔: {:.3}秒", frame_interval);
         println!("   🚀 开始提取视频帧...");
-        
-        // 对每个需要提取的时间点进行 seek 和解码
-        for i in 0..num_frames {
-            let target_time = i as f64 * frame_interval;
-            
-            // 如果超过视频时长，停止
-            if target_time >= duration {
-                break;
+
+        FrameIter::new(ictx, self.pool.clone(), self.seek_mode, self.hwaccel, fps_value, frame_interval, targets)
+    }
+
+    /// 提取指定时间点（秒）的帧，时间点应为之前采样得到的帧时间戳
+    ///
+    /// 从关键帧解码到时间点所在的帧（与 [`SeekMode::Exact`] 相同），保证取到的是同一帧
+    pub fn extract_frames_at(&self, times: &[f64], sample_rate: f64) -> Result<Vec<(f64, DynamicImage)>> {
+        let (fps_value, _width, _height) = self.get_video_info()?;
+        let fps_value = if fps_value > 0.0 { fps_value } else { 30.0 };
+        let ictx = self.open_input()?;
+        FrameIter::new(ictx, self.pool.clone(), SeekMode::Exact, self.hwaccel, fps_value, 1.0 / sample_rate, times.to_vec())?
+            .quiet()
+            .collect()
+    }
+
+    /// 解码任意时间点（秒）的单帧，用于封面图等不需要完整处理流程的场景
+    ///
+    /// 按精确 seek 解码到时间点所在的帧，返回帧的实际时间戳和图像（非方形像素按 SAR 缩放到显示分辨率）
+    pub fn extract_frame_at(&self, timestamp: f64) -> Result<(f64, DynamicImage)> {
+        if !timestamp.is_finite() || timestamp < 0.0 {
+            return Err(VideoParseError::InvalidInput(format!("无效的时间点: {}", timestamp)));
+        }
+        let (fps_value, _width, _height) = self.get_video_info()?;
+        let fps_value = if fps_value > 0.0 { fps_value } else { 30.0 };
+        let ictx = self.open_input()?;
+        let duration = Self::container_duration(&ictx);
+        if duration > 0.0 && timestamp >= duration {
+            return Err(VideoParseError::InvalidInput(format!("时间点 {:.3}s 超出视频时长 {:.3}s", timestamp, duration)));
+        }
+        FrameIter::new(ictx, self.pool.clone(), SeekMode::Exact, self.hwaccel, fps_value, 1.0 / fps_value, vec![timestamp])?
+            .quiet()
+            .next()
+            .unwrap_or_else(|| Err(VideoParseError::InvalidInput(format!("时间点 {:.3}s 没有可解码的帧", timestamp))))
+    }
+}
+
+/// 逐帧提取视频帧的迭代器，由 [`VideoProcessor::extract_frames_iter`] 创建
+///
+/// 产生 (时间戳(秒), 图像)，非方形像素的视频按 SAR 缩放到显示分辨率。seek 失败或找不到合适帧的时间点会被跳过
+pub struct FrameIter {
+    ictx: OpenedInput,
+    decoder: ffmpeg::codec::decoder::Video,
+    /// 正在使用的硬件解码设备，软件解码时为 None
+    hw_device: Option<HwAccel>,
+    /// 硬件帧下载失败，需要回退到软件解码
+    hw_failed: bool,
+    /// 按解码帧的格式和尺寸创建，硬件解码时输入为下载后的帧（通常为 NV12）
+    scaler: Option<ffmpeg::software::scaling::Context>,
+    /// 缩放输出的显示分辨率
+    output_size: (u32, u32),
+    video_stream_index: usize,
+    time_base: ffmpeg::Rational,
+    seek_mode: SeekMode,
+    fps: f64,
+    /// 采样间隔（秒）
+    frame_interval: f64,
+    /// 采样时间点
+    targets: Vec<f64>,
+    next_target: usize,
+    /// 解码帧，在整个提取过程中复用
+    decoded: ffmpeg::frame::Video,
+    /// 从显存下载到内存的硬件解码帧
+    transferred: ffmpeg::frame::Video,
+    /// 缩放输出帧，来自缓冲池，提取结束时归还
+    rgb_frame: Option<ffmpeg::frame::Video>,
+    pool: Arc<FramePool>,
+    /// 是否输出进度
+    verbose: bool,
+    progress: ExtractProgress,
+    extracted: usize,
+}
+
+/// 帧提取的进度统计
+struct ExtractProgress {
+    start: Instant,
+    last_log_time: Instant,
+    last_log_frame: usize,
+    /// 每5%显示一次进度条
+    progress_interval: usize,
+    /// 每10%输出一次详细日志
+    log_interval: usize,
+    pool_hits_before: u64,
+    pool_misses_before: u64,
+}
+
+impl FrameIter {
+    fn new(
+        ictx: OpenedInput,
+        pool: Arc<FramePool>,
+        seek_mode: SeekMode,
+        hwaccel: HwAccel,
+        fps: f64,
+        frame_interval: f64,
+        targets: Vec<f64>,
+    ) -> Result<Self> {
+        let video_stream_index = ictx
+            .streams()
+            .best(ffmpeg::media::Type::Video)
+            .ok_or(VideoParseError::NoVideoStream)?
+            .index();
+        let time_base = ictx.stream(video_stream_index).unwrap().time_base();
+
+        // 硬件解码默认关闭：部分驱动在频繁 seek 时不稳定，需要通过 hwaccel 配置显式开启
+        let (decoder, hw_device) = open_decoder(&ictx, video_stream_index, hwaccel)?;
+
+        // 缩放器在解码出第一帧后按帧格式创建，同时按 SAR 把存储分辨率缩放到显示分辨率
+        let sar = decoder.aspect_ratio();
+        let (output_width, output_height) = SampleAspectRatio::new(sar.numerator(), sar.denominator())
+            .display_size(decoder.width(), decoder.height());
+
+        // 缩放输出帧来自缓冲池
+        let rgb_frame = pool.take_video_frame(ffmpeg::format::Pixel::RGB24, output_width, output_height);
+        let (pool_hits_before, pool_misses_before) = pool.stats();
+        let now = Instant::now();
+        let progress = ExtractProgress {
+            start: now,
+            last_log_time: now,
+            last_log_frame: 0,
+            progress_interval: (targets.len() / 20).max(1),
+            log_interval: (targets.len() / 10).max(1),
+            pool_hits_before,
+            pool_misses_before,
+        };
+
+        Ok(Self {
+            ictx,
+            decoder,
+            hw_device,
+            hw_failed: false,
+            scaler: None,
+            output_size: (output_width, output_height),
+            video_stream_index,
+            time_base,
+            seek_mode,
+            fps,
+            frame_interval,
+            targets,
+            next_target: 0,
+            decoded: ffmpeg::frame::Video::empty(),
+            transferred: ffmpeg::frame::Video::empty(),
+            rgb_frame: Some(rgb_frame),
+            pool,
+            verbose: true,
+            progress,
+            extracted: 0,
+        })
+    }
+
+    /// 不输出进度
+    fn quiet(mut self) -> Self {
+        self.verbose = false;
+        self
+    }
+
+    /// seek 到目标时间点并解码最接近的帧
+    fn extract_at(&mut self, target_time: f64) -> Option<(f64, DynamicImage)> {
+        // 将时间转换为时间戳（基于 AV_TIME_BASE）
+        let timestamp = (target_time * ffmpeg::ffi::AV_TIME_BASE as f64) as i64;
+
+        // Seek 到目标时间点（向后查找最近的 keyframe）
+        unsafe {
+            let ret = ffmpeg::sys::av_seek_frame(
+                self.ictx.as_mut_ptr(),
+                -1, // 对所有流 seek
+                timestamp,
+                ffmpeg::sys::AVSEEK_FLAG_BACKWARD as i32, // 向后查找最近的 keyframe
+            );
+            if ret < 0 {
+                // Seek 失败，跳过这个时间点
+                return None;
             }
-            
-            // 将时间转换为时间戳（基于 AV_TIME_BASE）
-            let timestamp = (target_time * ffmpeg::ffi::AV_TIME_BASE as f64) as i64;
-            
-            // Seek 到目标时间点（向后查找最近的 keyframe）
-            unsafe {
-                let ret = ffmpeg::sys::av_seek_frame(
-                    ictx.as_mut_ptr(),
-                    -1, // 对所有流 seek
-                    timestamp,
-                    ffmpeg::sys::AVSEEK_FLAG_BACKWARD as i32, // 向后查找最近的 keyframe
-                );
-                if ret < 0 {
-                    // Seek 失败，跳过这个时间点
-                    continue;
-                }
+        }
+
+        // 刷新解码器缓冲区
+        self.decoder.flush();
+
+        // 读取并解码帧，直到找到目标时间点附近的帧
+        let mut found_frame = false;
+        let mut best_frame: Option<(f64, DynamicImage)> = None;
+        let mut best_time_diff = f64::MAX;
+        let rgb_frame = self.rgb_frame.as_mut()?;
+
+        // 读取一些数据包来找到最接近目标时间的帧
+        let mut packets_read = 0;
+        let max_packets_to_read = self.seek_mode.max_packets();
+
+        for (stream, packet) in self.ictx.packets() {
+            if stream.index() != self.video_stream_index {
+                continue;
             }
-            
-            // 刷新解码器缓冲区
-            decoder.flush();
-            
-            // 读取并解码帧，直到找到目标时间点附近的帧
-            let mut found_frame = false;
-            let mut best_frame: Option<(f64, DynamicImage)> = None;
-            let mut best_time_diff = f64::MAX;
-            
-            // 读取一些数据包来找到最接近目标时间的帧
-            let mut packets_read = 0;
-            const MAX_PACKETS_TO_READ: usize = 50; // 最多读取50个数据包来找到目标帧
-            
-            for (stream, packet) in ictx.packets() {
-                if stream.index() != video_stream_index {
-                    continue;
-                }
-                
-                packets_read += 1;
-                if packets_read > MAX_PACKETS_TO_READ {
-                    break; // 避免无限循环
-                }
-                
-                let send_result = decoder.send_packet(&packet);
-                if send_result.is_err() {
-                    // 发送数据包失败，跳过这个数据包
-                    continue;
-                }
-                
-                let mut decoded = ffmpeg::frame::Video::empty();
-                
-                while decoder.receive_frame(&mut decoded).is_ok() {
-                    let frame_time = decoded.timestamp()
-                        .map(|ts| {
-                            let tb_num = time_base.numerator() as f64;
-                            let tb_den = time_base.denominator() as f64;
-                            ts as f64 * tb_num / tb_den
-                        })
-                        .unwrap_or(0.0);
-                    
-                    let time_diff = (frame_time - target_time).abs();
-                    
-                    // 如果找到更接近目标时间的帧，保存它
-                    if time_diff < best_time_diff {
-                        best_time_diff = time_diff;
-                        
+
+            packets_read += 1;
+            if packets_read > max_packets_to_read {
+                break; // 避免无限循环
+            }
+
+            let send_result = self.decoder.send_packet(&packet);
+            if send_result.is_err() {
+                // 发送数据包失败，跳过这个数据包
+                continue;
+            }
+
+            while self.decoder.receive_frame(&mut self.decoded).is_ok() {
+                let frame_time = self.decoded.timestamp()
+                    .map(|ts| {
+                        let tb_num = self.time_base.numerator() as f64;
+                        let tb_den = self.time_base.denominator() as f64;
+                        ts as f64 * tb_num / tb_den
+                    })
+                    .unwrap_or(0.0);
+
+                let time_diff = (frame_time - target_time).abs();
+
+                // 如果找到更接近目标时间的帧，保存它
+                if time_diff < best_time_diff {
+                    best_time_diff = time_diff;
+
+                    let acceptable = match self.seek_mode {
+                        // 关键帧模式：seek 后解出的第一帧即可
+                        SeekMode::Keyframe => true,
                         // 如果时间差小于一个采样间隔的一半，认为找到了合适的帧
-                        if time_diff <= frame_interval / 2.0 {
-                            // 解码并转换帧（软件解码）
-                            let mut rgb_frame = ffmpeg::frame::Video::empty();
-                            if scaler.run(&decoded, &mut rgb_frame).is_ok() {
-                                if let Ok(img) = self.frame_to_image(&rgb_frame) {
-                                    best_frame = Some((frame_time, img));
-                                    found_frame = true;
+                        SeekMode::Nearest => time_diff <= self.frame_interval / 2.0,
+                        // 精确模式：到达目标时间点（容差半帧）的第一帧
+                        SeekMode::Exact => frame_time + 0.5 / self.fps >= target_time,
+                    };
+                    if acceptable {
+                        // 硬件解码的帧在显存中，先下载到内存再缩放
+                        let frame = if self.hw_device.is_some() && is_hw_format(self.decoded.format()) {
+                            if !transfer_hw_frame(&self.decoded, &mut self.transferred) {
+                                self.hw_failed = true;
+                                if let Some((_, img)) = best_frame.take() {
+                                    self.pool.recycle_image(img);
                                 }
+                                return None;
+                            }
+                            &self.transferred
+                        } else {
+                            &self.decoded
+                        };
+                        let scaled = scaler_for(&mut self.scaler, frame, self.output_size)
+                            .is_some_and(|scaler| scaler.run(frame, rgb_frame).is_ok());
+                        if scaled {
+                            if let Ok(img) = frame_to_image(&self.pool, rgb_frame) {
+                                // 被替换的候选帧归还缓冲池
+                                if let Some((_, replaced)) = best_frame.replace((frame_time, img)) {
+                                    self.pool.recycle_image(replaced);
+                                }
+                                found_frame = true;
                             }
                         }
                     }
-                    
-                    // 如果已经超过目标时间太多，停止搜索
-                    if frame_time > target_time + frame_interval {
-                        break;
-                    }
                 }
-                
-                // 如果找到了合适的帧，停止读取更多数据包
-                if found_frame {
+
+                // 如果已经超过目标时间太多，停止搜索
+                if frame_time > target_time + self.frame_interval {
                     break;
                 }
             }
-            
-            // 如果找到了帧，添加到结果中
-            if let Some((time, img)) = best_frame {
-                frames.push((time, img));
-            }
-            
-            // 显示进度条（每5%更新一次）
-            if (i + 1) % progress_interval == 0 || i == num_frames - 1 {
-                let progress = ((i + 1) as f64 / num_frames as f64 * 100.0) as u32;
-                let elapsed = extract_start_time.elapsed();
-                let elapsed_secs = elapsed.as_secs_f64();
-                let fps = (i + 1) as f64 / elapsed_secs.max(0.001);
-                let remaining_frames = num_frames - (i + 1);
-                let estimated_remaining = if fps > 0.0 {
-                    remaining_frames as f64 / fps
-                } else {
-                    0.0
-                };
-                
-                // 计算进度条
-                let bar_width = 30;
-                let filled = (progress as f64 / 100.0 * bar_width as f64) as usize;
-                let bar = "█".repeat(filled) + &"░".repeat(bar_width - filled);
-                
-                print!("\r   📈 进度: [{}] {}% ({}/{}) | 已用: {:.1}s | 速度: {:.1} 帧/s | 剩余: {:.1}s     ", 
-                    bar, progress, i + 1, num_frames, elapsed_secs, fps, estimated_remaining);
-                io::stdout().flush().ok();
-            }
-            
-            // 输出详细日志（每10%输出一次）
-            if (i + 1) % log_interval == 0 || i == num_frames - 1 {
-                let progress = ((i + 1) as f64 / num_frames as f64 * 100.0) as u32;
-                let elapsed = extract_start_time.elapsed();
-                let elapsed_secs = elapsed.as_secs_f64();
-                let avg_fps = (i + 1) as f64 / elapsed_secs.max(0.001);
-                
-                // 计算最近一段时间的速度
-                let frames_since_last_log = (i + 1) - last_log_frame;
-                let time_since_last_log = last_log_time.elapsed().as_secs_f64();
-                let recent_fps = if time_since_last_log > 0.0 && frames_since_last_log > 0 {
-                    frames_since_last_log as f64 / time_since_last_log
-                } else {
-                    avg_fps
-                };
-                
-                // 输出详细日志（换行输出，不影响进度条）
-                println!("\n   📝 进度日志: {}% ({}/{}) | 已用: {:.1}s | 平均速度: {:.1} 帧/s | 当前速度: {:.1} 帧/s", 
-                    progress, i + 1, num_frames, elapsed_secs, avg_fps, recent_fps);
-                
-                last_log_frame = i + 1;
-                last_log_time = Instant::now();
+
+            // 如果找到了合适的帧，停止读取更多数据包
+            if found_frame {
+                break;
             }
         }
-        
+
+        best_frame
+    }
+
+    /// 重新打开软件解码器，后续的采样点都使用软件解码
+    fn fallback_to_software(&mut self) -> Result<()> {
+        tracing::warn!(
+            device = self.hw_device.map(|d| d.as_str()),
+            "⚠️  硬件解码帧下载失败，回退到软件解码"
+        );
+        let (decoder, _) = open_decoder(&self.ictx, self.video_stream_index, HwAccel::None)?;
+        self.decoder = decoder;
+        self.hw_device = None;
+        self.hw_failed = false;
+        Ok(())
+    }
+
+    /// 显示第 `i` 个采样点处理完后的进度
+    fn report_progress(&mut self, i: usize) {
+        let num_frames = self.targets.len();
+        let progress = &mut self.progress;
+
+        // 显示进度条（每5%更新一次）
+        if (i + 1).is_multiple_of(progress.progress_interval) || i == num_frames - 1 {
+            let percent = ((i + 1) as f64 / num_frames as f64 * 100.0) as u32;
+            let elapsed_secs = progress.start.elapsed().as_secs_f64();
+            let fps = (i + 1) as f64 / elapsed_secs.max(0.001);
+            let remaining_frames = num_frames - (i + 1);
+            let estimated_remaining = if fps > 0.0 {
+                remaining_frames as f64 / fps
+            } else {
+                0.0
+            };
+
+            // 计算进度条
+            let bar_width = 30;
+            let filled = (percent as f64 / 100.0 * bar_width as f64) as usize;
+            let bar = "█".repeat(filled) + &"░".repeat(bar_width - filled);
+
+            print!("\r   📈 进度: [{}] {}% ({}/{}) | 已用: {:.1}s | 速度: {:.1} 帧/s | 剩余: {:.1}s     ",
+                bar, percent, i + 1, num_frames, elapsed_secs, fps, estimated_remaining);
+            io::stdout().flush().ok();
+        }
+
+        // 输出详细日志（每10%输出一次）
+        if (i + 1).is_multiple_of(progress.log_interval) || i == num_frames - 1 {
+            let percent = ((i + 1) as f64 / num_frames as f64 * 100.0) as u32;
+            let elapsed_secs = progress.start.elapsed().as_secs_f64();
+            let avg_fps = (i + 1) as f64 / elapsed_secs.max(0.001);
+
+            // 计算最近一段时间的速度
+            let frames_since_last_log = (i + 1) - progress.last_log_frame;
+            let time_since_last_log = progress.last_log_time.elapsed().as_secs_f64();
+            let recent_fps = if time_since_last_log > 0.0 && frames_since_last_log > 0 {
+                frames_since_last_log as f64 / time_since_last_log
+            } else {
+                avg_fps
+            };
+
+            // 输出详细日志（换行输出，不影响进度条）
+            println!("\n   📝 进度日志: {}% ({}/{}) | 已用: {:.1}s | 平均速度: {:.1} 帧/s | 当前速度: {:.1} 帧/s",
+                percent, i + 1, num_frames, elapsed_secs, avg_fps, recent_fps);
+
+            progress.last_log_frame = i + 1;
+            progress.last_log_time = Instant::now();
+        }
+    }
+
+    /// 所有采样点处理完毕：缩放输出帧归还缓冲池并输出总结
+    fn finish(&mut self) {
+        let Some(rgb_frame) = self.rgb_frame.take() else {
+            return;
+        };
+        self.pool.recycle_video_frame(rgb_frame);
+        if !self.verbose {
+            return;
+        }
         println!(); // 换行，结束进度显示
-        
+        let (pool_hits, pool_misses) = self.pool.stats();
+
         // 输出提取完成总结
-        let total_elapsed = extract_start_time.elapsed();
+        let total_elapsed = self.progress.start.elapsed();
         let total_secs = total_elapsed.as_secs_f64();
-        let avg_fps = frames.len() as f64 / total_secs.max(0.001);
+        let avg_fps = self.extracted as f64 / total_secs.max(0.001);
         println!("   ✅ 帧提取完成!");
-        println!("      • 成功提取: {} 帧", frames.len());
+        println!("      • 成功提取: {} 帧", self.extracted);
         println!("      • 总耗时: {:.2}秒 ({:.0}ms)", total_secs, total_elapsed.as_millis());
         println!("      • 平均速度: {:.2} 帧/秒", avg_fps);
-        println!("      • 平均耗时: {:.2}ms/帧", total_elapsed.as_millis() as f64 / frames.len().max(1) as f64);
-        
-        Ok(frames)
+        println!("      • 平均耗时: {:.2}ms/帧", total_elapsed.as_millis() as f64 / self.extracted.max(1) as f64);
+        println!("      • 缓冲池: 复用 {} 次, 新分配 {} 次",
+            pool_hits.saturating_sub(self.progress.pool_hits_before),
+            pool_misses.saturating_sub(self.progress.pool_misses_before));
     }
+}
 
-    /// 将 FFmpeg 帧转换为 DynamicImage
-    fn frame_to_image(&self, frame: &ffmpeg::frame::Video) -> Result<DynamicImage> {
-        let width = frame.width();
-        let height = frame.height();
-        let data = frame.data(0);
-        
-        // RGB24 格式：每个像素 3 字节
-        let mut img_buf = image::RgbImage::new(width, height);
-        
-        for y in 0..height {
-            for x in 0..width {
-                let idx = ((y * frame.stride(0) as u32) + (x * 3)) as usize;
-                if idx + 2 < data.len() {
-                    let r = data[idx];
-                    let g = data[idx + 1];
-                    let b = data[idx + 2];
-                    img_buf.put_pixel(x, y, image::Rgb([r, g, b]));
+impl Iterator for FrameIter {
+    type Item = Result<(f64, DynamicImage)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // 对每个需要提取的时间点进行 seek 和解码，跳过找不到帧的时间点
+        while self.next_target < self.targets.len() {
+            let i = self.next_target;
+            self.next_target += 1;
+            let mut frame = self.extract_at(self.targets[i]);
+            if self.hw_failed {
+                // 硬件帧无法下载（驱动或显存问题），换成软件解码重新提取这个时间点
+                if let Err(e) = self.fallback_to_software() {
+                    return Some(Err(e));
                 }
+                frame = self.extract_at(self.targets[i]);
+            }
+            if self.verbose {
+                self.report_progress(i);
+            }
+            if let Some(frame) = frame {
+                self.extracted += 1;
+                return Some(Ok(frame));
+            }
+        }
+        self.finish();
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.targets.len() - self.next_target))
+    }
+}
+
+impl Drop for FrameIter {
+    /// 提前结束迭代时同样归还缩放输出帧
+    fn drop(&mut self) {
+        if let Some(rgb_frame) = self.rgb_frame.take() {
+            self.pool.recycle_video_frame(rgb_frame);
+        }
+    }
+}
+
+/// 打开视频流的解码器，按 `hwaccel` 依次尝试硬件设备，都不可用时使用软件解码
+///
+/// 返回解码器和实际使用的硬件设备
+fn open_decoder(
+    ictx: &ffmpeg::format::context::Input,
+    stream_index: usize,
+    hwaccel: HwAccel,
+) -> Result<(ffmpeg::codec::decoder::Video, Option<HwAccel>)> {
+    let video_stream = ictx.stream(stream_index).unwrap();
+    let mut decoder_context = ffmpeg::codec::context::Context::from_parameters(video_stream.parameters())
+        .map_err(|e| VideoParseError::decode("无法创建解码器上下文", e))?;
+
+    let hw_device = hwaccel.device_types().into_iter().find(|device| {
+        let mut device_ctx: *mut ffmpeg::sys::AVBufferRef = std::ptr::null_mut();
+        let ret = unsafe {
+            ffmpeg::sys::av_hwdevice_ctx_create(
+                &mut device_ctx,
+                device.av_device_type(),
+                std::ptr::null(),
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if ret < 0 {
+            tracing::debug!(device = device.as_str(), ret, "硬件解码设备不可用");
+            return false;
+        }
+        // 解码器上下文持有设备引用，解码器释放时一并释放
+        unsafe {
+            (*decoder_context.as_mut_ptr()).hw_device_ctx = device_ctx;
+        }
+        true
+    });
+    match hw_device {
+        Some(device) => tracing::info!(device = device.as_str(), "🎮 使用硬件解码"),
+        None if hwaccel != HwAccel::None => {
+            tracing::warn!(hwaccel = hwaccel.as_str(), "⚠️  硬件解码设备不可用，回退到软件解码");
+        }
+        None => {}
+    }
+
+    match decoder_context.decoder().video() {
+        Ok(decoder) => Ok((decoder, hw_device)),
+        // 编码格式不支持硬件解码等情况下打开失败，换成软件解码再试一次
+        Err(e) if hw_device.is_some() => {
+            tracing::warn!(error = %e, "⚠️  硬件解码器打开失败，回退到软件解码");
+            open_decoder(ictx, stream_index, HwAccel::None)
+        }
+        Err(e) => Err(VideoParseError::decode("无法创建视频解码器", e)),
+    }
+}
+
+/// 是否为显存中的硬件帧格式
+fn is_hw_format(format: ffmpeg::format::Pixel) -> bool {
+    matches!(
+        format,
+        ffmpeg::format::Pixel::VAAPI | ffmpeg::format::Pixel::CUDA | ffmpeg::format::Pixel::VIDEOTOOLBOX
+    )
+}
+
+/// 把硬件帧下载到内存帧，失败时返回 false
+fn transfer_hw_frame(hw_frame: &ffmpeg::frame::Video, frame: &mut ffmpeg::frame::Video) -> bool {
+    unsafe {
+        ffmpeg::sys::av_frame_unref(frame.as_mut_ptr());
+        ffmpeg::sys::av_hwframe_transfer_data(frame.as_mut_ptr(), hw_frame.as_ptr(), 0) >= 0
+    }
+}
+
+/// 取与帧格式、尺寸一致的缩放器，不一致（第一帧、硬件解码回退）时重新创建
+fn scaler_for<'a>(
+    scaler: &'a mut Option<ffmpeg::software::scaling::Context>,
+    frame: &ffmpeg::frame::Video,
+    (output_width, output_height): (u32, u32),
+) -> Option<&'a mut ffmpeg::software::scaling::Context> {
+    let reusable = scaler.as_ref().is_some_and(|scaler| {
+        let input = scaler.input();
+        input.format == frame.format() && input.width == frame.width() && input.height == frame.height()
+    });
+    if !reusable {
+        match ffmpeg::software::scaling::Context::get(
+            frame.format(),
+            frame.width(),
+            frame.height(),
+            ffmpeg::format::Pixel::RGB24,
+            output_width,
+            output_height,
+            ffmpeg::software::scaling::Flags::BILINEAR,
+        ) {
+            Ok(created) => *scaler = Some(created),
+            Err(e) => {
+                tracing::warn!(error = %e, "无法创建缩放器");
+                *scaler = None;
             }
         }
-        
-        Ok(DynamicImage::ImageRgb8(img_buf))
     }
-    
-}
\ No newline at end of file
+    scaler.as_mut()
+}
+
+/// 将 FFmpeg 帧转换为 DynamicImage
+fn frame_to_image(pool: &FramePool, frame: &ffmpeg::frame::Video) -> Result<DynamicImage> {
+    let width = frame.width();
+    let height = frame.height();
+    let data = frame.data(0);
+    let stride = frame.stride(0);
+
+    // RGB24 格式：每个像素 3 字节，按行拷贝（每行末尾可能有对齐填充）
+    let mut img_buf = pool.take_image(width, height);
+    let row_len = width as usize * 3;
+
+    for (y, row) in img_buf.chunks_exact_mut(row_len).enumerate() {
+        let start = y * stride;
+        match data.get(start..start + row_len) {
+            Some(src) => row.copy_from_slice(src),
+            None => row.fill(0),
+        }
+    }
+
+    Ok(DynamicImage::ImageRgb8(img_buf))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_aspect_ratio() {
+        assert_eq!(SampleAspectRatio::new(0, 1), SampleAspectRatio::SQUARE);
+        assert_eq!(SampleAspectRatio::new(1, 0), SampleAspectRatio::SQUARE);
+        assert_eq!(SampleAspectRatio::new(16, 16).display_size(1920, 1080), (1920, 1080));
+
+        // DV 宽银幕 PAL：720x576 存储，16:15 SAR
+        let sar = SampleAspectRatio::new(64, 60);
+        assert_eq!(sar.to_string(), "16:15");
+        assert_eq!(sar.display_size(720, 576), (768, 576));
+        // HDV 1440x1080 变形宽银幕，4:3 SAR 显示为 1920x1080
+        assert_eq!(SampleAspectRatio::new(4, 3).display_size(1440, 1080), (1920, 1080));
+    }
+
+    #[test]
+    fn test_hwaccel() {
+        assert_eq!("".parse::<HwAccel>(), Ok(HwAccel::None));
+        assert_eq!("CUDA".parse::<HwAccel>(), Ok(HwAccel::Nvdec));
+        assert_eq!(" videotoolbox ".parse::<HwAccel>(), Ok(HwAccel::VideoToolbox));
+        assert!("dxva2".parse::<HwAccel>().is_err());
+        for hwaccel in [HwAccel::None, HwAccel::Auto, HwAccel::Vaapi, HwAccel::Nvdec, HwAccel::VideoToolbox] {
+            assert_eq!(hwaccel.as_str().parse::<HwAccel>(), Ok(hwaccel));
+        }
+
+        assert!(HwAccel::None.device_types().is_empty());
+        assert_eq!(HwAccel::Vaapi.device_types(), vec![HwAccel::Vaapi]);
+        // auto 只展开为具体设备
+        assert!(!HwAccel::Auto.device_types().is_empty());
+        assert!(!HwAccel::Auto.device_types().contains(&HwAccel::Auto));
+    }
+}
@@ -0,0 +1,176 @@
+//! 结果查看页：在输出目录生成 index.html，展示场景时间线、关键帧、时间点和音频播放器，
+//! 用浏览器直接打开即可浏览处理结果。模板随库编译（templates/viewer.html）
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use crate::metadata::{SceneMetadata, VideoMetadata};
+
+/// 查看页文件名
+pub const VIEWER_FILE: &str = "index.html";
+/// 查看页模板（minijinja，按 `.html` 扩展名自动做 HTML 转义）
+const TEMPLATE: &str = include_str!("../templates/viewer.html");
+
+/// 模板中的查看页数据
+#[derive(Serialize)]
+struct ViewerContext {
+    title: String,
+    summary: String,
+    /// 音频文件的 URL 路径，没有音频时为空
+    audio: Option<String>,
+    scenes: Vec<SceneView>,
+}
+
+/// 模板中的单个场景：时间点和比例已格式化
+#[derive(Serialize)]
+struct SceneView {
+    id: usize,
+    /// 点击后音频跳转到的时间（秒）
+    seek: String,
+    /// 时间线中所占宽度（百分比）
+    width: String,
+    start: String,
+    end: String,
+    src: String,
+    /// 场景编号、时长和可选的转场、景别
+    info: String,
+    caption: Option<String>,
+    tags: Vec<String>,
+    /// 识别文字（空白时为空）
+    text: Option<String>,
+}
+
+/// 由元数据生成查看页，关键帧和音频按输出目录中的相对路径引用
+pub fn render(metadata: &VideoMetadata) -> Result<String> {
+    let title = Path::new(&metadata.input_video)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| metadata.input_video.clone());
+    let summary = format!(
+        "{} 个场景 · 时长 {} · {} · {:.2} fps",
+        metadata.scene_count,
+        clock(metadata.total_duration),
+        metadata.resolution,
+        metadata.fps
+    );
+    let total = metadata.total_duration.max(f64::EPSILON);
+    let context = ViewerContext {
+        title,
+        summary,
+        audio: (!metadata.audio_file.is_empty()).then(|| url_path(&metadata.audio_file)),
+        scenes: metadata.scenes.iter().map(|scene| scene_view(scene, total)).collect(),
+    };
+
+    let mut env = minijinja::Environment::new();
+    env.add_template("viewer.html", TEMPLATE).context("解析查看页模板失败")?;
+    env.get_template("viewer.html")
+        .and_then(|template| template.render(&context))
+        .context("渲染查看页失败")
+}
+
+fn scene_view(scene: &SceneMetadata, total: f64) -> SceneView {
+    let mut info = vec![format!("场景 {}", scene.scene_id), format!("{:.1} 秒", scene.duration)];
+    info.extend(scene.transition_type.map(|transition| transition.as_str().to_string()));
+    info.extend(scene.shot_type.map(|shot_type| shot_type.as_str().to_string()));
+    SceneView {
+        id: scene.scene_id,
+        seek: format!("{:.3}", scene.start_time),
+        width: format!("{:.3}", (scene.duration / total * 100.0).clamp(0.0, 100.0)),
+        start: clock(scene.start_time),
+        end: clock(scene.end_time),
+        src: url_path(&scene.keyframe_file),
+        info: info.join(" · "),
+        caption: scene.caption.clone(),
+        tags: scene.tags.clone(),
+        text: scene.ocr_text.as_deref().map(str::trim).filter(|text| !text.is_empty()).map(String::from),
+    }
+}
+
+/// 生成查看页并写入 `output_dir/index.html`
+pub fn write(metadata: &VideoMetadata, output_dir: &Path) -> Result<()> {
+    let path = output_dir.join(VIEWER_FILE);
+    std::fs::write(&path, render(metadata)?).with_context(|| format!("写入查看页失败: {}", path.display()))
+}
+
+/// `h:mm:ss.s` 或 `mm:ss.s`
+fn clock(seconds: f64) -> String {
+    let tenths = (seconds.max(0.0) * 10.0).round() as u64;
+    let (hours, minutes, secs) = (tenths / 36000, tenths / 600 % 60, tenths % 600);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}.{}", hours, minutes, secs / 10, secs % 10)
+    } else {
+        format!("{:02}:{:02}.{}", minutes, secs / 10, secs % 10)
+    }
+}
+
+/// 相对路径按 URL 编码（保留 `/`），文件名中的空格、`#`、`?` 等不会被浏览器误解析
+fn url_path(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::MediaKind;
+    use crate::scene_detector::TransitionType;
+
+    #[test]
+    fn test_render_viewer() {
+        let metadata = VideoMetadata {
+            input_video: "/data/videos/<demo>.mp4".to_string(),
+            media_kind: MediaKind::Video,
+            limitations: Vec::new(),
+            total_duration: 3725.5,
+            fps: 25.0,
+            resolution: "1920x1080".to_string(),
+            storage_resolution: None,
+            sample_aspect_ratio: None,
+            scene_count: 2,
+            audio_file: "audio.aac".to_string(),
+            scenes: vec![
+                SceneMetadata::fixture(0, 0.0, 5.52),
+                SceneMetadata {
+                    keyframe_file: "demo #1.jpg".to_string(),
+                    transition_type: Some(TransitionType::Dissolve),
+                    caption: Some("海边 & \"日落\"".to_string()),
+                    tags: vec!["beach".to_string(), "<b>{{scenes}}</b>".to_string()],
+                    ocr_text: Some("  ".to_string()),
+                    ..SceneMetadata::fixture(1, 5.52, 3725.5)
+                },
+            ],
+            safety: None,
+            faces: None,
+            embeddings: None,
+            sprite: None,
+            loudness: None,
+            audio_segments: Vec::new(),
+            exports: Vec::new(),
+            fingerprint_file: None,
+            source_hash: None,
+            config_fingerprint: None,
+            metadata_file: "metadata.json".to_string(),
+        };
+
+        let html = render(&metadata).unwrap();
+        assert!(!html.contains("{{ ") && !html.contains("{%"));
+        assert!(html.contains("<h1>&lt;demo&gt;.mp4</h1>"));
+        assert!(html.contains("2 个场景 · 时长 1:02:05.5 · 1920x1080 · 25.00 fps"));
+        assert!(html.contains(r#"src="audio.aac""#));
+        assert!(html.contains(r#"<img src="demo%20%231.jpg""#));
+        assert!(html.contains(r#"data-start="5.520">00:05.5 – 1:02:05.5</button>"#));
+        assert!(html.contains("场景 1 · 3720.0 秒 · dissolve"));
+        assert!(html.contains("海边 &amp; &quot;日落&quot;"));
+        // 替换进去的值中的模板语法原样输出，不会再被展开
+        assert!(html.contains("&lt;b&gt;{{scenes}}&lt;&#x2f;b&gt;"));
+        assert!(html.contains(r#"<span class="tag">beach</span>"#));
+        // 空白的识别文字不显示，没有音频时不显示播放器
+        assert!(!html.contains(r#"class="text""#));
+        let silent = VideoMetadata { audio_file: String::new(), ..metadata };
+        assert!(!render(&silent).unwrap().contains("<audio"));
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// 处理阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// 下载 OSS 源文件
+    Download,
+    /// 视频帧提取
+    Extract,
+    /// 场景检测
+    Detect,
+    /// 上传处理结果
+    Upload,
+    /// 整体处理（由 [`ProcessOptions`](crate::ProcessOptions) 设置）
+    Process,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Download => "download",
+            Stage::Extract => "extract",
+            Stage::Detect => "detect",
+            Stage::Upload => "upload",
+            Stage::Process => "process",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Stage::Download => "下载",
+            Stage::Extract => "视频帧提取",
+            Stage::Detect => "场景检测",
+            Stage::Upload => "上传",
+            Stage::Process => "视频处理",
+        }
+    }
+}
+
+/// 各处理阶段的超时时间（None 表示不限制）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageTimeouts {
+    pub download: Option<Duration>,
+    pub extract: Option<Duration>,
+    pub detect: Option<Duration>,
+    pub upload: Option<Duration>,
+}
+
+impl StageTimeouts {
+    /// 指定阶段的超时时间
+    pub fn get(&self, stage: Stage) -> Option<Duration> {
+        match stage {
+            Stage::Download => self.download,
+            Stage::Extract => self.extract,
+            Stage::Detect => self.detect,
+            Stage::Upload => self.upload,
+            // 整体处理超时按次设置，不属于阶段超时配置
+            Stage::Process => None,
+        }
+    }
+}
+
+/// 秒数转换为超时时间，0 表示不限制
+pub fn timeout_from_secs(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// 阶段超时错误
+///
+/// 可以从 `anyhow::Error` 中 downcast 出来，用于区分超时和其他失败
+#[derive(Debug, Clone, Copy)]
+pub struct StageTimeoutError {
+    pub stage: Stage,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for StageTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}阶段超时（超过 {}s）", self.stage.label(), self.timeout.as_secs())
+    }
+}
+
+impl std::error::Error for StageTimeoutError {}
+
+/// 在阶段超时时间内等待 `future` 完成
+///
+/// 超时后返回 [`StageTimeoutError`]，`future` 被丢弃。
+/// 通过 `spawn_blocking` 执行的阶段无法被中断，阻塞线程会继续运行到结束，但任务本身立即失败
+pub async fn with_timeout<F: Future>(
+    stage: Stage,
+    timeout: Option<Duration>,
+    future: F,
+) -> Result<F::Output, StageTimeoutError> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, future)
+            .await
+            .map_err(|_| StageTimeoutError { stage, timeout }),
+        None => Ok(future.await),
+    }
+}
+
+/// 对已启动的后台任务施加阶段超时，超时时间从 `started` 开始计算
+///
+/// 超时后中止原任务并调用 `on_timeout`（例如让正在等待数据的读取方立即失败），
+/// 返回的任务以 [`StageTimeoutError`] 结束
+pub fn guard_task<T, E>(
+    stage: Stage,
+    timeout: Option<Duration>,
+    started: Instant,
+    task: JoinHandle<Result<T, E>>,
+    on_timeout: impl FnOnce(&StageTimeoutError) + Send + 'static,
+) -> JoinHandle<Result<T, E>>
+where
+    T: Send + 'static,
+    E: From<StageTimeoutError> + From<anyhow::Error> + Send + 'static,
+{
+    let Some(timeout) = timeout else {
+        return task;
+    };
+    tokio::spawn(async move {
+        let abort = task.abort_handle();
+        let remaining = timeout.saturating_sub(started.elapsed());
+        match tokio::time::timeout(remaining, task).await {
+            Ok(result) => result.map_err(|e| E::from(anyhow::anyhow!("{}任务异常终止: {}", stage.label(), e)))?,
+            Err(_) => {
+                abort.abort();
+                let error = StageTimeoutError { stage, timeout };
+                on_timeout(&error);
+                Err(error.into())
+            }
+        }
+    })
+}
+
+/// 判断错误是否为阶段超时（包括处理阶段失败中包装的超时）
+pub fn as_timeout(error: &anyhow::Error) -> Option<&StageTimeoutError> {
+    let error = match error.downcast_ref::<crate::failure::StageFailure>() {
+        Some(failure) => &failure.error,
+        None => error,
+    };
+    error.downcast_ref::<StageTimeoutError>().or_else(|| {
+        error
+            .downcast_ref::<crate::error::VideoParseError>()
+            .and_then(|error| error.as_timeout())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stage_timeout() {
+        let result = with_timeout(
+            Stage::Download,
+            Some(Duration::from_millis(10)),
+            tokio::time::sleep(Duration::from_secs(5)),
+        )
+        .await;
+        let err = anyhow::Error::from(result.unwrap_err());
+        assert_eq!(as_timeout(&err).unwrap().stage, Stage::Download);
+
+        let result = with_timeout(Stage::Upload, None, async { 42 }).await;
+        assert_eq!(result.unwrap(), 42);
+    }
+}
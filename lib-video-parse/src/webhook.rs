@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, instrument, warn};
+use crate::auth;
+use crate::oss_client::OssClient;
+
+/// 单次请求的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// 两次重试之间的最长等待时间
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// webhook 投递配置：重试和回调数据格式
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookConfig {
+    /// 最多尝试次数（包括第一次）
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍
+    pub initial_backoff: Duration,
+    /// 重试用尽后保存未送达回调的目录（None 表示不保存）
+    pub spool_dir: Option<PathBuf>,
+    /// 重试用尽后上传未送达回调的 OSS 位置（`oss://bucket/prefix`，None 表示不上传）
+    pub spool_oss: Option<String>,
+    /// 回调请求体的 HMAC-SHA256 签名密钥，签名放在 `x-video-parse-signature` 请求头（None 表示不签名）
+    pub secret: Option<String>,
+    /// 回调数据模板文件（minijinja 语法，渲染结果必须是 JSON），优先于 `fields`
+    pub template: Option<PathBuf>,
+    /// 只发送这些字段（`path` 或 `name=path`，path 以 `.` 分隔），为空时发送完整数据
+    pub fields: Vec<String>,
+    /// 同一阶段内进度回调的最短间隔
+    pub progress_interval: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            spool_dir: Some(default_spool_dir()),
+            spool_oss: None,
+            secret: None,
+            template: None,
+            fields: Vec::new(),
+            progress_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// 第 `attempt` 次尝试失败后的等待时间（指数退避，上限 60 秒）
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+}
+
+/// 默认的未送达回调目录
+pub fn default_spool_dir() -> PathBuf {
+    std::env::temp_dir().join("video-parse").join("webhook-spool")
+}
+
+/// 解析 `oss://bucket/prefix` 形式的位置，返回 (bucket, prefix)，prefix 不含首尾的 `/`
+pub fn parse_oss_location(location: &str) -> Option<(&str, &str)> {
+    let rest = location.trim().strip_prefix("oss://")?;
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    (!bucket.is_empty()).then_some((bucket, prefix.trim_matches('/')))
+}
+
+/// 构建回调 POST 请求：JSON 请求体，设置了 `secret` 时附带请求体的 HMAC-SHA256 签名
+///
+/// 签名格式与 OSS 事件回调的签名校验相同（`sha256=<hex>`），接收方用同一密钥对原始请求体计算并比较
+pub fn signed_post(client: &reqwest::Client, url: &str, secret: Option<&str>, payload: &Value) -> reqwest::RequestBuilder {
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    let mut request = client.post(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(secret) = secret {
+        request = request.header(auth::SIGNATURE_HEADER, auth::sign(secret, &body));
+    }
+    request.body(body)
+}
+
+/// 解析逗号分隔的字段列表
+pub fn parse_fields(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|field| field.trim().to_string())
+        .filter(|field| !field.is_empty())
+        .collect()
+}
+
+/// 按模板或字段列表生成实际发送的回调数据，都未配置时原样返回
+pub fn format_payload(config: &WebhookConfig, payload: Value) -> Result<Value> {
+    if let Some(ref path) = config.template {
+        let source = std::fs::read_to_string(path)
+            .context(format!("读取回调模板失败: {}", path.display()))?;
+        return render_template(&source, &payload);
+    }
+    if !config.fields.is_empty() {
+        return Ok(select_fields(&payload, &config.fields));
+    }
+    Ok(payload)
+}
+
+/// 用 minijinja 模板渲染回调数据
+///
+/// 模板中可以使用回调数据的各个顶层字段，输出不做转义，字符串和对象用 `tojson` 过滤器输出为 JSON
+pub fn render_template(source: &str, payload: &Value) -> Result<Value> {
+    let mut env = minijinja::Environment::new();
+    env.add_template("webhook", source).context("解析回调模板失败")?;
+    let rendered = env
+        .get_template("webhook")
+        .and_then(|template| template.render(payload))
+        .context("渲染回调模板失败")?;
+    serde_json::from_str(&rendered).context("回调模板的渲染结果不是合法的 JSON")
+}
+
+/// 从回调数据中选出指定字段
+///
+/// `metadata.resolution` 保持原有的嵌套结构，`resolution=metadata.resolution` 放到顶层并改名；
+/// 数组元素用下标选取（`metadata.scenes.0`）。不存在的字段被忽略
+pub fn select_fields(payload: &Value, fields: &[String]) -> Value {
+    let mut selected = Value::Object(Map::new());
+    for field in fields {
+        let (name, path) = match field.split_once('=') {
+            Some((name, path)) => (Some(name.trim()), path.trim()),
+            None => (None, field.trim()),
+        };
+        let Some(value) = path.split('.').try_fold(payload, |value, key| match value {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => value.get(key),
+        }) else {
+            continue;
+        };
+        let target = match name {
+            Some(name) => vec![name],
+            None => path.split('.').collect(),
+        };
+        let (last, parents) = target.split_last().expect("字段路径不为空");
+        let mut object = &mut selected;
+        for key in parents {
+            object = object
+                .as_object_mut()
+                .expect("选取结果是对象")
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !object.is_object() {
+                *object = Value::Object(Map::new());
+            }
+        }
+        if let Some(object) = object.as_object_mut() {
+            object.insert(last.to_string(), value.clone());
+        }
+    }
+    selected
+}
+
+/// 单次投递尝试
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    /// 第几次尝试（从 1 开始）
+    pub attempt: u32,
+    /// 尝试时间
+    pub at: String,
+    /// 响应状态码（请求未完成时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// 失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 请求耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// webhook 投递记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// 回调地址
+    pub url: String,
+    /// 是否送达
+    pub delivered: bool,
+    /// 各次尝试
+    pub attempts: Vec<DeliveryAttempt>,
+    /// 未送达时保存的文件（可用 `video-parse replay-webhooks` 重新投递）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spooled: Option<String>,
+    /// 未送达时上传到的 OSS 位置（`oss://bucket/key`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spooled_oss: Option<String>,
+}
+
+/// 保存到磁盘的未送达回调
+#[derive(Debug, Serialize, Deserialize)]
+struct SpooledWebhook {
+    url: String,
+    payload: Value,
+    attempts: Vec<DeliveryAttempt>,
+}
+
+/// 投递 webhook，失败时按指数退避重试
+///
+/// 先按配置的模板或字段列表生成回调数据。网络错误、5xx、408 和 429 会重试，其他 4xx 视为请求本身有误，不再重试。
+/// 重试用尽后把回调保存到 `spool_dir` 并上传到 `spool_oss`（配置了的话），返回投递记录
+#[instrument(name = "webhook", skip_all, fields(url = %url))]
+pub async fn deliver(config: &WebhookConfig, url: &str, payload: &impl Serialize) -> WebhookDelivery {
+    let payload = serde_json::to_value(payload)
+        .map_err(anyhow::Error::from)
+        .and_then(|payload| format_payload(config, payload));
+    let payload = match payload {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!(error = %e, "⚠️  [Webhook] 生成回调数据失败");
+            return WebhookDelivery { url: url.to_string(), ..Default::default() };
+        }
+    };
+    let mut delivery = send_with_retry(config, url, &payload, Vec::new()).await;
+    if !delivery.delivered {
+        spool_undelivered(config, url, payload, &mut delivery).await;
+    }
+    delivery
+}
+
+/// 保存未送达的回调：写入 `spool_dir`，配置了 `spool_oss` 时同时上传，一处失败不影响另一处
+async fn spool_undelivered(config: &WebhookConfig, url: &str, payload: Value, delivery: &mut WebhookDelivery) {
+    let dir = match (&config.spool_dir, &config.spool_oss) {
+        (Some(dir), _) => dir.clone(),
+        // 只上传 OSS 时先写入临时目录
+        (None, Some(_)) => std::env::temp_dir().join("video-parse").join("webhook-upload"),
+        (None, None) => return,
+    };
+    let path = match spool(&dir, url, payload, &delivery.attempts).await {
+        Ok(path) => path,
+        Err(e) => {
+            warn!(error = %e, "⚠️  [Webhook] 保存未送达回调失败");
+            return;
+        }
+    };
+    if config.spool_dir.is_some() {
+        warn!(path = %path.display(), "📥 [Webhook] 回调未送达，已保存待重新投递");
+        delivery.spooled = Some(path.to_string_lossy().to_string());
+    }
+    if let Some(ref location) = config.spool_oss {
+        match upload_spooled(location, &path).await {
+            Ok(uploaded) => {
+                warn!(location = %uploaded, "📥 [Webhook] 回调未送达，已上传到 OSS");
+                delivery.spooled_oss = Some(uploaded);
+            }
+            Err(e) => warn!(error = %e, "⚠️  [Webhook] 上传未送达回调失败"),
+        }
+        if config.spool_dir.is_none() {
+            tokio::fs::remove_file(&path).await.ok();
+        }
+    }
+}
+
+/// 把保存的回调文件上传到 `oss://bucket/prefix/{文件名}`，endpoint 使用 OSS_ENDPOINT 环境变量
+async fn upload_spooled(location: &str, path: &Path) -> Result<String> {
+    let (bucket, prefix) = parse_oss_location(location)
+        .with_context(|| format!("无效的 OSS 位置: {}", location))?;
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("webhook.json");
+    let key = if prefix.is_empty() { file_name.to_string() } else { format!("{}/{}", prefix, file_name) };
+    OssClient::new()?.upload_file(bucket, &key, path, None).await?;
+    Ok(format!("oss://{}/{}", bucket, key))
+}
+
+async fn send_with_retry(
+    config: &WebhookConfig,
+    url: &str,
+    payload: &Value,
+    mut attempts: Vec<DeliveryAttempt>,
+) -> WebhookDelivery {
+    let client = reqwest::Client::new();
+    // 重新投递时接着已有的尝试编号
+    let first = attempts.len() as u32 + 1;
+    let last = first + config.max_attempts.max(1) - 1;
+    for attempt in first..=last {
+        let start = Instant::now();
+        let at = chrono::Utc::now().to_rfc3339();
+        let result = signed_post(&client, url, config.secret.as_deref(), payload)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await;
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let (status, error, retryable) = match result {
+            Ok(response) if response.status().is_success() => {
+                attempts.push(DeliveryAttempt { attempt, at, status: Some(response.status().as_u16()), error: None, duration_ms });
+                info!(attempt, "✅ [Webhook] 回调送达");
+                return WebhookDelivery { url: url.to_string(), delivered: true, attempts, ..Default::default() };
+            }
+            Ok(response) => {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                let retryable = status.is_server_error()
+                    || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                (Some(status.as_u16()), format!("返回错误状态: {} - {}", status, error_text), retryable)
+            }
+            Err(e) => (None, format!("请求失败: {}", e), true),
+        };
+        warn!(attempt, status, error = %error, "⚠️  [Webhook] 回调失败");
+        attempts.push(DeliveryAttempt { attempt, at, status, error: Some(error), duration_ms });
+        if !retryable || attempt == last {
+            break;
+        }
+        tokio::time::sleep(config.backoff(attempt - first + 1)).await;
+    }
+    WebhookDelivery { url: url.to_string(), delivered: false, attempts, ..Default::default() }
+}
+
+async fn spool(dir: &Path, url: &str, payload: Value, attempts: &[DeliveryAttempt]) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .context(format!("创建目录失败: {}", dir.display()))?;
+    let name = format!("{}_{}.json", chrono::Utc::now().format("%Y%m%dT%H%M%S"), uuid::Uuid::new_v4());
+    let path = dir.join(name);
+    let spooled = SpooledWebhook { url: url.to_string(), payload, attempts: attempts.to_vec() };
+    tokio::fs::write(&path, serde_json::to_vec_pretty(&spooled)?)
+        .await
+        .context(format!("写入文件失败: {}", path.display()))?;
+    Ok(path)
+}
+
+/// 重新投递目录中保存的未送达回调
+///
+/// 送达的文件被删除，仍未送达的文件保留并追加本次的尝试记录。返回（送达数，未送达数）
+pub async fn replay(config: &WebhookConfig, dir: &Path) -> Result<(usize, usize)> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e).context(format!("读取目录失败: {}", dir.display())),
+    };
+    let mut paths = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let (mut delivered, mut failed) = (0, 0);
+    for path in paths {
+        let content = tokio::fs::read(&path).await.context(format!("读取文件失败: {}", path.display()))?;
+        let spooled: SpooledWebhook = match serde_json::from_slice(&content) {
+            Ok(spooled) => spooled,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "⚠️  [Webhook] 无法解析保存的回调，跳过");
+                failed += 1;
+                continue;
+            }
+        };
+        let delivery = send_with_retry(config, &spooled.url, &spooled.payload, spooled.attempts).await;
+        if delivery.delivered {
+            tokio::fs::remove_file(&path).await.context(format!("删除文件失败: {}", path.display()))?;
+            delivered += 1;
+        } else {
+            let spooled = SpooledWebhook { url: spooled.url, payload: spooled.payload, attempts: delivery.attempts };
+            tokio::fs::write(&path, serde_json::to_vec_pretty(&spooled)?).await?;
+            failed += 1;
+        }
+    }
+    Ok((delivered, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_format() {
+        let payload = serde_json::json!({
+            "status": "success",
+            "input_video": "/data/a \"b\".mp4",
+            "metadata": { "resolution": "1920x1080", "scenes": [{ "scene_id": 0 }, { "scene_id": 1 }] },
+        });
+        let fields = ["status", "metadata.resolution", "last=metadata.scenes.1.scene_id", "missing.field"]
+            .map(String::from);
+        assert_eq!(
+            select_fields(&payload, &fields),
+            serde_json::json!({ "status": "success", "metadata": { "resolution": "1920x1080" }, "last": 1 })
+        );
+
+        let template = r#"{ "event": "video.{{ status }}", "video": {{ input_video | tojson }}, "scenes": {{ metadata.scenes | length }} }"#;
+        let rendered = render_template(template, &payload).unwrap();
+        assert_eq!(rendered, serde_json::json!({ "event": "video.success", "video": "/data/a \"b\".mp4", "scenes": 2 }));
+    }
+
+    #[test]
+    fn test_signed_post() {
+        let payload = serde_json::json!({ "status": "failure", "error": "下载失败" });
+        let client = reqwest::Client::new();
+        let request = signed_post(&client, "http://127.0.0.1:1/webhook", Some("secret"), &payload).build().unwrap();
+        let body = request.body().and_then(|body| body.as_bytes()).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(body).unwrap(), payload);
+        let signature = request.headers()[auth::SIGNATURE_HEADER].to_str().unwrap();
+        assert!(auth::verify_signature("secret", body, signature));
+        let unsigned = signed_post(&client, "http://127.0.0.1:1/webhook", None, &payload).build().unwrap();
+        assert!(!unsigned.headers().contains_key(auth::SIGNATURE_HEADER));
+
+        assert_eq!(parse_oss_location("oss://logs/webhooks/failed/"), Some(("logs", "webhooks/failed")));
+        assert_eq!(parse_oss_location("oss://logs"), Some(("logs", "")));
+        assert_eq!(parse_oss_location("logs/webhooks"), None);
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_webhook_is_spooled() {
+        let config = WebhookConfig { initial_backoff: Duration::from_millis(10), ..Default::default() };
+        assert_eq!(config.backoff(1), Duration::from_millis(10));
+        assert_eq!(config.backoff(3), Duration::from_millis(40));
+        assert_eq!(WebhookConfig::default().backoff(10), MAX_BACKOFF);
+
+        let dir = std::env::temp_dir().join(format!("video-parse-webhook-test-{}", uuid::Uuid::new_v4()));
+        let config = WebhookConfig { max_attempts: 2, spool_dir: Some(dir.clone()), ..config };
+        // 端口 1 上没有服务，连接立即失败
+        let delivery = deliver(&config, "http://127.0.0.1:1/webhook", &serde_json::json!({ "status": "success" })).await;
+        assert!(!delivery.delivered);
+        assert_eq!(delivery.attempts.len(), 2);
+        assert!(delivery.spooled.is_some());
+
+        let (delivered, failed) = replay(&WebhookConfig { max_attempts: 1, ..config }, &dir).await.unwrap();
+        assert_eq!((delivered, failed), (0, 1));
+        let spooled: SpooledWebhook = serde_json::from_slice(&std::fs::read(delivery.spooled.unwrap()).unwrap()).unwrap();
+        assert_eq!(spooled.attempts.last().unwrap().attempt, 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+//! 用合成视频验证场景检测：已知切点的视频经过完整处理流程后，检测到的场景应与切点一致
+//!
+//! 运行：`cargo test --features test-support --test synthetic_scenes`
+
+use std::path::PathBuf;
+use video_parse::test_support::{SyntheticScene, SyntheticVideo};
+use video_parse::{process_video, ProcessConfig};
+
+const SAMPLE_RATE: f64 = 5.0;
+/// 切点允许的误差：一个采样间隔加上关键帧 seek 的偏差
+const TOLERANCE: f64 = 1.0 / SAMPLE_RATE + 0.1;
+
+fn work_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("video-parse-synthetic-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn config() -> ProcessConfig {
+    ProcessConfig::builder()
+        .sample_rate(SAMPLE_RATE)
+        .min_scene_duration(0.5)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_detects_synthetic_cuts() {
+    let dir = work_dir("cuts");
+    let video = SyntheticVideo::new(vec![
+        SyntheticScene::new(2.0, [200, 30, 30]),
+        SyntheticScene::new(3.0, [30, 200, 30]).with_motion(),
+        SyntheticScene::new(2.0, [30, 30, 200]).with_tone(440.0),
+    ]);
+    let input = dir.join("input.mkv");
+    video.write(&input).unwrap();
+
+    let output = process_video(&input, dir.join("output"), config()).await.unwrap();
+    let metadata = &output.metadata;
+    let expected = video.cut_points();
+    assert_eq!(metadata.scene_count, expected.len(), "场景: {:?}", metadata.scenes);
+    for (scene, cut) in metadata.scenes.iter().zip(&expected) {
+        assert!(
+            (scene.start_time - cut).abs() <= TOLERANCE,
+            "场景 {} 开始于 {:.2}s，期望 {:.2}s",
+            scene.scene_id,
+            scene.start_time,
+            cut
+        );
+    }
+    assert!((metadata.total_duration - video.duration()).abs() <= TOLERANCE);
+    assert_eq!(output.keyframe_files.len(), expected.len());
+    assert!(!output.audio_file.is_empty());
+    assert!(output.output_dir.join(&output.audio_file).exists());
+
+    // 有移动方块的场景运动量高于纯色场景
+    let motion: Vec<f64> = metadata.scenes.iter().map(|scene| scene.motion_score.unwrap_or(0.0)).collect();
+    assert!(motion[1] > motion[0] && motion[1] > motion[2], "运动量: {:?}", motion);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_single_scene_without_audio() {
+    let dir = work_dir("single");
+    let video = SyntheticVideo::new(vec![SyntheticScene::new(3.0, [120, 120, 120]).with_motion()]).without_audio();
+    let input = dir.join("input.mkv");
+    video.write(&input).unwrap();
+
+    let output = process_video(&input, dir.join("output"), config()).await.unwrap();
+    assert_eq!(output.metadata.scene_count, 1, "场景: {:?}", output.metadata.scenes);
+    assert_eq!(output.metadata.scenes[0].start_time, 0.0);
+    assert!(output.audio_file.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_probe_synthetic_video() {
+    let dir = work_dir("probe");
+    let video = SyntheticVideo::new(vec![SyntheticScene::new(4.0, [90, 90, 90]).with_tone(440.0)]);
+    let input = dir.join("input.mkv");
+    video.write(&input).unwrap();
+
+    let report = video_parse::preflight::probe(&input.to_string_lossy(), &config()).unwrap();
+    assert!((report.media.duration - video.duration()).abs() <= TOLERANCE);
+    let stream = report.media.video.as_ref().unwrap();
+    assert_eq!((stream.codec.as_str(), stream.width, stream.height), ("mpeg4", 320, 240));
+    assert!((stream.fps - 25.0).abs() < 0.01);
+    let audio = report.media.audio.as_ref().unwrap();
+    assert_eq!((audio.sample_rate, audio.channels), (44_100, 1));
+    assert_eq!(report.estimate.unwrap().sampled_frames, (video.duration() * SAMPLE_RATE) as u64);
+    assert!(report.rejection.is_none());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}